@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+/// Prefix of the stderr line [`crate::apply_patch`] writes for a file it
+/// could not patch, so a caller that ran it as a separate process can
+/// recover the conflict without reading the human-readable error.
+const REPORT_PREFIX: &str = "apply_patch conflict: ";
+
+/// Why a patch could not be applied to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// The lines the patch expected are not in the file.
+    ContextMismatch,
+    /// The file to update or delete does not exist.
+    MissingFile,
+    /// The file could not be written for lack of permission.
+    PermissionDenied,
+    /// Any other I/O failure.
+    Other,
+}
+
+impl ConflictReason {
+    pub(crate) fn from_io_error(err: &std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => ConflictReason::MissingFile,
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => {
+                ConflictReason::PermissionDenied
+            }
+            _ => ConflictReason::Other,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ConflictReason::ContextMismatch => "context-mismatch",
+            ConflictReason::MissingFile => "missing-file",
+            ConflictReason::PermissionDenied => "permission-denied",
+            ConflictReason::Other => "other",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        [
+            ConflictReason::ContextMismatch,
+            ConflictReason::MissingFile,
+            ConflictReason::PermissionDenied,
+            ConflictReason::Other,
+        ]
+        .into_iter()
+        .find(|reason| reason.as_str() == s)
+    }
+}
+
+/// A file a patch could not be applied to, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileConflict {
+    pub path: PathBuf,
+    pub reason: ConflictReason,
+}
+
+impl FileConflict {
+    /// The stderr line reporting this conflict; see [`Self::from_report`].
+    pub fn report_line(&self) -> String {
+        format!(
+            "{REPORT_PREFIX}{}: {}",
+            self.reason.as_str(),
+            self.path.display()
+        )
+    }
+
+    /// The conflicts reported on the stderr of an `apply_patch` run, in
+    /// order, without duplicates.
+    pub fn from_report(stderr: &str) -> Vec<FileConflict> {
+        let mut conflicts: Vec<FileConflict> = Vec::new();
+        for line in stderr.lines() {
+            let Some((reason, path)) = line
+                .strip_prefix(REPORT_PREFIX)
+                .and_then(|rest| rest.split_once(": "))
+            else {
+                continue;
+            };
+            let Some(reason) = ConflictReason::parse(reason) else {
+                continue;
+            };
+            let conflict = FileConflict {
+                path: PathBuf::from(path),
+                reason,
+            };
+            if !conflicts.contains(&conflict) {
+                conflicts.push(conflict);
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn report_lines_round_trip() {
+        let conflicts = vec![
+            FileConflict {
+                path: PathBuf::from("/repo/a: b.rs"),
+                reason: ConflictReason::ContextMismatch,
+            },
+            FileConflict {
+                path: PathBuf::from("/repo/c.rs"),
+                reason: ConflictReason::PermissionDenied,
+            },
+        ];
+        let stderr = format!(
+            "Failed to find expected lines [\"old\"] in /repo/a: b.rs\n{}\n{}\n{}\n",
+            conflicts[0].report_line(),
+            conflicts[1].report_line(),
+            conflicts[1].report_line(),
+        );
+
+        assert_eq!(FileConflict::from_report(&stderr), conflicts);
+        assert_eq!(
+            FileConflict::from_report("Failed to write file /repo/c.rs: denied\n"),
+            Vec::new()
+        );
+    }
+}
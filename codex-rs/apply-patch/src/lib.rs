@@ -1,4 +1,6 @@
+mod conflict;
 mod parser;
+mod progress;
 mod seek_sequence;
 mod standalone_executable;
 
@@ -6,14 +8,18 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::Utf8Error;
+use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
+pub use conflict::ConflictReason;
+pub use conflict::FileConflict;
 pub use parser::Hunk;
 pub use parser::ParseError;
 use parser::ParseError::*;
 use parser::UpdateFileChunk;
 pub use parser::parse_patch;
+pub use progress::FileProgress;
 use similar::TextDiff;
 use thiserror::Error;
 use tree_sitter::LanguageError;
@@ -34,14 +40,33 @@ pub enum ApplyPatchError {
     #[error(transparent)]
     IoError(#[from] IoError),
     /// Error that occurs while computing replacements when applying patch chunks
-    #[error("{0}")]
-    ComputeReplacements(String),
+    #[error("{message}")]
+    ComputeReplacements { message: String, path: PathBuf },
+}
+
+impl ApplyPatchError {
+    /// The file this error prevented the patch from being applied to, if it
+    /// is tied to one.
+    pub fn conflict(&self) -> Option<FileConflict> {
+        match self {
+            ApplyPatchError::ParseError(_) => None,
+            ApplyPatchError::IoError(IoError { path, source, .. }) => Some(FileConflict {
+                path: path.clone()?,
+                reason: ConflictReason::from_io_error(source),
+            }),
+            ApplyPatchError::ComputeReplacements { path, .. } => Some(FileConflict {
+                path: path.clone(),
+                reason: ConflictReason::ContextMismatch,
+            }),
+        }
+    }
 }
 
 impl From<std::io::Error> for ApplyPatchError {
     fn from(err: std::io::Error) -> Self {
         ApplyPatchError::IoError(IoError {
             context: "I/O error".to_string(),
+            path: None,
             source: err,
         })
     }
@@ -51,6 +76,7 @@ impl From<&std::io::Error> for ApplyPatchError {
     fn from(err: &std::io::Error) -> Self {
         ApplyPatchError::IoError(IoError {
             context: "I/O error".to_string(),
+            path: None,
             source: std::io::Error::new(err.kind(), err.to_string()),
         })
     }
@@ -60,13 +86,17 @@ impl From<&std::io::Error> for ApplyPatchError {
 #[error("{context}: {source}")]
 pub struct IoError {
     context: String,
+    /// The file the failed operation was on, if any.
+    path: Option<PathBuf>,
     #[source]
     source: std::io::Error,
 }
 
 impl PartialEq for IoError {
     fn eq(&self, other: &Self) -> bool {
-        self.context == other.context && self.source.to_string() == other.source.to_string()
+        self.context == other.context
+            && self.path == other.path
+            && self.source.to_string() == other.source.to_string()
     }
 }
 
@@ -165,6 +195,55 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    /// A git-style unified diff of every change, with paths relative to
+    /// `cwd`, without touching the filesystem. Fails if a file to delete
+    /// cannot be read; updates were already checked against the files on disk
+    /// when the action was built.
+    pub fn unified_diff(&self) -> std::result::Result<String, ApplyPatchError> {
+        let mut paths: Vec<&PathBuf> = self.changes.keys().collect();
+        paths.sort();
+        let mut out = String::new();
+        for path in paths {
+            let display = |p: &Path| p.strip_prefix(&self.cwd).unwrap_or(p).display().to_string();
+            let old_name = format!("a/{}", display(path));
+            match &self.changes[path] {
+                ApplyPatchFileChange::Add { content } => {
+                    let diff = TextDiff::from_lines("", content.as_str());
+                    let new_name = format!("b/{}", display(path));
+                    out.push_str(
+                        &diff
+                            .unified_diff()
+                            .header("/dev/null", &new_name)
+                            .to_string(),
+                    );
+                }
+                ApplyPatchFileChange::Delete => {
+                    let content = std::fs::read_to_string(path).map_err(|source| IoError {
+                        context: format!("Failed to read file to delete {}", path.display()),
+                        path: Some(path.clone()),
+                        source,
+                    })?;
+                    let diff = TextDiff::from_lines(content.as_str(), "");
+                    out.push_str(
+                        &diff
+                            .unified_diff()
+                            .header(&old_name, "/dev/null")
+                            .to_string(),
+                    );
+                }
+                ApplyPatchFileChange::Update {
+                    unified_diff,
+                    move_path,
+                    ..
+                } => {
+                    let new_name = format!("b/{}", display(move_path.as_deref().unwrap_or(path)));
+                    out.push_str(&format!("--- {old_name}\n+++ {new_name}\n{unified_diff}"));
+                }
+            }
+        }
+        Ok(out)
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -321,6 +400,37 @@ pub fn apply_patch(
     patch: &str,
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    apply_patch_reporting(patch, false, &WriteRetryPolicy::default(), stdout, stderr)
+}
+
+/// Like [`apply_patch`], but also writes a [`FileProgress`] report line to
+/// `stderr` as each file is written.
+pub fn apply_patch_with_progress(
+    patch: &str,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    apply_patch_with_progress_and_retry_policy(patch, &WriteRetryPolicy::default(), stdout, stderr)
+}
+
+/// Like [`apply_patch_with_progress`], but with an explicit policy for
+/// retrying transient write failures.
+pub fn apply_patch_with_progress_and_retry_policy(
+    patch: &str,
+    retry_policy: &WriteRetryPolicy,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    apply_patch_reporting(patch, true, retry_policy, stdout, stderr)
+}
+
+fn apply_patch_reporting(
+    patch: &str,
+    report_progress: bool,
+    retry_policy: &WriteRetryPolicy,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
     let hunks = match parse_patch(patch) {
         Ok(source) => source.hunks,
@@ -344,16 +454,135 @@ pub fn apply_patch(
         }
     };
 
-    apply_hunks(&hunks, stdout, stderr)?;
+    apply_hunks_with_writer(
+        &hunks,
+        &mut FsWriter,
+        retry_policy,
+        report_progress,
+        stdout,
+        stderr,
+    )?;
 
     Ok(())
 }
 
+/// Bounded retry with exponential backoff for file writes that fail with a
+/// transient error (`EAGAIN`, `ETXTBSY`, `EINTR`), as seen on networked and
+/// container filesystems. Any other error fails immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteRetryPolicy {
+    /// Total number of attempts per write, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled after each subsequent failure.
+    pub initial_backoff: Duration,
+}
+
+impl Default for WriteRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(20),
+        }
+    }
+}
+
+impl WriteRetryPolicy {
+    /// Encodes the policy as `<max_attempts>,<initial_backoff_ms>`, the form
+    /// read back by [`WriteRetryPolicy::from_env_value`]. This is how the
+    /// policy reaches an `apply_patch` run in a separate process.
+    pub fn to_env_value(&self) -> String {
+        format!("{},{}", self.max_attempts, self.initial_backoff.as_millis())
+    }
+
+    /// Parses a value written by [`WriteRetryPolicy::to_env_value`].
+    pub fn from_env_value(value: &str) -> Option<Self> {
+        let (max_attempts, initial_backoff_ms) = value.split_once(',')?;
+        Some(Self {
+            max_attempts: max_attempts.trim().parse().ok()?,
+            initial_backoff: Duration::from_millis(initial_backoff_ms.trim().parse().ok()?),
+        })
+    }
+}
+
+fn is_transient_io_error(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::WouldBlock
+            | std::io::ErrorKind::ExecutableFileBusy
+            | std::io::ErrorKind::Interrupted
+    )
+}
+
+/// Seam for the file-write step so the retry logic can be exercised with a
+/// writer that fails on demand.
+trait FileWriter {
+    fn write_file(&mut self, path: &Path, contents: &[u8]) -> std::io::Result<()>;
+}
+
+struct FsWriter;
+
+impl FileWriter for FsWriter {
+    fn write_file(&mut self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        std::fs::write(path, contents)
+    }
+}
+
+/// Write `contents` to `path`, retrying transient failures according to
+/// `policy`. Each retry is recorded in `retries` so it can be reported.
+fn write_with_retry(
+    writer: &mut impl FileWriter,
+    path: &Path,
+    contents: &[u8],
+    policy: &WriteRetryPolicy,
+    retries: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match writer.write_file(path, contents) {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < policy.max_attempts && is_transient_io_error(&err) => {
+                retries.push(format!(
+                    "Retrying write to {} after transient error (attempt {attempt}/{}): {err}",
+                    path.display(),
+                    policy.max_attempts
+                ));
+                std::thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
 /// Applies hunks and continues to update stdout/stderr
 pub fn apply_hunks(
     hunks: &[Hunk],
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    apply_hunks_with_retry_policy(hunks, &WriteRetryPolicy::default(), stdout, stderr)
+}
+
+/// Like [`apply_hunks`], but with an explicit policy for retrying transient
+/// write failures. Any retries are reported on `stderr`.
+pub fn apply_hunks_with_retry_policy(
+    hunks: &[Hunk],
+    retry_policy: &WriteRetryPolicy,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    apply_hunks_with_writer(hunks, &mut FsWriter, retry_policy, false, stdout, stderr)
+}
+
+fn apply_hunks_with_writer(
+    hunks: &[Hunk],
+    writer: &mut impl FileWriter,
+    retry_policy: &WriteRetryPolicy,
+    report_progress: bool,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
     let _existing_paths: Vec<&Path> = hunks
         .iter()
@@ -382,26 +611,72 @@ pub fn apply_hunks(
         .collect::<Vec<&Path>>();
 
     // Delegate to a helper that applies each hunk to the filesystem.
-    match apply_hunks_to_files(hunks) {
+    let mut retries = Vec::new();
+    let progress: Option<&mut dyn std::io::Write> = if report_progress {
+        Some(&mut *stderr)
+    } else {
+        None
+    };
+    let result = apply_hunks_to_files(hunks, writer, retry_policy, &mut retries, progress);
+    for retry in &retries {
+        writeln!(stderr, "{retry}").map_err(ApplyPatchError::from)?;
+    }
+    match result {
         Ok(affected) => {
             print_summary(&affected, stdout).map_err(ApplyPatchError::from)?;
             Ok(())
         }
         Err(err) => {
-            let msg = err.to_string();
-            writeln!(stderr, "{msg}").map_err(ApplyPatchError::from)?;
-            if let Some(io) = err.downcast_ref::<std::io::Error>() {
-                Err(ApplyPatchError::from(io))
-            } else {
-                Err(ApplyPatchError::IoError(IoError {
-                    context: msg,
-                    source: std::io::Error::other(err),
-                }))
+            // Include the underlying I/O error so callers can tell why the
+            // file could not be written.
+            writeln!(stderr, "{err:#}").map_err(ApplyPatchError::from)?;
+            let error = match err.downcast::<ApplyPatchError>() {
+                Ok(error) => error,
+                Err(err) => {
+                    let path = err.downcast_ref::<FileStep>().map(|step| step.path.clone());
+                    let source = match err.downcast_ref::<std::io::Error>() {
+                        Some(io) => std::io::Error::new(io.kind(), io.to_string()),
+                        None => std::io::Error::other(err.to_string()),
+                    };
+                    ApplyPatchError::IoError(IoError {
+                        context: err.to_string(),
+                        path,
+                        source,
+                    })
+                }
+            };
+            // Name the file that failed in a form callers can parse.
+            if let Some(conflict) = error.conflict() {
+                writeln!(stderr, "{}", conflict.report_line()).map_err(ApplyPatchError::from)?;
             }
+            Err(error)
+        }
+    }
+}
+
+/// Context for a failed filesystem step, kept typed so the file it was on can
+/// be reported as a [`FileConflict`].
+#[derive(Debug)]
+struct FileStep {
+    message: String,
+    path: PathBuf,
+}
+
+impl FileStep {
+    fn new(what: &str, path: &Path) -> Self {
+        Self {
+            message: format!("{what} {}", path.display()),
+            path: path.to_path_buf(),
         }
     }
 }
 
+impl std::fmt::Display for FileStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 /// Applies each parsed patch hunk to the filesystem.
 /// Returns an error if any of the changes could not be applied.
 /// Tracks file paths affected by applying a patch.
@@ -413,7 +688,15 @@ pub struct AffectedPaths {
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
 /// Returns an error if the patch could not be applied.
-fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
+/// When `progress` is set, a [`FileProgress`] report line is written to it
+/// after each file.
+fn apply_hunks_to_files(
+    hunks: &[Hunk],
+    writer: &mut impl FileWriter,
+    retry_policy: &WriteRetryPolicy,
+    retries: &mut Vec<String>,
+    mut progress: Option<&mut dyn std::io::Write>,
+) -> anyhow::Result<AffectedPaths> {
     if hunks.is_empty() {
         anyhow::bail!("No files were modified.");
     }
@@ -421,23 +704,23 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
-    for hunk in hunks {
+    for (index, hunk) in hunks.iter().enumerate() {
         match hunk {
             Hunk::AddFile { path, contents } => {
                 if let Some(parent) = path.parent()
                     && !parent.as_os_str().is_empty()
                 {
                     std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
+                        FileStep::new("Failed to create parent directories for", path)
                     })?;
                 }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+                write_with_retry(writer, path, contents.as_bytes(), retry_policy, retries)
+                    .with_context(|| FileStep::new("Failed to write file", path))?;
                 added.push(path.clone());
             }
             Hunk::DeleteFile { path } => {
                 std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
+                    .with_context(|| FileStep::new("Failed to delete file", path))?;
                 deleted.push(path.clone());
             }
             Hunk::UpdateFile {
@@ -452,21 +735,35 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                         && !parent.as_os_str().is_empty()
                     {
                         std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
+                            FileStep::new("Failed to create parent directories for", dest)
                         })?;
                     }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
+                    write_with_retry(writer, dest, new_contents.as_bytes(), retry_policy, retries)
+                        .with_context(|| FileStep::new("Failed to write file", dest))?;
                     std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
+                        .with_context(|| FileStep::new("Failed to remove original", path))?;
                     modified.push(dest.clone());
                 } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    write_with_retry(writer, path, new_contents.as_bytes(), retry_policy, retries)
+                        .with_context(|| FileStep::new("Failed to write file", path))?;
                     modified.push(path.clone());
                 }
             }
         }
+        if let Some(out) = progress.as_deref_mut() {
+            let (Hunk::AddFile { path, .. }
+            | Hunk::DeleteFile { path }
+            | Hunk::UpdateFile { path, .. }) = hunk;
+            let line = FileProgress {
+                path: path.clone(),
+                index,
+                total: hunks.len(),
+            }
+            .report_line();
+            // Progress is best effort: the files already written stay written,
+            // so failing to report them must not abort the rest of the patch.
+            let _ = writeln!(out, "{line}");
+        }
     }
     Ok(AffectedPaths {
         added,
@@ -491,6 +788,7 @@ fn derive_new_contents_from_chunks(
         Err(err) => {
             return Err(ApplyPatchError::IoError(IoError {
                 context: format!("Failed to read file to update {}", path.display()),
+                path: Some(path.to_path_buf()),
                 source: err,
             }));
         }
@@ -543,11 +841,10 @@ fn compute_replacements(
             ) {
                 line_index = idx + 1;
             } else {
-                return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
-                    ctx_line,
-                    path.display()
-                )));
+                return Err(ApplyPatchError::ComputeReplacements {
+                    message: format!("Failed to find context '{ctx_line}' in {}", path.display()),
+                    path: path.to_path_buf(),
+                });
             }
         }
 
@@ -600,11 +897,14 @@ fn compute_replacements(
             replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
             line_index = start_idx + pattern.len();
         } else {
-            return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines {:?} in {}",
-                chunk.old_lines,
-                path.display()
-            )));
+            return Err(ApplyPatchError::ComputeReplacements {
+                message: format!(
+                    "Failed to find expected lines {:?} in {}",
+                    chunk.old_lines,
+                    path.display()
+                ),
+                path: path.to_path_buf(),
+            });
         }
     }
 
@@ -1316,6 +1616,41 @@ g
         );
     }
 
+    #[test]
+    fn test_unified_diff_previews_every_change_without_writing() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("update.txt"), "one\ntwo\n").unwrap();
+        fs::write(dir.path().join("delete.txt"), "gone\n").unwrap();
+        let argv = strs_to_strings(&[
+            "apply_patch",
+            &wrap_patch(
+                "*** Add File: add.txt\n+new\n*** Delete File: delete.txt\n*** Update File: update.txt\n@@\n one\n-two\n+three",
+            ),
+        ]);
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified(&argv, dir.path())
+        else {
+            panic!("expected a verified patch");
+        };
+
+        assert_eq!(
+            action.unified_diff().unwrap(),
+            "--- /dev/null\n+++ b/add.txt\n@@ -0,0 +1 @@\n+new\n\
+             --- a/delete.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n\
+             --- a/update.txt\n+++ b/update.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+three\n"
+        );
+        assert!(!dir.path().join("add.txt").exists());
+        assert!(dir.path().join("delete.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("update.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+
+        // A missing file to delete is reported instead of silently skipped.
+        fs::remove_file(dir.path().join("delete.txt")).unwrap();
+        assert!(action.unified_diff().is_err());
+    }
+
     #[test]
     fn test_apply_patch_fails_on_write_error() {
         let dir = tempdir().unwrap();
@@ -1335,4 +1670,221 @@ g
         let result = apply_patch(&patch, &mut stdout, &mut stderr);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_stale_patch_reports_context_mismatch() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("stale.txt");
+        fs::write(&path, "changed since\n").unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-before\n+after",
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let error = apply_patch(&patch, &mut stdout, &mut stderr).unwrap_err();
+
+        let conflict = FileConflict {
+            path,
+            reason: ConflictReason::ContextMismatch,
+        };
+        assert_eq!(error.conflict(), Some(conflict.clone()));
+        let stderr = String::from_utf8(stderr).unwrap();
+        assert_eq!(FileConflict::from_report(&stderr), vec![conflict]);
+    }
+
+    #[test]
+    fn test_progress_is_reported_per_file_in_patch_order() {
+        let dir = tempdir().unwrap();
+        let paths: Vec<PathBuf> = ["c.txt", "a.txt", "b.txt"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        let patch = wrap_patch(
+            &paths
+                .iter()
+                .map(|path| format!("*** Add File: {}\n+x", path.display()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch_with_progress(&patch, &mut stdout, &mut stderr).unwrap();
+
+        let stderr = String::from_utf8(stderr).unwrap();
+        let reported: Vec<FileProgress> = stderr
+            .lines()
+            .filter_map(FileProgress::from_report_line)
+            .collect();
+        let expected: Vec<FileProgress> = paths
+            .into_iter()
+            .enumerate()
+            .map(|(index, path)| FileProgress {
+                path,
+                index,
+                total: 3,
+            })
+            .collect();
+        assert_eq!(reported, expected);
+    }
+
+    /// Output that rejects every write.
+    struct BrokenOutput;
+
+    impl std::io::Write for BrokenOutput {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_failing_progress_output_does_not_abort_the_patch() {
+        let dir = tempdir().unwrap();
+        let paths: Vec<PathBuf> = ["a.txt", "b.txt"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        let patch = wrap_patch(
+            &paths
+                .iter()
+                .map(|path| format!("*** Add File: {}\n+x", path.display()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        let hunks = parse_patch(&patch).unwrap().hunks;
+
+        let mut stdout = Vec::new();
+        apply_hunks_with_writer(
+            &hunks,
+            &mut FsWriter,
+            &WriteRetryPolicy::default(),
+            true,
+            &mut stdout,
+            &mut BrokenOutput,
+        )
+        .unwrap();
+
+        for path in &paths {
+            assert_eq!(fs::read_to_string(path).unwrap(), "x\n");
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_env_value_round_trips() {
+        let policy = WriteRetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+        };
+
+        assert_eq!(policy.to_env_value(), "5,250");
+        assert_eq!(
+            WriteRetryPolicy::from_env_value(&policy.to_env_value()),
+            Some(policy)
+        );
+        assert_eq!(WriteRetryPolicy::from_env_value("5"), None);
+    }
+
+    /// Writer that fails the first `failures` writes with `kind`, then
+    /// writes through to disk.
+    struct FlakyWriter {
+        failures: usize,
+        kind: std::io::ErrorKind,
+        attempts: usize,
+    }
+
+    impl FileWriter for FlakyWriter {
+        fn write_file(&mut self, path: &Path, contents: &[u8]) -> std::io::Result<()> {
+            self.attempts += 1;
+            if self.attempts <= self.failures {
+                return Err(std::io::Error::from(self.kind));
+            }
+            fs::write(path, contents)
+        }
+    }
+
+    fn no_backoff() -> WriteRetryPolicy {
+        WriteRetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_transient_write_error_is_retried_and_reported() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("flaky.txt");
+        let patch = wrap_patch(&format!("*** Add File: {}\n+hello", path.display()));
+        let hunks = parse_patch(&patch).unwrap().hunks;
+
+        let mut writer = FlakyWriter {
+            failures: 1,
+            kind: std::io::ErrorKind::WouldBlock,
+            attempts: 0,
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_hunks_with_writer(
+            &hunks,
+            &mut writer,
+            &no_backoff(),
+            false,
+            &mut stdout,
+            &mut stderr,
+        )
+        .unwrap();
+
+        assert_eq!(writer.attempts, 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello\n");
+        let stderr = String::from_utf8(stderr).unwrap();
+        assert!(
+            stderr.contains("Retrying write to") && stderr.contains("attempt 1/3"),
+            "unexpected stderr: {stderr}"
+        );
+    }
+
+    #[test]
+    fn test_permanent_write_error_is_not_retried() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("denied.txt");
+        let patch = wrap_patch(&format!("*** Add File: {}\n+hello", path.display()));
+        let hunks = parse_patch(&patch).unwrap().hunks;
+
+        let mut writer = FlakyWriter {
+            failures: 1,
+            kind: std::io::ErrorKind::PermissionDenied,
+            attempts: 0,
+        };
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_hunks_with_writer(
+            &hunks,
+            &mut writer,
+            &no_backoff(),
+            false,
+            &mut stdout,
+            &mut stderr,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(writer.attempts, 1);
+        let stderr = String::from_utf8(stderr).unwrap();
+        assert!(!stderr.contains("Retrying"));
+        assert!(
+            stderr.contains(&format!("Failed to write file {}: ", path.display())),
+            "unexpected stderr: {stderr}"
+        );
+        assert_eq!(
+            FileConflict::from_report(&stderr),
+            vec![FileConflict {
+                path,
+                reason: ConflictReason::PermissionDenied,
+            }]
+        );
+    }
 }
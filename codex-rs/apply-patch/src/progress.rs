@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+/// Prefix of the stderr line [`crate::apply_patch_with_progress`] writes as
+/// each file is written, so a caller that ran it as a separate process can
+/// report progress while the patch is still being applied.
+const REPORT_PREFIX: &str = "apply_patch progress: ";
+
+/// A file of a patch that has just been written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileProgress {
+    /// The file that was written (its source path for a move).
+    pub path: PathBuf,
+    /// Zero-based position of `path` among the patch's files, in the order
+    /// the patch lists them.
+    pub index: usize,
+    /// Number of files in the patch.
+    pub total: usize,
+}
+
+impl FileProgress {
+    /// The stderr line reporting this file; see [`Self::from_report_line`].
+    pub fn report_line(&self) -> String {
+        format!(
+            "{REPORT_PREFIX}{}/{}: {}",
+            self.index + 1,
+            self.total,
+            self.path.display()
+        )
+    }
+
+    /// Parses a single line written by [`Self::report_line`], with or without
+    /// its trailing newline.
+    pub fn from_report_line(line: &str) -> Option<FileProgress> {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (position, path) = line.strip_prefix(REPORT_PREFIX)?.split_once(": ")?;
+        let (written, total) = position.split_once('/')?;
+        let written: usize = written.parse().ok()?;
+        Some(FileProgress {
+            path: PathBuf::from(path),
+            index: written.checked_sub(1)?,
+            total: total.parse().ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn report_line_round_trips() {
+        let progress = FileProgress {
+            path: PathBuf::from("/repo/a: b.rs"),
+            index: 1,
+            total: 3,
+        };
+        let line = format!("{}\n", progress.report_line());
+
+        assert_eq!(line, "apply_patch progress: 2/3: /repo/a: b.rs\n");
+        assert_eq!(FileProgress::from_report_line(&line), Some(progress));
+        assert_eq!(
+            FileProgress::from_report_line("apply_patch conflict: other: /repo/a.rs"),
+            None
+        );
+    }
+}
@@ -22,10 +22,15 @@
 //!   `?` 错误传播语法）。
 
 use std::future::Future;
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 
+use anyhow::Context;
+use codex_apply_patch::WriteRetryPolicy;
 use codex_core::CODEX_APPLY_PATCH_ARG1;
+use codex_core::CODEX_APPLY_PATCH_STDIN_ARG1;
+use codex_core::CODEX_APPLY_PATCH_WRITE_RETRY_ENV_VAR;
 #[cfg(unix)]
 use std::os::unix::fs::symlink;
 use tempfile::TempDir;
@@ -34,6 +39,7 @@ use tempfile::TempDir;
 const LINUX_SANDBOX_ARG0: &str = "codex-linux-sandbox";
 const APPLY_PATCH_ARG0: &str = "apply_patch";
 const MISSPELLED_APPLY_PATCH_ARG0: &str = "applypatch";
+const APPLY_PATCH_STDIN_ARG0: &str = "codex-apply-patch-stdin";
 
 /// While we want to deploy the Codex CLI as a single executable for simplicity,
 /// we also want to expose some of its functionality as distinct CLIs, so we use
@@ -45,7 +51,12 @@ const MISSPELLED_APPLY_PATCH_ARG0: &str = "applypatch";
 /// 1. 根据 `argv[0]` 识别是否通过 alias/hard-link 调用（例如 `codex-linux-sandbox`,
 ///    `apply_patch`）。若匹配，则直接执行对应的子程序并直接返回（或永不返回）。
 /// 2. 检查第一个参数是否为 `--codex-run-as-apply-patch`（内部约定的 secret），
-///    若是则把后续 PATCH 参数交给 `codex_apply_patch::apply_patch` 执行并退出。
+///    若是则把后续 PATCH 参数交给
+///    `codex_apply_patch::apply_patch_with_progress_and_retry_policy`
+///    执行并退出（每写入一个文件就在 stderr 报告一行进度；写入重试策略取自
+///    `CODEX_APPLY_PATCH_WRITE_RETRY` 环境变量）。
+///    `--codex-run-as-apply-patch-stdin`（或 alias `codex-apply-patch-stdin`）
+///    则改为从 stdin 读取 PATCH，以避开 argv 长度限制。
 /// 3. 在常规流程中，先加载 `.env`（但禁止修改 `CODEX_` 前缀的环境），
 ///    然后在 PATH 前置一个临时目录（包含 `apply_patch` 的链接/脚本），
 ///    再创建 Tokio 运行时并执行外部传入的 `main_fn`（async closure）。
@@ -76,6 +87,9 @@ where
     } else if exe_name == APPLY_PATCH_ARG0 || exe_name == MISSPELLED_APPLY_PATCH_ARG0 {
         // 通过 alias 调用 apply_patch 子程序
         codex_apply_patch::main();
+    } else if exe_name == APPLY_PATCH_STDIN_ARG0 {
+        // 从 stdin 读取 PATCH，避免 argv 长度限制
+        std::process::exit(apply_patch_from_reader(std::io::stdin()));
     }
 
     // 检查第一个参数是否为内部约定的 apply-patch 标识（例如 --codex-run-as-apply-patch）
@@ -87,7 +101,12 @@ where
             Some(patch_arg) => {
                 let mut stdout = std::io::stdout();
                 let mut stderr = std::io::stderr();
-                match codex_apply_patch::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
+                match codex_apply_patch::apply_patch_with_progress_and_retry_policy(
+                    &patch_arg,
+                    &write_retry_policy_from_env(),
+                    &mut stdout,
+                    &mut stderr,
+                ) {
                     Ok(()) => 0,
                     Err(_) => 1,
                 }
@@ -98,6 +117,9 @@ where
             }
         };
         std::process::exit(exit_code);
+    } else if argv1 == CODEX_APPLY_PATCH_STDIN_ARG1 {
+        // 与上面相同，但 PATCH 从 stdin 读取，适用于超出参数长度限制的大补丁
+        std::process::exit(apply_patch_from_reader(std::io::stdin()));
     }
 
     // 在创建任何线程或 Tokio 运行时之前，加载 .env 环境变量（因为修改环境变量在多线程下不安全）
@@ -105,18 +127,12 @@ where
 
     // 在 PATH 前加入一个临时目录（包含 apply_patch 的链接/脚本），并保留 TempDir
     // 以确保临时目录在函数作用域内有效（函数结束时 TempDir 会被删除）。
-    let _path_entry = match prepend_path_entry_for_apply_patch() {
-        Ok(path_entry) => Some(path_entry),
-        Err(err) => {
-            // 非致命错误：如果无法更新 PATH，仍然可以继续运行，但告警用户
-            eprintln!("WARNING: proceeding, even though we could not update PATH: {err}");
-            None
-        }
-    };
+    // 设置 `CODEX_DISABLE_APPLY_PATCH_SHIM` 时跳过该步骤，PATH 保持不变。
+    let _path_entry = install_apply_patch_shim(apply_patch_shim_disabled());
 
     // 常规路径：构造一个 Tokio runtime 并执行异步 main_fn。
     // 注意：这里使用 block_on 在当前线程上等待异步完成，保持主流程简单。
-    let runtime = tokio::runtime::Runtime::new()?;
+    let runtime = build_runtime(tokio::runtime::Runtime::new)?;
     runtime.block_on(async move {
         // 仅在 Linux 平台将当前 exe 路径传递给子任务（用于 spawn sandbox）
         let codex_linux_sandbox_exe: Option<PathBuf> = if cfg!(target_os = "linux") {
@@ -129,24 +145,157 @@ where
     })
 }
 
+/// Builds the Tokio runtime used to drive `main_fn`.
+///
+/// Constrained environments (e.g. low thread or file-descriptor limits) can
+/// make the multi-threaded runtime fail to start. Rather than bailing out with
+/// an opaque error, fall back to a current-thread runtime and warn about the
+/// downgrade. `build_multi_thread` is injectable so the fallback can be tested.
+///
+/// 说明：多线程 runtime 创建失败时降级为单线程 runtime；两者都失败时返回带上下文的错误。
+fn build_runtime(
+    build_multi_thread: impl FnOnce() -> std::io::Result<tokio::runtime::Runtime>,
+) -> anyhow::Result<tokio::runtime::Runtime> {
+    let multi_thread_err = match build_multi_thread() {
+        Ok(runtime) => return Ok(runtime),
+        Err(err) => err,
+    };
+
+    eprintln!(
+        "WARNING: could not start the multi-threaded Tokio runtime ({multi_thread_err}); \
+         falling back to a current-thread runtime. Check thread/resource limits (e.g. `ulimit -u`)."
+    );
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .with_context(|| {
+            format!(
+                "failed to build a Tokio runtime (multi-threaded runtime failed with: \
+                 {multi_thread_err}); check thread/resource limits"
+            )
+        })
+}
+
+/// Reads the entire patch from `reader` and applies it, returning the process
+/// exit code.
+fn apply_patch_from_reader(mut reader: impl Read) -> i32 {
+    let mut patch = String::new();
+    if let Err(err) = reader.read_to_string(&mut patch) {
+        eprintln!("Error: failed to read a UTF-8 PATCH from stdin: {err}");
+        return 1;
+    }
+    if patch.is_empty() {
+        eprintln!("Error: {CODEX_APPLY_PATCH_STDIN_ARG1} requires a PATCH on stdin.");
+        return 1;
+    }
+
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    match codex_apply_patch::apply_patch_with_progress_and_retry_policy(
+        &patch,
+        &write_retry_policy_from_env(),
+        &mut stdout,
+        &mut stderr,
+    ) {
+        Ok(()) => 0,
+        Err(_) => 1,
+    }
+}
+
+/// The write retry policy Codex configured for this run, or the default when
+/// `apply_patch` was started some other way.
+fn write_retry_policy_from_env() -> WriteRetryPolicy {
+    std::env::var(CODEX_APPLY_PATCH_WRITE_RETRY_ENV_VAR)
+        .ok()
+        .and_then(|value| WriteRetryPolicy::from_env_value(&value))
+        .unwrap_or_default()
+}
+
+/// When set to a truthy value (anything other than empty, `0` or `false`),
+/// the `apply_patch` shim is not added to PATH. Useful in locked-down
+/// environments where PATH mutation is audited. The alias and
+/// `--codex-run-as-apply-patch` dispatch modes are unaffected.
+///
+/// Because `.env` files may not set `CODEX_` variables, this can only be set
+/// in the real process environment.
+const DISABLE_APPLY_PATCH_SHIM_ENV_VAR: &str = "CODEX_DISABLE_APPLY_PATCH_SHIM";
+
+fn apply_patch_shim_disabled() -> bool {
+    std::env::var(DISABLE_APPLY_PATCH_SHIM_ENV_VAR)
+        .map(|value| is_truthy(&value))
+        .unwrap_or(false)
+}
+
+fn is_truthy(value: &str) -> bool {
+    let value = value.trim();
+    !(value.is_empty() || value == "0" || value.eq_ignore_ascii_case("false"))
+}
+
+/// Prepends the `apply_patch` shim directory to PATH unless `disabled`.
+///
+/// The returned [`TempDir`] must be kept alive for as long as the shim
+/// should remain usable.
+fn install_apply_patch_shim(disabled: bool) -> Option<TempDir> {
+    if disabled {
+        return None;
+    }
+
+    match prepend_path_entry_for_apply_patch() {
+        Ok(path_entry) => Some(path_entry),
+        Err(err) => {
+            // 非致命错误：如果无法更新 PATH，仍然可以继续运行，但告警用户
+            eprintln!("WARNING: proceeding, even though we could not update PATH: {err}");
+            None
+        }
+    }
+}
+
 const ILLEGAL_ENV_VAR_PREFIX: &str = "CODEX_";
 
-/// Load env vars from ~/.codex/.env and `$(pwd)/.env`.
+/// Names an additional dotenv file to load after the default locations, e.g.
+/// for CI jobs that keep their environment in a specific file.
+const DOTENV_PATH_ENV_VAR: &str = "CODEX_DOTENV_PATH";
+
+/// Load env vars from ~/.codex/.env, `$(pwd)/.env` and, if set, the file
+/// named by `CODEX_DOTENV_PATH`.
 ///
 /// Security: Do not allow `.env` files to create or modify any variables
 /// with names starting with `CODEX_`.
 ///
 /// 说明：使用 `dotenvy` 来逐条读取环境变量并通过 `set_filtered` 过滤后设置。
+/// `CODEX_DOTENV_PATH` 在加载任何 `.env` 之前读取，因此 `.env` 无法改写它。
 fn load_dotenv() {
-    if let Ok(codex_home) = codex_core::config::find_codex_home()
-        && let Ok(iter) = dotenvy::from_path_iter(codex_home.join(".env"))
-    {
-        set_filtered(iter);
+    let override_path = std::env::var_os(DOTENV_PATH_ENV_VAR).map(PathBuf::from);
+
+    if let Ok(codex_home) = codex_core::config::find_codex_home() {
+        load_dotenv_file(&codex_home.join(".env"));
     }
 
     if let Ok(iter) = dotenvy::dotenv_iter() {
         set_filtered(iter);
     }
+
+    // Loaded last so that the explicitly requested file takes precedence.
+    if let Some(path) = override_path
+        && !load_dotenv_file(&path)
+    {
+        eprintln!(
+            "WARNING: could not read {DOTENV_PATH_ENV_VAR} file {}",
+            path.display()
+        );
+    }
+}
+
+/// Loads a single dotenv file through the `CODEX_` filter. Returns false if
+/// the file could not be opened.
+fn load_dotenv_file(path: &Path) -> bool {
+    match dotenvy::from_path_iter(path) {
+        Ok(iter) => {
+            set_filtered(iter);
+            true
+        }
+        Err(_) => false,
+    }
 }
 
 /// Helper to set vars from a dotenvy iterator while filtering out `CODEX_` keys.
@@ -238,3 +387,71 @@ fn prepend_path_entry_for_apply_patch() -> std::io::Result<TempDir> {
 
     Ok(temp_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_truthy_treats_empty_zero_and_false_as_unset() {
+        for value in ["", " ", "0", "false", "FALSE"] {
+            assert!(!is_truthy(value), "{value:?} should not be truthy");
+        }
+        for value in ["1", "true", "yes"] {
+            assert!(is_truthy(value), "{value:?} should be truthy");
+        }
+    }
+
+    #[test]
+    fn build_runtime_falls_back_to_current_thread() {
+        let runtime = build_runtime(|| Err(std::io::Error::other("simulated thread limit")))
+            .expect("fallback runtime should build");
+
+        assert_eq!(
+            runtime.handle().runtime_flavor(),
+            tokio::runtime::RuntimeFlavor::CurrentThread
+        );
+        let ran = runtime.block_on(async { tokio::task::spawn(async { 42 }).await });
+        assert_eq!(ran.expect("task should complete"), 42);
+    }
+
+    #[test]
+    fn apply_patch_from_reader_rejects_empty_input() {
+        assert_eq!(apply_patch_from_reader(std::io::empty()), 1);
+    }
+
+    #[test]
+    fn load_dotenv_file_sets_vars_and_skips_codex_prefix() {
+        let dir = TempDir::new().expect("create temp dir");
+        let path = dir.path().join("ci.env");
+        std::fs::write(
+            &path,
+            "ARG0_TEST_DOTENV_OVERRIDE=loaded\nCODEX_ARG0_TEST_DOTENV=blocked\n",
+        )
+        .expect("write dotenv file");
+
+        assert!(load_dotenv_file(&path));
+
+        assert_eq!(
+            std::env::var("ARG0_TEST_DOTENV_OVERRIDE").as_deref(),
+            Ok("loaded")
+        );
+        assert!(std::env::var_os("CODEX_ARG0_TEST_DOTENV").is_none());
+    }
+
+    #[test]
+    fn load_dotenv_file_reports_missing_file() {
+        let dir = TempDir::new().expect("create temp dir");
+        assert!(!load_dotenv_file(&dir.path().join("missing.env")));
+    }
+
+    #[test]
+    fn disabled_shim_leaves_path_unmodified() {
+        let before = std::env::var_os("PATH");
+
+        let path_entry = install_apply_patch_shim(true);
+
+        assert!(path_entry.is_none());
+        assert_eq!(std::env::var_os("PATH"), before);
+    }
+}
@@ -1,15 +1,18 @@
 use std::path::PathBuf;
 
 use codex_common::CliConfigOverrides;
+use codex_common::summarize_sandbox_policy;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
-use codex_core::exec_env::create_env;
+use codex_core::exec_env::create_command_env;
 use codex_core::landlock::spawn_command_under_linux_sandbox;
+use codex_core::sandbox_self_test;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::StdioPolicy;
 use codex_protocol::config_types::SandboxMode;
 
 use crate::LandlockCommand;
+use crate::SandboxSelfTestCommand;
 use crate::SeatbeltCommand;
 use crate::exit_status::handle_exit_status;
 
@@ -51,6 +54,65 @@ pub async fn run_command_under_landlock(
     .await
 }
 
+/// Runs the sandbox self-test probes under the configured policy and prints
+/// which operations were blocked. Exits non-zero if any probe misbehaved.
+pub async fn run_sandbox_self_test(
+    command: SandboxSelfTestCommand,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let SandboxSelfTestCommand {
+        full_auto,
+        config_overrides,
+    } = command;
+    let config = Config::load_with_cli_overrides(
+        config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+        ConfigOverrides {
+            sandbox_mode: Some(create_sandbox_mode(full_auto)),
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        },
+    )?;
+    let probe_dir = config.codex_home.join("sandbox-self-test");
+    let report = sandbox_self_test(
+        &config.sandbox_policy,
+        &config.cwd,
+        &probe_dir,
+        &config.codex_linux_sandbox_exe,
+    )
+    .await;
+    let _ = std::fs::remove_dir_all(&probe_dir);
+    let report = report?;
+
+    match report.sandbox_type {
+        Some(sandbox_type) => eprintln!("Sandbox: {sandbox_type:?}"),
+        None => eprintln!("Sandbox: none available on this platform"),
+    }
+    eprintln!(
+        "Policy: {}",
+        summarize_sandbox_policy(&config.sandbox_policy)
+    );
+    for result in &report.probes {
+        let expected = if result.expected_blocked {
+            "blocked"
+        } else {
+            "allowed"
+        };
+        let actual = if result.blocked { "blocked" } else { "allowed" };
+        let verdict = if result.passed() { "ok" } else { "FAIL" };
+        eprintln!(
+            "  [{verdict}] {}: {actual} (expected {expected})",
+            result.probe
+        );
+    }
+
+    if !report.all_passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 enum SandboxType {
     Seatbelt,
     Landlock,
@@ -76,7 +138,7 @@ async fn run_command_under_sandbox(
         },
     )?;
     let stdio_policy = StdioPolicy::Inherit;
-    let env = create_env(&config.shell_environment_policy);
+    let env = create_command_env(&config.shell_environment_policy, &cwd);
 
     let mut child = match sandbox_type {
         SandboxType::Seatbelt => {
@@ -20,6 +20,16 @@ pub struct SeatbeltCommand {
     pub command: Vec<String>,
 }
 
+#[derive(Debug, Parser)]
+pub struct SandboxSelfTestCommand {
+    /// Test the `workspace-write` sandbox used by `--full-auto` instead of `read-only`.
+    #[arg(long = "full-auto", default_value_t = false)]
+    pub full_auto: bool,
+
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+}
+
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
     /// Convenience alias for low-friction sandboxed automatic execution (network-disabled sandbox that can write to cwd and TMPDIR)
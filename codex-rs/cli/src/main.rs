@@ -6,6 +6,7 @@ use codex_arg0::arg0_dispatch_or_else;
 use codex_chatgpt::apply_command::ApplyCommand;
 use codex_chatgpt::apply_command::run_apply_command;
 use codex_cli::LandlockCommand;
+use codex_cli::SandboxSelfTestCommand;
 use codex_cli::SeatbeltCommand;
 use codex_cli::login::run_login_status;
 use codex_cli::login::run_login_with_api_key;
@@ -115,6 +116,9 @@ enum DebugCommand {
 
     /// Run a command under Landlock+seccomp (Linux only).
     Landlock(LandlockCommand),
+
+    /// Check that the platform sandbox blocks what the policy disallows.
+    SandboxSelfTest(SandboxSelfTestCommand),
 }
 
 #[derive(Debug, Parser)]
@@ -222,6 +226,14 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 )
                 .await?;
             }
+            DebugCommand::SandboxSelfTest(mut self_test_cli) => {
+                prepend_config_flags(&mut self_test_cli.config_overrides, cli.config_overrides);
+                codex_cli::debug_sandbox::run_sandbox_self_test(
+                    self_test_cli,
+                    codex_linux_sandbox_exe,
+                )
+                .await?;
+            }
         },
         Some(Subcommand::Apply(mut apply_cli)) => {
             prepend_config_flags(&mut apply_cli.config_overrides, cli.config_overrides);
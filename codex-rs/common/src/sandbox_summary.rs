@@ -9,6 +9,8 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             network_access,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
+            tracked_files_only,
+            allow_git_writes,
         } => {
             let mut summary = "workspace-write".to_string();
 
@@ -27,6 +29,12 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             );
 
             summary.push_str(&format!(" [{}]", writable_entries.join(", ")));
+            if *tracked_files_only {
+                summary.push_str(" (git-tracked files only)");
+            }
+            if *allow_git_writes {
+                summary.push_str(" (.git writable)");
+            }
             if *network_access {
                 summary.push_str(" (network access enabled)");
             }
@@ -1,18 +1,42 @@
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
 use crate::protocol::FileChange;
+use crate::protocol::PatchApplyBeginEvent;
+use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::PatchConflict;
+use crate::protocol::PatchConflictReason;
 use crate::protocol::ReviewDecision;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
+use crate::safety::common_grant_root;
+use crate::safety::patch_paths;
 use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchArgs;
 use codex_apply_patch::ApplyPatchFileChange;
+use codex_apply_patch::ConflictReason;
+use codex_apply_patch::FileConflict;
+use codex_apply_patch::Hunk;
+use codex_apply_patch::MaybeApplyPatch;
+use codex_apply_patch::maybe_parse_apply_patch;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 
 pub const CODEX_APPLY_PATCH_ARG1: &str = "--codex-run-as-apply-patch";
 
+/// Like [`CODEX_APPLY_PATCH_ARG1`], but the patch is read from stdin instead
+/// of argv so that large patches are not subject to OS argument-length limits.
+pub const CODEX_APPLY_PATCH_STDIN_ARG1: &str = "--codex-run-as-apply-patch-stdin";
+
+/// Environment variable carrying the configured
+/// [`codex_apply_patch::WriteRetryPolicy`] to an `apply_patch` run started
+/// with [`CODEX_APPLY_PATCH_ARG1`] or [`CODEX_APPLY_PATCH_STDIN_ARG1`].
+pub const CODEX_APPLY_PATCH_WRITE_RETRY_ENV_VAR: &str = "CODEX_APPLY_PATCH_WRITE_RETRY";
+
 pub(crate) enum InternalApplyPatchInvocation {
     /// The `apply_patch` call was handled programmatically, without any sort
     /// of sandbox, because the user explicitly approved it. This is the
@@ -46,12 +70,30 @@ pub(crate) async fn apply_patch(
     call_id: &str,
     action: ApplyPatchAction,
 ) -> InternalApplyPatchInvocation {
-    match assess_patch_safety(
+    if turn_context.apply_patch_dry_run {
+        return dry_run_apply_patch(sess, sub_id, call_id, &action)
+            .await
+            .into();
+    }
+    let check = assess_patch_safety(
         &action,
         turn_context.approval_policy,
         &turn_context.sandbox_policy,
         &turn_context.cwd,
-    ) {
+        sess.codex_home(),
+        &sess.tracked_files(),
+    );
+    // Writes the user already allowed for the session count as approved.
+    if sess
+        .write_grants()
+        .approves_patch(&check, &action, &turn_context.cwd, sess.codex_home())
+    {
+        return InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+            action,
+            user_explicitly_approved_this_action: true,
+        });
+    }
+    match check {
         SafetyCheck::AutoApprove { .. } => {
             InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                 action,
@@ -59,34 +101,44 @@ pub(crate) async fn apply_patch(
             })
         }
         SafetyCheck::AskUser => {
-            // Compute a readable summary of path changes to include in the
-            // approval request so the user can make an informed decision.
-            //
-            // Note that it might be worth expanding this approval request to
-            // give the user the option to expand the set of writable roots so
-            // that similar patches can be auto-approved in the future during
-            // this session.
+            // Offer to allow writes to the patched files, or to the directory
+            // containing them all, for the rest of the session so similar
+            // patches do not need approval again.
+            let grant_paths = patch_paths(&action, &turn_context.cwd);
+            let grant_root = common_grant_root(&grant_paths);
             let rx_approve = sess
-                .request_patch_approval(sub_id.to_owned(), call_id.to_owned(), &action, None, None)
+                .request_patch_approval(
+                    sub_id.to_owned(),
+                    call_id.to_owned(),
+                    &action,
+                    None,
+                    grant_root.clone(),
+                    grant_paths.clone(),
+                )
                 .await;
             match rx_approve.await.unwrap_or_default() {
-                ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
-                    InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
-                        action,
-                        user_explicitly_approved_this_action: true,
-                    })
+                ReviewDecision::Approved => {}
+                ReviewDecision::ApprovedForSession => {
+                    if let Some(root) = grant_root {
+                        sess.grant_write_root(root);
+                    }
                 }
+                ReviewDecision::ApprovedPathsForSession => sess.grant_write_paths(grant_paths),
                 ReviewDecision::Denied | ReviewDecision::Abort => {
-                    ResponseInputItem::FunctionCallOutput {
+                    return ResponseInputItem::FunctionCallOutput {
                         call_id: call_id.to_owned(),
                         output: FunctionCallOutputPayload {
                             content: "patch rejected by user".to_string(),
                             success: Some(false),
                         },
                     }
-                    .into()
+                    .into();
                 }
             }
+            InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+                action,
+                user_explicitly_approved_this_action: true,
+            })
         }
         SafetyCheck::Reject { reason } => ResponseInputItem::FunctionCallOutput {
             call_id: call_id.to_owned(),
@@ -99,6 +151,62 @@ pub(crate) async fn apply_patch(
     }
 }
 
+/// Validates `action` against the files on disk and reports the changes and
+/// unified diff it would produce through `PatchApplyBegin`/`PatchApplyEnd`,
+/// without writing anything. No approval is needed since nothing changes.
+async fn dry_run_apply_patch(
+    sess: &Session,
+    sub_id: &str,
+    call_id: &str,
+    action: &ApplyPatchAction,
+) -> ResponseInputItem {
+    sess.send_event(Event {
+        id: sub_id.to_owned(),
+        msg: EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+            call_id: call_id.to_owned(),
+            auto_approved: true,
+            changes: convert_apply_patch_to_protocol(action),
+            dry_run: true,
+        }),
+    })
+    .await;
+
+    let (stdout, stderr, success, conflicts) = match action.unified_diff() {
+        Ok(unified_diff) => (unified_diff, String::new(), true, Vec::new()),
+        Err(e) => (
+            String::new(),
+            format!("{e:#}"),
+            false,
+            convert_conflicts_to_protocol(e.conflict()),
+        ),
+    };
+    let content = if success {
+        format!("Dry run: the patch applies cleanly; no files were changed.\n{stdout}")
+    } else {
+        format!("Dry run: the patch does not apply: {stderr}")
+    };
+    sess.send_event(Event {
+        id: sub_id.to_owned(),
+        msg: EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+            call_id: call_id.to_owned(),
+            conflicts,
+            stdout,
+            stderr,
+            success,
+            dry_run: true,
+        }),
+    })
+    .await;
+
+    ResponseInputItem::FunctionCallOutput {
+        call_id: call_id.to_owned(),
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(success),
+        },
+    }
+}
+
 pub(crate) fn convert_apply_patch_to_protocol(
     action: &ApplyPatchAction,
 ) -> HashMap<PathBuf, FileChange> {
@@ -123,3 +231,85 @@ pub(crate) fn convert_apply_patch_to_protocol(
     }
     result
 }
+
+/// The files an `apply_patch` invocation names, for reporting a patch that
+/// failed verification. Updates carry no diff since none could be computed.
+pub(crate) fn unverified_patch_changes(
+    command: &[String],
+    cwd: &Path,
+) -> HashMap<PathBuf, FileChange> {
+    let MaybeApplyPatch::Body(ApplyPatchArgs { hunks, .. }) = maybe_parse_apply_patch(command)
+    else {
+        return HashMap::new();
+    };
+    hunks
+        .into_iter()
+        .map(|hunk| {
+            let path = hunk.resolve_path(cwd);
+            let change = match hunk {
+                Hunk::AddFile { contents, .. } => FileChange::Add { content: contents },
+                Hunk::DeleteFile { .. } => FileChange::Delete,
+                Hunk::UpdateFile { move_path, .. } => FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: move_path.map(|p| cwd.join(p)),
+                },
+            };
+            (path, change)
+        })
+        .collect()
+}
+
+/// Converts the conflicts `codex-apply-patch` reported to their protocol form.
+pub(crate) fn convert_conflicts_to_protocol(
+    conflicts: impl IntoIterator<Item = FileConflict>,
+) -> Vec<PatchConflict> {
+    conflicts
+        .into_iter()
+        .map(|FileConflict { path, reason }| PatchConflict {
+            path,
+            reason: match reason {
+                ConflictReason::ContextMismatch => PatchConflictReason::ContextMismatch,
+                ConflictReason::MissingFile => PatchConflictReason::MissingFile,
+                ConflictReason::PermissionDenied => PatchConflictReason::PermissionDenied,
+                ConflictReason::Other => PatchConflictReason::Other,
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn converts_each_conflict_reason() {
+        let reasons = [
+            (
+                ConflictReason::ContextMismatch,
+                PatchConflictReason::ContextMismatch,
+            ),
+            (
+                ConflictReason::MissingFile,
+                PatchConflictReason::MissingFile,
+            ),
+            (
+                ConflictReason::PermissionDenied,
+                PatchConflictReason::PermissionDenied,
+            ),
+            (ConflictReason::Other, PatchConflictReason::Other),
+        ];
+        for (reason, expected) in reasons {
+            assert_eq!(
+                convert_conflicts_to_protocol([FileConflict {
+                    path: PathBuf::from("/repo/a.rs"),
+                    reason,
+                }]),
+                vec![PatchConflict {
+                    path: PathBuf::from("/repo/a.rs"),
+                    reason: expected,
+                }]
+            );
+        }
+    }
+}
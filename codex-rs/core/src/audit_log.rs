@@ -0,0 +1,99 @@
+//! Append-only audit log of commands that ran outside the sandbox.
+//!
+//! Records are written to `~/.codex/audit/unsandboxed-exec.jsonl`, one JSON
+//! object per line:
+//!
+//! ````text
+//! {"timestamp":"2025-01-01T12:00:00.000Z","conversation_id":"<uuid>","command":["touch","x"],"cwd":"/repo","reason":"danger_full_access"}
+//! ````
+//!
+//! Logging is always on: every execution with [`crate::exec::SandboxType::None`]
+//! produces a record. As with the message history, each record is written
+//! with a single `write(2)` on a file opened with `O_APPEND`, so concurrent
+//! sessions do not interleave lines. The timestamp style follows the
+//! `timestamps` config option.
+
+use std::io::Result;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::config_types::TimestampFormat;
+
+const AUDIT_SUBDIR: &str = "audit";
+const UNSANDBOXED_EXEC_FILENAME: &str = "unsandboxed-exec.jsonl";
+
+/// Why a command was allowed to run without a sandbox.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsandboxedReason {
+    /// The command is on the built-in list of known safe commands.
+    KnownSafeCommand,
+    /// The user approved the command earlier in the session.
+    ApprovedForSession,
+    /// The sandbox policy is `danger-full-access`.
+    DangerFullAccess,
+    /// The sandbox policy asks for a sandbox, but none is available on this
+    /// platform.
+    NoSandboxAvailable,
+    /// The user approved this command (or patch) when asked.
+    UserApproved,
+    /// The command failed in the sandbox and the user approved a retry
+    /// without it.
+    RetryApproved,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnsandboxedExecRecord {
+    pub timestamp: String,
+    pub conversation_id: String,
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    pub reason: UnsandboxedReason,
+}
+
+/// Path of the unsandboxed-execution audit log under `codex_home`.
+pub fn unsandboxed_exec_log_path(codex_home: &Path) -> PathBuf {
+    codex_home
+        .join(AUDIT_SUBDIR)
+        .join(UNSANDBOXED_EXEC_FILENAME)
+}
+
+/// Append a record for `command` running unsandboxed in `cwd`.
+pub(crate) async fn record_unsandboxed_exec(
+    codex_home: &Path,
+    conversation_id: &Uuid,
+    command: &[String],
+    cwd: &Path,
+    reason: UnsandboxedReason,
+    timestamps: TimestampFormat,
+) -> Result<()> {
+    let record = UnsandboxedExecRecord {
+        timestamp: timestamps.now(),
+        conversation_id: conversation_id.to_string(),
+        command: command.to_vec(),
+        cwd: cwd.to_path_buf(),
+        reason,
+    };
+    let mut line = serde_json::to_string(&record)
+        .map_err(|e| std::io::Error::other(format!("failed to serialise audit record: {e}")))?;
+    line.push('\n');
+
+    let path = unsandboxed_exec_log_path(codex_home);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut options = tokio::fs::OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).await?;
+    file.write_all(line.as_bytes()).await?;
+    file.flush().await
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
 use bytes::Bytes;
@@ -6,6 +7,7 @@ use futures::Stream;
 use futures::StreamExt;
 use futures::TryStreamExt;
 use reqwest::StatusCode;
+use serde::Deserialize;
 use serde_json::json;
 use std::pin::Pin;
 use std::task::Context;
@@ -19,10 +21,12 @@ use crate::ModelProviderInfo;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
+use crate::client_common::ToolCallArgumentsAccumulator;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::model_family::ModelFamily;
 use crate::openai_tools::create_tools_json_for_chat_completions_api;
+use crate::protocol::TokenUsage;
 use crate::util::backoff;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ReasoningItemContent;
@@ -34,12 +38,15 @@ pub(crate) async fn stream_chat_completions(
     model_family: &ModelFamily,
     client: &reqwest::Client,
     provider: &ModelProviderInfo,
+    experiment_headers: &HashMap<String, String>,
+    max_output_tokens: Option<u64>,
 ) -> Result<ResponseStream> {
     // Build messages array
     let mut messages = Vec::<serde_json::Value>::new();
 
     let full_instructions = prompt.get_full_instructions(model_family);
-    messages.push(json!({"role": "system", "content": full_instructions}));
+    let instructions_role = provider.effective_instructions_role().as_str();
+    messages.push(json!({"role": instructions_role, "content": full_instructions}));
 
     let input = prompt.get_formatted_input();
 
@@ -139,12 +146,18 @@ pub(crate) async fn stream_chat_completions(
     }
 
     let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-    let payload = json!({
+    let mut payload = json!({
         "model": model_family.slug,
         "messages": messages,
         "stream": true,
         "tools": tools_json,
     });
+    if let Some(max_output_tokens) = max_output_tokens {
+        payload["max_tokens"] = json!(max_output_tokens);
+    }
+    if provider.stream_usage {
+        payload["stream_options"] = json!({ "include_usage": true });
+    }
 
     debug!(
         "POST to {}: {}",
@@ -157,7 +170,10 @@ pub(crate) async fn stream_chat_completions(
     loop {
         attempt += 1;
 
-        let req_builder = provider.create_request_builder(client, &None).await?;
+        let mut req_builder = provider.create_request_builder(client, &None).await?;
+        for (name, value) in experiment_headers {
+            req_builder = req_builder.header(name, value);
+        }
 
         let res = req_builder
             .header(reqwest::header::ACCEPT, "text/event-stream")
@@ -173,8 +189,12 @@ pub(crate) async fn stream_chat_completions(
                     stream,
                     tx_event,
                     provider.stream_idle_timeout(),
+                    provider.stream_usage,
                 ));
-                return Ok(ResponseStream { rx_event });
+                return Ok(ResponseStream {
+                    rx_event,
+                    timing: None,
+                });
             }
             Ok(res) => {
                 let status = res.status();
@@ -209,6 +229,40 @@ pub(crate) async fn stream_chat_completions(
     }
 }
 
+/// `usage` object of a Chat Completions chunk.
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    total_tokens: u64,
+    prompt_tokens_details: Option<ChatPromptTokensDetails>,
+    completion_tokens_details: Option<ChatCompletionTokensDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatPromptTokensDetails {
+    cached_tokens: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionTokensDetails {
+    reasoning_tokens: Option<u64>,
+}
+
+impl From<ChatUsage> for TokenUsage {
+    fn from(val: ChatUsage) -> Self {
+        TokenUsage {
+            input_tokens: val.prompt_tokens,
+            cached_input_tokens: val.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            output_tokens: val.completion_tokens,
+            reasoning_output_tokens: val
+                .completion_tokens_details
+                .and_then(|d| d.reasoning_tokens),
+            total_tokens: val.total_tokens,
+        }
+    }
+}
+
 /// Lightweight SSE processor for the Chat Completions streaming format. The
 /// output is mapped onto Codex's internal [`ResponseEvent`] so that the rest
 /// of the pipeline can stay agnostic of the underlying wire format.
@@ -216,25 +270,21 @@ async fn process_chat_sse<S>(
     stream: S,
     tx_event: mpsc::Sender<Result<ResponseEvent>>,
     idle_timeout: Duration,
+    stream_usage: bool,
 ) where
     S: Stream<Item = Result<Bytes>> + Unpin,
 {
     let mut stream = stream.eventsource();
+    // Latest usage reported by the stream, when `stream_usage` is set. It is
+    // the usage of the whole response once the stream ends.
+    let mut token_usage: Option<TokenUsage> = None;
 
-    // State to accumulate a function call across streaming chunks.
+    // State to accumulate function calls across streaming chunks.
     // OpenAI may split the `arguments` string over multiple `delta` events
     // until the chunk whose `finish_reason` is `tool_calls` is emitted. We
-    // keep collecting the pieces here and forward a single
-    // `ResponseItem::FunctionCall` once the call is complete.
-    #[derive(Default)]
-    struct FunctionCallState {
-        name: Option<String>,
-        arguments: String,
-        call_id: Option<String>,
-        active: bool,
-    }
-
-    let mut fn_call_state = FunctionCallState::default();
+    // keep collecting the pieces here and forward one
+    // `ResponseItem::FunctionCall` per call once they are complete.
+    let mut tool_calls_acc = ToolCallArgumentsAccumulator::default();
     let mut assistant_text = String::new();
     let mut reasoning_text = String::new();
 
@@ -252,18 +302,13 @@ async fn process_chat_sse<S>(
                 let _ = tx_event
                     .send(Ok(ResponseEvent::Completed {
                         response_id: String::new(),
-                        token_usage: None,
+                        token_usage,
                     }))
                     .await;
                 return;
             }
             Err(_) => {
-                let _ = tx_event
-                    .send(Err(CodexErr::Stream(
-                        "idle timeout waiting for SSE".into(),
-                        None,
-                    )))
-                    .await;
+                let _ = tx_event.send(Err(CodexErr::StreamIdleTimeout)).await;
                 return;
             }
         };
@@ -298,7 +343,7 @@ async fn process_chat_sse<S>(
             let _ = tx_event
                 .send(Ok(ResponseEvent::Completed {
                     response_id: String::new(),
-                    token_usage: None,
+                    token_usage,
                 }))
                 .await;
             return;
@@ -311,6 +356,21 @@ async fn process_chat_sse<S>(
         };
         trace!("chat_completions received SSE chunk: {chunk:?}");
 
+        if stream_usage && let Some(usage) = chunk.get("usage").filter(|usage| !usage.is_null()) {
+            match serde_json::from_value::<ChatUsage>(usage.clone()) {
+                Ok(usage) => {
+                    // The latest usage may be the final one, which goes out
+                    // with `Completed`; only superseded snapshots are partial.
+                    if let Some(previous) = token_usage.replace(usage.into()) {
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::PartialTokenUsage(previous)))
+                            .await;
+                    }
+                }
+                Err(e) => debug!("failed to parse chat usage: {e}"),
+            }
+        }
+
         let choice_opt = chunk.get("choices").and_then(|c| c.get(0));
 
         if let Some(choice) = choice_opt {
@@ -361,33 +421,16 @@ async fn process_chat_sse<S>(
                 .get("delta")
                 .and_then(|d| d.get("tool_calls"))
                 .and_then(|tc| tc.as_array())
-                && let Some(tool_call) = tool_calls.first()
             {
-                // Mark that we have an active function call in progress.
-                fn_call_state.active = true;
-
-                // Extract call_id if present.
-                if let Some(id) = tool_call.get("id").and_then(|v| v.as_str()) {
-                    fn_call_state.call_id.get_or_insert_with(|| id.to_string());
-                }
-
-                // Extract function details if present.
-                if let Some(function) = tool_call.get("function") {
-                    if let Some(name) = function.get("name").and_then(|n| n.as_str()) {
-                        fn_call_state.name.get_or_insert_with(|| name.to_string());
-                    }
-
-                    if let Some(args_fragment) = function.get("arguments").and_then(|a| a.as_str())
-                    {
-                        fn_call_state.arguments.push_str(args_fragment);
-                    }
+                for tool_call in tool_calls {
+                    tool_calls_acc.push_delta(tool_call);
                 }
             }
 
             // Emit end-of-turn when finish_reason signals completion.
             if let Some(finish_reason) = choice.get("finish_reason").and_then(|v| v.as_str()) {
                 match finish_reason {
-                    "tool_calls" if fn_call_state.active => {
+                    "tool_calls" if !tool_calls_acc.is_empty() => {
                         // First, flush the terminal raw reasoning so UIs can finalize
                         // the reasoning stream before any exec/tool events begin.
                         if !reasoning_text.is_empty() {
@@ -402,15 +445,10 @@ async fn process_chat_sse<S>(
                             let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
                         }
 
-                        // Then emit the FunctionCall response item.
-                        let item = ResponseItem::FunctionCall {
-                            id: None,
-                            name: fn_call_state.name.clone().unwrap_or_else(|| "".to_string()),
-                            arguments: fn_call_state.arguments.clone(),
-                            call_id: fn_call_state.call_id.clone().unwrap_or_else(String::new),
-                        };
-
-                        let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+                        // Then emit the FunctionCall response items.
+                        for item in std::mem::take(&mut tool_calls_acc).finish() {
+                            let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+                        }
                     }
                     "stop" => {
                         // Regular turn without tool-call. Emit the final assistant message
@@ -445,13 +483,10 @@ async fn process_chat_sse<S>(
                 let _ = tx_event
                     .send(Ok(ResponseEvent::Completed {
                         response_id: String::new(),
-                        token_usage: None,
+                        token_usage,
                     }))
                     .await;
 
-                // Prepare for potential next turn (should not happen in same stream).
-                // fn_call_state = FunctionCallState::default();
-
                 return; // End processing for this SSE stream.
             }
         }
@@ -628,6 +663,9 @@ where
                 Poll::Ready(Some(Ok(ResponseEvent::WebSearchCallBegin { call_id }))) => {
                     return Poll::Ready(Some(Ok(ResponseEvent::WebSearchCallBegin { call_id })));
                 }
+                Poll::Ready(Some(Ok(ResponseEvent::PartialTokenUsage(token_usage)))) => {
+                    return Poll::Ready(Some(Ok(ResponseEvent::PartialTokenUsage(token_usage))));
+                }
             }
         }
     }
@@ -0,0 +1,279 @@
+//! Per-provider circuit breaker. After repeated request failures the circuit
+//! opens and requests to the provider fail fast for a cooldown instead of
+//! exhausting the retry budget on every turn. Once the cooldown has elapsed a
+//! single probe request is let through (half-open): success closes the circuit
+//! again, failure reopens it. A probe that ends without an outcome (e.g. the
+//! request was cancelled) lets the next request probe instead.
+//! 按模型提供者划分的熔断器：连续失败后在冷却期内快速失败，冷却结束后放行一次探测请求。
+
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+// The tokio clock can be paused and advanced in tests.
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::error::CodexErr;
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_WINDOW_MS: u64 = 60_000;
+const DEFAULT_COOLDOWN_MS: u64 = 30_000;
+
+/// Thresholds for a provider's circuit breaker. A `failure_threshold` of 0
+/// disables the breaker.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed requests, within `window_ms`, that open the circuit.
+    pub failure_threshold: u32,
+    /// Failures older than this no longer count towards the threshold.
+    pub window_ms: u64,
+    /// How long the circuit stays open before a probe request is allowed.
+    pub cooldown_ms: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            window_ms: DEFAULT_WINDOW_MS,
+            cooldown_ms: DEFAULT_COOLDOWN_MS,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        failures: Vec<Instant>,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A probe request is in flight; everything else fails fast.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    provider: String,
+    config: CircuitBreakerConfig,
+    state: Mutex<State>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(provider: String, config: CircuitBreakerConfig) -> Self {
+        Self {
+            provider,
+            config,
+            state: Mutex::new(State::Closed {
+                failures: Vec::new(),
+            }),
+        }
+    }
+
+    /// Whether a request may be sent now. Fails with
+    /// [`CodexErr::CircuitOpen`] while the circuit is open or a probe is
+    /// already in flight. The outcome of the request goes to
+    /// [`Permit::record`].
+    pub(crate) fn check(&self) -> Result<Permit<'_>, CodexErr> {
+        let permit = |probe| Permit {
+            breaker: self,
+            probe,
+        };
+        if self.config.failure_threshold == 0 {
+            return Ok(permit(false));
+        }
+        let mut state = lock(&self.state);
+        let retry_in = match *state {
+            State::Closed { .. } => return Ok(permit(false)),
+            State::Open { until } => {
+                let now = Instant::now();
+                if now >= until {
+                    *state = State::HalfOpen;
+                    return Ok(permit(true));
+                }
+                until - now
+            }
+            State::HalfOpen => Duration::ZERO,
+        };
+        Err(CodexErr::CircuitOpen {
+            provider: self.provider.clone(),
+            retry_in,
+        })
+    }
+
+    /// Only errors suggesting the provider is down count as failures; any
+    /// other outcome means the provider answered and closes the circuit.
+    fn record<T>(&self, result: &Result<T, CodexErr>) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        match result {
+            Err(e) if is_provider_failure(e) => self.record_failure(),
+            _ => {
+                *lock(&self.state) = State::Closed {
+                    failures: Vec::new(),
+                }
+            }
+        }
+    }
+
+    fn record_failure(&self) {
+        let now = Instant::now();
+        let window = Duration::from_millis(self.config.window_ms);
+        let mut state = lock(&self.state);
+        let open = match &mut *state {
+            State::Closed { failures } => {
+                failures.retain(|at| now.duration_since(*at) < window);
+                failures.push(now);
+                failures.len() >= self.config.failure_threshold as usize
+            }
+            State::HalfOpen => true,
+            State::Open { .. } => false,
+        };
+        if open {
+            let cooldown = Duration::from_millis(self.config.cooldown_ms);
+            warn!(
+                "opening circuit for provider {} for {cooldown:?} after repeated failures",
+                self.provider
+            );
+            *state = State::Open {
+                until: now + cooldown,
+            };
+        }
+    }
+}
+
+/// A request let through by [`CircuitBreaker::check`].
+#[must_use]
+pub(crate) struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    /// This request is the half-open probe.
+    probe: bool,
+}
+
+impl Permit<'_> {
+    /// Records the outcome of the request.
+    pub(crate) fn record<T>(mut self, result: &Result<T, CodexErr>) {
+        self.probe = false;
+        self.breaker.record(result);
+    }
+}
+
+impl Drop for Permit<'_> {
+    /// A probe dropped without an outcome would leave the circuit half-open
+    /// forever; reopen it so the next request can probe.
+    fn drop(&mut self) {
+        if self.probe {
+            let mut state = lock(&self.breaker.state);
+            if matches!(*state, State::HalfOpen) {
+                *state = State::Open {
+                    until: Instant::now(),
+                };
+            }
+        }
+    }
+}
+
+fn is_provider_failure(e: &CodexErr) -> bool {
+    match e {
+        CodexErr::InternalServerError | CodexErr::Reqwest(_) => true,
+        CodexErr::RetryLimit(status) => status.is_server_error(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn breaker(failure_threshold: u32, cooldown_ms: u64) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "test".to_string(),
+            CircuitBreakerConfig {
+                failure_threshold,
+                window_ms: 60_000,
+                cooldown_ms,
+            },
+        )
+    }
+
+    fn fail(breaker: &CircuitBreaker) {
+        breaker.record::<()>(&Err(CodexErr::RetryLimit(StatusCode::SERVICE_UNAVAILABLE)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn opens_after_threshold_and_recovers_after_successful_probe() {
+        let breaker = breaker(2, 50);
+        fail(&breaker);
+        assert!(breaker.check().is_ok());
+        fail(&breaker);
+        assert!(matches!(breaker.check(), Err(CodexErr::CircuitOpen { .. })));
+
+        tokio::time::advance(Duration::from_millis(40)).await;
+        assert!(breaker.check().is_err());
+        tokio::time::advance(Duration::from_millis(20)).await;
+        // Only one probe is let through while half-open.
+        let probe = breaker.check().unwrap();
+        assert!(breaker.check().is_err());
+
+        probe.record(&Ok(()));
+        assert!(breaker.check().is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_probe_reopens_circuit() {
+        let breaker = breaker(1, 50);
+        fail(&breaker);
+        tokio::time::advance(Duration::from_millis(60)).await;
+        breaker
+            .check()
+            .unwrap()
+            .record::<()>(&Err(CodexErr::RetryLimit(StatusCode::SERVICE_UNAVAILABLE)));
+        assert!(breaker.check().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn abandoned_probe_lets_the_next_request_probe() {
+        let breaker = breaker(1, 50);
+        fail(&breaker);
+        tokio::time::advance(Duration::from_millis(60)).await;
+
+        // The probe request is cancelled before reporting an outcome.
+        drop(breaker.check().unwrap());
+
+        let probe = breaker.check().unwrap();
+        assert!(breaker.check().is_err());
+        probe.record(&Ok(()));
+        assert!(breaker.check().is_ok());
+    }
+
+    #[test]
+    fn client_errors_do_not_count_as_failures() {
+        let breaker = breaker(1, 50);
+        breaker.record::<()>(&Err(CodexErr::UnexpectedStatus(
+            StatusCode::BAD_REQUEST,
+            String::new(),
+        )));
+        assert!(breaker.check().is_ok());
+    }
+
+    #[test]
+    fn zero_threshold_disables_breaker() {
+        let breaker = breaker(0, 50);
+        fail(&breaker);
+        fail(&breaker);
+        assert!(breaker.check().is_ok());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::io::BufRead;
 use std::path::Path;
 use std::time::Duration;
@@ -21,18 +22,23 @@ use uuid::Uuid;
 
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
+use crate::circuit_breaker::CircuitBreaker;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
 use crate::client_common::ResponsesApiRequest;
+use crate::client_common::StreamTiming;
 use crate::client_common::create_reasoning_param_for_request;
 use crate::client_common::create_text_param_for_request;
 use crate::config::Config;
+use crate::default_client::create_client;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::UsageLimitReachedError;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
+use crate::mock_transport::MockTransport;
 use crate::model_family::ModelFamily;
+use crate::model_family::find_family_for_model;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
 use crate::openai_model_info::get_model_info;
@@ -69,6 +75,7 @@ pub struct ModelClient {
     session_id: Uuid,
     effort: ReasoningEffortConfig,
     summary: ReasoningSummaryConfig,
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl ModelClient {
@@ -80,14 +87,56 @@ impl ModelClient {
         summary: ReasoningSummaryConfig,
         session_id: Uuid,
     ) -> Self {
+        let circuit_breaker = Arc::new(CircuitBreaker::new(
+            provider.name.clone(),
+            provider.circuit_breaker.unwrap_or_default(),
+        ));
         Self {
             config,
             auth_manager,
-            client: reqwest::Client::new(),
+            client: create_client(),
             provider,
             session_id,
             effort,
             summary,
+            circuit_breaker,
+        }
+    }
+
+    /// Returns a copy of this client that targets `model` instead, keeping the
+    /// provider, auth and reasoning settings. Used when falling back from an
+    /// unavailable model and for a retried turn that names its own model.
+    pub fn with_model(&self, model: &str) -> Self {
+        let mut config = (*self.config).clone();
+        config.model = model.to_string();
+        config.model_family =
+            find_family_for_model(model).unwrap_or_else(|| self.config.model_family.clone());
+        if let Some(model_info) = get_model_info(&config.model_family) {
+            config.model_context_window = Some(model_info.context_window);
+        }
+        Self {
+            config: Arc::new(config),
+            ..self.clone()
+        }
+    }
+
+    /// Shares `other`'s circuit breaker, so a client rebuilt with new turn
+    /// settings keeps the provider's failure history.
+    pub(crate) fn with_circuit_breaker_of(self, other: &ModelClient) -> Self {
+        Self {
+            circuit_breaker: Arc::clone(&other.circuit_breaker),
+            ..self
+        }
+    }
+
+    /// Returns a copy of this client that sends `experiment_headers` with
+    /// each request instead of the configured ones.
+    pub fn with_experiment_headers(&self, experiment_headers: HashMap<String, String>) -> Self {
+        let mut config = (*self.config).clone();
+        config.experiment_headers = experiment_headers;
+        Self {
+            config: Arc::new(config),
+            ..self.clone()
         }
     }
 
@@ -100,7 +149,21 @@ impl ModelClient {
     /// Dispatches to either the Responses or Chat implementation depending on
     /// the provider config.  Public callers always invoke `stream()` – the
     /// specialised helpers are private to avoid accidental misuse.
+    ///
+    /// Fails fast with [`CodexErr::CircuitOpen`] while the provider's circuit
+    /// breaker is open.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        let permit = self.circuit_breaker.check()?;
+        let timing = StreamTiming::start();
+        let result = self.stream_inner(prompt).await;
+        permit.record(&result);
+        result.map(|stream| ResponseStream {
+            timing: Some(timing),
+            ..stream
+        })
+    }
+
+    async fn stream_inner(&self, prompt: &Prompt) -> Result<ResponseStream> {
         match self.provider.wire_api {
             WireApi::Responses => self.stream_responses(prompt).await,
             WireApi::Chat => {
@@ -110,6 +173,8 @@ impl ModelClient {
                     &self.config.model_family,
                     &self.client,
                     &self.provider,
+                    &self.config.experiment_headers,
+                    self.config.max_output_tokens,
                 )
                 .await?;
 
@@ -136,7 +201,19 @@ impl ModelClient {
                     }
                 });
 
-                Ok(ResponseStream { rx_event: rx })
+                Ok(ResponseStream {
+                    rx_event: rx,
+                    timing: None,
+                })
+            }
+            WireApi::Mock => {
+                let script = self.provider.mock_script.as_deref().ok_or_else(|| {
+                    CodexErr::MockScript(format!(
+                        "provider `{}` has wire_api = \"mock\" but no mock_script",
+                        self.provider.name
+                    ))
+                })?;
+                Ok(MockTransport::for_session(script, self.session_id)?.stream())
             }
         }
     }
@@ -161,11 +238,11 @@ impl ModelClient {
 
         let full_instructions = prompt.get_full_instructions(&self.config.model_family);
         let tools_json = create_tools_json_for_responses_api(&prompt.tools)?;
-        let reasoning = create_reasoning_param_for_request(
-            &self.config.model_family,
-            self.effort,
-            self.summary,
-        );
+        let reasoning = if self.config.disable_reasoning {
+            None
+        } else {
+            create_reasoning_param_for_request(&self.config.model_family, self.effort, self.summary)
+        };
 
         // Request encrypted COT if we are not storing responses,
         // otherwise reasoning items will be referenced by ID
@@ -201,8 +278,14 @@ impl ModelClient {
             store,
             stream: true,
             include,
-            prompt_cache_key: Some(self.session_id.to_string()),
+            prompt_cache_key: Some(
+                self.config
+                    .prompt_cache_key
+                    .clone()
+                    .unwrap_or_else(|| self.session_id.to_string()),
+            ),
             text,
+            max_output_tokens: self.config.max_output_tokens,
         };
 
         let mut attempt = 0;
@@ -241,6 +324,9 @@ impl ModelClient {
             let originator = &self.config.responses_originator_header;
             req_builder = req_builder.header("originator", originator);
             req_builder = req_builder.header("User-Agent", get_codex_user_agent(Some(originator)));
+            for (name, value) in &self.config.experiment_headers {
+                req_builder = req_builder.header(name, value);
+            }
 
             let res = req_builder.send().await;
             if let Ok(resp) = &res {
@@ -264,9 +350,13 @@ impl ModelClient {
                         stream,
                         tx_event,
                         self.provider.stream_idle_timeout(),
+                        self.provider.stream_usage,
                     ));
 
-                    return Ok(ResponseStream { rx_event });
+                    return Ok(ResponseStream {
+                        rx_event,
+                        timing: None,
+                    });
                 }
                 Ok(res) => {
                     let status = res.status();
@@ -346,6 +436,11 @@ impl ModelClient {
         }
     }
 
+    /// Whether a `TurnTiming` event should follow every model response.
+    pub fn reports_turn_timing(&self) -> bool {
+        self.config.report_turn_timing
+    }
+
     pub fn get_provider(&self) -> ModelProviderInfo {
         self.provider.clone()
     }
@@ -428,6 +523,7 @@ async fn process_sse<S>(
     stream: S,
     tx_event: mpsc::Sender<Result<ResponseEvent>>,
     idle_timeout: Duration,
+    stream_usage: bool,
 ) where
     S: Stream<Item = Result<Bytes>> + Unpin,
 {
@@ -471,12 +567,7 @@ async fn process_sse<S>(
                 return;
             }
             Err(_) => {
-                let _ = tx_event
-                    .send(Err(CodexErr::Stream(
-                        "idle timeout waiting for SSE".into(),
-                        None,
-                    )))
-                    .await;
+                let _ = tx_event.send(Err(CodexErr::StreamIdleTimeout)).await;
                 return;
             }
         };
@@ -492,6 +583,25 @@ async fn process_sse<S>(
             }
         };
 
+        // Providers that stream usage attach the usage so far to the
+        // `response` of events such as `response.in_progress`.
+        if stream_usage
+            && event.kind != "response.completed"
+            && let Some(usage) = event
+                .response
+                .as_ref()
+                .and_then(|response| response.get("usage"))
+                .filter(|usage| !usage.is_null())
+        {
+            match serde_json::from_value::<ResponseCompletedUsage>(usage.clone()) {
+                Ok(usage) => {
+                    let event = ResponseEvent::PartialTokenUsage(usage.into());
+                    let _ = tx_event.send(Ok(event)).await;
+                }
+                Err(e) => debug!("failed to parse streamed usage: {e}"),
+            }
+        }
+
         match event.kind.as_str() {
             // Individual output item finalised. Forward immediately so the
             // rest of the agent can stream assistant text/functions *live*
@@ -647,8 +757,12 @@ async fn stream_from_fixture(
         stream,
         tx_event,
         provider.stream_idle_timeout(),
+        provider.stream_usage,
     ));
-    Ok(ResponseStream { rx_event })
+    Ok(ResponseStream {
+        rx_event,
+        timing: None,
+    })
 }
 
 #[cfg(test)]
@@ -677,7 +791,12 @@ mod tests {
         let reader = builder.build();
         let stream = ReaderStream::new(reader).map_err(CodexErr::Io);
         let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent>>(16);
-        tokio::spawn(process_sse(stream, tx, provider.stream_idle_timeout()));
+        tokio::spawn(process_sse(
+            stream,
+            tx,
+            provider.stream_idle_timeout(),
+            provider.stream_usage,
+        ));
 
         let mut events = Vec::new();
         while let Some(ev) = rx.recv().await {
@@ -707,7 +826,12 @@ mod tests {
 
         let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent>>(8);
         let stream = ReaderStream::new(std::io::Cursor::new(body)).map_err(CodexErr::Io);
-        tokio::spawn(process_sse(stream, tx, provider.stream_idle_timeout()));
+        tokio::spawn(process_sse(
+            stream,
+            tx,
+            provider.stream_idle_timeout(),
+            provider.stream_usage,
+        ));
 
         let mut out = Vec::new();
         while let Some(ev) = rx.recv().await {
@@ -765,6 +889,12 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage: false,
         };
 
         let events = collect_events(
@@ -825,6 +955,12 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage: false,
         };
 
         let events = collect_events(&[sse1.as_bytes()], provider).await;
@@ -859,6 +995,12 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage: false,
         };
 
         let events = collect_events(&[sse1.as_bytes()], provider).await;
@@ -964,6 +1106,12 @@ mod tests {
                 stream_max_retries: Some(0),
                 stream_idle_timeout_ms: Some(1000),
                 requires_openai_auth: false,
+                instructions_role: None,
+                organization: None,
+                project: None,
+                circuit_breaker: None,
+                mock_script: None,
+                stream_usage: false,
             };
 
             let out = run_sse(evs, provider).await;
@@ -975,4 +1123,61 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn forwards_streamed_usage_when_provider_supports_it() {
+        fn usage(output_tokens: u64) -> serde_json::Value {
+            json!({
+                "input_tokens": 10,
+                "input_tokens_details": null,
+                "output_tokens": output_tokens,
+                "output_tokens_details": null,
+                "total_tokens": 10 + output_tokens
+            })
+        }
+        let events = vec![
+            json!({"type": "response.in_progress", "response": {"usage": usage(1)}}),
+            json!({"type": "response.in_progress", "response": {"usage": usage(5)}}),
+            json!({"type": "response.completed", "response": {"id": "c", "usage": usage(7)}}),
+        ];
+        let provider = |stream_usage| ModelProviderInfo {
+            name: "test".to_string(),
+            base_url: Some("https://test.com".to_string()),
+            env_key: Some("TEST_API_KEY".to_string()),
+            env_key_instructions: None,
+            wire_api: WireApi::Responses,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: Some(0),
+            stream_max_retries: Some(0),
+            stream_idle_timeout_ms: Some(1000),
+            requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage,
+        };
+
+        let output_tokens = |out: Vec<ResponseEvent>| -> Vec<(bool, u64)> {
+            out.into_iter()
+                .filter_map(|ev| match ev {
+                    ResponseEvent::PartialTokenUsage(usage) => Some((false, usage.output_tokens)),
+                    ResponseEvent::Completed {
+                        token_usage: Some(usage),
+                        ..
+                    } => Some((true, usage.output_tokens)),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let streamed = output_tokens(run_sse(events.clone(), provider(true)).await);
+        assert_eq!(streamed, vec![(false, 1), (false, 5), (true, 7)]);
+
+        let not_streamed = output_tokens(run_sse(events, provider(false)).await);
+        assert_eq!(not_streamed, vec![(true, 7)]);
+    }
 }
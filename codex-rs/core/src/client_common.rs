@@ -1,4 +1,5 @@
 use crate::config_types::Verbosity as VerbosityConfig;
+use crate::environment_context::ENVIRONMENT_CONTEXT_START;
 use crate::error::Result;
 use crate::model_family::ModelFamily;
 use crate::openai_tools::OpenAiTool;
@@ -14,6 +15,8 @@ use std::borrow::Cow;
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 use tokio::sync::mpsc;
 
 /// The `instructions` field in the payload sent to a model should always start
@@ -39,6 +42,10 @@ pub struct Prompt {
 
     /// Optional override for the built-in BASE_INSTRUCTIONS.
     pub base_instructions_override: Option<String>,
+
+    /// Send only the last this many user turns of `input`. Anything before
+    /// the first user turn (such as a compaction summary) is always kept.
+    pub max_context_turns: Option<usize>,
 }
 
 impl Prompt {
@@ -66,7 +73,10 @@ impl Prompt {
     }
 
     pub(crate) fn get_formatted_input(&self) -> Vec<ResponseItem> {
-        self.input.clone()
+        match self.max_context_turns {
+            Some(max_turns) => trim_to_last_turns(&self.input, max_turns),
+            None => self.input.clone(),
+        }
     }
 
     /// Creates a formatted user instructions message from a string
@@ -81,6 +91,47 @@ impl Prompt {
     }
 }
 
+/// Drop the user turns before the last `max_turns` (at least one, so the
+/// current input always goes out). A turn starts at a user message; the
+/// session's user instructions and environment context are not turns and are
+/// kept wherever they appear, as is everything before the first turn.
+fn trim_to_last_turns(input: &[ResponseItem], max_turns: usize) -> Vec<ResponseItem> {
+    let turn_starts: Vec<usize> = input
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| is_user_turn_start(item))
+        .map(|(i, _)| i)
+        .collect();
+    let max_turns = max_turns.max(1);
+    if turn_starts.len() <= max_turns {
+        return input.to_vec();
+    }
+    let (first_turn, keep_from) = (turn_starts[0], turn_starts[turn_starts.len() - max_turns]);
+    input
+        .iter()
+        .enumerate()
+        .filter(|(i, item)| *i < first_turn || *i >= keep_from || is_session_context(item))
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+pub(crate) fn is_user_turn_start(item: &ResponseItem) -> bool {
+    matches!(item, ResponseItem::Message { role, .. } if role == "user")
+        && !is_session_context(item)
+}
+
+/// User instructions or environment context injected by the session rather
+/// than typed by the user.
+fn is_session_context(item: &ResponseItem) -> bool {
+    let ResponseItem::Message { role, content, .. } = item else {
+        return false;
+    };
+    role == "user"
+        && matches!(content.first(), Some(ContentItem::InputText { text })
+            if text.starts_with(USER_INSTRUCTIONS_START)
+                || text.starts_with(ENVIRONMENT_CONTEXT_START))
+}
+
 #[derive(Debug)]
 pub enum ResponseEvent {
     Created,
@@ -96,6 +147,10 @@ pub enum ResponseEvent {
     WebSearchCallBegin {
         call_id: String,
     },
+    /// Token usage of the response so far, from providers that stream it.
+    /// Each value supersedes the previous one; the final usage is carried by
+    /// `Completed` only.
+    PartialTokenUsage(TokenUsage),
 }
 
 #[derive(Debug, Serialize)]
@@ -152,6 +207,8 @@ pub(crate) struct ResponsesApiRequest<'a> {
     pub(crate) prompt_cache_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) text: Option<TextControls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_output_tokens: Option<u64>,
 }
 
 pub(crate) fn create_reasoning_param_for_request(
@@ -174,15 +231,165 @@ pub(crate) fn create_text_param_for_request(
     })
 }
 
+/// Reassembles tool-call arguments that stream in as JSON string fragments.
+///
+/// Chat Completions providers split `function.arguments` across many
+/// `delta.tool_calls` entries, often mid-key or mid-escape, and only the first
+/// fragment of a call carries its `id` and `name`; later ones are matched by
+/// the call's `index`. Fragments are concatenated per call and turned into
+/// function calls in [`ToolCallArgumentsAccumulator::finish`].
+#[derive(Debug, Default)]
+pub(crate) struct ToolCallArgumentsAccumulator {
+    /// Calls in the order they were first seen.
+    calls: Vec<PendingToolCall>,
+}
+
+#[derive(Debug)]
+struct PendingToolCall {
+    index: Option<u64>,
+    call_id: Option<String>,
+    name: Option<String>,
+    arguments: String,
+}
+
+impl ToolCallArgumentsAccumulator {
+    /// Add one entry of a `delta.tool_calls` array.
+    pub(crate) fn push_delta(&mut self, tool_call: &serde_json::Value) {
+        let index = tool_call.get("index").and_then(serde_json::Value::as_u64);
+        let call_id = tool_call.get("id").and_then(|v| v.as_str());
+        let function = tool_call.get("function");
+        let name = function
+            .and_then(|f| f.get("name"))
+            .and_then(|v| v.as_str());
+        let fragment = function
+            .and_then(|f| f.get("arguments"))
+            .and_then(|v| v.as_str());
+
+        // Match on the index when the provider sends one, otherwise on the id;
+        // a fragment with neither continues the most recent call.
+        let position = match (index, call_id) {
+            (Some(index), _) => self.calls.iter().position(|c| c.index == Some(index)),
+            (None, Some(id)) => self
+                .calls
+                .iter()
+                .position(|c| c.call_id.as_deref() == Some(id)),
+            (None, None) => self.calls.len().checked_sub(1),
+        };
+        let call = match position {
+            Some(position) => &mut self.calls[position],
+            None => {
+                self.calls.push(PendingToolCall {
+                    index,
+                    call_id: None,
+                    name: None,
+                    arguments: String::new(),
+                });
+                let last = self.calls.len() - 1;
+                &mut self.calls[last]
+            }
+        };
+        if let Some(id) = call_id {
+            call.call_id.get_or_insert_with(|| id.to_string());
+        }
+        if let Some(name) = name {
+            call.name.get_or_insert_with(|| name.to_string());
+        }
+        if let Some(fragment) = fragment {
+            call.arguments.push_str(fragment);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Turn every call into a [`ResponseItem::FunctionCall`]. Empty arguments
+    /// are sent as `{}`; anything else is kept as received, so a call whose
+    /// arguments are not valid JSON is answered with an error output when it
+    /// is handled instead of failing the whole request.
+    pub(crate) fn finish(self) -> Vec<ResponseItem> {
+        self.calls
+            .into_iter()
+            .map(|call| {
+                let arguments = if call.arguments.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    call.arguments
+                };
+                ResponseItem::FunctionCall {
+                    id: None,
+                    name: call.name.unwrap_or_default(),
+                    arguments,
+                    call_id: call.call_id.unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Latency of one model request, measured from just before it was sent.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StreamTiming {
+    started: Instant,
+    first_output: Option<Duration>,
+    completed: Option<Duration>,
+}
+
+impl StreamTiming {
+    pub(crate) fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            first_output: None,
+            completed: None,
+        }
+    }
+
+    fn observe(&mut self, event: &ResponseEvent) {
+        match event {
+            ResponseEvent::Created => {}
+            ResponseEvent::Completed { .. } => {
+                self.completed.get_or_insert_with(|| self.started.elapsed());
+            }
+            _ => {
+                self.first_output
+                    .get_or_insert_with(|| self.started.elapsed());
+            }
+        }
+    }
+
+    /// Time until the first event carrying model output.
+    pub(crate) fn time_to_first_token(&self) -> Option<Duration> {
+        self.first_output
+    }
+
+    /// Time until `response.completed`, or `None` while still streaming.
+    pub(crate) fn total_duration(&self) -> Option<Duration> {
+        self.completed
+    }
+}
+
 pub(crate) struct ResponseStream {
     pub(crate) rx_event: mpsc::Receiver<Result<ResponseEvent>>,
+    /// Set by [`crate::client::ModelClient::stream`]; updated as events are
+    /// polled.
+    pub(crate) timing: Option<StreamTiming>,
+}
+
+impl ResponseStream {
+    pub(crate) fn timing(&self) -> Option<StreamTiming> {
+        self.timing
+    }
 }
 
 impl Stream for ResponseStream {
     type Item = Result<ResponseEvent>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.rx_event.poll_recv(cx)
+        let poll = self.rx_event.poll_recv(cx);
+        if let (Poll::Ready(Some(Ok(event))), Some(timing)) = (&poll, self.timing.as_mut()) {
+            timing.observe(event);
+        }
+        poll
     }
 }
 
@@ -203,6 +410,56 @@ mod tests {
         assert_eq!(full, expected);
     }
 
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![if role == "user" {
+                ContentItem::InputText {
+                    text: text.to_string(),
+                }
+            } else {
+                ContentItem::OutputText {
+                    text: text.to_string(),
+                }
+            }],
+        }
+    }
+
+    #[test]
+    fn max_context_turns_keeps_summary_and_last_turns() {
+        let input = vec![
+            Prompt::format_user_instructions_message("be brief"),
+            message("assistant", "summary of earlier work"),
+            message("user", "turn 1"),
+            message("assistant", "answer 1"),
+            message("user", "turn 2"),
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "call-2".to_string(),
+            },
+            message("assistant", "answer 2"),
+            message("user", "turn 3"),
+        ];
+        let prompt = Prompt {
+            input: input.clone(),
+            max_context_turns: Some(2),
+            ..Default::default()
+        };
+
+        let mut expected = input[..2].to_vec();
+        expected.extend_from_slice(&input[4..]);
+        assert_eq!(prompt.get_formatted_input(), expected);
+
+        let unlimited = Prompt {
+            input: input.clone(),
+            ..Default::default()
+        };
+        assert_eq!(unlimited.get_formatted_input(), input);
+    }
+
     #[test]
     fn serializes_text_verbosity_when_set() {
         let input: Vec<ResponseItem> = vec![];
@@ -222,6 +479,7 @@ mod tests {
             text: Some(TextControls {
                 verbosity: Some(OpenAiVerbosity::Low),
             }),
+            max_output_tokens: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -250,9 +508,104 @@ mod tests {
             include: vec![],
             prompt_cache_key: None,
             text: None,
+            max_output_tokens: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
         assert!(v.get("text").is_none());
     }
+
+    fn fragment(index: u64, arguments: &str) -> serde_json::Value {
+        serde_json::json!({ "index": index, "function": { "arguments": arguments } })
+    }
+
+    fn reassemble(fragments: &[&str]) -> Vec<ResponseItem> {
+        let mut acc = ToolCallArgumentsAccumulator::default();
+        acc.push_delta(&serde_json::json!({
+            "index": 0,
+            "id": "call_1",
+            "type": "function",
+            "function": { "name": "shell", "arguments": "" },
+        }));
+        for part in fragments {
+            acc.push_delta(&fragment(0, part));
+        }
+        acc.finish()
+    }
+
+    #[test]
+    fn tool_call_arguments_reassemble_at_every_split_point() {
+        let arguments = r#"{"command":["echo","say \"hi\"\n\u00e9 é"],"workdir":"/tmp"}"#;
+        for (split, _) in arguments.char_indices().skip(1) {
+            let (head, tail) = arguments.split_at(split);
+            let items = reassemble(&[head, tail]);
+            let [
+                ResponseItem::FunctionCall {
+                    name,
+                    arguments: got,
+                    call_id,
+                    ..
+                },
+            ] = items.as_slice()
+            else {
+                panic!("expected one function call, got {items:?}");
+            };
+            assert_eq!(name, "shell");
+            assert_eq!(call_id, "call_1");
+            assert_eq!(got, arguments, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn tool_call_arguments_split_mid_escape_and_mid_key() {
+        let items = reassemble(&[r#"{"comm"#, r#"and":["printf","a\"#, r#""b\"#, r#"\"]}"#]);
+        let [ResponseItem::FunctionCall { arguments, .. }] = items.as_slice() else {
+            panic!("expected one function call, got {items:?}");
+        };
+        let parsed: serde_json::Value = serde_json::from_str(arguments).expect("json");
+        assert_eq!(
+            parsed,
+            serde_json::json!({ "command": ["printf", "a\"b\\"] })
+        );
+    }
+
+    #[test]
+    fn interleaved_tool_calls_are_kept_apart() {
+        let mut acc = ToolCallArgumentsAccumulator::default();
+        acc.push_delta(&serde_json::json!({
+            "index": 0, "id": "call_a", "function": { "name": "shell", "arguments": "{\"comma" },
+        }));
+        acc.push_delta(&serde_json::json!({
+            "index": 1, "id": "call_b", "function": { "name": "update_plan", "arguments": "{\"pl" },
+        }));
+        acc.push_delta(&fragment(0, "nd\":[\"ls\"]}"));
+        acc.push_delta(&fragment(1, "an\":[]}"));
+
+        let calls: Vec<(String, String)> = acc
+            .finish()
+            .into_iter()
+            .map(|item| match item {
+                ResponseItem::FunctionCall {
+                    call_id, arguments, ..
+                } => (call_id, arguments),
+                other => panic!("unexpected item {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("call_a".to_string(), r#"{"command":["ls"]}"#.to_string()),
+                ("call_b".to_string(), r#"{"plan":[]}"#.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_tool_call_arguments_are_kept_as_received() {
+        let items = reassemble(&[r#"{"command":["ls""#]);
+        let [ResponseItem::FunctionCall { arguments, .. }] = items.as_slice() else {
+            panic!("expected one function call, got {items:?}");
+        };
+        assert_eq!(arguments, r#"{"command":["ls""#);
+    }
 }
@@ -18,17 +18,22 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::sync::Weak;
 use std::sync::atomic::AtomicU64;
 use std::time::Duration;
+use std::time::Instant;
 
 use async_channel::Receiver;
 use async_channel::Sender;
 use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::FileConflict;
 use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::WriteRetryPolicy;
 use codex_apply_patch::maybe_parse_apply_patch_verified;
 use codex_login::AuthManager;
 use codex_protocol::protocol::ConversationHistoryResponseEvent;
@@ -39,7 +44,9 @@ use futures::prelude::*;
 use mcp_types::CallToolResult;
 use serde::Serialize;
 use serde_json;
+use tokio::sync::OnceCell;
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tokio::task::AbortHandle;
 use tracing::debug;
 use tracing::error;
@@ -52,19 +59,31 @@ use crate::ModelProviderInfo;
 use crate::apply_patch;
 use crate::apply_patch::ApplyPatchExec;
 use crate::apply_patch::CODEX_APPLY_PATCH_ARG1;
+use crate::apply_patch::CODEX_APPLY_PATCH_WRITE_RETRY_ENV_VAR;
 use crate::apply_patch::InternalApplyPatchInvocation;
 use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::apply_patch::convert_conflicts_to_protocol;
+use crate::audit_log::UnsandboxedReason;
+use crate::audit_log::record_unsandboxed_exec;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::config::Config;
+use crate::config_types::ModelOutputStream;
+use crate::config_types::ProjectSummary;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::config_types::ShellKind;
+use crate::config_types::TimestampFormat;
 use crate::conversation_history::ConversationHistory;
+use crate::custom_prompts::render_prompt;
+use crate::environment_context::DEFAULT_PROJECT_SUMMARY_MAX_CHARS;
 use crate::environment_context::EnvironmentContext;
+use crate::environment_context::load_project_summary;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::error::SandboxErr;
 use crate::error::get_error_message_ui;
+use crate::exec::EXEC_TIMEOUT_EXIT_CODE;
 use crate::exec::ExecParams;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
@@ -76,12 +95,22 @@ use crate::exec_command::ExecCommandParams;
 use crate::exec_command::ExecSessionManager;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
-use crate::exec_env::create_env;
+use crate::exec_env::create_command_env;
+use crate::file_mentions::expand_file_mentions;
+use crate::git_info::GitInfo;
+use crate::git_info::RepoStatus;
+use crate::git_info::StashHandle;
+use crate::git_info::auto_stash;
+use crate::git_info::collect_git_info;
+use crate::git_info::repo_status;
+use crate::git_info::restore as restore_stash;
+use crate::is_safe_command::is_known_safe_command;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::StartupTimeoutError;
 use crate::mcp_tool_call::handle_mcp_tool_call;
-use crate::model_family::find_family_for_model;
-use crate::openai_model_info::get_model_info;
+use crate::mock_transport::MockTransport;
 use crate::openai_tools::ApplyPatchToolArgs;
+use crate::openai_tools::OpenAiTool;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
 use crate::openai_tools::get_openai_tools;
@@ -98,6 +127,10 @@ use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::BackgroundEventLevel;
+use crate::protocol::CommandEvaluationEvent;
+use crate::protocol::CompactCompleteEvent;
+use crate::protocol::DiffScope;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
@@ -105,25 +138,35 @@ use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::FileChange;
+use crate::protocol::GetDiffResponseEvent;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
+use crate::protocol::McpServerHealthResponseEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::ResolvedConfigEvent;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::SessionConfiguredEvent;
 use crate::protocol::StreamErrorEvent;
 use crate::protocol::Submission;
 use crate::protocol::TaskCompleteEvent;
+use crate::protocol::TaskCompleteReason;
+use crate::protocol::TokenUsage;
 use crate::protocol::TurnDiffEvent;
+use crate::protocol::TurnTimingEvent;
 use crate::protocol::WebSearchBeginEvent;
 use crate::protocol::WebSearchEndEvent;
 use crate::rollout::RolloutRecorder;
 use crate::safety::SafetyCheck;
+use crate::safety::TrackedFiles;
+use crate::safety::WriteGrants;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
+use crate::safety::heredoc_writes_outside_writable_roots;
 use crate::shell;
+use crate::turn_diff_tracker::SharedTurnDiffTracker;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
@@ -162,9 +205,11 @@ impl<T> MutexExt<T> for Mutex<T> {
 /// Codex 系统的高级接口。
 /// 它作为一个队列对运行，您发送提交并接收事件。
 pub struct Codex {
-    next_id: AtomicU64,         // 下一个提交ID的原子计数器
-    tx_sub: Sender<Submission>, // 用于发送提交的发送通道
-    rx_event: Receiver<Event>,  // 用于接收事件的接收通道
+    next_id: AtomicU64,                   // 下一个提交ID的原子计数器
+    tx_sub: Sender<Submission>,           // 用于发送提交的发送通道
+    rx_event: Receiver<Event>,            // 用于接收事件的接收通道
+    session: Weak<Session>,               // 会话的弱引用，用于读取历史记录
+    config: watch::Receiver<Arc<Config>>, // 包含运行中覆盖项的当前配置
 }
 
 /// Wrapper returned by [`Codex::spawn`] containing the spawned [`Codex`],
@@ -197,6 +242,7 @@ impl Codex {
         config: Config,                             // 配置对象
         auth_manager: Arc<AuthManager>,             // 认证管理器
         initial_history: Option<Vec<ResponseItem>>, // 可选的初始历史记录
+        label: Option<String>,                      // 沿用的会话标签（分叉、复制或恢复时）
     ) -> CodexResult<CodexSpawnOk> {
         // 创建有界的提交通道，用于发送用户操作
         let (tx_sub, rx_sub) = async_channel::bounded(SUBMISSION_CHANNEL_CAPACITY);
@@ -225,6 +271,7 @@ impl Codex {
             notify: config.notify.clone(),           // 通知命令配置
             cwd: config.cwd.clone(),                 // 工作目录
             resume_path,                             // 恢复路径
+            label,                                   // 会话标签
         };
 
         // Generate a unique ID for the lifetime of this Codex session.
@@ -242,6 +289,16 @@ impl Codex {
             CodexErr::InternalAgentDied
         })?;
         let session_id = session.session_id;
+        // 当前生效的配置，随运行中的覆盖操作更新
+        let (config_tx, config_rx) = watch::channel(config.clone());
+
+        // Optionally poll MCP server health until the session goes away.
+        // 可选地在后台定期检查 MCP 服务器健康状况，直到会话结束。
+        if let Some(interval) = config.mcp_health_check_interval
+            && !config.mcp_servers.is_empty()
+        {
+            tokio::spawn(poll_mcp_server_health(Arc::downgrade(&session), interval));
+        }
 
         // This task will run until Op::Shutdown is received.
         // 此任务将运行直到收到 Op::Shutdown 操作。
@@ -250,14 +307,17 @@ impl Codex {
             session.clone(), // 会话实例
             turn_context,    // 对话轮次上下文
             config,          // 配置
+            config_tx,       // 当前配置的发布通道
             rx_sub,          // 提交接收通道
         ));
 
         // 构建 Codex 实例，包含计数器和通道
         let codex = Codex {
-            next_id: AtomicU64::new(0), // 从0开始的提交ID计数器
-            tx_sub,                     // 提交发送通道
-            rx_event,                   // 事件接收通道
+            next_id: AtomicU64::new(0),        // 从0开始的提交ID计数器
+            tx_sub,                            // 提交发送通道
+            rx_event,                          // 事件接收通道
+            session: Arc::downgrade(&session), // 会话弱引用
+            config: config_rx,                 // 当前配置
         };
 
         Ok(CodexSpawnOk { codex, session_id })
@@ -290,6 +350,12 @@ impl Codex {
         Ok(())
     }
 
+    /// A second handle on the event stream; events taken through it are not
+    /// returned by `next_event`.
+    pub(crate) fn event_receiver(&self) -> Receiver<Event> {
+        self.rx_event.clone()
+    }
+
     /// 获取下一个事件，这是客户端接收 Codex 响应的主要方式。
     pub async fn next_event(&self) -> CodexResult<Event> {
         // 从事件通道接收下一个事件
@@ -300,6 +366,43 @@ impl Codex {
             .map_err(|_| CodexErr::InternalAgentDied)?;
         Ok(event)
     }
+
+    /// The conversation history recorded so far.
+    /// 获取目前为止记录的对话历史。
+    pub(crate) fn history(&self) -> CodexResult<Vec<ResponseItem>> {
+        let session = self.session.upgrade().ok_or(CodexErr::InternalAgentDied)?;
+        let history = session.state.lock_unchecked().history.contents();
+        Ok(history)
+    }
+
+    /// The session config including overrides applied while running
+    /// (`OverrideTurnContext`, `SetExperimentHeaders`).
+    /// 包含运行期间覆盖项的会话配置。
+    pub(crate) fn in_flight_config(&self) -> Config {
+        (**self.config.borrow()).clone()
+    }
+
+    /// The label set with `Op::SetConversationLabel`, if any.
+    /// 获取通过 `Op::SetConversationLabel` 设置的标签。
+    pub(crate) fn label(&self) -> Option<String> {
+        self.session.upgrade().and_then(|session| session.label())
+    }
+
+    /// Supply the result of tool call `call_id` without running the tool.
+    /// Only allowed when `stub_tool_calls` is enabled.
+    /// 不执行工具，直接提供工具调用 `call_id` 的结果；仅在启用 `stub_tool_calls` 时可用。
+    pub(crate) fn inject_tool_result(
+        &self,
+        call_id: String,
+        result: FunctionCallOutputPayload,
+    ) -> CodexResult<()> {
+        let session = self.session.upgrade().ok_or(CodexErr::InternalAgentDied)?;
+        if !session.stub_tool_calls {
+            return Err(CodexErr::ToolResultInjectionDisabled);
+        }
+        session.inject_tool_result(call_id, result);
+        Ok(())
+    }
 }
 
 /// Mutable state of the agent
@@ -311,6 +414,34 @@ struct State {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>, // 待审批的操作及其响应通道
     pending_input: Vec<ResponseInputItem>,   // 待处理的输入项
     history: ConversationHistory,            // 对话历史记录
+    /// Changes made by the current (or most recent) task, for `Op::GetDiff`.
+    current_diff_tracker: SharedTurnDiffTracker, // 当前任务的差异跟踪
+    /// Changes made since the session started, for `Op::GetDiff`.
+    cumulative_diff_tracker: SharedTurnDiffTracker, // 会话累计的差异跟踪
+    /// In-flight tool calls that can be cancelled with `Op::CancelToolCall`.
+    tool_call_cancellations: HashMap<String, oneshot::Sender<()>>, // 可取消的进行中工具调用
+    /// Fallback client in use after the configured model was unavailable.
+    model_fallback: Option<ModelFallback>, // 模型不可用时使用的回退客户端
+    /// Stashes created with `Op::AutoStash` and not yet restored, oldest first.
+    auto_stashes: Vec<StashHandle>, // 通过 Op::AutoStash 创建且尚未恢复的 stash
+    /// Writes the user allowed for the session when approving a patch.
+    write_grants: WriteGrants, // 用户在批准补丁时授予的会话级写权限
+    /// Human-readable label set with `Op::SetConversationLabel`.
+    label: Option<String>, // 会话的可读标签
+    /// Injected tool results whose call has not been made yet.
+    injected_tool_results: HashMap<String, FunctionCallOutputPayload>, // 尚未发生调用的注入结果
+    /// Stubbed tool calls waiting for an injected result.
+    pending_tool_results: HashMap<String, oneshot::Sender<FunctionCallOutputPayload>>, // 等待注入结果的工具调用
+    /// Git-tracked files listed at the start of the current task, for
+    /// `tracked_files_only`.
+    tracked_files: Arc<TrackedFiles>, // 当前任务开始时列出的 git 跟踪文件
+}
+
+/// A model that could not serve a turn and the client replacing it for the
+/// rest of the session.
+struct ModelFallback {
+    unavailable_model: String,
+    client: ModelClient,
 }
 
 /// Context for an initialized model agent
@@ -339,12 +470,27 @@ pub(crate) struct Session {
     state: Mutex<State>,                      // 受互斥锁保护的会话状态
     codex_linux_sandbox_exe: Option<PathBuf>, // Linux 沙箱可执行文件路径
     user_shell: shell::Shell,                 // 用户的默认 shell
-    show_raw_agent_reasoning: bool,           // 是否显示原始代理推理过程
+    model_fallbacks: Vec<String>,             // 模型不可用时依次尝试的回退模型
+    exec_shell: ShellKind,                    // exec_command 工具使用的 shell
+    codex_home: PathBuf,                      // 用于写入审计日志的 Codex 主目录
+    max_queued_inputs: Option<usize>,         // 运行中任务可排队的用户输入上限
+    max_context_turns: Option<usize>,         // 请求中最多发送的最近用户轮次数
+    diff_ignore_patterns: Vec<String>,        // 不计入差异报告的文件模式
+    diff_ignore_whitespace: bool,             // 差异报告中省略仅含空白改动的块
+    stub_tool_calls: bool,                    // 工具调用不执行，等待注入的结果（测试/回放）
+    timestamps: TimestampFormat,              // 审计日志中时间戳的格式
+    /// `environment.project_summary` and its length cap.
+    /// 配置中的项目摘要来源及其长度上限。
+    project_summary_source: Option<ProjectSummary>,
+    project_summary_max_chars: usize,
+    /// The project summary, read on first use and kept for the session.
+    /// 首次使用时读取并在会话内缓存的项目摘要。
+    project_summary: OnceCell<Option<String>>,
 }
 
 /// The context needed for a single turn of the conversation.
 /// 单次对话轮次所需的上下文。
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct TurnContext {
     pub(crate) client: ModelClient, // 模型客户端（用于与AI模型通信）
     /// The session's current working directory. All relative paths provided by
@@ -360,6 +506,47 @@ pub(crate) struct TurnContext {
     pub(crate) shell_environment_policy: ShellEnvironmentPolicy, // Shell 环境策略
     pub(crate) disable_response_storage: bool,    // 是否禁用响应存储
     pub(crate) tools_config: ToolsConfig,         // 工具配置（可用的函数调用）
+    /// When `false`, `AgentReasoningRawContent*` events are not emitted for
+    /// this turn. Reasoning items are still recorded in the history.
+    /// 为 `false` 时，本轮次不发送 `AgentReasoningRawContent*` 事件；推理项仍会记录到历史中。
+    pub(crate) include_raw_reasoning: bool,
+    /// Wall-clock budget for the task. Once exceeded, the task stops after
+    /// the current tool call instead of sampling the model again.
+    /// 任务的时间预算；超出后在当前工具调用完成后停止，不再请求模型。
+    pub(crate) max_turn_duration: Option<Duration>,
+    /// Whether the environment context includes a git summary of `cwd`.
+    /// 环境上下文中是否包含 `cwd` 的 git 摘要（分支、提交、远程地址）。
+    pub(crate) include_git_context: bool,
+    /// Whether a sandbox failure under `OnFailure` may be retried without
+    /// the sandbox after user approval.
+    /// 在 `OnFailure` 策略下，沙箱内失败的命令是否可以（经用户批准后）在沙箱外重试。
+    pub(crate) allow_unsandboxed_retry: bool,
+    /// Rate limit for streamed exec output deltas, in events per second.
+    /// 命令输出增量事件的速率上限（每秒事件数），期间的输出会被合并。
+    pub(crate) max_exec_output_deltas_per_second: Option<u32>,
+    /// When `true`, reasoning parameters are omitted from requests and no
+    /// `AgentReasoning*` events are emitted.
+    /// 为 `true` 时，请求中不带推理参数，也不发送任何 `AgentReasoning*` 事件。
+    pub(crate) disable_reasoning: bool,
+    /// External command used instead of the built-in `apply_patch`.
+    /// 用于替代内置 `apply_patch` 的外部命令，补丁通过 stdin 传入。
+    pub(crate) apply_patch_command: Option<Vec<String>>,
+    /// Whether `apply_patch` only validates and previews patches.
+    /// 为 `true` 时 `apply_patch` 只校验并预览补丁，不写入文件。
+    pub(crate) apply_patch_dry_run: bool,
+    /// How the built-in `apply_patch` retries transient write failures.
+    /// 内置 `apply_patch` 对暂时性写入失败的重试策略。
+    pub(crate) apply_patch_write_retry: WriteRetryPolicy,
+    /// Text prepended to the latest user message of each model request.
+    /// 每次模型请求时添加在最新用户消息前的文本（不写入历史）。
+    pub(crate) user_turn_preamble: Option<String>,
+    /// How stdout and stderr of exec calls are presented to the model.
+    /// 执行命令的 stdout 与 stderr 以何种形式提供给模型。
+    pub(crate) model_output_stream: ModelOutputStream,
+    /// Names of the tools the model may use; others are neither advertised
+    /// nor run. `None` allows all.
+    /// 模型可用的工具名称；其他工具既不提供也不执行。为 `None` 时允许全部工具。
+    pub(crate) allowed_tools: Option<Vec<String>>,
 }
 
 impl TurnContext {
@@ -369,6 +556,20 @@ impl TurnContext {
             .map(PathBuf::from)
             .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
     }
+
+    /// Whether `allowed_tools` lets the model use the tool `name`.
+    /// 判断 `allowed_tools` 是否允许模型使用名为 `name` 的工具。
+    fn allows_tool(&self, name: &str) -> bool {
+        self.allowed_tools
+            .as_ref()
+            .is_none_or(|allowed_tools| allowed_tools.iter().any(|allowed| allowed == name))
+    }
+}
+
+/// Output returned to the model for a call to a tool `allowed_tools` excludes.
+/// 模型调用了 `allowed_tools` 之外的工具时返回的输出。
+fn tool_not_allowed(name: &str) -> String {
+    format!("tool `{name}` is not allowed in this turn")
 }
 
 /// Configure the model session.
@@ -422,6 +623,11 @@ struct ConfigureSession {
     cwd: PathBuf,
 
     resume_path: Option<PathBuf>, // 恢复路径（用于会话恢复）
+
+    /// Label carried over from the conversation this one was forked,
+    /// duplicated or resumed from.
+    /// 从原会话（分叉、复制或恢复）沿用的标签。
+    label: Option<String>,
 }
 
 impl Session {
@@ -445,11 +651,20 @@ impl Session {
             notify,
             cwd,
             resume_path,
+            label,
         } = configure_session;
         debug!("Configuring session: model={model}; provider={provider:?}");
         if !cwd.is_absolute() {
             return Err(anyhow::anyhow!("cwd is not absolute: {cwd:?}"));
         }
+        if let Some(exec_shell) = config.exec_shell
+            && which::which(exec_shell.program()).is_err()
+        {
+            return Err(anyhow::anyhow!(
+                "exec_shell `{}` was not found on PATH",
+                exec_shell.program()
+            ));
+        }
 
         // Error messages to dispatch after SessionConfigured is sent.
         let mut post_session_configured_error_events = Vec::<Event>::new();
@@ -462,9 +677,13 @@ impl Session {
         // - load history metadata
         let rollout_fut = async {
             match resume_path.as_ref() {
-                Some(path) => RolloutRecorder::resume(path, cwd.clone())
-                    .await
-                    .map(|(rec, saved)| (saved.session_id, Some(saved), rec)),
+                Some(path) => RolloutRecorder::resume(
+                    path,
+                    cwd.clone(),
+                    config.rollout_image_inline_max_bytes,
+                )
+                .await
+                .map(|(rec, saved)| (saved.session_id, Some(saved), rec)),
                 None => {
                     let session_id = Uuid::new_v4();
                     RolloutRecorder::new(&config, session_id, user_instructions.clone())
@@ -474,7 +693,8 @@ impl Session {
             }
         };
 
-        let mcp_fut = McpConnectionManager::new(config.mcp_servers.clone());
+        let mcp_fut =
+            McpConnectionManager::new(config.mcp_servers.clone(), &config.mcp_tool_name_delimiter);
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
 
@@ -487,9 +707,13 @@ impl Session {
             session_id: Uuid,
             rollout_recorder: Option<RolloutRecorder>,
             restored_items: Option<Vec<ResponseItem>>,
+            restored_label: Option<String>,
         }
         let rollout_result = match rollout_res {
             Ok((session_id, maybe_saved, recorder)) => {
+                let restored_label = maybe_saved
+                    .as_ref()
+                    .and_then(|saved_session| saved_session.state.label.clone());
                 let restored_items: Option<Vec<ResponseItem>> = initial_history.or_else(|| {
                     maybe_saved.and_then(|saved_session| {
                         if saved_session.items.is_empty() {
@@ -503,6 +727,7 @@ impl Session {
                     session_id,
                     rollout_recorder: Some(recorder),
                     restored_items,
+                    restored_label,
                 }
             }
             Err(e) => {
@@ -525,6 +750,7 @@ impl Session {
                     session_id: Uuid::new_v4(),
                     rollout_recorder: None,
                     restored_items: None,
+                    restored_label: None,
                 }
             }
         };
@@ -533,11 +759,22 @@ impl Session {
             session_id,
             rollout_recorder,
             restored_items,
+            restored_label,
         } = rollout_result;
+        // A label handed over by the caller (e.g. a fork) wins over the one
+        // recorded in a resumed rollout.
+        // 调用方传入的标签（例如分叉时）优先于恢复的回放文件中记录的标签
+        let label = label.or(restored_label);
 
         // Create the mutable state for the Session.
         let mut state = State {
             history: ConversationHistory::new(),
+            label: label.clone(),
+            cumulative_diff_tracker: Arc::new(tokio::sync::Mutex::new(
+                TurnDiffTracker::new()
+                    .with_ignore_patterns(&config.diff_ignore_patterns)
+                    .with_ignore_whitespace(config.diff_ignore_whitespace),
+            )),
             ..Default::default()
         };
         if let Some(restored_items) = restored_items {
@@ -558,14 +795,28 @@ impl Session {
             }
         };
 
-        // Surface individual client start-up failures to the user.
+        // Surface individual client start-up failures to the user. Servers that
+        // merely timed out are reported as background events: the session
+        // proceeds without them.
         if !failed_clients.is_empty() {
             for (server_name, err) in failed_clients {
-                let message = format!("MCP client for `{server_name}` failed to start: {err:#}");
-                error!("{message}");
+                let msg = if err.downcast_ref::<StartupTimeoutError>().is_some() {
+                    let message =
+                        format!("MCP server `{server_name}` {err}; continuing without it.");
+                    warn!("{message}");
+                    EventMsg::BackgroundEvent(BackgroundEventEvent {
+                        message,
+                        level: BackgroundEventLevel::Info,
+                    })
+                } else {
+                    let message =
+                        format!("MCP client for `{server_name}` failed to start: {err:#}");
+                    error!("{message}");
+                    EventMsg::Error(ErrorEvent { message })
+                };
                 post_session_configured_error_events.push(Event {
                     id: INITIAL_SUBMIT_ID.to_owned(),
-                    msg: EventMsg::Error(ErrorEvent { message }),
+                    msg,
                 });
             }
         }
@@ -599,6 +850,18 @@ impl Session {
             shell_environment_policy: config.shell_environment_policy.clone(),
             cwd,
             disable_response_storage,
+            include_raw_reasoning: config.show_raw_agent_reasoning,
+            max_turn_duration: config.max_turn_duration,
+            include_git_context: config.include_git_context,
+            allow_unsandboxed_retry: config.allow_unsandboxed_retry,
+            max_exec_output_deltas_per_second: config.max_exec_output_deltas_per_second,
+            disable_reasoning: config.disable_reasoning,
+            apply_patch_command: config.apply_patch_command.clone(),
+            apply_patch_dry_run: config.apply_patch_dry_run,
+            apply_patch_write_retry: config.apply_patch_write_retry,
+            user_turn_preamble: config.user_turn_preamble.clone(),
+            model_output_stream: config.model_output_stream,
+            allowed_tools: None,
         };
         let sess = Arc::new(Session {
             session_id,
@@ -610,7 +873,23 @@ impl Session {
             rollout: Mutex::new(rollout_recorder),
             codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
             user_shell: default_shell,
-            show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            model_fallbacks: config.model_fallbacks.clone(),
+            exec_shell: config
+                .exec_shell
+                .unwrap_or_else(ShellKind::platform_default),
+            codex_home: config.codex_home.clone(),
+            max_queued_inputs: config.max_queued_inputs,
+            max_context_turns: config.max_context_turns,
+            diff_ignore_patterns: config.diff_ignore_patterns.clone(),
+            diff_ignore_whitespace: config.diff_ignore_whitespace,
+            stub_tool_calls: config.stub_tool_calls,
+            timestamps: config.timestamps,
+            project_summary_source: config.environment.project_summary.clone(),
+            project_summary_max_chars: config
+                .environment
+                .project_summary_max_chars
+                .unwrap_or(DEFAULT_PROJECT_SUMMARY_MAX_CHARS),
+            project_summary: OnceCell::new(),
         });
 
         // record the initial user instructions and environment context,
@@ -619,12 +898,22 @@ impl Session {
         if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
             conversation_items.push(Prompt::format_user_instructions_message(user_instructions));
         }
-        conversation_items.push(ResponseItem::from(EnvironmentContext::new(
-            Some(turn_context.cwd.clone()),
-            Some(turn_context.approval_policy),
-            Some(turn_context.sandbox_policy.clone()),
-            Some(sess.user_shell.clone()),
-        )));
+        let (git_info, repo_status) = if turn_context.include_git_context {
+            collect_git_context(&turn_context.cwd).await
+        } else {
+            (None, None)
+        };
+        conversation_items.push(ResponseItem::from(
+            EnvironmentContext::new(
+                Some(turn_context.cwd.clone()),
+                Some(turn_context.approval_policy),
+                Some(turn_context.sandbox_policy.clone()),
+                Some(sess.user_shell.clone()),
+            )
+            .with_git_info(git_info)
+            .with_repo_status(repo_status)
+            .with_project_summary(sess.project_summary(&turn_context.cwd).await),
+        ));
         sess.record_conversation_items(&conversation_items).await;
 
         // Dispatch the SessionConfiguredEvent first and then report any errors.
@@ -635,6 +924,8 @@ impl Session {
                 model,
                 history_log_id,
                 history_entry_count,
+                label,
+                max_output_tokens: config.max_output_tokens,
             }),
         })
         .chain(post_session_configured_error_events.into_iter());
@@ -690,11 +981,12 @@ impl Session {
                 reason,
             }),
         };
-        let _ = self.tx_event.send(event).await;
+        // Register before emitting the request so an immediate reply is not dropped.
         {
             let mut state = self.state.lock_unchecked();
             state.pending_approvals.insert(sub_id, tx_approve);
         }
+        let _ = self.tx_event.send(event).await;
         rx_approve
     }
 
@@ -705,6 +997,7 @@ impl Session {
         action: &ApplyPatchAction,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        grant_paths: Vec<PathBuf>,
     ) -> oneshot::Receiver<ReviewDecision> {
         let (tx_approve, rx_approve) = oneshot::channel();
         let event = Event {
@@ -714,13 +1007,15 @@ impl Session {
                 changes: convert_apply_patch_to_protocol(action),
                 reason,
                 grant_root,
+                grant_paths,
             }),
         };
-        let _ = self.tx_event.send(event).await;
+        // Register before emitting the request so an immediate reply is not dropped.
         {
             let mut state = self.state.lock_unchecked();
             state.pending_approvals.insert(sub_id, tx_approve);
         }
+        let _ = self.tx_event.send(event).await;
         rx_approve
     }
 
@@ -744,6 +1039,95 @@ impl Session {
         state.approved_commands.insert(cmd);
     }
 
+    pub(crate) fn codex_home(&self) -> &Path {
+        &self.codex_home
+    }
+
+    /// Git-tracked files listed for the current task.
+    pub(crate) fn tracked_files(&self) -> Arc<TrackedFiles> {
+        self.state.lock_unchecked().tracked_files.clone()
+    }
+
+    pub(crate) fn write_grants(&self) -> WriteGrants {
+        self.state.lock_unchecked().write_grants.clone()
+    }
+
+    pub(crate) fn grant_write_root(&self, root: PathBuf) {
+        self.state.lock_unchecked().write_grants.grant_root(root);
+    }
+
+    pub(crate) fn grant_write_paths(&self, paths: Vec<PathBuf>) {
+        self.state.lock_unchecked().write_grants.grant_paths(paths);
+    }
+
+    pub(crate) fn label(&self) -> Option<String> {
+        self.state.lock_unchecked().label.clone()
+    }
+
+    /// The configured project summary, read on first use (relative to `cwd`)
+    /// and cached for the rest of the session.
+    /// 配置的项目摘要：首次使用时读取（相对于 `cwd`），之后在会话内缓存。
+    async fn project_summary(&self, cwd: &Path) -> Option<String> {
+        let source = self.project_summary_source.as_ref()?;
+        self.project_summary
+            .get_or_init(|| load_project_summary(source, cwd, self.project_summary_max_chars))
+            .await
+            .clone()
+    }
+
+    /// Deliver an injected result to the stubbed call waiting for it, or keep
+    /// it until that call is made.
+    /// 将注入的结果交给正在等待的工具调用，若调用尚未发生则暂存。
+    fn inject_tool_result(&self, call_id: String, result: FunctionCallOutputPayload) {
+        let mut state = self.state.lock_unchecked();
+        match state.pending_tool_results.remove(&call_id) {
+            Some(tx_result) => {
+                let _ = tx_result.send(result);
+            }
+            None => {
+                state.injected_tool_results.insert(call_id, result);
+            }
+        }
+    }
+
+    /// Wait for the injected result of a stubbed tool call.
+    /// 等待被替身的工具调用的注入结果。
+    async fn stubbed_tool_result(&self, call_id: &str) -> FunctionCallOutputPayload {
+        let rx_result = {
+            let mut state = self.state.lock_unchecked();
+            if let Some(result) = state.injected_tool_results.remove(call_id) {
+                return result;
+            }
+            let (tx_result, rx_result) = oneshot::channel();
+            state
+                .pending_tool_results
+                .insert(call_id.to_string(), tx_result);
+            rx_result
+        };
+        rx_result
+            .await
+            .unwrap_or_else(|_| FunctionCallOutputPayload {
+                content: "aborted".to_string(),
+                success: Some(false),
+            })
+    }
+
+    /// A fresh diff tracker honoring the configured ignore patterns and
+    /// whitespace setting.
+    /// 按配置的忽略模式和空白设置创建新的差异跟踪器。
+    fn new_diff_tracker(&self) -> TurnDiffTracker {
+        TurnDiffTracker::new()
+            .with_ignore_patterns(&self.diff_ignore_patterns)
+            .with_ignore_whitespace(self.diff_ignore_whitespace)
+    }
+
+    /// Replace the conversation label and record it in the rollout right
+    /// away, so it survives even if nothing else is recorded afterwards.
+    async fn set_label(&self, label: Option<String>) {
+        self.state.lock_unchecked().label = label;
+        self.record_state_snapshot(&[]).await;
+    }
+
     /// Records items to both the rollout and the chat completions/ZDR
     /// transcript, if enabled.
     async fn record_conversation_items(&self, items: &[ResponseItem]) {
@@ -754,7 +1138,9 @@ impl Session {
     }
 
     async fn record_state_snapshot(&self, items: &[ResponseItem]) {
-        let snapshot = { crate::rollout::SessionStateSnapshot {} };
+        let snapshot = crate::rollout::SessionStateSnapshot {
+            label: self.label(),
+        };
 
         let recorder = {
             let guard = self.rollout.lock_unchecked();
@@ -773,8 +1159,9 @@ impl Session {
 
     async fn on_exec_command_begin(
         &self,
-        turn_diff_tracker: &mut TurnDiffTracker,
+        turn_diff_tracker: &SharedTurnDiffTracker,
         exec_command_context: ExecCommandContext,
+        sandbox_policy: &SandboxPolicy,
     ) {
         let ExecCommandContext {
             sub_id,
@@ -788,12 +1175,31 @@ impl Session {
                 user_explicitly_approved_this_action,
                 changes,
             }) => {
-                turn_diff_tracker.on_patch_begin(&changes);
+                turn_diff_tracker.lock().await.on_patch_begin(&changes);
+                let cumulative_diff_tracker =
+                    self.state.lock_unchecked().cumulative_diff_tracker.clone();
+                cumulative_diff_tracker
+                    .lock()
+                    .await
+                    .on_patch_begin(&changes);
+                for path in
+                    TurnDiffTracker::paths_outside_writable_roots(&changes, &cwd, sandbox_policy)
+                {
+                    self.notify_background_warning(
+                        &sub_id,
+                        format!(
+                            "apply_patch touched {}, which is outside the writable roots",
+                            path.display()
+                        ),
+                    )
+                    .await;
+                }
 
                 EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                     call_id,
                     auto_approved: !user_explicitly_approved_this_action,
                     changes,
+                    dry_run: false,
                 })
             }
             None => EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
@@ -815,11 +1221,12 @@ impl Session {
 
     async fn on_exec_command_end(
         &self,
-        turn_diff_tracker: &mut TurnDiffTracker,
+        turn_diff_tracker: &SharedTurnDiffTracker,
         sub_id: &str,
         call_id: &str,
         output: &ExecToolCallOutput,
         is_apply_patch: bool,
+        timed_out: bool,
     ) {
         let ExecToolCallOutput {
             stdout,
@@ -827,6 +1234,7 @@ impl Session {
             aggregated_output,
             duration,
             exit_code,
+            peak_rss_bytes,
         } = output;
         // Send full stdout/stderr to clients; do not truncate.
         let stdout = stdout.text.clone();
@@ -837,9 +1245,11 @@ impl Session {
         let msg = if is_apply_patch {
             EventMsg::PatchApplyEnd(PatchApplyEndEvent {
                 call_id: call_id.to_string(),
+                conflicts: convert_conflicts_to_protocol(FileConflict::from_report(&stderr)),
                 stdout,
                 stderr,
                 success: *exit_code == 0,
+                dry_run: false,
             })
         } else {
             EventMsg::ExecCommandEnd(ExecCommandEndEvent {
@@ -849,7 +1259,9 @@ impl Session {
                 aggregated_output,
                 exit_code: *exit_code,
                 duration: *duration,
+                timed_out,
                 formatted_output,
+                peak_rss_bytes: *peak_rss_bytes,
             })
         };
 
@@ -862,9 +1274,15 @@ impl Session {
         // If this is an apply_patch, after we emit the end patch, emit a second event
         // with the full turn diff if there is one.
         if is_apply_patch {
-            let unified_diff = turn_diff_tracker.get_unified_diff();
+            let (unified_diff, suppressed_files) = {
+                let mut tracker = turn_diff_tracker.lock().await;
+                (tracker.get_unified_diff(), tracker.suppressed_files())
+            };
             if let Ok(Some(unified_diff)) = unified_diff {
-                let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
+                let msg = EventMsg::TurnDiff(TurnDiffEvent {
+                    unified_diff,
+                    suppressed_files,
+                });
                 let event = Event {
                     id: sub_id.into(),
                     msg,
@@ -876,39 +1294,70 @@ impl Session {
     /// Runs the exec tool call and emits events for the begin and end of the
     /// command even on error.
     ///
+    /// Output deltas are only emitted while the exec future is being polled,
+    /// so every delta for `call_id` lands between its begin and end events,
+    /// including when the call is cancelled.
+    ///
     /// Returns the output of the exec tool call.
     async fn run_exec_with_events<'a>(
         &self,
-        turn_diff_tracker: &mut TurnDiffTracker,
+        turn_diff_tracker: &SharedTurnDiffTracker,
         begin_ctx: ExecCommandContext,
         exec_args: ExecInvokeArgs<'a>,
     ) -> crate::error::Result<ExecToolCallOutput> {
         let is_apply_patch = begin_ctx.apply_patch.is_some();
+        let is_read_only = !is_apply_patch && is_known_safe_command(&exec_args.params.command);
         let sub_id = begin_ctx.sub_id.clone();
         let call_id = begin_ctx.call_id.clone();
 
-        self.on_exec_command_begin(turn_diff_tracker, begin_ctx.clone())
-            .await;
-
-        let result = process_exec_tool_call(
-            exec_args.params,
-            exec_args.sandbox_type,
+        let cancel_rx = self.register_cancellable_tool_call(&call_id);
+        self.on_exec_command_begin(
+            turn_diff_tracker,
+            begin_ctx.clone(),
             exec_args.sandbox_policy,
-            exec_args.codex_linux_sandbox_exe,
-            exec_args.stdout_stream,
         )
         .await;
 
+        let result = self
+            .run_cancellable_tool_call(
+                &call_id,
+                cancel_rx,
+                process_exec_tool_call(
+                    exec_args.params,
+                    exec_args.sandbox_type,
+                    exec_args.sandbox_policy,
+                    exec_args.codex_linux_sandbox_exe,
+                    exec_args.stdout_stream,
+                ),
+            )
+            .await
+            .unwrap_or(Err(CodexErr::ToolCallCancelled));
+
+        // A patch only counts once applied; any other command that ran may
+        // have written files unless it is known to be read-only.
+        // 补丁仅在成功应用后计入；其他已运行的命令除非已知只读，都可能写入了文件。
+        if let Ok(output) = &result
+            && !is_read_only
+            && (!is_apply_patch || output.exit_code == 0)
+        {
+            turn_diff_tracker.lock().await.note_files_changed();
+        }
+
         let output_stderr;
         let borrowed: &ExecToolCallOutput = match &result {
             Ok(output) => output,
             Err(e) => {
+                let (exit_code, duration) = match e {
+                    CodexErr::ExecTimeout(timeout) => (EXEC_TIMEOUT_EXIT_CODE, *timeout),
+                    _ => (-1, Duration::default()),
+                };
                 output_stderr = ExecToolCallOutput {
-                    exit_code: -1,
+                    exit_code,
                     stdout: StreamOutput::new(String::new()),
                     stderr: StreamOutput::new(get_error_message_ui(e)),
                     aggregated_output: StreamOutput::new(get_error_message_ui(e)),
-                    duration: Duration::default(),
+                    duration,
+                    peak_rss_bytes: None,
                 };
                 &output_stderr
             }
@@ -919,12 +1368,47 @@ impl Session {
             &call_id,
             borrowed,
             is_apply_patch,
+            matches!(result, Err(CodexErr::ExecTimeout(_))),
         )
         .await;
 
         result
     }
 
+    /// Client for the next model request: the session's fallback client when
+    /// the turn's model was already found to be unavailable.
+    fn turn_client(&self, turn_context: &TurnContext) -> ModelClient {
+        let state = self.state.lock_unchecked();
+        match &state.model_fallback {
+            Some(fallback) if fallback.unavailable_model == turn_context.client.get_model() => {
+                fallback.client.clone()
+            }
+            _ => turn_context.client.clone(),
+        }
+    }
+
+    /// Switches to the `model_fallbacks` entry following `client`'s model and
+    /// keeps it for the rest of the session. Returns `None` once the chain is
+    /// exhausted.
+    fn fall_back_from(
+        &self,
+        turn_context: &TurnContext,
+        client: &ModelClient,
+    ) -> Option<ModelClient> {
+        let current = client.get_model();
+        let next = match self.model_fallbacks.iter().position(|m| *m == current) {
+            Some(idx) => self.model_fallbacks.get(idx + 1)?,
+            None => self.model_fallbacks.first()?,
+        };
+        let fallback = turn_context.client.with_model(next);
+        let mut state = self.state.lock_unchecked();
+        state.model_fallback = Some(ModelFallback {
+            unavailable_model: turn_context.client.get_model(),
+            client: fallback.clone(),
+        });
+        Some(fallback)
+    }
+
     /// Helper that emits a BackgroundEvent with the given message. This keeps
     /// the call‑sites terse so adding more diagnostics does not clutter the
     /// core agent logic.
@@ -933,6 +1417,36 @@ impl Session {
             id: sub_id.to_string(),
             msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
                 message: message.into(),
+                level: BackgroundEventLevel::Info,
+            }),
+        };
+        let _ = self.tx_event.send(event).await;
+    }
+
+    /// Append `command` to the unsandboxed-execution audit log. Failures are
+    /// logged but do not stop the command from running.
+    async fn audit_unsandboxed_exec(&self, params: &ExecParams, reason: UnsandboxedReason) {
+        if let Err(e) = record_unsandboxed_exec(
+            &self.codex_home,
+            &self.session_id,
+            &params.command,
+            &params.cwd,
+            reason,
+            self.timestamps,
+        )
+        .await
+        {
+            error!("failed to write unsandboxed exec audit record: {e}");
+        }
+    }
+
+    /// Like [`Session::notify_background_event`], but flagged as a warning.
+    async fn notify_background_warning(&self, sub_id: &str, message: impl Into<String>) {
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: message.into(),
+                level: BackgroundEventLevel::Warn,
             }),
         };
         let _ = self.tx_event.send(event).await;
@@ -965,6 +1479,23 @@ impl Session {
         }
     }
 
+    /// The configured `max_queued_inputs` if a task is running and that many
+    /// user inputs are already waiting for it.
+    fn full_input_queue_limit(&self) -> Option<usize> {
+        let max = self.max_queued_inputs?;
+        let state = self.state.lock_unchecked();
+        (state.current_task.is_some() && state.pending_input.len() >= max).then_some(max)
+    }
+
+    /// Tell the client that user input was dropped because the queue is full.
+    async fn reject_queued_input(&self, sub_id: &str, max: usize) {
+        self.notify_background_warning(
+            sub_id,
+            format!("input rejected: {max} inputs are already waiting for the running task"),
+        )
+        .await;
+    }
+
     pub fn get_pending_input(&self) -> Vec<ResponseInputItem> {
         let mut state = self.state.lock_unchecked();
         if state.pending_input.is_empty() {
@@ -982,19 +1513,76 @@ impl Session {
         tool: &str,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        attempts: &mut u32,
     ) -> anyhow::Result<CallToolResult> {
         self.mcp_connection_manager
-            .call_tool(server, tool, arguments, timeout)
+            .call_tool(server, tool, arguments, timeout, attempts)
             .await
     }
 
+    /// Marks `call_id` as in flight so that `Op::CancelToolCall` can reach it.
+    /// Register before announcing the call so a cancel request sent in reply
+    /// to the begin event cannot race the registration.
+    /// 注册可取消的工具调用（需在发送开始事件之前调用）。
+    pub(crate) fn register_cancellable_tool_call(&self, call_id: &str) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        self.state
+            .lock_unchecked()
+            .tool_call_cancellations
+            .insert(call_id.to_string(), tx);
+        rx
+    }
+
+    /// Runs `fut` for the tool call `call_id`, returning `None` if the call is
+    /// cancelled before it completes. The future is dropped on cancellation,
+    /// which kills any child process it spawned.
+    /// 运行可取消的工具调用；被取消时返回 `None`。
+    pub(crate) async fn run_cancellable_tool_call<T>(
+        &self,
+        call_id: &str,
+        cancel_rx: oneshot::Receiver<()>,
+        fut: impl Future<Output = T>,
+    ) -> Option<T> {
+        let result = tokio::select! {
+            output = fut => Some(output),
+            _ = cancel_rx => None,
+        };
+
+        self.state
+            .lock_unchecked()
+            .tool_call_cancellations
+            .remove(call_id);
+        result
+    }
+
+    /// Cancels the in-flight tool call `call_id`. Returns `false` if no such
+    /// call is running.
+    fn cancel_tool_call(&self, call_id: &str) -> bool {
+        let tx = self
+            .state
+            .lock_unchecked()
+            .tool_call_cancellations
+            .remove(call_id);
+        match tx {
+            Some(tx) => tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+
     fn interrupt_task(&self) {
         info!("interrupt received: abort current task, if any");
+        self.abort_task(TurnAbortReason::Interrupted);
+    }
+
+    /// Abort the current task, if any, reporting `reason` in `TurnAborted`.
+    fn abort_task(&self, reason: TurnAbortReason) {
         let mut state = self.state.lock_unchecked();
         state.pending_approvals.clear();
         state.pending_input.clear();
+        state.tool_call_cancellations.clear();
+        state.pending_tool_results.clear();
         if let Some(task) = state.current_task.take() {
-            task.abort(TurnAbortReason::Interrupted);
+            task.abort(reason);
         }
     }
 
@@ -1031,6 +1619,7 @@ impl Session {
 impl Drop for Session {
     fn drop(&mut self) {
         self.interrupt_task();
+        MockTransport::release_session(self.session_id);
     }
 }
 
@@ -1120,6 +1709,114 @@ impl AgentTask {
     }
 }
 
+/// The session config with the persistent overrides from `turn_context`
+/// applied, i.e. what a new session would need to continue where this one is.
+/// 将 `turn_context` 中持久的覆盖项应用到会话配置上，得到当前生效的配置。
+fn in_flight_config(config: &Config, turn_context: &TurnContext) -> Config {
+    let client = &turn_context.client;
+    let mut config = config.clone();
+    config.model = client.get_model();
+    config.model_family = client.get_model_family();
+    config.model_context_window = client.get_model_context_window();
+    config.model_reasoning_effort = client.get_reasoning_effort();
+    config.model_reasoning_summary = client.get_reasoning_summary();
+    config.approval_policy = turn_context.approval_policy;
+    config.sandbox_policy = turn_context.sandbox_policy.clone();
+    config.cwd = turn_context.cwd.clone();
+    config
+}
+
+/// Collect the git summary and repository status of `cwd` for the
+/// environment context.
+/// 收集 `cwd` 的 git 摘要和仓库状态，用于环境上下文。
+async fn collect_git_context(cwd: &Path) -> (Option<GitInfo>, Option<RepoStatus>) {
+    tokio::join!(collect_git_info(cwd), repo_status(cwd))
+}
+
+/// Report MCP server health every `interval` for as long as the session is
+/// alive.
+/// 在会话存活期间，每隔 `interval` 报告一次 MCP 服务器健康状况。
+async fn poll_mcp_server_health(session: Weak<Session>, interval: Duration) {
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let Some(sess) = session.upgrade() else {
+            break;
+        };
+        let servers = sess.mcp_connection_manager.check_health().await;
+        let event = Event {
+            id: INITIAL_SUBMIT_ID.to_owned(),
+            msg: EventMsg::McpServerHealthResponse(McpServerHealthResponseEvent { servers }),
+        };
+        if sess.tx_event.send(event).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Switching off the sandbox entirely needs explicit confirmation: reports
+/// an error and returns true if `requested` would move a session that is
+/// not yet running with `DangerFullAccess` to it without `confirmed`.
+/// 切换到完全访问模式需要明确确认；未确认时报告错误并返回 true
+async fn reject_unconfirmed_danger_full_access(
+    sess: &Session,
+    config: &Config,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    requested: Option<&SandboxPolicy>,
+    confirmed: bool,
+) -> bool {
+    if !config.confirm_danger_full_access
+        || confirmed
+        || requested != Some(&SandboxPolicy::DangerFullAccess)
+        || turn_context.sandbox_policy == SandboxPolicy::DangerFullAccess
+    {
+        return false;
+    }
+    let event = Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::Error(ErrorEvent {
+            message: "refusing to switch to danger-full-access without confirmation; \
+                      resend with `danger_full_access_confirmed` or set \
+                      `confirm_danger_full_access = false`"
+                .to_string(),
+        }),
+    };
+    if let Err(e) = sess.tx_event.send(event).await {
+        warn!("failed to send Error event: {e}");
+    }
+    true
+}
+
+/// Deliver user input to the running task, or start a new task with it.
+/// 将用户输入交给当前任务，或以其启动新任务
+async fn submit_user_input(
+    sess: &Arc<Session>,
+    turn_context: &Arc<TurnContext>,
+    config: &Config,
+    sub_id: String,
+    items: Vec<InputItem>,
+) {
+    if let Some(max) = sess.full_input_queue_limit() {
+        sess.reject_queued_input(&sub_id, max).await;
+        return;
+    }
+    let items = expand_file_mentions(
+        items,
+        &turn_context.cwd,
+        config.file_mention_mode,
+        config.file_mention_max_inline_bytes,
+    )
+    .await;
+    // attempt to inject input into current task
+    if let Err(items) = sess.inject_input(items) {
+        // no current task, spawn a new one
+        let task = AgentTask::spawn(sess.clone(), Arc::clone(turn_context), sub_id, items);
+        sess.set_task(task);
+    }
+}
+
 /// 提交循环 - Codex 系统的核心调度器
 ///
 /// 这是整个系统的心脏，负责：
@@ -1129,10 +1826,11 @@ impl AgentTask {
 /// 4. 处理配置覆盖和模型切换
 /// 5. 协调各种操作（用户输入、审批、历史记录等）
 async fn submission_loop(
-    sess: Arc<Session>,           // 会话实例
-    turn_context: TurnContext,    // 初始轮次上下文
-    config: Arc<Config>,          // 配置
-    rx_sub: Receiver<Submission>, // 提交接收通道
+    sess: Arc<Session>,                    // 会话实例
+    turn_context: TurnContext,             // 初始轮次上下文
+    mut config: Arc<Config>,               // 配置
+    config_tx: watch::Sender<Arc<Config>>, // 当前配置的发布通道
+    rx_sub: Receiver<Submission>,          // 提交接收通道
 ) {
     // Wrap once to avoid cloning TurnContext for each task.
     // 包装一次以避免为每个任务克隆 TurnContext
@@ -1155,15 +1853,28 @@ async fn submission_loop(
                 model,
                 effort,
                 summary,
+                danger_full_access_confirmed,
             } => {
+                if reject_unconfirmed_danger_full_access(
+                    &sess,
+                    &config,
+                    &turn_context,
+                    &sub.id,
+                    sandbox_policy.as_ref(),
+                    danger_full_access_confirmed,
+                )
+                .await
+                {
+                    continue;
+                }
+
                 // Recalculate the persistent turn context with provided overrides.
                 let prev = Arc::clone(&turn_context);
                 let provider = prev.client.get_provider();
 
                 // Effective model + family
                 let (effective_model, effective_family) = if let Some(m) = model {
-                    let fam =
-                        find_family_for_model(&m).unwrap_or_else(|| config.model_family.clone());
+                    let fam = config.model_family_for(&m);
                     (m, fam)
                 } else {
                     (prev.client.get_model(), prev.client.get_model_family())
@@ -1179,9 +1890,13 @@ async fn submission_loop(
                 let mut updated_config = (*config).clone();
                 updated_config.model = effective_model.clone();
                 updated_config.model_family = effective_family.clone();
-                if let Some(model_info) = get_model_info(&effective_family) {
-                    updated_config.model_context_window = Some(model_info.context_window);
+                if let Some(context_window) =
+                    config.model_context_window_for(&effective_model, &effective_family)
+                {
+                    updated_config.model_context_window = Some(context_window);
                 }
+                updated_config.model_pricing =
+                    config.model_pricing_for(&effective_model, &effective_family);
 
                 let client = ModelClient::new(
                     Arc::new(updated_config),
@@ -1190,7 +1905,8 @@ async fn submission_loop(
                     effective_effort,
                     effective_summary,
                     sess.session_id,
-                );
+                )
+                .with_circuit_breaker_of(&prev.client);
 
                 let new_approval_policy = approval_policy.unwrap_or(prev.approval_policy);
                 let new_sandbox_policy = sandbox_policy
@@ -1219,29 +1935,87 @@ async fn submission_loop(
                     shell_environment_policy: prev.shell_environment_policy.clone(),
                     cwd: new_cwd.clone(),
                     disable_response_storage: prev.disable_response_storage,
+                    include_raw_reasoning: prev.include_raw_reasoning,
+                    max_turn_duration: prev.max_turn_duration,
+                    include_git_context: prev.include_git_context,
+                    allow_unsandboxed_retry: prev.allow_unsandboxed_retry,
+                    max_exec_output_deltas_per_second: prev.max_exec_output_deltas_per_second,
+                    disable_reasoning: prev.disable_reasoning,
+                    apply_patch_command: prev.apply_patch_command.clone(),
+                    apply_patch_dry_run: prev.apply_patch_dry_run,
+                    apply_patch_write_retry: prev.apply_patch_write_retry,
+                    user_turn_preamble: prev.user_turn_preamble.clone(),
+                    model_output_stream: prev.model_output_stream,
+                    allowed_tools: prev.allowed_tools.clone(),
                 };
 
                 // Install the new persistent context for subsequent tasks/turns.
                 turn_context = Arc::new(new_turn_context);
+                config_tx.send_replace(Arc::new(in_flight_config(&config, &turn_context)));
                 if cwd.is_some() || approval_policy.is_some() || sandbox_policy.is_some() {
-                    sess.record_conversation_items(&[ResponseItem::from(EnvironmentContext::new(
-                        cwd,
-                        approval_policy,
-                        sandbox_policy,
-                        // Shell is not configurable from turn to turn
-                        None,
-                    ))])
+                    // Only a new cwd can change the git summary.
+                    let (git_info, repo_status) = match &cwd {
+                        Some(cwd) if turn_context.include_git_context => {
+                            collect_git_context(cwd).await
+                        }
+                        _ => (None, None),
+                    };
+                    sess.record_conversation_items(&[ResponseItem::from(
+                        EnvironmentContext::new(
+                            cwd,
+                            approval_policy,
+                            sandbox_policy,
+                            // Shell is not configurable from turn to turn
+                            None,
+                        )
+                        .with_git_info(git_info)
+                        .with_repo_status(repo_status),
+                    )])
                     .await;
                 }
             }
-            Op::UserInput { items } => {
-                // attempt to inject input into current task
-                if let Err(items) = sess.inject_input(items) {
-                    // no current task, spawn a new one
-                    let task =
-                        AgentTask::spawn(sess.clone(), Arc::clone(&turn_context), sub.id, items);
-                    sess.set_task(task);
+            Op::SetExperimentHeaders { headers } => {
+                let invalid = headers.iter().find(|(name, value)| {
+                    reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err()
+                        || value.as_deref().is_some_and(|value| {
+                            reqwest::header::HeaderValue::from_str(value).is_err()
+                        })
+                });
+                if let Some((name, _)) = invalid {
+                    sess.notify_background_warning(
+                        &sub.id,
+                        format!("invalid experiment header `{name}`; headers left unchanged"),
+                    )
+                    .await;
+                    continue;
                 }
+
+                // Update the config too so later per-turn clients (`UserTurn`,
+                // `OverrideTurnContext`) keep the headers.
+                let mut updated_config = (*config).clone();
+                for (name, value) in headers {
+                    // Header names are case-insensitive.
+                    updated_config
+                        .experiment_headers
+                        .retain(|existing, _| !existing.eq_ignore_ascii_case(&name));
+                    if let Some(value) = value {
+                        updated_config.experiment_headers.insert(name, value);
+                    }
+                }
+                config = Arc::new(updated_config);
+                turn_context = Arc::new(TurnContext {
+                    client: turn_context
+                        .client
+                        .with_experiment_headers(config.experiment_headers.clone()),
+                    ..(*turn_context).clone()
+                });
+                config_tx.send_replace(Arc::new(in_flight_config(&config, &turn_context)));
+            }
+            Op::SetConversationLabel { label } => {
+                sess.set_label(label).await;
+            }
+            Op::UserInput { items } => {
+                submit_user_input(&sess, &turn_context, &config, sub.id, items).await;
             }
             Op::UserTurn {
                 items,
@@ -1251,7 +2025,34 @@ async fn submission_loop(
                 model,
                 effort,
                 summary,
+                include_raw_reasoning,
+                allowed_tools,
+                max_output_tokens,
+                danger_full_access_confirmed,
             } => {
+                if reject_unconfirmed_danger_full_access(
+                    &sess,
+                    &config,
+                    &turn_context,
+                    &sub.id,
+                    Some(&sandbox_policy),
+                    danger_full_access_confirmed,
+                )
+                .await
+                {
+                    continue;
+                }
+                if let Some(max) = sess.full_input_queue_limit() {
+                    sess.reject_queued_input(&sub.id, max).await;
+                    continue;
+                }
+                let items = expand_file_mentions(
+                    items,
+                    &cwd,
+                    config.file_mention_mode,
+                    config.file_mention_max_inline_bytes,
+                )
+                .await;
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
                     // Derive a fresh TurnContext for this turn using the provided overrides.
@@ -1259,19 +2060,26 @@ async fn submission_loop(
                     let auth_manager = turn_context.client.get_auth_manager();
 
                     // Derive a model family for the requested model; fall back to the session's.
-                    let model_family = find_family_for_model(&model)
-                        .unwrap_or_else(|| config.model_family.clone());
+                    let model_family = config.model_family_for(&model);
 
                     // Create a per‑turn Config clone with the requested model/family.
                     let mut per_turn_config = (*config).clone();
                     per_turn_config.model = model.clone();
                     per_turn_config.model_family = model_family.clone();
-                    if let Some(model_info) = get_model_info(&model_family) {
-                        per_turn_config.model_context_window = Some(model_info.context_window);
+                    per_turn_config.show_raw_agent_reasoning = include_raw_reasoning;
+                    if max_output_tokens.is_some() {
+                        per_turn_config.max_output_tokens = max_output_tokens;
+                    }
+                    if let Some(context_window) =
+                        config.model_context_window_for(&model, &model_family)
+                    {
+                        per_turn_config.model_context_window = Some(context_window);
                     }
+                    per_turn_config.model_pricing = config.model_pricing_for(&model, &model_family);
 
                     // Build a new client with per‑turn reasoning settings.
-                    // Reuse the same provider and session id; auth defaults to env/API key.
+                    // Reuse the same provider, session id and circuit breaker;
+                    // auth defaults to env/API key.
                     let client = ModelClient::new(
                         Arc::new(per_turn_config),
                         auth_manager,
@@ -1279,7 +2087,8 @@ async fn submission_loop(
                         effort,
                         summary,
                         sess.session_id,
-                    );
+                    )
+                    .with_circuit_breaker_of(&turn_context.client);
 
                     let fresh_turn_context = TurnContext {
                         client,
@@ -1301,6 +2110,19 @@ async fn submission_loop(
                         shell_environment_policy: turn_context.shell_environment_policy.clone(),
                         cwd,
                         disable_response_storage: turn_context.disable_response_storage,
+                        include_raw_reasoning,
+                        max_turn_duration: turn_context.max_turn_duration,
+                        include_git_context: turn_context.include_git_context,
+                        allow_unsandboxed_retry: turn_context.allow_unsandboxed_retry,
+                        max_exec_output_deltas_per_second: turn_context
+                            .max_exec_output_deltas_per_second,
+                        disable_reasoning: turn_context.disable_reasoning,
+                        apply_patch_command: turn_context.apply_patch_command.clone(),
+                        apply_patch_dry_run: turn_context.apply_patch_dry_run,
+                        apply_patch_write_retry: turn_context.apply_patch_write_retry,
+                        user_turn_preamble: turn_context.user_turn_preamble.clone(),
+                        model_output_stream: turn_context.model_output_stream,
+                        allowed_tools,
                     };
                     // TODO: record the new environment context in the conversation history
                     // no current task, spawn a new one with the per‑turn context
@@ -1309,15 +2131,65 @@ async fn submission_loop(
                     sess.set_task(task);
                 }
             }
+            // 撤回上一轮用户输入及其后的历史，再重新执行该轮
+            Op::RetryLastTurn { model } => {
+                let retried = {
+                    let mut state = sess.state.lock_unchecked();
+                    if state.current_task.is_some() {
+                        Err("cannot retry while a task is running")
+                    } else {
+                        state
+                            .history
+                            .remove_last_user_turn()
+                            .ok_or("nothing to retry yet")
+                    }
+                };
+                let content = match retried {
+                    Ok(content) => content,
+                    Err(message) => {
+                        let event = Event {
+                            id: sub.id,
+                            msg: EventMsg::Error(ErrorEvent {
+                                message: message.to_string(),
+                            }),
+                        };
+                        if let Err(e) = sess.tx_event.send(event).await {
+                            warn!("failed to send Error event: {e}");
+                        }
+                        continue;
+                    }
+                };
+                let items = content
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        ContentItem::InputText { text } => Some(InputItem::Text { text }),
+                        ContentItem::InputImage { image_url } => {
+                            Some(InputItem::Image { image_url })
+                        }
+                        ContentItem::OutputText { .. } => None,
+                    })
+                    .collect();
+                // The model override covers this turn only.
+                // 模型覆盖仅作用于本轮重试。
+                let retry_turn_context = match model {
+                    Some(model) => Arc::new(TurnContext {
+                        client: turn_context.client.with_model(&model),
+                        ..(*turn_context).clone()
+                    }),
+                    None => Arc::clone(&turn_context),
+                };
+                let task = AgentTask::spawn(sess.clone(), retry_turn_context, sub.id, items);
+                sess.set_task(task);
+            }
             Op::ExecApproval { id, decision } => match decision {
                 ReviewDecision::Abort => {
-                    sess.interrupt_task();
+                    sess.abort_task(TurnAbortReason::ApprovalDenied);
                 }
                 other => sess.notify_approval(&id, other),
             },
             Op::PatchApproval { id, decision } => match decision {
                 ReviewDecision::Abort => {
-                    sess.interrupt_task();
+                    sess.abort_task(TurnAbortReason::ApprovalDenied);
                 }
                 other => sess.notify_approval(&id, other),
             },
@@ -1383,25 +2255,231 @@ async fn submission_loop(
                     warn!("failed to send McpListToolsResponse event: {e}");
                 }
             }
+            Op::PingMcpServers => {
+                let sess = sess.clone();
+                let sub_id = sub.id.clone();
+
+                // Each server gets a real request, so don't block the loop.
+                tokio::spawn(async move {
+                    let servers = sess.mcp_connection_manager.check_health().await;
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpServerHealthResponse(McpServerHealthResponseEvent {
+                            servers,
+                        }),
+                    };
+                    if let Err(e) = sess.tx_event.send(event).await {
+                        warn!("failed to send McpServerHealthResponse event: {e}");
+                    }
+                });
+            }
             Op::ListCustomPrompts => {
                 let tx_event = sess.tx_event.clone();
                 let sub_id = sub.id.clone();
 
                 let custom_prompts: Vec<CustomPrompt> =
-                    if let Some(dir) = crate::custom_prompts::default_prompts_dir() {
-                        crate::custom_prompts::discover_prompts_in(&dir).await
-                    } else {
-                        Vec::new()
-                    };
+                    crate::custom_prompts::discover_custom_prompts().await;
 
                 let event = Event {
-                    id: sub_id,
-                    msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
-                        custom_prompts,
+                    id: sub_id,
+                    msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
+                        custom_prompts,
+                    }),
+                };
+                if let Err(e) = tx_event.send(event).await {
+                    warn!("failed to send ListCustomPromptsResponse event: {e}");
+                }
+            }
+            Op::RunCustomPrompt { name, arguments } => {
+                let prompt = crate::custom_prompts::discover_custom_prompts()
+                    .await
+                    .into_iter()
+                    .find(|prompt| prompt.name == name);
+                let rendered = match prompt {
+                    Some(prompt) => render_prompt(&prompt.content, &arguments)
+                        .map_err(|e| format!("custom prompt `{name}`: {e}")),
+                    None => Err(format!("unknown custom prompt `{name}`")),
+                };
+                match rendered {
+                    Ok(text) => {
+                        let items = vec![InputItem::Text { text }];
+                        submit_user_input(&sess, &turn_context, &config, sub.id, items).await;
+                    }
+                    Err(message) => {
+                        let event = Event {
+                            id: sub.id.clone(),
+                            msg: EventMsg::Error(ErrorEvent { message }),
+                        };
+                        if let Err(e) = sess.tx_event.send(event).await {
+                            warn!("failed to send error event: {e}");
+                        }
+                    }
+                }
+            }
+            Op::GetConfig => {
+                let client = sess.turn_client(&turn_context);
+                let tools = turn_tools(&sess, &turn_context)
+                    .iter()
+                    .map(|tool| tool.name().to_string())
+                    .collect();
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::ResolvedConfig(ResolvedConfigEvent {
+                        model: client.get_model(),
+                        model_provider_id: config.model_provider_id.clone(),
+                        cwd: turn_context.cwd.clone(),
+                        approval_policy: turn_context.approval_policy,
+                        sandbox_policy: turn_context.sandbox_policy.clone(),
+                        reasoning_effort: client.get_reasoning_effort(),
+                        reasoning_summary: client.get_reasoning_summary(),
+                        tools,
+                    }),
+                };
+                if let Err(e) = sess.tx_event.send(event).await {
+                    warn!("failed to send ResolvedConfig event: {e}");
+                }
+            }
+            // 按需返回当前任务或整个会话的差异
+            Op::GetDiff { scope } => {
+                // Clone the tracker out so the diff is computed (which reads
+                // every tracked file) without holding the session state lock.
+                // 先取出跟踪器，计算差异（需读取文件）时不持有会话状态锁。
+                let tracker = {
+                    let state = sess.state.lock_unchecked();
+                    match scope {
+                        DiffScope::CurrentTurn => state.current_diff_tracker.clone(),
+                        DiffScope::Cumulative => state.cumulative_diff_tracker.clone(),
+                    }
+                };
+                let unified_diff = {
+                    let mut tracker = tracker.lock().await;
+                    tracker
+                        .get_unified_diff()
+                        .map(|diff| (diff, tracker.suppressed_files()))
+                };
+                let msg = match unified_diff {
+                    Ok((unified_diff, suppressed_files)) => {
+                        EventMsg::GetDiffResponse(GetDiffResponseEvent {
+                            scope,
+                            unified_diff: unified_diff.unwrap_or_default(),
+                            suppressed_files,
+                        })
+                    }
+                    Err(e) => EventMsg::Error(ErrorEvent {
+                        message: format!("failed to compute diff: {e:#}"),
+                    }),
+                };
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg,
+                };
+                if let Err(e) = sess.tx_event.send(event).await {
+                    warn!("failed to send GetDiffResponse event: {e}");
+                }
+            }
+            // 取消单个进行中的工具调用，任务继续运行
+            Op::CancelToolCall { call_id } => {
+                if !sess.cancel_tool_call(&call_id) {
+                    let event = Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::Error(ErrorEvent {
+                            message: format!("no in-flight tool call with id `{call_id}`"),
+                        }),
+                    };
+                    if let Err(e) = sess.tx_event.send(event).await {
+                        warn!("failed to send error event: {e}");
+                    }
+                }
+            }
+            // 评估命令在当前策略下的处理方式（不实际执行）
+            Op::EvaluateCommand { command, cwd } => {
+                let assessment = {
+                    let state = sess.state.lock_unchecked();
+                    assess_command_safety(
+                        &command,
+                        &turn_context.cwd.join(&cwd),
+                        turn_context.approval_policy,
+                        &turn_context.sandbox_policy,
+                        &state.approved_commands,
+                        false,
+                        &state.tracked_files,
+                    )
+                };
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::CommandEvaluation(CommandEvaluationEvent {
+                        command,
+                        cwd: turn_context.cwd.join(cwd),
+                        assessment: assessment.into(),
                     }),
                 };
-                if let Err(e) = tx_event.send(event).await {
-                    warn!("failed to send ListCustomPromptsResponse event: {e}");
+                if let Err(e) = sess.tx_event.send(event).await {
+                    warn!("failed to send CommandEvaluation event: {e}");
+                }
+            }
+            Op::AutoStash { message } => {
+                let message = message.unwrap_or_else(|| "codex auto-stash".to_string());
+                match auto_stash(&turn_context.cwd, &message).await {
+                    Ok(handle) => match handle.stash_sha() {
+                        Some(sha) => {
+                            let note = format!("stashed uncommitted changes as {}", sha.0);
+                            sess.state.lock_unchecked().auto_stashes.push(handle);
+                            sess.notify_background_event(&sub.id, note).await;
+                        }
+                        None => {
+                            sess.notify_background_event(&sub.id, "nothing to stash")
+                                .await;
+                        }
+                    },
+                    Err(e) => {
+                        sess.notify_background_warning(&sub.id, format!("auto-stash failed: {e}"))
+                            .await;
+                    }
+                }
+            }
+            Op::RestoreStash => {
+                let handle = sess.state.lock_unchecked().auto_stashes.pop();
+                match handle {
+                    Some(handle) => {
+                        if let Err(e) = restore_stash(handle.clone()).await {
+                            sess.state.lock_unchecked().auto_stashes.push(handle);
+                            sess.notify_background_warning(
+                                &sub.id,
+                                format!("failed to restore stash: {e}"),
+                            )
+                            .await;
+                        } else {
+                            sess.notify_background_event(&sub.id, "restored stashed changes")
+                                .await;
+                        }
+                    }
+                    None => {
+                        sess.notify_background_warning(&sub.id, "no auto-stash to restore")
+                            .await;
+                    }
+                }
+            }
+            Op::SaveSessionAs { path } => {
+                let path = turn_context.cwd.join(path);
+                let recorder = sess.rollout.lock_unchecked().clone();
+                match recorder {
+                    Some(recorder) => match recorder.save_as(path.clone()).await {
+                        Ok(()) => {
+                            let note = format!("session saved to {}", path.display());
+                            sess.notify_background_event(&sub.id, note).await;
+                        }
+                        Err(e) => {
+                            sess.notify_background_warning(
+                                &sub.id,
+                                format!("failed to save session to {}: {e}", path.display()),
+                            )
+                            .await;
+                        }
+                    },
+                    None => {
+                        sess.notify_background_warning(&sub.id, "session is not being recorded")
+                            .await;
+                    }
                 }
             }
             Op::Compact => {
@@ -1425,6 +2503,26 @@ async fn submission_loop(
             Op::Shutdown => {
                 info!("Shutting down Codex instance");
 
+                // Bring back any changes still stashed via `Op::AutoStash`,
+                // newest first so each pop applies on top of the older ones.
+                let auto_stashes = std::mem::take(&mut sess.state.lock_unchecked().auto_stashes);
+                for handle in auto_stashes.into_iter().rev() {
+                    let sha = handle
+                        .stash_sha()
+                        .map(|sha| sha.0.clone())
+                        .unwrap_or_default();
+                    if let Err(e) = restore_stash(handle).await {
+                        warn!("failed to restore auto-stash {sha}: {e}");
+                        sess.notify_background_warning(
+                            &sub.id,
+                            format!(
+                                "could not restore stash {sha} ({e}); recover it with `git stash apply {sha}`"
+                            ),
+                        )
+                        .await;
+                    }
+                }
+
                 // Gracefully flush and shutdown rollout recorder on session end so tests
                 // that inspect the rollout file do not race with the background writer.
                 let recorder_opt = sess.rollout.lock_unchecked().take();
@@ -1488,9 +2586,11 @@ async fn submission_loop(
 ///   back to the model in the next turn.
 /// - If the model sends only an assistant message, we record it in the
 ///   conversation history and consider the task complete.
+///
 /// 接受用户消息作为输入并运行一个循环，在每个轮次中，模型回复以下之一：
 /// - 请求的函数调用
 /// - 助手消息
+///
 /// 虽然模型可能在单个轮次中返回多个这些项，但在实践中，我们通常每轮次一个项：
 /// - 如果模型请求函数调用，我们执行它并在下一轮次将输出发送回模型。
 /// - 如果模型只发送助手消息，我们将其记录在对话历史中并认为任务完成。
@@ -1521,12 +2621,24 @@ async fn run_task(
     sess.record_conversation_items(&[initial_input_for_turn.clone().into()])
         .await;
 
+    // List git-tracked files once for the whole task; safety checks consult
+    // this list instead of running git for every command or patch.
+    // 为整个任务列出一次 git 跟踪文件，安全检查复用该列表而不是每次都运行 git。
+    let tracked_files =
+        TrackedFiles::collect(&turn_context.sandbox_policy, &turn_context.cwd).await;
+    sess.state.lock_unchecked().tracked_files = Arc::new(tracked_files);
+
     let mut last_agent_message: Option<String> = None;
+    let mut reason = TaskCompleteReason::Completed;
+    let mut produced_message = false;
+    let task_started_at = Instant::now();
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
     // 从 codex.rs 的角度来看，TurnDiffTracker 具有包含多个轮次的任务的生命周期，
     // 但从用户的角度来看，这是单个轮次。
-    let mut turn_diff_tracker = TurnDiffTracker::new();
+    let turn_diff_tracker: SharedTurnDiffTracker =
+        Arc::new(tokio::sync::Mutex::new(sess.new_diff_tracker()));
+    sess.state.lock_unchecked().current_diff_tracker = turn_diff_tracker.clone();
 
     // 主任务循环，处理与模型的多轮对话
     loop {
@@ -1550,7 +2662,10 @@ async fn run_task(
         // 构建我们将发送给模型的输入。使用聊天完成 API（或 ZDR 客户端）时，
         // 模型需要每轮的完整对话历史。但是，rollout 文件应仅记录此轮次中产生的新项，
         // 以便它表示无重复的仅追加日志。
-        let turn_input: Vec<ResponseItem> = sess.turn_input_with_history(pending_input);
+        let turn_input: Vec<ResponseItem> = with_user_turn_preamble(
+            sess.turn_input_with_history(pending_input),
+            turn_context.user_turn_preamble.as_deref(),
+        );
 
         let turn_input_messages: Vec<String> = turn_input
             .iter()
@@ -1568,7 +2683,7 @@ async fn run_task(
         match run_turn(
             &sess,
             turn_context,
-            &mut turn_diff_tracker,
+            &turn_diff_tracker,
             sub_id.clone(),
             turn_input,
         )
@@ -1580,7 +2695,14 @@ async fn run_task(
                 for processed_response_item in turn_output {
                     let ProcessedResponseItem { item, response } = processed_response_item;
                     match (&item, &response) {
-                        (ResponseItem::Message { role, .. }, None) if role == "assistant" => {
+                        (ResponseItem::Message { role, content, .. }, None)
+                            if role == "assistant" =>
+                        {
+                            // Every output text part was surfaced as an AgentMessage.
+                            // 每个输出文本片段都已作为 AgentMessage 发出。
+                            produced_message |= content
+                                .iter()
+                                .any(|c| matches!(c, ContentItem::OutputText { .. }));
                             // If the model returned a message, we need to record it.
                             items_to_record_in_conversation_history.push(item);
                         }
@@ -1686,9 +2808,24 @@ async fn run_task(
                     });
                     break;
                 }
+
+                // Tool calls from this turn have completed; do not sample the
+                // model again if the time budget has been used up.
+                // 本轮工具调用已完成；若时间预算耗尽，则不再请求模型。
+                if let Some(budget) = turn_context.max_turn_duration
+                    && task_started_at.elapsed() >= budget
+                {
+                    info!("Turn exceeded time budget of {budget:?}; stopping");
+                    reason = TaskCompleteReason::TimeBudgetExceeded;
+                    break;
+                }
             }
             Err(e) => {
                 info!("Turn error: {e:#}");
+                // Release the session before reporting, so input sent in
+                // response to these events starts a new task.
+                // 先释放会话再上报，使收到事件后发送的输入能启动新任务。
+                sess.remove_task(&sub_id);
                 let event = Event {
                     id: sub_id.clone(),
                     msg: EventMsg::Error(ErrorEvent {
@@ -1696,47 +2833,120 @@ async fn run_task(
                     }),
                 };
                 sess.tx_event.send(event).await.ok();
-                // let the user continue the conversation
-                break;
+                let event = Event {
+                    id: sub_id.clone(),
+                    msg: EventMsg::TurnAborted(TurnAbortedEvent {
+                        reason: turn_abort_reason(&e),
+                    }),
+                };
+                sess.tx_event.send(event).await.ok();
+                // The abort ends the task; no TaskComplete follows.
+                // 中止即结束任务，不再发送 TaskComplete。
+                return;
             }
         }
     }
     sess.remove_task(&sub_id);
+    let produced_changes = turn_diff_tracker.lock().await.files_changed();
     let event = Event {
         id: sub_id,
-        msg: EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message,
+            reason,
+            produced_changes,
+            produced_message,
+        }),
     };
     sess.tx_event.send(event).await.ok();
 }
 
+/// Why a turn that failed with `e` was aborted.
+fn turn_abort_reason(e: &CodexErr) -> TurnAbortReason {
+    match e {
+        CodexErr::StreamIdleTimeout => TurnAbortReason::Timeout,
+        CodexErr::Reqwest(e) if e.is_timeout() => TurnAbortReason::Timeout,
+        e => TurnAbortReason::StreamError {
+            message: e.to_string(),
+        },
+    }
+}
+
+/// Tools advertised to the model for a turn, limited to
+/// `turn_context.allowed_tools` when set.
+fn turn_tools(sess: &Session, turn_context: &TurnContext) -> Vec<OpenAiTool> {
+    let mut tools = get_openai_tools(
+        &turn_context.tools_config,
+        Some(sess.mcp_connection_manager.list_all_tools()),
+    );
+    tools.retain(|tool| turn_context.allows_tool(tool.name()));
+    tools
+}
+
 async fn run_turn(
     sess: &Session,
     turn_context: &TurnContext,
-    turn_diff_tracker: &mut TurnDiffTracker,
+    turn_diff_tracker: &SharedTurnDiffTracker,
     sub_id: String,
     input: Vec<ResponseItem>,
 ) -> CodexResult<Vec<ProcessedResponseItem>> {
-    let tools = get_openai_tools(
-        &turn_context.tools_config,
-        Some(sess.mcp_connection_manager.list_all_tools()),
-    );
+    let tools = turn_tools(sess, turn_context);
 
     let prompt = Prompt {
         input,
         store: !turn_context.disable_response_storage,
         tools,
         base_instructions_override: turn_context.base_instructions.clone(),
+        max_context_turns: sess.max_context_turns,
     };
 
+    let mut client = sess.turn_client(turn_context);
     let mut retries = 0;
     loop {
-        match try_run_turn(sess, turn_context, turn_diff_tracker, &sub_id, &prompt).await {
+        match try_run_turn(
+            sess,
+            turn_context,
+            &client,
+            turn_diff_tracker,
+            &sub_id,
+            &prompt,
+        )
+        .await
+        {
             Ok(output) => return Ok(output),
             Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
-            Err(e @ (CodexErr::UsageLimitReached(_) | CodexErr::UsageNotIncluded)) => {
+            Err(
+                e @ (CodexErr::UsageLimitReached(_)
+                | CodexErr::UsageNotIncluded
+                | CodexErr::MockScript(_)),
+            ) => {
                 return Err(e);
             }
+            Err(e @ CodexErr::CircuitOpen { .. }) => {
+                sess.notify_background_event(&sub_id, e.to_string()).await;
+                return Err(e);
+            }
+            Err(e) if e.is_model_unavailable() => {
+                let Some(fallback) = sess.fall_back_from(turn_context, &client) else {
+                    return Err(e);
+                };
+                warn!(
+                    "model {} unavailable; falling back to {}",
+                    client.get_model(),
+                    fallback.get_model()
+                );
+                sess.notify_background_event(
+                    &sub_id,
+                    format!(
+                        "model `{}` is unavailable ({e}); falling back to `{}` for this session",
+                        client.get_model(),
+                        fallback.get_model()
+                    ),
+                )
+                .await;
+                client = fallback;
+                retries = 0;
+            }
             Err(e) => {
                 // Use the configured provider-specific stream retry budget.
                 let max_retries = turn_context.client.get_provider().stream_max_retries();
@@ -1783,7 +2993,8 @@ struct ProcessedResponseItem {
 async fn try_run_turn(
     sess: &Session,
     turn_context: &TurnContext,
-    turn_diff_tracker: &mut TurnDiffTracker,
+    client: &ModelClient,
+    turn_diff_tracker: &SharedTurnDiffTracker,
     sub_id: &str,
     prompt: &Prompt,
 ) -> CodexResult<Vec<ProcessedResponseItem>> {
@@ -1842,7 +3053,7 @@ async fn try_run_turn(
         })
     };
 
-    let mut stream = turn_context.client.clone().stream(&prompt).await?;
+    let mut stream = client.clone().stream(&prompt).await?;
 
     let mut output = Vec::new();
 
@@ -1891,6 +3102,15 @@ async fn try_run_turn(
                     })
                     .await;
             }
+            ResponseEvent::PartialTokenUsage(token_usage) => {
+                sess.tx_event
+                    .send(Event {
+                        id: sub_id.to_string(),
+                        msg: EventMsg::PartialTokenCount(token_usage),
+                    })
+                    .await
+                    .ok();
+            }
             ResponseEvent::Completed {
                 response_id: _,
                 token_usage,
@@ -1905,9 +3125,30 @@ async fn try_run_turn(
                         .ok();
                 }
 
-                let unified_diff = turn_diff_tracker.get_unified_diff();
+                if client.reports_turn_timing()
+                    && let Some(timing) = stream.timing()
+                    && let Some(total_duration) = timing.total_duration()
+                {
+                    let msg = EventMsg::TurnTiming(TurnTimingEvent {
+                        time_to_first_token: timing.time_to_first_token(),
+                        total_duration,
+                    });
+                    let event = Event {
+                        id: sub_id.to_string(),
+                        msg,
+                    };
+                    let _ = sess.tx_event.send(event).await;
+                }
+
+                let (unified_diff, suppressed_files) = {
+                    let mut tracker = turn_diff_tracker.lock().await;
+                    (tracker.get_unified_diff(), tracker.suppressed_files())
+                };
                 if let Ok(Some(unified_diff)) = unified_diff {
-                    let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
+                    let msg = EventMsg::TurnDiff(TurnDiffEvent {
+                        unified_diff,
+                        suppressed_files,
+                    });
                     let event = Event {
                         id: sub_id.to_string(),
                         msg,
@@ -1924,6 +3165,10 @@ async fn try_run_turn(
                 };
                 sess.tx_event.send(event).await.ok();
             }
+            ResponseEvent::ReasoningSummaryDelta(_)
+            | ResponseEvent::ReasoningSummaryPartAdded
+            | ResponseEvent::ReasoningContentDelta(_)
+                if turn_context.disable_reasoning => {}
             ResponseEvent::ReasoningSummaryDelta(delta) => {
                 let event = Event {
                     id: sub_id.to_string(),
@@ -1939,7 +3184,7 @@ async fn try_run_turn(
                 sess.tx_event.send(event).await.ok();
             }
             ResponseEvent::ReasoningContentDelta(delta) => {
-                if sess.show_raw_agent_reasoning {
+                if turn_context.include_raw_reasoning {
                     let event = Event {
                         id: sub_id.to_string(),
                         msg: EventMsg::AgentReasoningRawContentDelta(
@@ -1953,6 +3198,31 @@ async fn try_run_turn(
     }
 }
 
+/// Prepends `preamble` to the most recent user message. History never holds
+/// the preamble, so every request carries it exactly once.
+/// 将 preamble 添加到最新的用户消息之前；历史中不保存它，因此每个请求只包含一次。
+fn with_user_turn_preamble(
+    mut input: Vec<ResponseItem>,
+    preamble: Option<&str>,
+) -> Vec<ResponseItem> {
+    let Some(preamble) = preamble else {
+        return input;
+    };
+    let last_user_message = input.iter_mut().rev().find_map(|item| match item {
+        ResponseItem::Message { role, content, .. } if role == "user" => Some(content),
+        _ => None,
+    });
+    if let Some(content) = last_user_message {
+        content.insert(
+            0,
+            ContentItem::InputText {
+                text: preamble.to_string(),
+            },
+        );
+    }
+    input
+}
+
 async fn run_compact_task(
     sess: Arc<Session>,
     turn_context: &TurnContext,
@@ -1980,16 +3250,17 @@ async fn run_compact_task(
         store: !turn_context.disable_response_storage,
         tools: Vec::new(),
         base_instructions_override: Some(compact_instructions.clone()),
+        max_context_turns: None,
     };
 
     let max_retries = turn_context.client.get_provider().stream_max_retries();
     let mut retries = 0;
 
-    loop {
+    let token_usage = loop {
         let attempt_result = drain_to_completed(&sess, turn_context, &sub_id, &prompt).await;
 
         match attempt_result {
-            Ok(()) => break,
+            Ok(token_usage) => break token_usage,
             Err(CodexErr::Interrupted) => return,
             Err(e) => {
                 if retries < max_retries {
@@ -2016,15 +3287,44 @@ async fn run_compact_task(
                 }
             }
         }
-    }
+    };
 
     sess.remove_task(&sub_id);
 
-    {
+    let summary = {
         let mut state = sess.state.lock_unchecked();
         state.history.keep_last_messages(1);
-    }
+        state
+            .history
+            .contents()
+            .iter()
+            .map(|item| match item {
+                ResponseItem::Message { content, .. } => content
+                    .iter()
+                    .filter_map(|c| match c {
+                        ContentItem::OutputText { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<String>(),
+                _ => String::new(),
+            })
+            .collect::<String>()
+    };
+
+    // The size of the compacted context is only known once the next request
+    // reports its usage.
+    // 压缩后上下文的大小要等下一次请求报告用量后才能得知。
+    let event = Event {
+        id: sub_id.clone(),
+        msg: EventMsg::CompactComplete(CompactCompleteEvent {
+            token_usage_before: token_usage,
+            token_usage_after: None,
+            summary,
+        }),
+    };
+    sess.send_event(event).await;
 
+    // Older clients only look for this message.
     let event = Event {
         id: sub_id.clone(),
         msg: EventMsg::AgentMessage(AgentMessageEvent {
@@ -2036,6 +3336,9 @@ async fn run_compact_task(
         id: sub_id.clone(),
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            reason: TaskCompleteReason::Completed,
+            produced_changes: false,
+            produced_message: true,
         }),
     };
     sess.send_event(event).await;
@@ -2044,7 +3347,7 @@ async fn run_compact_task(
 async fn handle_response_item(
     sess: &Session,
     turn_context: &TurnContext,
-    turn_diff_tracker: &mut TurnDiffTracker,
+    turn_diff_tracker: &SharedTurnDiffTracker,
     sub_id: &str,
     item: ResponseItem,
 ) -> CodexResult<Option<ResponseInputItem>> {
@@ -2062,6 +3365,7 @@ async fn handle_response_item(
             }
             None
         }
+        ResponseItem::Reasoning { .. } if turn_context.disable_reasoning => None,
         ResponseItem::Reasoning {
             id: _,
             summary,
@@ -2078,7 +3382,7 @@ async fn handle_response_item(
                 };
                 sess.tx_event.send(event).await.ok();
             }
-            if sess.show_raw_agent_reasoning
+            if turn_context.include_raw_reasoning
                 && let Some(content) = content
             {
                 for item in content {
@@ -2097,6 +3401,23 @@ async fn handle_response_item(
             }
             None
         }
+        // With `stub_tool_calls`, results come from `inject_tool_result`.
+        // 启用 `stub_tool_calls` 时，结果来自 `inject_tool_result`。
+        ResponseItem::FunctionCall { call_id, .. }
+        | ResponseItem::LocalShellCall {
+            call_id: Some(call_id),
+            ..
+        } if sess.stub_tool_calls => {
+            let output = sess.stubbed_tool_result(&call_id).await;
+            Some(ResponseInputItem::FunctionCallOutput { call_id, output })
+        }
+        ResponseItem::CustomToolCall { call_id, .. } if sess.stub_tool_calls => {
+            let output = sess.stubbed_tool_result(&call_id).await;
+            Some(ResponseInputItem::CustomToolCallOutput {
+                call_id,
+                output: output.content,
+            })
+        }
         ResponseItem::FunctionCall {
             name,
             arguments,
@@ -2125,6 +3446,15 @@ async fn handle_response_item(
         } => {
             let LocalShellAction::Exec(action) = action;
             tracing::info!("LocalShellCall: {action:?}");
+            if !turn_context.allows_tool("local_shell") {
+                return Ok(Some(ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id.or(id).unwrap_or_default(),
+                    output: FunctionCallOutputPayload {
+                        content: tool_not_allowed("local_shell"),
+                        success: Some(false),
+                    },
+                }));
+            }
             let params = ShellToolCallParams {
                 command: action.command,
                 workdir: action.working_directory,
@@ -2187,11 +3517,15 @@ async fn handle_response_item(
             None
         }
         ResponseItem::WebSearchCall { id, action, .. } => {
-            if let WebSearchAction::Search { query } = action {
+            if let WebSearchAction::Search { query, sources } = action {
                 let call_id = id.unwrap_or_else(|| "".to_string());
                 let event = Event {
                     id: sub_id.to_string(),
-                    msg: EventMsg::WebSearchEnd(WebSearchEndEvent { call_id, query }),
+                    msg: EventMsg::WebSearchEnd(WebSearchEndEvent {
+                        call_id,
+                        query,
+                        results: sources,
+                    }),
                 };
                 sess.tx_event.send(event).await.ok();
             }
@@ -2204,14 +3538,36 @@ async fn handle_response_item(
 
 /// 处理模型请求的函数调用，根据函数名分发到对应的处理器
 async fn handle_function_call(
-    sess: &Session,                          // 会话实例
-    turn_context: &TurnContext,              // 轮次上下文
-    turn_diff_tracker: &mut TurnDiffTracker, // 差异跟踪器
-    sub_id: String,                          // 提交ID
-    name: String,                            // 函数名
-    arguments: String,                       // 函数参数（JSON字符串）
-    call_id: String,                         // 调用ID
+    sess: &Session,                            // 会话实例
+    turn_context: &TurnContext,                // 轮次上下文
+    turn_diff_tracker: &SharedTurnDiffTracker, // 差异跟踪器
+    sub_id: String,                            // 提交ID
+    name: String,                              // 函数名
+    arguments: String,                         // 函数参数（JSON字符串）
+    call_id: String,                           // 调用ID
 ) -> ResponseInputItem {
+    // 拒绝 `allowed_tools` 之外的工具调用
+    if !turn_context.allows_tool(&name) {
+        return ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: tool_not_allowed(&name),
+                success: Some(false),
+            },
+        };
+    }
+    // 参数不是合法 JSON 时告知模型，让其重新发起调用，而不是重试整个请求
+    if !arguments.trim().is_empty()
+        && let Err(e) = serde_json::from_str::<serde_json::Value>(&arguments)
+    {
+        return ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: format!("malformed arguments for tool call `{name}`: {e}"),
+                success: Some(false),
+            },
+        };
+    }
     // 根据函数名匹配并执行相应的处理逻辑
     match name.as_str() {
         // 处理容器执行或shell命令
@@ -2283,6 +3639,7 @@ async fn handle_function_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                stdin: None,
             };
             handle_container_exec_with_params(
                 exec_params,
@@ -2311,7 +3668,7 @@ async fn handle_function_call(
             };
             let result = sess
                 .session_manager
-                .handle_exec_command_request(exec_params)
+                .handle_exec_command_request(exec_params, sess.exec_shell)
                 .await;
             let function_call_output = crate::exec_command::result_into_payload(result);
             ResponseInputItem::FunctionCallOutput {
@@ -2371,13 +3728,19 @@ async fn handle_function_call(
 async fn handle_custom_tool_call(
     sess: &Session,
     turn_context: &TurnContext,
-    turn_diff_tracker: &mut TurnDiffTracker,
+    turn_diff_tracker: &SharedTurnDiffTracker,
     sub_id: String,
     name: String,
     input: String,
     call_id: String,
 ) -> ResponseInputItem {
     info!("CustomToolCall: {name} {input}");
+    if !turn_context.allows_tool(&name) {
+        return ResponseInputItem::CustomToolCallOutput {
+            call_id,
+            output: tool_not_allowed(&name),
+        };
+    }
     match name.as_str() {
         "apply_patch" => {
             let exec_params = ExecParams {
@@ -2387,6 +3750,7 @@ async fn handle_custom_tool_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                stdin: None,
             };
             let resp = handle_container_exec_with_params(
                 exec_params,
@@ -2425,9 +3789,10 @@ fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> Ex
         command: params.command,
         cwd: turn_context.resolve_path(params.workdir.clone()),
         timeout_ms: params.timeout_ms,
-        env: create_env(&turn_context.shell_environment_policy),
+        env: create_command_env(&turn_context.shell_environment_policy, &turn_context.cwd),
         with_escalated_permissions: params.with_escalated_permissions,
         justification: params.justification,
+        stdin: None,
     }
 }
 
@@ -2483,7 +3848,7 @@ async fn handle_container_exec_with_params(
     params: ExecParams,
     sess: &Session,
     turn_context: &TurnContext,
-    turn_diff_tracker: &mut TurnDiffTracker,
+    turn_diff_tracker: &SharedTurnDiffTracker,
     sub_id: String,
     call_id: String,
 ) -> ResponseInputItem {
@@ -2498,6 +3863,38 @@ async fn handle_container_exec_with_params(
             }
         }
         MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
+            // When the failure is tied to a particular file (a stale patch, a
+            // missing file), report it to clients like any failed patch so
+            // they can show what failed and why.
+            // 若失败与具体文件相关（补丁过期、文件缺失），像其他失败的补丁一样通知客户端。
+            let conflicts = convert_conflicts_to_protocol(parse_error.conflict());
+            if !conflicts.is_empty() {
+                sess.send_event(Event {
+                    id: sub_id.clone(),
+                    msg: EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+                        call_id: call_id.clone(),
+                        auto_approved: true,
+                        changes: apply_patch::unverified_patch_changes(
+                            &params.command,
+                            &params.cwd,
+                        ),
+                        dry_run: turn_context.apply_patch_dry_run,
+                    }),
+                })
+                .await;
+                sess.send_event(Event {
+                    id: sub_id.clone(),
+                    msg: EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+                        call_id: call_id.clone(),
+                        stdout: String::new(),
+                        stderr: format!("{parse_error:#}"),
+                        success: false,
+                        dry_run: turn_context.apply_patch_dry_run,
+                        conflicts,
+                    }),
+                })
+                .await;
+            }
             // It looks like an invocation of `apply_patch`, but we
             // could not resolve it into a patch that would apply
             // cleanly. Return to model for resample.
@@ -2521,30 +3918,48 @@ async fn handle_container_exec_with_params(
             action: ApplyPatchAction { patch, cwd, .. },
             user_explicitly_approved_this_action,
         }) => {
-            let path_to_codex = std::env::current_exe()
-                .ok()
-                .map(|p| p.to_string_lossy().to_string());
-            let Some(path_to_codex) = path_to_codex else {
-                return ResponseInputItem::FunctionCallOutput {
-                    call_id,
-                    output: FunctionCallOutputPayload {
-                        content: "failed to determine path to codex executable".to_string(),
-                        success: None,
-                    },
-                };
+            // An external applier reads the patch from stdin.
+            // 外部补丁工具从 stdin 读取补丁。
+            let (command, stdin, env) = match &turn_context.apply_patch_command {
+                Some(apply_patch_command) => (
+                    apply_patch_command.clone(),
+                    Some(patch.clone()),
+                    HashMap::new(),
+                ),
+                None => {
+                    let path_to_codex = std::env::current_exe()
+                        .ok()
+                        .map(|p| p.to_string_lossy().to_string());
+                    let Some(path_to_codex) = path_to_codex else {
+                        return ResponseInputItem::FunctionCallOutput {
+                            call_id,
+                            output: FunctionCallOutputPayload {
+                                content: "failed to determine path to codex executable".to_string(),
+                                success: None,
+                            },
+                        };
+                    };
+                    let command = vec![
+                        path_to_codex,
+                        CODEX_APPLY_PATCH_ARG1.to_string(),
+                        patch.clone(),
+                    ];
+                    let env = HashMap::from([(
+                        CODEX_APPLY_PATCH_WRITE_RETRY_ENV_VAR.to_string(),
+                        turn_context.apply_patch_write_retry.to_env_value(),
+                    )]);
+                    (command, None, env)
+                }
             };
 
             let params = ExecParams {
-                command: vec![
-                    path_to_codex,
-                    CODEX_APPLY_PATCH_ARG1.to_string(),
-                    patch.clone(),
-                ],
+                command,
                 cwd: cwd.clone(),
                 timeout_ms: params.timeout_ms,
-                env: HashMap::new(),
+                env,
                 with_escalated_permissions: params.with_escalated_permissions,
                 justification: params.justification.clone(),
+                stdin,
             };
             let safety = if *user_explicitly_approved_this_action {
                 SafetyCheck::AutoApprove {
@@ -2568,10 +3983,12 @@ async fn handle_container_exec_with_params(
                 let state = sess.state.lock_unchecked();
                 assess_command_safety(
                     &params.command,
+                    &params.cwd,
                     turn_context.approval_policy,
                     &turn_context.sandbox_policy,
                     &state.approved_commands,
                     params.with_escalated_permissions.unwrap_or(false),
+                    &state.tracked_files,
                 )
             };
             let command_for_display = params.command.clone();
@@ -2579,20 +3996,65 @@ async fn handle_container_exec_with_params(
         }
     };
 
-    let sandbox_type = match safety {
-        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+    let (sandbox_type, unsandboxed_reason) = match safety {
+        SafetyCheck::AutoApprove {
+            sandbox_type: SandboxType::None,
+        } => {
+            let reason = match &apply_patch_exec {
+                Some(ApplyPatchExec {
+                    user_explicitly_approved_this_action: true,
+                    ..
+                }) => UnsandboxedReason::UserApproved,
+                None if sess
+                    .state
+                    .lock_unchecked()
+                    .approved_commands
+                    .contains(&params.command) =>
+                {
+                    UnsandboxedReason::ApprovedForSession
+                }
+                None if is_known_safe_command(&params.command) => {
+                    UnsandboxedReason::KnownSafeCommand
+                }
+                _ if turn_context.sandbox_policy == SandboxPolicy::DangerFullAccess => {
+                    UnsandboxedReason::DangerFullAccess
+                }
+                _ => UnsandboxedReason::NoSandboxAvailable,
+            };
+            (SandboxType::None, Some(reason))
+        }
+        SafetyCheck::AutoApprove { sandbox_type } => (sandbox_type, None),
         SafetyCheck::AskUser => {
+            // Tell the user which files a heredoc would write outside the
+            // sandbox, when that is why approval is needed.
+            // 若因 heredoc 写入沙箱外文件而需审批，则告知用户目标文件。
+            let reason = params.justification.clone().or_else(|| {
+                let paths = heredoc_writes_outside_writable_roots(
+                    &params.command,
+                    &params.cwd,
+                    &turn_context.sandbox_policy,
+                    &sess.tracked_files(),
+                );
+                (!paths.is_empty()).then(|| {
+                    let paths: Vec<String> =
+                        paths.iter().map(|p| p.display().to_string()).collect();
+                    format!(
+                        "writes {} from a heredoc, outside the writable roots",
+                        paths.join(", ")
+                    )
+                })
+            });
             let rx_approve = sess
                 .request_command_approval(
                     sub_id.clone(),
                     call_id.clone(),
                     params.command.clone(),
                     params.cwd.clone(),
-                    params.justification.clone(),
+                    reason,
                 )
                 .await;
             match rx_approve.await.unwrap_or_default() {
-                ReviewDecision::Approved => (),
+                ReviewDecision::Approved | ReviewDecision::ApprovedPathsForSession => (),
                 ReviewDecision::ApprovedForSession => {
                     sess.add_approved_command(params.command.clone());
                 }
@@ -2610,7 +4072,7 @@ async fn handle_container_exec_with_params(
             // explicit approval. Often, we end up in this case because
             // the command cannot be run in a sandbox, such as
             // installing a new dependency that requires network access.
-            SandboxType::None
+            (SandboxType::None, Some(UnsandboxedReason::UserApproved))
         }
         SafetyCheck::Reject { reason } => {
             return ResponseInputItem::FunctionCallOutput {
@@ -2640,6 +4102,9 @@ async fn handle_container_exec_with_params(
     };
 
     let params = maybe_translate_shell_command(params, sess, turn_context);
+    if let Some(reason) = unsandboxed_reason {
+        sess.audit_unsandboxed_exec(&params, reason).await;
+    }
     let output_result = sess
         .run_exec_with_events(
             turn_diff_tracker,
@@ -2649,15 +4114,16 @@ async fn handle_container_exec_with_params(
                 sandbox_type,
                 sandbox_policy: &turn_context.sandbox_policy,
                 codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
-                stdout_stream: if exec_command_context.apply_patch.is_some() {
-                    None
-                } else {
-                    Some(StdoutStream {
-                        sub_id: sub_id.clone(),
-                        call_id: call_id.clone(),
-                        tx_event: sess.tx_event.clone(),
-                    })
-                },
+                stdout_stream: Some(StdoutStream {
+                    sub_id: sub_id.clone(),
+                    call_id: call_id.clone(),
+                    tx_event: sess.tx_event.clone(),
+                    max_deltas_per_second: turn_context.max_exec_output_deltas_per_second,
+                    apply_patch_cwd: exec_command_context
+                        .apply_patch
+                        .is_some()
+                        .then(|| exec_command_context.cwd.clone()),
+                }),
             },
         )
         .await;
@@ -2667,7 +4133,7 @@ async fn handle_container_exec_with_params(
             let ExecToolCallOutput { exit_code, .. } = &output;
 
             let is_success = *exit_code == 0;
-            let content = format_exec_output(&output);
+            let content = format_exec_output(&output, turn_context.model_output_stream);
             ResponseInputItem::FunctionCallOutput {
                 call_id: call_id.clone(),
                 output: FunctionCallOutputPayload {
@@ -2676,6 +4142,16 @@ async fn handle_container_exec_with_params(
                 },
             }
         }
+        Err(CodexErr::ExecTimeout(timeout)) => ResponseInputItem::FunctionCallOutput {
+            call_id: call_id.clone(),
+            output: FunctionCallOutputPayload {
+                content: format!(
+                    "command timed out after {} milliseconds",
+                    timeout.as_millis()
+                ),
+                success: Some(false),
+            },
+        },
         Err(CodexErr::Sandbox(error)) => {
             handle_sandbox_error(
                 turn_diff_tracker,
@@ -2699,7 +4175,7 @@ async fn handle_container_exec_with_params(
 }
 
 async fn handle_sandbox_error(
-    turn_diff_tracker: &mut TurnDiffTracker,
+    turn_diff_tracker: &SharedTurnDiffTracker,
     params: ExecParams,
     exec_command_context: ExecCommandContext,
     error: SandboxErr,
@@ -2712,8 +4188,20 @@ async fn handle_sandbox_error(
     let cwd = exec_command_context.cwd.clone();
 
     // Early out if either the user never wants to be asked for approval, or
-    // we're letting the model manage escalation requests. Otherwise, continue
+    // we're letting the model manage escalation requests, or the user opted
+    // out of unsandboxed retries under `OnFailure`. Otherwise, continue
     match turn_context.approval_policy {
+        AskForApproval::OnFailure if !turn_context.allow_unsandboxed_retry => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: format!(
+                        "failed in sandbox {sandbox_type:?} with execution error: {error}"
+                    ),
+                    success: Some(false),
+                },
+            };
+        }
         AskForApproval::Never | AskForApproval::OnRequest => {
             return ResponseInputItem::FunctionCallOutput {
                 call_id,
@@ -2728,20 +4216,6 @@ async fn handle_sandbox_error(
         AskForApproval::UnlessTrusted | AskForApproval::OnFailure => (),
     }
 
-    // similarly, if the command timed out, we can simply return this failure to the model
-    if matches!(error, SandboxErr::Timeout) {
-        return ResponseInputItem::FunctionCallOutput {
-            call_id,
-            output: FunctionCallOutputPayload {
-                content: format!(
-                    "command timed out after {} milliseconds",
-                    params.timeout_duration().as_millis()
-                ),
-                success: Some(false),
-            },
-        };
-    }
-
     // Note that when `error` is `SandboxErr::Denied`, it could be a false
     // positive. That is, it may have exited with a non-zero exit code, not
     // because the sandbox denied it, but because that is its expected behavior,
@@ -2765,7 +4239,9 @@ async fn handle_sandbox_error(
         .await;
 
     match rx_approve.await.unwrap_or_default() {
-        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+        ReviewDecision::Approved
+        | ReviewDecision::ApprovedForSession
+        | ReviewDecision::ApprovedPathsForSession => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future
             // executions skip the sandbox directly.
@@ -2777,6 +4253,8 @@ async fn handle_sandbox_error(
 
             // This is an escalated retry; the policy will not be
             // examined and the sandbox has been set to `None`.
+            sess.audit_unsandboxed_exec(&params, UnsandboxedReason::RetryApproved)
+                .await;
             let retry_output_result = sess
                 .run_exec_with_events(
                     turn_diff_tracker,
@@ -2786,15 +4264,16 @@ async fn handle_sandbox_error(
                         sandbox_type: SandboxType::None,
                         sandbox_policy: &turn_context.sandbox_policy,
                         codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
-                        stdout_stream: if exec_command_context.apply_patch.is_some() {
-                            None
-                        } else {
-                            Some(StdoutStream {
-                                sub_id: sub_id.clone(),
-                                call_id: call_id.clone(),
-                                tx_event: sess.tx_event.clone(),
-                            })
-                        },
+                        stdout_stream: Some(StdoutStream {
+                            sub_id: sub_id.clone(),
+                            call_id: call_id.clone(),
+                            tx_event: sess.tx_event.clone(),
+                            max_deltas_per_second: turn_context.max_exec_output_deltas_per_second,
+                            apply_patch_cwd: exec_command_context
+                                .apply_patch
+                                .is_some()
+                                .then(|| exec_command_context.cwd.clone()),
+                        }),
                     },
                 )
                 .await;
@@ -2804,7 +4283,8 @@ async fn handle_sandbox_error(
                     let ExecToolCallOutput { exit_code, .. } = &retry_output;
 
                     let is_success = *exit_code == 0;
-                    let content = format_exec_output(&retry_output);
+                    let content =
+                        format_exec_output(&retry_output, turn_context.model_output_stream);
 
                     ResponseInputItem::FunctionCallOutput {
                         call_id: call_id.clone(),
@@ -2842,12 +4322,15 @@ fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
         aggregated_output, ..
     } = exec_output;
 
+    truncate_for_model(&aggregated_output.text)
+}
+
+fn truncate_for_model(s: &str) -> String {
     // Head+tail truncation for the model: show the beginning and end with an elision.
     // Clients still receive full streams; only this formatted summary is capped.
     // 为模型进行头尾截断：显示开头和结尾，中间用省略号。
     // 客户端仍然接收完整流；只有这个格式化摘要被限制。
 
-    let s = aggregated_output.text.as_str();
     let total_lines = s.lines().count();
     // 如果输出在限制范围内，直接返回原文本
     if s.len() <= MODEL_FORMAT_MAX_BYTES && total_lines <= MODEL_FORMAT_MAX_LINES {
@@ -2943,10 +4426,17 @@ fn take_last_bytes_at_char_boundary(s: &str, maxb: usize) -> &str {
     &s[start..]
 }
 
-/// Exec output is a pre-serialized JSON payload
-fn format_exec_output(exec_output: &ExecToolCallOutput) -> String {
+/// Exec output is a pre-serialized JSON payload. With
+/// [`ModelOutputStream::Separate`] stdout and stderr are reported (and
+/// truncated) individually instead of as one `output` field.
+fn format_exec_output(
+    exec_output: &ExecToolCallOutput,
+    model_output_stream: ModelOutputStream,
+) -> String {
     let ExecToolCallOutput {
         exit_code,
+        stdout,
+        stderr,
         duration,
         ..
     } = exec_output;
@@ -2958,26 +4448,39 @@ fn format_exec_output(exec_output: &ExecToolCallOutput) -> String {
     }
 
     #[derive(Serialize)]
-    struct ExecOutput<'a> {
-        output: &'a str,
+    struct ExecOutput {
+        output: String,
+        metadata: ExecMetadata,
+    }
+
+    #[derive(Serialize)]
+    struct SeparateExecOutput {
+        stdout: String,
+        stderr: String,
         metadata: ExecMetadata,
     }
 
     // round to 1 decimal place
     let duration_seconds = ((duration.as_secs_f32()) * 10.0).round() / 10.0;
+    let metadata = ExecMetadata {
+        exit_code: *exit_code,
+        duration_seconds,
+    };
 
-    let formatted_output = format_exec_output_str(exec_output);
-
-    let payload = ExecOutput {
-        output: &formatted_output,
-        metadata: ExecMetadata {
-            exit_code: *exit_code,
-            duration_seconds,
-        },
+    let serialized = match model_output_stream {
+        ModelOutputStream::Combined => serde_json::to_string(&ExecOutput {
+            output: format_exec_output_str(exec_output),
+            metadata,
+        }),
+        ModelOutputStream::Separate => serde_json::to_string(&SeparateExecOutput {
+            stdout: truncate_for_model(&stdout.text),
+            stderr: truncate_for_model(&stderr.text),
+            metadata,
+        }),
     };
 
     #[expect(clippy::expect_used)]
-    serde_json::to_string(&payload).expect("serialize ExecOutput")
+    serialized.expect("serialize ExecOutput")
 }
 
 fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
@@ -3000,13 +4503,15 @@ fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<St
     })
 }
 
+/// Stream `prompt` to completion, recording output items into the history.
+/// Returns the token usage of the response.
 async fn drain_to_completed(
     sess: &Session,
     turn_context: &TurnContext,
     sub_id: &str,
     prompt: &Prompt,
-) -> CodexResult<()> {
-    let mut stream = turn_context.client.clone().stream(prompt).await?;
+) -> CodexResult<TokenUsage> {
+    let mut stream = sess.turn_client(turn_context).stream(prompt).await?;
     loop {
         let maybe_event = stream.next().await;
         let Some(event) = maybe_event else {
@@ -3031,12 +4536,12 @@ async fn drain_to_completed(
                 sess.tx_event
                     .send(Event {
                         id: sub_id.to_string(),
-                        msg: EventMsg::TokenCount(token_usage),
+                        msg: EventMsg::TokenCount(token_usage.clone()),
                     })
                     .await
                     .ok();
 
-                return Ok(());
+                return Ok(token_usage);
             }
             Ok(_) => continue,
             Err(e) => return Err(e),
@@ -3133,6 +4638,7 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new(full.clone()),
             duration: StdDuration::from_secs(1),
+            peak_rss_bytes: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -3161,6 +4667,50 @@ mod tests {
         assert!(tail.ends_with(&expected_tail), "tail mismatch");
     }
 
+    fn exec_output_with_both_streams() -> ExecToolCallOutput {
+        ExecToolCallOutput {
+            exit_code: 1,
+            stdout: StreamOutput::new("out1\nout2\n".to_string()),
+            stderr: StreamOutput::new("err1\n".to_string()),
+            aggregated_output: StreamOutput::new("out1\nerr1\nout2\n".to_string()),
+            duration: StdDuration::from_millis(1230),
+            peak_rss_bytes: None,
+        }
+    }
+
+    #[test]
+    fn combined_model_output_stream_interleaves_output() {
+        let out = format_exec_output(
+            &exec_output_with_both_streams(),
+            ModelOutputStream::Combined,
+        );
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "output": "out1\nerr1\nout2\n",
+                "metadata": { "exit_code": 1, "duration_seconds": 1.2 },
+            })
+        );
+    }
+
+    #[test]
+    fn separate_model_output_stream_splits_stdout_and_stderr() {
+        let out = format_exec_output(
+            &exec_output_with_both_streams(),
+            ModelOutputStream::Separate,
+        );
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "stdout": "out1\nout2\n",
+                "stderr": "err1\n",
+                "metadata": { "exit_code": 1, "duration_seconds": 1.2 },
+            })
+        );
+    }
+
     #[test]
     fn model_truncation_respects_byte_budget() {
         // Construct a large output (about 100kB) so byte budget dominates
@@ -3175,6 +4725,7 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new(full.clone()),
             duration: StdDuration::from_secs(1),
+            peak_rss_bytes: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -3248,4 +4799,19 @@ mod tests {
 
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn only_model_request_timeouts_abort_as_timeout() {
+        assert_eq!(
+            turn_abort_reason(&CodexErr::StreamIdleTimeout),
+            TurnAbortReason::Timeout
+        );
+        // A child process timeout is not a model request timeout.
+        assert_eq!(
+            turn_abort_reason(&CodexErr::Timeout),
+            TurnAbortReason::StreamError {
+                message: CodexErr::Timeout.to_string()
+            }
+        );
+    }
 }
@@ -1,18 +1,88 @@
 use crate::codex::Codex;
+use crate::config::Config;
+use crate::config_snapshot::ResolvedConfigSnapshot;
 use crate::error::Result as CodexResult;
 use crate::protocol::Event;
+use crate::protocol::EventMsg;
 use crate::protocol::Op;
 use crate::protocol::Submission;
+use crate::protocol::TokenUsage;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseItem;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Number of events held back while delivery is paused. Beyond it the oldest
+/// events are dropped, streaming deltas first.
+const PAUSED_EVENT_BUFFER_CAPACITY: usize = 1024;
 
 pub struct CodexConversation {
     codex: Codex,
+    config_snapshot: ResolvedConfigSnapshot,
+    events_paused: watch::Sender<bool>,
+    /// Events taken off the stream while paused, delivered before any newer
+    /// event once resumed.
+    paused_events: Arc<Mutex<PausedEvents>>,
+    /// Task moving events into `paused_events` during the current pause.
+    pause_drain: Mutex<Option<JoinHandle<()>>>,
+    /// Usage from the latest `TokenCount` event delivered by `next_event`.
+    token_usage: watch::Sender<Option<TokenUsage>>,
 }
 
 /// Conduit for the bidirectional stream of messages that compose a conversation
 /// in Codex.
 impl CodexConversation {
-    pub(crate) fn new(codex: Codex) -> Self {
-        Self { codex }
+    pub(crate) fn new(codex: Codex, config_snapshot: ResolvedConfigSnapshot) -> Self {
+        Self {
+            codex,
+            config_snapshot,
+            events_paused: watch::Sender::new(false),
+            paused_events: Arc::new(Mutex::new(PausedEvents::new(PAUSED_EVENT_BUFFER_CAPACITY))),
+            pause_drain: Mutex::new(None),
+            token_usage: watch::Sender::new(None),
+        }
+    }
+
+    /// The resolved configuration this conversation was spawned with.
+    pub fn config_snapshot(&self) -> &ResolvedConfigSnapshot {
+        &self.config_snapshot
+    }
+
+    /// The token usage most recently reported to the consumer of this
+    /// conversation's events, or `None` before the first report.
+    pub fn token_usage(&self) -> Option<TokenUsage> {
+        self.token_usage.borrow().clone()
+    }
+
+    /// The history recorded so far, read directly from the session.
+    pub(crate) fn history(&self) -> CodexResult<Vec<ResponseItem>> {
+        self.codex.history()
+    }
+
+    /// The full config with the overrides applied since spawn.
+    pub(crate) fn in_flight_config(&self) -> Config {
+        self.codex.in_flight_config()
+    }
+
+    /// The label set with `Op::SetConversationLabel`, if any.
+    pub fn label(&self) -> Option<String> {
+        self.codex.label()
+    }
+
+    /// Supply the result of tool call `call_id` instead of running the tool,
+    /// for deterministic tests and replays. The session must be configured
+    /// with `stub_tool_calls`; the turn waits for this result when the model
+    /// makes the call. Results may be injected before the call is made.
+    pub fn inject_tool_result(
+        &self,
+        call_id: String,
+        result: FunctionCallOutputPayload,
+    ) -> CodexResult<()> {
+        self.codex.inject_tool_result(call_id, result)
     }
 
     pub async fn submit(&self, op: Op) -> CodexResult<String> {
@@ -24,7 +94,210 @@ impl CodexConversation {
         self.codex.submit_with_id(sub).await
     }
 
+    /// Returns the next event, waiting while delivery is paused.
     pub async fn next_event(&self) -> CodexResult<Event> {
-        self.codex.next_event().await
+        let mut paused = self.events_paused.subscribe();
+        loop {
+            // The sender lives as long as `self`, so these waits cannot fail.
+            let _ = paused.wait_for(|paused| !*paused).await;
+            // Let the drain task of the last pause store what it took off the
+            // stream, so buffered events come out before newer ones.
+            let drain = self
+                .pause_drain
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .take();
+            if let Some(drain) = drain {
+                let _ = drain.await;
+            }
+            let buffered = self
+                .paused_events
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .pop();
+            let event = match buffered {
+                Some(event) => Ok(event),
+                None => tokio::select! {
+                    biased;
+                    _ = paused.wait_for(|paused| *paused) => continue,
+                    event = self.codex.next_event() => event,
+                },
+            };
+            if let Ok(Event {
+                msg: EventMsg::TokenCount(usage),
+                ..
+            }) = &event
+            {
+                self.token_usage.send_replace(Some(usage.clone()));
+            }
+            return event;
+        }
+    }
+
+    /// Holds back `next_event` delivery until `resume_events` is called.
+    /// Events produced in the meantime are buffered, up to
+    /// `PAUSED_EVENT_BUFFER_CAPACITY`, and delivered in order. Must be called
+    /// from within a Tokio runtime.
+    pub fn pause_events(&self) {
+        if self.events_paused.send_replace(true) {
+            return;
+        }
+        let mut paused = self.events_paused.subscribe();
+        let rx_event = self.codex.event_receiver();
+        let buffer = Arc::clone(&self.paused_events);
+        let mut pause_drain = self
+            .pause_drain
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // The last pause's task may not have seen the resume yet; it keeps
+        // draining until it does, so wait for it to keep events in order.
+        let previous = pause_drain.take();
+        *pause_drain = Some(tokio::spawn(async move {
+            if let Some(previous) = previous {
+                let _ = previous.await;
+            }
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = paused.wait_for(|paused| !*paused) => break,
+                    event = rx_event.recv() => match event {
+                        Ok(event) => buffer
+                            .lock()
+                            .unwrap_or_else(std::sync::PoisonError::into_inner)
+                            .push(event),
+                        Err(_) => break,
+                    },
+                }
+            }
+        }));
+    }
+
+    /// Resumes `next_event` delivery after `pause_events`. Returns how many
+    /// events were dropped because the buffer overflowed, logging a warning
+    /// if any were.
+    pub fn resume_events(&self) -> usize {
+        if !self.events_paused.send_replace(false) {
+            return 0;
+        }
+        let dropped = self
+            .paused_events
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take_dropped();
+        if dropped > 0 {
+            warn!(
+                "event buffer overflowed while paused: dropped {dropped} events (capacity {PAUSED_EVENT_BUFFER_CAPACITY})"
+            );
+        }
+        dropped
+    }
+}
+
+/// Bounded buffer of events held back by `pause_events`.
+struct PausedEvents {
+    events: VecDeque<Event>,
+    capacity: usize,
+    dropped: usize,
+}
+
+impl PausedEvents {
+    fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Appends `event`, first dropping the oldest streaming delta when full,
+    /// or the oldest event if no delta is buffered. Deltas are safe to lose:
+    /// the completed item they stream is delivered separately.
+    fn push(&mut self, event: Event) {
+        if self.events.len() >= self.capacity {
+            let victim = self
+                .events
+                .iter()
+                .position(|event| is_streaming_delta(&event.msg))
+                .unwrap_or(0);
+            self.events.remove(victim);
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    fn pop(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    fn take_dropped(&mut self) -> usize {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+fn is_streaming_delta(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::AgentMessageDelta(_)
+            | EventMsg::AgentReasoningDelta(_)
+            | EventMsg::AgentReasoningRawContentDelta(_)
+            | EventMsg::ExecCommandOutputDelta(_)
+            | EventMsg::PartialTokenCount(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::AgentMessageDeltaEvent;
+    use crate::protocol::AgentMessageEvent;
+    use pretty_assertions::assert_eq;
+
+    fn delta(id: &str) -> Event {
+        Event {
+            id: id.to_string(),
+            msg: EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: id.to_string(),
+            }),
+        }
+    }
+
+    fn message(id: &str) -> Event {
+        Event {
+            id: id.to_string(),
+            msg: EventMsg::AgentMessage(AgentMessageEvent {
+                message: id.to_string(),
+            }),
+        }
+    }
+
+    fn drain_ids(buffer: &mut PausedEvents) -> Vec<String> {
+        std::iter::from_fn(|| buffer.pop())
+            .map(|event| event.id)
+            .collect()
+    }
+
+    #[test]
+    fn overflow_drops_oldest_deltas_first_and_counts_them() {
+        let mut buffer = PausedEvents::new(3);
+        buffer.push(message("m1"));
+        buffer.push(delta("d1"));
+        buffer.push(delta("d2"));
+        buffer.push(message("m2"));
+        buffer.push(message("m3"));
+
+        assert_eq!(drain_ids(&mut buffer), vec!["m1", "m2", "m3"]);
+        assert_eq!(buffer.take_dropped(), 2);
+        assert_eq!(buffer.take_dropped(), 0);
+    }
+
+    #[test]
+    fn overflow_without_deltas_drops_oldest_event() {
+        let mut buffer = PausedEvents::new(2);
+        for id in ["m1", "m2", "m3"] {
+            buffer.push(message(id));
+        }
+
+        assert_eq!(drain_ids(&mut buffer), vec!["m2", "m3"]);
+        assert_eq!(buffer.take_dropped(), 1);
     }
 }
@@ -1,20 +1,33 @@
 use crate::config_profile::ConfigProfile;
+use crate::config_types::ApplyPatchWriteRetryConfig;
+use crate::config_types::Environment;
+use crate::config_types::FileMentionMode;
 use crate::config_types::History;
 use crate::config_types::McpServerConfig;
+use crate::config_types::ModelOutputStream;
+use crate::config_types::ModelOverride;
+use crate::config_types::OutputVerbosity;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
+use crate::config_types::ShellKind;
+use crate::config_types::Theme;
+use crate::config_types::TimestampFormat;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
 use crate::config_types::Verbosity;
 use crate::git_info::resolve_root_git_project_for_trust;
+use crate::mcp_connection_manager::DEFAULT_MCP_TOOL_NAME_DELIMITER;
+use crate::mcp_connection_manager::is_valid_mcp_tool_name_delimiter;
 use crate::model_family::ModelFamily;
 use crate::model_family::find_family_for_model;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
 use crate::protocol::AskForApproval;
+use crate::protocol::ModelPricing;
 use crate::protocol::SandboxPolicy;
+use codex_apply_patch::WriteRetryPolicy;
 use codex_login::AuthMode;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
@@ -24,6 +37,7 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 use toml::Value as TomlValue;
 use toml_edit::DocumentMut;
@@ -35,6 +49,14 @@ const OPENAI_DEFAULT_MODEL: &str = "gpt-5";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
+/// Images whose data URL is longer than this are stored next to the rollout
+/// file instead of inline.
+pub(crate) const DEFAULT_ROLLOUT_IMAGE_INLINE_MAX_BYTES: usize = 16 * 1024; // 16 KiB
+
+/// Files mentioned with `@path` that are larger than this are summarized
+/// rather than inlined.
+pub(crate) const DEFAULT_FILE_MENTION_MAX_INLINE_BYTES: usize = 32 * 1024; // 32 KiB
+
 const CONFIG_TOML_FILE: &str = "config.toml";
 
 const DEFAULT_RESPONSES_ORIGINATOR_HEADER: &str = "codex_cli_rs";
@@ -53,6 +75,13 @@ pub struct Config {
     /// Maximum number of output tokens.
     pub model_max_output_tokens: Option<u64>,
 
+    /// Per-model capabilities keyed by model slug, overriding the built-in
+    /// metadata for that model.
+    pub model_overrides: HashMap<String, ModelOverride>,
+
+    /// Pricing of `model`, used to estimate the cost of token usage.
+    pub model_pricing: Option<ModelPricing>,
+
     /// Key into the model_providers map that specifies which provider to use.
     pub model_provider_id: String,
 
@@ -116,12 +145,139 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Separator between the server name and the tool name in the
+    /// fully-qualified names of MCP tools. Defaults to `__`.
+    pub mcp_tool_name_delimiter: String,
+
+    /// When set, MCP servers are health-checked in the background at this
+    /// interval and the result is reported as `McpServerHealthResponse`.
+    pub mcp_health_check_interval: Option<Duration>,
+
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: usize,
 
+    /// Wall-clock budget for a single task. Once exceeded, Codex stops after
+    /// the in-flight tool call completes instead of calling the model again.
+    /// `None` means unlimited.
+    pub max_turn_duration: Option<Duration>,
+
+    /// Include a git summary (branch, commit, remote URL) of the working
+    /// directory in the environment context sent to the model. Defaults to
+    /// `true`; disable it if branch names or remotes are sensitive.
+    pub include_git_context: bool,
+
+    /// Fixed `prompt_cache_key` to send with Responses API requests. When
+    /// unset, each conversation uses its own id so that every turn of a
+    /// conversation shares one cache key.
+    pub prompt_cache_key: Option<String>,
+
+    /// Cap on the number of tokens the model may generate per request. A
+    /// `UserTurn` can set its own cap; otherwise this one applies. `None`
+    /// leaves the limit to the provider.
+    pub max_output_tokens: Option<u64>,
+
+    /// Under `approval_policy = "on-failure"`, offer to re-run a command
+    /// outside the sandbox when it fails in the sandbox. When `false`, the
+    /// failure is returned to the model without prompting. Defaults to `true`.
+    pub allow_unsandboxed_retry: bool,
+
+    /// Maximum number of `ExecCommandOutputDelta` events per second for each
+    /// output stream of a command. Output in between is coalesced into the
+    /// next event; the final `ExecCommandEnd` output is always complete.
+    /// `None` means no rate limit.
+    pub max_exec_output_deltas_per_second: Option<u32>,
+
+    /// Models to try, in order, when the API answers `model_not_found` for
+    /// the configured model. Once a fallback succeeds it is used for the
+    /// rest of the session.
+    pub model_fallbacks: Vec<String>,
+
+    /// Shell the `exec_command` tool runs commands through. `None` means the
+    /// platform default (`bash`, or `pwsh` on Windows).
+    pub exec_shell: Option<ShellKind>,
+
+    /// Never ask the model to reason: requests omit the `reasoning`
+    /// parameters and no reasoning events are emitted, even for
+    /// reasoning-capable models.
+    pub disable_reasoning: bool,
+
+    /// Output verbosity level the defaults below were derived from.
+    pub verbosity: OutputVerbosity,
+
+    /// Maximum number of exec output lines a front-end shows for a command.
+    /// `None` keeps the front-end's own limit.
+    pub exec_output_max_lines: Option<usize>,
+
+    /// External command that applies patches instead of the built-in
+    /// `apply_patch`. The patch is fed to it on stdin.
+    pub apply_patch_command: Option<Vec<String>>,
+
+    /// When true, `apply_patch` only validates and previews patches; nothing
+    /// is written to disk.
+    pub apply_patch_dry_run: bool,
+
+    /// How the built-in `apply_patch` retries file writes that fail with a
+    /// transient error.
+    pub apply_patch_write_retry: WriteRetryPolicy,
+
+    /// Prompt to start with when none is given on the command line, taken
+    /// from the `[projects]` entry matching the cwd.
+    pub initial_prompt: Option<String>,
+
+    /// Images larger than this many bytes are written to files under the
+    /// session directory and referenced from the rollout instead of being
+    /// stored inline.
+    pub rollout_image_inline_max_bytes: usize,
+
+    /// Emit a `TurnTiming` event with the latency of every model request.
+    pub report_turn_timing: bool,
+
+    /// Text prepended to the latest user message of every request sent to
+    /// the model. It is not stored in the conversation history.
+    pub user_turn_preamble: Option<String>,
+
+    /// Whether the model sees exec output as one interleaved stream or as
+    /// separate stdout and stderr.
+    pub model_output_stream: ModelOutputStream,
+
+    /// How `@path` mentions of files in user input are expanded.
+    pub file_mention_mode: FileMentionMode,
+
+    /// Files larger than this are summarized instead of inlined when
+    /// `file_mention_mode` is `inline`.
+    pub file_mention_max_inline_bytes: usize,
+
+    /// Maximum number of user inputs that may wait for the running task.
+    /// Further inputs are rejected. `None` means no limit.
+    pub max_queued_inputs: Option<usize>,
+
+    /// For tests and replays: tool calls are not executed; each one waits for
+    /// a result supplied with `CodexConversation::inject_tool_result`.
+    pub stub_tool_calls: bool,
+
+    /// How timestamps are written to rollouts, audit logs and log files.
+    pub timestamps: TimestampFormat,
+
+    /// Send only the last this many user turns of history to the model,
+    /// relying on compaction summaries for older context. `None` sends the
+    /// full history.
+    pub max_context_turns: Option<usize>,
+
+    /// Glob patterns for files whose changes are applied but left out of the
+    /// reported turn diff (e.g. `Cargo.lock`, `*.snap`).
+    pub diff_ignore_patterns: Vec<String>,
+
+    /// When true, hunks that only change whitespace are applied but left out
+    /// of the reported turn diff.
+    pub diff_ignore_whitespace: bool,
+
+    /// When true, `Op::OverrideTurnContext` may only switch the sandbox to
+    /// `DangerFullAccess` if it sets `danger_full_access_confirmed`.
+    pub confirm_danger_full_access: bool,
+
     /// Directory containing all Codex state (defaults to `~/.codex` but can be
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
@@ -136,6 +292,12 @@ pub struct Config {
     /// Collection of settings that are specific to the TUI.
     pub tui: Tui,
 
+    /// Settings for the environment context sent to the model.
+    pub environment: Environment,
+
+    /// Colors the TUI uses for semantic roles.
+    pub theme: Theme,
+
     /// Path to the `codex-linux-sandbox` executable. This must be set if
     /// [`crate::exec::SandboxType::LinuxSeccomp`] is used. Note that this
     /// cannot be set in the config file: it must be set in code via
@@ -174,6 +336,11 @@ pub struct Config {
     /// The value for the `originator` header included with Responses API requests.
     pub responses_originator_header: String,
 
+    /// Headers sent with every model request, used to toggle experimental
+    /// features behind a gateway. Changed mid-session with
+    /// `Op::SetExperimentHeaders`.
+    pub experiment_headers: HashMap<String, String>,
+
     /// If set to `true`, the API key will be signed with the `originator` header.
     pub preferred_auth_method: AuthMode,
 
@@ -221,6 +388,78 @@ impl Config {
         // Step 4: merge with the strongly-typed overrides.
         Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)
     }
+
+    /// Returns the prompt a session should start with: `cli_prompt` when one
+    /// was given, otherwise the project's `initial_prompt`.
+    pub fn resolve_initial_prompt(&self, cli_prompt: Option<String>) -> Option<String> {
+        cli_prompt.or_else(|| self.initial_prompt.clone())
+    }
+
+    /// Model family for `slug` with its `[model_overrides]` entry applied.
+    /// Slugs without a known family reuse this config's family.
+    pub fn model_family_for(&self, slug: &str) -> ModelFamily {
+        let mut family = find_family_for_model(slug).unwrap_or_else(|| self.model_family.clone());
+        if let Some(model_override) = self.model_overrides.get(slug) {
+            apply_model_override(&mut family, model_override);
+        }
+        family
+    }
+
+    /// Pricing for `slug`: its `[model_overrides]` prices layered over the
+    /// built-in pricing for `family`.
+    pub fn model_pricing_for(&self, slug: &str, family: &ModelFamily) -> Option<ModelPricing> {
+        resolve_model_pricing(
+            &self.model_overrides.get(slug).copied().unwrap_or_default(),
+            family,
+        )
+    }
+
+    /// Context window for `slug`: its `[model_overrides]` entry if set,
+    /// otherwise the built-in metadata for `family`.
+    pub fn model_context_window_for(&self, slug: &str, family: &ModelFamily) -> Option<u64> {
+        self.model_overrides
+            .get(slug)
+            .and_then(|model_override| model_override.context_window)
+            .or_else(|| get_model_info(family).map(|info| info.context_window))
+    }
+}
+
+/// Prices set in `model_override` win field by field over the built-in
+/// pricing. Without both an input and an output price there is no pricing.
+fn resolve_model_pricing(
+    model_override: &ModelOverride,
+    family: &ModelFamily,
+) -> Option<ModelPricing> {
+    let built_in = get_model_info(family).and_then(|info| info.pricing);
+    Some(ModelPricing {
+        input_price_per_mtok: model_override
+            .input_price_per_mtok
+            .or(built_in.map(|p| p.input_price_per_mtok))?,
+        output_price_per_mtok: model_override
+            .output_price_per_mtok
+            .or(built_in.map(|p| p.output_price_per_mtok))?,
+        cached_input_price_per_mtok: model_override
+            .cached_input_price_per_mtok
+            .or(built_in.and_then(|p| p.cached_input_price_per_mtok)),
+    })
+}
+
+/// Fields left unset in `apply_patch_write_retry` keep the built-in defaults.
+fn write_retry_policy(config: ApplyPatchWriteRetryConfig) -> WriteRetryPolicy {
+    let default = WriteRetryPolicy::default();
+    WriteRetryPolicy {
+        max_attempts: config.max_attempts.unwrap_or(default.max_attempts),
+        initial_backoff: config
+            .initial_backoff_ms
+            .map(Duration::from_millis)
+            .unwrap_or(default.initial_backoff),
+    }
+}
+
+fn apply_model_override(family: &mut ModelFamily, model_override: &ModelOverride) {
+    if let Some(supports_reasoning_summaries) = model_override.supports_reasoning_summaries {
+        family.supports_reasoning_summaries = supports_reasoning_summaries;
+    }
 }
 
 pub fn load_config_as_toml_with_cli_overrides(
@@ -402,6 +641,10 @@ pub struct ConfigToml {
     /// Maximum number of output tokens.
     pub model_max_output_tokens: Option<u64>,
 
+    /// Context window, output limit and reasoning support per model slug,
+    /// e.g. for models served by a local OSS provider.
+    pub model_overrides: Option<HashMap<String, ModelOverride>>,
+
     /// Default approval policy for executing commands.
     pub approval_policy: Option<AskForApproval>,
 
@@ -430,6 +673,14 @@ pub struct ConfigToml {
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Separator between the server name and the tool name in the
+    /// fully-qualified names of MCP tools. Must match `^[a-zA-Z0-9_-]+$`.
+    pub mcp_tool_name_delimiter: Option<String>,
+
+    /// Interval in milliseconds between background MCP server health
+    /// checks. Disabled when unset.
+    pub mcp_health_check_interval_ms: Option<u64>,
+
     /// User-defined provider entries that extend/override the built-in list.
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
@@ -437,6 +688,92 @@ pub struct ConfigToml {
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
+    /// Wall-clock budget (in milliseconds) for a single task.
+    pub max_turn_duration_ms: Option<u64>,
+
+    /// Include the git summary in the environment context. Defaults to `true`.
+    pub include_git_context: Option<bool>,
+
+    /// Override for the `prompt_cache_key` sent with Responses API requests.
+    pub prompt_cache_key: Option<String>,
+
+    /// Maximum output tokens per model request, unless a turn sets its own.
+    pub max_output_tokens: Option<u64>,
+
+    /// Offer an unsandboxed retry when a command fails in the sandbox under
+    /// `on-failure`. Defaults to `true`.
+    pub allow_unsandboxed_retry: Option<bool>,
+
+    /// Rate limit for streamed exec output deltas (events per second).
+    pub max_exec_output_deltas_per_second: Option<u32>,
+
+    /// Ordered fallback models used when the configured model is unavailable.
+    pub model_fallbacks: Option<Vec<String>>,
+
+    /// Shell used by the `exec_command` tool: `bash`, `sh` or `pwsh`.
+    pub exec_shell: Option<ShellKind>,
+
+    /// Omit reasoning parameters and suppress reasoning events.
+    pub disable_reasoning: Option<bool>,
+
+    /// `concise`, `normal` or `verbose`: defaults for the reasoning summary,
+    /// raw reasoning display and exec output truncation.
+    pub verbosity: Option<OutputVerbosity>,
+
+    /// Maximum number of exec output lines shown per command.
+    pub exec_output_max_lines: Option<usize>,
+
+    /// External patch applier, e.g. `["my-patcher", "--check"]`. It receives
+    /// the patch on stdin; a zero exit status means the patch was applied.
+    pub apply_patch_command: Option<Vec<String>>,
+
+    /// Validate and preview patches without writing them. Defaults to false.
+    pub apply_patch_dry_run: Option<bool>,
+
+    /// Retries of file writes that fail with a transient error.
+    pub apply_patch_write_retry: Option<ApplyPatchWriteRetryConfig>,
+
+    /// Size in bytes above which images are persisted out of line.
+    pub rollout_image_inline_max_bytes: Option<usize>,
+
+    /// Report time-to-first-token and total stream time per turn.
+    pub report_turn_timing: Option<bool>,
+
+    /// Fixed text (e.g. a compliance disclaimer) prepended to every user turn.
+    pub user_turn_preamble: Option<String>,
+
+    /// `combined` or `separate` stdout/stderr in exec output sent to the model.
+    pub model_output_stream: Option<ModelOutputStream>,
+
+    /// `path`, `inline` or `summary` expansion of `@path` file mentions.
+    pub file_mention_mode: Option<FileMentionMode>,
+
+    /// Size guard, per mentioned file, for `file_mention_mode = "inline"`.
+    pub file_mention_max_inline_bytes: Option<usize>,
+
+    /// Reject user inputs beyond this many while a task is running.
+    pub max_queued_inputs: Option<usize>,
+
+    /// Wait for injected tool results instead of running tools (tests and
+    /// replays only).
+    pub stub_tool_calls: Option<bool>,
+
+    /// `rfc3339` (default), `utc` or `local` timestamps in persisted logs.
+    pub timestamps: Option<TimestampFormat>,
+
+    /// Number of most recent user turns sent to the model.
+    pub max_context_turns: Option<usize>,
+
+    /// Files to leave out of the reported turn diff.
+    pub diff_ignore_patterns: Option<Vec<String>>,
+
+    /// Leave whitespace-only hunks out of the reported turn diff.
+    pub diff_ignore_whitespace: Option<bool>,
+
+    /// Require explicit confirmation to switch to `danger-full-access`
+    /// mid-session. Defaults to `true`.
+    pub confirm_danger_full_access: Option<bool>,
+
     /// Profile to use from the `profiles` map.
     pub profile: Option<String>,
 
@@ -455,6 +792,12 @@ pub struct ConfigToml {
     /// Collection of settings that are specific to the TUI.
     pub tui: Option<Tui>,
 
+    /// `[environment]` settings, e.g. a `project_summary`.
+    pub environment: Option<Environment>,
+
+    /// Overrides for the TUI's default color theme.
+    pub theme: Option<Theme>,
+
     /// When set to `true`, `AgentReasoning` events will be hidden from the
     /// UI/output. Defaults to `false`.
     pub hide_agent_reasoning: Option<bool>,
@@ -485,6 +828,10 @@ pub struct ConfigToml {
     /// The value for the `originator` header included with Responses API requests.
     pub responses_originator_header_internal_override: Option<String>,
 
+    /// Headers sent with every model request. Profiles can add to or replace
+    /// individual entries.
+    pub experiment_headers: Option<HashMap<String, String>>,
+
     pub projects: Option<HashMap<String, ProjectConfig>>,
 
     /// If set to `true`, the API key will be signed with the `originator` header.
@@ -499,9 +846,21 @@ pub struct ConfigToml {
     pub disable_paste_burst: Option<bool>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct ProjectConfig {
     pub trust_level: Option<String>,
+
+    /// Model to use when Codex is launched in this project. A `--model`
+    /// override or the selected profile still take precedence.
+    pub model: Option<String>,
+
+    /// Reasoning effort to use when Codex is launched in this project.
+    #[serde(alias = "reasoning_effort")]
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+
+    /// Prompt to start with when Codex is launched in this project without
+    /// one. A prompt given on the command line takes precedence.
+    pub initial_prompt: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -528,11 +887,15 @@ impl ConfigToml {
                     network_access,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
+                    tracked_files_only,
+                    allow_git_writes,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
+                    tracked_files_only: *tracked_files_only,
+                    allow_git_writes: *allow_git_writes,
                 },
                 None => SandboxPolicy::new_workspace_write_policy(),
             },
@@ -566,6 +929,18 @@ impl ConfigToml {
         false
     }
 
+    /// Settings of the `[projects]` entry closest to `resolved_cwd`: the cwd
+    /// itself or its nearest configured ancestor, falling back to the root
+    /// git project so worktrees share the main project's settings.
+    fn project_config_for_cwd(&self, resolved_cwd: &Path) -> Option<ProjectConfig> {
+        let projects = self.projects.as_ref()?;
+        let lookup = |path: &Path| projects.get(path.to_string_lossy().as_ref()).cloned();
+
+        resolved_cwd.ancestors().find_map(lookup).or_else(|| {
+            resolve_root_git_project_for_trust(resolved_cwd).and_then(|root| lookup(&root))
+        })
+    }
+
     pub fn get_config_profile(
         &self,
         override_profile: Option<String>,
@@ -649,6 +1024,29 @@ impl Config {
             None => ConfigProfile::default(),
         };
 
+        let resolved_cwd = {
+            use std::env;
+
+            match cwd {
+                None => {
+                    tracing::info!("cwd not set, using current dir");
+                    env::current_dir()?
+                }
+                Some(p) if p.is_absolute() => p,
+                Some(p) => {
+                    // Resolve relative path against the current working directory.
+                    tracing::info!("cwd is relative, resolving against current dir");
+                    let mut current = env::current_dir()?;
+                    current.push(p);
+                    current
+                }
+            }
+        };
+
+        let project = cfg
+            .project_config_for_cwd(&resolved_cwd)
+            .unwrap_or_default();
+
         let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
 
         let mut model_providers = built_in_model_providers();
@@ -673,25 +1071,6 @@ impl Config {
 
         let shell_environment_policy = cfg.shell_environment_policy.into();
 
-        let resolved_cwd = {
-            use std::env;
-
-            match cwd {
-                None => {
-                    tracing::info!("cwd not set, using current dir");
-                    env::current_dir()?
-                }
-                Some(p) if p.is_absolute() => p,
-                Some(p) => {
-                    // Resolve relative path against the current working directory.
-                    tracing::info!("cwd is relative, resolving against current dir");
-                    let mut current = env::current_dir()?;
-                    current.push(p);
-                    current
-                }
-            }
-        };
-
         let history = cfg.history.unwrap_or_default();
 
         let tools_web_search_request = override_tools_web_search_request
@@ -704,9 +1083,12 @@ impl Config {
 
         let model = model
             .or(config_profile.model)
+            .or(project.model)
             .or(cfg.model)
             .unwrap_or_else(default_model);
-        let model_family = find_family_for_model(&model).unwrap_or_else(|| {
+        let model_overrides = cfg.model_overrides.unwrap_or_default();
+        let model_override = model_overrides.get(&model).copied().unwrap_or_default();
+        let mut model_family = find_family_for_model(&model).unwrap_or_else(|| {
             let supports_reasoning_summaries =
                 cfg.model_supports_reasoning_summaries.unwrap_or(false);
             ModelFamily {
@@ -718,16 +1100,23 @@ impl Config {
                 apply_patch_tool_type: None,
             }
         });
+        apply_model_override(&mut model_family, &model_override);
 
         let openai_model_info = get_model_info(&model_family);
-        let model_context_window = cfg
-            .model_context_window
+        let model_context_window = model_override
+            .context_window
+            .or(cfg.model_context_window)
             .or_else(|| openai_model_info.as_ref().map(|info| info.context_window));
-        let model_max_output_tokens = cfg.model_max_output_tokens.or_else(|| {
-            openai_model_info
-                .as_ref()
-                .map(|info| info.max_output_tokens)
-        });
+        let model_max_output_tokens = model_override
+            .max_output_tokens
+            .or(cfg.model_max_output_tokens)
+            .or_else(|| {
+                openai_model_info
+                    .as_ref()
+                    .map(|info| info.max_output_tokens)
+            });
+
+        let model_pricing = resolve_model_pricing(&model_override, &model_family);
 
         let experimental_resume = cfg.experimental_resume;
 
@@ -746,11 +1135,30 @@ impl Config {
             .responses_originator_header_internal_override
             .unwrap_or(DEFAULT_RESPONSES_ORIGINATOR_HEADER.to_owned());
 
+        let verbosity = cfg.verbosity.unwrap_or_default();
+
+        let mut experiment_headers = cfg.experiment_headers.unwrap_or_default();
+        experiment_headers.extend(config_profile.experiment_headers.unwrap_or_default());
+
+        let mcp_tool_name_delimiter = cfg
+            .mcp_tool_name_delimiter
+            .unwrap_or_else(|| DEFAULT_MCP_TOOL_NAME_DELIMITER.to_string());
+        if !is_valid_mcp_tool_name_delimiter(&mcp_tool_name_delimiter) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "invalid mcp_tool_name_delimiter `{mcp_tool_name_delimiter}`: must match pattern ^[a-zA-Z0-9_-]+$"
+                ),
+            ));
+        }
+
         let config = Self {
             model,
             model_family,
             model_context_window,
             model_max_output_tokens,
+            model_overrides,
+            model_pricing,
             model_provider_id,
             model_provider,
             cwd: resolved_cwd,
@@ -769,27 +1177,71 @@ impl Config {
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
+            mcp_tool_name_delimiter,
+            mcp_health_check_interval: cfg
+                .mcp_health_check_interval_ms
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis),
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
+            max_turn_duration: cfg.max_turn_duration_ms.map(Duration::from_millis),
+            include_git_context: cfg.include_git_context.unwrap_or(true),
+            prompt_cache_key: cfg.prompt_cache_key,
+            max_output_tokens: cfg.max_output_tokens,
+            allow_unsandboxed_retry: cfg.allow_unsandboxed_retry.unwrap_or(true),
+            max_exec_output_deltas_per_second: cfg.max_exec_output_deltas_per_second,
+            model_fallbacks: cfg.model_fallbacks.unwrap_or_default(),
+            exec_shell: cfg.exec_shell,
+            disable_reasoning: cfg.disable_reasoning.unwrap_or(false),
+            verbosity,
+            exec_output_max_lines: cfg
+                .exec_output_max_lines
+                .or(verbosity.exec_output_max_lines()),
+            apply_patch_command: cfg.apply_patch_command.filter(|cmd| !cmd.is_empty()),
+            apply_patch_dry_run: cfg.apply_patch_dry_run.unwrap_or(false),
+            apply_patch_write_retry: write_retry_policy(
+                cfg.apply_patch_write_retry.unwrap_or_default(),
+            ),
+            initial_prompt: project.initial_prompt.filter(|p| !p.trim().is_empty()),
+            rollout_image_inline_max_bytes: cfg
+                .rollout_image_inline_max_bytes
+                .unwrap_or(DEFAULT_ROLLOUT_IMAGE_INLINE_MAX_BYTES),
+            report_turn_timing: cfg.report_turn_timing.unwrap_or(false),
+            user_turn_preamble: cfg.user_turn_preamble.filter(|p| !p.trim().is_empty()),
+            model_output_stream: cfg.model_output_stream.unwrap_or_default(),
+            file_mention_mode: cfg.file_mention_mode.unwrap_or_default(),
+            file_mention_max_inline_bytes: cfg
+                .file_mention_max_inline_bytes
+                .unwrap_or(DEFAULT_FILE_MENTION_MAX_INLINE_BYTES),
+            max_queued_inputs: cfg.max_queued_inputs,
+            stub_tool_calls: cfg.stub_tool_calls.unwrap_or(false),
+            timestamps: cfg.timestamps.unwrap_or_default(),
+            max_context_turns: cfg.max_context_turns,
+            diff_ignore_patterns: cfg.diff_ignore_patterns.unwrap_or_default(),
+            diff_ignore_whitespace: cfg.diff_ignore_whitespace.unwrap_or(false),
+            confirm_danger_full_access: cfg.confirm_danger_full_access.unwrap_or(true),
             codex_home,
             history,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             tui: cfg.tui.unwrap_or_default(),
+            environment: cfg.environment.unwrap_or_default(),
+            theme: cfg.theme.unwrap_or_default(),
             codex_linux_sandbox_exe,
 
             hide_agent_reasoning: cfg.hide_agent_reasoning.unwrap_or(false),
             show_raw_agent_reasoning: cfg
                 .show_raw_agent_reasoning
                 .or(show_raw_agent_reasoning)
-                .unwrap_or(false),
+                .unwrap_or(verbosity.show_raw_agent_reasoning()),
             model_reasoning_effort: config_profile
                 .model_reasoning_effort
+                .or(project.model_reasoning_effort)
                 .or(cfg.model_reasoning_effort)
                 .unwrap_or_default(),
             model_reasoning_summary: config_profile
                 .model_reasoning_summary
                 .or(cfg.model_reasoning_summary)
-                .unwrap_or_default(),
+                .unwrap_or(verbosity.reasoning_summary()),
             model_verbosity: config_profile.model_verbosity.or(cfg.model_verbosity),
             chatgpt_base_url: config_profile
                 .chatgpt_base_url
@@ -801,6 +1253,7 @@ impl Config {
             include_apply_patch_tool: include_apply_patch_tool.unwrap_or(false),
             tools_web_search_request,
             responses_originator_header,
+            experiment_headers,
             preferred_auth_method: cfg.preferred_auth_method.unwrap_or(AuthMode::ChatGPT),
             use_experimental_streamable_shell_tool: cfg
                 .experimental_use_exec_command_tool
@@ -913,6 +1366,7 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::ThemeColor;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -950,6 +1404,40 @@ persistence = "none"
         );
     }
 
+    #[test]
+    fn test_theme_overrides_default_color() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r##"
+[theme]
+user_message = "light-blue"
+diff_add = "#00ff7f"
+"##,
+        )
+        .expect("TOML deserialization should succeed");
+        let theme = cfg.theme.expect("theme table should be parsed");
+
+        assert_eq!(theme.user_message, ThemeColor::LightBlue);
+        assert_eq!(theme.diff_add, ThemeColor::Rgb(0x00, 0xff, 0x7f));
+        // Unconfigured roles keep the default theme's colors.
+        assert_eq!(theme.error, Theme::default().error);
+    }
+
+    #[test]
+    fn test_theme_rejects_unknown_color() {
+        let err = toml::from_str::<ConfigToml>(
+            r#"
+[theme]
+error = "blurple"
+"#,
+        )
+        .expect_err("unknown color should fail to parse");
+
+        assert!(
+            err.to_string().contains("unknown color `blurple`"),
+            "unexpected error: {err}"
+        );
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -1001,6 +1489,8 @@ exclude_slash_tmp = true
                 network_access: false,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                tracked_files_only: false,
+                allow_git_writes: false,
             },
             sandbox_workspace_write_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
@@ -1087,6 +1577,12 @@ disable_response_storage = true
             stream_max_retries: Some(10),
             stream_idle_timeout_ms: Some(300_000),
             requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage: false,
         };
         let model_provider_map = {
             let mut model_provider_map = built_in_model_providers();
@@ -1144,6 +1640,12 @@ disable_response_storage = true
                 model_family: find_family_for_model("o3").expect("known model slug"),
                 model_context_window: Some(200_000),
                 model_max_output_tokens: Some(100_000),
+                model_overrides: HashMap::new(),
+                model_pricing: Some(ModelPricing {
+                    input_price_per_mtok: 2.0,
+                    output_price_per_mtok: 8.0,
+                    cached_input_price_per_mtok: Some(0.5),
+                }),
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
                 approval_policy: AskForApproval::Never,
@@ -1154,12 +1656,44 @@ disable_response_storage = true
                 notify: None,
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
+                mcp_tool_name_delimiter: DEFAULT_MCP_TOOL_NAME_DELIMITER.to_string(),
+                mcp_health_check_interval: None,
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+                max_turn_duration: None,
+                include_git_context: true,
+                prompt_cache_key: None,
+                max_output_tokens: None,
+                allow_unsandboxed_retry: true,
+                max_exec_output_deltas_per_second: None,
+                model_fallbacks: Vec::new(),
+                exec_shell: None,
+                disable_reasoning: false,
+                verbosity: OutputVerbosity::Normal,
+                exec_output_max_lines: None,
+                apply_patch_command: None,
+                apply_patch_dry_run: false,
+                apply_patch_write_retry: WriteRetryPolicy::default(),
+                initial_prompt: None,
+                rollout_image_inline_max_bytes: DEFAULT_ROLLOUT_IMAGE_INLINE_MAX_BYTES,
+                report_turn_timing: false,
+                user_turn_preamble: None,
+                model_output_stream: ModelOutputStream::Combined,
+                file_mention_mode: FileMentionMode::Path,
+                file_mention_max_inline_bytes: DEFAULT_FILE_MENTION_MAX_INLINE_BYTES,
+                max_queued_inputs: None,
+                stub_tool_calls: false,
+                timestamps: TimestampFormat::Rfc3339,
+                max_context_turns: None,
+                diff_ignore_patterns: Vec::new(),
+                diff_ignore_whitespace: false,
+                confirm_danger_full_access: true,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 tui: Tui::default(),
+                environment: Environment::default(),
+                theme: Theme::default(),
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
                 show_raw_agent_reasoning: false,
@@ -1173,6 +1707,7 @@ disable_response_storage = true
                 include_apply_patch_tool: false,
                 tools_web_search_request: false,
                 responses_originator_header: "codex_cli_rs".to_string(),
+                experiment_headers: HashMap::new(),
                 preferred_auth_method: AuthMode::ChatGPT,
                 use_experimental_streamable_shell_tool: false,
                 include_view_image_tool: true,
@@ -1202,6 +1737,12 @@ disable_response_storage = true
             model_family: find_family_for_model("gpt-3.5-turbo").expect("known model slug"),
             model_context_window: Some(16_385),
             model_max_output_tokens: Some(4_096),
+            model_overrides: HashMap::new(),
+            model_pricing: Some(ModelPricing {
+                input_price_per_mtok: 0.5,
+                output_price_per_mtok: 1.5,
+                cached_input_price_per_mtok: None,
+            }),
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
             approval_policy: AskForApproval::UnlessTrusted,
@@ -1212,12 +1753,44 @@ disable_response_storage = true
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            mcp_tool_name_delimiter: DEFAULT_MCP_TOOL_NAME_DELIMITER.to_string(),
+            mcp_health_check_interval: None,
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            max_turn_duration: None,
+            include_git_context: true,
+            prompt_cache_key: None,
+            max_output_tokens: None,
+            allow_unsandboxed_retry: true,
+            max_exec_output_deltas_per_second: None,
+            model_fallbacks: Vec::new(),
+            exec_shell: None,
+            disable_reasoning: false,
+            verbosity: OutputVerbosity::Normal,
+            exec_output_max_lines: None,
+            apply_patch_command: None,
+            apply_patch_dry_run: false,
+            apply_patch_write_retry: WriteRetryPolicy::default(),
+            initial_prompt: None,
+            rollout_image_inline_max_bytes: DEFAULT_ROLLOUT_IMAGE_INLINE_MAX_BYTES,
+            report_turn_timing: false,
+            user_turn_preamble: None,
+            model_output_stream: ModelOutputStream::Combined,
+            file_mention_mode: FileMentionMode::Path,
+            file_mention_max_inline_bytes: DEFAULT_FILE_MENTION_MAX_INLINE_BYTES,
+            max_queued_inputs: None,
+            stub_tool_calls: false,
+            timestamps: TimestampFormat::Rfc3339,
+            max_context_turns: None,
+            diff_ignore_patterns: Vec::new(),
+            diff_ignore_whitespace: false,
+            confirm_danger_full_access: true,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            environment: Environment::default(),
+            theme: Theme::default(),
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
@@ -1231,6 +1804,7 @@ disable_response_storage = true
             include_apply_patch_tool: false,
             tools_web_search_request: false,
             responses_originator_header: "codex_cli_rs".to_string(),
+            experiment_headers: HashMap::new(),
             preferred_auth_method: AuthMode::ChatGPT,
             use_experimental_streamable_shell_tool: false,
             include_view_image_tool: true,
@@ -1275,6 +1849,12 @@ disable_response_storage = true
             model_family: find_family_for_model("o3").expect("known model slug"),
             model_context_window: Some(200_000),
             model_max_output_tokens: Some(100_000),
+            model_overrides: HashMap::new(),
+            model_pricing: Some(ModelPricing {
+                input_price_per_mtok: 2.0,
+                output_price_per_mtok: 8.0,
+                cached_input_price_per_mtok: Some(0.5),
+            }),
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
@@ -1285,12 +1865,44 @@ disable_response_storage = true
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            mcp_tool_name_delimiter: DEFAULT_MCP_TOOL_NAME_DELIMITER.to_string(),
+            mcp_health_check_interval: None,
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            max_turn_duration: None,
+            include_git_context: true,
+            prompt_cache_key: None,
+            max_output_tokens: None,
+            allow_unsandboxed_retry: true,
+            max_exec_output_deltas_per_second: None,
+            model_fallbacks: Vec::new(),
+            exec_shell: None,
+            disable_reasoning: false,
+            verbosity: OutputVerbosity::Normal,
+            exec_output_max_lines: None,
+            apply_patch_command: None,
+            apply_patch_dry_run: false,
+            apply_patch_write_retry: WriteRetryPolicy::default(),
+            initial_prompt: None,
+            rollout_image_inline_max_bytes: DEFAULT_ROLLOUT_IMAGE_INLINE_MAX_BYTES,
+            report_turn_timing: false,
+            user_turn_preamble: None,
+            model_output_stream: ModelOutputStream::Combined,
+            file_mention_mode: FileMentionMode::Path,
+            file_mention_max_inline_bytes: DEFAULT_FILE_MENTION_MAX_INLINE_BYTES,
+            max_queued_inputs: None,
+            stub_tool_calls: false,
+            timestamps: TimestampFormat::Rfc3339,
+            max_context_turns: None,
+            diff_ignore_patterns: Vec::new(),
+            diff_ignore_whitespace: false,
+            confirm_danger_full_access: true,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            environment: Environment::default(),
+            theme: Theme::default(),
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
@@ -1304,6 +1916,7 @@ disable_response_storage = true
             include_apply_patch_tool: false,
             tools_web_search_request: false,
             responses_originator_header: "codex_cli_rs".to_string(),
+            experiment_headers: HashMap::new(),
             preferred_auth_method: AuthMode::ChatGPT,
             use_experimental_streamable_shell_tool: false,
             include_view_image_tool: true,
@@ -1315,6 +1928,302 @@ disable_response_storage = true
         Ok(())
     }
 
+    #[test]
+    fn test_project_model_override_applies_only_to_matching_cwd() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project_dir = TempDir::new()?;
+        let other_dir = TempDir::new()?;
+        // Keep the AGENTS.md / git root lookups inside the temp dirs.
+        std::fs::write(project_dir.path().join(".git"), "gitdir: nowhere")?;
+        std::fs::write(other_dir.path().join(".git"), "gitdir: nowhere")?;
+        let nested = project_dir.path().join("docs");
+        std::fs::create_dir(&nested)?;
+
+        let mut cfg: ConfigToml = toml::from_str(
+            r#"
+model = "o3"
+model_reasoning_effort = "low"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        cfg.projects = Some(HashMap::from([(
+            project_dir.path().to_string_lossy().to_string(),
+            ProjectConfig {
+                model: Some("gpt-4.1".to_string()),
+                model_reasoning_effort: Some(ReasoningEffort::High),
+                ..Default::default()
+            },
+        )]));
+
+        let load = |cwd: &Path| {
+            Config::load_from_base_config_with_overrides(
+                cfg.clone(),
+                ConfigOverrides {
+                    cwd: Some(cwd.to_path_buf()),
+                    ..Default::default()
+                },
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        for cwd in [project_dir.path(), nested.as_path()] {
+            let config = load(cwd)?;
+            assert_eq!(config.model, "gpt-4.1");
+            assert_eq!(config.model_reasoning_effort, ReasoningEffort::High);
+        }
+
+        let config = load(other_dir.path())?;
+        assert_eq!(config.model, "o3");
+        assert_eq!(config.model_reasoning_effort, ReasoningEffort::Low);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_initial_prompt_applies_to_matching_cwd() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project_dir = TempDir::new()?;
+        let other_dir = TempDir::new()?;
+
+        let cfg = ConfigToml {
+            projects: Some(HashMap::from([(
+                project_dir.path().to_string_lossy().to_string(),
+                ProjectConfig {
+                    initial_prompt: Some("Summarize open TODOs".to_string()),
+                    ..Default::default()
+                },
+            )])),
+            ..Default::default()
+        };
+
+        let load = |cwd: &Path| {
+            Config::load_from_base_config_with_overrides(
+                cfg.clone(),
+                ConfigOverrides {
+                    cwd: Some(cwd.to_path_buf()),
+                    ..Default::default()
+                },
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let config = load(project_dir.path())?;
+        assert_eq!(
+            config.resolve_initial_prompt(None),
+            Some("Summarize open TODOs".to_string())
+        );
+
+        let config = load(other_dir.path())?;
+        assert_eq!(config.resolve_initial_prompt(None), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_prompt_wins_over_project_initial_prompt() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project_dir = TempDir::new()?;
+        let project: ProjectConfig = toml::from_str(r#"initial_prompt = "Run the tests""#)
+            .expect("TOML deserialization should succeed");
+        let cfg = ConfigToml {
+            projects: Some(HashMap::from([(
+                project_dir.path().to_string_lossy().to_string(),
+                project,
+            )])),
+            ..Default::default()
+        };
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(project_dir.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            config.resolve_initial_prompt(Some("Fix the build".to_string())),
+            Some("Fix the build".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_overrides_apply_to_local_models() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+model = "qwen2.5-coder:32b"
+model_context_window = 4096
+
+[model_overrides."qwen2.5-coder:32b"]
+context_window = 32768
+max_output_tokens = 8192
+supports_reasoning_summaries = true
+
+[model_overrides.gpt-oss-20b]
+context_window = 131072
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(config.model_context_window, Some(32768));
+        assert_eq!(config.model_max_output_tokens, Some(8192));
+        assert!(config.model_family.supports_reasoning_summaries);
+
+        // Switching models mid-session consults the same table, falling back
+        // to the built-in metadata for models without an entry.
+        let oss = config.model_family_for("gpt-oss-20b");
+        assert_eq!(oss.family, "gpt-oss");
+        assert_eq!(
+            config.model_context_window_for("gpt-oss-20b", &oss),
+            Some(131072)
+        );
+        let o3 = config.model_family_for("o3");
+        assert_eq!(config.model_context_window_for("o3", &o3), Some(200_000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_overrides_set_pricing() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+model = "qwen2.5-coder:32b"
+
+[model_overrides."qwen2.5-coder:32b"]
+input_price_per_mtok = 0.2
+output_price_per_mtok = 0.6
+
+[model_overrides.o3]
+output_price_per_mtok = 6.0
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(
+            config.model_pricing,
+            Some(ModelPricing {
+                input_price_per_mtok: 0.2,
+                output_price_per_mtok: 0.6,
+                cached_input_price_per_mtok: None,
+            })
+        );
+
+        // A partial entry only replaces the prices it sets.
+        let o3 = config.model_family_for("o3");
+        assert_eq!(
+            config.model_pricing_for("o3", &o3),
+            Some(ModelPricing {
+                input_price_per_mtok: 2.0,
+                output_price_per_mtok: 6.0,
+                cached_input_price_per_mtok: Some(0.5),
+            })
+        );
+
+        // Unknown models without prices have no pricing.
+        let oss = config.model_family_for("gpt-oss-20b");
+        assert_eq!(config.model_pricing_for("gpt-oss-20b", &oss), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verbosity_levels_resolve_to_documented_defaults() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cases = [
+            ("concise", ReasoningSummary::Concise, false, Some(2)),
+            ("normal", ReasoningSummary::Auto, false, None),
+            ("verbose", ReasoningSummary::Detailed, true, Some(50)),
+        ];
+        for (level, summary, show_raw, max_lines) in cases {
+            let cfg: ConfigToml = toml::from_str(&format!("verbosity = \"{level}\""))
+                .expect("TOML deserialization should succeed");
+            let config = Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides::default(),
+                codex_home.path().to_path_buf(),
+            )?;
+            assert_eq!(config.model_reasoning_summary, summary, "{level}");
+            assert_eq!(config.show_raw_agent_reasoning, show_raw, "{level}");
+            assert_eq!(config.exec_output_max_lines, max_lines, "{level}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_granular_settings_override_verbosity() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+verbosity = "verbose"
+model_reasoning_summary = "none"
+show_raw_agent_reasoning = false
+exec_output_max_lines = 10
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(config.verbosity, OutputVerbosity::Verbose);
+        assert_eq!(config.model_reasoning_summary, ReasoningSummary::None);
+        assert!(!config.show_raw_agent_reasoning);
+        assert_eq!(config.exec_output_max_lines, Some(10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_patch_write_retry_keeps_defaults_for_unset_fields() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+[apply_patch_write_retry]
+max_attempts = 5
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            config.apply_patch_write_retry,
+            WriteRetryPolicy {
+                max_attempts: 5,
+                ..WriteRetryPolicy::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_reasoning_effort_alias() {
+        let project: ProjectConfig = toml::from_str(
+            r#"
+model = "gpt-4.1"
+reasoning_effort = "minimal"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        assert_eq!(
+            project.model_reasoning_effort,
+            Some(ReasoningEffort::Minimal)
+        );
+    }
+
     #[test]
     fn test_set_project_trusted_writes_explicit_tables() -> anyhow::Result<()> {
         let codex_home = TempDir::new().unwrap();
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::config_types::Verbosity;
@@ -21,4 +22,6 @@ pub struct ConfigProfile {
     pub model_verbosity: Option<Verbosity>,
     pub chatgpt_base_url: Option<String>,
     pub experimental_instructions_file: Option<PathBuf>,
+    /// Merged over the top-level `experiment_headers`.
+    pub experiment_headers: Option<HashMap<String, String>>,
 }
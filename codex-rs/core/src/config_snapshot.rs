@@ -0,0 +1,102 @@
+//! Serializable snapshot of the resolved [`Config`] a conversation was spawned
+//! with, intended for auditing by embedders.
+//! 会话创建时所用（已解析）配置的可序列化快照，供嵌入方审计使用。
+//!
+//! Values that may carry credentials (provider HTTP header values and MCP
+//! server environment variables) are replaced with [`REDACTED`].
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::ReasoningSummary;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::model_provider_info::WireApi;
+use crate::protocol::AskForApproval;
+use crate::protocol::SandboxPolicy;
+
+/// Placeholder used in place of redacted values.
+pub const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedConfigSnapshot {
+    pub model: String,
+    pub model_provider_id: String,
+    pub model_provider: ModelProviderSnapshot,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    pub cwd: PathBuf,
+    pub model_reasoning_effort: ReasoningEffort,
+    pub model_reasoning_summary: ReasoningSummary,
+    pub disable_response_storage: bool,
+    pub mcp_servers: BTreeMap<String, McpServerSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelProviderSnapshot {
+    pub name: String,
+    pub base_url: Option<String>,
+    pub wire_api: WireApi,
+    /// Name of the environment variable holding the API key (never its value).
+    pub env_key: Option<String>,
+    /// Header names with their values redacted.
+    pub http_headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerSnapshot {
+    pub command: String,
+    pub args: Vec<String>,
+    /// Environment variable names with their values redacted.
+    pub env: BTreeMap<String, String>,
+}
+
+fn redacted_keys<'a>(keys: impl Iterator<Item = &'a String>) -> BTreeMap<String, String> {
+    keys.map(|k| (k.clone(), REDACTED.to_string())).collect()
+}
+
+impl From<&Config> for ResolvedConfigSnapshot {
+    fn from(config: &Config) -> Self {
+        let provider = &config.model_provider;
+        Self {
+            model: config.model.clone(),
+            model_provider_id: config.model_provider_id.clone(),
+            model_provider: ModelProviderSnapshot {
+                name: provider.name.clone(),
+                base_url: provider.base_url.clone(),
+                wire_api: provider.wire_api,
+                env_key: provider.env_key.clone(),
+                http_headers: redacted_keys(
+                    provider
+                        .http_headers
+                        .iter()
+                        .chain(provider.env_http_headers.iter())
+                        .flat_map(|headers| headers.keys()),
+                ),
+            },
+            approval_policy: config.approval_policy,
+            sandbox_policy: config.sandbox_policy.clone(),
+            cwd: config.cwd.clone(),
+            model_reasoning_effort: config.model_reasoning_effort,
+            model_reasoning_summary: config.model_reasoning_summary,
+            disable_response_storage: config.disable_response_storage,
+            mcp_servers: config
+                .mcp_servers
+                .iter()
+                .map(|(name, server)| {
+                    (
+                        name.clone(),
+                        McpServerSnapshot {
+                            command: server.command.clone(),
+                            args: server.args.clone(),
+                            env: redacted_keys(server.env.iter().flat_map(|env| env.keys())),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
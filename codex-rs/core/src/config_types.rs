@@ -20,6 +20,57 @@ pub struct McpServerConfig {
 
     #[serde(default)]
     pub env: Option<HashMap<String, String>>,
+
+    /// Maximum time (ms) for the server to start, answer `initialize`, and
+    /// list its tools. Servers that miss it are reported as failed and the
+    /// session continues without them. Defaults to 10 seconds.
+    #[serde(default)]
+    pub startup_timeout_ms: Option<u64>,
+
+    /// Timeout (ms) for each tool call made to this server. Unset means no
+    /// timeout.
+    #[serde(default)]
+    pub request_timeout_ms: Option<u64>,
+
+    /// Retry tool calls that fail in transport (`[mcp_servers.<id>.retry]`).
+    /// Unset means no retries.
+    #[serde(default)]
+    pub retry: Option<McpRetryConfig>,
+}
+
+/// Retries with exponential backoff for tool calls to one MCP server. Only
+/// calls that time out are retried; a closed transport, errors returned by
+/// the server and errors reported by the tool are not.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct McpRetryConfig {
+    /// Retries after the first attempt.
+    pub max_retries: u32,
+
+    /// Delay (ms) before the first retry, doubled for each later one.
+    /// Defaults to 500.
+    #[serde(default)]
+    pub initial_backoff_ms: Option<u64>,
+
+    /// Upper bound (ms) on the delay between retries. Defaults to 5000.
+    #[serde(default)]
+    pub max_backoff_ms: Option<u64>,
+
+    /// Tools that are never retried. A timed-out call may still have run on
+    /// the server, so list tools that are not idempotent here.
+    #[serde(default)]
+    pub no_retry_tools: Vec<String>,
+}
+
+/// Retries of `apply_patch` file writes that fail with a transient error
+/// (`EAGAIN`, `ETXTBSY`, `EINTR`). Other errors are never retried.
+#[derive(Deserialize, Debug, Copy, Clone, Default, PartialEq)]
+pub struct ApplyPatchWriteRetryConfig {
+    /// Attempts per write, including the first one. Defaults to 3.
+    pub max_attempts: Option<u32>,
+
+    /// Delay (ms) before the first retry, doubled for each later one.
+    /// Defaults to 20.
+    pub initial_backoff_ms: Option<u64>,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -74,9 +125,163 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Capabilities of one model from `[model_overrides."<slug>"]`, for models
+/// Codex has no built-in metadata for, such as local or self-hosted ones.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct ModelOverride {
+    /// Size of the context window, in tokens.
+    pub context_window: Option<u64>,
+
+    /// Maximum number of output tokens.
+    pub max_output_tokens: Option<u64>,
+
+    /// Whether the model accepts `reasoning` parameters and produces
+    /// reasoning summaries.
+    pub supports_reasoning_summaries: Option<bool>,
+
+    /// Price per million input tokens, used to estimate cost.
+    pub input_price_per_mtok: Option<f64>,
+
+    /// Price per million output tokens.
+    pub output_price_per_mtok: Option<f64>,
+
+    /// Price per million cached input tokens. Defaults to the input price.
+    pub cached_input_price_per_mtok: Option<f64>,
+}
+
+/// Settings for the `<environment_context>` message sent to the model.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Environment {
+    /// Short orientation blurb about the project, included in the
+    /// environment context of every session.
+    pub project_summary: Option<ProjectSummary>,
+
+    /// Longer summaries are truncated to this many characters (default:
+    /// 2000).
+    pub project_summary_max_chars: Option<usize>,
+}
+
+/// Either the summary text itself, or `{ path = "..." }` naming a file that
+/// holds it. Relative paths are resolved against the session's cwd.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ProjectSummary {
+    Text(String),
+    File { path: PathBuf },
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Tui {}
+pub struct Tui {
+    /// Warn once the context window left drops below this percentage
+    /// (default: 15). `0` disables the warning.
+    pub context_warning_percent: Option<u8>,
+
+    /// Ask for a second confirmation before applying a patch that deletes
+    /// more than this many files (default: 5).
+    pub patch_delete_confirmation_threshold: Option<usize>,
+
+    /// Show the configured `user_turn_preamble` above each user message in
+    /// the transcript. Hidden by default.
+    #[serde(default)]
+    pub show_user_turn_preamble: bool,
+}
+
+/// A color used by the TUI [`Theme`]: one of the 16 terminal color names
+/// (e.g. `red`, `light-blue`, `dark-gray`) or a `#rrggbb` hex value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeColor {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+    DarkGray,
+    LightRed,
+    LightGreen,
+    LightYellow,
+    LightBlue,
+    LightMagenta,
+    LightCyan,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+impl std::str::FromStr for ThemeColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(ThemeColor::Rgb(r, g, b)),
+                _ => Err(format!("invalid hex color `{s}`, expected `#rrggbb`")),
+            };
+        }
+        let color = match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "black" => ThemeColor::Black,
+            "red" => ThemeColor::Red,
+            "green" => ThemeColor::Green,
+            "yellow" => ThemeColor::Yellow,
+            "blue" => ThemeColor::Blue,
+            "magenta" => ThemeColor::Magenta,
+            "cyan" => ThemeColor::Cyan,
+            "gray" | "grey" => ThemeColor::Gray,
+            "dark-gray" | "dark-grey" => ThemeColor::DarkGray,
+            "light-red" => ThemeColor::LightRed,
+            "light-green" => ThemeColor::LightGreen,
+            "light-yellow" => ThemeColor::LightYellow,
+            "light-blue" => ThemeColor::LightBlue,
+            "light-magenta" => ThemeColor::LightMagenta,
+            "light-cyan" => ThemeColor::LightCyan,
+            "white" => ThemeColor::White,
+            _ => return Err(format!("unknown color `{s}`")),
+        };
+        Ok(color)
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Colors the TUI uses for semantic roles, configured via the `[theme]`
+/// table. Roles that are not configured keep the default theme's color.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct Theme {
+    pub user_message: ThemeColor,
+    pub agent_message: ThemeColor,
+    pub reasoning: ThemeColor,
+    pub error: ThemeColor,
+    pub diff_add: ThemeColor,
+    pub diff_remove: ThemeColor,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            user_message: ThemeColor::Cyan,
+            agent_message: ThemeColor::Magenta,
+            reasoning: ThemeColor::Magenta,
+            error: ThemeColor::Red,
+            diff_add: ThemeColor::Green,
+            diff_remove: ThemeColor::Red,
+        }
+    }
+}
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SandboxWorkspaceWrite {
@@ -88,6 +293,10 @@ pub struct SandboxWorkspaceWrite {
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
     pub exclude_slash_tmp: bool,
+    #[serde(default)]
+    pub tracked_files_only: bool,
+    #[serde(default)]
+    pub allow_git_writes: bool,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
@@ -122,6 +331,10 @@ pub struct ShellEnvironmentPolicyToml {
     pub include_only: Option<Vec<String>>,
 
     pub experimental_use_profile: Option<bool>,
+
+    /// `.env`-style file, relative to the session cwd, whose variables are
+    /// added to the environment of commands the agent runs.
+    pub command_env_file: Option<PathBuf>,
 }
 
 pub type EnvironmentVariablePattern = WildMatchPattern<'*', '?'>;
@@ -131,8 +344,10 @@ pub type EnvironmentVariablePattern = WildMatchPattern<'*', '?'>;
 /// 2. If `ignore_default_excludes` is false, filter the map using the default
 ///    exclude pattern(s), which are: `"*KEY*"` and `"*TOKEN*"`.
 /// 3. If `exclude` is not empty, filter the map using the provided patterns.
-/// 4. Insert any entries from `r#set` into the map.
-/// 5. If non-empty, filter the map using the `include_only` patterns.
+/// 4. Insert the entries from the command environment file that survive the
+///    filters from steps 2 and 3 and do not start with `CODEX_`.
+/// 5. Insert any entries from `r#set` into the map.
+/// 6. If non-empty, filter the map using the `include_only` patterns.
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct ShellEnvironmentPolicy {
     /// Starting point when building the environment.
@@ -153,6 +368,11 @@ pub struct ShellEnvironmentPolicy {
 
     /// If true, the shell profile will be used to run the command.
     pub use_profile: bool,
+
+    /// Per-project `.env`-style file, relative to the cwd, whose variables are
+    /// injected into spawned commands (but not Codex's own process). `None`
+    /// means `.codex/command.env`.
+    pub command_env_file: Option<PathBuf>,
 }
 
 impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
@@ -174,6 +394,7 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
             .map(|s| EnvironmentVariablePattern::new_case_insensitive(&s))
             .collect();
         let use_profile = toml.experimental_use_profile.unwrap_or(false);
+        let command_env_file = toml.command_env_file;
 
         Self {
             inherit,
@@ -182,6 +403,111 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
             r#set,
             include_only,
             use_profile,
+            command_env_file,
+        }
+    }
+}
+
+/// Shell used by the `exec_command` tool to run the command string it is
+/// given.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellKind {
+    Bash,
+    Sh,
+    Pwsh,
+}
+
+impl ShellKind {
+    /// The shell commands run through when `exec_shell` is not configured.
+    pub fn platform_default() -> Self {
+        if cfg!(windows) {
+            ShellKind::Pwsh
+        } else {
+            ShellKind::Bash
+        }
+    }
+
+    /// Program name looked up on `PATH`.
+    pub fn program(self) -> &'static str {
+        match self {
+            ShellKind::Bash => "bash",
+            ShellKind::Sh => "sh",
+            ShellKind::Pwsh => "pwsh",
+        }
+    }
+}
+
+/// How the output of an exec call is presented to the model.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelOutputStream {
+    /// stdout and stderr interleaved in the order they were produced.
+    #[default]
+    Combined,
+    /// stdout and stderr reported as two separate fields.
+    Separate,
+}
+
+/// How timestamps are written to rollouts, audit logs and log files. See
+/// [`crate::timestamps`] for the exact formats.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// RFC 3339 in UTC, e.g. `2025-01-02T03:04:05.678Z`.
+    #[default]
+    Rfc3339,
+    /// UTC, space separated, e.g. `2025-01-02 03:04:05.678 UTC`.
+    Utc,
+    /// Local time with its offset, e.g. `2025-01-02 04:04:05.678 +01:00`.
+    Local,
+}
+
+/// How `@path` mentions of files in user input are presented to the model.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileMentionMode {
+    /// Only the mention itself is sent; the model reads the file if needed.
+    #[default]
+    Path,
+    /// The file contents are attached to the message.
+    Inline,
+    /// The file size and its first lines are attached to the message.
+    Summary,
+}
+
+/// How much of the agent's work is surfaced to the user. Each level picks
+/// defaults for the reasoning summary, raw reasoning display and exec output
+/// truncation; the individual settings still override it.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputVerbosity {
+    Concise,
+    #[default]
+    Normal,
+    Verbose,
+}
+
+impl OutputVerbosity {
+    pub fn reasoning_summary(self) -> codex_protocol::config_types::ReasoningSummary {
+        use codex_protocol::config_types::ReasoningSummary;
+        match self {
+            OutputVerbosity::Concise => ReasoningSummary::Concise,
+            OutputVerbosity::Normal => ReasoningSummary::Auto,
+            OutputVerbosity::Verbose => ReasoningSummary::Detailed,
+        }
+    }
+
+    pub fn show_raw_agent_reasoning(self) -> bool {
+        matches!(self, OutputVerbosity::Verbose)
+    }
+
+    /// `None` keeps each front-end's own limit.
+    pub fn exec_output_max_lines(self) -> Option<usize> {
+        match self {
+            OutputVerbosity::Concise => Some(2),
+            OutputVerbosity::Normal => None,
+            OutputVerbosity::Verbose => Some(50),
         }
     }
 }
@@ -1,8 +1,32 @@
+use std::path::Path;
+
+use crate::client_common::is_user_turn_start;
+use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Version written to, and required by, the JSON export produced by
+/// [`ConversationHistory::to_json`].
+pub const HISTORY_EXPORT_VERSION: u32 = 1;
+
+/// On-disk shape of an exported history: a version header followed by the
+/// ordered items.
+#[derive(Serialize)]
+struct HistoryExport<'a> {
+    version: u32,
+    items: &'a [ResponseItem],
+}
+
+#[derive(Deserialize)]
+struct HistoryImport {
+    version: u32,
+    items: Vec<ResponseItem>,
+}
 
 /// Transcript of conversation history
 #[derive(Debug, Clone, Default)]
-pub(crate) struct ConversationHistory {
+pub struct ConversationHistory {
     /// The oldest items are at the beginning of the vector.
     items: Vec<ResponseItem>,
 }
@@ -13,10 +37,54 @@ impl ConversationHistory {
     }
 
     /// Returns a clone of the contents in the transcript.
-    pub(crate) fn contents(&self) -> Vec<ResponseItem> {
+    pub fn contents(&self) -> Vec<ResponseItem> {
         self.items.clone()
     }
 
+    /// Serializes the raw, ordered `ResponseItem`s (oldest first) as JSON,
+    /// prefixed with a `version` header, for consumption by external tools.
+    ///
+    /// Items use their regular wire serialization, so local-only bookkeeping
+    /// such as `FunctionCallOutputPayload::success` is not preserved.
+    pub fn to_json(&self) -> String {
+        let export = HistoryExport {
+            version: HISTORY_EXPORT_VERSION,
+            items: &self.items,
+        };
+        #[expect(clippy::expect_used)]
+        serde_json::to_string(&export).expect("ResponseItem serialization cannot fail")
+    }
+
+    /// Writes [`Self::to_json`] to `path`.
+    pub fn write_json(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Parses a history previously produced by [`Self::to_json`]. Fails with
+    /// [`std::io::ErrorKind::InvalidData`] if the JSON is malformed or was
+    /// written by an unsupported export version.
+    pub fn from_json(json: &str) -> std::io::Result<Self> {
+        let import: HistoryImport = serde_json::from_str(json)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if import.version != HISTORY_EXPORT_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported conversation history export version {} (expected {HISTORY_EXPORT_VERSION})",
+                    import.version
+                ),
+            ));
+        }
+        Ok(Self {
+            items: import.items,
+        })
+    }
+
+    /// Reads and parses a history file written by [`Self::write_json`].
+    pub fn read_json(path: &Path) -> std::io::Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
     /// `items` is ordered from oldest to newest.
     pub(crate) fn record_items<I>(&mut self, items: I)
     where
@@ -59,6 +127,17 @@ impl ConversationHistory {
         kept.reverse();
         self.items = kept;
     }
+
+    /// Drops the most recent user turn (the user's message and everything
+    /// recorded after it) and returns that message's content, or `None` if
+    /// the history holds no user turn.
+    pub(crate) fn remove_last_user_turn(&mut self) -> Option<Vec<ContentItem>> {
+        let start = self.items.iter().rposition(is_user_turn_start)?;
+        match self.items.drain(start..).next() {
+            Some(ResponseItem::Message { content, .. }) => Some(content),
+            _ => None,
+        }
+    }
 }
 
 /// Anything that is not a system message or "reasoning" message is considered
@@ -79,7 +158,7 @@ fn is_api_message(message: &ResponseItem) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::FunctionCallOutputPayload;
 
     fn assistant_msg(text: &str) -> ResponseItem {
         ResponseItem::Message {
@@ -101,6 +180,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn json_export_round_trips_items() {
+        let mut h = ConversationHistory::default();
+        let call = ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: r#"{"command":["ls"]}"#.to_string(),
+            call_id: "call-1".to_string(),
+        };
+        let output = ResponseItem::FunctionCallOutput {
+            call_id: "call-1".to_string(),
+            output: FunctionCallOutputPayload {
+                content: "README.md".to_string(),
+                success: None,
+            },
+        };
+        h.record_items([&user_msg("hi"), &call, &output, &assistant_msg("done")]);
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("history.json");
+        h.write_json(&path).expect("write export");
+        let imported = ConversationHistory::read_json(&path).expect("read export");
+
+        assert_eq!(imported.contents(), h.contents());
+    }
+
+    #[test]
+    fn json_import_rejects_unknown_version() {
+        let err = ConversationHistory::from_json(r#"{"version":99,"items":[]}"#)
+            .expect_err("unsupported version should fail");
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("version 99"), "{err}");
+    }
+
+    #[test]
+    fn remove_last_user_turn_drops_the_turn_and_returns_its_input() {
+        let mut h = ConversationHistory::default();
+        let call = ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: r#"{"command":["ls"]}"#.to_string(),
+            call_id: "call-1".to_string(),
+        };
+        h.record_items([
+            &user_msg("first"),
+            &assistant_msg("one"),
+            &user_msg("second"),
+            &call,
+            &assistant_msg("two"),
+        ]);
+
+        assert_eq!(
+            h.remove_last_user_turn(),
+            Some(vec![ContentItem::OutputText {
+                text: "second".to_string()
+            }])
+        );
+        assert_eq!(h.contents(), vec![user_msg("first"), assistant_msg("one")]);
+
+        h.remove_last_user_turn();
+        assert_eq!(h.remove_last_user_turn(), None);
+        assert_eq!(h.contents(), Vec::new());
+    }
+
     #[test]
     fn filters_non_api_messages() {
         let mut h = ConversationHistory::default();
@@ -12,6 +12,8 @@
 //! - 共享状态：使用 Arc + RwLock 实现线程安全的会话共享
 
 use std::collections::HashMap; // 用于存储会话ID到会话实例的映射
+use std::collections::HashSet; // 用于收集已有结果的工具调用ID
+use std::path::Path; // 回放文件路径
 use std::sync::Arc; // 原子引用计数，实现安全的跨线程共享
 
 use codex_login::AuthManager; // 认证管理器，处理用户登录状态
@@ -24,11 +26,15 @@ use crate::codex::CodexSpawnOk; // Codex创建成功的返回结构
 use crate::codex::INITIAL_SUBMIT_ID; // 初始提交ID常量
 use crate::codex_conversation::CodexConversation; // 会话包装器
 use crate::config::Config; // 系统配置
+use crate::config_snapshot::ResolvedConfigSnapshot; // 已解析配置快照
 use crate::error::CodexErr; // 错误类型定义
 use crate::error::Result as CodexResult; // 结果类型别名
+use crate::model_family::find_family_for_model; // 根据模型名称查找模型族
 use crate::protocol::Event; // 事件消息类型
 use crate::protocol::EventMsg; // 事件消息内容
 use crate::protocol::SessionConfiguredEvent; // 会话配置完成事件
+use crate::protocol::TokenUsage; // 令牌用量统计
+use crate::rollout::RolloutRecorder; // 会话回放记录器，用于读取已持久化的会话
 use codex_protocol::models::ResponseItem; // 响应项模型
 
 /// Represents a newly created Codex conversation, including the first event
@@ -42,6 +48,18 @@ pub struct NewConversation {
     pub session_configured: SessionConfiguredEvent, // 会话配置完成事件，包含初始化参数
 }
 
+/// Metadata about a live conversation, as returned by
+/// [`ConversationManager::list_conversations`].
+///
+/// 活跃会话的摘要信息，供多会话前端渲染会话切换列表
+#[derive(Debug, Clone)]
+pub struct ConversationSummary {
+    pub conversation_id: Uuid,           // 会话唯一标识符
+    pub model: String,                   // 会话配置的模型名称
+    pub label: Option<String>,           // 会话的可读标签，未设置时为None
+    pub token_usage: Option<TokenUsage>, // 最近一次上报的令牌用量，尚未上报时为None
+}
+
 /// [`ConversationManager`] is responsible for creating conversations and
 /// maintaining them in memory.
 ///
@@ -86,22 +104,25 @@ impl ConversationManager {
         config: Config,                 // 会话配置参数
         auth_manager: Arc<AuthManager>, // 认证管理器
     ) -> CodexResult<NewConversation> {
+        let config_snapshot = ResolvedConfigSnapshot::from(&config); // 在配置被消费前记录快照
         let CodexSpawnOk {
             codex,                       // 创建的Codex核心实例
             session_id: conversation_id, // 会话ID（重命名为conversation_id以符合语义）
         } = {
             let initial_history = None; // 初始对话历史为空（新会话）
-            Codex::spawn(config, auth_manager, initial_history).await? // 调用Codex::spawn创建核心实例
+            Codex::spawn(config, auth_manager, initial_history, None).await? // 调用Codex::spawn创建核心实例
         };
-        self.finalize_spawn(codex, conversation_id).await // 完成会话初始化流程
+        self.finalize_spawn(codex, conversation_id, config_snapshot)
+            .await // 完成会话初始化流程
     }
 
     /// 完成会话创建的最终步骤
     /// 验证首个事件、封装会话实例、注册到管理器并返回完整的新会话信息
     async fn finalize_spawn(
         &self,
-        codex: Codex,          // 已创建的Codex实例
-        conversation_id: Uuid, // 会话唯一标识符
+        codex: Codex,                            // 已创建的Codex实例
+        conversation_id: Uuid,                   // 会话唯一标识符
+        config_snapshot: ResolvedConfigSnapshot, // 会话所用配置的快照
     ) -> CodexResult<NewConversation> {
         // The first event must be `SessionInitialized`. Validate and forward it
         // to the caller so that they can display it in the conversation
@@ -118,7 +139,7 @@ impl ConversationManager {
             }
         };
 
-        let conversation = Arc::new(CodexConversation::new(codex)); // 将Codex封装为CodexConversation
+        let conversation = Arc::new(CodexConversation::new(codex, config_snapshot)); // 将Codex封装为CodexConversation
         self.conversations
             .write() // 获取会话映射表的写锁
             .await
@@ -144,6 +165,37 @@ impl ConversationManager {
             .ok_or_else(|| CodexErr::ConversationNotFound(conversation_id)) // 未找到时返回ConversationNotFound错误
     }
 
+    /// Return the resolved configuration (with secrets redacted) that the
+    /// conversation was spawned with, or `None` if it is not known.
+    ///
+    /// 获取会话创建时使用的已解析配置快照（敏感信息已脱敏）
+    pub async fn get_conversation_config(
+        &self,
+        conversation_id: Uuid,
+    ) -> Option<ResolvedConfigSnapshot> {
+        let conversations = self.conversations.read().await; // 获取会话映射表的读锁
+        conversations
+            .get(&conversation_id)
+            .map(|conversation| conversation.config_snapshot().clone())
+    }
+
+    /// List the conversations currently held by the manager, in no
+    /// particular order.
+    ///
+    /// 列出管理器中所有活跃会话的摘要信息（顺序不固定）
+    pub async fn list_conversations(&self) -> Vec<ConversationSummary> {
+        let conversations = self.conversations.read().await; // 获取会话映射表的读锁
+        conversations
+            .iter()
+            .map(|(conversation_id, conversation)| ConversationSummary {
+                conversation_id: *conversation_id,
+                model: conversation.config_snapshot().model.clone(),
+                label: conversation.label(),
+                token_usage: conversation.token_usage(),
+            })
+            .collect()
+    }
+
     /// 从管理器中移除指定会话
     /// 从会话映射表中删除指定ID的会话，会话实例的生命周期由Arc引用计数管理
     pub async fn remove_conversation(&self, conversation_id: Uuid) {
@@ -154,7 +206,8 @@ impl ConversationManager {
     /// Fork an existing conversation by dropping the last `drop_last_messages`
     /// user/assistant messages from its transcript and starting a new
     /// conversation with identical configuration (unless overridden by the
-    /// caller's `config`). The new conversation will have a fresh id.
+    /// caller's `config`). The new conversation will have a fresh id and no
+    /// label; see [`Self::fork_conversation_with_label`] to keep one.
     ///
     /// 分叉现有会话功能
     /// 通过删除最后N条用户/助手消息来截断对话历史，然后基于截断后的历史创建新会话
@@ -164,6 +217,21 @@ impl ConversationManager {
         conversation_history: Vec<ResponseItem>, // 原会话的完整对话历史
         num_messages_to_drop: usize,             // 要删除的最后N条消息数量
         config: Config,                          // 新会话的配置（可覆盖原配置）
+    ) -> CodexResult<NewConversation> {
+        self.fork_conversation_with_label(conversation_history, num_messages_to_drop, config, None)
+            .await
+    }
+
+    /// Like [`Self::fork_conversation`], but the new conversation is labeled
+    /// `label`, typically the source conversation's label.
+    ///
+    /// 与 `fork_conversation` 相同，但新会话使用给定的标签（通常沿用原会话的标签）
+    pub async fn fork_conversation_with_label(
+        &self,
+        conversation_history: Vec<ResponseItem>, // 原会话的完整对话历史
+        num_messages_to_drop: usize,             // 要删除的最后N条消息数量
+        config: Config,                          // 新会话的配置（可覆盖原配置）
+        label: Option<String>,                   // 新会话的标签
     ) -> CodexResult<NewConversation> {
         // Compute the prefix up to the cut point.
         // 计算截断点，生成新的对话历史前缀
@@ -173,15 +241,120 @@ impl ConversationManager {
         // Spawn a new conversation with the computed initial history.
         // 使用计算出的初始历史创建新会话
         let auth_manager = self.auth_manager.clone(); // 复用当前的认证管理器
+        let config_snapshot = ResolvedConfigSnapshot::from(&config); // 记录新会话的配置快照
+        let CodexSpawnOk {
+            codex,                       // 新创建的Codex实例
+            session_id: conversation_id, // 新会话ID
+        } = Codex::spawn(config, auth_manager, Some(truncated_history), label).await?; // 传入截断后的历史作为初始历史
+
+        self.finalize_spawn(codex, conversation_id, config_snapshot)
+            .await // 完成新会话的初始化
+    }
+
+    /// Duplicate a live conversation under a fresh id. The duplicate starts
+    /// from the source's full history and its in-flight config, including
+    /// overrides applied after spawn; the two evolve independently afterwards.
+    /// As with [`Self::fork_conversation`], the duplicate records its own
+    /// initial context after the copied history. A source with a task in
+    /// flight may have tool calls still waiting for their output; those calls
+    /// are left out of the copy, since the model API rejects a call without
+    /// its output.
+    ///
+    /// 复制一个正在运行的会话
+    /// 以新的会话ID创建副本，沿用原会话的完整历史和当前生效的配置（包括运行中的覆盖项），之后两者互不影响
+    pub async fn duplicate_conversation(
+        &self,
+        conversation_id: Uuid, // 要复制的会话ID
+    ) -> CodexResult<NewConversation> {
+        let source = self.get_conversation(conversation_id).await?; // 查找原会话
+        let history = drop_dangling_tool_calls(source.history()?); // 原会话的完整历史，丢弃进行中任务尚无结果的工具调用
+        let mut config = source.in_flight_config(); // 原会话当前生效的配置
+        config.experimental_resume = None; // 副本写入自己的回放文件，不续写原会话的文件
+
+        let auth_manager = self.auth_manager.clone(); // 复用当前的认证管理器
+        let config_snapshot = ResolvedConfigSnapshot::from(&config); // 记录副本的配置快照
+        let CodexSpawnOk {
+            codex,                       // 新创建的Codex实例
+            session_id: conversation_id, // 副本的新会话ID
+        } = Codex::spawn(config, auth_manager, Some(history), source.label()).await?; // 以原历史作为初始历史，并沿用原会话的标签
+
+        self.finalize_spawn(codex, conversation_id, config_snapshot)
+            .await // 完成副本的初始化
+    }
+
+    /// Start a new conversation whose initial history is the transcript
+    /// recorded in the rollout file at `path`. The new conversation gets a
+    /// fresh id. When the rollout records a model with a known family, that
+    /// model replaces the one in `config`.
+    ///
+    /// 从回放文件恢复会话
+    /// 读取已持久化的对话记录作为初始历史创建新会话（新会话ID），并尽量沿用原会话的模型
+    pub async fn resume_conversation_from_rollout(
+        &self,
+        path: &Path,        // 回放文件路径
+        mut config: Config, // 新会话的配置
+    ) -> CodexResult<NewConversation> {
+        let saved = RolloutRecorder::load(path, config.rollout_image_inline_max_bytes).await?; // 读取回放文件中的会话记录
+        if let Some(model) = saved.session.model
+            && find_family_for_model(&model).is_some()
+        {
+            // 原模型属于已知模型族时沿用原模型
+            let model_family = config.model_family_for(&model);
+            if let Some(context_window) = config.model_context_window_for(&model, &model_family) {
+                config.model_context_window = Some(context_window);
+            }
+            config.model_pricing = config.model_pricing_for(&model, &model_family);
+            config.model = model;
+            config.model_family = model_family;
+        }
+        // 新会话写入自己的回放文件，而不是追加到原文件
+        config.experimental_resume = None;
+
+        let initial_history = drop_dangling_tool_calls(saved.items); // 丢弃缺少结果的工具调用
+        let config_snapshot = ResolvedConfigSnapshot::from(&config); // 记录新会话的配置快照
         let CodexSpawnOk {
             codex,                       // 新创建的Codex实例
             session_id: conversation_id, // 新会话ID
-        } = Codex::spawn(config, auth_manager, Some(truncated_history)).await?; // 传入截断后的历史作为初始历史
+        } = Codex::spawn(
+            config,
+            self.auth_manager.clone(),
+            Some(initial_history),
+            saved.state.label, // 沿用原会话的标签
+        )
+        .await?;
 
-        self.finalize_spawn(codex, conversation_id).await // 完成新会话的初始化
+        self.finalize_spawn(codex, conversation_id, config_snapshot)
+            .await // 完成新会话的初始化
     }
 }
 
+/// Remove tool calls whose output was never recorded (e.g. the session ended
+/// while the call was running), since the model API rejects a call without
+/// its output.
+///
+/// 丢弃没有对应结果的工具调用，保证恢复的对话历史有效
+fn drop_dangling_tool_calls(items: Vec<ResponseItem>) -> Vec<ResponseItem> {
+    let answered: HashSet<String> = items
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCallOutput { call_id, .. }
+            | ResponseItem::CustomToolCallOutput { call_id, .. } => Some(call_id.clone()),
+            _ => None,
+        })
+        .collect(); // 已有结果的调用ID集合
+    items
+        .into_iter()
+        .filter(|item| match item {
+            ResponseItem::FunctionCall { call_id, .. }
+            | ResponseItem::CustomToolCall { call_id, .. } => answered.contains(call_id),
+            ResponseItem::LocalShellCall { call_id, .. } => call_id
+                .as_ref()
+                .is_some_and(|call_id| answered.contains(call_id)),
+            _ => true,
+        })
+        .collect()
+}
+
 /// Return a prefix of `items` obtained by dropping the last `n` user messages
 /// and all items that follow them.
 ///
@@ -229,6 +402,7 @@ mod tests {
     // 测试模块，验证会话管理器功能
     use super::*;
     use codex_protocol::models::ContentItem; // 导入内容项模型
+    use codex_protocol::models::FunctionCallOutputPayload; // 导入函数调用结果模型
     use codex_protocol::models::ReasoningItemReasoningSummary; // 导入推理摘要模型
     use codex_protocol::models::ResponseItem; // 导入响应项模型
 
@@ -256,6 +430,41 @@ mod tests {
         }
     }
 
+    #[test]
+    /// 测试恢复会话时丢弃缺少结果的工具调用，保留已完成的调用及其结果
+    fn drops_tool_calls_without_output() {
+        let function_call = |call_id: &str| ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        };
+        let output = ResponseItem::FunctionCallOutput {
+            call_id: "answered".to_string(),
+            output: FunctionCallOutputPayload {
+                content: "ok".to_string(),
+                success: Some(true),
+            },
+        };
+        let items = vec![
+            user_msg("u1"),
+            function_call("answered"),
+            output.clone(),
+            assistant_msg("a1"),
+            function_call("dangling"),
+        ];
+
+        assert_eq!(
+            drop_dangling_tool_calls(items),
+            vec![
+                user_msg("u1"),
+                function_call("answered"),
+                output,
+                assistant_msg("a1"),
+            ]
+        );
+    }
+
     #[test]
     /// 测试截断功能只从最后一个用户消息开始删除
     /// 验证截断逻辑正确识别用户消息并从指定位置开始删除所有后续内容
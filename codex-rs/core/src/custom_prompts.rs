@@ -1,4 +1,5 @@
 use codex_protocol::custom_prompts::CustomPrompt;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
@@ -12,6 +13,15 @@ pub fn default_prompts_dir() -> Option<PathBuf> {
         .map(|home| home.join("prompts"))
 }
 
+/// The prompts listed by `Op::ListCustomPrompts` and runnable with
+/// `Op::RunCustomPrompt`: those in [`default_prompts_dir`].
+pub async fn discover_custom_prompts() -> Vec<CustomPrompt> {
+    match default_prompts_dir() {
+        Some(dir) => discover_prompts_in(&dir).await,
+        None => Vec::new(),
+    }
+}
+
 /// Discover prompt files in the given directory, returning entries sorted by name.
 /// Non-files are ignored. If the directory does not exist or cannot be read, returns empty.
 pub async fn discover_prompts_in(dir: &Path) -> Vec<CustomPrompt> {
@@ -63,16 +73,109 @@ pub async fn discover_prompts_in_excluding(
             Ok(s) => s,
             Err(_) => continue,
         };
+        let arguments = prompt_arguments(&content);
         out.push(CustomPrompt {
             name,
             path,
             content,
+            arguments,
         });
     }
     out.sort_by(|a, b| a.name.cmp(&b.name));
     out
 }
 
+/// Error returned by [`render_prompt`] when placeholders have no value.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("missing value for prompt argument(s): {}", .0.join(", "))]
+pub struct MissingPromptArguments(pub Vec<String>);
+
+/// A piece of prompt content: literal text or a `{{name}}` placeholder.
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Split `content` into literal text and placeholders. `\{{` escapes a
+/// literal `{{`, and a `{{ ... }}` whose inside is not a valid argument name
+/// is kept as text.
+fn segments(content: &str) -> Vec<Segment<'_>> {
+    let mut out = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        if rest[..start].ends_with('\\') {
+            out.push(Segment::Text(&rest[..start - 1]));
+            out.push(Segment::Text("{{"));
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push(Segment::Text(&rest[..start]));
+        let inner = &rest[start + 2..];
+        match inner.find("}}") {
+            Some(end) if is_argument_name(inner[..end].trim()) => {
+                out.push(Segment::Placeholder(inner[..end].trim()));
+                rest = &inner[end + 2..];
+            }
+            _ => {
+                out.push(Segment::Text("{{"));
+                rest = inner;
+            }
+        }
+    }
+    out.push(Segment::Text(rest));
+    out
+}
+
+fn is_argument_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Names of the `{{name}}` placeholders in `content`, in order of first
+/// appearance and without duplicates.
+pub fn prompt_arguments(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in segments(content) {
+        if let Segment::Placeholder(name) = segment
+            && !names.iter().any(|n| n == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Substitute every `{{name}}` placeholder in `content` with `arguments[name]`.
+/// Fails, listing the names, if any placeholder has no value; unused
+/// entries in `arguments` are ignored.
+pub fn render_prompt(
+    content: &str,
+    arguments: &HashMap<String, String>,
+) -> Result<String, MissingPromptArguments> {
+    let mut out = String::with_capacity(content.len());
+    let mut missing: Vec<String> = Vec::new();
+    for segment in segments(content) {
+        match segment {
+            Segment::Text(text) => out.push_str(text),
+            Segment::Placeholder(name) => match arguments.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    if !missing.iter().any(|n| n == name) {
+                        missing.push(name.to_string());
+                    }
+                }
+            },
+        }
+    }
+    if missing.is_empty() {
+        Ok(out)
+    } else {
+        Err(MissingPromptArguments(missing))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +227,56 @@ mod tests {
         let names: Vec<String> = found.into_iter().map(|e| e.name).collect();
         assert_eq!(names, vec!["good"]);
     }
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn discovered_prompts_declare_their_arguments() {
+        let tmp = tempdir().expect("create TempDir");
+        let dir = tmp.path();
+        fs::write(
+            dir.join("review.md"),
+            b"Review {{file}} for {{ focus }}; {{file}}",
+        )
+        .unwrap();
+        let found = discover_prompts_in(dir).await;
+        assert_eq!(found[0].arguments, vec!["file", "focus"]);
+    }
+
+    #[test]
+    fn substitutes_every_placeholder() {
+        let rendered = render_prompt(
+            "Review {{file}} for {{ focus }}. Then fix {{file}}.",
+            &args(&[("file", "src/lib.rs"), ("focus", "panics"), ("unused", "x")]),
+        );
+        assert_eq!(
+            rendered,
+            Ok("Review src/lib.rs for panics. Then fix src/lib.rs.".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_arguments_are_reported_by_name() {
+        let err = render_prompt("{{a}} {{b}} {{a}} {{c}}", &args(&[("b", "1")])).unwrap_err();
+        assert_eq!(err, MissingPromptArguments(vec!["a".into(), "c".into()]));
+        assert_eq!(
+            err.to_string(),
+            "missing value for prompt argument(s): a, c"
+        );
+    }
+
+    #[test]
+    fn escaped_and_malformed_braces_are_literal() {
+        let content = r"Use \{{name}} in templates, not {{ two words }} or {{}}: {{name}}";
+        assert_eq!(prompt_arguments(content), vec!["name"]);
+        assert_eq!(
+            render_prompt(content, &args(&[("name", "x")])),
+            Ok("Use {{name}} in templates, not {{ two words }} or {{}}: x".to_string())
+        );
+    }
 }
@@ -0,0 +1,21 @@
+//! The HTTP client used for requests to model providers.
+//! 发往模型提供者的请求所用的 HTTP 客户端。
+
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderValue;
+
+use crate::user_agent::DEFAULT_ORIGINATOR;
+use crate::user_agent::get_codex_user_agent;
+
+/// Builds a client that sends the default `originator` header and the Codex
+/// user agent with every request. Individual requests may still override
+/// either header.
+pub fn create_client() -> reqwest::Client {
+    let mut headers = HeaderMap::new();
+    headers.insert("originator", HeaderValue::from_static(DEFAULT_ORIGINATOR));
+    reqwest::Client::builder()
+        .user_agent(get_codex_user_agent(None))
+        .default_headers(headers)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
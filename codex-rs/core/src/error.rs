@@ -24,10 +24,6 @@ pub enum SandboxErr {
     #[error("seccomp backend error")]
     SeccompBackend(#[from] seccompiler::BackendError),
 
-    /// Command timed out
-    #[error("command timed out")]
-    Timeout,
-
     /// Command was killed by a signal
     #[error("command was killed by a signal")]
     Signal(i32),
@@ -48,6 +44,11 @@ pub enum CodexErr {
     #[error("stream disconnected before completion: {0}")]
     Stream(String, Option<Duration>),
 
+    /// The SSE stream produced no event within the provider's
+    /// `stream_idle_timeout_ms`. Retried like [`CodexErr::Stream`].
+    #[error("stream disconnected before completion: idle timeout waiting for SSE")]
+    StreamIdleTimeout,
+
     #[error("no conversation with id: {0}")]
     ConversationNotFound(Uuid),
 
@@ -58,6 +59,11 @@ pub enum CodexErr {
     #[error("timeout waiting for child process to exit")]
     Timeout,
 
+    /// A shell tool command ran past its timeout and was killed together
+    /// with its process group.
+    #[error("command timed out after {} ms", .0.as_millis())]
+    ExecTimeout(Duration),
+
     /// Returned by run_command_stream when the child could not be spawned (its stdout/stderr pipes
     /// could not be captured). Analogous to the previous `CodexError::Spawn` variant.
     #[error("spawn failed: child stdout/stderr not captured")]
@@ -68,6 +74,12 @@ pub enum CodexErr {
     #[error("interrupted (Ctrl-C)")]
     Interrupted,
 
+    /// Returned when the user cancelled an in-flight tool call with
+    /// `Op::CancelToolCall`. The turn continues and the model is told the call
+    /// was cancelled.
+    #[error("tool call cancelled by user")]
+    ToolCallCancelled,
+
     /// Unexpected HTTP status code.
     #[error("unexpected status {0}: {1}")]
     UnexpectedStatus(StatusCode, String),
@@ -87,6 +99,25 @@ pub enum CodexErr {
     #[error("exceeded retry limit, last status: {0}")]
     RetryLimit(StatusCode),
 
+    /// The provider's circuit breaker is open after repeated failures, so the
+    /// request was not sent.
+    #[error(
+        "provider `{provider}` is failing repeatedly; skipping requests for {}s",
+        retry_in.as_secs()
+    )]
+    CircuitOpen {
+        provider: String,
+        retry_in: Duration,
+    },
+
+    /// The script of a `wire_api = "mock"` provider is missing or invalid.
+    #[error("mock provider script error: {0}")]
+    MockScript(String),
+
+    /// `inject_tool_result` was called on a session that runs its tools.
+    #[error("tool results can only be injected when `stub_tool_calls` is enabled")]
+    ToolResultInjectionDisabled,
+
     /// Agent loop died unexpectedly
     #[error("internal error; agent loop died unexpectedly")]
     InternalAgentDied,
@@ -219,13 +250,23 @@ impl CodexErr {
     pub fn downcast_ref<T: std::any::Any>(&self) -> Option<&T> {
         (self as &dyn std::any::Any).downcast_ref::<T>()
     }
+
+    /// Whether the API rejected the requested model as unknown or retired
+    /// (an error body with code `model_not_found`), so another model might
+    /// serve the turn. Other 404s and rate limits say nothing about the model.
+    pub fn is_model_unavailable(&self) -> bool {
+        match self {
+            CodexErr::UnexpectedStatus(_, body) => serde_json::from_str::<serde_json::Value>(body)
+                .is_ok_and(|body| body["error"]["code"] == "model_not_found"),
+            _ => false,
+        }
+    }
 }
 
 pub fn get_error_message_ui(e: &CodexErr) -> String {
     match e {
         CodexErr::Sandbox(SandboxErr::Denied(_, _, stderr)) => stderr.to_string(),
-        // Timeouts are not sandbox errors from a UX perspective; present them plainly
-        CodexErr::Sandbox(SandboxErr::Timeout) => "error: command timed out".to_string(),
+        CodexErr::ExecTimeout(_) => format!("error: {e}"),
         _ => e.to_string(),
     }
 }
@@ -234,6 +275,32 @@ pub fn get_error_message_ui(e: &CodexErr) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn only_model_not_found_bodies_mean_the_model_is_unavailable() {
+        let model_not_found = r#"{"error":{"message":"The model `gpt-x` does not exist","type":"invalid_request_error","code":"model_not_found"}}"#;
+        assert!(
+            CodexErr::UnexpectedStatus(StatusCode::NOT_FOUND, model_not_found.to_string())
+                .is_model_unavailable()
+        );
+        assert!(
+            CodexErr::UnexpectedStatus(StatusCode::BAD_REQUEST, model_not_found.to_string())
+                .is_model_unavailable()
+        );
+
+        assert!(
+            !CodexErr::UnexpectedStatus(StatusCode::NOT_FOUND, "Not Found".to_string())
+                .is_model_unavailable()
+        );
+        assert!(
+            !CodexErr::UnexpectedStatus(
+                StatusCode::BAD_REQUEST,
+                r#"{"error":{"message":"unknown parameter: model_not_found"}}"#.to_string()
+            )
+            .is_model_unavailable()
+        );
+        assert!(!CodexErr::RetryLimit(StatusCode::TOO_MANY_REQUESTS).is_model_unavailable());
+    }
+
     #[test]
     fn usage_limit_reached_error_formats_plus_plan() {
         let err = UsageLimitReachedError {
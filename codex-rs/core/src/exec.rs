@@ -3,14 +3,17 @@ use std::os::unix::process::ExitStatusExt;
 
 use std::collections::HashMap;
 use std::io;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::ExitStatus;
 use std::time::Duration;
 use std::time::Instant;
 
 use async_channel::Sender;
+use codex_apply_patch::FileProgress;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 
@@ -22,6 +25,7 @@ use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
+use crate::protocol::PatchApplyProgressEvent;
 use crate::protocol::SandboxPolicy;
 use crate::seatbelt::spawn_command_under_seatbelt;
 use crate::spawn::StdioPolicy;
@@ -36,6 +40,10 @@ const SIGKILL_CODE: i32 = 9;
 const TIMEOUT_CODE: i32 = 64;
 const EXIT_CODE_SIGNAL_BASE: i32 = 128; // conventional shell: 128 + signal
 
+/// Exit code reported in `ExecCommandEndEvent` for a command that was killed
+/// because it exceeded its timeout, matching coreutils `timeout(1)`.
+pub const EXEC_TIMEOUT_EXIT_CODE: i32 = 124;
+
 // I/O buffer sizing
 const READ_CHUNK_SIZE: usize = 8192; // bytes per read
 const AGGREGATE_BUFFER_INITIAL_CAPACITY: usize = 8 * 1024; // 8 KiB
@@ -52,12 +60,23 @@ pub struct ExecParams {
     pub env: HashMap<String, String>,
     pub with_escalated_permissions: Option<bool>,
     pub justification: Option<String>,
+    /// Written to the command's stdin, which is then closed. When `None`,
+    /// stdin is `/dev/null`.
+    pub stdin: Option<String>,
 }
 
 impl ExecParams {
     pub fn timeout_duration(&self) -> Duration {
         Duration::from_millis(self.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS))
     }
+
+    fn stdio_policy(&self) -> StdioPolicy {
+        if self.stdin.is_some() {
+            StdioPolicy::RedirectForShellToolWithStdin
+        } else {
+            StdioPolicy::RedirectForShellTool
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -76,6 +95,15 @@ pub struct StdoutStream {
     pub sub_id: String,
     pub call_id: String,
     pub tx_event: Sender<Event>,
+    /// Maximum number of `ExecCommandOutputDelta` events per second for each
+    /// of stdout and stderr. Output produced in between is coalesced into the
+    /// next delta. `None` emits one delta per read.
+    pub max_deltas_per_second: Option<u32>,
+    /// Set when streaming an `apply_patch` run in this directory. Instead of
+    /// output deltas, the applier's per-file progress lines on stderr are
+    /// reported as `PatchApplyProgress` (paths resolved against it) and
+    /// dropped from the captured output.
+    pub apply_patch_cwd: Option<PathBuf>,
 }
 
 pub async fn process_exec_tool_call(
@@ -86,29 +114,35 @@ pub async fn process_exec_tool_call(
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
+    let timeout = params.timeout_duration();
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
         SandboxType::None => exec(params, sandbox_policy, stdout_stream.clone()).await,
         SandboxType::MacosSeatbelt => {
             let timeout = params.timeout_duration();
+            let stdio_policy = params.stdio_policy();
             let ExecParams {
-                command, cwd, env, ..
-            } = params;
-            let child = spawn_command_under_seatbelt(
                 command,
-                sandbox_policy,
                 cwd,
-                StdioPolicy::RedirectForShellTool,
                 env,
-            )
-            .await?;
-            consume_truncated_output(child, timeout, stdout_stream.clone()).await
+                stdin,
+                ..
+            } = params;
+            let child =
+                spawn_command_under_seatbelt(command, sandbox_policy, cwd, stdio_policy, env)
+                    .await?;
+            consume_truncated_output(child, timeout, stdin, stdout_stream.clone()).await
         }
         SandboxType::LinuxSeccomp => {
             let timeout = params.timeout_duration();
+            let stdio_policy = params.stdio_policy();
             let ExecParams {
-                command, cwd, env, ..
+                command,
+                cwd,
+                env,
+                stdin,
+                ..
             } = params;
 
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
@@ -119,12 +153,12 @@ pub async fn process_exec_tool_call(
                 command,
                 sandbox_policy,
                 cwd,
-                StdioPolicy::RedirectForShellTool,
+                stdio_policy,
                 env,
             )
             .await?;
 
-            consume_truncated_output(child, timeout, stdout_stream).await
+            consume_truncated_output(child, timeout, stdin, stdout_stream).await
         }
     };
     let duration = start.elapsed();
@@ -135,7 +169,7 @@ pub async fn process_exec_tool_call(
 
             #[cfg(target_family = "unix")]
             match raw_output.exit_status.signal() {
-                Some(TIMEOUT_CODE) => return Err(CodexErr::Sandbox(SandboxErr::Timeout)),
+                Some(TIMEOUT_CODE) => return Err(CodexErr::ExecTimeout(timeout)),
                 Some(signal) => {
                     return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
                 }
@@ -158,6 +192,7 @@ pub async fn process_exec_tool_call(
                 stderr,
                 aggregated_output: raw_output.aggregated_output.from_utf8_lossy(),
                 duration,
+                peak_rss_bytes: raw_output.peak_rss_bytes,
             })
         }
         Err(err) => {
@@ -198,6 +233,7 @@ struct RawExecToolCallOutput {
     pub stdout: StreamOutput<Vec<u8>>,
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
+    pub peak_rss_bytes: Option<u64>,
 }
 
 impl StreamOutput<String> {
@@ -230,6 +266,9 @@ pub struct ExecToolCallOutput {
     pub stderr: StreamOutput<String>,
     pub aggregated_output: StreamOutput<String>,
     pub duration: Duration,
+    /// Peak resident set size of the command and the processes it waited
+    /// for. Only measured on Linux.
+    pub peak_rss_bytes: Option<u64>,
 }
 
 async fn exec(
@@ -238,8 +277,13 @@ async fn exec(
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.timeout_duration();
+    let stdio_policy = params.stdio_policy();
     let ExecParams {
-        command, cwd, env, ..
+        command,
+        cwd,
+        env,
+        stdin,
+        ..
     } = params;
 
     let (program, args) = command.split_first().ok_or_else(|| {
@@ -255,18 +299,20 @@ async fn exec(
         arg0,
         cwd,
         sandbox_policy,
-        StdioPolicy::RedirectForShellTool,
+        stdio_policy,
         env,
     )
     .await?;
-    consume_truncated_output(child, timeout, stdout_stream).await
+    consume_truncated_output(child, timeout, stdin, stdout_stream).await
 }
 
 /// Consumes the output of a child process, truncating it so it is suitable for
 /// use as the output of a `shell` tool call. Also enforces specified timeout.
+/// `stdin`, if any, is written to the child while its output is read.
 async fn consume_truncated_output(
     mut child: Child,
     timeout: Duration,
+    stdin: Option<String>,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
     // Both stdout and stderr were configured with `Stdio::piped()`
@@ -284,42 +330,58 @@ async fn consume_truncated_output(
         ))
     })?;
 
+    let stdin_writer = child.stdin.take();
+    let stdin_fut = async move {
+        if let (Some(mut writer), Some(input)) = (stdin_writer, stdin) {
+            // The command may exit without reading all of its input, so a
+            // broken pipe is not an error. Dropping `writer` closes stdin.
+            let _ = writer.write_all(input.as_bytes()).await;
+        }
+    };
+
     let (agg_tx, agg_rx) = async_channel::unbounded::<Vec<u8>>();
 
-    let stdout_handle = tokio::spawn(read_capped(
+    // The readers are polled as part of this future rather than spawned, so
+    // once it completes or is dropped (e.g. the tool call is cancelled) no
+    // further `ExecCommandOutputDelta` can be emitted. This is what lets the
+    // caller guarantee that a call's deltas all precede its `ExecCommandEnd`.
+    let stdout_fut = read_capped(
         BufReader::new(stdout_reader),
         stdout_stream.clone(),
         false,
         Some(agg_tx.clone()),
-    ));
-    let stderr_handle = tokio::spawn(read_capped(
+    );
+    let stderr_fut = read_capped(
         BufReader::new(stderr_reader),
         stdout_stream.clone(),
         true,
         Some(agg_tx.clone()),
-    ));
-
-    let exit_status = tokio::select! {
-        result = tokio::time::timeout(timeout, child.wait()) => {
-            match result {
-                Ok(Ok(exit_status)) => exit_status,
-                Ok(e) => e?,
-                Err(_) => {
-                    // timeout
-                    child.start_kill()?;
-                    // Debatable whether `child.wait().await` should be called here.
-                    synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE)
+    );
+    let wait_fut = async {
+        tokio::select! {
+            result = tokio::time::timeout(timeout, wait_with_peak_rss(&mut child)) => {
+                match result {
+                    Ok(Ok(exit)) => Ok(exit),
+                    Ok(Err(e)) => Err(e),
+                    Err(_) => {
+                        // timeout
+                        kill_child_process_group(&mut child)?;
+                        // Debatable whether `child.wait().await` should be called here.
+                        Ok((synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), None))
+                    }
                 }
             }
-        }
-        _ = tokio::signal::ctrl_c() => {
-            child.start_kill()?;
-            synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE)
+            _ = tokio::signal::ctrl_c() => {
+                kill_child_process_group(&mut child)?;
+                Ok((synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), None))
+            }
         }
     };
 
-    let stdout = stdout_handle.await??;
-    let stderr = stderr_handle.await??;
+    let (exit, stdout, stderr, ()) = tokio::join!(wait_fut, stdout_fut, stderr_fut, stdin_fut);
+    let (exit_status, peak_rss_bytes) = exit?;
+    let stdout = stdout?;
+    let stderr = stderr?;
 
     drop(agg_tx);
 
@@ -337,10 +399,84 @@ async fn consume_truncated_output(
         stdout,
         stderr,
         aggregated_output,
+        peak_rss_bytes,
     })
 }
 
-async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
+/// Kills `child` along with every process in its process group, so that
+/// background jobs it started (which may hold the output pipes open) do not
+/// outlive it. Shell tool commands are spawned as process group leaders.
+fn kill_child_process_group(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: `killpg` has no memory-safety preconditions.
+        let rc = unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+        if rc == -1 {
+            let err = io::Error::last_os_error();
+            // The group may already be gone.
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                return Err(err);
+            }
+        }
+    }
+    child.start_kill()
+}
+
+/// Waits for `child` to exit and returns its exit status together with its
+/// peak RSS, when the platform can report it.
+async fn wait_with_peak_rss(child: &mut Child) -> io::Result<(ExitStatus, Option<u64>)> {
+    #[cfg(target_os = "linux")]
+    let peak_rss_bytes = match child.id() {
+        Some(pid) => tokio::task::spawn_blocking(move || wait_for_peak_rss_bytes(pid))
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+    #[cfg(not(target_os = "linux"))]
+    let peak_rss_bytes = None;
+
+    let exit_status = child.wait().await?;
+    Ok((exit_status, peak_rss_bytes))
+}
+
+/// Blocks until `pid` has exited and reads its resource usage, which covers
+/// the process itself and every descendant it reaped. `WNOWAIT` leaves the
+/// process as a zombie so `Child::wait()` still collects its exit status.
+#[cfg(target_os = "linux")]
+fn wait_for_peak_rss_bytes(pid: u32) -> Option<u64> {
+    // SAFETY: both are plain C structs for which all-zero bytes are valid.
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // The libc `waitid` wrapper has no `rusage` argument, but the raw
+        // syscall does.
+        // SAFETY: `info` and `usage` outlive the call.
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_waitid,
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info as *mut libc::siginfo_t,
+                libc::WEXITED | libc::WNOWAIT,
+                &mut usage as *mut libc::rusage,
+            )
+        };
+        if rc == 0 {
+            break;
+        }
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return None;
+        }
+    }
+    // `ru_maxrss` is reported in kilobytes.
+    u64::try_from(usage.ru_maxrss)
+        .ok()
+        .filter(|&kb| kb > 0)
+        .map(|kb| kb * 1024)
+}
+
+async fn read_capped<R: AsyncRead + Unpin>(
     mut reader: R,
     stream: Option<StdoutStream>,
     is_stderr: bool,
@@ -349,51 +485,139 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY);
     let mut tmp = [0u8; READ_CHUNK_SIZE];
     let mut emitted_deltas: usize = 0;
+    // Bytes read but not yet sent as a delta because of the rate limit.
+    let mut pending: Vec<u8> = Vec::new();
+    let min_delta_interval = stream
+        .as_ref()
+        .and_then(|s| s.max_deltas_per_second)
+        .map(|rate| Duration::from_secs(1) / rate.max(1));
+    let mut next_delta_at = Instant::now();
+    // Unfinished last line of an `apply_patch` run's stderr, held back until
+    // its newline so progress lines are only ever matched whole.
+    let mut partial_line: Vec<u8> = Vec::new();
 
     // No caps: append all bytes
 
     loop {
-        let n = reader.read(&mut tmp).await?;
+        let n = if pending.is_empty() {
+            reader.read(&mut tmp).await?
+        } else {
+            // Flush coalesced output once the rate limit allows it, even if
+            // the command goes quiet. `read` is cancel safe.
+            tokio::select! {
+                n = reader.read(&mut tmp) => n?,
+                _ = tokio::time::sleep_until(next_delta_at.into()) => {
+                    if let Some(stream) = &stream {
+                        emit_delta(stream, is_stderr, std::mem::take(&mut pending)).await;
+                        emitted_deltas += 1;
+                    }
+                    next_delta_at = Instant::now() + min_delta_interval.unwrap_or_default();
+                    continue;
+                }
+            }
+        };
         if n == 0 {
             break;
         }
 
+        let mut chunk = &tmp[..n];
+        let other_lines;
         if let Some(stream) = &stream
+            && let Some(cwd) = &stream.apply_patch_cwd
+        {
+            if is_stderr {
+                partial_line.extend_from_slice(chunk);
+                other_lines = report_patch_progress(stream, cwd, &mut partial_line).await;
+                chunk = &other_lines;
+            }
+        } else if let Some(stream) = &stream
             && emitted_deltas < MAX_EXEC_OUTPUT_DELTAS_PER_CALL
         {
-            let chunk = tmp[..n].to_vec();
-            let msg = EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
-                call_id: stream.call_id.clone(),
-                stream: if is_stderr {
-                    ExecOutputStream::Stderr
-                } else {
-                    ExecOutputStream::Stdout
-                },
-                chunk: ByteBuf::from(chunk),
-            });
-            let event = Event {
-                id: stream.sub_id.clone(),
-                msg,
-            };
-            #[allow(clippy::let_unit_value)]
-            let _ = stream.tx_event.send(event).await;
-            emitted_deltas += 1;
+            pending.extend_from_slice(chunk);
+            let now = Instant::now();
+            if now >= next_delta_at {
+                emit_delta(stream, is_stderr, std::mem::take(&mut pending)).await;
+                emitted_deltas += 1;
+                next_delta_at = now + min_delta_interval.unwrap_or_default();
+            }
         }
 
-        if let Some(tx) = &aggregate_tx {
-            let _ = tx.send(tmp[..n].to_vec()).await;
+        if let Some(tx) = &aggregate_tx
+            && !chunk.is_empty()
+        {
+            let _ = tx.send(chunk.to_vec()).await;
         }
 
-        append_all(&mut buf, &tmp[..n]);
+        append_all(&mut buf, chunk);
         // Continue reading to EOF to avoid back-pressure
     }
 
+    if !partial_line.is_empty() {
+        if let Some(tx) = &aggregate_tx {
+            let _ = tx.send(partial_line.clone()).await;
+        }
+        append_all(&mut buf, &partial_line);
+    }
+
+    if let Some(stream) = &stream
+        && !pending.is_empty()
+    {
+        emit_delta(stream, is_stderr, pending).await;
+    }
+
     Ok(StreamOutput {
         text: buf,
         truncated_after_lines: None,
     })
 }
 
+/// Reports each complete progress line in `partial` as a `PatchApplyProgress`
+/// event and returns the other complete lines, leaving any unfinished line
+/// in `partial`.
+async fn report_patch_progress(
+    stream: &StdoutStream,
+    cwd: &Path,
+    partial: &mut Vec<u8>,
+) -> Vec<u8> {
+    let mut other_lines = Vec::new();
+    while let Some(end) = partial.iter().position(|&b| b == b'\n') {
+        let line: Vec<u8> = partial.drain(..=end).collect();
+        let Some(progress) = FileProgress::from_report_line(&String::from_utf8_lossy(&line)) else {
+            other_lines.extend_from_slice(&line);
+            continue;
+        };
+        let event = Event {
+            id: stream.sub_id.clone(),
+            msg: EventMsg::PatchApplyProgress(PatchApplyProgressEvent {
+                call_id: stream.call_id.clone(),
+                path: cwd.join(progress.path),
+                index: progress.index,
+                total: progress.total,
+            }),
+        };
+        let _ = stream.tx_event.send(event).await;
+    }
+    other_lines
+}
+
+async fn emit_delta(stream: &StdoutStream, is_stderr: bool, chunk: Vec<u8>) {
+    let msg = EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+        call_id: stream.call_id.clone(),
+        stream: if is_stderr {
+            ExecOutputStream::Stderr
+        } else {
+            ExecOutputStream::Stdout
+        },
+        chunk: ByteBuf::from(chunk),
+    });
+    let event = Event {
+        id: stream.sub_id.clone(),
+        msg,
+    };
+    #[allow(clippy::let_unit_value)]
+    let _ = stream.tx_event.send(event).await;
+}
+
 #[cfg(unix)]
 fn synthetic_exit_status(code: i32) -> ExitStatus {
     use std::os::unix::process::ExitStatusExt;
@@ -13,8 +13,9 @@ pub struct ExecCommandParams {
     #[serde(default = "max_output_tokens")]
     pub(crate) max_output_tokens: u64,
 
-    #[serde(default = "default_shell")]
-    pub(crate) shell: String,
+    /// Explicit shell program; `None` uses the configured `exec_shell`.
+    #[serde(default)]
+    pub(crate) shell: Option<String>,
 
     #[serde(default = "default_login")]
     pub(crate) login: bool,
@@ -32,10 +33,6 @@ fn default_login() -> bool {
     true
 }
 
-fn default_shell() -> String {
-    "/bin/bash".to_string()
-}
-
 #[derive(Debug, Deserialize, Serialize)]
 pub struct WriteStdinParams {
     pub(crate) session_id: SessionId,
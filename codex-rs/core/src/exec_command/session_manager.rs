@@ -15,6 +15,7 @@ use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio::time::timeout;
 
+use crate::config_types::ShellKind;
 use crate::exec_command::exec_command_params::ExecCommandParams;
 use crate::exec_command::exec_command_params::WriteStdinParams;
 use crate::exec_command::exec_command_session::ExecCommandSession;
@@ -84,6 +85,7 @@ impl SessionManager {
     pub async fn handle_exec_command_request(
         &self,
         params: ExecCommandParams,
+        default_shell: ShellKind,
     ) -> Result<ExecCommandOutput, String> {
         // Allocate a session id.
         let session_id = SessionId(
@@ -91,15 +93,14 @@ impl SessionManager {
                 .fetch_add(1, std::sync::atomic::Ordering::SeqCst),
         );
 
-        let (session, mut exit_rx) =
-            create_exec_command_session(params.clone())
-                .await
-                .map_err(|err| {
-                    format!(
-                        "failed to create exec command session for session id {}: {err}",
-                        session_id.0
-                    )
-                })?;
+        let (session, mut exit_rx) = create_exec_command_session(params.clone(), default_shell)
+            .await
+            .map_err(|err| {
+                format!(
+                    "failed to create exec command session for session id {}: {err}",
+                    session_id.0
+                )
+            })?;
 
         // Insert into session map.
         let mut output_rx = session.output_receiver();
@@ -240,9 +241,28 @@ impl SessionManager {
     }
 }
 
+/// Argv that runs `cmd` through `shell`. `login` loads the user's profile.
+fn shell_command_argv(shell: ShellKind, cmd: String, login: bool) -> Vec<String> {
+    let program = shell.program().to_string();
+    match shell {
+        ShellKind::Bash | ShellKind::Sh => {
+            let shell_mode_opt = if login { "-lc" } else { "-c" };
+            vec![program, shell_mode_opt.to_string(), cmd]
+        }
+        ShellKind::Pwsh if login => vec![program, "-Command".to_string(), cmd],
+        ShellKind::Pwsh => vec![
+            program,
+            "-NoProfile".to_string(),
+            "-Command".to_string(),
+            cmd,
+        ],
+    }
+}
+
 /// Spawn PTY and child process per spawn_exec_command_session logic.
 async fn create_exec_command_session(
     params: ExecCommandParams,
+    default_shell: ShellKind,
 ) -> anyhow::Result<(ExecCommandSession, oneshot::Receiver<i32>)> {
     let ExecCommandParams {
         cmd,
@@ -264,10 +284,15 @@ async fn create_exec_command_session(
     })?;
 
     // Spawn a shell into the pty
-    let mut command_builder = CommandBuilder::new(shell);
-    let shell_mode_opt = if login { "-lc" } else { "-c" };
-    command_builder.arg(shell_mode_opt);
-    command_builder.arg(cmd);
+    let argv = match shell {
+        Some(shell) => {
+            let shell_mode_opt = if login { "-lc" } else { "-c" };
+            vec![shell, shell_mode_opt.to_string(), cmd]
+        }
+        None => shell_command_argv(default_shell, cmd, login),
+    };
+    let mut command_builder = CommandBuilder::new(&argv[0]);
+    command_builder.args(&argv[1..]);
 
     let mut child = pair.slave.spawn_command(command_builder)?;
     // Obtain a killer that can signal the process independently of `.wait()`.
@@ -489,11 +514,11 @@ PY"#
             cmd,
             yield_time_ms: 3_000,
             max_output_tokens: 1_000, // large enough to avoid truncation here
-            shell: "/bin/bash".to_string(),
+            shell: Some("/bin/bash".to_string()),
             login: false,
         };
         let initial_output = match session_manager
-            .handle_exec_command_request(params.clone())
+            .handle_exec_command_request(params.clone(), ShellKind::Bash)
             .await
         {
             Ok(v) => v,
@@ -566,6 +591,61 @@ PY"#
         );
     }
 
+    /// The configured `exec_shell` wraps commands that rely on shell
+    /// features when the model does not pick a shell itself.
+    #[cfg(unix)]
+    #[allow(clippy::print_stderr)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn configured_exec_shell_wraps_command() {
+        for (shell, expected) in [(ShellKind::Sh, "sh 42"), (ShellKind::Bash, "bash 42")] {
+            let session_manager = SessionManager::default();
+            // `$0` names the shell running the command; `$((...))` needs a shell.
+            // The short sleep lets the reader subscribe before output is printed.
+            let params = ExecCommandParams {
+                cmd: "sleep 0.2; echo \"$0 $((20 + 22))\"".to_string(),
+                yield_time_ms: 5_000,
+                max_output_tokens: 1_000,
+                shell: None,
+                login: false,
+            };
+            let output = match session_manager
+                .handle_exec_command_request(params, shell)
+                .await
+            {
+                Ok(v) => v,
+                Err(e) => {
+                    if e.contains("openpty") || e.contains("Operation not permitted") {
+                        eprintln!("skipping test due to restricted PTY: {e}");
+                        return;
+                    }
+                    panic!("exec request failed unexpectedly: {e}");
+                }
+            };
+
+            assert!(matches!(output.exit_status, ExitStatus::Exited(0)));
+            assert!(
+                output
+                    .output
+                    .lines()
+                    .any(|line| line.trim_end() == expected),
+                "expected `{expected}` in output, got: {:?}",
+                output.output
+            );
+        }
+    }
+
+    #[test]
+    fn pwsh_argv_skips_profile_unless_login() {
+        assert_eq!(
+            shell_command_argv(ShellKind::Pwsh, "Get-Date".to_string(), false),
+            vec!["pwsh", "-NoProfile", "-Command", "Get-Date"]
+        );
+        assert_eq!(
+            shell_command_argv(ShellKind::Pwsh, "Get-Date".to_string(), true),
+            vec!["pwsh", "-Command", "Get-Date"]
+        );
+    }
+
     #[cfg(unix)]
     fn extract_monotonic_numbers(s: &str) -> Vec<i64> {
         s.lines()
@@ -3,6 +3,15 @@ use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyInherit;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::Path;
+
+/// Project-relative location of the per-project command environment file,
+/// used when `shell_environment_policy.command_env_file` is not set.
+pub const DEFAULT_COMMAND_ENV_FILE: &str = ".codex/command.env";
+
+/// Prefix reserved for Codex's own configuration. Variables with this prefix
+/// are never taken from the command environment file.
+const RESERVED_ENV_VAR_PREFIX: &str = "CODEX_";
 
 /// Construct an environment map based on the rules in the specified policy. The
 /// resulting map can be passed directly to `Command::envs()` after calling
@@ -15,7 +24,58 @@ pub fn create_env(policy: &ShellEnvironmentPolicy) -> HashMap<String, String> {
     populate_env(std::env::vars(), policy)
 }
 
+/// Like [`create_env`], but additionally layers in the variables from the
+/// project's command environment file (see
+/// [`ShellEnvironmentPolicy::command_env_file`]), resolved against `cwd`.
+///
+/// The file is only read here, so its variables reach commands the agent runs
+/// but never Codex's own process environment.
+pub fn create_command_env(policy: &ShellEnvironmentPolicy, cwd: &Path) -> HashMap<String, String> {
+    let command_env = load_command_env_file(policy, cwd);
+    populate_env_with_command_env(std::env::vars(), command_env, policy)
+}
+
+/// Read the `.env`-style command environment file for `cwd`. A missing file
+/// yields no variables; a malformed one is logged and ignored.
+fn load_command_env_file(policy: &ShellEnvironmentPolicy, cwd: &Path) -> Vec<(String, String)> {
+    let path = cwd.join(
+        policy
+            .command_env_file
+            .as_deref()
+            .unwrap_or(Path::new(DEFAULT_COMMAND_ENV_FILE)),
+    );
+    if !path.is_file() {
+        return Vec::new();
+    }
+    match dotenvy::from_path_iter(&path) {
+        Ok(iter) => iter
+            .filter_map(|entry| match entry {
+                Ok(pair) => Some(pair),
+                Err(e) => {
+                    tracing::warn!("skipping invalid entry in {}: {e}", path.display());
+                    None
+                }
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("failed to read {}: {e}", path.display());
+            Vec::new()
+        }
+    }
+}
+
 fn populate_env<I>(vars: I, policy: &ShellEnvironmentPolicy) -> HashMap<String, String>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    populate_env_with_command_env(vars, Vec::new(), policy)
+}
+
+fn populate_env_with_command_env<I>(
+    vars: I,
+    command_env: Vec<(String, String)>,
+    policy: &ShellEnvironmentPolicy,
+) -> HashMap<String, String>
 where
     I: IntoIterator<Item = (String, String)>,
 {
@@ -41,12 +101,16 @@ where
     };
 
     // Step 2 – Apply the default exclude if not disabled.
-    if !policy.ignore_default_excludes {
-        let default_excludes = vec![
+    let default_excludes = if policy.ignore_default_excludes {
+        Vec::new()
+    } else {
+        vec![
             EnvironmentVariablePattern::new_case_insensitive("*KEY*"),
             EnvironmentVariablePattern::new_case_insensitive("*SECRET*"),
             EnvironmentVariablePattern::new_case_insensitive("*TOKEN*"),
-        ];
+        ]
+    };
+    if !default_excludes.is_empty() {
         env_map.retain(|k, _| !matches_any(k, &default_excludes));
     }
 
@@ -55,6 +119,19 @@ where
         env_map.retain(|k, _| !matches_any(k, &policy.exclude));
     }
 
+    // Step 3b – Layer in the project's command environment file. These go
+    // through the same secret filters as inherited variables so the file
+    // cannot smuggle in credentials, and may not touch `CODEX_*`.
+    for (key, val) in command_env {
+        if key.starts_with(RESERVED_ENV_VAR_PREFIX)
+            || matches_any(&key, &default_excludes)
+            || matches_any(&key, &policy.exclude)
+        {
+            continue;
+        }
+        env_map.insert(key, val);
+    }
+
     // Step 4 – Apply user-provided overrides.
     for (key, val) in &policy.r#set {
         env_map.insert(key.clone(), val.clone());
@@ -172,6 +249,35 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_command_env_is_filtered_and_yields_to_set() {
+        let vars = make_vars(&[("PATH", "/usr/bin")]);
+        let command_env = make_vars(&[
+            ("PROJECT_MODE", "dev"),
+            ("SHARED", "from-file"),
+            ("DB_TOKEN", "t"),
+            ("CODEX_HOME", "/elsewhere"),
+            ("AWS_REGION", "us-east-1"),
+        ]);
+
+        let mut policy = ShellEnvironmentPolicy {
+            inherit: ShellEnvironmentPolicyInherit::All,
+            exclude: vec![EnvironmentVariablePattern::new_case_insensitive("AWS_*")],
+            ..Default::default()
+        };
+        policy
+            .r#set
+            .insert("SHARED".to_string(), "from-config".to_string());
+
+        let result = populate_env_with_command_env(vars, command_env, &policy);
+        let expected: HashMap<String, String> = hashmap! {
+            "PATH".to_string() => "/usr/bin".to_string(),
+            "PROJECT_MODE".to_string() => "dev".to_string(),
+            "SHARED".to_string() => "from-config".to_string(),
+        };
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn test_inherit_none() {
         let vars = make_vars(&[("PATH", "/usr/bin"), ("HOME", "/home")]);
@@ -0,0 +1,302 @@
+//! Expansion of `@path` file mentions in user input, as selected by
+//! `file_mention_mode`.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use tokio::io::AsyncReadExt;
+
+use crate::config_types::FileMentionMode;
+use crate::protocol::InputItem;
+
+/// Number of leading lines of a file included in its summary.
+const SUMMARY_PREVIEW_LINES: usize = 20;
+
+/// Bytes read from the start of a file for its summary preview.
+const SUMMARY_PREVIEW_MAX_BYTES: u64 = 4 * 1024;
+
+/// Bytes kept of each summary preview line; longer lines are cut with `…`.
+const SUMMARY_PREVIEW_LINE_BYTES: usize = 200;
+
+/// Characters that commonly follow a mention in prose, e.g. `see @a.rs.`.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', '"', '\''];
+
+/// Attach the contents or a summary of every file mentioned as `@path` in
+/// the text items of `items`, resolving relative paths against `cwd`.
+///
+/// Each file is attached once, as an extra text item after the user's own
+/// items. Mentions that do not name a regular file are left alone, and in
+/// [`FileMentionMode::Path`] the input is returned unchanged.
+pub(crate) async fn expand_file_mentions(
+    items: Vec<InputItem>,
+    cwd: &Path,
+    mode: FileMentionMode,
+    max_inline_bytes: usize,
+) -> Vec<InputItem> {
+    if mode == FileMentionMode::Path {
+        return items;
+    }
+
+    let mut seen = HashSet::new();
+    let mut attachments = Vec::new();
+    for item in &items {
+        let InputItem::Text { text } = item else {
+            continue;
+        };
+        for token in text.split_whitespace() {
+            let Some(mention) = token.strip_prefix('@') else {
+                continue;
+            };
+            let Some(mention) = resolve_mention(mention, cwd).await else {
+                continue;
+            };
+            if seen.insert(mention.to_string())
+                && let Some(text) = describe_file(mention, cwd, mode, max_inline_bytes).await
+            {
+                attachments.push(InputItem::Text { text });
+            }
+        }
+    }
+
+    let mut items = items;
+    items.extend(attachments);
+    items
+}
+
+/// Returns the part of `mention` that names a regular file, dropping trailing
+/// punctuation if the mention as written does not.
+async fn resolve_mention<'a>(mention: &'a str, cwd: &Path) -> Option<&'a str> {
+    for candidate in [mention, mention.trim_end_matches(TRAILING_PUNCTUATION)] {
+        if !candidate.is_empty()
+            && tokio::fs::metadata(cwd.join(candidate))
+                .await
+                .is_ok_and(|metadata| metadata.is_file())
+        {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+async fn describe_file(
+    mention: &str,
+    cwd: &Path,
+    mode: FileMentionMode,
+    max_inline_bytes: usize,
+) -> Option<String> {
+    let path = cwd.join(mention);
+    let size = tokio::fs::metadata(&path).await.ok()?.len();
+
+    if mode == FileMentionMode::Inline
+        && size <= max_inline_bytes as u64
+        && let Ok(contents) = tokio::fs::read_to_string(&path).await
+    {
+        let contents = contents.strip_suffix('\n').unwrap_or(&contents);
+        return Some(format!("<file path=\"{mention}\">\n{contents}\n</file>"));
+    }
+
+    // Too large to inline, not UTF-8, or summary mode. Only the head of the
+    // file is read, so a huge file or a single huge line stays cheap.
+    let mut head = Vec::new();
+    tokio::fs::File::open(&path)
+        .await
+        .ok()?
+        .take(SUMMARY_PREVIEW_MAX_BYTES)
+        .read_to_end(&mut head)
+        .await
+        .ok()?;
+    let head = String::from_utf8_lossy(&head);
+    let mut summary = format!("<file_summary path=\"{mention}\" bytes=\"{size}\">\n");
+    for line in head.lines().take(SUMMARY_PREVIEW_LINES) {
+        summary.push_str(truncate_line(line));
+        if line.len() > SUMMARY_PREVIEW_LINE_BYTES {
+            summary.push('…');
+        }
+        summary.push('\n');
+    }
+    summary.push_str("</file_summary>");
+    Some(summary)
+}
+
+/// The longest prefix of `line` within `SUMMARY_PREVIEW_LINE_BYTES` that
+/// ends on a character boundary.
+fn truncate_line(line: &str) -> &str {
+    let mut end = line.len().min(SUMMARY_PREVIEW_LINE_BYTES);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn text(text: &str) -> InputItem {
+        InputItem::Text {
+            text: text.to_string(),
+        }
+    }
+
+    fn texts(items: &[InputItem]) -> Vec<&str> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                InputItem::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn repo_with_file() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn path_mode_sends_the_mention_only() {
+        let dir = repo_with_file();
+
+        let items = expand_file_mentions(
+            vec![text("look at @src/lib.rs")],
+            dir.path(),
+            FileMentionMode::Path,
+            1024,
+        )
+        .await;
+
+        assert_eq!(texts(&items), vec!["look at @src/lib.rs"]);
+    }
+
+    #[tokio::test]
+    async fn inline_mode_attaches_file_contents() {
+        let dir = repo_with_file();
+
+        let items = expand_file_mentions(
+            vec![text("look at @src/lib.rs, please")],
+            dir.path(),
+            FileMentionMode::Inline,
+            1024,
+        )
+        .await;
+
+        assert_eq!(
+            texts(&items),
+            vec![
+                "look at @src/lib.rs, please",
+                "<file path=\"src/lib.rs\">\nfn a() {}\nfn b() {}\n</file>",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn inline_mode_summarizes_files_over_the_size_guard() {
+        let dir = repo_with_file();
+
+        let items = expand_file_mentions(
+            vec![text("@src/lib.rs")],
+            dir.path(),
+            FileMentionMode::Inline,
+            8,
+        )
+        .await;
+
+        assert_eq!(
+            texts(&items)[1],
+            "<file_summary path=\"src/lib.rs\" bytes=\"20\">\nfn a() {}\nfn b() {}\n</file_summary>"
+        );
+    }
+
+    #[tokio::test]
+    async fn summary_mode_attaches_size_and_leading_lines() {
+        let dir = TempDir::new().unwrap();
+        let long: String = (1..=30).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(dir.path().join("notes.txt"), &long).unwrap();
+
+        let items = expand_file_mentions(
+            vec![text("summarize @notes.txt")],
+            dir.path(),
+            FileMentionMode::Summary,
+            1024 * 1024,
+        )
+        .await;
+
+        let summary = texts(&items)[1];
+        assert!(
+            summary.starts_with(&format!(
+                "<file_summary path=\"notes.txt\" bytes=\"{}\">\nline 1\n",
+                long.len()
+            )),
+            "{summary}"
+        );
+        assert!(summary.ends_with("line 20\n</file_summary>"), "{summary}");
+        assert!(!summary.contains("line 21"), "{summary}");
+    }
+
+    #[tokio::test]
+    async fn unknown_and_repeated_mentions_are_attached_at_most_once() {
+        let dir = repo_with_file();
+
+        let items = expand_file_mentions(
+            vec![
+                text("@src/lib.rs and @missing.rs and user@example.com"),
+                text("again @src/lib.rs"),
+            ],
+            dir.path(),
+            FileMentionMode::Inline,
+            1024,
+        )
+        .await;
+
+        assert_eq!(items.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn summary_of_a_single_huge_line_is_capped() {
+        let dir = TempDir::new().unwrap();
+        let huge = "x".repeat(10 * 1024 * 1024);
+        std::fs::write(dir.path().join("min.js"), &huge).unwrap();
+
+        let items = expand_file_mentions(
+            vec![text("@min.js")],
+            dir.path(),
+            FileMentionMode::Inline,
+            1024,
+        )
+        .await;
+
+        let summary = texts(&items)[1];
+        assert_eq!(
+            summary,
+            format!(
+                "<file_summary path=\"min.js\" bytes=\"{}\">\n{}…\n</file_summary>",
+                huge.len(),
+                "x".repeat(SUMMARY_PREVIEW_LINE_BYTES)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn summary_preview_reads_a_bounded_number_of_bytes() {
+        let dir = TempDir::new().unwrap();
+        let long: String = (0..20).map(|_| format!("{}\n", "y".repeat(1000))).collect();
+        std::fs::write(dir.path().join("wide.txt"), &long).unwrap();
+
+        let items = expand_file_mentions(
+            vec![text("@wide.txt")],
+            dir.path(),
+            FileMentionMode::Summary,
+            1024,
+        )
+        .await;
+
+        let summary = texts(&items)[1];
+        let preview_lines = summary.lines().count() - 2;
+        assert_eq!(preview_lines, 5, "{summary}");
+        assert!(summary.len() < 2 * 1024, "{summary}");
+    }
+}
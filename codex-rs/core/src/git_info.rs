@@ -28,6 +28,24 @@ pub struct GitInfo {
     pub repository_url: Option<String>,
 }
 
+/// Branch, upstream and working tree state of a repository, as reported by
+/// `git status`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RepoStatus {
+    /// Current branch name; `None` when HEAD is detached.
+    pub branch: Option<String>,
+    /// Commit HEAD points at; `None` in a repository without commits.
+    pub commit: Option<String>,
+    /// Upstream the current branch tracks, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Commits on the branch that are not on its upstream.
+    pub ahead: usize,
+    /// Commits on the upstream that are not on the branch.
+    pub behind: usize,
+    /// Whether there are staged, unstaged or untracked changes.
+    pub dirty: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GitDiffToRemote {
     pub sha: GitSha,
@@ -109,6 +127,165 @@ pub async fn git_diff_to_remote(cwd: &Path) -> Option<GitDiffToRemote> {
     })
 }
 
+/// Branch names commonly used as a repository's default branch, in order of preference.
+const COMMON_DEFAULT_BRANCHES: [&str; 3] = ["main", "master", "trunk"];
+
+/// Detect the repository's default branch (e.g. `main`) for review-style workflows.
+///
+/// Resolution order:
+/// 1) `refs/remotes/origin/HEAD`, when the remote HEAD has been recorded locally
+/// 2) The first of `main`, `master`, `trunk` that exists locally or on `origin`
+/// 3) The upstream of the current branch, with the remote prefix stripped
+///
+/// Never contacts the network, so repositories without a remote are handled by
+/// the local fallbacks. Returns None outside a git repository.
+pub async fn default_branch(cwd: &Path) -> Option<String> {
+    if !is_inside_git_repo(cwd) {
+        return None;
+    }
+
+    if let Some(output) = run_git_command_with_timeout(
+        &["symbolic-ref", "--quiet", "refs/remotes/origin/HEAD"],
+        cwd,
+    )
+    .await
+        && output.status.success()
+        && let Ok(sym) = String::from_utf8(output.stdout)
+        && let Some(name) = sym.trim().strip_prefix("refs/remotes/origin/")
+        && !name.is_empty()
+    {
+        return Some(name.to_string());
+    }
+
+    for candidate in COMMON_DEFAULT_BRANCHES {
+        for reference in [
+            format!("refs/heads/{candidate}"),
+            format!("refs/remotes/origin/{candidate}"),
+        ] {
+            if let Some(verify) =
+                run_git_command_with_timeout(&["rev-parse", "--verify", "--quiet", &reference], cwd)
+                    .await
+                && verify.status.success()
+            {
+                return Some(candidate.to_string());
+            }
+        }
+    }
+
+    // e.g. `origin/develop`; a local upstream (remote ".") has no prefix.
+    let output =
+        run_git_command_with_timeout(&["rev-parse", "--abbrev-ref", "@{upstream}"], cwd).await?;
+    if !output.status.success() {
+        return None;
+    }
+    let upstream = String::from_utf8(output.stdout).ok()?;
+    let upstream = upstream.trim();
+    let name = upstream
+        .split_once('/')
+        .map_or(upstream, |(_, branch)| branch);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// A stash created by [`auto_stash`], to be handed back to [`restore`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StashHandle {
+    cwd: PathBuf,
+    /// Commit of the stash entry, or `None` if the tree was clean and nothing
+    /// was stashed.
+    stash: Option<GitSha>,
+}
+
+impl StashHandle {
+    /// Commit of the stash entry, usable with `git stash apply <sha>` to
+    /// recover by hand. `None` if nothing was stashed.
+    pub fn stash_sha(&self) -> Option<&GitSha> {
+        self.stash.as_ref()
+    }
+}
+
+/// Stash all uncommitted changes in `cwd`, untracked files included, so the
+/// working tree is clean afterwards. A clean tree is left alone and yields a
+/// handle that [`restore`] treats as a no-op.
+pub async fn auto_stash(cwd: &Path, message: &str) -> std::io::Result<StashHandle> {
+    if !is_inside_git_repo(cwd) {
+        return Err(std::io::Error::other(format!(
+            "{} is not inside a git repository",
+            cwd.display()
+        )));
+    }
+
+    let status = run_git_checked(&["status", "--porcelain"], cwd).await?;
+    if status.trim().is_empty() {
+        return Ok(StashHandle {
+            cwd: cwd.to_path_buf(),
+            stash: None,
+        });
+    }
+
+    run_git_checked(
+        &["stash", "push", "--include-untracked", "--message", message],
+        cwd,
+    )
+    .await?;
+    let sha = run_git_checked(&["rev-parse", "stash@{0}"], cwd).await?;
+    Ok(StashHandle {
+        cwd: cwd.to_path_buf(),
+        stash: Some(GitSha::new(sha.trim())),
+    })
+}
+
+/// Re-apply the changes stashed by [`auto_stash`] (including the index) and
+/// drop the stash entry. Other stashes created in the meantime are left
+/// untouched. If applying conflicts, git keeps the entry so nothing is lost.
+pub async fn restore(handle: StashHandle) -> std::io::Result<()> {
+    let Some(sha) = handle.stash else {
+        return Ok(());
+    };
+    let list = run_git_checked(&["stash", "list", "--format=%H"], &handle.cwd).await?;
+    let Some(index) = list.lines().position(|line| line.trim() == sha.0) else {
+        return Err(std::io::Error::other(format!(
+            "stash {} no longer exists",
+            sha.0
+        )));
+    };
+    let stash_ref = format!("stash@{{{index}}}");
+    run_git_checked(&["stash", "pop", "--index", &stash_ref], &handle.cwd).await?;
+    Ok(())
+}
+
+/// Files tracked by git under `root`, as absolute paths, or `None` when
+/// `root` is not inside a git work tree or git does not answer in time.
+pub async fn tracked_files(root: &Path) -> Option<HashSet<PathBuf>> {
+    let output = run_git_command_with_timeout(&["ls-files", "-z"], root).await?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        output
+            .stdout
+            .split(|b| *b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| root.join(String::from_utf8_lossy(name).as_ref()))
+            .collect(),
+    )
+}
+
+/// Run a git command with [`run_git_command_with_timeout`] and return its
+/// stdout, turning a failure into an error that carries git's stderr.
+async fn run_git_checked(args: &[&str], cwd: &Path) -> std::io::Result<String> {
+    let output = run_git_command_with_timeout(args, cwd)
+        .await
+        .ok_or_else(|| std::io::Error::other(format!("git {} did not complete", args.join(" "))))?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 /// Run a git command with a timeout to prevent blocking on large repositories
 async fn run_git_command_with_timeout(args: &[&str], cwd: &Path) -> Option<std::process::Output> {
     let result = timeout(
@@ -439,6 +616,71 @@ async fn diff_against_sha(cwd: &Path, sha: &GitSha) -> Option<String> {
     Some(diff)
 }
 
+/// Report the branch, upstream, ahead/behind counts and dirty state of the
+/// repository containing `cwd` with a single `git status` call. Returns None
+/// outside a git repository, if git fails, or if it times out.
+///
+/// It never takes the index lock.
+pub async fn repo_status(cwd: &Path) -> Option<RepoStatus> {
+    let output = run_git_command_with_timeout(
+        &[
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v2",
+            "--branch",
+        ],
+        cwd,
+    )
+    .await?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_porcelain_v2_status(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parse the output of `git status --porcelain=v2 --branch`.
+fn parse_porcelain_v2_status(output: &str) -> RepoStatus {
+    let mut status = RepoStatus {
+        branch: None,
+        commit: None,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        dirty: false,
+    };
+    for line in output.lines() {
+        let Some(header) = line.strip_prefix("# ") else {
+            // Every non-header line is a changed, unmerged or untracked entry.
+            status.dirty |= !line.is_empty();
+            continue;
+        };
+        match header.split_once(' ') {
+            Some(("branch.oid", oid)) if oid != "(initial)" => {
+                status.commit = Some(oid.to_string());
+            }
+            Some(("branch.head", head)) if head != "(detached)" => {
+                status.branch = Some(head.to_string());
+            }
+            Some(("branch.upstream", upstream)) => {
+                status.upstream = Some(upstream.to_string());
+            }
+            Some(("branch.ab", ab)) => {
+                for count in ab.split_whitespace() {
+                    if let Some(ahead) = count.strip_prefix('+') {
+                        status.ahead = ahead.parse().unwrap_or(0);
+                    } else if let Some(behind) = count.strip_prefix('-') {
+                        status.behind = behind.parse().unwrap_or(0);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    status
+}
+
 /// Resolve the path that should be used for trust checks. Similar to
 /// `[utils::is_inside_git_repo]`, but resolves to the root of the main
 /// repository. Handles worktrees.
@@ -572,6 +814,20 @@ mod tests {
         (repo_path, branch)
     }
 
+    #[tokio::test]
+    async fn test_tracked_files() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        fs::write(repo_path.join("untracked.txt"), "").expect("Failed to write file");
+
+        let files = tracked_files(&repo_path).await.expect("tracked files");
+        assert_eq!(files, HashSet::from([repo_path.join("test.txt")]));
+
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir(&outside).expect("Failed to create dir");
+        assert_eq!(tracked_files(&outside).await, None);
+    }
+
     #[tokio::test]
     async fn test_collect_git_info_non_git_directory() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -889,6 +1145,220 @@ mod tests {
         assert!(state.diff.contains("updated"));
     }
 
+    async fn run_git(repo_path: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(repo_path)
+            .output()
+            .await
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {args:?} failed: {output:?}");
+    }
+
+    async fn add_bare_origin(temp_dir: &TempDir, repo_path: &Path) {
+        let remote_path = temp_dir.path().join("remote.git");
+        run_git(
+            temp_dir.path(),
+            &["init", "--bare", remote_path.to_str().unwrap()],
+        )
+        .await;
+        run_git(
+            repo_path,
+            &["remote", "add", "origin", remote_path.to_str().unwrap()],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_default_branch_non_git_directory() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        assert_eq!(default_branch(temp_dir.path()).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_default_branch_prefers_origin_head() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        run_git(&repo_path, &["branch", "-M", "develop"]).await;
+        // A local `main` must not win over the recorded remote HEAD.
+        run_git(&repo_path, &["branch", "main"]).await;
+        add_bare_origin(&temp_dir, &repo_path).await;
+        run_git(&repo_path, &["push", "-u", "origin", "develop"]).await;
+        run_git(&repo_path, &["remote", "set-head", "origin", "develop"]).await;
+
+        assert_eq!(
+            default_branch(&repo_path).await,
+            Some("develop".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_branch_falls_back_to_common_name_without_remote() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        run_git(&repo_path, &["branch", "-M", "trunk"]).await;
+        run_git(&repo_path, &["checkout", "-b", "feature"]).await;
+
+        assert_eq!(default_branch(&repo_path).await, Some("trunk".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_default_branch_falls_back_to_upstream() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        run_git(&repo_path, &["branch", "-M", "release"]).await;
+
+        // No remote and no common branch names: nothing to detect.
+        assert_eq!(default_branch(&repo_path).await, None);
+
+        add_bare_origin(&temp_dir, &repo_path).await;
+        run_git(&repo_path, &["push", "-u", "origin", "release"]).await;
+
+        assert_eq!(
+            default_branch(&repo_path).await,
+            Some("release".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn auto_stash_cleans_dirty_tree_and_restore_brings_changes_back() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        fs::write(repo_path.join("test.txt"), "edited").unwrap();
+        fs::write(repo_path.join("new.txt"), "untracked").unwrap();
+        run_git(&repo_path, &["stash", "push", "--message", "unrelated"]).await;
+        fs::write(repo_path.join("test.txt"), "edited again").unwrap();
+
+        let handle = auto_stash(&repo_path, "before risky command")
+            .await
+            .expect("stash");
+        assert!(handle.stash_sha().is_some());
+        assert_eq!(
+            fs::read_to_string(repo_path.join("test.txt")).unwrap(),
+            "test content"
+        );
+        assert!(!repo_path.join("new.txt").exists());
+
+        restore(handle).await.expect("restore");
+        assert_eq!(
+            fs::read_to_string(repo_path.join("test.txt")).unwrap(),
+            "edited again"
+        );
+        assert_eq!(
+            fs::read_to_string(repo_path.join("new.txt")).unwrap(),
+            "untracked"
+        );
+        // The unrelated stash made before is still there.
+        let list = run_git_checked(&["stash", "list", "--format=%s"], &repo_path)
+            .await
+            .unwrap();
+        assert_eq!(list.lines().count(), 1, "{list}");
+    }
+
+    #[tokio::test]
+    async fn auto_stash_is_a_no_op_on_clean_tree() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+
+        let handle = auto_stash(&repo_path, "nothing to stash")
+            .await
+            .expect("stash");
+        assert_eq!(handle.stash_sha(), None);
+        restore(handle).await.expect("restore");
+        let list = run_git_checked(&["stash", "list"], &repo_path)
+            .await
+            .unwrap();
+        assert!(list.is_empty(), "{list}");
+    }
+
+    async fn head_sha(repo_path: &Path) -> String {
+        run_git_checked(&["rev-parse", "HEAD"], repo_path)
+            .await
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn repo_status_non_git_directory() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        assert_eq!(repo_status(temp_dir.path()).await, None);
+    }
+
+    #[tokio::test]
+    async fn repo_status_clean_repo() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+
+        let status = repo_status(&repo_path).await.expect("status");
+
+        assert!(status.branch.is_some());
+        assert_eq!(status.commit, Some(head_sha(&repo_path).await));
+        assert_eq!(status.upstream, None);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+        assert!(!status.dirty);
+    }
+
+    #[tokio::test]
+    async fn repo_status_dirty_repo() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        fs::write(repo_path.join("test.txt"), "modified").unwrap();
+
+        assert!(repo_status(&repo_path).await.expect("status").dirty);
+
+        run_git(&repo_path, &["checkout", "--", "test.txt"]).await;
+        fs::write(repo_path.join("untracked.txt"), "new").unwrap();
+
+        assert!(repo_status(&repo_path).await.expect("status").dirty);
+    }
+
+    #[tokio::test]
+    async fn repo_status_detached_head() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        let sha = head_sha(&repo_path).await;
+        run_git(&repo_path, &["checkout", "--detach"]).await;
+
+        let status = repo_status(&repo_path).await.expect("status");
+
+        assert_eq!(status.branch, None);
+        assert_eq!(status.commit, Some(sha));
+        assert_eq!(status.upstream, None);
+    }
+
+    #[tokio::test]
+    async fn repo_status_without_commits() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        run_git(temp_dir.path(), &["init"]).await;
+
+        let status = repo_status(temp_dir.path()).await.expect("status");
+
+        assert!(status.branch.is_some());
+        assert_eq!(status.commit, None);
+        assert!(!status.dirty);
+    }
+
+    #[tokio::test]
+    async fn repo_status_reports_upstream_ahead_and_behind() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let (repo_path, branch) = create_test_git_repo_with_remote(&temp_dir).await;
+        // Push a commit, drop it locally, and make a different one instead.
+        fs::write(repo_path.join("test.txt"), "pushed").unwrap();
+        run_git(&repo_path, &["commit", "-am", "pushed change"]).await;
+        run_git(&repo_path, &["push"]).await;
+        run_git(&repo_path, &["reset", "--hard", "HEAD~1"]).await;
+        fs::write(repo_path.join("test.txt"), "local").unwrap();
+        run_git(&repo_path, &["commit", "-am", "local change"]).await;
+
+        let status = repo_status(&repo_path).await.expect("status");
+
+        assert_eq!(status.branch.as_deref(), Some(branch.as_str()));
+        assert_eq!(status.upstream, Some(format!("origin/{branch}")));
+        assert_eq!((status.ahead, status.behind), (1, 1));
+        assert!(!status.dirty);
+    }
+
     #[test]
     fn test_git_info_serialization() {
         let git_info = GitInfo {
@@ -1,5 +1,7 @@
 use crate::bash::try_parse_bash;
 use crate::bash::try_parse_word_only_commands_sequence;
+use crate::windows_shell::WindowsCommand;
+use crate::windows_shell::try_parse_windows_commands;
 
 pub fn is_known_safe_command(command: &[String]) -> bool {
     if is_safe_to_call_with_exec(command) {
@@ -25,6 +27,19 @@ pub fn is_known_safe_command(command: &[String]) -> bool {
         return true;
     }
 
+    // Support `cmd /c ...`, `powershell -Command ...` and, on Windows, bare
+    // cmdlets and builtins when every command in the script is read-only (e.g. `dir`,
+    // `Get-ChildItem`). Destructive commands such as `del` or `Remove-Item`
+    // are never considered safe.
+    if let Some(commands) = try_parse_windows_commands(command)
+        && commands
+            .iter()
+            .map(WindowsCommand::kind)
+            .all(|kind| kind.is_read_only())
+    {
+        return true;
+    }
+
     false
 }
 
@@ -341,4 +356,70 @@ mod tests {
             "> redirection should be rejected"
         );
     }
+
+    #[test]
+    fn windows_read_only_commands_are_safe() {
+        assert!(is_known_safe_command(&vec_str(&[
+            "cmd.exe", "/c", "dir", "/b"
+        ])));
+        assert!(is_known_safe_command(&vec_str(&[
+            "powershell",
+            "-Command",
+            "Get-ChildItem src | Select-String TODO"
+        ])));
+    }
+
+    #[test]
+    fn windows_destructive_commands_are_not_safe() {
+        assert!(!is_known_safe_command(&vec_str(&[
+            "cmd",
+            "/c",
+            "dir && del /q out.txt"
+        ])));
+        assert!(!is_known_safe_command(&vec_str(&[
+            "pwsh",
+            "-c",
+            "Remove-Item -Recurse build"
+        ])));
+        assert!(!is_known_safe_command(&vec_str(&["Remove-Item", "build"])));
+        assert!(
+            !is_known_safe_command(&vec_str(&["cmd", "/c", "dir > out.txt"])),
+            "> redirection should be rejected"
+        );
+    }
+
+    #[test]
+    fn windows_separators_inside_words_are_not_safe() {
+        for command in [
+            vec_str(&["cmd", "/c", "dir foo&del bar"]),
+            vec_str(&["cmd.exe", "/c", "dir foo|del bar"]),
+            vec_str(&["pwsh", "-c", "Get-ChildItem x;Remove-Item y"]),
+            vec_str(&["powershell", "-Command", "Get-ChildItem x; Remove-Item y"]),
+        ] {
+            assert!(!is_known_safe_command(&command), "{command:?}");
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_bare_builtins() {
+        assert!(is_known_safe_command(&vec_str(&["dir"])));
+        assert!(is_known_safe_command(&vec_str(&[
+            "Get-ChildItem",
+            "-Recurse"
+        ])));
+        assert!(!is_known_safe_command(&vec_str(&["del", "out.txt"])));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn bare_windows_commands_are_not_trusted_elsewhere() {
+        for command in [
+            vec_str(&["dir"]),
+            vec_str(&["Get-ChildItem", "-Recurse"]),
+            vec_str(&["Get-Content", "README.md"]),
+        ] {
+            assert!(!is_known_safe_command(&command), "{command:?}");
+        }
+    }
 }
@@ -21,8 +21,10 @@
 // 下面的 `mod` / `pub mod` 声明把实现拆分到不同文件中。注：`mod x;` 把模块包含进来，
 // 但并不对外导出；若希望其他 crate 使用，需要使用 `pub mod` 或者 `pub use` 重新导出类型。
 mod apply_patch; // 负责将 agent 生成的补丁应用到工作区（验证、写盘、调用 git apply 等）
+pub mod audit_log; // 沙箱外执行命令的只追加审计日志（~/.codex/audit/）
 mod bash; // 与 shell/命令相关的辅助代码
 mod chat_completions; // 与模型聊天补全（chat completions）相关的 glue 代码
+pub mod circuit_breaker; // 模型提供者的熔断器（连续失败后快速失败）
 mod client; // 与外部服务交互的客户端包装（可能包含 HTTP 调用等）
 mod client_common; // client 的共用工具
 pub mod codex; // 对外公开的高层 API（可能包含较为稳定的抽象）
@@ -30,14 +32,17 @@ mod codex_conversation; // 会话（conversation）逻辑实现
 pub use codex_conversation::CodexConversation; // 重新导出便于上层调用者直接使用
 pub mod config; // 配置加载与解析
 pub mod config_profile; // 配置 profile（多套配置）
+pub mod config_snapshot; // 会话所用已解析配置的快照（脱敏）
 pub mod config_types; // 配置相关的类型定义
-mod conversation_history; // 会话历史的持久化与读取
+pub mod conversation_history; // 会话历史的持久化与读取
 pub mod custom_prompts; // 自定义 prompt 管理
+pub mod default_client; // 发往模型提供者的请求所用的 HTTP 客户端
 mod environment_context; // 运行时环境相关的上下文（cwd、env 等）
 pub mod error; // 错误类型与处理工具
 pub mod exec; // 执行/运行命令的高级封装
 mod exec_command; // 低层 exec 命令实现
 pub mod exec_env; // exec 相关的环境管理（沙箱、路径等）
+mod file_mentions; // 按 file_mention_mode 展开用户输入中的 @path 文件引用
 mod flags; // CLI/运行时标志解析辅助
 pub mod git_info; // 与 git 仓库元信息相关的工具
 mod is_safe_command; // 判断命令是否安全（用于 sandbox 策略）
@@ -45,16 +50,21 @@ pub mod landlock; // Linux landlock 相关封装（如果支持）
 mod mcp_connection_manager; // MCP 连接管理
 mod mcp_tool_call; // MCP 工具调用封装
 mod message_history; // 消息历史（可能与 conversation_history 有区别）
+mod mock_transport; // 离线模拟传输：回放脚本中的模型响应（演示用）
 mod model_provider_info; // 模型提供者信息与注册
 pub mod parse_command; // 将用户/agent 的文本解析为可执行命令的工具
 // 下面几行把 model_provider_info 中的一些常用常量/类型对外导出，方便调用端写 `codex_core::ModelProviderInfo`。
 pub use model_provider_info::BUILT_IN_OSS_MODEL_PROVIDER_ID;
+pub use model_provider_info::InstructionsRole;
 pub use model_provider_info::ModelProviderInfo;
+pub use model_provider_info::ProviderHealth;
 pub use model_provider_info::WireApi;
 pub use model_provider_info::built_in_model_providers;
 pub use model_provider_info::create_oss_provider_with_base_url;
 mod conversation_manager; // 会话管理器（新会话、会话切换等）
+pub use config_snapshot::ResolvedConfigSnapshot; // 会话配置快照
 pub use conversation_manager::ConversationManager; // 重新导出
+pub use conversation_manager::ConversationSummary; // 活跃会话摘要
 pub use conversation_manager::NewConversation; // 新会话构造器
 pub mod model_family; // 模型家族/分组相关类型
 mod openai_model_info; // OpenAI 模型相关的硬编码或映射表
@@ -65,16 +75,25 @@ mod rollout; // rollout/特性开关等
 pub(crate) mod safety; // crate 私有的安全工具（仅在 core 内可见）
 pub mod seatbelt; // macOS Seatbelt sandbox 集成
 pub mod shell; // shell 交互封装
+mod shell_dialect; // 按 fish / PowerShell 语法切分命令脚本
 pub mod spawn; // spawn 子进程工具
 pub mod terminal; // 终端相关抽象（例如处理 tty）
+pub mod timestamps; // 按 timestamps 配置格式化持久化与日志中的时间戳
 mod tool_apply_patch; // 作为工具调用时的 apply_patch glue
 pub mod turn_diff_tracker; // 跟踪 turn（agent 轮次）的 diff
 pub mod user_agent; // 用户 agent 相关类型/逻辑
 mod user_notification; // 用户通知（桌面通知等）
 pub mod util; // 通用工具函数
+mod windows_shell; // 识别 cmd.exe / PowerShell 命令
 // 下面是对外常量与工具函数导出
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
+pub use apply_patch::CODEX_APPLY_PATCH_STDIN_ARG1;
+pub use apply_patch::CODEX_APPLY_PATCH_WRITE_RETRY_ENV_VAR;
+pub use safety::SelfTestProbe;
+pub use safety::SelfTestProbeResult;
+pub use safety::SelfTestReport;
 pub use safety::get_platform_sandbox;
+pub use safety::self_test as sandbox_self_test;
 
 // Re-export the protocol types from the standalone `codex-protocol` crate so existing
 // `codex_core::protocol::...` references continue to work across the workspace.
@@ -4,7 +4,9 @@
 //! configured server (keyed by the *server name*). It offers convenience
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
-//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//! `"<server><delimiter><tool>"` as the key. The delimiter defaults to
+//! [`DEFAULT_MCP_TOOL_NAME_DELIMITER`] and can be changed with the
+//! `mcp_tool_name_delimiter` config option.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -15,6 +17,7 @@ use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use codex_mcp_client::McpClient;
+use codex_mcp_client::TransportError;
 use mcp_types::ClientCapabilities;
 use mcp_types::Implementation;
 use mcp_types::Tool;
@@ -26,46 +29,73 @@ use tokio::task::JoinSet;
 use tracing::info;
 use tracing::warn;
 
+use crate::config_types::McpRetryConfig;
 use crate::config_types::McpServerConfig;
+use crate::protocol::McpServerHealth;
 
-/// Delimiter used to separate the server name from the tool name in a fully
-/// qualified tool name.
+/// Default delimiter used to separate the server name from the tool name in a
+/// fully qualified tool name.
 ///
-/// OpenAI requires tool names to conform to `^[a-zA-Z0-9_-]+$`, so we must
-/// choose a delimiter from this character set.
-const MCP_TOOL_NAME_DELIMITER: &str = "__";
+/// OpenAI requires tool names to conform to `^[a-zA-Z0-9_-]+$`, so any
+/// delimiter must be chosen from this character set (see
+/// [`is_valid_mcp_tool_name_delimiter`]).
+pub(crate) const DEFAULT_MCP_TOOL_NAME_DELIMITER: &str = "__";
 const MAX_TOOL_NAME_LENGTH: usize = 64;
 
-/// Timeout for the `tools/list` request.
-const LIST_TOOLS_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default time a server has to start, answer `initialize`, and answer
+/// `tools/list` before it is reported as failed.
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time a server has to answer a health check when it has no
+/// `request_timeout_ms`.
+const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default delay before the first retry of a tool call.
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Default upper bound on the delay between tool call retries.
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
 
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
 
-fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
+/// Startup error for a server that did not answer `initialize` and
+/// `tools/list` within its `startup_timeout_ms`.
+#[derive(Debug, thiserror::Error)]
+#[error("did not become ready within {} ms", .0.as_millis())]
+pub(crate) struct StartupTimeoutError(pub Duration);
+
+/// Returns true if `delimiter` can be used to build tool names that satisfy
+/// `^[a-zA-Z0-9_-]+$`.
+pub(crate) fn is_valid_mcp_tool_name_delimiter(delimiter: &str) -> bool {
+    is_valid_mcp_server_name(delimiter)
+}
+
+fn qualify_tools(tools: Vec<ToolInfo>, delimiter: &str) -> HashMap<String, ToolInfo> {
     let mut used_names = HashSet::new();
-    let mut qualified_tools = HashMap::new();
+    let mut qualified_tools: HashMap<String, ToolInfo> = HashMap::new();
     for tool in tools {
-        let mut qualified_name = format!(
-            "{}{}{}",
-            tool.server_name, MCP_TOOL_NAME_DELIMITER, tool.tool_name
-        );
+        let full_name = format!("{}{delimiter}{}", tool.server_name, tool.tool_name);
+        let mut qualified_name = full_name.clone();
         if qualified_name.len() > MAX_TOOL_NAME_LENGTH {
-            let mut hasher = Sha1::new();
-            hasher.update(qualified_name.as_bytes());
-            let sha1 = hasher.finalize();
-            let sha1_str = format!("{sha1:x}");
-
-            // Truncate to make room for the hash suffix
-            let prefix_len = MAX_TOOL_NAME_LENGTH - sha1_str.len();
-
-            qualified_name = format!("{}{}", &qualified_name[..prefix_len], sha1_str);
+            qualified_name = truncate_with_hash(&qualified_name, &qualified_name);
         }
 
-        if used_names.contains(&qualified_name) {
-            warn!("skipping duplicated tool {}", qualified_name);
-            continue;
+        if let Some(existing) = qualified_tools.get(&qualified_name) {
+            if existing.server_name == tool.server_name && existing.tool_name == tool.tool_name {
+                warn!("skipping duplicated tool {}", qualified_name);
+                continue;
+            }
+            // A different (server, tool) pair produced the same name, e.g.
+            // because the tool name contains the delimiter. Disambiguate by
+            // hashing the pair itself so both tools stay reachable.
+            let pair = format!("{}\0{}", tool.server_name, tool.tool_name);
+            qualified_name = truncate_with_hash(&full_name, &pair);
+            if used_names.contains(&qualified_name) {
+                warn!("skipping duplicated tool {}", qualified_name);
+                continue;
+            }
         }
 
         used_names.insert(qualified_name.clone());
@@ -75,6 +105,67 @@ fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     qualified_tools
 }
 
+/// Replace the tail of `name` with the SHA-1 of `key` so the result fits in
+/// [`MAX_TOOL_NAME_LENGTH`].
+fn truncate_with_hash(name: &str, key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    let sha1 = hasher.finalize();
+    let sha1_str = format!("{sha1:x}");
+
+    // Truncate to make room for the hash suffix
+    let prefix_len = (MAX_TOOL_NAME_LENGTH - sha1_str.len()).min(name.len());
+
+    format!("{}{}", &name[..prefix_len], sha1_str)
+}
+
+/// How tool calls to one server are retried after a timeout.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct McpRetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Tools that opted out of retries. A timed-out call may still have run
+    /// on the server, so tools that are not idempotent belong here.
+    pub no_retry_tools: HashSet<String>,
+}
+
+impl From<McpRetryConfig> for McpRetryPolicy {
+    fn from(config: McpRetryConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            initial_backoff: config
+                .initial_backoff_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_INITIAL_BACKOFF),
+            max_backoff: config
+                .max_backoff_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_MAX_BACKOFF),
+            no_retry_tools: config.no_retry_tools.into_iter().collect(),
+        }
+    }
+}
+
+impl McpRetryPolicy {
+    fn max_retries_for(&self, tool: &str) -> u32 {
+        if self.no_retry_tools.contains(tool) {
+            0
+        } else {
+            self.max_retries
+        }
+    }
+
+    /// Delay before the `retry`-th retry (starting at 1): the initial
+    /// backoff doubled for each earlier retry, capped at `max_backoff`.
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
 struct ToolInfo {
     server_name: String,
     tool_name: String,
@@ -92,6 +183,17 @@ pub(crate) struct McpConnectionManager {
 
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
+
+    /// Server-name -> default timeout for `tools/call`, from
+    /// `request_timeout_ms`.
+    request_timeouts: HashMap<String, Duration>,
+
+    /// Server-name -> retry policy for `tools/call`, from `retry`.
+    retry_policies: HashMap<String, McpRetryPolicy>,
+
+    /// Server-name -> startup error, for servers that never became usable.
+    /// Reported as unreachable by [`McpConnectionManager::check_health`].
+    start_errors: HashMap<String, String>,
 }
 
 impl McpConnectionManager {
@@ -100,11 +202,16 @@ impl McpConnectionManager {
     /// * `mcp_servers` – Map loaded from the user configuration where *keys*
     ///   are human-readable server identifiers and *values* are the spawn
     ///   instructions.
+    /// * `tool_name_delimiter` – Separator placed between the server name and
+    ///   the tool name in fully-qualified tool names.
     ///
-    /// Servers that fail to start are reported in `ClientStartErrors`: the
-    /// user should be informed about these errors.
+    /// Servers that fail to start, or that do not become ready within their
+    /// `startup_timeout_ms` ([`StartupTimeoutError`]), are reported in
+    /// `ClientStartErrors`: the user should be informed about these errors.
+    /// The remaining servers are still usable.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
+        tool_name_delimiter: &str,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
@@ -114,6 +221,8 @@ impl McpConnectionManager {
         // Launch all configured servers concurrently.
         let mut join_set = JoinSet::new();
         let mut errors = ClientStartErrors::new();
+        let mut request_timeouts = HashMap::new();
+        let mut retry_policies = HashMap::new();
 
         for (server_name, cfg) in mcp_servers {
             // Validate server name before spawning
@@ -126,69 +235,81 @@ impl McpConnectionManager {
                 continue;
             }
 
+            let McpServerConfig {
+                command,
+                args,
+                env,
+                startup_timeout_ms,
+                request_timeout_ms,
+                retry,
+            } = cfg;
+            if let Some(ms) = request_timeout_ms {
+                request_timeouts.insert(server_name.clone(), Duration::from_millis(ms));
+            }
+            if let Some(retry) = retry {
+                retry_policies.insert(server_name.clone(), McpRetryPolicy::from(retry));
+            }
+            let startup_timeout = startup_timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+
             join_set.spawn(async move {
-                let McpServerConfig { command, args, env } = cfg;
-                let client_res = McpClient::new_stdio_client(
-                    command.into(),
-                    args.into_iter().map(OsString::from).collect(),
-                    env,
-                )
-                .await;
-                match client_res {
-                    Ok(client) => {
-                        // Initialize the client.
-                        let params = mcp_types::InitializeRequestParams {
-                            capabilities: ClientCapabilities {
-                                experimental: None,
-                                roots: None,
-                                sampling: None,
-                                // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                                // indicates this should be an empty object.
-                                elicitation: Some(json!({})),
-                            },
-                            client_info: Implementation {
-                                name: "codex-mcp-client".to_owned(),
-                                version: env!("CARGO_PKG_VERSION").to_owned(),
-                                title: Some("Codex".into()),
-                            },
-                            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                        };
-                        let initialize_notification_params = None;
-                        let timeout = Some(Duration::from_secs(10));
-                        match client
-                            .initialize(params, initialize_notification_params, timeout)
-                            .await
-                        {
-                            Ok(_response) => (server_name, Ok(client)),
-                            Err(e) => (server_name, Err(e)),
-                        }
-                    }
-                    Err(e) => (server_name, Err(e.into())),
-                }
+                // Bound the whole startup sequence so that a server which never
+                // answers cannot hold up session start. Dropping the client on
+                // timeout kills the child process.
+                let res = tokio::time::timeout(startup_timeout, start_server(command, args, env))
+                    .await
+                    .unwrap_or_else(|_| Err(StartupTimeoutError(startup_timeout).into()));
+                (server_name, res)
             });
         }
 
         let mut clients: HashMap<String, std::sync::Arc<McpClient>> =
             HashMap::with_capacity(join_set.len());
+        let mut all_tools = Vec::new();
 
         while let Some(res) = join_set.join_next().await {
-            let (server_name, client_res) = res?; // JoinError propagation
-
-            match client_res {
-                Ok(client) => {
+            let (server_name, start_res) = res?; // JoinError propagation
+
+            match start_res {
+                Ok((client, tools)) => {
+                    all_tools.extend(tools.into_iter().map(|tool| ToolInfo {
+                        server_name: server_name.clone(),
+                        tool_name: tool.name.clone(),
+                        tool,
+                    }));
                     clients.insert(server_name, std::sync::Arc::new(client));
                 }
                 Err(e) => {
+                    request_timeouts.remove(&server_name);
+                    retry_policies.remove(&server_name);
                     errors.insert(server_name, e);
                 }
             }
         }
 
-        let all_tools = list_all_tools(&clients).await?;
-
-        let tools = qualify_tools(all_tools);
+        info!(
+            "aggregated {} tools from {} servers",
+            all_tools.len(),
+            clients.len()
+        );
 
-        Ok((Self { clients, tools }, errors))
+        let tools = qualify_tools(all_tools, tool_name_delimiter);
+        let start_errors = errors
+            .iter()
+            .map(|(server_name, e)| (server_name.clone(), format!("{e:#}")))
+            .collect();
+
+        Ok((
+            Self {
+                clients,
+                tools,
+                request_timeouts,
+                retry_policies,
+                start_errors,
+            },
+            errors,
+        ))
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
@@ -200,24 +321,98 @@ impl McpConnectionManager {
             .collect()
     }
 
-    /// Invoke the tool indicated by the (server, tool) pair.
+    /// Send a `tools/list` request to every running server concurrently and
+    /// report whether it answered within its `request_timeout_ms` (or
+    /// [`DEFAULT_HEALTH_CHECK_TIMEOUT`]). Servers that failed to start are
+    /// always reported as unreachable.
+    pub async fn check_health(&self) -> HashMap<String, McpServerHealth> {
+        let mut join_set = JoinSet::new();
+        for (server_name, client) in &self.clients {
+            let server_name = server_name.clone();
+            let client = client.clone();
+            let timeout = self
+                .request_timeouts
+                .get(&server_name)
+                .copied()
+                .unwrap_or(DEFAULT_HEALTH_CHECK_TIMEOUT);
+            join_set.spawn(async move {
+                let health = match client.list_tools(None, Some(timeout)).await {
+                    Ok(_) => McpServerHealth::Healthy,
+                    Err(e) => McpServerHealth::Unreachable {
+                        error: format!("{e:#}"),
+                    },
+                };
+                (server_name, health)
+            });
+        }
+
+        let mut health: HashMap<String, McpServerHealth> = self
+            .start_errors
+            .iter()
+            .map(|(server_name, error)| {
+                (
+                    server_name.clone(),
+                    McpServerHealth::Unreachable {
+                        error: error.clone(),
+                    },
+                )
+            })
+            .collect();
+        while let Some(res) = join_set.join_next().await {
+            match res {
+                Ok((server_name, server_health)) => {
+                    health.insert(server_name, server_health);
+                }
+                Err(e) => warn!("MCP health check task failed: {e}"),
+            }
+        }
+        health
+    }
+
+    /// Invoke the tool indicated by the (server, tool) pair. When `timeout`
+    /// is `None`, the server's configured `request_timeout_ms` applies.
+    ///
+    /// Calls that time out are retried according to the server's retry
+    /// policy. A closed transport means the server is gone, so it fails the
+    /// call right away. `attempts` is incremented for every request sent, so it is
+    /// accurate even if the returned future is dropped part-way.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        attempts: &mut u32,
     ) -> Result<mcp_types::CallToolResult> {
         let client = self
             .clients
             .get(server)
             .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?
             .clone();
-
-        client
-            .call_tool(tool.to_string(), arguments, timeout)
-            .await
-            .with_context(|| format!("tool call failed for `{server}/{tool}`"))
+        let timeout = timeout.or_else(|| self.request_timeouts.get(server).copied());
+        let retry_policy = self.retry_policies.get(server);
+        let max_retries = retry_policy.map_or(0, |policy| policy.max_retries_for(tool));
+
+        loop {
+            *attempts += 1;
+            let result = client
+                .call_tool(tool.to_string(), arguments.clone(), timeout)
+                .await;
+            match (result, retry_policy) {
+                (Err(e), Some(policy)) if *attempts <= max_retries && is_retryable(&e) => {
+                    let backoff = policy.backoff(*attempts);
+                    warn!(
+                        "tool call to `{server}/{tool}` failed: {e}; retrying in {} ms",
+                        backoff.as_millis()
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                (result, _) => {
+                    return result
+                        .with_context(|| format!("tool call failed for `{server}/{tool}`"));
+                }
+            }
+        }
     }
 
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
@@ -227,50 +422,53 @@ impl McpConnectionManager {
     }
 }
 
-/// Query every server for its available tools and return a single map that
-/// contains **all** tools. Each key is the fully-qualified name for the tool.
-async fn list_all_tools(
-    clients: &HashMap<String, std::sync::Arc<McpClient>>,
-) -> Result<Vec<ToolInfo>> {
-    let mut join_set = JoinSet::new();
-
-    // Spawn one task per server so we can query them concurrently. This
-    // keeps the overall latency roughly at the slowest server instead of
-    // the cumulative latency.
-    for (server_name, client) in clients {
-        let server_name_cloned = server_name.clone();
-        let client_clone = client.clone();
-        join_set.spawn(async move {
-            let res = client_clone
-                .list_tools(None, Some(LIST_TOOLS_TIMEOUT))
-                .await;
-            (server_name_cloned, res)
-        });
-    }
-
-    let mut aggregated: Vec<ToolInfo> = Vec::with_capacity(join_set.len());
-
-    while let Some(join_res) = join_set.join_next().await {
-        let (server_name, list_result) = join_res?;
-        let list_result = list_result?;
-
-        for tool in list_result.tools {
-            let tool_info = ToolInfo {
-                server_name: server_name.clone(),
-                tool_name: tool.name.clone(),
-                tool,
-            };
-            aggregated.push(tool_info);
-        }
-    }
-
-    info!(
-        "aggregated {} tools from {} servers",
-        aggregated.len(),
-        clients.len()
-    );
+/// Whether a failed tool call may be sent again. Only a timeout qualifies: a
+/// closed writer or reply channel means the server process has exited.
+fn is_retryable(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<TransportError>(),
+        Some(TransportError::TimedOut)
+    )
+}
 
-    Ok(aggregated)
+/// Spawn a single server, run the `initialize` handshake, and fetch its
+/// tools. The caller bounds the sequence as a whole with the server's startup
+/// timeout.
+async fn start_server(
+    command: String,
+    args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+) -> Result<(McpClient, Vec<Tool>)> {
+    let client = McpClient::new_stdio_client(
+        command.into(),
+        args.into_iter().map(OsString::from).collect(),
+        env,
+    )
+    .await?;
+
+    let params = mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+            // indicates this should be an empty object.
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "codex-mcp-client".to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            title: Some("Codex".into()),
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+    };
+    let initialize_notification_params = None;
+    client
+        .initialize(params, initialize_notification_params, None)
+        .await?;
+
+    let list_result = client.list_tools(None, None).await?;
+    Ok((client, list_result.tools))
 }
 
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
@@ -304,6 +502,215 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unresponsive_server_is_marked_failed_after_startup_timeout() {
+        let servers = HashMap::from([(
+            "hung".to_string(),
+            McpServerConfig {
+                // Reads stdin forever and never answers `initialize`.
+                command: "sleep".to_string(),
+                args: vec!["30".to_string()],
+                env: None,
+                startup_timeout_ms: Some(200),
+                request_timeout_ms: None,
+                retry: None,
+            },
+        )]);
+
+        let start = std::time::Instant::now();
+        let (manager, errors) = McpConnectionManager::new(servers, DEFAULT_MCP_TOOL_NAME_DELIMITER)
+            .await
+            .expect("startup should not fail as a whole");
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(manager.clients.is_empty());
+        let err = errors.get("hung").expect("hung server should be reported");
+        assert!(err.downcast_ref::<StartupTimeoutError>().is_some());
+    }
+
+    /// An MCP server speaking JSON-RPC from `sh` that never answers the first
+    /// two `tools/call` requests and succeeds from the third on.
+    #[cfg(unix)]
+    const FLAKY_SERVER_SCRIPT: &str = r#"
+calls=0
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"flaky","version":"0"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"echo","inputSchema":{"type":"object"}},{"name":"create","inputSchema":{"type":"object"}}]}}\n' "$id" ;;
+    *'"method":"tools/call"'*)
+      calls=$((calls + 1))
+      if [ "$calls" -gt 2 ]; then
+        printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"ok"}]}}\n' "$id"
+      fi ;;
+  esac
+done
+"#;
+
+    #[cfg(unix)]
+    async fn start_flaky_server() -> McpConnectionManager {
+        let servers = HashMap::from([(
+            "flaky".to_string(),
+            McpServerConfig {
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), FLAKY_SERVER_SCRIPT.to_string()],
+                env: None,
+                startup_timeout_ms: None,
+                request_timeout_ms: Some(200),
+                retry: Some(McpRetryConfig {
+                    max_retries: 2,
+                    initial_backoff_ms: Some(100),
+                    max_backoff_ms: Some(150),
+                    no_retry_tools: vec!["create".to_string()],
+                }),
+            },
+        )]);
+        let (manager, errors) = McpConnectionManager::new(servers, DEFAULT_MCP_TOOL_NAME_DELIMITER)
+            .await
+            .expect("startup should not fail as a whole");
+        assert!(errors.is_empty(), "{errors:?}");
+        manager
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn timeouts_are_retried_with_backoff() {
+        let manager = start_flaky_server().await;
+
+        let start = std::time::Instant::now();
+        let mut attempts = 0;
+        let result = manager
+            .call_tool("flaky", "echo", None, None, &mut attempts)
+            .await;
+
+        assert!(result.is_ok(), "{result:?}");
+        assert_eq!(attempts, 3);
+        // Two 200 ms timeouts, then backoffs of 100 ms and 150 ms (capped).
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(650), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(5), "{elapsed:?}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn opted_out_tools_are_not_retried() {
+        let manager = start_flaky_server().await;
+
+        let mut attempts = 0;
+        let err = manager
+            .call_tool("flaky", "create", None, None, &mut attempts)
+            .await
+            .expect_err("the first call is dropped by the server");
+
+        assert_eq!(attempts, 1);
+        assert_eq!(
+            err.downcast_ref::<TransportError>(),
+            Some(&TransportError::TimedOut)
+        );
+    }
+
+    /// An MCP server that completes startup and then exits, so every later
+    /// request goes unanswered.
+    #[cfg(unix)]
+    const ONE_SHOT_SERVER_SCRIPT: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"one-shot","version":"0"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[]}}\n' "$id"
+      exit 0 ;;
+  esac
+done
+"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn health_check_reports_reachable_and_broken_servers() {
+        let sh_server = |script: &str| McpServerConfig {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: None,
+            startup_timeout_ms: None,
+            request_timeout_ms: Some(200),
+            retry: None,
+        };
+        let servers = HashMap::from([
+            ("healthy".to_string(), sh_server(FLAKY_SERVER_SCRIPT)),
+            ("broken".to_string(), sh_server(ONE_SHOT_SERVER_SCRIPT)),
+        ]);
+        let (manager, errors) = McpConnectionManager::new(servers, DEFAULT_MCP_TOOL_NAME_DELIMITER)
+            .await
+            .expect("startup should not fail as a whole");
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let health = manager.check_health().await;
+
+        assert_eq!(health.len(), 2);
+        assert_eq!(health.get("healthy"), Some(&McpServerHealth::Healthy));
+        assert!(
+            matches!(
+                health.get("broken"),
+                Some(McpServerHealth::Unreachable { error }) if !error.is_empty()
+            ),
+            "{health:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn servers_that_failed_to_start_are_unreachable() {
+        let manager = McpConnectionManager {
+            start_errors: HashMap::from([("gone".to_string(), "spawn failed".to_string())]),
+            ..McpConnectionManager::default()
+        };
+
+        let health = manager.check_health().await;
+
+        assert_eq!(
+            health,
+            HashMap::from([(
+                "gone".to_string(),
+                McpServerHealth::Unreachable {
+                    error: "spawn failed".to_string()
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn only_timeouts_are_retryable() {
+        assert!(is_retryable(&TransportError::TimedOut.into()));
+        assert!(!is_retryable(&TransportError::WriterClosed.into()));
+        assert!(!is_retryable(&TransportError::ReplyChannelClosed.into()));
+        assert!(!is_retryable(&anyhow!("tool reported an error")));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = McpRetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            no_retry_tools: HashSet::new(),
+        };
+
+        let backoffs: Vec<Duration> = (1..=4).map(|retry| policy.backoff(retry)).collect();
+
+        assert_eq!(
+            backoffs,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(350),
+                Duration::from_millis(350),
+            ]
+        );
+    }
+
     #[test]
     fn test_qualify_tools_short_non_duplicated_names() {
         let tools = vec![
@@ -311,7 +718,7 @@ mod tests {
             create_test_tool("server1", "tool2"),
         ];
 
-        let qualified_tools = qualify_tools(tools);
+        let qualified_tools = qualify_tools(tools, DEFAULT_MCP_TOOL_NAME_DELIMITER);
 
         assert_eq!(qualified_tools.len(), 2);
         assert!(qualified_tools.contains_key("server1__tool1"));
@@ -325,7 +732,7 @@ mod tests {
             create_test_tool("server1", "duplicate_tool"),
         ];
 
-        let qualified_tools = qualify_tools(tools);
+        let qualified_tools = qualify_tools(tools, DEFAULT_MCP_TOOL_NAME_DELIMITER);
 
         // Only the first tool should remain, the second is skipped
         assert_eq!(qualified_tools.len(), 1);
@@ -347,7 +754,7 @@ mod tests {
             ),
         ];
 
-        let qualified_tools = qualify_tools(tools);
+        let qualified_tools = qualify_tools(tools, DEFAULT_MCP_TOOL_NAME_DELIMITER);
 
         assert_eq!(qualified_tools.len(), 2);
 
@@ -366,4 +773,72 @@ mod tests {
             "my_server__yet_another_e1c3987bd9c50b826cbe1687966f79f0c602d19ca"
         );
     }
+
+    fn manager_with_tools(tools: Vec<ToolInfo>, delimiter: &str) -> McpConnectionManager {
+        McpConnectionManager {
+            tools: qualify_tools(tools, delimiter),
+            ..McpConnectionManager::default()
+        }
+    }
+
+    #[test]
+    fn test_qualify_tools_custom_delimiter_round_trips() {
+        let manager = manager_with_tools(
+            vec![
+                create_test_tool("server1", "tool1"),
+                create_test_tool("server1", "tool__with__underscores"),
+            ],
+            "-",
+        );
+
+        let mut names: Vec<_> = manager.list_all_tools().into_keys().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["server1-tool1", "server1-tool__with__underscores"]
+        );
+        assert_eq!(
+            manager.parse_tool_name("server1-tool__with__underscores"),
+            Some(("server1".to_string(), "tool__with__underscores".to_string()))
+        );
+        assert_eq!(manager.parse_tool_name("server1__tool1"), None);
+    }
+
+    #[test]
+    fn test_qualify_tools_delimiter_in_tool_name_stays_unique() {
+        // Both pairs naively qualify to "a__b__c".
+        let manager = manager_with_tools(
+            vec![create_test_tool("a", "b__c"), create_test_tool("a__b", "c")],
+            DEFAULT_MCP_TOOL_NAME_DELIMITER,
+        );
+
+        let names: Vec<_> = manager.list_all_tools().into_keys().collect();
+        assert_eq!(names.len(), 2);
+        let mut parsed: Vec<_> = names
+            .iter()
+            .map(|name| {
+                assert!(name.len() <= MAX_TOOL_NAME_LENGTH);
+                manager
+                    .parse_tool_name(name)
+                    .expect("qualified name parses")
+            })
+            .collect();
+        parsed.sort();
+        assert_eq!(
+            parsed,
+            vec![
+                ("a".to_string(), "b__c".to_string()),
+                ("a__b".to_string(), "c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tool_name_delimiter_validation() {
+        assert!(is_valid_mcp_tool_name_delimiter("__"));
+        assert!(is_valid_mcp_tool_name_delimiter("-"));
+        assert!(!is_valid_mcp_tool_name_delimiter(""));
+        assert!(!is_valid_mcp_tool_name_delimiter("."));
+        assert!(!is_valid_mcp_tool_name_delimiter("::"));
+    }
 }
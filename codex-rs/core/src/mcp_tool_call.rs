@@ -4,6 +4,7 @@ use std::time::Instant;
 use tracing::error;
 
 use crate::codex::Session;
+use crate::error::CodexErr;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
@@ -53,19 +54,33 @@ pub(crate) async fn handle_mcp_tool_call(
         call_id: call_id.clone(),
         invocation: invocation.clone(),
     });
+    let cancel_rx = sess.register_cancellable_tool_call(&call_id);
     notify_mcp_tool_call_event(sess, sub_id, tool_call_begin_event).await;
 
     let start = Instant::now();
+    let mut attempts = 0;
     // Perform the tool call.
     let result = sess
-        .call_tool(&server, &tool_name, arguments_value.clone(), timeout)
+        .run_cancellable_tool_call(
+            &call_id,
+            cancel_rx,
+            sess.call_tool(
+                &server,
+                &tool_name,
+                arguments_value.clone(),
+                timeout,
+                &mut attempts,
+            ),
+        )
         .await
+        .unwrap_or_else(|| Err(CodexErr::ToolCallCancelled.into()))
         .map_err(|e| format!("tool call error: {e}"));
     let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
         call_id: call_id.clone(),
         invocation,
         duration: start.elapsed(),
         result: result.clone(),
+        attempts,
     });
 
     notify_mcp_tool_call_event(sess, sub_id, tool_call_end_event.clone()).await;
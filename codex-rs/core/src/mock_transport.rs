@@ -0,0 +1,208 @@
+//! Offline transport for providers with `wire_api = "mock"`. Instead of
+//! talking to a model, each request replays the next scripted response from
+//! a file, so the whole agent loop (tool calls included) runs without
+//! network access — handy for demos and onboarding.
+//! 离线的模拟传输：按顺序回放脚本文件中的响应，无需联网即可跑通完整流程。
+//!
+//! The script is a JSON Lines file. Every non-empty line is one model
+//! response: a JSON array of Responses API output items, e.g.
+//!
+//! ```text
+//! [{"type":"function_call","name":"shell","arguments":"{\"command\":[\"ls\"]}","call_id":"c1"}]
+//! [{"type":"message","role":"assistant","content":[{"type":"output_text","text":"Done!"}]}]
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+
+/// Reply sent once every scripted response has been used.
+const SCRIPT_EXHAUSTED_MESSAGE: &str = "The mock script has no more responses.";
+
+#[derive(Debug)]
+pub(crate) struct MockTransport {
+    responses: Vec<Vec<ResponseItem>>,
+    next: Mutex<usize>,
+}
+
+type TransportKey = (PathBuf, Uuid);
+
+/// Transports keyed by script and session, so a conversation keeps its place
+/// in the script even though a new client is created for every turn. A
+/// session's entries are removed with [`MockTransport::release_session`] when
+/// it is dropped.
+static TRANSPORTS: LazyLock<Mutex<HashMap<TransportKey, Arc<MockTransport>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+impl MockTransport {
+    /// Returns the transport replaying `script` for `session_id`, loading
+    /// the script on first use.
+    pub(crate) fn for_session(script: &Path, session_id: Uuid) -> Result<Arc<Self>> {
+        let key = (script.to_path_buf(), session_id);
+        let mut transports = lock(&TRANSPORTS);
+        if let Some(transport) = transports.get(&key) {
+            return Ok(transport.clone());
+        }
+        let transport = Arc::new(Self::load(script)?);
+        transports.insert(key, transport.clone());
+        Ok(transport)
+    }
+
+    /// Forgets every transport created for `session_id`.
+    pub(crate) fn release_session(session_id: Uuid) {
+        lock(&TRANSPORTS).retain(|(_, id), _| *id != session_id);
+    }
+
+    fn load(script: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(script).map_err(|e| {
+            CodexErr::MockScript(format!("failed to read {}: {e}", script.display()))
+        })?;
+        Self::parse(&contents)
+            .map_err(|e| CodexErr::MockScript(format!("{}: {e}", script.display())))
+    }
+
+    fn parse(contents: &str) -> std::result::Result<Self, String> {
+        let responses = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str::<Vec<ResponseItem>>(line)
+                    .map_err(|e| format!("line {}: {e}", idx + 1))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Self {
+            responses,
+            next: Mutex::new(0),
+        })
+    }
+
+    /// Streams the next scripted response as if it came from the model.
+    pub(crate) fn stream(&self) -> ResponseStream {
+        let (index, items) = {
+            let mut next = lock(&self.next);
+            let index = *next;
+            *next += 1;
+            let items = self.responses.get(index).cloned().unwrap_or_else(|| {
+                vec![ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: SCRIPT_EXHAUSTED_MESSAGE.to_string(),
+                    }],
+                }]
+            });
+            (index, items)
+        };
+
+        let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(items.len() + 2);
+        let events = std::iter::once(ResponseEvent::Created)
+            .chain(items.into_iter().map(ResponseEvent::OutputItemDone))
+            .chain(std::iter::once(ResponseEvent::Completed {
+                response_id: format!("mock-{index}"),
+                token_usage: None,
+            }));
+        for event in events {
+            // The channel has room for every event, so this cannot fail.
+            let _ = tx_event.try_send(Ok(event));
+        }
+        ResponseStream {
+            rx_event,
+            timing: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(stream: ResponseStream) -> Vec<ResponseEvent> {
+        let mut rx = stream.rx_event;
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event.expect("mock events are never errors"));
+        }
+        events
+    }
+
+    #[test]
+    fn replays_responses_in_order_then_reports_exhaustion() {
+        let transport = MockTransport::parse(
+            r#"[{"type":"message","role":"assistant","content":[{"type":"output_text","text":"one"}]}]
+
+[{"type":"function_call","name":"shell","arguments":"{}","call_id":"c1"}]
+"#,
+        )
+        .expect("valid script");
+
+        let first = collect(transport.stream());
+        assert!(matches!(first.first(), Some(ResponseEvent::Created)));
+        assert!(matches!(
+            &first[1],
+            ResponseEvent::OutputItemDone(ResponseItem::Message { content, .. })
+                if matches!(&content[..], [ContentItem::OutputText { text }] if text == "one")
+        ));
+        assert!(matches!(
+            first.last(),
+            Some(ResponseEvent::Completed { response_id, .. }) if response_id == "mock-0"
+        ));
+
+        let second = collect(transport.stream());
+        assert!(matches!(
+            &second[1],
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id, .. }) if call_id == "c1"
+        ));
+
+        let exhausted = collect(transport.stream());
+        assert!(matches!(
+            &exhausted[1],
+            ResponseEvent::OutputItemDone(ResponseItem::Message { content, .. })
+                if matches!(&content[..], [ContentItem::OutputText { text }] if text == SCRIPT_EXHAUSTED_MESSAGE)
+        ));
+    }
+
+    #[test]
+    fn session_transports_are_shared_until_released() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let script = dir.path().join("script.jsonl");
+        std::fs::write(&script, "[]\n").expect("write script");
+        let session_id = Uuid::new_v4();
+
+        let first = MockTransport::for_session(&script, session_id).expect("transport");
+        let again = MockTransport::for_session(&script, session_id).expect("transport");
+        assert!(Arc::ptr_eq(&first, &again));
+
+        MockTransport::release_session(session_id);
+        assert!(!lock(&TRANSPORTS).keys().any(|(_, id)| *id == session_id));
+        let fresh = MockTransport::for_session(&script, session_id).expect("transport");
+        assert!(!Arc::ptr_eq(&first, &fresh));
+        MockTransport::release_session(session_id);
+    }
+
+    #[test]
+    fn invalid_line_is_reported_with_its_number() {
+        let err = MockTransport::parse("[]\nnot json\n").expect_err("invalid script");
+        assert!(err.starts_with("line 2:"), "{err}");
+    }
+}
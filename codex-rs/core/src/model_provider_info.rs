@@ -11,8 +11,12 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::env::VarError;
+use std::path::PathBuf;
 use std::time::Duration;
+use std::time::Instant;
 
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::default_client::create_client;
 use crate::error::EnvVarError;
 const DEFAULT_STREAM_IDLE_TIMEOUT_MS: u64 = 300_000;
 const DEFAULT_STREAM_MAX_RETRIES: u64 = 5;
@@ -22,6 +26,9 @@ const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
 const MAX_REQUEST_MAX_RETRIES: u64 = 100;
 
+const OPENAI_ORGANIZATION_HEADER: &str = "OpenAI-Organization";
+const OPENAI_PROJECT_HEADER: &str = "OpenAI-Project";
+
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
 /// itself (and a handful of others) additionally expose the more modern
@@ -37,6 +44,29 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// Offline replay of the responses in `mock_script`; nothing is sent
+    /// over the network.
+    Mock,
+}
+
+/// Chat Completions role that carries the base instructions. OpenAI's own
+/// endpoints understand `developer`, whereas most other implementations only
+/// accept `system`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstructionsRole {
+    System,
+    Developer,
+}
+
+impl InstructionsRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InstructionsRole::System => "system",
+            InstructionsRole::Developer => "developer",
+        }
+    }
 }
 
 /// Serializable representation of a provider definition.
@@ -83,9 +113,60 @@ pub struct ModelProviderInfo {
     /// Whether this provider requires some form of standard authentication (API key, ChatGPT token).
     #[serde(default)]
     pub requires_openai_auth: bool,
+
+    /// Role used for the instructions message in Chat Completions requests.
+    /// When unset, the provider's known convention is used (see
+    /// [`ModelProviderInfo::effective_instructions_role`]).
+    pub instructions_role: Option<InstructionsRole>,
+
+    /// OpenAI organization ID, sent as the `OpenAI-Organization` header.
+    pub organization: Option<String>,
+
+    /// OpenAI project ID, sent as the `OpenAI-Project` header.
+    pub project: Option<String>,
+
+    /// Thresholds for failing fast when the provider keeps failing. Defaults
+    /// apply when unset.
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+
+    /// The provider reports token usage while streaming, not only once the
+    /// response is complete. Usage seen mid-stream is forwarded as
+    /// `PartialTokenCount` updates during the turn.
+    #[serde(default)]
+    pub stream_usage: bool,
+
+    /// JSON Lines file of scripted responses for `wire_api = "mock"`.
+    pub mock_script: Option<PathBuf>,
+}
+
+/// Result of [`ModelProviderInfo::health_check`].
+/// [`ModelProviderInfo::health_check`] 的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderHealth {
+    /// Round-trip time of the probe request.
+    pub latency: Duration,
+    /// HTTP status code returned by the provider.
+    pub status: u16,
+    /// Whether the provider accepted the credentials (i.e. did not answer
+    /// with 401/403).
+    pub auth_ok: bool,
 }
 
 impl ModelProviderInfo {
+    /// Role to send the instructions under when using the Chat Completions
+    /// API: the configured `instructions_role`, or else `developer` for
+    /// OpenAI-hosted endpoints and `system` for everything else.
+    pub fn effective_instructions_role(&self) -> InstructionsRole {
+        if let Some(role) = self.instructions_role {
+            return role;
+        }
+        match self.base_url.as_deref() {
+            None => InstructionsRole::Developer,
+            Some(url) if url.contains("api.openai.com") => InstructionsRole::Developer,
+            Some(_) => InstructionsRole::System,
+        }
+    }
+
     /// Construct a `POST` RequestBuilder for the given URL using the provided
     /// reqwest Client applying:
     ///   • provider-specific headers (static + env based)
@@ -99,17 +180,7 @@ impl ModelProviderInfo {
         client: &'a reqwest::Client,
         auth: &Option<CodexAuth>,
     ) -> crate::error::Result<reqwest::RequestBuilder> {
-        let effective_auth = match self.api_key() {
-            Ok(Some(key)) => Some(CodexAuth::from_api_key(&key)),
-            Ok(None) => auth.clone(),
-            Err(err) => {
-                if auth.is_some() {
-                    auth.clone()
-                } else {
-                    return Err(err);
-                }
-            }
-        };
+        let effective_auth = self.effective_auth(auth)?;
 
         let url = self.get_full_url(&effective_auth);
 
@@ -122,6 +193,71 @@ impl ModelProviderInfo {
         Ok(self.apply_http_headers(builder))
     }
 
+    /// Probe the provider with a minimal authenticated `GET {base_url}/models`
+    /// request without starting a conversation.
+    /// 在不创建会话的情况下，用一次最小的带鉴权请求探测提供者是否可用。
+    ///
+    /// Any HTTP response counts as "reachable"; a 401/403 status is reported
+    /// through [`ProviderHealth::auth_ok`] rather than as an error. Transport
+    /// failures (DNS, connection refused, ...) and missing credentials are
+    /// returned as [`Err`].
+    pub async fn health_check(
+        &self,
+        auth: &Option<CodexAuth>,
+    ) -> crate::error::Result<ProviderHealth> {
+        if self.wire_api == WireApi::Mock {
+            return Ok(ProviderHealth {
+                latency: Duration::ZERO,
+                status: 200,
+                auth_ok: true,
+            });
+        }
+        let effective_auth = self.effective_auth(auth)?;
+        let url = format!(
+            "{}/models{}",
+            self.get_base_url(&effective_auth),
+            self.get_query_string()
+        );
+
+        let mut builder = create_client().get(url);
+        if let Some(auth) = effective_auth.as_ref() {
+            builder = builder.bearer_auth(auth.get_token().await?);
+        }
+        let builder = self.apply_http_headers(builder);
+
+        let start = Instant::now();
+        let response = builder.send().await?;
+        let latency = start.elapsed();
+
+        let status = response.status();
+        let auth_ok = !matches!(
+            status,
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN
+        );
+
+        Ok(ProviderHealth {
+            latency,
+            status: status.as_u16(),
+            auth_ok,
+        })
+    }
+
+    /// Resolve the credentials to use for a request: an API key from
+    /// `env_key` takes precedence over the supplied `auth`.
+    fn effective_auth(&self, auth: &Option<CodexAuth>) -> crate::error::Result<Option<CodexAuth>> {
+        match self.api_key() {
+            Ok(Some(key)) => Ok(Some(CodexAuth::from_api_key(&key))),
+            Ok(None) => Ok(auth.clone()),
+            Err(err) => {
+                if auth.is_some() {
+                    Ok(auth.clone())
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
     fn get_query_string(&self) -> String {
         self.query_params
             .as_ref()
@@ -135,7 +271,7 @@ impl ModelProviderInfo {
             })
     }
 
-    pub(crate) fn get_full_url(&self, auth: &Option<CodexAuth>) -> String {
+    fn get_base_url(&self, auth: &Option<CodexAuth>) -> String {
         let default_base_url = if matches!(
             auth,
             Some(CodexAuth {
@@ -147,35 +283,65 @@ impl ModelProviderInfo {
         } else {
             "https://api.openai.com/v1"
         };
-        let query_string = self.get_query_string();
-        let base_url = self
-            .base_url
+        self.base_url
             .clone()
-            .unwrap_or(default_base_url.to_string());
+            .unwrap_or(default_base_url.to_string())
+    }
+
+    pub(crate) fn get_full_url(&self, auth: &Option<CodexAuth>) -> String {
+        let query_string = self.get_query_string();
+        let base_url = self.get_base_url(auth);
 
         match self.wire_api {
             WireApi::Responses => format!("{base_url}/responses{query_string}"),
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            // Never requested: mock responses are replayed locally.
+            WireApi::Mock => format!("{base_url}{query_string}"),
         }
     }
 
     /// Apply provider-specific HTTP headers (both static and environment-based)
     /// onto an existing `reqwest::RequestBuilder` and return the updated
-    /// builder.
+    /// builder. A header set from the environment replaces a static header of
+    /// the same name.
     fn apply_http_headers(&self, mut builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let account_headers = [
+            (OPENAI_ORGANIZATION_HEADER, self.organization.as_ref()),
+            (OPENAI_PROJECT_HEADER, self.project.as_ref()),
+        ];
+        let mut env_headers = Vec::new();
+        if let Some(env_http_headers) = &self.env_http_headers {
+            for (header, env_var) in env_http_headers {
+                // Explicitly configured account headers win over the environment.
+                let overridden = account_headers
+                    .iter()
+                    .any(|(name, value)| value.is_some() && header.eq_ignore_ascii_case(name));
+                if !overridden
+                    && let Ok(val) = std::env::var(env_var)
+                    && !val.trim().is_empty()
+                {
+                    env_headers.push((header, val));
+                }
+            }
+        }
+
         if let Some(extra) = &self.http_headers {
             for (k, v) in extra {
-                builder = builder.header(k, v);
+                let from_env = env_headers
+                    .iter()
+                    .any(|(header, _)| header.eq_ignore_ascii_case(k));
+                if !from_env {
+                    builder = builder.header(k, v);
+                }
             }
         }
+        for (header, val) in env_headers {
+            builder = builder.header(header, val);
+        }
 
-        if let Some(env_headers) = &self.env_http_headers {
-            for (header, env_var) in env_headers {
-                if let Ok(val) = std::env::var(env_var)
-                    && !val.trim().is_empty()
-                {
-                    builder = builder.header(header, val);
-                }
+        for (name, value) in account_headers {
+            if let Some(value) = value {
+                builder = builder.header(name, value);
             }
         }
         builder
@@ -266,10 +432,13 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 env_http_headers: Some(
                     [
                         (
-                            "OpenAI-Organization".to_string(),
+                            OPENAI_ORGANIZATION_HEADER.to_string(),
                             "OPENAI_ORGANIZATION".to_string(),
                         ),
-                        ("OpenAI-Project".to_string(), "OPENAI_PROJECT".to_string()),
+                        (
+                            OPENAI_PROJECT_HEADER.to_string(),
+                            "OPENAI_PROJECT".to_string(),
+                        ),
                     ]
                     .into_iter()
                     .collect(),
@@ -279,6 +448,12 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
                 requires_openai_auth: true,
+                instructions_role: None,
+                organization: None,
+                project: None,
+                circuit_breaker: None,
+                mock_script: None,
+                stream_usage: false,
             },
         ),
         (BUILT_IN_OSS_MODEL_PROVIDER_ID, create_oss_provider()),
@@ -323,6 +498,12 @@ pub fn create_oss_provider_with_base_url(base_url: &str) -> ModelProviderInfo {
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
         requires_openai_auth: false,
+        instructions_role: None,
+        organization: None,
+        project: None,
+        circuit_breaker: None,
+        mock_script: None,
+        stream_usage: false,
     }
 }
 
@@ -350,6 +531,12 @@ base_url = "http://localhost:11434/v1"
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage: false,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -379,6 +566,12 @@ query_params = { api-version = "2025-04-01-preview" }
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage: false,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -411,9 +604,125 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            instructions_role: None,
+            organization: None,
+            project: None,
+            circuit_breaker: None,
+            mock_script: None,
+            stream_usage: false,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
         assert_eq!(expected_provider, provider);
     }
+
+    #[tokio::test]
+    async fn organization_and_project_headers_sent_when_set() {
+        let mut provider = create_oss_provider_with_base_url("http://localhost:11434/v1");
+        provider.organization = Some("org-123".into());
+        provider.project = Some("proj_456".into());
+
+        let request = provider
+            .create_request_builder(&reqwest::Client::new(), &None)
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(request.headers()["OpenAI-Organization"], "org-123");
+        assert_eq!(request.headers()["OpenAI-Project"], "proj_456");
+    }
+
+    #[tokio::test]
+    async fn organization_and_project_headers_omitted_when_unset() {
+        let provider = create_oss_provider_with_base_url("http://localhost:11434/v1");
+
+        let request = provider
+            .create_request_builder(&reqwest::Client::new(), &None)
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert!(!request.headers().contains_key("OpenAI-Organization"));
+        assert!(!request.headers().contains_key("OpenAI-Project"));
+    }
+
+    #[tokio::test]
+    async fn http_headers_are_merged_with_env_headers_taking_precedence() {
+        // `PATH` is set in any environment the tests run in.
+        let path = std::env::var("PATH").unwrap_or_default();
+        let provider = ModelProviderInfo {
+            http_headers: Some(maplit::hashmap! {
+                "X-Org-Id".to_string() => "static-org".to_string(),
+                "X-Gateway".to_string() => "static-gateway".to_string(),
+            }),
+            env_http_headers: Some(maplit::hashmap! {
+                "x-gateway".to_string() => "PATH".to_string(),
+                "X-Missing".to_string() => "CODEX_TEST_UNSET_HEADER_VAR".to_string(),
+            }),
+            ..create_oss_provider_with_base_url("http://localhost:1234/v1")
+        };
+
+        let request = provider
+            .create_request_builder(&reqwest::Client::new(), &None)
+            .await
+            .expect("request builder")
+            .build()
+            .expect("request");
+        let headers = request.headers();
+
+        assert_eq!(
+            headers.get_all("X-Org-Id").iter().collect::<Vec<_>>(),
+            vec!["static-org"]
+        );
+        assert_eq!(
+            headers.get_all("X-Gateway").iter().collect::<Vec<_>>(),
+            vec![path.as_str()]
+        );
+        assert!(headers.get("X-Missing").is_none());
+    }
+
+    #[test]
+    fn test_deserialize_organization_and_project() {
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "OpenAI (org)"
+organization = "org-123"
+project = "proj_456"
+"#,
+        )
+        .unwrap();
+        assert_eq!(provider.organization.as_deref(), Some("org-123"));
+        assert_eq!(provider.project.as_deref(), Some("proj_456"));
+    }
+
+    #[test]
+    fn test_instructions_role_defaults_and_overrides() {
+        let mut provider = create_oss_provider_with_base_url("http://localhost:11434/v1");
+        assert_eq!(
+            provider.effective_instructions_role(),
+            InstructionsRole::System
+        );
+
+        provider.base_url = Some("https://api.openai.com/v1".into());
+        assert_eq!(
+            provider.effective_instructions_role(),
+            InstructionsRole::Developer
+        );
+
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Custom"
+base_url = "https://api.openai.com/v1"
+instructions_role = "system"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(provider.instructions_role, Some(InstructionsRole::System));
+        assert_eq!(
+            provider.effective_instructions_role(),
+            InstructionsRole::System
+        );
+    }
 }
@@ -1,10 +1,7 @@
 use crate::model_family::ModelFamily;
+use crate::protocol::ModelPricing;
 
 /// Metadata about a model, particularly OpenAI models.
-/// We may want to consider including details like the pricing for
-/// input tokens, output tokens, etc., though users will need to be able to
-/// override this in config.toml, as this information can get out of date.
-/// Though this would help present more accurate pricing information in the UI.
 #[derive(Debug)]
 pub(crate) struct ModelInfo {
     /// Size of the context window in tokens.
@@ -12,6 +9,19 @@ pub(crate) struct ModelInfo {
 
     /// Maximum number of output tokens that can be generated for the model.
     pub(crate) max_output_tokens: u64,
+
+    /// List price in USD, when known. This can get out of date, so users can
+    /// override it per model in `[model_overrides]`.
+    pub(crate) pricing: Option<ModelPricing>,
+}
+
+/// Pricing per million tokens: input, cached input, output.
+const fn pricing(input: f64, cached_input: Option<f64>, output: f64) -> Option<ModelPricing> {
+    Some(ModelPricing {
+        input_price_per_mtok: input,
+        output_price_per_mtok: output,
+        cached_input_price_per_mtok: cached_input,
+    })
 }
 
 pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
@@ -23,27 +33,32 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         "gpt-oss-20b" => Some(ModelInfo {
             context_window: 96_000,
             max_output_tokens: 32_000,
+            pricing: None,
         }),
         "gpt-oss-120b" => Some(ModelInfo {
             context_window: 96_000,
             max_output_tokens: 32_000,
+            pricing: None,
         }),
         // https://platform.openai.com/docs/models/o3
         "o3" => Some(ModelInfo {
             context_window: 200_000,
             max_output_tokens: 100_000,
+            pricing: pricing(2.0, Some(0.5), 8.0),
         }),
 
         // https://platform.openai.com/docs/models/o4-mini
         "o4-mini" => Some(ModelInfo {
             context_window: 200_000,
             max_output_tokens: 100_000,
+            pricing: pricing(1.1, Some(0.275), 4.4),
         }),
 
         // https://platform.openai.com/docs/models/codex-mini-latest
         "codex-mini-latest" => Some(ModelInfo {
             context_window: 200_000,
             max_output_tokens: 100_000,
+            pricing: pricing(1.5, Some(0.375), 6.0),
         }),
 
         // As of Jun 25, 2025, gpt-4.1 defaults to gpt-4.1-2025-04-14.
@@ -51,6 +66,7 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         "gpt-4.1" | "gpt-4.1-2025-04-14" => Some(ModelInfo {
             context_window: 1_047_576,
             max_output_tokens: 32_768,
+            pricing: pricing(2.0, Some(0.5), 8.0),
         }),
 
         // As of Jun 25, 2025, gpt-4o defaults to gpt-4o-2024-08-06.
@@ -58,34 +74,40 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         "gpt-4o" | "gpt-4o-2024-08-06" => Some(ModelInfo {
             context_window: 128_000,
             max_output_tokens: 16_384,
+            pricing: pricing(2.5, Some(1.25), 10.0),
         }),
 
         // https://platform.openai.com/docs/models/gpt-4o?snapshot=gpt-4o-2024-05-13
         "gpt-4o-2024-05-13" => Some(ModelInfo {
             context_window: 128_000,
             max_output_tokens: 4_096,
+            pricing: pricing(5.0, None, 15.0),
         }),
 
         // https://platform.openai.com/docs/models/gpt-4o?snapshot=gpt-4o-2024-11-20
         "gpt-4o-2024-11-20" => Some(ModelInfo {
             context_window: 128_000,
             max_output_tokens: 16_384,
+            pricing: pricing(2.5, Some(1.25), 10.0),
         }),
 
         // https://platform.openai.com/docs/models/gpt-3.5-turbo
         "gpt-3.5-turbo" => Some(ModelInfo {
             context_window: 16_385,
             max_output_tokens: 4_096,
+            pricing: pricing(0.5, None, 1.5),
         }),
 
         "gpt-5" => Some(ModelInfo {
             context_window: 400_000,
             max_output_tokens: 128_000,
+            pricing: pricing(1.25, Some(0.125), 10.0),
         }),
 
         _ if slug.starts_with("codex-") => Some(ModelInfo {
             context_window: 400_000,
             max_output_tokens: 128_000,
+            pricing: None,
         }),
 
         _ => None,
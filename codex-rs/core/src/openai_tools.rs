@@ -55,6 +55,17 @@ pub(crate) enum OpenAiTool {
     Freeform(FreeformTool),
 }
 
+impl OpenAiTool {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            OpenAiTool::Function(ResponsesApiTool { name, .. }) => name,
+            OpenAiTool::LocalShell {} => "local_shell",
+            OpenAiTool::WebSearch {} => "web_search",
+            OpenAiTool::Freeform(FreeformTool { name, .. }) => name,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ConfigShellToolType {
     DefaultShell,
@@ -604,15 +615,7 @@ mod tests {
     use super::*;
 
     fn assert_eq_tool_names(tools: &[OpenAiTool], expected_names: &[&str]) {
-        let tool_names = tools
-            .iter()
-            .map(|tool| match tool {
-                OpenAiTool::Function(ResponsesApiTool { name, .. }) => name,
-                OpenAiTool::LocalShell {} => "local_shell",
-                OpenAiTool::WebSearch {} => "web_search",
-                OpenAiTool::Freeform(FreeformTool { name, .. }) => name,
-            })
-            .collect::<Vec<_>>();
+        let tool_names = tools.iter().map(OpenAiTool::name).collect::<Vec<_>>();
 
         assert_eq!(
             tool_names.len(),
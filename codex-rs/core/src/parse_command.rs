@@ -1,60 +1,180 @@
 use crate::bash::try_parse_bash;
 use crate::bash::try_parse_word_only_commands_sequence;
+use crate::shell_dialect::ScriptCommand;
+use crate::windows_shell::WindowsCommand;
+use crate::windows_shell::WindowsCommandKind;
+use crate::windows_shell::WindowsShell;
+use crate::windows_shell::try_parse_windows_commands;
 use serde::Deserialize;
 use serde::Serialize;
 use shlex::split as shlex_split;
 use shlex::try_join as shlex_try_join;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
 
+pub use crate::shell_dialect::ShellDialect;
+
+/// Every variant carries `elevated`, set when the command runs through
+/// `sudo`, `doas`, `su` or `pkexec`. `cmd` then includes that prefix while the
+/// variant still describes the command being elevated, and the safety layer
+/// never auto-approves it.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ParsedCommand {
     Read {
         cmd: String,
         name: String,
+        #[serde(default)]
+        elevated: bool,
     },
     ListFiles {
         cmd: String,
         path: Option<String>,
+        #[serde(default)]
+        elevated: bool,
     },
     Search {
         cmd: String,
         query: Option<String>,
         path: Option<String>,
+        #[serde(default)]
+        elevated: bool,
     },
     Format {
         cmd: String,
         tool: Option<String>,
         targets: Option<Vec<String>>,
+        #[serde(default)]
+        elevated: bool,
     },
     Test {
         cmd: String,
+        #[serde(default)]
+        elevated: bool,
     },
     Lint {
         cmd: String,
         tool: Option<String>,
         targets: Option<Vec<String>>,
+        #[serde(default)]
+        elevated: bool,
     },
     Noop {
         cmd: String,
+        #[serde(default)]
+        elevated: bool,
     },
     Unknown {
         cmd: String,
+        #[serde(default)]
+        elevated: bool,
     },
 }
 
+impl ParsedCommand {
+    pub fn is_elevated(&self) -> bool {
+        match self {
+            ParsedCommand::Read { elevated, .. }
+            | ParsedCommand::ListFiles { elevated, .. }
+            | ParsedCommand::Search { elevated, .. }
+            | ParsedCommand::Format { elevated, .. }
+            | ParsedCommand::Test { elevated, .. }
+            | ParsedCommand::Lint { elevated, .. }
+            | ParsedCommand::Noop { elevated, .. }
+            | ParsedCommand::Unknown { elevated, .. } => *elevated,
+        }
+    }
+
+    /// Replaces the displayed command while keeping the classification.
+    fn with_cmd(mut self, new_cmd: String) -> Self {
+        match &mut self {
+            ParsedCommand::Read { cmd, .. }
+            | ParsedCommand::ListFiles { cmd, .. }
+            | ParsedCommand::Search { cmd, .. }
+            | ParsedCommand::Format { cmd, .. }
+            | ParsedCommand::Test { cmd, .. }
+            | ParsedCommand::Lint { cmd, .. }
+            | ParsedCommand::Noop { cmd, .. }
+            | ParsedCommand::Unknown { cmd, .. } => *cmd = new_cmd,
+        }
+        self
+    }
+
+    fn elevated(mut self) -> Self {
+        match &mut self {
+            ParsedCommand::Read { elevated, .. }
+            | ParsedCommand::ListFiles { elevated, .. }
+            | ParsedCommand::Search { elevated, .. }
+            | ParsedCommand::Format { elevated, .. }
+            | ParsedCommand::Test { elevated, .. }
+            | ParsedCommand::Lint { elevated, .. }
+            | ParsedCommand::Noop { elevated, .. }
+            | ParsedCommand::Unknown { elevated, .. } => *elevated = true,
+        }
+        self
+    }
+}
+
 // Convert core's parsed command enum into the protocol's simplified type so
 // events can carry the canonical representation across process boundaries.
 impl From<ParsedCommand> for codex_protocol::parse_command::ParsedCommand {
     fn from(v: ParsedCommand) -> Self {
         use codex_protocol::parse_command::ParsedCommand as P;
         match v {
-            ParsedCommand::Read { cmd, name } => P::Read { cmd, name },
-            ParsedCommand::ListFiles { cmd, path } => P::ListFiles { cmd, path },
-            ParsedCommand::Search { cmd, query, path } => P::Search { cmd, query, path },
-            ParsedCommand::Format { cmd, tool, targets } => P::Format { cmd, tool, targets },
-            ParsedCommand::Test { cmd } => P::Test { cmd },
-            ParsedCommand::Lint { cmd, tool, targets } => P::Lint { cmd, tool, targets },
-            ParsedCommand::Noop { cmd } => P::Noop { cmd },
-            ParsedCommand::Unknown { cmd } => P::Unknown { cmd },
+            ParsedCommand::Read {
+                cmd,
+                name,
+                elevated,
+            } => P::Read {
+                cmd,
+                name,
+                elevated,
+            },
+            ParsedCommand::ListFiles {
+                cmd,
+                path,
+                elevated,
+            } => P::ListFiles {
+                cmd,
+                path,
+                elevated,
+            },
+            ParsedCommand::Search {
+                cmd,
+                query,
+                path,
+                elevated,
+            } => P::Search {
+                cmd,
+                query,
+                path,
+                elevated,
+            },
+            ParsedCommand::Format {
+                cmd,
+                tool,
+                targets,
+                elevated,
+            } => P::Format {
+                cmd,
+                tool,
+                targets,
+                elevated,
+            },
+            ParsedCommand::Test { cmd, elevated } => P::Test { cmd, elevated },
+            ParsedCommand::Lint {
+                cmd,
+                tool,
+                targets,
+                elevated,
+            } => P::Lint {
+                cmd,
+                tool,
+                targets,
+                elevated,
+            },
+            ParsedCommand::Noop { cmd, elevated } => P::Noop { cmd, elevated },
+            ParsedCommand::Unknown { cmd, elevated } => P::Unknown { cmd, elevated },
         }
     }
 }
@@ -74,9 +194,21 @@ fn shlex_join(tokens: &[String]) -> String {
 /// The parsing is slightly lossy due to the ~infinite expressiveness of an arbitrary command.
 /// The goal of the parsed metadata is to be able to provide the user with a human readable gis
 /// of what it is doing.
+///
+/// The syntax dialect is picked from the program `command` runs; see
+/// [`parse_command_with_dialect`] to choose it explicitly.
 pub fn parse_command(command: &[String]) -> Vec<ParsedCommand> {
+    parse_command_with_dialect(command, ShellDialect::for_command(command))
+}
+
+/// Like [`parse_command`], but scripts passed to the shell are tokenized
+/// following `dialect`'s quoting and separator rules.
+pub fn parse_command_with_dialect(command: &[String], dialect: ShellDialect) -> Vec<ParsedCommand> {
     // Parse and then collapse consecutive duplicate commands to avoid redundant summaries.
-    let parsed = parse_command_impl(command);
+    let parsed = match dialect.script(command) {
+        Some(script) => parse_dialect_script(&script, dialect),
+        None => parse_command_impl(command),
+    };
     let mut deduped: Vec<ParsedCommand> = Vec::with_capacity(parsed.len());
     for cmd in parsed.into_iter() {
         if deduped.last().is_some_and(|prev| prev == &cmd) {
@@ -112,8 +244,87 @@ mod tests {
             &vec_str(&["git", "status"]),
             vec![ParsedCommand::Unknown {
                 cmd: "git status".to_string(),
+                elevated: false,
+            }],
+        );
+    }
+
+    #[test]
+    fn leading_sudo_or_doas_is_elevated() {
+        assert_parsed(
+            &vec_str(&["sudo", "rm", "x"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "sudo rm x".to_string(),
+                elevated: true,
+            }],
+        );
+        // The elevated command keeps its own classification.
+        assert_parsed(
+            &vec_str(&["sudo", "-u", "root", "cat", "foo.txt"]),
+            vec![ParsedCommand::Read {
+                cmd: "sudo -u root cat foo.txt".to_string(),
+                name: "foo.txt".to_string(),
+                elevated: true,
+            }],
+        );
+        assert_parsed(
+            &vec_str(&["bash", "-lc", "cd build && doas make install"]),
+            vec![
+                ParsedCommand::Unknown {
+                    cmd: "cd build".to_string(),
+                    elevated: false,
+                },
+                ParsedCommand::Unknown {
+                    cmd: "doas make install".to_string(),
+                    elevated: true,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn elevation_is_found_anywhere_in_a_command() {
+        for script in [
+            "sudo rm -rf x 2>/dev/null",
+            "(sudo rm -rf x)",
+            "echo $(sudo cat /etc/shadow)",
+            "ls && `doas reboot`",
+            "sh -c 'sudo rm -rf x'",
+            "eval 'su -c id'",
+        ] {
+            assert!(
+                is_elevated_command(&vec_str(&["bash", "-lc", script])),
+                "{script}"
+            );
+        }
+        for command in [
+            vec_str(&["sh", "-c", "sudo rm -rf x"]),
+            vec_str(&["env", "FOO=1", "sudo", "rm", "x"]),
+            vec_str(&["nohup", "sudo", "rm", "x"]),
+            vec_str(&["timeout", "5", "sudo", "rm", "x"]),
+            vec_str(&["xargs", "-n", "1", "sudo", "rm"]),
+            vec_str(&["/usr/bin/pkexec", "rm", "x"]),
+            vec_str(&["FOO=1", "sudo", "rm", "x"]),
+        ] {
+            assert!(is_elevated_command(&command), "{command:?}");
+        }
+    }
+
+    #[test]
+    fn sudo_as_argument_is_not_elevated() {
+        assert_parsed(
+            &vec_str(&["git", "commit", "-m", "sudo"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "git commit -m sudo".to_string(),
+                elevated: false,
             }],
         );
+        assert!(!is_elevated_command(&vec_str(&["grep", "-r", "sudo", "."])));
+        assert!(!is_elevated_command(&vec_str(&[
+            "bash",
+            "-lc",
+            "echo sudo"
+        ])));
     }
 
     #[test]
@@ -123,6 +334,7 @@ mod tests {
             &vec_str(&["bash", "-lc", inner]),
             vec![ParsedCommand::Unknown {
                 cmd: "git status | wc -l".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -134,6 +346,7 @@ mod tests {
             &vec_str(&["bash", "-lc", inner]),
             vec![ParsedCommand::Unknown {
                 cmd: "echo foo > bar".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -150,20 +363,25 @@ mod tests {
                     cmd: "rg --version".to_string(),
                     query: None,
                     path: None,
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "node -v".to_string(),
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "pnpm -v".to_string(),
+                    elevated: false,
                 },
                 ParsedCommand::Search {
                     cmd: "rg --files".to_string(),
                     query: None,
                     path: None,
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "head -n 40".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -178,6 +396,7 @@ mod tests {
                 cmd: "rg -n navigate-to-route -S".to_string(),
                 query: Some("navigate-to-route".to_string()),
                 path: None,
+                elevated: false,
             }],
         );
         Ok(())
@@ -193,9 +412,11 @@ mod tests {
                     cmd: "rg -n 'BUG|FIXME|TODO|XXX|HACK' -S".to_string(),
                     query: Some("BUG|FIXME|TODO|XXX|HACK".to_string()),
                     path: None,
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "head -n 200".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -210,6 +431,7 @@ mod tests {
                 cmd: "rg --files webview/src".to_string(),
                 query: None,
                 path: Some("webview".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -224,9 +446,11 @@ mod tests {
                     cmd: "rg --files".to_string(),
                     query: None,
                     path: None,
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "head -n 50".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -240,6 +464,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: inner.to_string(),
                 name: "README.md".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -252,6 +477,7 @@ mod tests {
             vec![ParsedCommand::ListFiles {
                 cmd: "ls -la".to_string(),
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -264,6 +490,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: inner.to_string(),
                 name: "Cargo.toml".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -276,6 +503,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: inner.to_string(),
                 name: "Cargo.toml".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -288,6 +516,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: inner.to_string(),
                 name: "README.md".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -301,6 +530,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: inner.to_string(),
                 name: "README.md".to_string(),
+                elevated: false,
             }]
         );
     }
@@ -311,6 +541,7 @@ mod tests {
             &vec_str(&["npm", "run", "build"]),
             vec![ParsedCommand::Unknown {
                 cmd: "npm run build".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -332,6 +563,7 @@ mod tests {
                 cmd: "npm run lint -- --max-warnings 0 --format json".to_string(),
                 tool: Some("npm-script:lint".to_string()),
                 targets: None,
+                elevated: false,
             }],
         );
     }
@@ -344,6 +576,7 @@ mod tests {
                 cmd: "grep -R CODEX_SANDBOX_ENV_VAR -n .".to_string(),
                 query: Some("CODEX_SANDBOX_ENV_VAR".to_string()),
                 path: Some(".".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -362,6 +595,7 @@ mod tests {
                 cmd: "grep -R CODEX_SANDBOX_ENV_VAR -n core/src/spawn.rs".to_string(),
                 query: Some("CODEX_SANDBOX_ENV_VAR".to_string()),
                 path: Some("spawn.rs".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -376,6 +610,7 @@ mod tests {
                 cmd: "grep -R src/main.rs -n .".to_string(),
                 query: Some("src/main.rs".to_string()),
                 path: Some(".".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -388,6 +623,7 @@ mod tests {
                 cmd: "grep -R 'COD`EX_SANDBOX' -n".to_string(),
                 query: Some("COD`EX_SANDBOX".to_string()),
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -399,11 +635,13 @@ mod tests {
             vec![
                 ParsedCommand::Unknown {
                     cmd: "cd codex-rs".to_string(),
+                    elevated: false,
                 },
                 ParsedCommand::Search {
                     cmd: "rg --files".to_string(),
                     query: None,
                     path: None,
+                    elevated: false,
                 },
             ],
         );
@@ -415,6 +653,7 @@ mod tests {
             &shlex_split_safe("echo Running tests... && cargo test --all-features --quiet"),
             vec![ParsedCommand::Test {
                 cmd: "cargo test --all-features --quiet".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -430,9 +669,11 @@ mod tests {
                     cmd: "cargo fmt -- --config 'imports_granularity=Item'".to_string(),
                     tool: Some("cargo fmt".to_string()),
                     targets: None,
+                    elevated: false,
                 },
                 ParsedCommand::Test {
                     cmd: "cargo test -p core --all-features".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -446,6 +687,7 @@ mod tests {
                 cmd: "rustfmt src/main.rs".to_string(),
                 tool: Some("rustfmt".to_string()),
                 targets: Some(vec!["src/main.rs".to_string()]),
+                elevated: false,
             }],
         );
 
@@ -455,6 +697,7 @@ mod tests {
                 cmd: "cargo clippy -p core --all-features -- -D warnings".to_string(),
                 tool: Some("cargo clippy".to_string()),
                 targets: None,
+                elevated: false,
             }],
         );
     }
@@ -468,6 +711,7 @@ mod tests {
             vec![ParsedCommand::Test {
                 cmd: "pytest -k 'Login and not slow' tests/test_login.py::TestLogin::test_ok"
                     .to_string(),
+                elevated: false,
             }],
         );
 
@@ -477,6 +721,7 @@ mod tests {
                 cmd: "go fmt ./...".to_string(),
                 tool: Some("go fmt".to_string()),
                 targets: Some(vec!["./...".to_string()]),
+                elevated: false,
             }],
         );
 
@@ -484,6 +729,7 @@ mod tests {
             &shlex_split_safe("go test ./pkg -run TestThing"),
             vec![ParsedCommand::Test {
                 cmd: "go test ./pkg -run TestThing".to_string(),
+                elevated: false,
             }],
         );
 
@@ -493,6 +739,7 @@ mod tests {
                 cmd: "eslint . --max-warnings 0".to_string(),
                 tool: Some("eslint".to_string()),
                 targets: Some(vec![".".to_string()]),
+                elevated: false,
             }],
         );
 
@@ -502,6 +749,7 @@ mod tests {
                 cmd: "prettier -w .".to_string(),
                 tool: Some("prettier".to_string()),
                 targets: Some(vec![".".to_string()]),
+                elevated: false,
             }],
         );
     }
@@ -512,6 +760,7 @@ mod tests {
             &shlex_split_safe("jest -t 'should work' src/foo.test.ts"),
             vec![ParsedCommand::Test {
                 cmd: "jest -t 'should work' src/foo.test.ts".to_string(),
+                elevated: false,
             }],
         );
 
@@ -519,6 +768,7 @@ mod tests {
             &shlex_split_safe("vitest -t 'runs' src/foo.test.tsx"),
             vec![ParsedCommand::Test {
                 cmd: "vitest -t runs src/foo.test.tsx".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -531,6 +781,7 @@ mod tests {
                 cmd: "npx eslint src".to_string(),
                 tool: Some("eslint".to_string()),
                 targets: Some(vec!["src".to_string()]),
+                elevated: false,
             }],
         );
 
@@ -540,6 +791,7 @@ mod tests {
                 cmd: "npx prettier -c .".to_string(),
                 tool: Some("prettier".to_string()),
                 targets: Some(vec![".".to_string()]),
+                elevated: false,
             }],
         );
 
@@ -549,6 +801,7 @@ mod tests {
                 cmd: "pnpm run lint -- --max-warnings 0".to_string(),
                 tool: Some("pnpm-script:lint".to_string()),
                 targets: None,
+                elevated: false,
             }],
         );
 
@@ -556,6 +809,7 @@ mod tests {
             &shlex_split_safe("npm test"),
             vec![ParsedCommand::Test {
                 cmd: "npm test".to_string(),
+                elevated: false,
             }],
         );
 
@@ -563,6 +817,7 @@ mod tests {
             &shlex_split_safe("yarn test"),
             vec![ParsedCommand::Test {
                 cmd: "yarn test".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -654,6 +909,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: inner.to_string(),
                 name: "parse_command.rs".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -666,6 +922,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: inner.to_string(),
                 name: "history_cell.rs".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -679,6 +936,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: "cat -- ansi-escape/Cargo.toml".to_string(),
                 name: "Cargo.toml".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -693,6 +951,7 @@ mod tests {
                 cmd: "rg --files".to_string(),
                 query: None,
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -708,6 +967,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: "sed -n '260,640p' exec/src/event_processor_with_human_output.rs".to_string(),
                 name: "event_processor_with_human_output.rs".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -720,6 +980,7 @@ mod tests {
                 cmd: "rg -n 'foo bar' -S".to_string(),
                 query: Some("foo bar".to_string()),
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -731,6 +992,7 @@ mod tests {
             vec![ParsedCommand::ListFiles {
                 cmd: "ls -I '*.test.js'".to_string(),
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -744,9 +1006,11 @@ mod tests {
                     cmd: "rg foo".to_string(),
                     query: Some("foo".to_string()),
                     path: None,
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "echo done".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -762,9 +1026,11 @@ mod tests {
                     cmd: "rg foo".to_string(),
                     query: Some("foo".to_string()),
                     path: None,
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "echo done".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -779,6 +1045,7 @@ mod tests {
                 cmd: "rg --files".to_string(),
                 query: None,
                 path: None,
+                elevated: false,
             }],
         );
 
@@ -788,6 +1055,7 @@ mod tests {
                 cmd: "rg --files".to_string(),
                 query: None,
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -801,6 +1069,7 @@ mod tests {
                 cmd: "rg --files".to_string(),
                 query: None,
                 path: None,
+                elevated: false,
             }],
         );
 
@@ -811,6 +1080,7 @@ mod tests {
                 cmd: "rg --files".to_string(),
                 query: None,
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -822,6 +1092,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: r#"cat "pkg\\src\\main.rs""#.to_string(),
                 name: "main.rs".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -833,6 +1104,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: "head -n50 Cargo.toml".to_string(),
                 name: "Cargo.toml".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -848,9 +1120,11 @@ mod tests {
                     cmd: "rg --files".to_string(),
                     query: None,
                     path: None,
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "head -n 1".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -863,6 +1137,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: "tail -n+10 README.md".to_string(),
                 name: "README.md".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -873,6 +1148,7 @@ mod tests {
             &shlex_split_safe("pnpm test"),
             vec![ParsedCommand::Test {
                 cmd: "pnpm test".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -886,9 +1162,11 @@ mod tests {
             vec![
                 ParsedCommand::Unknown {
                     cmd: "cd codex-cli".to_string(),
+                    elevated: false,
                 },
                 ParsedCommand::Unknown {
                     cmd: "pnpm exec vitest run tests/file-tag-utils.test.ts '--threads=false' --passWithNoTests".to_string(),
+                    elevated: false,
                 },
             ],
         );
@@ -900,6 +1178,7 @@ mod tests {
             &shlex_split_safe("cargo test -p codex-core parse_command::"),
             vec![ParsedCommand::Test {
                 cmd: "cargo test -p codex-core parse_command::".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -912,6 +1191,7 @@ mod tests {
             ),
             vec![ParsedCommand::Test {
                 cmd: "cargo test -q parse_command::tests::bash_dash_c_pipeline_parsing parse_command::tests::fd_file_finder_variants".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -922,6 +1202,7 @@ mod tests {
             &shlex_split_safe("cd core && cargo test -q parse_command::tests"),
             vec![ParsedCommand::Test {
                 cmd: "cargo test -q parse_command::tests".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -932,6 +1213,7 @@ mod tests {
             &shlex_split_safe("cd core && cargo test --all-features parse_command -- --nocapture"),
             vec![ParsedCommand::Test {
                 cmd: "cargo test --all-features parse_command -- --nocapture".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -946,6 +1228,7 @@ mod tests {
                 cmd: "black src".to_string(),
                 tool: Some("black".to_string()),
                 targets: Some(vec!["src".to_string()]),
+                elevated: false,
             }],
         );
 
@@ -956,6 +1239,7 @@ mod tests {
                 cmd: "ruff check .".to_string(),
                 tool: Some("ruff".to_string()),
                 targets: Some(vec![".".to_string()]),
+                elevated: false,
             }],
         );
 
@@ -966,6 +1250,7 @@ mod tests {
                 cmd: "ruff format pkg/".to_string(),
                 tool: Some("ruff".to_string()),
                 targets: Some(vec!["pkg/".to_string()]),
+                elevated: false,
             }],
         );
     }
@@ -977,6 +1262,7 @@ mod tests {
             &shlex_split_safe("pnpm -r test"),
             vec![ParsedCommand::Test {
                 cmd: "pnpm -r test".to_string(),
+                elevated: false,
             }],
         );
 
@@ -987,6 +1273,7 @@ mod tests {
                 cmd: "npm run format -- -w .".to_string(),
                 tool: Some("npm-script:format".to_string()),
                 targets: None,
+                elevated: false,
             }],
         );
     }
@@ -997,6 +1284,7 @@ mod tests {
             &shlex_split_safe("yarn test"),
             vec![ParsedCommand::Test {
                 cmd: "yarn test".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -1008,6 +1296,7 @@ mod tests {
             &shlex_split_safe("pytest tests/test_example.py"),
             vec![ParsedCommand::Test {
                 cmd: "pytest tests/test_example.py".to_string(),
+                elevated: false,
             }],
         );
 
@@ -1016,6 +1305,7 @@ mod tests {
             &shlex_split_safe("go test ./... -run '^TestFoo$'"),
             vec![ParsedCommand::Test {
                 cmd: "go test ./... -run '^TestFoo$'".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -1028,6 +1318,7 @@ mod tests {
                 cmd: "grep -R TODO src".to_string(),
                 query: Some("TODO".to_string()),
                 path: Some("src".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -1040,6 +1331,7 @@ mod tests {
                 cmd: "rg '--colors=never' -n foo src".to_string(),
                 query: Some("foo".to_string()),
                 path: Some("src".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -1052,6 +1344,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: "cat -- ./-strange-file-name".to_string(),
                 name: "-strange-file-name".to_string(),
+                elevated: false,
             }],
         );
 
@@ -1061,6 +1354,7 @@ mod tests {
             vec![ParsedCommand::Read {
                 cmd: "sed -n '12,20p' Cargo.toml".to_string(),
                 name: "Cargo.toml".to_string(),
+                elevated: false,
             }],
         );
     }
@@ -1074,6 +1368,7 @@ mod tests {
                 cmd: "rg --files".to_string(),
                 query: None,
                 path: None,
+                elevated: false,
             }],
         );
     }
@@ -1086,6 +1381,7 @@ mod tests {
                 cmd: "ls '--time-style=long-iso' ./dist".to_string(),
                 // short_display_path drops "dist" and shows "." as the last useful segment
                 path: Some(".".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -1098,6 +1394,7 @@ mod tests {
                 cmd: "eslint -c .eslintrc.json src".to_string(),
                 tool: Some("eslint".to_string()),
                 targets: Some(vec!["src".to_string()]),
+                elevated: false,
             }],
         );
     }
@@ -1110,6 +1407,7 @@ mod tests {
                 cmd: "npx eslint -c .eslintrc src".to_string(),
                 tool: Some("eslint".to_string()),
                 targets: Some(vec!["src".to_string()]),
+                elevated: false,
             }],
         );
     }
@@ -1122,6 +1420,7 @@ mod tests {
                 cmd: "fd -t f src/".to_string(),
                 query: None,
                 path: Some("src".to_string()),
+                elevated: false,
             }],
         );
 
@@ -1132,6 +1431,7 @@ mod tests {
                 cmd: "fd main src".to_string(),
                 query: Some("main".to_string()),
                 path: Some("src".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -1144,6 +1444,7 @@ mod tests {
                 cmd: "find . -name '*.rs'".to_string(),
                 query: Some("*.rs".to_string()),
                 path: Some(".".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -1156,6 +1457,319 @@ mod tests {
                 cmd: "find src -type f".to_string(),
                 query: None,
                 path: Some("src".to_string()),
+                elevated: false,
+            }],
+        );
+    }
+
+    #[test]
+    fn cmd_dir_is_list_files_and_del_is_unknown() {
+        assert_parsed(
+            &vec_str(&["cmd.exe", "/c", "dir /b src && del out.txt"]),
+            vec![
+                ParsedCommand::ListFiles {
+                    cmd: "dir /b src".to_string(),
+                    path: Some("src".to_string()),
+                    elevated: false,
+                },
+                ParsedCommand::Unknown {
+                    cmd: "del out.txt".to_string(),
+                    elevated: false,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn powershell_cmdlets_are_classified() {
+        assert_parsed(
+            &vec_str(&[
+                "powershell",
+                "-NoProfile",
+                "-Command",
+                "Get-ChildItem -Recurse docs ; Get-Content README.md",
+            ]),
+            vec![
+                ParsedCommand::ListFiles {
+                    cmd: "Get-ChildItem -Recurse docs".to_string(),
+                    path: Some("docs".to_string()),
+                    elevated: false,
+                },
+                ParsedCommand::Read {
+                    cmd: "Get-Content README.md".to_string(),
+                    name: "README.md".to_string(),
+                    elevated: false,
+                },
+            ],
+        );
+        assert_parsed(
+            &vec_str(&["Remove-Item", "-Recurse", "build"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "Remove-Item -Recurse build".to_string(),
+                elevated: false,
+            }],
+        );
+    }
+
+    #[test]
+    fn fish_chains_with_and_or_and_escapes_single_quotes() {
+        assert_parsed(
+            &vec_str(&[
+                "fish",
+                "-c",
+                "ls src; and cat 'my notes.txt'; or rg 'it\\'s here' docs",
+            ]),
+            vec![
+                ParsedCommand::ListFiles {
+                    cmd: "ls src".to_string(),
+                    path: Some("src".to_string()),
+                    elevated: false,
+                },
+                ParsedCommand::Read {
+                    cmd: "cat 'my notes.txt'".to_string(),
+                    name: "my notes.txt".to_string(),
+                    elevated: false,
+                },
+                ParsedCommand::Search {
+                    cmd: "rg 'it\\'s here' docs".to_string(),
+                    query: Some("it's here".to_string()),
+                    path: Some("docs".to_string()),
+                    elevated: false,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn fish_redirections_and_command_substitution_are_unknown() {
+        assert_parsed(
+            &vec_str(&["fish", "-c", "cat a.txt > 'out file.txt'"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "cat a.txt > 'out file.txt'".to_string(),
+                elevated: false,
+            }],
+        );
+        assert_parsed(
+            &vec_str(&["fish", "-c", "rg needle 2>/dev/null"]),
+            vec![ParsedCommand::Search {
+                cmd: "rg needle 2>/dev/null".to_string(),
+                query: Some("needle".to_string()),
+                path: None,
+                elevated: false,
+            }],
+        );
+        assert_parsed(
+            &vec_str(&["fish", "-c", "cat (ls)"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "cat (ls)".to_string(),
+                elevated: false,
+            }],
+        );
+    }
+
+    #[test]
+    fn powershell_splits_on_semicolons_and_pipes() {
+        assert_parsed(
+            &vec_str(&[
+                "pwsh",
+                "-Command",
+                "Get-Content 'My Notes.txt' ; git status | Select-String \"needle here\"",
+            ]),
+            vec![
+                ParsedCommand::Read {
+                    cmd: "Get-Content 'My Notes.txt'".to_string(),
+                    name: "My Notes.txt".to_string(),
+                    elevated: false,
+                },
+                ParsedCommand::Unknown {
+                    cmd: "git status".to_string(),
+                    elevated: false,
+                },
+                ParsedCommand::Search {
+                    cmd: "Select-String 'needle here'".to_string(),
+                    query: Some("needle here".to_string()),
+                    path: None,
+                    elevated: false,
+                },
+            ],
+        );
+        // A separator inside a word is rejected, as by the safety check.
+        assert_parsed(
+            &vec_str(&["pwsh", "-Command", "Get-ChildItem x; Remove-Item y"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "Get-ChildItem x; Remove-Item y".to_string(),
+                elevated: false,
+            }],
+        );
+    }
+
+    #[test]
+    fn powershell_escapes_are_not_interpreted() {
+        assert_parsed(
+            &vec_str(&[
+                "powershell.exe",
+                "-Command",
+                "Select-String needle \"a `\"b`\".txt\"",
+            ]),
+            vec![ParsedCommand::Unknown {
+                cmd: "Select-String needle \"a `\"b`\".txt\"".to_string(),
+                elevated: false,
+            }],
+        );
+    }
+
+    #[test]
+    fn powershell_redirections_and_subexpressions() {
+        assert_parsed(
+            &vec_str(&["pwsh", "-Command", "Get-Content a.txt > b.txt"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "Get-Content a.txt > b.txt".to_string(),
+                elevated: false,
+            }],
+        );
+        assert_parsed(
+            &vec_str(&["pwsh", "-Command", "Get-Content $(Get-Item x)"]),
+            vec![ParsedCommand::Unknown {
+                cmd: "Get-Content $(Get-Item x)".to_string(),
+                elevated: false,
+            }],
+        );
+    }
+
+    fn search_roots(scope: &SearchScope) -> Vec<(PathBuf, bool)> {
+        scope
+            .roots
+            .iter()
+            .map(|root| (root.path.clone(), root.outside_workspace))
+            .collect()
+    }
+
+    #[test]
+    fn search_scope_rg_with_relative_root() {
+        let cwd = Path::new("/work/repo");
+        let scopes = parse_search_scopes(&vec_str(&["rg", "foo", "src/"]), cwd);
+        assert_eq!(scopes.len(), 1);
+        assert_eq!(scopes[0].pattern.as_deref(), Some("foo"));
+        assert_eq!(
+            search_roots(&scopes[0]),
+            vec![(PathBuf::from("/work/repo/src"), false)]
+        );
+        assert!(!scopes[0].escapes_workspace());
+    }
+
+    #[test]
+    fn search_scope_find_defaults_to_cwd_root() {
+        let cwd = Path::new("/work/repo");
+        let scopes = parse_search_scopes(&shlex_split_safe("find . -name '*.rs'"), cwd);
+        assert_eq!(scopes.len(), 1);
+        assert_eq!(scopes[0].pattern.as_deref(), Some("*.rs"));
+        assert_eq!(
+            search_roots(&scopes[0]),
+            vec![(PathBuf::from("/work/repo"), false)]
+        );
+
+        let scopes = parse_search_scopes(&vec_str(&["rg", "-e", "needle"]), cwd);
+        assert_eq!(scopes[0].pattern.as_deref(), Some("needle"));
+        assert_eq!(
+            search_roots(&scopes[0]),
+            vec![(PathBuf::from("/work/repo"), false)]
+        );
+    }
+
+    #[test]
+    fn search_scope_flags_roots_outside_workspace() {
+        let cwd = Path::new("/work/repo");
+        let scopes = parse_search_scopes(
+            &vec_str(&[
+                "bash",
+                "-lc",
+                "grep -rn token ../other /etc && fd -e rs main",
+            ]),
+            cwd,
+        );
+        assert_eq!(scopes.len(), 2);
+        assert_eq!(scopes[0].pattern.as_deref(), Some("token"));
+        assert_eq!(
+            search_roots(&scopes[0]),
+            vec![
+                (PathBuf::from("/work/other"), true),
+                (PathBuf::from("/etc"), true),
+            ]
+        );
+        assert!(scopes[0].escapes_workspace());
+        assert_eq!(scopes[1].pattern.as_deref(), Some("main"));
+        assert!(!scopes[1].escapes_workspace());
+    }
+
+    #[test]
+    fn search_scope_ignores_non_search_commands() {
+        let scopes = parse_search_scopes(&vec_str(&["cat", "/etc/passwd"]), Path::new("/w"));
+        assert!(scopes.is_empty());
+    }
+
+    #[test]
+    fn heredoc_write_inside_workspace() {
+        let cwd = Path::new("/work/repo");
+        let script = "mkdir -p docs && cat <<'EOF' > docs/notes.md\nrm -rf /\nEOF\necho done";
+        let command = vec_str(&["bash", "-lc", script]);
+        assert_eq!(
+            parse_heredoc_writes(&command, cwd),
+            vec![HeredocWrite {
+                cmd: "cat > docs/notes.md".to_string(),
+                path: PathBuf::from("/work/repo/docs/notes.md"),
+                append: false,
+                outside_workspace: false,
+            }]
+        );
+        // The body is data: the script is not summarized as running `rm`.
+        assert_eq!(
+            parse_command(&command),
+            vec![ParsedCommand::Unknown {
+                cmd: script.to_string(),
+                elevated: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn heredoc_write_outside_workspace_is_flagged() {
+        let cwd = Path::new("/work/repo");
+        let script = "cat <<-EOF | tee -a ../../etc/profile\n\texport X=1\n\tEOF";
+        let writes = parse_heredoc_writes(&vec_str(&["bash", "-c", script]), cwd);
+        assert_eq!(
+            writes,
+            vec![HeredocWrite {
+                cmd: "cat | tee -a ../../etc/profile".to_string(),
+                path: PathBuf::from("/etc/profile"),
+                append: true,
+                outside_workspace: true,
+            }]
+        );
+
+        let writes = parse_heredoc_writes(
+            &vec_str(&["bash", "-lc", "cat >>/tmp/out.txt <<\"END\"\nhello\nEND"]),
+            cwd,
+        );
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].path, PathBuf::from("/tmp/out.txt"));
+        assert!(writes[0].append && writes[0].outside_workspace);
+    }
+
+    #[test]
+    fn heredoc_without_file_target_is_not_a_write() {
+        let command = vec_str(&["bash", "-lc", "python3 <<'EOF'\nprint(1)\nEOF"]);
+        assert!(parse_heredoc_writes(&command, Path::new("/w")).is_empty());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn bare_cmd_builtins_are_classified_on_windows() {
+        assert_parsed(
+            &vec_str(&["dir", "src"]),
+            vec![ParsedCommand::ListFiles {
+                cmd: "dir src".to_string(),
+                path: Some("src".to_string()),
+                elevated: false,
             }],
         );
     }
@@ -1166,6 +1780,10 @@ pub fn parse_command_impl(command: &[String]) -> Vec<ParsedCommand> {
         return commands;
     }
 
+    if let Some(commands) = try_parse_windows_commands(command) {
+        return commands.iter().map(summarize_windows_command).collect();
+    }
+
     let normalized = normalize_tokens(command);
 
     let parts = if contains_connectors(&normalized) {
@@ -1190,13 +1808,100 @@ pub fn parse_command_impl(command: &[String]) -> Vec<ParsedCommand> {
     commands
 }
 
+/// Summarize a fish or PowerShell script command by command. Commands that
+/// redirect to a file are reported as `Unknown` so the write is visible.
+fn parse_dialect_script(script: &str, dialect: ShellDialect) -> Vec<ParsedCommand> {
+    let Some(script_commands) = dialect.split_script(script) else {
+        return vec![ParsedCommand::Unknown {
+            cmd: script.to_string(),
+            elevated: false,
+        }];
+    };
+    let mut commands: Vec<ParsedCommand> = script_commands
+        .into_iter()
+        .map(|command| summarize_script_command(command, dialect))
+        .collect();
+    while let Some(next) = simplify_once(&commands) {
+        commands = next;
+    }
+    commands
+}
+
+/// Summarize one command of a fish or PowerShell script, keeping the
+/// command as written (rather than re-quoted for bash) for display.
+fn summarize_script_command(command: ScriptCommand, dialect: ShellDialect) -> ParsedCommand {
+    let ScriptCommand {
+        words,
+        redirected,
+        text: cmd,
+    } = command;
+    if redirected {
+        let elevated = words.first().is_some_and(|word| is_elevation_command(word));
+        return ParsedCommand::Unknown { cmd, elevated };
+    }
+    let windows_command = WindowsCommand {
+        shell: WindowsShell::PowerShell,
+        tokens: words,
+    };
+    let parsed = if dialect == ShellDialect::PowerShell
+        && windows_command.kind() != WindowsCommandKind::Unknown
+    {
+        summarize_windows_command(&windows_command)
+    } else {
+        summarize_main_tokens(&windows_command.tokens)
+    };
+    parsed.with_cmd(cmd)
+}
+
+/// Summarize a command run by `cmd.exe` or PowerShell. Destructive and
+/// unrecognised commands are reported as `Unknown` so they are shown verbatim.
+fn summarize_windows_command(command: &WindowsCommand) -> ParsedCommand {
+    let cmd = shlex_join(&command.tokens);
+    // Positional arguments, skipping `/switch` (cmd) and `-Switch` (PowerShell) flags.
+    let mut args = command
+        .tokens
+        .iter()
+        .skip(1)
+        .filter(|a| !a.starts_with('/') && !a.starts_with('-'));
+    match command.kind() {
+        WindowsCommandKind::ListFiles => ParsedCommand::ListFiles {
+            cmd,
+            path: args.next().map(|p| short_display_path(p)),
+            elevated: false,
+        },
+        WindowsCommandKind::Read => match args.next() {
+            Some(path) => ParsedCommand::Read {
+                cmd,
+                name: short_display_path(path),
+                elevated: false,
+            },
+            None => ParsedCommand::Unknown {
+                cmd,
+                elevated: false,
+            },
+        },
+        WindowsCommandKind::Search => ParsedCommand::Search {
+            cmd,
+            query: args.next().cloned(),
+            path: args.next().map(|p| short_display_path(p)),
+            elevated: false,
+        },
+        WindowsCommandKind::ReadOnly
+        | WindowsCommandKind::Destructive
+        | WindowsCommandKind::Unknown => ParsedCommand::Unknown {
+            cmd,
+            elevated: false,
+        },
+    }
+}
+
 fn simplify_once(commands: &[ParsedCommand]) -> Option<Vec<ParsedCommand>> {
     if commands.len() <= 1 {
         return None;
     }
 
     // echo ... && ...rest => ...rest
-    if let ParsedCommand::Unknown { cmd } = &commands[0]
+    if let ParsedCommand::Unknown { cmd, .. } = &commands[0]
         && shlex_split(cmd).is_some_and(|t| t.first().map(|s| s.as_str()) == Some("echo"))
     {
         return Some(commands[1..].to_vec());
@@ -1204,7 +1909,7 @@ fn simplify_once(commands: &[ParsedCommand]) -> Option<Vec<ParsedCommand>> {
 
     // cd foo && [any Test command] => [any Test command]
     if let Some(idx) = commands.iter().position(|pc| match pc {
-        ParsedCommand::Unknown { cmd } => {
+        ParsedCommand::Unknown { cmd, .. } => {
             shlex_split(cmd).is_some_and(|t| t.first().map(|s| s.as_str()) == Some("cd"))
         }
         _ => false,
@@ -1221,7 +1926,7 @@ fn simplify_once(commands: &[ParsedCommand]) -> Option<Vec<ParsedCommand>> {
 
     // cmd || true => cmd
     if let Some(idx) = commands.iter().position(|pc| match pc {
-        ParsedCommand::Noop { cmd } => cmd == "true",
+        ParsedCommand::Noop { cmd, .. } => cmd == "true",
         _ => false,
     }) {
         let mut out = Vec::with_capacity(commands.len() - 1);
@@ -1232,7 +1937,7 @@ fn simplify_once(commands: &[ParsedCommand]) -> Option<Vec<ParsedCommand>> {
 
     // nl -[any_flags] && ...rest => ...rest
     if let Some(idx) = commands.iter().position(|pc| match pc {
-        ParsedCommand::Unknown { cmd } => {
+        ParsedCommand::Unknown { cmd, .. } => {
             if let Some(tokens) = shlex_split(cmd) {
                 tokens.first().is_some_and(|s| s.as_str() == "nl")
                     && tokens.iter().skip(1).all(|t| t.starts_with('-'))
@@ -1535,6 +2240,7 @@ fn classify_npm_like(tool: &str, tail: &[String], full_cmd: &[String]) -> Option
         if lname == "test" || lname == "unit" || lname == "jest" || lname == "vitest" {
             return Some(ParsedCommand::Test {
                 cmd: shlex_join(full_cmd),
+                elevated: false,
             });
         }
         if lname == "lint" || lname == "eslint" {
@@ -1542,6 +2248,7 @@ fn classify_npm_like(tool: &str, tail: &[String], full_cmd: &[String]) -> Option
                 cmd: shlex_join(full_cmd),
                 tool: Some(format!("{tool}-script:{name}")),
                 targets: None,
+                elevated: false,
             });
         }
         if lname == "format" || lname == "fmt" || lname == "prettier" {
@@ -1549,6 +2256,7 @@ fn classify_npm_like(tool: &str, tail: &[String], full_cmd: &[String]) -> Option
                 cmd: shlex_join(full_cmd),
                 tool: Some(format!("{tool}-script:{name}")),
                 targets: None,
+                elevated: false,
             });
         }
     }
@@ -1579,6 +2287,7 @@ fn parse_bash_lc_commands(original: &[String]) -> Option<Vec<ParsedCommand>> {
         if filtered_commands.is_empty() {
             return Some(vec![ParsedCommand::Unknown {
                 cmd: script.clone(),
+                elevated: false,
             }]);
         }
         let mut commands: Vec<ParsedCommand> = filtered_commands
@@ -1599,83 +2308,40 @@ fn parse_bash_lc_commands(original: &[String]) -> Option<Vec<ParsedCommand>> {
                     .any(|t| t == "|" || t == "&&" || t == "||" || t == ";");
             commands = commands
                 .into_iter()
-                .map(|pc| match pc {
-                    ParsedCommand::Read { name, cmd, .. } => {
-                        if had_connectors {
-                            let has_pipe = script_tokens.iter().any(|t| t == "|");
-                            let has_sed_n = script_tokens.windows(2).any(|w| {
-                                w.first().map(|s| s.as_str()) == Some("sed")
-                                    && w.get(1).map(|s| s.as_str()) == Some("-n")
-                            });
-                            if has_pipe && has_sed_n {
-                                ParsedCommand::Read {
-                                    cmd: script.clone(),
-                                    name,
+                .map(|pc| {
+                    let cmd = match &pc {
+                        ParsedCommand::Read { cmd, .. } => {
+                            if had_connectors {
+                                let has_pipe = script_tokens.iter().any(|t| t == "|");
+                                let has_sed_n = script_tokens.windows(2).any(|w| {
+                                    w.first().map(|s| s.as_str()) == Some("sed")
+                                        && w.get(1).map(|s| s.as_str()) == Some("-n")
+                                });
+                                if has_pipe && has_sed_n {
+                                    script.clone()
+                                } else {
+                                    cmd.clone()
                                 }
                             } else {
-                                ParsedCommand::Read {
-                                    cmd: cmd.clone(),
-                                    name,
-                                }
-                            }
-                        } else {
-                            ParsedCommand::Read {
-                                cmd: shlex_join(&script_tokens),
-                                name,
+                                shlex_join(&script_tokens)
                             }
                         }
-                    }
-                    ParsedCommand::ListFiles { path, cmd, .. } => {
-                        if had_connectors {
-                            ParsedCommand::ListFiles {
-                                cmd: cmd.clone(),
-                                path,
-                            }
-                        } else {
-                            ParsedCommand::ListFiles {
-                                cmd: shlex_join(&script_tokens),
-                                path,
+                        ParsedCommand::ListFiles { cmd, .. }
+                        | ParsedCommand::Search { cmd, .. } => {
+                            if had_connectors {
+                                cmd.clone()
+                            } else {
+                                shlex_join(&script_tokens)
                             }
                         }
-                    }
-                    ParsedCommand::Search {
-                        query, path, cmd, ..
-                    } => {
-                        if had_connectors {
-                            ParsedCommand::Search {
-                                cmd: cmd.clone(),
-                                query,
-                                path,
-                            }
-                        } else {
-                            ParsedCommand::Search {
-                                cmd: shlex_join(&script_tokens),
-                                query,
-                                path,
-                            }
+                        ParsedCommand::Format { cmd, .. }
+                        | ParsedCommand::Test { cmd, .. }
+                        | ParsedCommand::Lint { cmd, .. } => cmd.clone(),
+                        ParsedCommand::Unknown { .. } | ParsedCommand::Noop { .. } => {
+                            script.clone()
                         }
-                    }
-                    ParsedCommand::Format {
-                        tool, targets, cmd, ..
-                    } => ParsedCommand::Format {
-                        cmd: cmd.clone(),
-                        tool,
-                        targets,
-                    },
-                    ParsedCommand::Test { cmd, .. } => ParsedCommand::Test { cmd: cmd.clone() },
-                    ParsedCommand::Lint {
-                        tool, targets, cmd, ..
-                    } => ParsedCommand::Lint {
-                        cmd: cmd.clone(),
-                        tool,
-                        targets,
-                    },
-                    ParsedCommand::Unknown { .. } => ParsedCommand::Unknown {
-                        cmd: script.clone(),
-                    },
-                    ParsedCommand::Noop { .. } => ParsedCommand::Noop {
-                        cmd: script.clone(),
-                    },
+                    };
+                    pc.with_cmd(cmd)
                 })
                 .collect();
         }
@@ -1683,9 +2349,158 @@ fn parse_bash_lc_commands(original: &[String]) -> Option<Vec<ParsedCommand>> {
     }
     Some(vec![ParsedCommand::Unknown {
         cmd: script.clone(),
+        elevated: false,
     }])
 }
 
+/// Whether `program` runs its arguments with elevated privileges.
+fn is_elevation_command(program: &str) -> bool {
+    matches!(
+        Path::new(program)
+            .file_name()
+            .and_then(|name| name.to_str()),
+        Some("sudo" | "doas" | "su" | "pkexec")
+    )
+}
+
+/// Options of elevation commands that consume the following word.
+const ELEVATION_FLAGS_WITH_VALUES: &[&str] = &[
+    "-u", "-g", "-h", "-p", "-C", "-D", "-r", "-t", "-U", "-T", "-R", "--user", "--group",
+    "--host", "--prompt", "--chdir", "--role", "--type",
+];
+
+/// The command run by `program` (an elevation command) given its arguments,
+/// or `None` if it cannot be determined (e.g. `su -c '...'`).
+fn elevated_command<'a>(program: &str, args: &'a [String]) -> Option<&'a [String]> {
+    if Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        == Some("su")
+    {
+        return None;
+    }
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        if arg == "--" {
+            return Some(&args[i + 1..]);
+        }
+        if !arg.starts_with('-') {
+            break;
+        }
+        i += if ELEVATION_FLAGS_WITH_VALUES.contains(&arg.as_str()) {
+            2
+        } else {
+            1
+        };
+    }
+    args.get(i..)
+}
+
+/// Programs that run (part of) their arguments as another command.
+const COMMAND_WRAPPERS: &[&str] = &[
+    "env", "nohup", "timeout", "xargs", "nice", "ionice", "stdbuf", "setsid", "exec", "command",
+    "builtin", "chrt", "taskset", "flock", "watch", "time", "find",
+];
+
+/// Shells whose command-string flag (`-c`, `-Command`, `/c`) runs a script.
+const SCRIPT_SHELLS: &[&str] = &[
+    "bash",
+    "sh",
+    "zsh",
+    "dash",
+    "ksh",
+    "fish",
+    "pwsh",
+    "powershell",
+    "cmd",
+];
+
+/// Beyond this many nested wrappers/shells a command is assumed elevated.
+const MAX_ELEVATION_SCAN_DEPTH: usize = 8;
+
+/// Returns true if any command in `command` may run with elevated privileges.
+///
+/// Unlike [`parse_command`], this is a conservative scan meant for approval
+/// decisions: it looks through wrappers such as `env`, `timeout` and `xargs`,
+/// shell `-c` scripts and `eval`, and falls back to splitting scripts it
+/// cannot parse on shell separators, so it may report false positives.
+pub fn is_elevated_command(command: &[String]) -> bool {
+    words_are_elevated(command, 0)
+}
+
+fn words_are_elevated(words: &[String], depth: usize) -> bool {
+    if depth > MAX_ELEVATION_SCAN_DEPTH {
+        return true;
+    }
+    // Skip reserved words, variable assignments and redirections preceding
+    // the program itself.
+    let Some(start) = words.iter().position(|word| {
+        !matches!(
+            word.as_str(),
+            "{" | "!" | "if" | "then" | "else" | "elif" | "do" | "while" | "until" | "time"
+        ) && !is_assignment(word)
+            && !word.contains(['<', '>'])
+    }) else {
+        return false;
+    };
+    let (program, args) = (&words[start], &words[start + 1..]);
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    if is_elevation_command(name) {
+        return true;
+    }
+    if COMMAND_WRAPPERS.contains(&name) {
+        // Wrapper options and their values cannot be told apart from the
+        // wrapped program in general, so try every later word as the program.
+        return (0..args.len()).any(|i| words_are_elevated(&args[i..], depth + 1));
+    }
+    if name == "eval" {
+        return script_is_elevated(&args.join(" "), depth + 1);
+    }
+    if SCRIPT_SHELLS.contains(&name)
+        && let Some(flag) = args
+            .iter()
+            .position(|arg| is_command_string_flag(name, arg))
+    {
+        return script_is_elevated(&args[flag + 1..].join(" "), depth + 1);
+    }
+    false
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c == '_' || c.is_ascii_alphanumeric())
+    })
+}
+
+fn is_command_string_flag(shell: &str, arg: &str) -> bool {
+    match shell {
+        "cmd" => arg.eq_ignore_ascii_case("/c") || arg.eq_ignore_ascii_case("/k"),
+        "pwsh" | "powershell" => {
+            arg.eq_ignore_ascii_case("-c") || arg.eq_ignore_ascii_case("-command")
+        }
+        _ => arg.starts_with('-') && !arg.starts_with("--") && arg.contains('c'),
+    }
+}
+
+/// Splits `script` into commands on shell separators (ignoring quoting, so
+/// a quoted separator only adds commands to check) and scans each of them.
+fn script_is_elevated(script: &str, depth: usize) -> bool {
+    script
+        .split([';', '&', '|', '(', ')', '`', '\n'])
+        .any(|segment| {
+            let words = shlex_split(segment).unwrap_or_else(|| {
+                segment
+                    .split_whitespace()
+                    .map(|word| word.trim_matches(['\'', '"']).to_string())
+                    .collect()
+            });
+            words_are_elevated(&words, depth)
+        })
+}
+
 /// Return true if this looks like a small formatting helper in a pipeline.
 /// Examples: `head -n 40`, `tail -n +10`, `wc -l`, `awk ...`, `cut ...`, `tr ...`.
 /// We try to keep variants that clearly include a file path (e.g. `tail -n 30 file`).
@@ -1728,8 +2543,19 @@ fn drop_small_formatting_commands(mut commands: Vec<Vec<String>>) -> Vec<Vec<Str
 
 fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
     match main_cmd.split_first() {
+        Some((head, tail)) if is_elevation_command(head) => {
+            let elevated = match elevated_command(head, tail) {
+                Some(rest) if !rest.is_empty() => summarize_main_tokens(rest),
+                _ => ParsedCommand::Unknown {
+                    cmd: String::new(),
+                    elevated: false,
+                },
+            };
+            elevated.with_cmd(shlex_join(main_cmd)).elevated()
+        }
         Some((head, tail)) if head == "true" && tail.is_empty() => ParsedCommand::Noop {
             cmd: shlex_join(main_cmd),
+            elevated: false,
         },
         // (sed-specific logic handled below in dedicated arm returning Read)
         Some((head, tail))
@@ -1739,6 +2565,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 tool: Some("cargo fmt".to_string()),
                 targets: collect_non_flag_targets(&tail[1..]),
+                elevated: false,
             }
         }
         Some((head, tail))
@@ -1748,6 +2575,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 tool: Some("cargo clippy".to_string()),
                 targets: collect_non_flag_targets(&tail[1..]),
+                elevated: false,
             }
         }
         Some((head, tail))
@@ -1755,27 +2583,32 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
         {
             ParsedCommand::Test {
                 cmd: shlex_join(main_cmd),
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "rustfmt" => ParsedCommand::Format {
             cmd: shlex_join(main_cmd),
             tool: Some("rustfmt".to_string()),
             targets: collect_non_flag_targets(tail),
+            elevated: false,
         },
         Some((head, tail)) if head == "go" && tail.first().map(|s| s.as_str()) == Some("fmt") => {
             ParsedCommand::Format {
                 cmd: shlex_join(main_cmd),
                 tool: Some("go fmt".to_string()),
                 targets: collect_non_flag_targets(&tail[1..]),
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "go" && tail.first().map(|s| s.as_str()) == Some("test") => {
             ParsedCommand::Test {
                 cmd: shlex_join(main_cmd),
+                elevated: false,
             }
         }
         Some((head, _)) if head == "pytest" => ParsedCommand::Test {
             cmd: shlex_join(main_cmd),
+            elevated: false,
         },
         Some((head, tail)) if head == "eslint" => {
             // Treat configuration flags with values (e.g. `-c .eslintrc`) as non-targets.
@@ -1784,17 +2617,20 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 tool: Some("eslint".to_string()),
                 targets,
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "prettier" => ParsedCommand::Format {
             cmd: shlex_join(main_cmd),
             tool: Some("prettier".to_string()),
             targets: collect_non_flag_targets(tail),
+            elevated: false,
         },
         Some((head, tail)) if head == "black" => ParsedCommand::Format {
             cmd: shlex_join(main_cmd),
             tool: Some("black".to_string()),
             targets: collect_non_flag_targets(tail),
+            elevated: false,
         },
         Some((head, tail))
             if head == "ruff" && tail.first().map(|s| s.as_str()) == Some("check") =>
@@ -1803,6 +2639,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 tool: Some("ruff".to_string()),
                 targets: collect_non_flag_targets(&tail[1..]),
+                elevated: false,
             }
         }
         Some((head, tail))
@@ -1812,10 +2649,12 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 tool: Some("ruff".to_string()),
                 targets: collect_non_flag_targets(&tail[1..]),
+                elevated: false,
             }
         }
         Some((head, _)) if (head == "jest" || head == "vitest") => ParsedCommand::Test {
             cmd: shlex_join(main_cmd),
+            elevated: false,
         },
         Some((head, tail))
             if head == "npx" && tail.first().map(|s| s.as_str()) == Some("eslint") =>
@@ -1825,6 +2664,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 tool: Some("eslint".to_string()),
                 targets,
+                elevated: false,
             }
         }
         Some((head, tail))
@@ -1834,6 +2674,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 tool: Some("prettier".to_string()),
                 targets: collect_non_flag_targets(&tail[1..]),
+                elevated: false,
             }
         }
         // NPM-like scripts including yarn
@@ -1843,6 +2684,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
             } else {
                 ParsedCommand::Unknown {
                     cmd: shlex_join(main_cmd),
+                    elevated: false,
                 }
             }
         }
@@ -1867,6 +2709,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
             ParsedCommand::ListFiles {
                 cmd: shlex_join(main_cmd),
                 path,
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "rg" => {
@@ -1888,6 +2731,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 query,
                 path,
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "fd" => {
@@ -1896,6 +2740,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 query,
                 path,
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "find" => {
@@ -1905,6 +2750,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 query,
                 path,
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "grep" => {
@@ -1921,6 +2767,7 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 cmd: shlex_join(main_cmd),
                 query,
                 path,
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "cat" => {
@@ -1935,10 +2782,12 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 ParsedCommand::Read {
                     cmd: shlex_join(main_cmd),
                     name,
+                    elevated: false,
                 }
             } else {
                 ParsedCommand::Unknown {
                     cmd: shlex_join(main_cmd),
+                    elevated: false,
                 }
             }
         }
@@ -1973,11 +2822,13 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                     return ParsedCommand::Read {
                         cmd: shlex_join(main_cmd),
                         name,
+                        elevated: false,
                     };
                 }
             }
             ParsedCommand::Unknown {
                 cmd: shlex_join(main_cmd),
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "tail" => {
@@ -2015,11 +2866,13 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                     return ParsedCommand::Read {
                         cmd: shlex_join(main_cmd),
                         name,
+                        elevated: false,
                     };
                 }
             }
             ParsedCommand::Unknown {
                 cmd: shlex_join(main_cmd),
+                elevated: false,
             }
         }
         Some((head, tail)) if head == "nl" => {
@@ -2030,10 +2883,12 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 ParsedCommand::Read {
                     cmd: shlex_join(main_cmd),
                     name,
+                    elevated: false,
                 }
             } else {
                 ParsedCommand::Unknown {
                     cmd: shlex_join(main_cmd),
+                    elevated: false,
                 }
             }
         }
@@ -2048,16 +2903,320 @@ fn summarize_main_tokens(main_cmd: &[String]) -> ParsedCommand {
                 ParsedCommand::Read {
                     cmd: shlex_join(main_cmd),
                     name,
+                    elevated: false,
                 }
             } else {
                 ParsedCommand::Unknown {
                     cmd: shlex_join(main_cmd),
+                    elevated: false,
                 }
             }
         }
         // Other commands
         _ => ParsedCommand::Unknown {
             cmd: shlex_join(main_cmd),
+            elevated: false,
         },
     }
 }
+
+/// Read scope of a `find`/`grep`/`rg`/`fd` invocation: the pattern it looks
+/// for and the directories or files it searches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchScope {
+    pub cmd: String,
+    pub pattern: Option<String>,
+    pub roots: Vec<SearchRoot>,
+}
+
+/// A search root resolved against the effective cwd.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchRoot {
+    pub path: PathBuf,
+    /// `true` when the root is not inside the workspace (the cwd), e.g.
+    /// `rg foo ../other` or `find / -name id_rsa`.
+    pub outside_workspace: bool,
+}
+
+impl SearchScope {
+    /// Whether any root escapes the workspace.
+    pub fn escapes_workspace(&self) -> bool {
+        self.roots.iter().any(|root| root.outside_workspace)
+    }
+}
+
+/// Extracts the read scope of every search command in `command`. Relative
+/// roots are resolved against `cwd`, which is also the workspace boundary
+/// used to flag roots that escape it. Searches without an explicit root
+/// default to `cwd`.
+pub fn parse_search_scopes(command: &[String], cwd: &Path) -> Vec<SearchScope> {
+    let normalized = normalize_tokens(command);
+    split_on_connectors(&normalized)
+        .iter()
+        .filter_map(|tokens| search_scope_for_tokens(tokens, cwd))
+        .collect()
+}
+
+fn search_scope_for_tokens(tokens: &[String], cwd: &Path) -> Option<SearchScope> {
+    let (head, tail) = tokens.split_first()?;
+    let (pattern, roots) = match head.as_str() {
+        "rg" => parse_rg_pattern_and_roots(tail),
+        "grep" => parse_grep_pattern_and_roots(tail),
+        "fd" => parse_fd_pattern_and_roots(tail),
+        "find" => parse_find_pattern_and_roots(tail),
+        _ => return None,
+    };
+    let roots = if roots.is_empty() {
+        vec![cwd.to_path_buf()]
+    } else {
+        roots
+            .iter()
+            .map(|root| normalize_lexically(&cwd.join(root)))
+            .collect()
+    };
+    let workspace = normalize_lexically(cwd);
+    Some(SearchScope {
+        cmd: shlex_join(tokens),
+        pattern,
+        roots: roots
+            .into_iter()
+            .map(|path| SearchRoot {
+                outside_workspace: !path.starts_with(&workspace),
+                path,
+            })
+            .collect(),
+    })
+}
+
+/// Splits positional operands of `rg`/`grep` into a pattern and roots,
+/// honouring `-e PATTERN` and skipping values of flags that take one.
+fn parse_pattern_first_operands(
+    args: &[String],
+    flags_with_vals: &[&str],
+) -> (Option<String>, Vec<String>) {
+    let mut pattern: Option<String> = None;
+    let mut operands: Vec<String> = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-e" || arg == "--regexp" {
+            pattern = iter.next().cloned();
+        } else if let Some(value) = arg.strip_prefix("--regexp=") {
+            pattern = Some(value.to_string());
+        } else if flags_with_vals.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            operands.push(arg.clone());
+        }
+    }
+    if pattern.is_none() && !operands.is_empty() {
+        pattern = Some(operands.remove(0));
+    }
+    (pattern, operands)
+}
+
+fn parse_rg_pattern_and_roots(tail: &[String]) -> (Option<String>, Vec<String>) {
+    let args = trim_at_connector(tail);
+    let flags_with_vals = [
+        "-g",
+        "--glob",
+        "-t",
+        "--type",
+        "-T",
+        "--type-not",
+        "-m",
+        "--max-count",
+        "-A",
+        "-B",
+        "-C",
+        "--max-depth",
+        "-f",
+        "--file",
+    ];
+    if args.iter().any(|a| a == "--files") {
+        let roots = skip_flag_values(&args, &flags_with_vals)
+            .into_iter()
+            .filter(|a| !a.starts_with('-'))
+            .cloned()
+            .collect();
+        return (None, roots);
+    }
+    parse_pattern_first_operands(&args, &flags_with_vals)
+}
+
+fn parse_grep_pattern_and_roots(tail: &[String]) -> (Option<String>, Vec<String>) {
+    let args = trim_at_connector(tail);
+    parse_pattern_first_operands(
+        &args,
+        &["-f", "--file", "-m", "--max-count", "-A", "-B", "-C"],
+    )
+}
+
+fn parse_fd_pattern_and_roots(tail: &[String]) -> (Option<String>, Vec<String>) {
+    let args = trim_at_connector(tail);
+    let mut roots: Vec<String> = Vec::new();
+    let mut operands: Vec<String> = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--search-path" {
+            roots.extend(iter.next().cloned());
+        } else if ["-t", "--type", "-e", "--extension", "-E", "--exclude"].contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            operands.push(arg.clone());
+        }
+    }
+    // A lone path-like operand is a root, matching `parse_fd_query_and_path`.
+    let pattern = match operands.as_slice() {
+        [one] if is_pathish(one) => None,
+        [] => None,
+        _ => Some(operands.remove(0)),
+    };
+    roots.extend(operands);
+    (pattern, roots)
+}
+
+fn parse_find_pattern_and_roots(tail: &[String]) -> (Option<String>, Vec<String>) {
+    let args = trim_at_connector(tail);
+    // Roots are the operands before the first expression token.
+    let roots = args
+        .iter()
+        .take_while(|a| !a.starts_with('-') && *a != "!" && *a != "(")
+        .cloned()
+        .collect();
+    let (pattern, _) = parse_find_query_and_path(&args);
+    (pattern, roots)
+}
+
+/// A file written from a heredoc by `cat` or `tee`, e.g.
+/// `cat <<'EOF' > notes.md`. The heredoc body is data, not commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeredocWrite {
+    /// The command that consumes the heredoc, without the `<<` operator.
+    pub cmd: String,
+    /// Target file resolved against the effective cwd.
+    pub path: PathBuf,
+    /// `true` for `>>` and `tee -a`.
+    pub append: bool,
+    /// `true` when the target is not inside the workspace (the cwd).
+    pub outside_workspace: bool,
+}
+
+/// Extracts the files written from heredocs in a `bash -c`/`bash -lc` (or
+/// `sh`/`zsh`) script. Heredoc bodies are skipped so their contents are never
+/// mistaken for commands. Relative targets are resolved against `cwd`, which
+/// is also the workspace boundary used to flag targets that escape it.
+pub fn parse_heredoc_writes(command: &[String], cwd: &Path) -> Vec<HeredocWrite> {
+    let [shell, flag, script] = command else {
+        return Vec::new();
+    };
+    if !matches!(shell.as_str(), "bash" | "sh" | "zsh") || (flag != "-c" && flag != "-lc") {
+        return Vec::new();
+    }
+
+    let workspace = normalize_lexically(cwd);
+    let mut writes = Vec::new();
+    let mut lines = script.lines();
+    while let Some(line) = lines.next() {
+        let Some((tokens, delimiter, strip_tabs)) = parse_heredoc_start(line) else {
+            continue;
+        };
+        for body_line in lines.by_ref() {
+            let body_line = if strip_tabs {
+                body_line.trim_start_matches('\t')
+            } else {
+                body_line
+            };
+            if body_line == delimiter {
+                break;
+            }
+        }
+        if let Some((target, append)) = heredoc_write_target(&tokens) {
+            let path = normalize_lexically(&cwd.join(target));
+            writes.push(HeredocWrite {
+                // Plain join: shell operators such as `>` must not be quoted.
+                cmd: tokens.join(" "),
+                outside_workspace: !path.starts_with(&workspace),
+                path,
+                append,
+            });
+        }
+    }
+    writes
+}
+
+/// If `line` starts a heredoc, returns the pipeline that consumes it (with
+/// the `<<` operator removed), the delimiter, and whether leading tabs are
+/// stripped from body lines (`<<-`).
+fn parse_heredoc_start(line: &str) -> Option<(Vec<String>, String, bool)> {
+    let tokens = shlex_split(line)?;
+    let idx = tokens
+        .iter()
+        .position(|t| t.starts_with("<<") && !t.starts_with("<<<"))?;
+    let op = &tokens[idx][2..];
+    let strip_tabs = op.starts_with('-');
+    let op = op.strip_prefix('-').unwrap_or(op);
+    let (delimiter, op_len) = if op.is_empty() {
+        (tokens.get(idx + 1)?.clone(), 2)
+    } else {
+        (op.to_string(), 1)
+    };
+
+    // Only keep the `&&`/`||`/`;`-separated command the heredoc belongs to.
+    let is_separator = |t: &String| t == "&&" || t == "||" || t == ";";
+    let start = tokens[..idx]
+        .iter()
+        .rposition(is_separator)
+        .map_or(0, |i| i + 1);
+    let end = tokens[idx..]
+        .iter()
+        .position(is_separator)
+        .map_or(tokens.len(), |i| idx + i);
+    let mut command = tokens[start..idx].to_vec();
+    command.extend_from_slice(&tokens[idx + op_len..end]);
+    Some((command, delimiter, strip_tabs))
+}
+
+/// Returns the file a heredoc pipeline writes to: a `>`/`>>` redirect of
+/// `cat`, or the first file operand of `tee`.
+fn heredoc_write_target(tokens: &[String]) -> Option<(String, bool)> {
+    tokens.split(|t| t == "|").find_map(|part| {
+        let (head, tail) = part.split_first()?;
+        match head.as_str() {
+            "cat" => {
+                let idx = tail
+                    .iter()
+                    .position(|t| t.starts_with('>') && !t.starts_with(">&"))?;
+                let redirect = &tail[idx];
+                let append = redirect.starts_with(">>");
+                let inline = redirect.trim_start_matches('>');
+                let target = if inline.is_empty() {
+                    tail.get(idx + 1)?.clone()
+                } else {
+                    inline.to_string()
+                };
+                Some((target, append))
+            }
+            "tee" => {
+                let append = tail.iter().any(|t| t == "-a" || t == "--append");
+                let target = tail.iter().find(|t| !t.starts_with('-'))?;
+                Some((target.clone(), append))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Removes `.` and resolves `..` without touching the filesystem.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
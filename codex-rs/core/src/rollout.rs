@@ -1,9 +1,14 @@
 //! Persist Codex session rollouts (.jsonl) so sessions can be replayed or inspected later.
 
+use std::ffi::OsStr;
 use std::fs::File;
 use std::fs::{self};
 use std::io::Error as IoError;
 use std::path::Path;
+use std::path::PathBuf;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -22,15 +27,23 @@ use uuid::Uuid;
 use crate::config::Config;
 use crate::git_info::GitInfo;
 use crate::git_info::collect_git_info;
+use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 
 const SESSIONS_SUBDIR: &str = "sessions";
 
+/// Prefix of the `image_url` that stands in for an image stored out of line:
+/// `codex-image-ref:<mime>;<file name>`.
+const IMAGE_REF_PREFIX: &str = "codex-image-ref:";
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SessionMeta {
     pub id: Uuid,
     pub timestamp: String,
     pub instructions: Option<String>,
+    /// Model the session was started with. Missing from older rollouts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -42,7 +55,11 @@ struct SessionMetaWithGit {
 }
 
 #[derive(Serialize, Deserialize, Default, Clone)]
-pub struct SessionStateSnapshot {}
+pub struct SessionStateSnapshot {
+    /// Label set with `Op::SetConversationLabel`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SavedSession {
@@ -71,7 +88,13 @@ pub(crate) struct RolloutRecorder {
 enum RolloutCmd {
     AddItems(Vec<ResponseItem>),
     UpdateState(SessionStateSnapshot),
-    Shutdown { ack: oneshot::Sender<()> },
+    SaveAs {
+        path: PathBuf,
+        ack: oneshot::Sender<std::io::Result<()>>,
+    },
+    Shutdown {
+        ack: oneshot::Sender<()>,
+    },
 }
 
 impl RolloutRecorder {
@@ -85,16 +108,12 @@ impl RolloutRecorder {
     ) -> std::io::Result<Self> {
         let LogFileInfo {
             file,
+            path,
             session_id,
             timestamp,
         } = create_log_file(config, uuid)?;
-
-        let timestamp_format: &[FormatItem] = format_description!(
-            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
-        );
-        let timestamp = timestamp
-            .format(timestamp_format)
-            .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
+        let images = ImageStore::for_rollout(&path, config.rollout_image_inline_max_bytes);
+        let timestamp = config.timestamps.format(timestamp);
 
         // Clone the cwd for the spawned task to collect git info asynchronously
         let cwd = config.cwd.clone();
@@ -109,13 +128,16 @@ impl RolloutRecorder {
         // driver instead of blocking the runtime.
         tokio::task::spawn(rollout_writer(
             tokio::fs::File::from_std(file),
+            path,
             rx,
             Some(SessionMeta {
                 timestamp,
                 id: session_id,
                 instructions,
+                model: Some(config.model.clone()),
             }),
             cwd,
+            images,
         ));
 
         Ok(Self { tx })
@@ -160,59 +182,11 @@ impl RolloutRecorder {
     pub async fn resume(
         path: &Path,
         cwd: std::path::PathBuf,
+        image_inline_max_bytes: usize,
     ) -> std::io::Result<(Self, SavedSession)> {
         info!("Resuming rollout from {path:?}");
-        let text = tokio::fs::read_to_string(path).await?;
-        let mut lines = text.lines();
-        let meta_line = lines
-            .next()
-            .ok_or_else(|| IoError::other("empty session file"))?;
-        let session: SessionMeta = serde_json::from_str(meta_line)
-            .map_err(|e| IoError::other(format!("failed to parse session meta: {e}")))?;
-        let mut items = Vec::new();
-        let mut state = SessionStateSnapshot::default();
-
-        for line in lines {
-            if line.trim().is_empty() {
-                continue;
-            }
-            let v: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            if v.get("record_type")
-                .and_then(|rt| rt.as_str())
-                .map(|s| s == "state")
-                .unwrap_or(false)
-            {
-                if let Ok(s) = serde_json::from_value::<SessionStateSnapshot>(v.clone()) {
-                    state = s
-                }
-                continue;
-            }
-            match serde_json::from_value::<ResponseItem>(v.clone()) {
-                Ok(item) => match item {
-                    ResponseItem::Message { .. }
-                    | ResponseItem::LocalShellCall { .. }
-                    | ResponseItem::FunctionCall { .. }
-                    | ResponseItem::FunctionCallOutput { .. }
-                    | ResponseItem::CustomToolCall { .. }
-                    | ResponseItem::CustomToolCallOutput { .. }
-                    | ResponseItem::Reasoning { .. } => items.push(item),
-                    ResponseItem::WebSearchCall { .. } | ResponseItem::Other => {}
-                },
-                Err(e) => {
-                    warn!("failed to parse item: {v:?}, error: {e}");
-                }
-            }
-        }
-
-        let saved = SavedSession {
-            session: session.clone(),
-            items: items.clone(),
-            state: state.clone(),
-            session_id: session.id,
-        };
+        let images = ImageStore::for_rollout(path, image_inline_max_bytes);
+        let saved = read_saved_session(path, &images).await?;
 
         let file = std::fs::OpenOptions::new()
             .append(true)
@@ -222,14 +196,37 @@ impl RolloutRecorder {
         let (tx, rx) = mpsc::channel::<RolloutCmd>(256);
         tokio::task::spawn(rollout_writer(
             tokio::fs::File::from_std(file),
+            path.to_path_buf(),
             rx,
             None,
             cwd,
+            images,
         ));
         info!("Resumed rollout successfully from {path:?}");
         Ok((Self { tx }, saved))
     }
 
+    /// Read the session recorded in the rollout at `path` without reopening
+    /// it for writing.
+    pub async fn load(path: &Path, image_inline_max_bytes: usize) -> std::io::Result<SavedSession> {
+        let images = ImageStore::for_rollout(path, image_inline_max_bytes);
+        read_saved_session(path, &images).await
+    }
+
+    /// Move the rollout file (and its stored images) to `path` and keep
+    /// recording there. Fails without touching the current file if `path`
+    /// already exists or cannot be created.
+    pub(crate) async fn save_as(&self, path: PathBuf) -> std::io::Result<()> {
+        let (ack, rx_done) = oneshot::channel();
+        self.tx
+            .send(RolloutCmd::SaveAs { path, ack })
+            .await
+            .map_err(|e| IoError::other(format!("failed to queue rollout move: {e}")))?;
+        rx_done
+            .await
+            .map_err(|e| IoError::other(format!("failed waiting for rollout move: {e}")))?
+    }
+
     pub async fn shutdown(&self) -> std::io::Result<()> {
         let (tx_done, rx_done) = oneshot::channel();
         match self.tx.send(RolloutCmd::Shutdown { ack: tx_done }).await {
@@ -250,6 +247,9 @@ struct LogFileInfo {
     /// Opened file handle to the rollout file.
     file: File,
 
+    /// Path of the rollout file.
+    path: PathBuf,
+
     /// Session ID (also embedded in filename).
     session_id: Uuid,
 
@@ -286,16 +286,75 @@ fn create_log_file(config: &Config, session_id: Uuid) -> std::io::Result<LogFile
 
     Ok(LogFileInfo {
         file,
+        path,
         session_id,
         timestamp,
     })
 }
 
+/// Parse the session meta line, items and latest state recorded in the
+/// rollout at `path`.
+async fn read_saved_session(path: &Path, images: &ImageStore) -> std::io::Result<SavedSession> {
+    let text = tokio::fs::read_to_string(path).await?;
+    let mut lines = text.lines();
+    let meta_line = lines
+        .next()
+        .ok_or_else(|| IoError::other("empty session file"))?;
+    let session: SessionMeta = serde_json::from_str(meta_line)
+        .map_err(|e| IoError::other(format!("failed to parse session meta: {e}")))?;
+    let mut items = Vec::new();
+    let mut state = SessionStateSnapshot::default();
+
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let v: Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if v.get("record_type")
+            .and_then(|rt| rt.as_str())
+            .map(|s| s == "state")
+            .unwrap_or(false)
+        {
+            if let Ok(s) = serde_json::from_value::<SessionStateSnapshot>(v.clone()) {
+                state = s
+            }
+            continue;
+        }
+        match serde_json::from_value::<ResponseItem>(v.clone()) {
+            Ok(item) => match item {
+                ResponseItem::Message { .. }
+                | ResponseItem::LocalShellCall { .. }
+                | ResponseItem::FunctionCall { .. }
+                | ResponseItem::FunctionCallOutput { .. }
+                | ResponseItem::CustomToolCall { .. }
+                | ResponseItem::CustomToolCallOutput { .. }
+                | ResponseItem::Reasoning { .. } => items.push(images.rehydrate(item).await),
+                ResponseItem::WebSearchCall { .. } | ResponseItem::Other => {}
+            },
+            Err(e) => {
+                warn!("failed to parse item: {v:?}, error: {e}");
+            }
+        }
+    }
+
+    Ok(SavedSession {
+        session_id: session.id,
+        session,
+        items,
+        state,
+    })
+}
+
 async fn rollout_writer(
     file: tokio::fs::File,
+    mut path: PathBuf,
     mut rx: mpsc::Receiver<RolloutCmd>,
     mut meta: Option<SessionMeta>,
     cwd: std::path::PathBuf,
+    mut images: ImageStore,
 ) -> std::io::Result<()> {
     let mut writer = JsonlWriter { file };
 
@@ -324,7 +383,7 @@ async fn rollout_writer(
                         | ResponseItem::CustomToolCall { .. }
                         | ResponseItem::CustomToolCallOutput { .. }
                         | ResponseItem::Reasoning { .. } => {
-                            writer.write_line(&item).await?;
+                            writer.write_line(&images.externalize(item).await).await?;
                         }
                         ResponseItem::WebSearchCall { .. } | ResponseItem::Other => {}
                     }
@@ -344,6 +403,14 @@ async fn rollout_writer(
                     })
                     .await?;
             }
+            RolloutCmd::SaveAs { path: dest, ack } => {
+                let result = writer.move_to(&path, &dest).await;
+                if result.is_ok() {
+                    images.move_to(&dest).await;
+                    path = dest;
+                }
+                let _ = ack.send(result);
+            }
             RolloutCmd::Shutdown { ack } => {
                 let _ = ack.send(());
             }
@@ -353,6 +420,140 @@ async fn rollout_writer(
     Ok(())
 }
 
+/// Out-of-line storage for large images of one rollout: a directory next to
+/// the rollout file named `<rollout file stem>-images`. Only the persisted
+/// items refer to it; the in-memory history keeps the full data URLs.
+struct ImageStore {
+    dir: PathBuf,
+    inline_max_bytes: usize,
+}
+
+impl ImageStore {
+    fn for_rollout(rollout_path: &Path, inline_max_bytes: usize) -> Self {
+        let stem = rollout_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        Self {
+            dir: rollout_path.with_file_name(format!("{stem}-images")),
+            inline_max_bytes,
+        }
+    }
+
+    /// Switches to the store of the rollout at `rollout_path`, moving the
+    /// images stored so far along with it.
+    async fn move_to(&mut self, rollout_path: &Path) {
+        let moved = Self::for_rollout(rollout_path, self.inline_max_bytes);
+        if tokio::fs::try_exists(&self.dir).await.unwrap_or(false)
+            && let Err(e) = tokio::fs::rename(&self.dir, &moved.dir).await
+        {
+            warn!("failed to move stored images to {:?}: {e}", moved.dir);
+            return;
+        }
+        *self = moved;
+    }
+
+    /// Replaces base64 `data:` images larger than the threshold with
+    /// references to files in the store. Images that cannot be written stay
+    /// inline.
+    async fn externalize(&self, item: ResponseItem) -> ResponseItem {
+        let ResponseItem::Message { id, role, content } = item else {
+            return item;
+        };
+        let mut stored = Vec::with_capacity(content.len());
+        for content_item in content {
+            stored.push(match content_item {
+                ContentItem::InputImage { image_url }
+                    if image_url.len() > self.inline_max_bytes =>
+                {
+                    match self.store(&image_url).await {
+                        Ok(Some(reference)) => ContentItem::InputImage {
+                            image_url: reference,
+                        },
+                        Ok(None) => ContentItem::InputImage { image_url },
+                        Err(e) => {
+                            warn!("failed to store image out of line: {e}");
+                            ContentItem::InputImage { image_url }
+                        }
+                    }
+                }
+                other => other,
+            });
+        }
+        ResponseItem::Message {
+            id,
+            role,
+            content: stored,
+        }
+    }
+
+    /// Writes the decoded image to the store and returns its reference, or
+    /// `None` when `image_url` is not a base64 data URL.
+    async fn store(&self, image_url: &str) -> std::io::Result<Option<String>> {
+        let Some((mime, data)) = image_url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(";base64,"))
+        else {
+            return Ok(None);
+        };
+        let Ok(bytes) = BASE64_STANDARD.decode(data) else {
+            return Ok(None);
+        };
+        let extension = mime_guess::get_mime_extensions_str(mime)
+            .and_then(|extensions| extensions.first())
+            .copied()
+            .unwrap_or("bin");
+        let file_name = format!("{}.{extension}", Uuid::new_v4());
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.dir.join(&file_name), bytes).await?;
+        Ok(Some(format!("{IMAGE_REF_PREFIX}{mime};{file_name}")))
+    }
+
+    /// Inverse of [`ImageStore::externalize`]: turns references back into
+    /// data URLs. A reference whose file is gone becomes a text note.
+    async fn rehydrate(&self, item: ResponseItem) -> ResponseItem {
+        let ResponseItem::Message { id, role, content } = item else {
+            return item;
+        };
+        let mut loaded = Vec::with_capacity(content.len());
+        for content_item in content {
+            let reference = match &content_item {
+                ContentItem::InputImage { image_url } => image_url
+                    .strip_prefix(IMAGE_REF_PREFIX)
+                    .and_then(|rest| rest.split_once(';')),
+                _ => None,
+            };
+            let Some((mime, file_name)) = reference else {
+                loaded.push(content_item);
+                continue;
+            };
+            // Only plain file names are valid; never follow a path out of
+            // the store.
+            let bytes = if Path::new(file_name).file_name() == Some(OsStr::new(file_name)) {
+                tokio::fs::read(self.dir.join(file_name)).await
+            } else {
+                Err(IoError::other("invalid image reference"))
+            };
+            loaded.push(match bytes {
+                Ok(bytes) => ContentItem::InputImage {
+                    image_url: format!("data:{mime};base64,{}", BASE64_STANDARD.encode(bytes)),
+                },
+                Err(e) => {
+                    warn!("failed to load stored image {file_name}: {e}");
+                    ContentItem::InputText {
+                        text: format!("[image {file_name} is no longer available]"),
+                    }
+                }
+            });
+        }
+        ResponseItem::Message {
+            id,
+            role,
+            content: loaded,
+        }
+    }
+}
+
 struct JsonlWriter {
     file: tokio::fs::File,
 }
@@ -365,4 +566,85 @@ impl JsonlWriter {
         self.file.flush().await?;
         Ok(())
     }
+
+    /// Copy the file at `from` (the one being written) to the new file `to`,
+    /// continue writing there and remove `from`.
+    async fn move_to(&mut self, from: &Path, to: &Path) -> std::io::Result<()> {
+        self.file.flush().await?;
+        // Creating the destination first checks that it is writable and
+        // never overwrites an existing file.
+        tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(to)
+            .await?;
+        if let Err(e) = tokio::fs::copy(from, to).await {
+            let _ = tokio::fs::remove_file(to).await;
+            return Err(e);
+        }
+        self.file = tokio::fs::OpenOptions::new().append(true).open(to).await?;
+        if let Err(e) = tokio::fs::remove_file(from).await {
+            warn!("failed to remove old rollout file {from:?}: {e}");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn user_image(image_url: String) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![
+                ContentItem::InputText {
+                    text: "look".to_string(),
+                },
+                ContentItem::InputImage { image_url },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn large_images_are_stored_out_of_line_and_reloaded() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("rollout-test.jsonl");
+        let meta = serde_json::to_string(&SessionMeta::default()).expect("serialize meta");
+        std::fs::write(&path, format!("{meta}\n")).expect("write meta");
+
+        let image_bytes = vec![7u8; 4096];
+        let large = user_image(format!(
+            "data:image/png;base64,{}",
+            BASE64_STANDARD.encode(&image_bytes)
+        ));
+        let small = user_image("data:image/png;base64,AAAA".to_string());
+
+        let (recorder, _) = RolloutRecorder::resume(&path, dir.path().to_path_buf(), 1024)
+            .await
+            .expect("open rollout");
+        recorder
+            .record_items(&[large.clone(), small.clone()])
+            .await
+            .expect("record items");
+        recorder.shutdown().await.expect("shutdown");
+
+        let rollout = std::fs::read_to_string(&path).expect("read rollout");
+        assert!(rollout.contains(IMAGE_REF_PREFIX), "{rollout}");
+        assert!(rollout.contains("data:image/png;base64,AAAA"), "{rollout}");
+        let stored: Vec<_> = std::fs::read_dir(dir.path().join("rollout-test-images"))
+            .expect("images dir")
+            .map(|entry| entry.expect("dir entry").path())
+            .collect();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(std::fs::read(&stored[0]).expect("read image"), image_bytes);
+
+        let (_, saved) = RolloutRecorder::resume(&path, dir.path().to_path_buf(), 1024)
+            .await
+            .expect("resume rollout");
+        assert_eq!(saved.items, vec![large, small]);
+    }
 }
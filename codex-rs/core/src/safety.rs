@@ -1,15 +1,28 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 
+use crate::config_types::ShellEnvironmentPolicy;
+use crate::exec::ExecParams;
 use crate::exec::SandboxType;
+use crate::exec::process_exec_tool_call;
+use crate::exec_env::create_env;
+use crate::git_info;
 use crate::is_safe_command::is_known_safe_command;
+use crate::parse_command::SearchScope;
+use crate::parse_command::is_elevated_command;
+use crate::parse_command::parse_heredoc_writes;
+use crate::parse_command::parse_search_scopes;
 use crate::protocol::AskForApproval;
+use crate::protocol::SafetyAssessment;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::WritableRoot;
 
 #[derive(Debug, PartialEq)]
 pub enum SafetyCheck {
@@ -18,11 +31,57 @@ pub enum SafetyCheck {
     Reject { reason: String },
 }
 
+impl From<SafetyCheck> for SafetyAssessment {
+    fn from(check: SafetyCheck) -> Self {
+        match check {
+            SafetyCheck::AutoApprove { sandbox_type } => SafetyAssessment::AutoApprove {
+                sandboxed: sandbox_type != SandboxType::None,
+            },
+            SafetyCheck::AskUser => SafetyAssessment::AskUser,
+            SafetyCheck::Reject { reason } => SafetyAssessment::Reject { reason },
+        }
+    }
+}
+
+/// Files tracked by git under each writable root, for `tracked_files_only`.
+/// Listing them runs git, so a turn collects them once up front rather than
+/// on every safety check.
+#[derive(Debug, Default)]
+pub(crate) struct TrackedFiles {
+    by_root: HashMap<PathBuf, Arc<HashSet<PathBuf>>>,
+}
+
+impl TrackedFiles {
+    /// Lists the tracked files under the writable roots of `sandbox_policy`
+    /// for `cwd`. Empty unless the policy sets `tracked_files_only`; roots
+    /// outside a git work tree are left out and stay fully writable.
+    pub(crate) async fn collect(sandbox_policy: &SandboxPolicy, cwd: &Path) -> Self {
+        let mut by_root = HashMap::new();
+        if sandbox_policy.tracked_files_only() {
+            for root in sandbox_policy.get_writable_roots_with_cwd(cwd) {
+                if let Some(files) = git_info::tracked_files(&root.root).await {
+                    by_root.insert(root.root, Arc::new(files));
+                }
+            }
+        }
+        Self { by_root }
+    }
+
+    /// Limits each of `roots` to its tracked files, when they were listed.
+    fn restrict(&self, roots: &mut [WritableRoot]) {
+        for root in roots {
+            root.tracked_files = self.by_root.get(&root.root).cloned();
+        }
+    }
+}
+
 pub fn assess_patch_safety(
     action: &ApplyPatchAction,
     policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    codex_home: &Path,
+    tracked_files: &TrackedFiles,
 ) -> SafetyCheck {
     if action.is_empty() {
         return SafetyCheck::Reject {
@@ -41,12 +100,32 @@ pub fn assess_patch_safety(
         }
     }
 
+    // Codex's own configuration is never writable without approval, even when
+    // it lies inside a writable root, so the agent cannot loosen its own
+    // safety settings.
+    if !sandbox_policy.has_full_disk_write_access()
+        && patch_touches_codex_config(action, cwd, codex_home)
+    {
+        return match policy {
+            AskForApproval::Never => SafetyCheck::Reject {
+                reason: "writing to Codex configuration; rejected by user approval settings"
+                    .to_string(),
+            },
+            _ => SafetyCheck::AskUser,
+        };
+    }
+
     // Even though the patch *appears* to be constrained to writable paths, it
     // is possible that paths in the patch are hard links to files outside the
     // writable roots, so we should still run `apply_patch` in a sandbox in that
     // case.
-    if is_write_patch_constrained_to_writable_paths(action, sandbox_policy, cwd)
-        || policy == AskForApproval::OnFailure
+    if is_write_patch_constrained_to_writable_paths(
+        action,
+        sandbox_policy,
+        cwd,
+        codex_home,
+        tracked_files,
+    ) || policy == AskForApproval::OnFailure
     {
         // Only auto‑approve when we can actually enforce a sandbox. Otherwise
         // fall back to asking the user because the patch may touch arbitrary
@@ -69,15 +148,47 @@ pub fn assess_patch_safety(
 /// true:
 ///
 /// - the user has explicitly approved the command
-/// - the command is on the "known safe" list
+/// - the command is on the "known safe" list and, if it is a search, stays
+///   inside the workspace (`cwd`)
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
 pub fn assess_command_safety(
     command: &[String],
+    cwd: &Path,
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
     with_escalated_permissions: bool,
+    tracked_files: &TrackedFiles,
 ) -> SafetyCheck {
+    // `sudo`, `doas`, `su` and `pkexec` escape any sandbox, so commands that
+    // may run them always need an explicit decision, even if an identical
+    // command was approved for the session.
+    if is_elevated_command(command) {
+        return match approval_policy {
+            AskForApproval::Never => SafetyCheck::Reject {
+                reason: "auto-rejected because the command runs with elevated privileges"
+                    .to_string(),
+            },
+            _ => SafetyCheck::AskUser,
+        };
+    }
+
+    // Writing a heredoc to a file the sandbox would not let the command
+    // write needs a decision up front rather than a sandbox failure.
+    if !approved.contains(command)
+        && !heredoc_writes_outside_writable_roots(command, cwd, sandbox_policy, tracked_files)
+            .is_empty()
+    {
+        return match approval_policy {
+            AskForApproval::Never => SafetyCheck::Reject {
+                reason:
+                    "auto-rejected because the command writes a heredoc outside the writable roots"
+                        .to_string(),
+            },
+            _ => SafetyCheck::AskUser,
+        };
+    }
+
     // A command is "trusted" because either:
     // - it belongs to a set of commands we consider "safe" by default, or
     // - the user has explicitly approved the command for this session
@@ -91,7 +202,13 @@ pub fn assess_command_safety(
     // would probably be fine to run the command in a sandbox, but when
     // `approved.contains(command)` is `true`, the user may have approved it for
     // the session _because_ they know it needs to run outside a sandbox.
-    if is_known_safe_command(command) || approved.contains(command) {
+    //
+    // Searches are known safe because they only read, but one that reaches
+    // outside the workspace is not trusted without the sandbox or approval.
+    let search_escapes_workspace = parse_search_scopes(command, cwd)
+        .iter()
+        .any(SearchScope::escapes_workspace);
+    if (is_known_safe_command(command) && !search_escapes_workspace) || approved.contains(command) {
         return SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
         };
@@ -100,6 +217,34 @@ pub fn assess_command_safety(
     assess_safety_for_untrusted_command(approval_policy, sandbox_policy, with_escalated_permissions)
 }
 
+/// Files that `command` writes from a heredoc (`cat <<EOF > path`) and that
+/// `sandbox_policy` does not allow writing. Empty under `DangerFullAccess`.
+pub(crate) fn heredoc_writes_outside_writable_roots(
+    command: &[String],
+    cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+    tracked_files: &TrackedFiles,
+) -> Vec<PathBuf> {
+    if sandbox_policy.has_full_disk_write_access() {
+        return Vec::new();
+    }
+    let writes = parse_heredoc_writes(command, cwd);
+    if writes.is_empty() {
+        return Vec::new();
+    }
+    let mut writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+    tracked_files.restrict(&mut writable_roots);
+    writes
+        .into_iter()
+        .map(|write| write.path)
+        .filter(|path| {
+            !writable_roots
+                .iter()
+                .any(|root| root.is_path_writable(path))
+        })
+        .collect()
+}
+
 pub(crate) fn assess_safety_for_untrusted_command(
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
@@ -169,37 +314,216 @@ pub fn get_platform_sandbox() -> Option<SandboxType> {
     }
 }
 
+/// A probe run by [`self_test`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTestProbe {
+    /// Create a file outside the policy's writable roots.
+    WriteOutsideWritableRoots,
+    /// Open a TCP connection (to a listener on localhost).
+    NetworkConnect,
+    /// Read a file containing a secret. The sandbox policies do not restrict
+    /// reads, so this is expected to succeed.
+    ReadSecretPath,
+}
+
+impl std::fmt::Display for SelfTestProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SelfTestProbe::WriteOutsideWritableRoots => "write outside writable roots",
+            SelfTestProbe::NetworkConnect => "network connect",
+            SelfTestProbe::ReadSecretPath => "read secret path",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Outcome of a single [`SelfTestProbe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestProbeResult {
+    pub probe: SelfTestProbe,
+    /// Whether the policy is supposed to block the operation.
+    pub expected_blocked: bool,
+    /// Whether the operation was actually blocked.
+    pub blocked: bool,
+}
+
+impl SelfTestProbeResult {
+    /// The sandbox behaved as the policy prescribes.
+    pub fn passed(&self) -> bool {
+        self.expected_blocked == self.blocked
+    }
+}
+
+/// Result of [`self_test`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfTestReport {
+    /// Sandbox the probes ran under; `None` when the platform has none.
+    pub sandbox_type: Option<SandboxType>,
+    pub probes: Vec<SelfTestProbeResult>,
+}
+
+impl SelfTestReport {
+    pub fn all_passed(&self) -> bool {
+        self.probes.iter().all(SelfTestProbeResult::passed)
+    }
+}
+
+const SELF_TEST_TIMEOUT_MS: u64 = 5_000;
+
+/// Runs a battery of probe operations under the platform sandbox with
+/// `policy` and reports which of them were blocked.
+///
+/// `probe_dir` is where the probes create their files; it should lie outside
+/// the policy's writable roots (e.g. a directory under `CODEX_HOME`) so the
+/// write probe exercises the sandbox.
+pub async fn self_test(
+    policy: &SandboxPolicy,
+    cwd: &Path,
+    probe_dir: &Path,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+) -> std::io::Result<SelfTestReport> {
+    std::fs::create_dir_all(probe_dir)?;
+    let sandbox_type = get_platform_sandbox();
+    let run = |command: Vec<String>| async move {
+        let params = ExecParams {
+            command,
+            cwd: cwd.to_path_buf(),
+            timeout_ms: Some(SELF_TEST_TIMEOUT_MS),
+            env: create_env(&ShellEnvironmentPolicy::default()),
+            with_escalated_permissions: None,
+            justification: None,
+            stdin: None,
+        };
+        process_exec_tool_call(
+            params,
+            sandbox_type.unwrap_or(SandboxType::None),
+            policy,
+            codex_linux_sandbox_exe,
+            None,
+        )
+        .await
+        .ok()
+    };
+    let mut probes = Vec::new();
+
+    let target = probe_dir.join(format!("write-probe-{}", std::process::id()));
+    let _ = std::fs::remove_file(&target);
+    let expected_blocked = !policy.has_full_disk_write_access()
+        && !policy
+            .get_writable_roots_with_cwd(cwd)
+            .iter()
+            .any(|root| root.is_path_writable(&target));
+    run(vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo probe > \"$0\"".to_string(),
+        target.to_string_lossy().to_string(),
+    ])
+    .await;
+    probes.push(SelfTestProbeResult {
+        probe: SelfTestProbe::WriteOutsideWritableRoots,
+        expected_blocked,
+        blocked: !target.exists(),
+    });
+    let _ = std::fs::remove_file(&target);
+
+    // The kernel completes the handshake even before `accept()`, so a
+    // pending connection on the listener means the connect went through.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    listener.set_nonblocking(true)?;
+    let port = listener.local_addr()?.port();
+    run(vec![
+        "bash".to_string(),
+        "-c".to_string(),
+        "exec 3<>\"/dev/tcp/127.0.0.1/$0\"".to_string(),
+        port.to_string(),
+    ])
+    .await;
+    probes.push(SelfTestProbeResult {
+        probe: SelfTestProbe::NetworkConnect,
+        expected_blocked: !policy.has_full_network_access(),
+        blocked: listener.accept().is_err(),
+    });
+
+    let secret = probe_dir.join(format!("secret-probe-{}", std::process::id()));
+    let token = format!("codex-self-test-{}", std::process::id());
+    std::fs::write(&secret, &token)?;
+    let output = run(vec![
+        "cat".to_string(),
+        secret.to_string_lossy().to_string(),
+    ])
+    .await;
+    probes.push(SelfTestProbeResult {
+        probe: SelfTestProbe::ReadSecretPath,
+        expected_blocked: false,
+        blocked: !output.is_some_and(|output| output.stdout.text.contains(&token)),
+    });
+    let _ = std::fs::remove_file(&secret);
+
+    Ok(SelfTestReport {
+        sandbox_type,
+        probes,
+    })
+}
+
+/// Whether any path written by `action` is inside `codex_home` or a `.codex`
+/// project directory.
+fn patch_touches_codex_config(action: &ApplyPatchAction, cwd: &Path, codex_home: &Path) -> bool {
+    let is_protected = |p: &PathBuf| {
+        let abs = normalize(&cwd.join(p));
+        abs.components()
+            .any(|comp| comp == Component::Normal(".codex".as_ref()))
+            || abs.starts_with(codex_home)
+    };
+
+    action.changes().iter().any(|(path, change)| {
+        is_protected(path)
+            || matches!(
+                change,
+                ApplyPatchFileChange::Update {
+                    move_path: Some(dest),
+                    ..
+                } if is_protected(dest)
+            )
+    })
+}
+
+/// Normalize a path by removing `.` and resolving `..` without touching the
+/// filesystem (works even if the file does not exist).
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => { /* skip */ }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
 fn is_write_patch_constrained_to_writable_paths(
     action: &ApplyPatchAction,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    codex_home: &Path,
+    tracked_files: &TrackedFiles,
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
-    let writable_roots = match sandbox_policy {
+    let mut writable_roots = match sandbox_policy {
         SandboxPolicy::ReadOnly => {
             return false;
         }
         SandboxPolicy::DangerFullAccess => {
             return true;
         }
-        SandboxPolicy::WorkspaceWrite { .. } => sandbox_policy.get_writable_roots_with_cwd(cwd),
-    };
-
-    // Normalize a path by removing `.` and resolving `..` without touching the
-    // filesystem (works even if the file does not exist).
-    fn normalize(path: &Path) -> Option<PathBuf> {
-        let mut out = PathBuf::new();
-        for comp in path.components() {
-            match comp {
-                Component::ParentDir => {
-                    out.pop();
-                }
-                Component::CurDir => { /* skip */ }
-                other => out.push(other.as_os_str()),
-            }
+        SandboxPolicy::WorkspaceWrite { .. } => {
+            sandbox_policy.get_writable_roots_with_cwd_and_codex_home(cwd, codex_home)
         }
-        Some(out)
-    }
+    };
+    tracked_files.restrict(&mut writable_roots);
 
     // Determine whether `path` is inside **any** writable root. Both `path`
     // and roots are converted to absolute, normalized forms before the
@@ -210,10 +534,7 @@ fn is_write_patch_constrained_to_writable_paths(
         } else {
             cwd.join(p)
         };
-        let abs = match normalize(&abs) {
-            Some(v) => v,
-            None => return false,
-        };
+        let abs = normalize(&abs);
 
         writable_roots
             .iter()
@@ -243,6 +564,95 @@ fn is_write_patch_constrained_to_writable_paths(
     true
 }
 
+/// Write access the user granted during the session on top of the sandbox
+/// policy's writable roots, from approving a patch "for the session".
+#[derive(Debug, Default, Clone)]
+pub(crate) struct WriteGrants {
+    /// Directories under which any path is writable.
+    roots: Vec<PathBuf>,
+    /// Exact files that are writable.
+    paths: HashSet<PathBuf>,
+}
+
+impl WriteGrants {
+    pub(crate) fn grant_root(&mut self, root: PathBuf) {
+        self.roots.push(root);
+    }
+
+    pub(crate) fn grant_paths(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        self.paths.extend(paths);
+    }
+
+    fn allows(&self, abs_path: &Path) -> bool {
+        self.paths.contains(abs_path) || self.roots.iter().any(|root| abs_path.starts_with(root))
+    }
+
+    /// Whether every path `action` writes has been granted. Being inside a
+    /// writable root of the sandbox policy does not count, and patches that
+    /// touch Codex's own configuration are never covered.
+    pub(crate) fn covers_patch(
+        &self,
+        action: &ApplyPatchAction,
+        cwd: &Path,
+        codex_home: &Path,
+    ) -> bool {
+        if action.is_empty() || patch_touches_codex_config(action, cwd, codex_home) {
+            return false;
+        }
+        patch_paths(action, cwd)
+            .iter()
+            .all(|path| self.allows(path))
+    }
+
+    /// Whether the grants approve a patch that `check` would otherwise ask
+    /// the user about. Grants never override a rejection.
+    pub(crate) fn approves_patch(
+        &self,
+        check: &SafetyCheck,
+        action: &ApplyPatchAction,
+        cwd: &Path,
+        codex_home: &Path,
+    ) -> bool {
+        matches!(check, SafetyCheck::AskUser) && self.covers_patch(action, cwd, codex_home)
+    }
+}
+
+/// Absolute, normalized paths written by `action`, including move
+/// destinations, sorted and deduplicated.
+pub(crate) fn patch_paths(action: &ApplyPatchAction, cwd: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = action
+        .changes()
+        .iter()
+        .flat_map(|(path, change)| {
+            let dest = match change {
+                ApplyPatchFileChange::Update { move_path, .. } => move_path.clone(),
+                _ => None,
+            };
+            std::iter::once(path.clone()).chain(dest)
+        })
+        .map(|path| normalize(&cwd.join(path)))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// The deepest directory containing all of `paths`, offered as the broader
+/// grant alongside the individual files. `None` when that would be the
+/// filesystem root.
+pub(crate) fn common_grant_root(paths: &[PathBuf]) -> Option<PathBuf> {
+    let (first, rest) = paths.split_first()?;
+    let mut root = first.parent()?.to_path_buf();
+    for path in rest {
+        while !path.starts_with(&root) {
+            if !root.pop() {
+                return None;
+            }
+        }
+    }
+    root.parent().is_some().then_some(root)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -255,6 +665,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let cwd = tmp.path().to_path_buf();
         let parent = cwd.parent().unwrap().to_path_buf();
+        let codex_home = cwd.join("codex-home");
 
         // Helper to build a single‑entry patch that adds a file at `p`.
         let make_add_change = |p: PathBuf| ApplyPatchAction::new_add_for_test(&p, "".to_string());
@@ -269,18 +680,24 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
         };
 
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_inside,
             &policy_workspace_only,
             &cwd,
+            &codex_home,
+            &TrackedFiles::default(),
         ));
 
         assert!(!is_write_patch_constrained_to_writable_paths(
             &add_outside,
             &policy_workspace_only,
             &cwd,
+            &codex_home,
+            &TrackedFiles::default(),
         ));
 
         // With the parent dir explicitly added as a writable root, the
@@ -290,14 +707,159 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
         };
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
             &policy_with_parent,
             &cwd,
+            &codex_home,
+            &TrackedFiles::default(),
         ));
     }
 
+    #[tokio::test]
+    async fn test_tracked_files_only_limits_patches_to_tracked_files() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().to_path_buf();
+        let codex_home = tmp.path().join("codex-home");
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .env("GIT_CONFIG_NOSYSTEM", "1")
+                .arg("-C")
+                .arg(&cwd)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q"]);
+        std::fs::write(cwd.join("tracked.txt"), "").unwrap();
+        git(&["add", "tracked.txt"]);
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only: true,
+            allow_git_writes: false,
+        };
+        let tracked_files = TrackedFiles::collect(&policy, &cwd).await;
+        let make_add_change = |p: PathBuf| ApplyPatchAction::new_add_for_test(&p, "".to_string());
+
+        assert!(is_write_patch_constrained_to_writable_paths(
+            &make_add_change(cwd.join("tracked.txt")),
+            &policy,
+            &cwd,
+            &codex_home,
+            &tracked_files,
+        ));
+        assert!(!is_write_patch_constrained_to_writable_paths(
+            &make_add_change(cwd.join("untracked.txt")),
+            &policy,
+            &cwd,
+            &codex_home,
+            &tracked_files,
+        ));
+    }
+
+    #[test]
+    fn granted_paths_allow_exactly_those_files() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().join("workspace");
+        let codex_home = tmp.path().join("codex-home");
+        let outside = tmp.path().join("outside");
+        let add = |p: PathBuf| ApplyPatchAction::new_add_for_test(&p, "".to_string());
+        let granted = add(outside.join("granted.txt"));
+        let sibling = add(outside.join("sibling.txt"));
+
+        let mut grants = WriteGrants::default();
+        assert!(!grants.covers_patch(&granted, &cwd, &codex_home));
+
+        grants.grant_paths(patch_paths(&granted, &cwd));
+        assert!(grants.covers_patch(&granted, &cwd, &codex_home));
+        assert!(!grants.covers_patch(&sibling, &cwd, &codex_home));
+
+        grants.grant_root(outside.clone());
+        assert!(grants.covers_patch(&sibling, &cwd, &codex_home));
+    }
+
+    #[test]
+    fn grant_does_not_cover_unrelated_workspace_files() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().join("workspace");
+        let codex_home = tmp.path().join("codex-home");
+        let add = |p: PathBuf| ApplyPatchAction::new_add_for_test(&p, "".to_string());
+        let granted = add(cwd.join("src/granted.rs"));
+        let sibling = add(cwd.join("src/sibling.rs"));
+
+        let mut grants = WriteGrants::default();
+        grants.grant_paths(patch_paths(&granted, &cwd));
+
+        // `sibling.rs` is inside the workspace, which a workspace-write
+        // sandbox would allow, but it was never granted.
+        assert!(grants.covers_patch(&granted, &cwd, &codex_home));
+        assert!(!grants.covers_patch(&sibling, &cwd, &codex_home));
+    }
+
+    #[test]
+    fn grants_never_cover_codex_config() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().join("workspace");
+        let codex_home = tmp.path().join("codex-home");
+        let add = |p: PathBuf| ApplyPatchAction::new_add_for_test(&p, "".to_string());
+
+        let mut grants = WriteGrants::default();
+        grants.grant_root(tmp.path().to_path_buf());
+
+        assert!(grants.covers_patch(&add(cwd.join("a.txt")), &cwd, &codex_home));
+        assert!(!grants.covers_patch(&add(cwd.join(".codex/config.toml")), &cwd, &codex_home));
+        assert!(!grants.covers_patch(&add(codex_home.join("config.toml")), &cwd, &codex_home));
+    }
+
+    #[test]
+    fn grants_do_not_override_rejections() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().join("workspace");
+        let codex_home = tmp.path().join("codex-home");
+        let action =
+            ApplyPatchAction::new_add_for_test(&tmp.path().join("outside/a.txt"), "".to_string());
+        let mut grants = WriteGrants::default();
+        grants.grant_paths(patch_paths(&action, &cwd));
+        let assess = |policy| {
+            assess_patch_safety(
+                &action,
+                policy,
+                &SandboxPolicy::ReadOnly,
+                &cwd,
+                &codex_home,
+                &TrackedFiles::default(),
+            )
+        };
+
+        let ask = assess(AskForApproval::OnRequest);
+        assert_eq!(ask, SafetyCheck::AskUser);
+        assert!(grants.approves_patch(&ask, &action, &cwd, &codex_home));
+
+        let reject = assess(AskForApproval::Never);
+        assert!(matches!(reject, SafetyCheck::Reject { .. }));
+        assert!(!grants.approves_patch(&reject, &action, &cwd, &codex_home));
+    }
+
+    #[test]
+    fn common_grant_root_is_deepest_shared_directory() {
+        let paths = vec![
+            PathBuf::from("/repo/src/a.rs"),
+            PathBuf::from("/repo/src/nested/b.rs"),
+        ];
+        assert_eq!(common_grant_root(&paths), Some(PathBuf::from("/repo/src")));
+        let paths = vec![PathBuf::from("/etc/hosts"), PathBuf::from("/home/x")];
+        assert_eq!(common_grant_root(&paths), None);
+    }
+
     #[test]
     fn test_request_escalated_privileges() {
         // Should not be a trusted command
@@ -309,10 +871,12 @@ mod tests {
 
         let safety_check = assess_command_safety(
             &command,
+            Path::new("/work"),
             approval_policy,
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            &TrackedFiles::default(),
         );
 
         assert_eq!(safety_check, SafetyCheck::AskUser);
@@ -328,10 +892,12 @@ mod tests {
 
         let safety_check = assess_command_safety(
             &command,
+            Path::new("/work"),
             approval_policy,
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            &TrackedFiles::default(),
         );
 
         let expected = match get_platform_sandbox() {
@@ -340,4 +906,239 @@ mod tests {
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn test_sudo_command_always_requires_approval() {
+        let command = vec!["sudo".to_string(), "rm".to_string(), "x".to_string()];
+        // Even an identical command approved for the session is asked again.
+        let approved: HashSet<Vec<String>> = HashSet::from([command.clone()]);
+
+        let safety_check = assess_command_safety(
+            &command,
+            Path::new("/work"),
+            AskForApproval::OnRequest,
+            &SandboxPolicy::DangerFullAccess,
+            &approved,
+            false,
+            &TrackedFiles::default(),
+        );
+        assert_eq!(safety_check, SafetyCheck::AskUser);
+
+        let safety_check = assess_command_safety(
+            &command,
+            Path::new("/work"),
+            AskForApproval::Never,
+            &SandboxPolicy::DangerFullAccess,
+            &approved,
+            false,
+            &TrackedFiles::default(),
+        );
+        assert!(matches!(safety_check, SafetyCheck::Reject { .. }));
+    }
+
+    #[test]
+    fn test_sudo_as_argument_is_not_elevated() {
+        let command = vec!["echo".to_string(), "sudo".to_string()];
+        let safety_check = assess_command_safety(
+            &command,
+            Path::new("/work"),
+            AskForApproval::OnRequest,
+            &SandboxPolicy::DangerFullAccess,
+            &HashSet::new(),
+            false,
+            &TrackedFiles::default(),
+        );
+        assert_eq!(
+            safety_check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+    }
+
+    #[test]
+    fn test_search_outside_workspace_is_not_trusted() {
+        let cwd = Path::new("/work/repo");
+        let assess = |script: &str| {
+            assess_command_safety(
+                &["bash", "-lc", script].map(str::to_string),
+                cwd,
+                AskForApproval::UnlessTrusted,
+                &SandboxPolicy::ReadOnly,
+                &HashSet::new(),
+                false,
+                &TrackedFiles::default(),
+            )
+        };
+
+        assert_eq!(
+            assess("rg foo src/"),
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+        assert_eq!(assess("rg foo ../other"), SafetyCheck::AskUser);
+        assert_eq!(assess("find / -name id_rsa"), SafetyCheck::AskUser);
+    }
+
+    #[test]
+    fn test_heredoc_write_outside_writable_roots_requires_approval() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().join("repo");
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
+        };
+        let assess = |script: &str, approval_policy| {
+            assess_command_safety(
+                &["bash", "-lc", script].map(str::to_string),
+                &cwd,
+                approval_policy,
+                &policy,
+                &HashSet::new(),
+                false,
+                &TrackedFiles::default(),
+            )
+        };
+
+        let inside = "cat <<'EOF' > notes.md\nhello\nEOF";
+        assert!(matches!(
+            assess(inside, AskForApproval::OnRequest),
+            SafetyCheck::AutoApprove { .. }
+        ));
+        let outside = "cat <<'EOF' >> /etc/profile\nexport X=1\nEOF";
+        assert_eq!(
+            assess(outside, AskForApproval::OnRequest),
+            SafetyCheck::AskUser
+        );
+        assert!(matches!(
+            assess(outside, AskForApproval::Never),
+            SafetyCheck::Reject { .. }
+        ));
+        assert_eq!(
+            heredoc_writes_outside_writable_roots(
+                &["bash", "-lc", outside].map(str::to_string),
+                &cwd,
+                &SandboxPolicy::DangerFullAccess,
+                &TrackedFiles::default(),
+            ),
+            Vec::<PathBuf>::new()
+        );
+    }
+
+    #[test]
+    fn test_codex_config_paths_are_protected() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().to_path_buf();
+        let codex_home = tmp.path().join("home").join(".codex-home");
+        let make_add_change = |p: PathBuf| ApplyPatchAction::new_add_for_test(&p, "".to_string());
+
+        let home_config = make_add_change(codex_home.join("config.toml"));
+        let project_config = make_add_change(cwd.join(".codex").join("config.toml"));
+        let sibling = make_add_change(cwd.join("notes.txt"));
+
+        assert!(patch_touches_codex_config(&home_config, &cwd, &codex_home));
+        assert!(patch_touches_codex_config(
+            &project_config,
+            &cwd,
+            &codex_home
+        ));
+        assert!(!patch_touches_codex_config(&sibling, &cwd, &codex_home));
+    }
+
+    #[test]
+    fn test_project_codex_dir_requires_approval() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().to_path_buf();
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
+        };
+        let codex_home = tmp.path().join("codex-home");
+        let action =
+            ApplyPatchAction::new_add_for_test(&cwd.join(".codex/config.toml"), "".to_string());
+
+        assert_eq!(
+            assess_patch_safety(
+                &action,
+                AskForApproval::OnFailure,
+                &policy,
+                &cwd,
+                &codex_home,
+                &TrackedFiles::default()
+            ),
+            SafetyCheck::AskUser
+        );
+        assert!(matches!(
+            assess_patch_safety(
+                &action,
+                AskForApproval::Never,
+                &policy,
+                &cwd,
+                &codex_home,
+                &TrackedFiles::default()
+            ),
+            SafetyCheck::Reject { .. }
+        ));
+        assert!(matches!(
+            assess_patch_safety(
+                &action,
+                AskForApproval::Never,
+                &SandboxPolicy::DangerFullAccess,
+                &cwd,
+                &codex_home,
+                &TrackedFiles::default()
+            ),
+            SafetyCheck::AutoApprove { .. }
+        ));
+    }
+
+    #[test]
+    fn test_codex_home_inside_writable_root_requires_approval() {
+        let tmp = TempDir::new().unwrap();
+        let home = tmp.path().to_path_buf();
+        let codex_home = home.join("codex-home");
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
+        };
+        let config =
+            ApplyPatchAction::new_add_for_test(&codex_home.join("config.toml"), "".to_string());
+        let sibling = ApplyPatchAction::new_add_for_test(&home.join("notes.txt"), "".to_string());
+
+        assert_eq!(
+            assess_patch_safety(
+                &config,
+                AskForApproval::OnRequest,
+                &policy,
+                &home,
+                &codex_home,
+                &TrackedFiles::default()
+            ),
+            SafetyCheck::AskUser
+        );
+        assert!(matches!(
+            assess_patch_safety(
+                &sibling,
+                AskForApproval::OnRequest,
+                &policy,
+                &home,
+                &codex_home,
+                &TrackedFiles::default()
+            ),
+            SafetyCheck::AutoApprove { .. }
+        ));
+    }
 }
@@ -157,6 +157,8 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
         };
 
         let args = create_seatbelt_command_args(
@@ -232,6 +234,8 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            tracked_files_only: false,
+            allow_git_writes: false,
         };
 
         let args = create_seatbelt_command_args(
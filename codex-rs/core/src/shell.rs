@@ -1,7 +1,9 @@
+use crate::shell_dialect::ShellDialect;
 use serde::Deserialize;
 use serde::Serialize;
 use shlex;
 use std::path::PathBuf;
+use tracing::warn;
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct ZshShell {
@@ -33,14 +35,15 @@ impl Shell {
                 let mut result = vec![zsh.shell_path.clone()];
                 result.push("-lc".to_string());
 
-                let joined = strip_bash_lc(&command)
-                    .or_else(|| shlex::try_join(command.iter().map(|s| s.as_str())).ok());
+                let joined = match strip_bash_lc(&command) {
+                    Some(script) => script,
+                    // Without safe quoting, run the argv directly instead.
+                    None => format_command(ShellDialect::Bash, &command)
+                        .inspect_err(|e| warn!("not wrapping command in {}: {e}", zsh.shell_path))
+                        .ok()?,
+                };
 
-                if let Some(joined) = joined {
-                    result.push(format!("source {} && ({joined})", zsh.zshrc_path));
-                } else {
-                    return None;
-                }
+                result.push(format!("source {} && ({joined})", zsh.zshrc_path));
                 Some(result)
             }
             Shell::PowerShell(ps) => {
@@ -69,20 +72,19 @@ impl Shell {
                 // turn it into a PowerShell command.
                 let first = command.first().map(String::as_str);
                 if first != Some(ps.exe.as_str()) {
-                    // TODO (CODEX_2900): Handle escaping newlines.
-                    if command.iter().any(|a| a.contains('\n') || a.contains('\r')) {
-                        return Some(command);
-                    }
-
-                    let joined = shlex::try_join(command.iter().map(|s| s.as_str())).ok();
-                    return joined.map(|arg| {
-                        vec![
+                    return match format_command(ShellDialect::PowerShell, &command) {
+                        Ok(script) => Some(vec![
                             ps.exe.clone(),
                             "-NoProfile".to_string(),
                             "-Command".to_string(),
-                            arg,
-                        ]
-                    });
+                            script,
+                        ]),
+                        // Without safe quoting, run the argv directly instead.
+                        Err(e) => {
+                            warn!("not wrapping command in {}: {e}", ps.exe);
+                            Some(command)
+                        }
+                    };
                 }
 
                 // Model generated a PowerShell command. Run it.
@@ -103,6 +105,70 @@ impl Shell {
     }
 }
 
+/// An argument that cannot be quoted so that the shell reads it back verbatim.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UnquotableArgument {
+    #[error("argument contains a NUL byte: {0:?}")]
+    NulByte(String),
+    // TODO (CODEX_2900): Handle escaping newlines for PowerShell.
+    #[error("argument contains a line break: {0:?}")]
+    LineBreak(String),
+}
+
+/// Join `command` into a script for `dialect` in which every argument is
+/// quoted, so that agent-chosen values such as file names containing spaces,
+/// quotes, `;` or `$(...)` are passed through literally and never interpreted
+/// by the shell. Fails if some argument cannot be represented safely.
+pub fn format_command(
+    dialect: ShellDialect,
+    command: &[String],
+) -> Result<String, UnquotableArgument> {
+    if let Some(arg) = command.iter().find(|arg| arg.contains('\0')) {
+        return Err(UnquotableArgument::NulByte(arg.clone()));
+    }
+    match dialect {
+        ShellDialect::Bash | ShellDialect::Fish => {
+            shlex::try_join(command.iter().map(String::as_str))
+                .map_err(|_| UnquotableArgument::NulByte(command.join(" ")))
+        }
+        ShellDialect::PowerShell => {
+            if let Some(arg) = command.iter().find(|arg| arg.contains(['\n', '\r'])) {
+                return Err(UnquotableArgument::LineBreak(arg.clone()));
+            }
+            let quoted: Vec<String> = command.iter().map(|arg| quote_powershell(arg)).collect();
+            Ok(match quoted.first() {
+                // A quoted first word is a string expression, not a command;
+                // the call operator runs it.
+                Some(first) if first.starts_with('\'') => format!("& {}", quoted.join(" ")),
+                _ => quoted.join(" "),
+            })
+        }
+    }
+}
+
+/// Quote `arg` as a PowerShell verbatim string unless it is a plain word.
+/// Inside single quotes only quote characters are special (PowerShell also
+/// accepts the typographic ones), and each is escaped by doubling it.
+fn quote_powershell(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '\\' | ':'));
+    if is_plain {
+        return arg.to_string();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('\'');
+    for c in arg.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
 fn strip_bash_lc(command: &Vec<String>) -> Option<String> {
     match command.as_slice() {
         // exactly three items
@@ -196,6 +262,105 @@ pub async fn default_user_shell() -> Shell {
     }
 }
 
+#[cfg(test)]
+mod format_command_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// File names an agent might pick that would break naive interpolation.
+    const HOSTILE_NAMES: &[&str] = &[
+        "my file.txt",
+        "it's.txt",
+        "say \"hi\".txt",
+        "a; touch injected",
+        "$(touch injected)",
+        "`touch injected`",
+        "a && touch injected",
+        "*.txt",
+    ];
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn posix_quoting_passes_hostile_names_through_literally() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in HOSTILE_NAMES {
+            let script =
+                format_command(ShellDialect::Bash, &argv(&["printf", "%s", name])).unwrap();
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(&script)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout),
+                *name,
+                "script: {script}"
+            );
+        }
+        assert!(!dir.path().join("injected").exists());
+    }
+
+    #[test]
+    fn powershell_quoting_neutralizes_hostile_names() {
+        let cases = [
+            ("my file.txt", "rm 'my file.txt'"),
+            ("it's.txt", "rm 'it''s.txt'"),
+            ("it\u{2019}s.txt", "rm 'it\u{2019}\u{2019}s.txt'"),
+            ("a; touch injected", "rm 'a; touch injected'"),
+            ("$(touch injected)", "rm '$(touch injected)'"),
+            ("`touch injected`", "rm '`touch injected`'"),
+            ("", "rm ''"),
+            ("C:\\Users\\person\\a.txt", "rm C:\\Users\\person\\a.txt"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(
+                format_command(ShellDialect::PowerShell, &argv(&["rm", name])),
+                Ok(expected.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn powershell_quoted_program_is_invoked_with_call_operator() {
+        assert_eq!(
+            format_command(
+                ShellDialect::PowerShell,
+                &argv(&["C:\\Program Files\\tool.exe", "-v"])
+            ),
+            Ok("& 'C:\\Program Files\\tool.exe' -v".to_string())
+        );
+    }
+
+    #[test]
+    fn unquotable_arguments_are_flagged() {
+        assert_eq!(
+            format_command(ShellDialect::Bash, &argv(&["rm", "a\0b"])),
+            Err(UnquotableArgument::NulByte("a\0b".to_string()))
+        );
+        assert_eq!(
+            format_command(ShellDialect::PowerShell, &argv(&["rm", "a\nb"])),
+            Err(UnquotableArgument::LineBreak("a\nb".to_string()))
+        );
+        let zsh = Shell::Zsh(ZshShell {
+            shell_path: "/bin/zsh".to_string(),
+            zshrc_path: std::env::current_dir()
+                .unwrap()
+                .join("Cargo.toml")
+                .to_string_lossy()
+                .to_string(),
+        });
+        assert_eq!(
+            zsh.format_default_shell_invocation(argv(&["rm", "a\0b"])),
+            None
+        );
+    }
+}
+
 #[cfg(test)]
 #[cfg(target_os = "macos")]
 mod tests {
@@ -317,6 +482,7 @@ mod tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    stdin: None,
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
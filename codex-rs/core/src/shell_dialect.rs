@@ -0,0 +1,365 @@
+//! Shell syntax dialects understood by [`crate::parse_command`].
+//! [`crate::parse_command`] 支持的 shell 语法方言。
+//!
+//! Bash scripts go through the tree-sitter based parser. fish quotes, escapes
+//! and chains commands differently, so its scripts are split with a small
+//! tokenizer that follows its rules instead. PowerShell scripts are split by
+//! [`crate::windows_shell`], which also decides whether they are safe to run.
+
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::shell::format_command;
+use crate::windows_shell;
+use crate::windows_shell::program_name;
+
+/// The shell whose syntax a command string is written in.
+/// 命令字符串所使用的 shell 语法。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShellDialect {
+    /// POSIX-style shells (`bash`, `sh`, `zsh`).
+    #[default]
+    Bash,
+    /// `fish`: chains with `; and` / `; or`, and `\'` escapes inside single quotes.
+    Fish,
+    /// `pwsh` / `powershell`: separates with `;`, escapes with a backtick and
+    /// treats `\` as a literal path separator.
+    PowerShell,
+}
+
+/// One command of a script split by [`ShellDialect::split_script`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ScriptCommand {
+    /// The words after quote removal, without redirections.
+    pub words: Vec<String>,
+    /// Whether input or output is redirected to or from a file.
+    pub redirected: bool,
+    /// The command as written in the script.
+    pub text: String,
+}
+
+impl ShellDialect {
+    /// The dialect of the shell `command` invokes: `fish` and
+    /// `pwsh`/`powershell` map to their own dialect, anything else to `Bash`.
+    /// 根据 `command` 调用的程序推断方言，其他程序一律视为 `Bash`。
+    pub fn for_command(command: &[String]) -> Self {
+        match command.first().map(|arg0| program_name(arg0)).as_deref() {
+            Some("fish") => ShellDialect::Fish,
+            Some("pwsh" | "powershell") => ShellDialect::PowerShell,
+            _ => ShellDialect::Bash,
+        }
+    }
+
+    /// The script passed to the dialect's shell in `command`, e.g. the
+    /// argument of `fish -c` or everything after `pwsh -Command`. `None` for
+    /// `Bash` and for commands that do not run a script.
+    pub(crate) fn script(self, command: &[String]) -> Option<String> {
+        let (_, rest) = command.split_first()?;
+        if Self::for_command(command) != self {
+            return None;
+        }
+        match self {
+            ShellDialect::Bash => None,
+            ShellDialect::Fish => {
+                // `-c`, `--command` or a combined short flag such as `-lc`.
+                let idx = rest.iter().position(|a| {
+                    a == "--command"
+                        || (a.starts_with('-') && !a.starts_with("--") && a.ends_with('c'))
+                })?;
+                rest.get(idx + 1).cloned()
+            }
+            ShellDialect::PowerShell => {
+                let idx = rest.iter().position(|a| {
+                    matches!(
+                        a.to_ascii_lowercase().as_str(),
+                        "-command" | "-c" | "/command"
+                    )
+                })?;
+                let script = rest[idx + 1..].join(" ");
+                (!script.is_empty()).then_some(script)
+            }
+        }
+    }
+
+    /// Split `script` into its commands following this dialect's quoting,
+    /// escaping and separator rules. Pipelines, `;`, newlines, `&&`/`||` and
+    /// fish's `and`/`or` all start a new command.
+    ///
+    /// Returns `None` if the script cannot be tokenized, or uses command
+    /// substitution, subexpressions, blocks or brace expansion, since those
+    /// hide further commands.
+    /// 按方言规则将脚本切分为多条命令；无法可靠切分时返回 `None`。
+    pub(crate) fn split_script(self, script: &str) -> Option<Vec<ScriptCommand>> {
+        if self == ShellDialect::PowerShell {
+            // Display the same commands that the safety check looks at.
+            // 与安全检查使用同一套切分结果。
+            return windows_shell::split_script(script)?
+                .into_iter()
+                .map(|words| {
+                    let text = format_command(self, &words).ok()?;
+                    Some(ScriptCommand {
+                        words,
+                        redirected: false,
+                        text,
+                    })
+                })
+                .collect();
+        }
+        let mut splitter = Splitter {
+            dialect: self,
+            script,
+            chars: script.char_indices().peekable(),
+            commands: Vec::new(),
+            words: Vec::new(),
+            word: None,
+            redirected: false,
+            pending_redirect: false,
+            start: 0,
+        };
+        splitter.run()?;
+        Some(splitter.commands)
+    }
+}
+
+struct Splitter<'a> {
+    dialect: ShellDialect,
+    script: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    commands: Vec<ScriptCommand>,
+    words: Vec<String>,
+    /// The word being read; `Some("")` after an empty quoted string.
+    word: Option<String>,
+    redirected: bool,
+    /// The next word is the target of a redirection.
+    pending_redirect: bool,
+    /// Byte offset where the current command starts.
+    start: usize,
+}
+
+impl Splitter<'_> {
+    fn run(&mut self) -> Option<()> {
+        while let Some((pos, c)) = self.chars.next() {
+            match c {
+                ' ' | '\t' | '\r' => self.finish_word()?,
+                '\n' | ';' => self.finish_command(pos)?,
+                '#' if self.word.is_none() => {
+                    while self.chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                }
+                '|' => {
+                    self.chars.next_if(|&(_, c)| c == '|');
+                    self.finish_command(pos)?;
+                }
+                '&' => {
+                    if self.chars.next_if(|&(_, c)| c == '&').is_some() {
+                        self.finish_command(pos)?;
+                    } else if self.chars.next_if(|&(_, c)| c == '>').is_some() {
+                        // `&>file` redirects both stdout and stderr.
+                        self.start_redirect()?;
+                    } else {
+                        // Runs the preceding command in the background.
+                        self.finish_command(pos)?;
+                    }
+                }
+                '>' | '<' => {
+                    // A leading file descriptor (`2>`) belongs to the operator.
+                    if self
+                        .word
+                        .as_deref()
+                        .is_some_and(|w| !w.is_empty() && w.chars().all(|c| c.is_ascii_digit()))
+                    {
+                        self.word = None;
+                    }
+                    self.finish_word()?;
+                    self.chars.next_if(|&(_, c)| c == '>' || c == '|');
+                    if self.chars.next_if(|&(_, c)| c == '&').is_some() {
+                        // Duplicating a descriptor (`2>&1`) touches no file.
+                        while self.chars.next_if(|&(_, c)| c.is_ascii_digit()).is_some() {}
+                    } else {
+                        self.start_redirect()?;
+                    }
+                }
+                '(' | ')' | '{' | '}' => return None,
+                '\'' => self.single_quoted()?,
+                '"' => self.double_quoted()?,
+                '`' if self.dialect == ShellDialect::Bash => return None,
+                '\\' => {
+                    let (_, escaped) = self.chars.next()?;
+                    // A backslash-newline continues the line.
+                    if escaped != '\n' {
+                        self.push(escaped);
+                    }
+                }
+                c => self.push(c),
+            }
+        }
+        self.finish_command(self.script.len())
+    }
+
+    fn single_quoted(&mut self) -> Option<()> {
+        self.word.get_or_insert_default();
+        loop {
+            let (_, c) = self.chars.next()?;
+            match (self.dialect, c) {
+                (_, '\'') => return Some(()),
+                (ShellDialect::Fish, '\\') => {
+                    match self.chars.next_if(|&(_, c)| c == '\'' || c == '\\') {
+                        Some((_, escaped)) => self.push(escaped),
+                        None => self.push('\\'),
+                    }
+                }
+                (_, c) => self.push(c),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self) -> Option<()> {
+        self.word.get_or_insert_default();
+        loop {
+            let (_, c) = self.chars.next()?;
+            match (self.dialect, c) {
+                (_, '"') => return Some(()),
+                (_, '$') if self.chars.peek().is_some_and(|&(_, c)| c == '(') => return None,
+                (ShellDialect::Bash, '`') => return None,
+                (_, '\\') => {
+                    match self
+                        .chars
+                        .next_if(|&(_, c)| matches!(c, '"' | '\\' | '$' | '\n'))
+                    {
+                        Some((_, '\n')) => {}
+                        Some((_, escaped)) => self.push(escaped),
+                        None => self.push('\\'),
+                    }
+                }
+                (_, c) => self.push(c),
+            }
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        self.word.get_or_insert_default().push(c);
+    }
+
+    fn start_redirect(&mut self) -> Option<()> {
+        if self.pending_redirect {
+            return None;
+        }
+        self.pending_redirect = true;
+        Some(())
+    }
+
+    fn finish_word(&mut self) -> Option<()> {
+        let Some(word) = self.word.take() else {
+            return Some(());
+        };
+        if self.pending_redirect {
+            self.pending_redirect = false;
+            self.redirected |= !is_null_device(&word);
+        } else {
+            self.words.push(word);
+        }
+        Some(())
+    }
+
+    fn finish_command(&mut self, end: usize) -> Option<()> {
+        self.finish_word()?;
+        if self.pending_redirect {
+            // A redirection without a target is a syntax error.
+            return None;
+        }
+        let text = self.script[self.start..end].trim().to_string();
+        // The separator has been consumed; the next command starts after it.
+        self.start = self.chars.peek().map_or(self.script.len(), |&(pos, _)| pos);
+        let mut words = std::mem::take(&mut self.words);
+        if self.dialect == ShellDialect::Fish
+            && words
+                .first()
+                .is_some_and(|w| matches!(w.as_str(), "and" | "or" | "not"))
+        {
+            words.remove(0);
+        }
+        let redirected = std::mem::take(&mut self.redirected);
+        if !words.is_empty() || redirected {
+            let text = match self.dialect {
+                ShellDialect::Fish => strip_fish_keyword(&text).to_string(),
+                _ => text,
+            };
+            self.commands.push(ScriptCommand {
+                words,
+                redirected,
+                text,
+            });
+        }
+        Some(())
+    }
+}
+
+fn strip_fish_keyword(text: &str) -> &str {
+    ["and ", "or ", "not "]
+        .iter()
+        .find_map(|keyword| text.strip_prefix(keyword))
+        .map_or(text, str::trim_start)
+}
+
+/// Redirection targets that discard output.
+fn is_null_device(target: &str) -> bool {
+    target == "/dev/null"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn command(words: &[&str], redirected: bool, text: &str) -> ScriptCommand {
+        ScriptCommand {
+            words: words.iter().map(|w| w.to_string()).collect(),
+            redirected,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn detects_dialect_from_program() {
+        let dialect = |arg0: &str| ShellDialect::for_command(&[arg0.to_string()]);
+        assert_eq!(dialect("/usr/bin/fish"), ShellDialect::Fish);
+        assert_eq!(
+            dialect("C:\\Tools\\PowerShell.exe"),
+            ShellDialect::PowerShell
+        );
+        assert_eq!(dialect("zsh"), ShellDialect::Bash);
+    }
+
+    #[test]
+    fn two_character_separators_and_descriptor_duplication() {
+        assert_eq!(
+            ShellDialect::Fish.split_script("make 2>&1 && echo ok || echo failed"),
+            Some(vec![
+                command(&["make"], false, "make 2>&1"),
+                command(&["echo", "ok"], false, "echo ok"),
+                command(&["echo", "failed"], false, "echo failed"),
+            ])
+        );
+    }
+
+    #[test]
+    fn powershell_call_operator_and_scriptblocks() {
+        let script = "& 'C:\\Program Files\\tool.exe' --check";
+        assert_eq!(
+            ShellDialect::PowerShell.split_script(script),
+            Some(vec![command(
+                &["C:\\Program Files\\tool.exe", "--check"],
+                false,
+                script
+            )])
+        );
+        assert_eq!(
+            ShellDialect::PowerShell.split_script("Get-ChildItem | ForEach-Object { rm $_ }"),
+            None
+        );
+        // Rejected like the safety check rejects it.
+        assert_eq!(
+            ShellDialect::PowerShell.split_script("Get-ChildItem x; Remove-Item y"),
+            None
+        );
+    }
+}
@@ -25,6 +25,9 @@ pub const CODEX_SANDBOX_ENV_VAR: &str = "CODEX_SANDBOX";
 #[derive(Debug, Clone, Copy)]
 pub enum StdioPolicy {
     RedirectForShellTool,
+    /// Like [`StdioPolicy::RedirectForShellTool`], but stdin is a pipe the
+    /// caller writes the command's input to.
+    RedirectForShellToolWithStdin,
     Inherit,
 }
 
@@ -86,14 +89,23 @@ pub(crate) async fn spawn_child_async(
     }
 
     match stdio_policy {
-        StdioPolicy::RedirectForShellTool => {
-            // Do not create a file descriptor for stdin because otherwise some
-            // commands may hang forever waiting for input. For example, ripgrep has
-            // a heuristic where it may try to read from stdin as explained here:
-            // https://github.com/BurntSushi/ripgrep/blob/e2362d4d5185d02fa857bf381e7bd52e66fafc73/crates/core/flags/hiargs.rs#L1101-L1103
-            cmd.stdin(Stdio::null());
+        StdioPolicy::RedirectForShellTool | StdioPolicy::RedirectForShellToolWithStdin => {
+            if matches!(stdio_policy, StdioPolicy::RedirectForShellToolWithStdin) {
+                cmd.stdin(Stdio::piped());
+            } else {
+                // Do not create a file descriptor for stdin because otherwise some
+                // commands may hang forever waiting for input. For example, ripgrep has
+                // a heuristic where it may try to read from stdin as explained here:
+                // https://github.com/BurntSushi/ripgrep/blob/e2362d4d5185d02fa857bf381e7bd52e66fafc73/crates/core/flags/hiargs.rs#L1101-L1103
+                cmd.stdin(Stdio::null());
+            }
 
             cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+            // Lead a new process group so a timed-out command can be killed
+            // together with any processes it spawned.
+            #[cfg(unix)]
+            cmd.process_group(0);
         }
         StdioPolicy::Inherit => {
             // Inherit stdin, stdout, and stderr from the parent process.
@@ -0,0 +1,108 @@
+//! Formatting of persisted and logged timestamps according to the
+//! `timestamps` config option.
+
+use std::fmt;
+
+use time::OffsetDateTime;
+use time::UtcOffset;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+
+use crate::config_types::TimestampFormat;
+
+const RFC3339_FORMAT: &[FormatItem] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+
+const UTC_FORMAT: &[FormatItem] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:3] UTC");
+
+const LOCAL_FORMAT: &[FormatItem] = format_description!(
+    "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:3] [offset_hour sign:mandatory]:[offset_minute]"
+);
+
+impl TimestampFormat {
+    /// Format `instant` in this style. `local` falls back to UTC when the
+    /// local offset cannot be determined.
+    pub fn format(self, instant: OffsetDateTime) -> String {
+        let local_offset = match self {
+            TimestampFormat::Local => UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC),
+            TimestampFormat::Rfc3339 | TimestampFormat::Utc => UtcOffset::UTC,
+        };
+        self.format_with_local_offset(instant, local_offset)
+    }
+
+    /// The current time, formatted in this style.
+    pub fn now(self) -> String {
+        self.format(OffsetDateTime::now_utc())
+    }
+
+    fn format_with_local_offset(self, instant: OffsetDateTime, local_offset: UtcOffset) -> String {
+        let (instant, format) = match self {
+            TimestampFormat::Rfc3339 => (instant.to_offset(UtcOffset::UTC), RFC3339_FORMAT),
+            TimestampFormat::Utc => (instant.to_offset(UtcOffset::UTC), UTC_FORMAT),
+            TimestampFormat::Local => (instant.to_offset(local_offset), LOCAL_FORMAT),
+        };
+        // The format descriptions only use components every instant has.
+        instant.format(format).unwrap_or_default()
+    }
+}
+
+/// A `tracing_subscriber` timer that stamps log lines in the configured
+/// style.
+#[derive(Debug, Clone, Copy)]
+pub struct TimestampTimer(pub TimestampFormat);
+
+impl FormatTime for TimestampTimer {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        write!(w, "{}", self.0.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use time::macros::datetime;
+    use time::macros::offset;
+
+    const INSTANT: OffsetDateTime = datetime!(2025-01-02 03:04:05.678 UTC);
+
+    #[test]
+    fn rfc3339_is_utc_with_z_suffix() {
+        assert_eq!(
+            TimestampFormat::Rfc3339.format_with_local_offset(INSTANT, offset!(+1)),
+            "2025-01-02T03:04:05.678Z"
+        );
+    }
+
+    #[test]
+    fn rfc3339_converts_offset_instants_to_utc() {
+        let instant = INSTANT.to_offset(offset!(-8));
+        assert_eq!(
+            TimestampFormat::Rfc3339.format(instant),
+            "2025-01-02T03:04:05.678Z"
+        );
+    }
+
+    #[test]
+    fn utc_is_space_separated_with_zone_name() {
+        assert_eq!(
+            TimestampFormat::Utc.format_with_local_offset(INSTANT, offset!(+1)),
+            "2025-01-02 03:04:05.678 UTC"
+        );
+    }
+
+    #[test]
+    fn local_uses_the_local_offset() {
+        assert_eq!(
+            TimestampFormat::Local.format_with_local_offset(INSTANT, offset!(+5:30)),
+            "2025-01-02 08:34:05.678 +05:30"
+        );
+        assert_eq!(
+            TimestampFormat::Local.format_with_local_offset(INSTANT, offset!(-8)),
+            "2025-01-01 19:04:05.678 -08:00"
+        );
+    }
+}
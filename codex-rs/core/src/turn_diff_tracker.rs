@@ -3,14 +3,21 @@ use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::Arc;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use sha1::digest::Output;
 use uuid::Uuid;
+use wildmatch::WildMatchPattern;
 
 use crate::protocol::FileChange;
+use crate::protocol::SandboxPolicy;
+
+/// A tracker shared between the task that records patches into it and
+/// `Op::GetDiff`, which may read it while the task is running.
+pub type SharedTurnDiffTracker = Arc<tokio::sync::Mutex<TurnDiffTracker>>;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
 const DEV_NULL: &str = "/dev/null";
@@ -22,6 +29,17 @@ struct BaselineFileInfo {
     oid: String,
 }
 
+/// Outcome of [`TurnDiffTracker::revert`], with paths sorted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RevertReport {
+    /// Files written back to their pre-turn content (edited, deleted or moved).
+    pub restored: Vec<PathBuf>,
+    /// Files created during the turn that were removed.
+    pub removed: Vec<PathBuf>,
+    /// Files left as they are because they lie outside the writable roots.
+    pub skipped: Vec<PathBuf>,
+}
+
 /// Tracks sets of changes to files and exposes the overall unified diff.
 /// Internally, the way this works is now:
 /// 1. Maintain an in-memory baseline snapshot of files when they are first seen.
@@ -40,6 +58,18 @@ pub struct TurnDiffTracker {
     temp_name_to_current_path: HashMap<String, PathBuf>,
     /// Cache of known git worktree roots to avoid repeated filesystem walks.
     git_root_cache: Vec<PathBuf>,
+    /// When true, hunks whose only changes are whitespace are omitted from the
+    /// reported diff. The changes themselves are still applied on disk.
+    ignore_whitespace: bool,
+    /// Files matching any of these globs are still applied on disk but left
+    /// out of the reported diff.
+    ignore_patterns: Vec<WildMatchPattern<'*', '?'>>,
+    /// Number of changed files left out of the last computed diff because they
+    /// matched `ignore_patterns`.
+    suppressed_files: usize,
+    /// Whether this turn may have written any file, including ones left out
+    /// of the reported diff and ones written by shell commands.
+    files_changed: bool,
 }
 
 impl TurnDiffTracker {
@@ -47,6 +77,42 @@ impl TurnDiffTracker {
         Self::default()
     }
 
+    /// Omit whitespace-only hunks from [`TurnDiffTracker::get_unified_diff`]
+    /// when `ignore_whitespace` is set.
+    pub fn with_ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.ignore_whitespace = ignore_whitespace;
+        self
+    }
+
+    /// Exclude files matching any of `patterns` from the reported diff. A
+    /// pattern without a `/` (e.g. `*.snap`) matches the file name; one with
+    /// a `/` matches the path relative to the git root.
+    pub fn with_ignore_patterns(mut self, patterns: &[String]) -> Self {
+        self.ignore_patterns = patterns
+            .iter()
+            .map(|pattern| WildMatchPattern::new(pattern))
+            .collect();
+        self
+    }
+
+    /// Number of changed files omitted from the last
+    /// [`TurnDiffTracker::get_unified_diff`] because they matched an ignore
+    /// pattern.
+    pub fn suppressed_files(&self) -> usize {
+        self.suppressed_files
+    }
+
+    /// Record that a patch was applied or a command that may write files ran.
+    pub fn note_files_changed(&mut self) {
+        self.files_changed = true;
+    }
+
+    /// Whether [`TurnDiffTracker::note_files_changed`] was called, without
+    /// computing the diff.
+    pub fn files_changed(&self) -> bool {
+        self.files_changed
+    }
+
     /// Front-run apply patch calls to track the starting contents of any modified files.
     /// - Creates an in-memory baseline snapshot for files that already exist on disk when first seen.
     /// - For additions, we intentionally do not create a baseline snapshot so that diffs are proper additions.
@@ -127,6 +193,48 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Paths touched by `changes` (move destinations included) that fall
+    /// outside the writable roots of `sandbox_policy` relative to `cwd`,
+    /// sorted. Only `WorkspaceWrite` has roots to check against; under the
+    /// other policies nothing is reported.
+    pub fn paths_outside_writable_roots(
+        changes: &HashMap<PathBuf, FileChange>,
+        cwd: &Path,
+        sandbox_policy: &SandboxPolicy,
+    ) -> Vec<PathBuf> {
+        if !matches!(sandbox_policy, SandboxPolicy::WorkspaceWrite { .. }) {
+            return Vec::new();
+        }
+        let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+        let mut outside: Vec<PathBuf> = changes
+            .iter()
+            .flat_map(|(path, change)| {
+                let dest = match change {
+                    FileChange::Update {
+                        move_path: Some(dest),
+                        ..
+                    } => Some(dest),
+                    _ => None,
+                };
+                std::iter::once(path).chain(dest)
+            })
+            .filter(|path| {
+                let path = if path.is_absolute() {
+                    path.to_path_buf()
+                } else {
+                    cwd.join(path)
+                };
+                !writable_roots
+                    .iter()
+                    .any(|root| root.is_path_writable(&path))
+            })
+            .cloned()
+            .collect();
+        outside.sort();
+        outside.dedup();
+        outside
+    }
+
     fn get_path_for_internal(&self, internal: &str) -> Option<PathBuf> {
         self.temp_name_to_current_path
             .get(internal)
@@ -235,8 +343,14 @@ impl TurnDiffTracker {
                 .unwrap_or_default()
         });
 
+        self.suppressed_files = 0;
         for internal in baseline_file_names {
-            aggregated.push_str(self.get_file_diff(&internal).as_str());
+            let file_diff = self.get_file_diff(&internal);
+            if !file_diff.is_empty() && self.is_ignored(&internal) {
+                self.suppressed_files += 1;
+                continue;
+            }
+            aggregated.push_str(file_diff.as_str());
             if !aggregated.ends_with('\n') {
                 aggregated.push('\n');
             }
@@ -249,6 +363,97 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Whether the file tracked as `internal` matches an ignore pattern, either
+    /// at its current path or at its baseline path (for moves).
+    fn is_ignored(&mut self, internal: &str) -> bool {
+        if self.ignore_patterns.is_empty() {
+            return false;
+        }
+        let paths: Vec<PathBuf> = self
+            .temp_name_to_current_path
+            .get(internal)
+            .into_iter()
+            .chain(self.baseline_file_info.get(internal).map(|info| &info.path))
+            .cloned()
+            .collect();
+        paths.iter().any(|path| {
+            let relative = self.relative_to_git_root_str(path);
+            let file_name = relative.rsplit('/').next().unwrap_or(&relative);
+            self.ignore_patterns.iter().any(|pattern| {
+                if pattern.pattern_chars().contains(&'/') {
+                    pattern.matches(&relative)
+                } else {
+                    pattern.matches(file_name)
+                }
+            })
+        })
+    }
+
+    /// Discard every change tracked so far: edited and deleted files get their
+    /// baseline snapshot back, files added during the turn are removed and
+    /// moved files return to their original path. Paths that `sandbox_policy`
+    /// does not allow writing (relative to `cwd`) are skipped; under a
+    /// read-only policy only paths inside `cwd` are touched.
+    pub fn revert(&mut self, cwd: &Path, sandbox_policy: &SandboxPolicy) -> Result<RevertReport> {
+        let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+        let is_writable = |path: &Path| match sandbox_policy {
+            SandboxPolicy::DangerFullAccess => true,
+            SandboxPolicy::ReadOnly => path.starts_with(cwd),
+            SandboxPolicy::WorkspaceWrite { .. } => writable_roots
+                .iter()
+                .any(|root| root.is_path_writable(path)),
+        };
+
+        let mut report = RevertReport::default();
+        let internal_names: Vec<String> = self.baseline_file_info.keys().cloned().collect();
+        for internal in internal_names {
+            let Some(current_path) = self.get_path_for_internal(&internal) else {
+                continue;
+            };
+            let Some(baseline) = self.baseline_file_info.get(&internal) else {
+                continue;
+            };
+            if !is_writable(&baseline.path) || !is_writable(&current_path) {
+                report.skipped.push(baseline.path.clone());
+                continue;
+            }
+
+            let current_exists = fs::symlink_metadata(&current_path).is_ok();
+            if baseline.oid == ZERO_OID {
+                // Added during the turn (possibly moved afterwards).
+                if current_exists {
+                    fs::remove_file(&current_path).with_context(|| {
+                        format!("failed to remove added file {}", current_path.display())
+                    })?;
+                    report.removed.push(current_path);
+                }
+                continue;
+            }
+
+            let moved = current_path != baseline.path;
+            if moved && current_exists {
+                fs::remove_file(&current_path).with_context(|| {
+                    format!("failed to remove moved file {}", current_path.display())
+                })?;
+            }
+            let current_mode = file_mode_for_path(&baseline.path);
+            let unchanged = current_mode == Some(baseline.mode)
+                && blob_bytes(&baseline.path, &baseline.mode).as_deref()
+                    == Some(baseline.content.as_slice());
+            if !unchanged {
+                restore_baseline(baseline)?;
+            }
+            if moved || !unchanged {
+                report.restored.push(baseline.path.clone());
+            }
+        }
+
+        report.restored.sort();
+        report.removed.sort();
+        report.skipped.sort();
+        Ok(report)
+    }
+
     fn get_file_diff(&mut self, internal_file_name: &str) -> String {
         let mut aggregated = String::new();
 
@@ -327,6 +532,22 @@ impl TurnDiffTracker {
             let l = left_text.unwrap_or("");
             let r = right_text.unwrap_or("");
 
+            let ignore_whitespace = self.ignore_whitespace;
+            let diff = similar::TextDiff::from_lines(l, r);
+            let mut unified = diff.unified_diff();
+            unified.context_radius(3);
+            let hunks: Vec<_> = unified
+                .iter_hunks()
+                .filter(|hunk| !(ignore_whitespace && is_whitespace_only_hunk(hunk)))
+                .collect();
+
+            // Every hunk was whitespace-only: drop the file from the diff
+            // entirely unless there is something else (mode change,
+            // add/delete) worth reporting.
+            if hunks.is_empty() && !is_add && !is_delete && baseline_mode == current_mode {
+                return String::new();
+            }
+
             aggregated.push_str(&format!("index {left_oid}..{right_oid}\n"));
 
             let old_header = if left_present {
@@ -340,14 +561,13 @@ impl TurnDiffTracker {
                 DEV_NULL.to_string()
             };
 
-            let diff = similar::TextDiff::from_lines(l, r);
-            let unified = diff
-                .unified_diff()
-                .context_radius(3)
-                .header(&old_header, &new_header)
-                .to_string();
-
-            aggregated.push_str(&unified);
+            if !hunks.is_empty() {
+                aggregated.push_str(&format!("--- {old_header}\n"));
+                aggregated.push_str(&format!("+++ {new_header}\n"));
+                for hunk in hunks {
+                    aggregated.push_str(&hunk.to_string());
+                }
+            }
         } else {
             aggregated.push_str(&format!("index {left_oid}..{right_oid}\n"));
             let old_header = if left_present {
@@ -368,6 +588,74 @@ impl TurnDiffTracker {
     }
 }
 
+/// Write `baseline` back to its path, replacing whatever is there now.
+fn restore_baseline(baseline: &BaselineFileInfo) -> Result<()> {
+    let path = &baseline.path;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory {}", parent.display()))?;
+    }
+    if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink()) {
+        fs::remove_file(path)
+            .with_context(|| format!("failed to remove symlink {}", path.display()))?;
+    }
+
+    match baseline.mode {
+        FileMode::Symlink => restore_symlink(path, &baseline.content)?,
+        _ => fs::write(path, &baseline.content)
+            .with_context(|| format!("failed to restore {}", path.display()))?,
+    }
+
+    #[cfg(unix)]
+    if baseline.mode != FileMode::Symlink {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path)?.permissions();
+        let mode = perms.mode();
+        let mode = if baseline.mode == FileMode::Executable {
+            mode | 0o111
+        } else {
+            mode & !0o111
+        };
+        perms.set_mode(mode);
+        fs::set_permissions(path, perms)
+            .with_context(|| format!("failed to restore mode of {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn restore_symlink(path: &Path, target: &[u8]) -> Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+    std::os::unix::fs::symlink(std::ffi::OsStr::from_bytes(target), path)
+        .with_context(|| format!("failed to restore symlink {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn restore_symlink(path: &Path, _target: &[u8]) -> Result<()> {
+    Err(anyhow!("cannot restore symlink {}", path.display()))
+}
+
+/// Returns true if the removed and inserted lines of `hunk` are identical once
+/// all whitespace is stripped (e.g. a pure re-indentation).
+fn is_whitespace_only_hunk<'a>(
+    hunk: &similar::udiff::UnifiedDiffHunk<'_, 'a, 'a, '_, str>,
+) -> bool {
+    let mut removed = String::new();
+    let mut inserted = String::new();
+    for change in hunk.iter_changes() {
+        let target = match change.tag() {
+            similar::ChangeTag::Delete => &mut removed,
+            similar::ChangeTag::Insert => &mut inserted,
+            similar::ChangeTag::Equal => continue,
+        };
+        target.extend(change.value().chars().filter(|c| !c.is_whitespace()));
+    }
+    removed == inserted
+}
+
 /// Compute the Git SHA-1 blob object ID for the given content (bytes).
 fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
     // Git blob hash is sha1 of: "blob <len>\0<data>"
@@ -883,4 +1171,278 @@ index {ZERO_OID}..{right_oid}
         };
         assert_eq!(combined, expected_combined);
     }
+
+    /// Map of relative path -> contents for every file under `root`.
+    fn snapshot_tree(root: &Path) -> std::collections::BTreeMap<PathBuf, Vec<u8>> {
+        let mut out = std::collections::BTreeMap::new();
+        let mut dirs = vec![root.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else {
+                    let rel = path.strip_prefix(root).unwrap().to_path_buf();
+                    out.insert(rel, fs::read(&path).unwrap());
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn revert_restores_original_tree_after_add_edit_delete() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let edited = root.join("edited.txt");
+        let deleted = root.join("deleted.txt");
+        let added = root.join("added.txt");
+        fs::write(&edited, "one\ntwo\n").unwrap();
+        fs::write(&deleted, "gone\n").unwrap();
+        fs::write(root.join("untouched.txt"), "same\n").unwrap();
+        let original = snapshot_tree(root);
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([
+            (
+                added.clone(),
+                FileChange::Add {
+                    content: "new\n".to_string(),
+                },
+            ),
+            (
+                edited.clone(),
+                FileChange::Update {
+                    unified_diff: "".to_owned(),
+                    move_path: None,
+                },
+            ),
+            (deleted.clone(), FileChange::Delete),
+        ]));
+        fs::write(&added, "new\n").unwrap();
+        fs::write(&edited, "one\nTWO\n").unwrap();
+        fs::remove_file(&deleted).unwrap();
+
+        let report = acc
+            .revert(root, &SandboxPolicy::new_workspace_write_policy())
+            .unwrap();
+
+        assert_eq!(
+            report,
+            RevertReport {
+                restored: vec![deleted, edited],
+                removed: vec![added],
+                skipped: vec![],
+            }
+        );
+        assert_eq!(snapshot_tree(root), original);
+        assert_eq!(acc.get_unified_diff().unwrap(), None);
+    }
+
+    #[test]
+    fn revert_skips_paths_outside_writable_roots() {
+        let workspace = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        let file = outside.path().join("a.txt");
+        fs::write(&file, "before\n").unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([(
+            file.clone(),
+            FileChange::Update {
+                unified_diff: "".to_owned(),
+                move_path: None,
+            },
+        )]));
+        fs::write(&file, "after\n").unwrap();
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
+        };
+        let report = acc.revert(workspace.path(), &policy).unwrap();
+
+        assert_eq!(report.skipped, vec![file.clone()]);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "after\n");
+    }
+
+    fn workspace_write_without_tmp() -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: false,
+        }
+    }
+
+    #[test]
+    fn change_outside_writable_roots_is_reported() {
+        let workspace = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        let inside_file = workspace.path().join("a.txt");
+        let moved_out = outside.path().join("b.txt");
+        let changes = HashMap::from([(
+            inside_file,
+            FileChange::Update {
+                unified_diff: "".to_owned(),
+                move_path: Some(moved_out.clone()),
+            },
+        )]);
+
+        let reported = TurnDiffTracker::paths_outside_writable_roots(
+            &changes,
+            workspace.path(),
+            &workspace_write_without_tmp(),
+        );
+
+        assert_eq!(reported, vec![moved_out]);
+    }
+
+    #[test]
+    fn change_inside_writable_roots_is_not_reported() {
+        let workspace = tempdir().unwrap();
+        let changes = HashMap::from([
+            (
+                workspace.path().join("a.txt"),
+                FileChange::Add {
+                    content: "a\n".to_string(),
+                },
+            ),
+            (PathBuf::from("relative.txt"), FileChange::Delete),
+        ]);
+
+        let reported = TurnDiffTracker::paths_outside_writable_roots(
+            &changes,
+            workspace.path(),
+            &workspace_write_without_tmp(),
+        );
+
+        assert_eq!(reported, Vec::<PathBuf>::new());
+    }
+
+    fn reindent_file(ignore_whitespace: bool) -> (tempfile::TempDir, Option<String>) {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(&file, "fn main() {\nprintln!(\"hi\");\n}\n").unwrap();
+
+        let mut acc = TurnDiffTracker::new().with_ignore_whitespace(ignore_whitespace);
+        let update_changes = HashMap::from([(
+            file.clone(),
+            FileChange::Update {
+                unified_diff: "".to_owned(),
+                move_path: None,
+            },
+        )]);
+        acc.on_patch_begin(&update_changes);
+
+        // Simulate a formatter re-indenting the body.
+        fs::write(&file, "fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+        let diff = acc.get_unified_diff().unwrap();
+        (dir, diff)
+    }
+
+    #[test]
+    fn indentation_only_change_is_omitted_when_ignoring_whitespace() {
+        let (_dir, diff) = reindent_file(true);
+        assert_eq!(diff, None);
+    }
+
+    #[test]
+    fn indentation_only_change_is_shown_by_default() {
+        let (dir, diff) = reindent_file(false);
+        let diff = normalize_diff_for_test(&diff.unwrap(), dir.path());
+        assert!(
+            diff.contains("-println!(\"hi\");\n+    println!(\"hi\");"),
+            "unexpected diff: {diff}"
+        );
+    }
+
+    #[test]
+    fn ignore_whitespace_keeps_substantive_hunks() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        let baseline: String = (1..=20).map(|i| format!("line{i}\n")).collect();
+        fs::write(&file, &baseline).unwrap();
+
+        let mut acc = TurnDiffTracker::new().with_ignore_whitespace(true);
+        let update_changes = HashMap::from([(
+            file.clone(),
+            FileChange::Update {
+                unified_diff: "".to_owned(),
+                move_path: None,
+            },
+        )]);
+        acc.on_patch_begin(&update_changes);
+
+        // Re-indent line 2 and change line 18; the hunks are far enough apart
+        // to be reported separately.
+        let updated = baseline
+            .replace("line2\n", "  line2\n")
+            .replace("line18\n", "LINE18\n");
+        fs::write(&file, updated).unwrap();
+
+        let diff = acc.get_unified_diff().unwrap().unwrap();
+        assert!(!diff.contains("  line2"), "unexpected diff: {diff}");
+        assert!(diff.contains("+LINE18"), "unexpected diff: {diff}");
+    }
+
+    /// Update `Cargo.lock` and `src/lib.rs` in a git repo tracked with
+    /// `ignore_patterns`, returning the diff and the suppressed count.
+    fn update_lockfile_and_source(ignore_patterns: &[String]) -> (Option<String>, usize) {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        let lockfile = dir.path().join("Cargo.lock");
+        let source = dir.path().join("src").join("lib.rs");
+        fs::write(&lockfile, "version = 3\n").unwrap();
+        fs::write(&source, "pub fn a() {}\n").unwrap();
+
+        let mut acc = TurnDiffTracker::new().with_ignore_patterns(ignore_patterns);
+        let update = || FileChange::Update {
+            unified_diff: "".to_owned(),
+            move_path: None,
+        };
+        acc.on_patch_begin(&HashMap::from([
+            (lockfile.clone(), update()),
+            (source.clone(), update()),
+        ]));
+
+        fs::write(&lockfile, "version = 4\n").unwrap();
+        fs::write(&source, "pub fn b() {}\n").unwrap();
+        let diff = acc.get_unified_diff().unwrap();
+        (diff, acc.suppressed_files())
+    }
+
+    #[test]
+    fn ignored_lockfile_change_is_suppressed() {
+        let (diff, suppressed) = update_lockfile_and_source(&["Cargo.lock".to_string()]);
+        let diff = diff.unwrap();
+        assert!(!diff.contains("Cargo.lock"), "{diff}");
+        assert!(diff.contains("+pub fn b() {}"), "{diff}");
+        assert_eq!(suppressed, 1);
+    }
+
+    #[test]
+    fn lockfile_change_is_shown_when_not_ignored() {
+        let (diff, suppressed) = update_lockfile_and_source(&["*.snap".to_string()]);
+        let diff = diff.unwrap();
+        assert!(diff.contains("+version = 4"), "{diff}");
+        assert!(diff.contains("+pub fn b() {}"), "{diff}");
+        assert_eq!(suppressed, 0);
+    }
+
+    #[test]
+    fn pattern_with_slash_matches_repo_relative_path() {
+        let (diff, suppressed) = update_lockfile_and_source(&["src/*.rs".to_string()]);
+        let diff = diff.unwrap();
+        assert!(diff.contains("+version = 4"), "{diff}");
+        assert!(!diff.contains("pub fn"), "{diff}");
+        assert_eq!(suppressed, 1);
+    }
 }
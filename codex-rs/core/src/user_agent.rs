@@ -1,4 +1,4 @@
-const DEFAULT_ORIGINATOR: &str = "codex_cli_rs";
+pub(crate) const DEFAULT_ORIGINATOR: &str = "codex_cli_rs";
 
 pub fn get_codex_user_agent(originator: Option<&str>) -> String {
     let build_version = env!("CARGO_PKG_VERSION");
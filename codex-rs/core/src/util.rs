@@ -1,6 +1,11 @@
 use std::path::Path;
 use std::time::Duration;
 
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ReasoningItemReasoningSummary;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::WebSearchAction;
 use rand::Rng;
 
 const INITIAL_DELAY_MS: u64 = 200;
@@ -42,3 +47,281 @@ pub fn is_inside_git_repo(base_dir: &Path) -> bool {
 
     false
 }
+
+/// Render a conversation as Markdown, e.g. to save a session transcript.
+///
+/// Messages get a header per role, reasoning summaries are quoted, and tool
+/// calls and their output are shown in fenced code blocks. Images are
+/// replaced by a placeholder link; inline `data:` images are not embedded.
+pub fn transcript_to_markdown(entries: &[ResponseItem]) -> String {
+    let mut sections: Vec<String> = Vec::new();
+    for entry in entries {
+        match entry {
+            ResponseItem::Message { role, content, .. } => {
+                let body: Vec<String> = content
+                    .iter()
+                    .map(|item| match item {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            text.trim_end().to_string()
+                        }
+                        ContentItem::InputImage { image_url } => image_placeholder(image_url),
+                    })
+                    .collect();
+                sections.push(format!("## {}\n\n{}", role_title(role), body.join("\n\n")));
+            }
+            ResponseItem::Reasoning { summary, .. } => {
+                let quoted: Vec<String> = summary
+                    .iter()
+                    .map(|ReasoningItemReasoningSummary::SummaryText { text }| {
+                        text.trim_end()
+                            .lines()
+                            .map(|line| format!("> {line}").trim_end().to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .collect();
+                if !quoted.is_empty() {
+                    sections.push(format!("### Reasoning\n\n{}", quoted.join("\n>\n")));
+                }
+            }
+            ResponseItem::LocalShellCall {
+                action: LocalShellAction::Exec(exec),
+                ..
+            } => {
+                sections.push(format!(
+                    "### Tool call: `shell`\n\n{}",
+                    fenced("sh", &join_command(&exec.command))
+                ));
+            }
+            ResponseItem::FunctionCall {
+                name, arguments, ..
+            } => {
+                let shell_command = serde_json::from_str::<serde_json::Value>(arguments)
+                    .ok()
+                    .and_then(|args| {
+                        let command: Vec<String> =
+                            serde_json::from_value(args.get("command")?.clone()).ok()?;
+                        Some(join_command(&command))
+                    });
+                let block = match shell_command {
+                    Some(command) if name == "shell" => fenced("sh", &command),
+                    _ => fenced("json", arguments),
+                };
+                sections.push(format!("### Tool call: `{name}`\n\n{block}"));
+            }
+            ResponseItem::CustomToolCall { name, input, .. } => {
+                sections.push(format!("### Tool call: `{name}`\n\n{}", fenced("", input)));
+            }
+            ResponseItem::FunctionCallOutput { output, .. } => {
+                sections.push(tool_output_section(&output.content));
+            }
+            ResponseItem::CustomToolCallOutput { output, .. } => {
+                sections.push(tool_output_section(output));
+            }
+            ResponseItem::WebSearchCall { action, .. } => match action {
+                WebSearchAction::Search { query, .. } => {
+                    sections.push(format!("### Web search\n\n`{query}`"));
+                }
+                WebSearchAction::Other => sections.push("### Web search".to_string()),
+            },
+            ResponseItem::Other => {}
+        }
+    }
+
+    let mut markdown = sections.join("\n\n");
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+    markdown
+}
+
+fn role_title(role: &str) -> String {
+    let mut chars = role.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Unknown".to_string(),
+    }
+}
+
+fn image_placeholder(image_url: &str) -> String {
+    if image_url.starts_with("http://") || image_url.starts_with("https://") {
+        format!("[image]({image_url})")
+    } else {
+        "[image](#image)".to_string()
+    }
+}
+
+fn join_command(command: &[String]) -> String {
+    shlex::try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "))
+}
+
+/// Exec output is recorded as `{"output": ..., "metadata": {"exit_code": ..}}`;
+/// show just the output and exit code for those, and anything else verbatim.
+fn tool_output_section(content: &str) -> String {
+    let exec = serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|value| {
+            let output = value.get("output")?.as_str()?.to_string();
+            let exit_code = value
+                .get("metadata")
+                .and_then(|metadata| metadata.get("exit_code"))
+                .and_then(serde_json::Value::as_i64);
+            Some((output, exit_code))
+        });
+    match exec {
+        Some((output, Some(exit_code))) => {
+            format!(
+                "### Output (exit code {exit_code})\n\n{}",
+                fenced("text", &output)
+            )
+        }
+        Some((output, None)) => format!("### Output\n\n{}", fenced("text", &output)),
+        None => format!("### Output\n\n{}", fenced("text", content)),
+    }
+}
+
+/// Fence `body` with enough backticks that backtick runs inside it cannot
+/// close the block early.
+fn fenced(info: &str, body: &str) -> String {
+    let longest_run = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{info}\n{}\n{fence}", body.trim_end_matches('\n'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::models::LocalShellExecAction;
+    use codex_protocol::models::LocalShellStatus;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, content: ContentItem) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![content],
+        }
+    }
+
+    #[test]
+    fn renders_role_headers_reasoning_and_exec_output() {
+        let entries = vec![
+            message(
+                "user",
+                ContentItem::InputText {
+                    text: "list files".to_string(),
+                },
+            ),
+            ResponseItem::Reasoning {
+                id: "r1".to_string(),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: "Run ls.".to_string(),
+                }],
+                content: None,
+                encrypted_content: None,
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls","-la","my dir"]}"#.to_string(),
+                call_id: "c1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "c1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: r#"{"output":"a.txt\nb.txt\n","metadata":{"exit_code":0,"duration_seconds":0.1}}"#
+                        .to_string(),
+                    success: Some(true),
+                },
+            },
+            message(
+                "assistant",
+                ContentItem::OutputText {
+                    text: "Two files.".to_string(),
+                },
+            ),
+            ResponseItem::Other,
+        ];
+
+        assert_eq!(
+            transcript_to_markdown(&entries),
+            "## User\n\nlist files\n\n\
+             ### Reasoning\n\n> Run ls.\n\n\
+             ### Tool call: `shell`\n\n```sh\nls -la 'my dir'\n```\n\n\
+             ### Output (exit code 0)\n\n```text\na.txt\nb.txt\n```\n\n\
+             ## Assistant\n\nTwo files.\n"
+        );
+    }
+
+    #[test]
+    fn renders_local_shell_calls_and_other_tools() {
+        let entries = vec![
+            ResponseItem::LocalShellCall {
+                id: None,
+                call_id: Some("c1".to_string()),
+                status: LocalShellStatus::Completed,
+                action: LocalShellAction::Exec(LocalShellExecAction {
+                    command: vec!["echo".to_string(), "hi".to_string()],
+                    timeout_ms: None,
+                    working_directory: None,
+                    env: None,
+                    user: None,
+                }),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "update_plan".to_string(),
+                arguments: r#"{"plan":[]}"#.to_string(),
+                call_id: "c2".to_string(),
+            },
+            ResponseItem::CustomToolCallOutput {
+                call_id: "c3".to_string(),
+                output: "Done!".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            transcript_to_markdown(&entries),
+            "### Tool call: `shell`\n\n```sh\necho hi\n```\n\n\
+             ### Tool call: `update_plan`\n\n```json\n{\"plan\":[]}\n```\n\n\
+             ### Output\n\n```text\nDone!\n```\n"
+        );
+    }
+
+    #[test]
+    fn output_containing_fences_gets_a_longer_fence() {
+        let entries = vec![ResponseItem::CustomToolCallOutput {
+            call_id: "c1".to_string(),
+            output: "```rust\nfn main() {}\n```".to_string(),
+        }];
+
+        assert_eq!(
+            transcript_to_markdown(&entries),
+            "### Output\n\n````text\n```rust\nfn main() {}\n```\n````\n"
+        );
+    }
+
+    #[test]
+    fn images_become_placeholder_links() {
+        let entries = vec![
+            message(
+                "user",
+                ContentItem::InputImage {
+                    image_url: "data:image/png;base64,AAAA".to_string(),
+                },
+            ),
+            message(
+                "user",
+                ContentItem::InputImage {
+                    image_url: "https://example.com/cat.png".to_string(),
+                },
+            ),
+        ];
+
+        assert_eq!(
+            transcript_to_markdown(&entries),
+            "## User\n\n[image](#image)\n\n## User\n\n[image](https://example.com/cat.png)\n"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Helpers for recognising commands written for `cmd.exe` or PowerShell.
+//! 识别 `cmd.exe` / PowerShell 命令的辅助函数。
+//!
+//! The rest of the command parsing assumes POSIX shell syntax. On Windows the
+//! model frequently emits `cmd /c dir` or `powershell -Command Get-ChildItem`,
+//! and bare builtins such as `del`, which would otherwise be left
+//! unclassified.
+
+use shlex::split as shlex_split;
+
+/// Which Windows shell a command is interpreted by. The same alias can mean
+/// different things in each (e.g. `ls` is only an alias in PowerShell).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowsShell {
+    Cmd,
+    PowerShell,
+}
+
+/// Coarse classification of a single Windows command.
+/// 单条 Windows 命令的粗略分类。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowsCommandKind {
+    /// Lists directory contents (`dir`, `Get-ChildItem`).
+    ListFiles,
+    /// Prints a file (`type`, `Get-Content`).
+    Read,
+    /// Searches file contents (`findstr`, `Select-String`).
+    Search,
+    /// Other commands without side effects (`cd`, `echo`, `Get-Location`).
+    ReadOnly,
+    /// Deletes or overwrites data (`del`, `rd`, `Remove-Item`).
+    Destructive,
+    /// Anything we do not recognise.
+    Unknown,
+}
+
+impl WindowsCommandKind {
+    /// Whether the command can be run without side effects.
+    pub(crate) fn is_read_only(self) -> bool {
+        matches!(
+            self,
+            Self::ListFiles | Self::Read | Self::Search | Self::ReadOnly
+        )
+    }
+}
+
+/// A command extracted from a Windows shell invocation, together with the
+/// shell that will interpret it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WindowsCommand {
+    pub shell: WindowsShell,
+    pub tokens: Vec<String>,
+}
+
+impl WindowsCommand {
+    pub(crate) fn kind(&self) -> WindowsCommandKind {
+        classify_windows_command(self.shell, &self.tokens)
+    }
+}
+
+/// Extract the individual commands from a Windows shell invocation:
+/// 从 Windows shell 调用中提取各条命令：
+///
+/// - `cmd /c <script>` (also `cmd.exe`, `/C`, `/k`)
+/// - `powershell -Command <script>` (also `pwsh`, `-c`, leading `-NoProfile`
+///   style switches)
+/// - a bare PowerShell cmdlet such as `Get-ChildItem` or `cmd.exe` builtin
+///   such as `dir` or `del`, on Windows only, since elsewhere those names
+///   run whatever binary happens to be on `PATH`
+///
+/// The script is split on `&&`, `||`, `&`, `;` and `|` when they stand alone
+/// as words. Returns `None` if the command is not recognisably
+/// Windows-specific, or the script cannot be tokenised, joins commands with a
+/// separator inside a word (`dir a&del b`), or uses redirections,
+/// subexpressions or variable expansion.
+pub(crate) fn try_parse_windows_commands(command: &[String]) -> Option<Vec<WindowsCommand>> {
+    let (first, rest) = command.split_first()?;
+    let program = program_name(first);
+
+    let (shell, script) = match program.as_str() {
+        "cmd" => {
+            let idx = rest
+                .iter()
+                .position(|a| matches!(a.to_ascii_lowercase().as_str(), "/c" | "/k"))?;
+            (WindowsShell::Cmd, rest[idx + 1..].join(" "))
+        }
+        "powershell" | "pwsh" => {
+            let idx = rest.iter().position(|a| {
+                matches!(
+                    a.to_ascii_lowercase().as_str(),
+                    "-command" | "-c" | "/command"
+                )
+            })?;
+            (WindowsShell::PowerShell, rest[idx + 1..].join(" "))
+        }
+        _ if cfg!(windows) => {
+            let shell = if is_powershell_cmdlet(first) {
+                WindowsShell::PowerShell
+            } else if is_cmd_builtin(&program) {
+                WindowsShell::Cmd
+            } else {
+                return None;
+            };
+            return Some(vec![WindowsCommand {
+                shell,
+                tokens: command.to_vec(),
+            }]);
+        }
+        _ => return None,
+    };
+
+    let commands = split_script(&script)?;
+    if commands.is_empty() {
+        return None;
+    }
+    Some(
+        commands
+            .into_iter()
+            .map(|tokens| WindowsCommand { shell, tokens })
+            .collect(),
+    )
+}
+
+/// Classify a single tokenised command as interpreted by `shell`.
+/// 按 `shell` 的语义对单条命令进行分类。
+pub(crate) fn classify_windows_command(
+    shell: WindowsShell,
+    tokens: &[String],
+) -> WindowsCommandKind {
+    let Some(first) = tokens.first() else {
+        return WindowsCommandKind::Unknown;
+    };
+    let name = program_name(first);
+
+    let cmd_kind = match name.as_str() {
+        "dir" | "tree" => Some(WindowsCommandKind::ListFiles),
+        "type" | "more" => Some(WindowsCommandKind::Read),
+        "findstr" | "find" => Some(WindowsCommandKind::Search),
+        "cd" | "chdir" | "echo" | "where" | "ver" => Some(WindowsCommandKind::ReadOnly),
+        "del" | "erase" | "rd" | "rmdir" | "format" => Some(WindowsCommandKind::Destructive),
+        _ => None,
+    };
+
+    match shell {
+        WindowsShell::Cmd => cmd_kind.unwrap_or(WindowsCommandKind::Unknown),
+        WindowsShell::PowerShell => match name.as_str() {
+            "get-childitem" | "gci" | "ls" | "dir" => WindowsCommandKind::ListFiles,
+            "get-content" | "gc" | "cat" | "type" => WindowsCommandKind::Read,
+            "select-string" | "sls" => WindowsCommandKind::Search,
+            "get-location" | "gl" | "pwd" | "set-location" | "sl" | "cd" | "chdir" | "get-item"
+            | "gi" | "test-path" | "resolve-path" | "write-output" | "echo" | "write-host"
+            | "measure-object" | "measure" => WindowsCommandKind::ReadOnly,
+            "remove-item" | "ri" | "rm" | "del" | "erase" | "rd" | "rmdir" | "clear-content"
+            | "clc" | "clear-item" | "cli" | "set-content" | "sc" | "format-volume" => {
+                WindowsCommandKind::Destructive
+            }
+            _ => WindowsCommandKind::Unknown,
+        },
+    }
+}
+
+/// Lower-cased program name with any directory and `.exe` suffix removed.
+pub(crate) fn program_name(arg0: &str) -> String {
+    let base = arg0.rsplit(['\\', '/']).next().unwrap_or(arg0);
+    let lower = base.to_ascii_lowercase();
+    lower
+        .strip_suffix(".exe")
+        .map(str::to_string)
+        .unwrap_or(lower)
+}
+
+/// PowerShell cmdlets follow the `Verb-Noun` naming scheme.
+fn is_powershell_cmdlet(arg0: &str) -> bool {
+    const VERBS: &[&str] = &[
+        "get", "set", "remove", "clear", "select", "test", "resolve", "write", "measure", "format",
+        "new", "copy", "move", "rename", "invoke", "start", "stop",
+    ];
+    match arg0.split_once('-') {
+        Some((verb, noun)) => {
+            !noun.is_empty()
+                && noun.chars().all(|c| c.is_ascii_alphanumeric())
+                && VERBS.contains(&verb.to_ascii_lowercase().as_str())
+        }
+        None => false,
+    }
+}
+
+fn is_cmd_builtin(name: &str) -> bool {
+    matches!(
+        name,
+        "dir" | "type" | "del" | "erase" | "rd" | "rmdir" | "findstr" | "where" | "ver"
+    )
+}
+
+/// Split a `cmd.exe` or PowerShell script into the words of each command.
+/// Returns `None` for scripts whose effects cannot be judged from the words.
+pub(crate) fn split_script(script: &str) -> Option<Vec<Vec<String>>> {
+    // Redirections (`>`, `<`), subexpressions (`$(...)`, `(...)`), escapes
+    // and variable expansion could turn an otherwise read-only command into
+    // one with side effects, so refuse to interpret them.
+    const UNSUPPORTED: &[char] = &['>', '<', '(', ')', '$', '`', '^', '%', '{', '}', '@'];
+    // Both shells also treat these as command separators in the middle of a
+    // word, so only accept them as standalone tokens.
+    const SEPARATORS: &[char] = &['&', '|', ';'];
+
+    let tokens = shlex_split(script)?;
+    let mut commands = Vec::new();
+    let mut current = Vec::new();
+    for token in tokens {
+        match token.as_str() {
+            "&&" | "||" | "&" | ";" | "|" => {
+                if !current.is_empty() {
+                    commands.push(std::mem::take(&mut current));
+                }
+            }
+            t if t.contains(UNSUPPORTED) || t.contains(SEPARATORS) => return None,
+            _ => current.push(token),
+        }
+    }
+    if !current.is_empty() {
+        commands.push(current);
+    }
+    Some(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn kinds(command: &[&str]) -> Option<Vec<WindowsCommandKind>> {
+        try_parse_windows_commands(&vec_str(command))
+            .map(|cmds| cmds.iter().map(WindowsCommand::kind).collect())
+    }
+
+    #[test]
+    fn cmd_wrapper_splits_script() {
+        assert_eq!(
+            kinds(&["cmd.exe", "/C", "dir /b && del foo.txt"]),
+            Some(vec![
+                WindowsCommandKind::ListFiles,
+                WindowsCommandKind::Destructive
+            ])
+        );
+    }
+
+    #[test]
+    fn powershell_wrapper_uses_powershell_aliases() {
+        assert_eq!(
+            kinds(&[
+                "powershell",
+                "-NoProfile",
+                "-Command",
+                "Get-ChildItem src ; rm build"
+            ]),
+            Some(vec![
+                WindowsCommandKind::ListFiles,
+                WindowsCommandKind::Destructive
+            ])
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn bare_cmdlets_are_recognised_on_windows() {
+        assert_eq!(
+            kinds(&["Remove-Item", "-Recurse", "build"]),
+            Some(vec![WindowsCommandKind::Destructive])
+        );
+        assert_eq!(
+            kinds(&["Get-Content", "README.md"]),
+            Some(vec![WindowsCommandKind::Read])
+        );
+    }
+
+    #[test]
+    fn scripts_with_redirection_are_rejected() {
+        assert_eq!(kinds(&["cmd", "/c", "dir > listing.txt"]), None);
+        assert_eq!(kinds(&["pwsh", "-c", "Get-Content $(Remove-Item x)"]), None);
+    }
+
+    #[test]
+    fn separators_inside_words_are_rejected() {
+        assert_eq!(kinds(&["cmd", "/c", "dir foo&del bar"]), None);
+        assert_eq!(kinds(&["cmd", "/c", "dir foo&&del bar"]), None);
+        assert_eq!(kinds(&["cmd", "/c", "dir foo|del bar"]), None);
+        assert_eq!(
+            kinds(&["pwsh", "-c", "Get-ChildItem x;Remove-Item y"]),
+            None
+        );
+        assert_eq!(
+            kinds(&["pwsh", "-c", "Get-ChildItem x; Remove-Item y"]),
+            None
+        );
+        assert_eq!(kinds(&["pwsh", "-c", "Get-Content `whoami`"]), None);
+        assert_eq!(
+            kinds(&["pwsh", "-c", "Get-Content x$(Remove-Item y)"]),
+            None
+        );
+        assert_eq!(kinds(&["cmd", "/c", "type a.txt>b.txt"]), None);
+    }
+
+    #[test]
+    fn posix_commands_are_not_windows_commands() {
+        assert_eq!(kinds(&["ls", "-la"]), None);
+        assert_eq!(kinds(&["bash", "-lc", "rm -rf build"]), None);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn bare_cmd_builtins_are_recognised_on_windows() {
+        assert_eq!(
+            kinds(&["del", "foo.txt"]),
+            Some(vec![WindowsCommandKind::Destructive])
+        );
+        assert_eq!(kinds(&["dir"]), Some(vec![WindowsCommandKind::ListFiles]));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn bare_windows_commands_are_ignored_elsewhere() {
+        assert_eq!(kinds(&["dir"]), None);
+        assert_eq!(kinds(&["Get-Content", "README.md"]), None);
+        assert_eq!(kinds(&["Remove-Item", "-Recurse", "build"]), None);
+    }
+}
@@ -1,11 +1,42 @@
 #![expect(clippy::expect_used)]
 
+use std::sync::Arc;
+
 use tempfile::TempDir;
+use uuid::Uuid;
+use wiremock::MockServer;
 
 use codex_core::CodexConversation;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::config::ConfigToml;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+
+pub mod responses;
+
+/// Whether the tests run inside a Codex sandbox without network access, where
+/// a mock server cannot be reached.
+pub fn network_disabled() -> bool {
+    std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok()
+}
+
+/// Returns early from a test that talks to a mock server when the network is
+/// disabled.
+#[macro_export]
+macro_rules! skip_if_no_network {
+    () => {
+        if $crate::network_disabled() {
+            println!(
+                "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+            );
+            return;
+        }
+    };
+}
 
 /// Returns a default `Config` whose on-disk state is confined to the provided
 /// temporary directory. Using a per-test directory keeps tests hermetic and
@@ -19,6 +50,46 @@ pub fn load_default_config_for_test(codex_home: &TempDir) -> Config {
     .expect("defaults for test should always succeed")
 }
 
+/// A conversation whose model provider is a mock server, as started by
+/// [`start_mock_conversation`].
+pub struct MockConversation {
+    /// `CODEX_HOME` of the conversation, removed on drop.
+    pub home: TempDir,
+    /// The config the conversation was started with.
+    pub config: Config,
+    pub conversation_id: Uuid,
+    pub codex: Arc<CodexConversation>,
+}
+
+/// Starts a conversation in a fresh `CODEX_HOME` that sends its model
+/// requests to `server`, authenticated with a dummy API key. `configure` runs
+/// on the default test config after the provider has been pointed at the
+/// server, so it may also adjust the provider.
+pub async fn start_mock_conversation(
+    server: &MockServer,
+    configure: impl FnOnce(&mut Config),
+) -> MockConversation {
+    let home = TempDir::new().expect("create codex home");
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    configure(&mut config);
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let new_conversation = conversation_manager
+        .new_conversation(config.clone())
+        .await
+        .expect("start conversation");
+    MockConversation {
+        home,
+        config,
+        conversation_id: new_conversation.conversation_id,
+        codex: new_conversation.conversation,
+    }
+}
+
 /// Builds an SSE stream body from a JSON fixture.
 ///
 /// The fixture must contain an array of objects where each object represents a
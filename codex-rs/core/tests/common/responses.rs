@@ -0,0 +1,54 @@
+//! Helpers for scripting the mock Responses API in integration tests.
+
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Build an SSE stream body from a list of JSON events.
+pub fn sse(events: Vec<Value>) -> String {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    for ev in events {
+        let kind = ev
+            .get("type")
+            .and_then(|v| v.as_str())
+            .expect("event missing type");
+        writeln!(&mut out, "event: {kind}").expect("write to String");
+        if !ev.as_object().map(|o| o.len() == 1).unwrap_or(false) {
+            write!(&mut out, "data: {ev}\n\n").expect("write to String");
+        } else {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// A `response.completed` event for response `id`, with zero usage.
+pub fn completed(id: &str) -> Value {
+    json!({
+        "type": "response.completed",
+        "response": {
+            "id": id,
+            "usage": {"input_tokens":0,"input_tokens_details":null,"output_tokens":0,"output_tokens_details":null,"total_tokens":0}
+        }
+    })
+}
+
+/// Serve `body` as the SSE stream for the next `POST /v1/responses` only.
+/// Mount one per expected request, in order.
+pub async fn mount_once(server: &MockServer, body: String) {
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(body, "text/event-stream"),
+        )
+        .up_to_n_times(1)
+        .mount(server)
+        .await;
+}
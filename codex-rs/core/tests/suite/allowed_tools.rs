@@ -0,0 +1,141 @@
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn user_turn(text: &str, cwd: &TempDir, allowed_tools: Option<Vec<String>>) -> Op {
+    Op::UserTurn {
+        items: vec![InputItem::Text { text: text.into() }],
+        cwd: cwd.path().to_path_buf(),
+        approval_policy: AskForApproval::Never,
+        sandbox_policy: SandboxPolicy::new_read_only_policy(),
+        model: "codex-mini-latest".to_string(),
+        effort: ReasoningEffort::Medium,
+        summary: ReasoningSummary::Auto,
+        include_raw_reasoning: false,
+        allowed_tools,
+        max_output_tokens: None,
+        danger_full_access_confirmed: false,
+    }
+}
+
+/// `allowed_tools` limits the advertised tools for its own turn only.
+#[tokio::test]
+async fn allowed_tools_limits_tools_for_one_turn() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let cwd = TempDir::new().expect("tempdir");
+    let test = start_mock_conversation(&server, |config| config.include_plan_tool = true).await;
+    let codex = test.codex;
+
+    codex
+        .submit(user_turn(
+            "shell only",
+            &cwd,
+            Some(vec!["local_shell".to_string()]),
+        ))
+        .await
+        .expect("submit restricted turn");
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    codex
+        .submit(user_turn("all tools", &cwd, None))
+        .await
+        .expect("submit unrestricted turn");
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.expect("requests");
+    let tools: Vec<Value> = requests
+        .iter()
+        .map(|req| req.body_json::<Value>().expect("json body")["tools"].clone())
+        .collect();
+    assert_eq!(tools[0], json!([{ "type": "local_shell" }]));
+    let unrestricted = tools[1].as_array().expect("tools array");
+    assert!(unrestricted.len() > 1, "{unrestricted:?}");
+    assert!(unrestricted.contains(&json!({ "type": "local_shell" })));
+}
+
+/// A call to a tool outside `allowed_tools` is not run; the model gets an
+/// error output instead.
+#[tokio::test]
+async fn call_to_tool_outside_allowed_tools_is_rejected() {
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(vec![
+            json!({
+                "type": "response.output_item.done",
+                "item": {
+                    "type": "function_call",
+                    "name": "update_plan",
+                    "arguments": r#"{"plan":[{"step":"sneak","status":"pending"}]}"#,
+                    "call_id": "call-plan"
+                }
+            }),
+            completed("resp1"),
+        ]),
+    )
+    .await;
+    mount_once(&server, sse(vec![completed("resp2")])).await;
+
+    let cwd = TempDir::new().expect("tempdir");
+    let test = start_mock_conversation(&server, |config| config.include_plan_tool = true).await;
+    let codex = test.codex;
+
+    codex
+        .submit(user_turn(
+            "shell only",
+            &cwd,
+            Some(vec!["local_shell".to_string()]),
+        ))
+        .await
+        .expect("submit restricted turn");
+    let event = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::PlanUpdate(_) | EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert!(matches!(event, EventMsg::TaskComplete(_)), "{event:?}");
+
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 2);
+    let input = requests[1].body_json::<Value>().expect("json body")["input"].clone();
+    let output = input
+        .as_array()
+        .expect("input array")
+        .iter()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == "call-plan")
+        .expect("function_call_output for the rejected call");
+    assert_eq!(
+        output["output"],
+        "tool `update_plan` is not allowed in this turn"
+    );
+}
@@ -0,0 +1,125 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::CodexConversation;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const PATCH: &str = "*** Begin Patch\n*** Add File: hello.txt\n+hello\n*** End Patch";
+
+/// A patch larger than Linux's 128 KiB limit on a single argument.
+fn large_patch() -> String {
+    let body = "+0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef\n".repeat(4096);
+    format!("*** Begin Patch\n*** Add File: large.txt\n{body}*** End Patch")
+}
+
+/// Runs one turn in which the model calls `apply_patch` with `patch` and
+/// returns the resulting `PatchApplyEnd` event.
+async fn apply_patch_with(
+    workspace: &TempDir,
+    patch: &str,
+    command: Vec<String>,
+) -> PatchApplyEndEvent {
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(vec![
+            json!({
+                "type": "response.output_item.done",
+                "item": {
+                    "type": "function_call",
+                    "name": "apply_patch",
+                    "arguments": json!({ "input": patch }).to_string(),
+                    "call_id": "call-1"
+                }
+            }),
+            completed("r1"),
+        ]),
+    )
+    .await;
+    mount_once(&server, sse(vec![completed("r2")])).await;
+
+    let test = start_mock_conversation(&server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        config.apply_patch_command = Some(command);
+    })
+    .await;
+    run_task(&test.codex).await
+}
+
+async fn run_task(codex: &CodexConversation) -> PatchApplyEndEvent {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "add hello.txt".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    let EventMsg::PatchApplyEnd(end) =
+        wait_for_event(codex, |ev| matches!(ev, EventMsg::PatchApplyEnd(_))).await
+    else {
+        unreachable!()
+    };
+    wait_for_event(codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    end
+}
+
+fn sh(script: &str) -> Vec<String> {
+    vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn external_apply_patch_command_receives_patch_on_stdin() {
+    skip_if_no_network!();
+
+    for patch in [PATCH.to_string(), large_patch()] {
+        let workspace = TempDir::new().unwrap();
+        let end = apply_patch_with(
+            &workspace,
+            &patch,
+            sh("cat > received.patch && echo applied"),
+        )
+        .await;
+
+        assert!(end.success, "{end:?}");
+        assert_eq!(end.stdout.trim(), "applied");
+        let received = std::fs::read_to_string(workspace.path().join("received.patch")).unwrap();
+        assert_eq!(received, patch);
+        // The built-in applier was not used.
+        assert!(!workspace.path().join("hello.txt").exists());
+        assert!(!workspace.path().join("large.txt").exists());
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failing_external_apply_patch_command_reports_failure() {
+    skip_if_no_network!();
+
+    let workspace = TempDir::new().unwrap();
+    let end = apply_patch_with(
+        &workspace,
+        PATCH,
+        sh("cat > /dev/null; echo 'patch does not apply' >&2; exit 1"),
+    )
+    .await;
+
+    assert!(!end.success, "{end:?}");
+    assert!(end.stderr.contains("patch does not apply"), "{end:?}");
+}
@@ -0,0 +1,100 @@
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::PatchConflict;
+use codex_core::protocol::PatchConflictReason;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+/// Written against an older copy of notes.txt that still said "before".
+const STALE_PATCH: &str =
+    "*** Begin Patch\n*** Update File: notes.txt\n@@\n-before\n+after\n*** End Patch";
+
+/// A patch whose context no longer matches the file is reported as a failed
+/// patch whose conflicts name the stale file.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stale_patch_reports_context_mismatch_for_file() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(vec![
+            json!({
+                "type": "response.output_item.done",
+                "item": {
+                    "type": "function_call",
+                    "name": "apply_patch",
+                    "arguments": json!({ "input": STALE_PATCH }).to_string(),
+                    "call_id": "call-1"
+                }
+            }),
+            completed("r1"),
+        ]),
+    )
+    .await;
+    mount_once(&server, sse(vec![completed("r2")])).await;
+
+    let workspace = TempDir::new().unwrap();
+    let notes = workspace.path().join("notes.txt");
+    std::fs::write(&notes, "changed since\n").unwrap();
+
+    let test = start_mock_conversation(&server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::new_read_only_policy();
+    })
+    .await;
+    let codex = test.codex;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "edit notes.txt".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    // The failed patch is reported like any other: a begin, then an end.
+    let EventMsg::PatchApplyBegin(begin) = wait_for_event(&codex, |ev| {
+        matches!(
+            ev,
+            EventMsg::PatchApplyBegin(_) | EventMsg::PatchApplyEnd(_)
+        )
+    })
+    .await
+    else {
+        panic!("PatchApplyEnd arrived before PatchApplyBegin");
+    };
+    assert_eq!(begin.call_id, "call-1");
+    assert!(begin.changes.contains_key(&notes), "{begin:?}");
+    let EventMsg::PatchApplyEnd(end) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::PatchApplyEnd(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(!end.success, "{end:?}");
+    assert!(
+        end.stderr.contains("Failed to find expected lines"),
+        "{end:?}"
+    );
+    assert_eq!(
+        end.conflicts,
+        vec![PatchConflict {
+            path: notes.clone(),
+            reason: PatchConflictReason::ContextMismatch,
+        }]
+    );
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert_eq!(std::fs::read_to_string(&notes).unwrap(), "changed since\n");
+}
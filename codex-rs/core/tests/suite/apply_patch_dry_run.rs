@@ -0,0 +1,89 @@
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const PATCH: &str = "*** Begin Patch\n*** Update File: notes.txt\n@@\n-before\n+after\n*** Add File: new.txt\n+new\n*** End Patch";
+
+/// In dry-run mode a patch is validated and previewed through the usual
+/// events, but the files on disk stay untouched, even under a read-only
+/// sandbox that would reject the write.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn dry_run_previews_patch_without_touching_files() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(vec![
+            json!({
+                "type": "response.output_item.done",
+                "item": {
+                    "type": "function_call",
+                    "name": "apply_patch",
+                    "arguments": json!({ "input": PATCH }).to_string(),
+                    "call_id": "call-1"
+                }
+            }),
+            completed("r1"),
+        ]),
+    )
+    .await;
+    mount_once(&server, sse(vec![completed("r2")])).await;
+
+    let workspace = TempDir::new().unwrap();
+    let notes = workspace.path().join("notes.txt");
+    std::fs::write(&notes, "before\n").unwrap();
+
+    let test = start_mock_conversation(&server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::new_read_only_policy();
+        config.apply_patch_dry_run = true;
+    })
+    .await;
+    let codex = test.codex;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "edit notes.txt".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::PatchApplyBegin(begin) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::PatchApplyBegin(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(begin.dry_run);
+    assert_eq!(begin.changes.len(), 2);
+
+    let EventMsg::PatchApplyEnd(end) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::PatchApplyEnd(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(end.dry_run && end.success, "{end:?}");
+    assert!(
+        end.stdout
+            .contains("--- a/notes.txt\n+++ b/notes.txt\n@@ -1 +1 @@\n-before\n+after\n"),
+        "{end:?}"
+    );
+    assert!(end.stdout.contains("+++ b/new.txt\n"), "{end:?}");
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert_eq!(std::fs::read_to_string(&notes).unwrap(), "before\n");
+    assert!(!workspace.path().join("new.txt").exists());
+}
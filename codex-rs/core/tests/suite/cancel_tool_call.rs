@@ -0,0 +1,142 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_with_timeout;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::MockServer;
+
+fn shell_call(call_id: &str, command: &[&str]) -> Value {
+    json!({
+        "type": "response.output_item.done",
+        "item": {
+            "type": "function_call",
+            "name": "shell",
+            "arguments": json!({ "command": command }).to_string(),
+            "call_id": call_id
+        }
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancel_tool_call_cancels_only_that_call() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(vec![
+            shell_call("call-slow", &["sleep", "30"]),
+            shell_call("call-fast", &["echo", "done"]),
+            completed("r1"),
+        ]),
+    )
+    .await;
+    mount_once(&server, sse(vec![completed("r2")])).await;
+
+    let test = start_mock_conversation(&server, |config| {
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    })
+    .await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "run both".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::ExecCommandBegin(begin) if begin.call_id == "call-slow"),
+    )
+    .await;
+    codex
+        .submit(Op::CancelToolCall {
+            call_id: "call-slow".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::ExecCommandEnd(slow_end) = wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::ExecCommandEnd(end) if end.call_id == "call-slow"),
+    )
+    .await
+    else {
+        unreachable!()
+    };
+    assert_ne!(slow_end.exit_code, 0);
+    assert!(slow_end.stderr.contains("cancelled"), "{slow_end:?}");
+
+    let EventMsg::ExecCommandEnd(fast_end) = wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::ExecCommandEnd(end) if end.call_id == "call-fast"),
+    )
+    .await
+    else {
+        unreachable!()
+    };
+    assert_eq!(fast_end.exit_code, 0);
+    assert_eq!(fast_end.stdout.trim(), "done");
+
+    // The turn was not aborted: both outputs go back to the model.
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let body = requests[1].body_json::<Value>().unwrap();
+    let outputs: Vec<&Value> = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|item| item["type"] == "function_call_output")
+        .collect();
+    assert_eq!(outputs.len(), 2);
+    assert!(
+        outputs[0]["output"]
+            .as_str()
+            .unwrap()
+            .contains("tool call cancelled by user")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancel_unknown_tool_call_reports_error() {
+    let server = MockServer::start().await;
+    let test = start_mock_conversation(&server, |_| {}).await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::CancelToolCall {
+            call_id: "missing".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::Error(err) = wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(err.message.contains("`missing`"), "{}", err.message);
+}
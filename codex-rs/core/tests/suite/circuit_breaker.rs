@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use codex_core::circuit_breaker::CircuitBreakerConfig;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses::completed;
+use core_test_support::responses::sse;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeated_failures_open_circuit_until_probe_succeeds() {
+    let server = MockServer::start().await;
+
+    // The provider fails twice, then recovers.
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse(vec![completed("r1")]), "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+
+    let cooldown = Duration::from_millis(300);
+    let test = start_mock_conversation(&server, |config| {
+        let provider = &mut config.model_provider;
+        provider.request_max_retries = Some(0);
+        provider.stream_max_retries = Some(0);
+        provider.circuit_breaker = Some(CircuitBreakerConfig {
+            failure_threshold: 2,
+            window_ms: 60_000,
+            cooldown_ms: cooldown.as_millis() as u64,
+        });
+    })
+    .await;
+    let codex = test.codex;
+
+    let submit = |text: &str| {
+        codex.submit(Op::UserInput {
+            items: vec![InputItem::Text { text: text.into() }],
+        })
+    };
+
+    // Two failing turns open the circuit.
+    for text in ["one", "two"] {
+        submit(text).await.unwrap();
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await;
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+    }
+
+    // While open, turns fail fast without reaching the provider.
+    submit("three").await.unwrap();
+    let EventMsg::BackgroundEvent(notice) = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::BackgroundEvent(ev) if ev.message.contains("failing repeatedly"))
+    })
+    .await
+    else {
+        unreachable!()
+    };
+    assert!(notice.message.contains("OpenAI"));
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+    // After the cooldown a probe is let through; its success closes the circuit.
+    tokio::time::sleep(cooldown + Duration::from_millis(100)).await;
+    for text in ["four", "five"] {
+        submit(text).await.unwrap();
+        let ev = wait_for_event(&codex, |ev| {
+            matches!(ev, EventMsg::TaskComplete(_) | EventMsg::Error(_))
+        })
+        .await;
+        assert!(matches!(ev, EventMsg::TaskComplete(_)), "{ev:?}");
+    }
+    assert_eq!(server.received_requests().await.unwrap().len(), 4);
+}
@@ -0,0 +1,69 @@
+#![cfg(unix)]
+#![expect(clippy::expect_used)]
+
+use std::path::Path;
+
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::exec::ExecParams;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_command_env;
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+
+fn write_command_env(dir: &Path, contents: &str) {
+    let codex_dir = dir.join(".codex");
+    std::fs::create_dir_all(&codex_dir).expect("create .codex");
+    std::fs::write(codex_dir.join("command.env"), contents).expect("write command.env");
+}
+
+async fn run_in(cwd: &Path, script: &str) -> String {
+    let params = ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(5_000),
+        env: create_command_env(&ShellEnvironmentPolicy::default(), cwd),
+        with_escalated_permissions: None,
+        justification: None,
+        stdin: None,
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+    let output = process_exec_tool_call(params, SandboxType::None, &policy, &None, None)
+        .await
+        .expect("exec should succeed");
+    assert_eq!(output.exit_code, 0);
+    output.stdout.text
+}
+
+#[tokio::test]
+async fn command_env_file_skips_reserved_and_secret_names() {
+    let cwd = TempDir::new().expect("tempdir");
+    write_command_env(
+        cwd.path(),
+        "CODEX_TEST_PROJECT_FLAVOR=staging\nPROJECT_DB_PASSWORD_TOKEN=hunter2\n",
+    );
+
+    let stdout = run_in(
+        cwd.path(),
+        "printf '%s|%s' \"$CODEX_TEST_PROJECT_FLAVOR\" \"$PROJECT_DB_PASSWORD_TOKEN\"",
+    )
+    .await;
+
+    // `CODEX_*` names are reserved for Codex and secrets are filtered out.
+    assert_eq!(stdout, "|");
+    assert!(std::env::var_os("CODEX_TEST_PROJECT_FLAVOR").is_none());
+}
+
+#[tokio::test]
+async fn command_env_file_variables_are_not_set_in_codex_process() {
+    let cwd = TempDir::new().expect("tempdir");
+    write_command_env(cwd.path(), "PROJECT_COMMAND_ENV_FLAVOR=staging\n");
+
+    let stdout = run_in(cwd.path(), "printf '%s' \"$PROJECT_COMMAND_ENV_FLAVOR\"").await;
+
+    assert_eq!(stdout, "staging");
+    assert!(
+        std::env::var_os("PROJECT_COMMAND_ENV_FLAVOR").is_none(),
+        "command env file must not leak into Codex's own environment"
+    );
+}
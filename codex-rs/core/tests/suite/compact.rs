@@ -249,3 +249,65 @@ async fn summarize_context_three_requests_and_instructions() {
         "third request should not include the summarize trigger"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn compact_emits_compact_complete_event() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let summary_sse = sse(vec![
+        ev_assistant_message("m1", SUMMARY_TEXT),
+        serde_json::json!({
+            "type": "response.completed",
+            "response": {
+                "id": "r1",
+                "usage": {"input_tokens":1200,"input_tokens_details":null,"output_tokens":200,"output_tokens_details":null,"total_tokens":1400}
+            }
+        }),
+    ]);
+    let summarize_matcher = |req: &wiremock::Request| {
+        let body = std::str::from_utf8(&req.body).unwrap_or("");
+        body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+    };
+    mount_sse_once(&server, summarize_matcher, summary_sse).await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex.submit(Op::Compact).await.unwrap();
+    let EventMsg::CompactComplete(event) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::CompactComplete(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(event.summary, SUMMARY_TEXT);
+    assert_eq!(event.token_usage_before.total_tokens, 1400);
+    // Compaction does not measure the compacted context.
+    assert!(event.token_usage_after.is_none());
+    assert_eq!(event.tokens_saved(), None);
+
+    // The agent message is still sent for older clients.
+    let EventMsg::AgentMessage(message) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::AgentMessage(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(message.message, "Compact task completed");
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+}
@@ -0,0 +1,138 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::ConversationManager;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::CodexAuth;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::load_default_config_for_test;
+use tempfile::TempDir;
+
+/// Starts a read-only session, asks to switch it to `DangerFullAccess` and
+/// returns the error reported for the switch (if any) along with the
+/// resulting sandbox policy.
+async fn switch_to_danger_full_access(
+    confirm_danger_full_access: bool,
+    danger_full_access_confirmed: bool,
+) -> (Option<String>, SandboxPolicy) {
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.sandbox_policy = SandboxPolicy::new_read_only_policy();
+    config.confirm_danger_full_access = confirm_danger_full_access;
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: None,
+            sandbox_policy: Some(SandboxPolicy::DangerFullAccess),
+            model: None,
+            effort: None,
+            summary: None,
+            danger_full_access_confirmed,
+        })
+        .await
+        .unwrap();
+    codex.submit(Op::GetConfig).await.unwrap();
+
+    let mut error = None;
+    loop {
+        match codex.next_event().await.unwrap().msg {
+            EventMsg::Error(ev) => error = Some(ev.message),
+            EventMsg::ResolvedConfig(resolved) => return (error, resolved.sandbox_policy),
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn unconfirmed_switch_to_danger_full_access_is_rejected() {
+    let (error, sandbox_policy) = switch_to_danger_full_access(true, false).await;
+
+    let error = error.expect("expected an error for the unconfirmed switch");
+    assert!(error.contains("danger-full-access"), "{error}");
+    assert!(error.contains("danger_full_access_confirmed"), "{error}");
+    assert_eq!(sandbox_policy, SandboxPolicy::new_read_only_policy());
+}
+
+#[tokio::test]
+async fn confirmed_switch_to_danger_full_access_is_applied() {
+    let (error, sandbox_policy) = switch_to_danger_full_access(true, true).await;
+
+    assert_eq!(error, None);
+    assert_eq!(sandbox_policy, SandboxPolicy::DangerFullAccess);
+}
+
+#[tokio::test]
+async fn switch_needs_no_confirmation_when_disabled() {
+    let (error, sandbox_policy) = switch_to_danger_full_access(false, false).await;
+
+    assert_eq!(error, None);
+    assert_eq!(sandbox_policy, SandboxPolicy::DangerFullAccess);
+}
+
+/// A per-turn `sandbox_policy` cannot bypass the confirmation either: the
+/// turn is rejected before any task starts.
+#[tokio::test]
+async fn unconfirmed_user_turn_with_danger_full_access_is_rejected() {
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.sandbox_policy = SandboxPolicy::new_read_only_policy();
+    let cwd = config.cwd.clone();
+    let model = config.model.clone();
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+            cwd,
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model,
+            effort: ReasoningEffort::Medium,
+            summary: ReasoningSummary::Auto,
+            include_raw_reasoning: false,
+            allowed_tools: None,
+            max_output_tokens: None,
+            danger_full_access_confirmed: false,
+        })
+        .await
+        .unwrap();
+    codex.submit(Op::GetConfig).await.unwrap();
+
+    let mut error = None;
+    loop {
+        match codex.next_event().await.unwrap().msg {
+            EventMsg::Error(ev) => error = Some(ev.message),
+            EventMsg::TaskStarted(_) => panic!("unconfirmed turn must not start a task"),
+            EventMsg::ResolvedConfig(resolved) => {
+                assert_eq!(
+                    resolved.sandbox_policy,
+                    SandboxPolicy::new_read_only_policy()
+                );
+                break;
+            }
+            _ => {}
+        }
+    }
+    let error = error.expect("expected an error for the unconfirmed turn");
+    assert!(error.contains("danger_full_access_confirmed"), "{error}");
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use codex_core::ConversationManager;
+use codex_core::config_snapshot::REDACTED;
+use codex_core::config_types::McpServerConfig;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn snapshot_reflects_resolved_config_and_redacts_secrets() {
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model = "o3".to_string();
+    config.approval_policy = AskForApproval::OnRequest;
+    config.sandbox_policy = SandboxPolicy::new_read_only_policy();
+    config.model_provider.http_headers = Some(HashMap::from([(
+        "X-Api-Token".to_string(),
+        "super-secret".to_string(),
+    )]));
+    config.mcp_servers = HashMap::from([(
+        "docs".to_string(),
+        McpServerConfig {
+            command: "docs-server".to_string(),
+            args: vec!["--stdio".to_string()],
+            env: Some(HashMap::from([(
+                "DOCS_TOKEN".to_string(),
+                "also-secret".to_string(),
+            )])),
+            startup_timeout_ms: None,
+            request_timeout_ms: None,
+            retry: None,
+        },
+    )]);
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let conversation_id = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation_id;
+
+    let snapshot = conversation_manager
+        .get_conversation_config(conversation_id)
+        .await
+        .unwrap();
+
+    assert_eq!(snapshot.model, "o3");
+    assert_eq!(snapshot.approval_policy, AskForApproval::OnRequest);
+    assert_eq!(
+        snapshot.sandbox_policy,
+        SandboxPolicy::new_read_only_policy()
+    );
+    assert_eq!(
+        snapshot.model_provider.http_headers.get("X-Api-Token"),
+        Some(&REDACTED.to_string())
+    );
+    assert_eq!(
+        snapshot.mcp_servers["docs"].env.get("DOCS_TOKEN"),
+        Some(&REDACTED.to_string())
+    );
+    let serialized = serde_json::to_string(&snapshot).unwrap();
+    assert!(!serialized.contains("secret"), "{serialized}");
+
+    conversation_manager
+        .remove_conversation(conversation_id)
+        .await;
+    assert!(
+        conversation_manager
+            .get_conversation_config(conversation_id)
+            .await
+            .is_none()
+    );
+}
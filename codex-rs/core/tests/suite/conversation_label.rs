@@ -0,0 +1,97 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use codex_core::ConversationManager;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+
+const LABEL: &str = "auth refactor";
+
+/// A label set on a conversation is carried over to its fork and duplicate,
+/// and a session resumed from the fork's rollout keeps it.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn label_survives_fork_and_resume() {
+    let codex_home = TempDir::new().unwrap();
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+
+    let source = conversation_manager
+        .new_conversation(load_default_config_for_test(&codex_home))
+        .await
+        .unwrap();
+    assert_eq!(source.session_configured.label, None);
+    let codex = source.conversation;
+    codex
+        .submit(Op::SetConversationLabel {
+            label: Some(LABEL.to_string()),
+        })
+        .await
+        .unwrap();
+    codex.submit(Op::GetHistory).await.unwrap();
+    let EventMsg::ConversationHistory(history) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ConversationHistory(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(codex.label().as_deref(), Some(LABEL));
+    let summaries = conversation_manager.list_conversations().await;
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].label.as_deref(), Some(LABEL));
+
+    let fork = conversation_manager
+        .fork_conversation_with_label(
+            history.entries.clone(),
+            0,
+            load_default_config_for_test(&codex_home),
+            codex.label(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(fork.session_configured.label.as_deref(), Some(LABEL));
+    assert_eq!(fork.conversation.label().as_deref(), Some(LABEL));
+
+    let duplicate = conversation_manager
+        .duplicate_conversation(source.conversation_id)
+        .await
+        .unwrap();
+    assert_eq!(duplicate.session_configured.label.as_deref(), Some(LABEL));
+
+    // A plain fork only needs the history, even once the source is gone.
+    conversation_manager
+        .remove_conversation(source.conversation_id)
+        .await;
+    let unlabeled = conversation_manager
+        .fork_conversation(
+            history.entries,
+            0,
+            load_default_config_for_test(&codex_home),
+        )
+        .await
+        .unwrap();
+    assert_eq!(unlabeled.session_configured.label, None);
+
+    // Shutting down flushes the fork's rollout.
+    fork.conversation.submit(Op::Shutdown).await.unwrap();
+    wait_for_event(&fork.conversation, |ev| {
+        matches!(ev, EventMsg::ShutdownComplete)
+    })
+    .await;
+    let fork_id = fork.conversation_id.to_string();
+    let rollout = walkdir::WalkDir::new(codex_home.path().join("sessions"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(|entry| entry.into_path())
+        .find(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().contains(&fork_id))
+        })
+        .expect("fork rollout");
+
+    let resumed = conversation_manager
+        .resume_conversation_from_rollout(&rollout, load_default_config_for_test(&codex_home))
+        .await
+        .unwrap();
+    assert_eq!(resumed.session_configured.label.as_deref(), Some(LABEL));
+}
@@ -0,0 +1,102 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses::completed;
+use core_test_support::responses::sse;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Runs one turn against a response that contains reasoning and returns the
+/// request body together with the names of the reasoning events emitted.
+async fn run_turn_with_reasoning(disable_reasoning: bool) -> (Value, Vec<&'static str>) {
+    let server = MockServer::start().await;
+    let body = sse(vec![
+        json!({"type": "response.reasoning_summary_part.added"}),
+        json!({"type": "response.reasoning_summary_text.delta", "delta": "thinking"}),
+        json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "reasoning",
+                "id": "rs_1",
+                "summary": [{"type": "summary_text", "text": "thinking"}]
+            }
+        }),
+        completed("r1"),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(body, "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let test = start_mock_conversation(&server, |config| {
+        config.model = "gpt-5".to_string();
+        config.model_family = codex_core::model_family::find_family_for_model("gpt-5").unwrap();
+        // Without stored responses, reasoning would also request encrypted content.
+        config.disable_response_storage = true;
+        config.disable_reasoning = disable_reasoning;
+    })
+    .await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let mut reasoning_events = Vec::new();
+    wait_for_event(&codex, |ev| {
+        match ev {
+            EventMsg::AgentReasoning(_) => reasoning_events.push("agent_reasoning"),
+            EventMsg::AgentReasoningDelta(_) => reasoning_events.push("agent_reasoning_delta"),
+            EventMsg::AgentReasoningSectionBreak(_) => {
+                reasoning_events.push("agent_reasoning_section_break")
+            }
+            EventMsg::AgentReasoningRawContent(_) | EventMsg::AgentReasoningRawContentDelta(_) => {
+                reasoning_events.push("agent_reasoning_raw_content")
+            }
+            _ => {}
+        }
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let request = &server.received_requests().await.unwrap()[0];
+    (request.body_json::<Value>().unwrap(), reasoning_events)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reasoning_is_requested_and_reported_by_default() {
+    let (body, reasoning_events) = run_turn_with_reasoning(false).await;
+
+    assert!(body["reasoning"].is_object(), "body: {body}");
+    assert_eq!(body["include"], json!(["reasoning.encrypted_content"]));
+    assert!(!reasoning_events.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn disable_reasoning_omits_parameters_and_events() {
+    let (body, reasoning_events) = run_turn_with_reasoning(true).await;
+
+    assert_eq!(body["reasoning"], Value::Null, "body: {body}");
+    assert_eq!(body["include"], json!([]));
+    assert_eq!(reasoning_events, Vec::<&str>::new());
+}
@@ -0,0 +1,116 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::CodexConversation;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use codex_protocol::models::ResponseItem;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+async fn send_turn(codex: &CodexConversation, text: &str) {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: text.to_string(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+}
+
+async fn history(codex: &CodexConversation) -> Vec<ResponseItem> {
+    codex.submit(Op::GetHistory).await.unwrap();
+    let EventMsg::ConversationHistory(history) =
+        wait_for_event(codex, |ev| matches!(ev, EventMsg::ConversationHistory(_))).await
+    else {
+        unreachable!()
+    };
+    history.entries
+}
+
+/// A duplicate starts from the source's history and in-flight overrides under
+/// a new id, and new turns in the duplicate leave the source untouched.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn duplicate_conversation_copies_history_and_diverges_independently() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.approval_policy = AskForApproval::OnRequest;
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let source = conversation_manager.new_conversation(config).await.unwrap();
+
+    source
+        .conversation
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+            danger_full_access_confirmed: false,
+        })
+        .await
+        .unwrap();
+    send_turn(&source.conversation, "first").await;
+    let source_history = history(&source.conversation).await;
+
+    let duplicate = conversation_manager
+        .duplicate_conversation(source.conversation_id)
+        .await
+        .unwrap();
+    assert_ne!(duplicate.conversation_id, source.conversation_id);
+    assert_eq!(
+        duplicate.conversation.config_snapshot().approval_policy,
+        AskForApproval::Never
+    );
+    let duplicate_history = history(&duplicate.conversation).await;
+    assert_eq!(
+        duplicate_history[..source_history.len()],
+        source_history[..]
+    );
+
+    send_turn(&duplicate.conversation, "second").await;
+    assert!(history(&duplicate.conversation).await.len() > duplicate_history.len());
+    assert_eq!(history(&source.conversation).await, source_history);
+}
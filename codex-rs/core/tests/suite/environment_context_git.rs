@@ -0,0 +1,134 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::path::Path;
+use std::process::Command;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const BRANCH: &str = "sensitive-branch-name";
+const REMOTE_URL: &str = "https://example.com/sensitive/repo.git";
+
+fn git(repo: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .envs([
+            ("GIT_CONFIG_GLOBAL", "/dev/null"),
+            ("GIT_CONFIG_NOSYSTEM", "1"),
+        ])
+        .args(args)
+        .current_dir(repo)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn init_repo_with_remote(repo: &Path) {
+    git(repo, &["init"]);
+    git(repo, &["config", "user.name", "Test User"]);
+    git(repo, &["config", "user.email", "test@example.com"]);
+    std::fs::write(repo.join("README.md"), "hello\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "initial"]);
+    git(repo, &["checkout", "-b", BRANCH]);
+    git(repo, &["remote", "add", "origin", REMOTE_URL]);
+}
+
+/// Runs a single turn in a git repository and returns the serialized
+/// `<environment_context>` message that was sent to the model.
+async fn environment_context_text(include_git_context: bool) -> String {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let repo = TempDir::new().unwrap();
+    init_repo_with_remote(repo.path());
+
+    let codex_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&codex_home);
+    config.cwd = repo.path().to_path_buf();
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.include_git_context = include_git_context;
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation")
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    let body = requests[0].body_json::<serde_json::Value>().unwrap();
+    body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .find(|text| text.starts_with("<environment_context>"))
+        .expect("environment context message")
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn environment_context_includes_git_summary_by_default() {
+    let text = environment_context_text(true).await;
+
+    assert!(
+        text.contains(&format!("<git_branch>{BRANCH}</git_branch>")),
+        "{text}"
+    );
+    assert!(
+        text.contains(&format!("<git_remote>{REMOTE_URL}</git_remote>")),
+        "{text}"
+    );
+    assert!(text.contains("<git_commit>"), "{text}");
+    // No upstream is tracked, so ahead/behind are omitted.
+    assert!(text.contains("<git_dirty>false</git_dirty>"), "{text}");
+    assert!(!text.contains("<git_ahead>"), "{text}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn environment_context_omits_git_summary_when_disabled() {
+    let text = environment_context_text(false).await;
+
+    assert!(!text.contains("<git_"), "{text}");
+    assert!(!text.contains(BRANCH), "{text}");
+    assert!(!text.contains(REMOTE_URL), "{text}");
+}
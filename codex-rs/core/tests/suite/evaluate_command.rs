@@ -0,0 +1,67 @@
+#![expect(clippy::unwrap_used)]
+
+use std::path::PathBuf;
+
+use codex_core::ConversationManager;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::CommandEvaluationEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SafetyAssessment;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+
+async fn evaluate(command: &[&str]) -> CommandEvaluationEvent {
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.approval_policy = AskForApproval::UnlessTrusted;
+    config.sandbox_policy = SandboxPolicy::new_read_only_policy();
+    let session_cwd = config.cwd.clone();
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::EvaluateCommand {
+            command: command.iter().map(|s| s.to_string()).collect(),
+            cwd: PathBuf::from("sub"),
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::CommandEvaluation(evaluation) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::CommandEvaluation(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(evaluation.cwd, session_cwd.join("sub"));
+    evaluation
+}
+
+#[tokio::test]
+async fn read_only_command_is_auto_approved() {
+    let evaluation = evaluate(&["cat", "README.md"]).await;
+
+    assert_eq!(
+        evaluation.command,
+        vec!["cat".to_string(), "README.md".to_string()]
+    );
+    assert_eq!(
+        evaluation.assessment,
+        SafetyAssessment::AutoApprove { sandboxed: false }
+    );
+}
+
+#[tokio::test]
+async fn destructive_command_prompts_user() {
+    let evaluation = evaluate(&["rm", "-rf", "build"]).await;
+
+    assert_eq!(evaluation.assessment, SafetyAssessment::AskUser);
+}
@@ -0,0 +1,42 @@
+#![cfg(target_os = "linux")]
+
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::exec::ExecParams;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_command_env;
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+
+const ALLOCATED_BYTES: u64 = 32 * 1024 * 1024;
+
+/// The shell holds a 32 MiB string in memory, so its peak RSS must be at
+/// least that large.
+#[tokio::test]
+async fn exec_reports_peak_rss_of_memory_hungry_command() {
+    let cwd = TempDir::new().expect("tempdir");
+    let script = format!("x=$(head -c {ALLOCATED_BYTES} /dev/zero | tr '\\0' a); echo ${{#x}}");
+    let params = ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script],
+        cwd: cwd.path().to_path_buf(),
+        timeout_ms: Some(30_000),
+        env: create_command_env(&ShellEnvironmentPolicy::default(), cwd.path()),
+        with_escalated_permissions: None,
+        justification: None,
+        stdin: None,
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let output = process_exec_tool_call(params, SandboxType::None, &policy, &None, None)
+        .await
+        .expect("exec should succeed");
+
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout.text.trim(), ALLOCATED_BYTES.to_string());
+    let peak = output
+        .peak_rss_bytes
+        .expect("peak RSS is measured on Linux");
+    assert!(peak >= ALLOCATED_BYTES, "implausible peak RSS: {peak}");
+    // Sanity bound so a unit mix-up (e.g. double-scaling kilobytes) fails.
+    assert!(peak < 64 * ALLOCATED_BYTES, "implausible peak RSS: {peak}");
+}
@@ -2,14 +2,18 @@
 
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use async_channel::Receiver;
+use async_channel::Sender;
 use codex_core::exec::ExecParams;
 use codex_core::exec::SandboxType;
 use codex_core::exec::StdoutStream;
 use codex_core::exec::process_exec_tool_call;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecCommandBeginEvent;
+use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::ExecCommandOutputDeltaEvent;
 use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::SandboxPolicy;
@@ -37,6 +41,8 @@ async fn test_exec_stdout_stream_events_echo() {
         sub_id: "test-sub".to_string(),
         call_id: "call-1".to_string(),
         tx_event: tx,
+        max_deltas_per_second: None,
+        apply_patch_cwd: None,
     };
 
     let cmd = vec![
@@ -53,6 +59,7 @@ async fn test_exec_stdout_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        stdin: None,
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -87,6 +94,8 @@ async fn test_exec_stderr_stream_events_echo() {
         sub_id: "test-sub".to_string(),
         call_id: "call-2".to_string(),
         tx_event: tx,
+        max_deltas_per_second: None,
+        apply_patch_cwd: None,
     };
 
     let cmd = vec![
@@ -103,6 +112,7 @@ async fn test_exec_stderr_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        stdin: None,
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -156,6 +166,7 @@ async fn test_aggregated_output_interleaves_in_order() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        stdin: None,
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -170,3 +181,191 @@ async fn test_aggregated_output_interleaves_in_order() {
     assert_eq!(result.aggregated_output.text, "O1\nE1\nO2\nE2\n");
     assert_eq!(result.aggregated_output.truncated_after_lines, None);
 }
+
+#[tokio::test]
+async fn test_exec_output_deltas_are_rate_limited_and_coalesced() {
+    let (tx, rx) = async_channel::unbounded::<Event>();
+
+    let stdout_stream = StdoutStream {
+        sub_id: "test-sub".to_string(),
+        call_id: "call-3".to_string(),
+        tx_event: tx,
+        max_deltas_per_second: Some(2),
+        apply_patch_cwd: None,
+    };
+
+    // A burst of small writes, each of which would otherwise be its own delta.
+    let cmd = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "for i in 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20; do printf \"line$i\\n\"; sleep 0.01; done".to_string(),
+    ];
+
+    let params = ExecParams {
+        command: cmd,
+        cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        timeout_ms: Some(5_000),
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+        stdin: None,
+    };
+
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::None,
+        &policy,
+        &None,
+        Some(stdout_stream),
+    )
+    .await
+    .expect("process_exec_tool_call");
+
+    let expected: String = (1..=20).map(|i| format!("line{i}\n")).collect();
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(result.stdout.text, expected);
+    assert_eq!(result.aggregated_output.text, expected);
+
+    let mut deltas = 0;
+    let mut streamed = Vec::new();
+    while let Ok(ev) = rx.try_recv() {
+        if let EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent { chunk, .. }) = ev.msg
+        {
+            deltas += 1;
+            streamed.extend_from_slice(&chunk);
+        }
+    }
+    // ~200ms of output at 2 events/s: the first write, then at most one
+    // coalesced flush and the final flush at EOF.
+    assert!(deltas <= 3, "expected coalesced deltas, got {deltas}");
+    assert_eq!(String::from_utf8_lossy(&streamed), expected);
+}
+
+/// Mirrors how the session wraps an exec call: a begin event, the streamed
+/// output and an end event, all on one shared channel. If `budget` elapses the
+/// exec future is dropped, like a cancelled tool call.
+async fn run_with_events(tx: Sender<Event>, call_id: &str, script: &str, budget: Duration) {
+    let command = vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()];
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let send = |msg| {
+        tx.send(Event {
+            id: "test-sub".to_string(),
+            msg,
+        })
+    };
+
+    let begin = send(EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+        call_id: call_id.to_string(),
+        command: command.clone(),
+        cwd: cwd.clone(),
+        parsed_cmd: Vec::new(),
+    }));
+    assert!(begin.await.is_ok());
+
+    let params = ExecParams {
+        command,
+        cwd,
+        timeout_ms: Some(10_000),
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+        stdin: None,
+    };
+    let stdout_stream = StdoutStream {
+        sub_id: "test-sub".to_string(),
+        call_id: call_id.to_string(),
+        tx_event: tx.clone(),
+        max_deltas_per_second: None,
+        apply_patch_cwd: None,
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+    let exec = process_exec_tool_call(
+        params,
+        SandboxType::None,
+        &policy,
+        &None,
+        Some(stdout_stream),
+    );
+    let exit_code = match tokio::time::timeout(budget, exec).await {
+        Ok(Ok(output)) => output.exit_code,
+        Ok(Err(e)) => panic!("process_exec_tool_call failed: {e}"),
+        Err(_) => -1,
+    };
+
+    let end = send(EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+        call_id: call_id.to_string(),
+        stdout: String::new(),
+        stderr: String::new(),
+        aggregated_output: String::new(),
+        exit_code,
+        duration: Duration::ZERO,
+        timed_out: false,
+        formatted_output: String::new(),
+        peak_rss_bytes: None,
+    }));
+    assert!(end.await.is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_exec_events_are_ordered_per_call_when_interleaved() {
+    let (tx, rx) = async_channel::unbounded::<Event>();
+
+    tokio::join!(
+        run_with_events(
+            tx.clone(),
+            "call-a",
+            "for i in 1 2 3 4 5; do echo a$i; sleep 0.05; done",
+            Duration::from_secs(5),
+        ),
+        run_with_events(
+            tx.clone(),
+            "call-b",
+            "for i in 1 2 3 4 5; do echo b$i 1>&2; sleep 0.05; done",
+            Duration::from_secs(5),
+        ),
+        // Never exits on its own: cancelled while still producing output.
+        run_with_events(
+            tx.clone(),
+            "call-c",
+            "while true; do echo c; sleep 0.01; done",
+            Duration::from_millis(150),
+        ),
+    );
+    // Give any straggling output of the cancelled call a chance to show up.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(tx);
+
+    // (call_id, phase) with phase 0 = begin, 1 = delta, 2 = end.
+    let mut sequence: Vec<(String, u8)> = Vec::new();
+    while let Ok(ev) = rx.try_recv() {
+        match ev.msg {
+            EventMsg::ExecCommandBegin(ev) => sequence.push((ev.call_id, 0)),
+            EventMsg::ExecCommandOutputDelta(ev) => sequence.push((ev.call_id, 1)),
+            EventMsg::ExecCommandEnd(ev) => sequence.push((ev.call_id, 2)),
+            _ => {}
+        }
+    }
+
+    // The calls' deltas really do interleave globally.
+    let delta_calls: Vec<&str> = sequence
+        .iter()
+        .filter(|(_, phase)| *phase == 1)
+        .map(|(call_id, _)| call_id.as_str())
+        .collect();
+    let switches = delta_calls.windows(2).filter(|w| w[0] != w[1]).count();
+    assert!(switches > 2, "expected interleaved deltas: {delta_calls:?}");
+
+    for call_id in ["call-a", "call-b", "call-c"] {
+        let phases: Vec<u8> = sequence
+            .iter()
+            .filter(|(id, _)| id == call_id)
+            .map(|(_, phase)| *phase)
+            .collect();
+        assert_eq!(phases.first(), Some(&0), "{call_id}: {phases:?}");
+        assert_eq!(phases.last(), Some(&2), "{call_id}: {phases:?}");
+        assert_eq!(phases.iter().filter(|p| **p != 1).count(), 2);
+        assert!(phases.contains(&1), "{call_id} produced no deltas");
+    }
+}
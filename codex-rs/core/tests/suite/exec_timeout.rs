@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::error::CodexErr;
+use codex_core::exec::ExecParams;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_command_env;
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+
+fn shell_params(cwd: &TempDir, script: &str, timeout_ms: u64) -> ExecParams {
+    ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.path().to_path_buf(),
+        timeout_ms: Some(timeout_ms),
+        env: create_command_env(&ShellEnvironmentPolicy::default(), cwd.path()),
+        with_escalated_permissions: None,
+        justification: None,
+        stdin: None,
+    }
+}
+
+#[tokio::test]
+async fn command_finishing_before_timeout_succeeds() {
+    let cwd = TempDir::new().expect("tempdir");
+    let params = shell_params(&cwd, "sleep 0.1; echo done", 10_000);
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let output = process_exec_tool_call(params, SandboxType::None, &policy, &None, None)
+        .await
+        .expect("exec should succeed");
+
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout.text, "done\n");
+}
+
+/// The background `sleep` keeps the output pipe open, so the call can only
+/// return promptly if the whole process group is killed.
+#[tokio::test]
+async fn command_exceeding_timeout_is_killed_with_its_process_group() {
+    let cwd = TempDir::new().expect("tempdir");
+    let params = shell_params(&cwd, "sleep 30 & sleep 30", 300);
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let start = Instant::now();
+    let result = process_exec_tool_call(params, SandboxType::None, &policy, &None, None).await;
+
+    match result {
+        Err(CodexErr::ExecTimeout(timeout)) => assert_eq!(timeout, Duration::from_millis(300)),
+        other => panic!("expected ExecTimeout, got {other:?}"),
+    }
+    assert!(
+        start.elapsed() < Duration::from_secs(10),
+        "timed-out command took {:?} to return",
+        start.elapsed()
+    );
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const EXPERIMENT_HEADER: &str = "x-gateway-experiment";
+
+/// An experiment header set mid-session is sent with the next request, and
+/// clearing it removes it again.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn experiment_headers_can_be_set_and_cleared() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().expect("tempdir");
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation;
+
+    let send_turn = async |experiment: Option<&str>| {
+        codex
+            .submit(Op::SetExperimentHeaders {
+                headers: HashMap::from([(
+                    EXPERIMENT_HEADER.to_string(),
+                    experiment.map(str::to_string),
+                )]),
+            })
+            .await
+            .expect("set experiment headers");
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text {
+                    text: "hello".into(),
+                }],
+            })
+            .await
+            .expect("submit input");
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    };
+    send_turn(Some("fast-path")).await;
+    send_turn(None).await;
+
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[0]
+            .headers
+            .get(EXPERIMENT_HEADER)
+            .map(|value| value.to_str().expect("ascii header")),
+        Some("fast-path")
+    );
+    assert!(requests[1].headers.get(EXPERIMENT_HEADER).is_none());
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use codex_core::ConversationManager;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn get_config_reports_resolved_values_without_secrets() {
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model = "o3".to_string();
+    config.approval_policy = AskForApproval::OnRequest;
+    config.sandbox_policy = SandboxPolicy::new_read_only_policy();
+    config.model_provider.http_headers = Some(HashMap::from([(
+        "X-Api-Token".to_string(),
+        "super-secret".to_string(),
+    )]));
+    let cwd = config.cwd.clone();
+
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("api-key-secret"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    // Overrides applied after spawn are reflected in the reply.
+    codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+            danger_full_access_confirmed: false,
+        })
+        .await
+        .unwrap();
+    codex.submit(Op::GetConfig).await.unwrap();
+
+    let EventMsg::ResolvedConfig(resolved) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ResolvedConfig(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(resolved.model, "o3");
+    assert_eq!(resolved.model_provider_id, "openai");
+    assert_eq!(resolved.cwd, cwd);
+    assert_eq!(resolved.approval_policy, AskForApproval::Never);
+    assert_eq!(
+        resolved.sandbox_policy,
+        SandboxPolicy::new_read_only_policy()
+    );
+    assert!(resolved.tools.iter().any(|tool| tool == "shell"));
+
+    let serialized = serde_json::to_string(&resolved).unwrap();
+    for secret in ["super-secret", "api-key-secret"] {
+        assert!(
+            !serialized.contains(secret),
+            "`{secret}` leaked into {serialized}"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::CodexConversation;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::DiffScope;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+fn apply_patch_call(call_id: &str, file: &str) -> String {
+    let patch =
+        format!("*** Begin Patch\n*** Update File: {file}\n@@\n-before\n+after\n*** End Patch");
+    sse(vec![
+        json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "function_call",
+                "name": "apply_patch",
+                "arguments": json!({ "input": patch }).to_string(),
+                "call_id": call_id
+            }
+        }),
+        completed(call_id),
+    ])
+}
+
+async fn run_task(codex: &CodexConversation, text: &str) {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text { text: text.into() }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+}
+
+async fn get_diff(codex: &CodexConversation, scope: DiffScope) -> String {
+    codex.submit(Op::GetDiff { scope }).await.unwrap();
+    let EventMsg::GetDiffResponse(response) =
+        wait_for_event(codex, |ev| matches!(ev, EventMsg::GetDiffResponse(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(response.scope, scope);
+    response.unified_diff
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_diff_reports_current_turn_and_cumulative_changes() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    mount_once(&server, apply_patch_call("call-a", "a.txt")).await;
+    mount_once(&server, sse(vec![completed("r1")])).await;
+    mount_once(&server, apply_patch_call("call-b", "b.txt")).await;
+    mount_once(&server, sse(vec![completed("r2")])).await;
+
+    let workspace = TempDir::new().unwrap();
+    let a = workspace.path().join("a.txt");
+    let b = workspace.path().join("b.txt");
+    std::fs::write(&a, "before\n").unwrap();
+    std::fs::write(&b, "before\n").unwrap();
+
+    let test = start_mock_conversation(&server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    })
+    .await;
+    let codex = test.codex;
+
+    assert_eq!(get_diff(&codex, DiffScope::Cumulative).await, "");
+
+    // The tracker snapshots each file when the patch begins and diffs it
+    // against what is on disk, so edit the files directly rather than relying
+    // on the patch subprocess.
+    run_task(&codex, "edit a").await;
+    std::fs::write(&a, "after\n").unwrap();
+    let first = get_diff(&codex, DiffScope::CurrentTurn).await;
+    assert!(
+        first.contains("a.txt") && first.contains("+after"),
+        "{first}"
+    );
+
+    run_task(&codex, "edit b").await;
+    std::fs::write(&b, "after\n").unwrap();
+    let current = get_diff(&codex, DiffScope::CurrentTurn).await;
+    assert!(current.contains("b.txt"), "{current}");
+    assert!(!current.contains("a.txt"), "{current}");
+
+    let cumulative = get_diff(&codex, DiffScope::Cumulative).await;
+    assert!(
+        cumulative.contains("a.txt") && cumulative.contains("b.txt"),
+        "{cumulative}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_diff_omits_whitespace_only_changes_when_configured() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    mount_once(&server, apply_patch_call("call-a", "a.txt")).await;
+    mount_once(&server, sse(vec![completed("r1")])).await;
+
+    let workspace = TempDir::new().unwrap();
+    let a = workspace.path().join("a.txt");
+    std::fs::write(&a, "before\n").unwrap();
+
+    let test = start_mock_conversation(&server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        config.diff_ignore_whitespace = true;
+    })
+    .await;
+    let codex = test.codex;
+
+    run_task(&codex, "edit a").await;
+    std::fs::write(&a, "    before\n").unwrap();
+    assert_eq!(get_diff(&codex, DiffScope::CurrentTurn).await, "");
+    assert_eq!(get_diff(&codex, DiffScope::Cumulative).await, "");
+
+    std::fs::write(&a, "    after\n").unwrap();
+    let diff = get_diff(&codex, DiffScope::CurrentTurn).await;
+    assert!(diff.contains("+    after"), "{diff}");
+}
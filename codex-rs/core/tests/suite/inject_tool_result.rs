@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use codex_core::error::CodexErr;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_protocol::models::FunctionCallOutputPayload;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::MockServer;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn injected_result_unblocks_stubbed_tool_call() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(vec![
+            json!({
+                "type": "response.output_item.done",
+                "item": {
+                    "type": "function_call",
+                    "name": "shell",
+                    "arguments": json!({ "command": ["false"] }).to_string(),
+                    "call_id": "call-1"
+                }
+            }),
+            completed("r1"),
+        ]),
+    )
+    .await;
+    mount_once(&server, sse(vec![completed("r2")])).await;
+
+    let test = start_mock_conversation(&server, |config| {
+        config.approval_policy = AskForApproval::Never;
+        config.stub_tool_calls = true;
+    })
+    .await;
+    let codex = test.codex;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "run it".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    // The turn makes the call and then waits: no follow-up request is sent.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while server.received_requests().await.unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    codex
+        .inject_tool_result(
+            "call-1".to_string(),
+            FunctionCallOutputPayload {
+                content: "stubbed output".to_string(),
+                success: Some(true),
+            },
+        )
+        .unwrap();
+
+    loop {
+        match codex.next_event().await.unwrap().msg {
+            EventMsg::ExecCommandBegin(ev) => panic!("stubbed tool was executed: {ev:?}"),
+            EventMsg::TaskComplete(_) => break,
+            _ => {}
+        }
+    }
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let body = requests[1].body_json::<Value>().unwrap();
+    let output = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["type"] == "function_call_output")
+        .expect("function_call_output in follow-up request");
+    assert_eq!(output["call_id"], "call-1");
+    assert_eq!(output["output"], "stubbed output");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn injection_is_rejected_unless_tool_calls_are_stubbed() {
+    let server = MockServer::start().await;
+    let test = start_mock_conversation(&server, |config| {
+        config.approval_policy = AskForApproval::Never;
+        config.stub_tool_calls = false;
+    })
+    .await;
+    let codex = test.codex;
+
+    let err = codex
+        .inject_tool_result(
+            "call-1".to_string(),
+            FunctionCallOutputPayload {
+                content: "stubbed output".to_string(),
+                success: Some(true),
+            },
+        )
+        .unwrap_err();
+
+    assert!(
+        matches!(err, CodexErr::ToolResultInjectionDisabled),
+        "{err:?}"
+    );
+}
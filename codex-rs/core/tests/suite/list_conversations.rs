@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+
+use codex_core::ConversationManager;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn list_conversations_returns_every_live_conversation() {
+    let home = TempDir::new().unwrap();
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+
+    let mut config = load_default_config_for_test(&home);
+    config.model = "o3".to_string();
+    let first = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation_id;
+
+    let mut config = load_default_config_for_test(&home);
+    config.model = "gpt-5".to_string();
+    let second = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation_id;
+
+    let summaries = conversation_manager.list_conversations().await;
+    assert_eq!(summaries.len(), 2);
+    let ids: HashSet<_> = summaries.iter().map(|s| s.conversation_id).collect();
+    assert_eq!(ids, HashSet::from([first, second]));
+    for summary in &summaries {
+        let expected_model = if summary.conversation_id == first {
+            "o3"
+        } else {
+            "gpt-5"
+        };
+        assert_eq!(summary.model, expected_model);
+        assert!(summary.token_usage.is_none());
+    }
+
+    conversation_manager.remove_conversation(first).await;
+    let summaries = conversation_manager.list_conversations().await;
+    assert_eq!(
+        summaries
+            .iter()
+            .map(|s| s.conversation_id)
+            .collect::<Vec<_>>(),
+        vec![second]
+    );
+}
@@ -0,0 +1,115 @@
+#![expect(clippy::unwrap_used)]
+
+use std::time::Duration;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BackgroundEventLevel;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// A tool call keeps the task going for a second request, which picks up the
+/// queued input.
+const SSE_TOOL_CALL: &str = r#"[
+  {"type": "response.output_item.done", "item": {
+    "type": "function_call",
+    "name": "no_such_tool",
+    "arguments": "{}",
+    "call_id": "call-1"
+  }},
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+const SSE_COMPLETED: &str = r#"[
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+async fn submit_text(codex: &codex_core::CodexConversation, text: &str) {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text { text: text.into() }],
+        })
+        .await
+        .unwrap();
+}
+
+/// With `max_queued_inputs = 1`, a second input submitted while the task is
+/// busy is rejected with a warning; the first queued one is still processed.
+#[tokio::test]
+async fn inputs_beyond_max_queued_inputs_are_rejected() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(SSE_TOOL_CALL, "r1"),
+                    "text/event-stream",
+                )
+                .set_delay(Duration::from_millis(500)),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(SSE_COMPLETED, "r2"),
+                    "text/event-stream",
+                ),
+        )
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.max_queued_inputs = Some(1);
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    submit_text(&codex, "first input").await;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskStarted(_))).await;
+    submit_text(&codex, "queued input").await;
+    submit_text(&codex, "overflow input").await;
+
+    let EventMsg::BackgroundEvent(BackgroundEventEvent { message, level }) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::BackgroundEvent(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(level, BackgroundEventLevel::Warn);
+    assert!(message.contains("input rejected"), "{message}");
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let second = requests[1].body_json::<Value>().unwrap().to_string();
+    assert!(second.contains("queued input"), "{second}");
+    assert!(!second.contains("overflow input"), "{second}");
+}
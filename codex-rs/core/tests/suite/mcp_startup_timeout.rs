@@ -0,0 +1,91 @@
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::config_types::McpServerConfig;
+use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// A configured MCP server that never answers `initialize` must not block the
+/// session: it is reported via a background event once its startup timeout
+/// elapses and the conversation keeps working without it.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unresponsive_mcp_server_times_out_and_session_continues() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let codex_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.mcp_servers = HashMap::from([(
+        "hung".to_string(),
+        McpServerConfig {
+            command: "sleep".to_string(),
+            args: vec!["30".to_string()],
+            env: None,
+            startup_timeout_ms: Some(300),
+            request_timeout_ms: None,
+            retry: None,
+        },
+    )]);
+
+    let start = Instant::now();
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation")
+        .conversation;
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    let EventMsg::BackgroundEvent(BackgroundEventEvent { message, .. }) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::BackgroundEvent(_))).await
+    else {
+        unreachable!()
+    };
+    assert!(message.contains("`hung`"), "{message}");
+    assert!(message.contains("300 ms"), "{message}");
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+}
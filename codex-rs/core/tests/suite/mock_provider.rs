@@ -0,0 +1,100 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::CodexConversation;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use tempfile::TempDir;
+
+/// Submits `text` and returns the agent messages and finished commands of
+/// the resulting task.
+async fn run_task(codex: &CodexConversation, text: &str) -> (Vec<String>, Vec<String>) {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text { text: text.into() }],
+        })
+        .await
+        .unwrap();
+    let mut messages = Vec::new();
+    let mut command_output = Vec::new();
+    loop {
+        match wait_for_event(codex, |_| true).await {
+            EventMsg::AgentMessage(ev) => messages.push(ev.message),
+            EventMsg::ExecCommandEnd(ev) => command_output.push(ev.stdout),
+            EventMsg::Error(ev) => panic!("unexpected error: {}", ev.message),
+            EventMsg::TaskComplete(_) => return (messages, command_output),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn scripted_conversation_runs_offline() {
+    let home = TempDir::new().unwrap();
+    let script = home.path().join("demo.jsonl");
+    let responses = [
+        json!([{
+            "type": "function_call",
+            "name": "shell",
+            "arguments": json!({ "command": ["echo", "hello from the mock"] }).to_string(),
+            "call_id": "call-1"
+        }]),
+        json!([{
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "output_text", "text": "I ran echo for you." }]
+        }]),
+        json!([{
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "output_text", "text": "Second turn." }]
+        }]),
+    ];
+    let lines: Vec<String> = responses.iter().map(ToString::to_string).collect();
+    std::fs::write(&script, lines.join("\n")).unwrap();
+
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        name: "Mock".to_string(),
+        wire_api: WireApi::Mock,
+        mock_script: Some(script),
+        stream_usage: false,
+        // Nothing listens here: any real request would fail the test.
+        base_url: Some("http://127.0.0.1:9".to_string()),
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    let (messages, command_output) = run_task(&codex, "say hello").await;
+    assert_eq!(command_output, vec!["hello from the mock\n".to_string()]);
+    assert_eq!(messages, vec!["I ran echo for you.".to_string()]);
+
+    let (messages, _) = run_task(&codex, "again").await;
+    assert_eq!(messages, vec!["Second turn.".to_string()]);
+
+    let (messages, _) = run_task(&codex, "and again").await;
+    assert_eq!(
+        messages,
+        vec!["The mock script has no more responses.".to_string()]
+    );
+}
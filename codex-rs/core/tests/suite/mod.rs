@@ -1,12 +1,57 @@
 // Aggregates all former standalone integration tests as modules.
 
+mod allowed_tools;
+mod apply_patch_command;
+mod apply_patch_conflicts;
+mod apply_patch_dry_run;
+mod cancel_tool_call;
+mod chat_instructions_role;
+mod circuit_breaker;
 mod cli_stream;
 mod client;
+mod command_env_file;
 mod compact;
+mod confirm_danger_full_access;
+mod conversation_config_snapshot;
+mod conversation_label;
+mod disable_reasoning;
+mod duplicate_conversation;
+mod environment_context_git;
+mod evaluate_command;
 mod exec;
+mod exec_peak_rss;
 mod exec_stream_events;
+mod exec_timeout;
+mod experiment_headers;
+mod get_config;
+mod get_diff;
+mod inject_tool_result;
+mod list_conversations;
 mod live_cli;
+mod malformed_tool_arguments;
+mod max_output_tokens;
+mod max_queued_inputs;
+mod mcp_startup_timeout;
+mod mock_provider;
+mod model_fallback;
+mod model_overrides;
+mod patch_apply_progress;
+mod pause_events;
+mod project_summary;
 mod prompt_caching;
+mod provider_health;
+mod raw_reasoning;
+mod resume_from_rollout;
+mod retry_last_turn;
+mod save_session_as;
 mod seatbelt;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod stream_usage;
+mod task_complete_outputs;
+mod turn_time_budget;
+mod turn_timing;
+mod unsandboxed_audit_log;
+mod unsandboxed_retry;
+mod user_turn_preamble;
+mod web_search_results;
@@ -0,0 +1,86 @@
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses::completed;
+use core_test_support::responses::sse;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::body_partial_json;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn falls_back_to_next_model_when_primary_is_unavailable() {
+    let server = MockServer::start().await;
+
+    // The primary model is rejected only once: later turns must go straight
+    // to the fallback.
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(body_partial_json(json!({ "model": "primary-model" })))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "error": {
+                "message": "The model `primary-model` does not exist",
+                "type": "invalid_request_error",
+                "code": "model_not_found"
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .and(body_partial_json(json!({ "model": "fallback-model" })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse(vec![completed("r1")]), "text/event-stream"),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let test = start_mock_conversation(&server, |config| {
+        config.model = "primary-model".to_string();
+        config.model_fallbacks = vec!["fallback-model".to_string()];
+        config.model_provider.request_max_retries = Some(0);
+        config.model_provider.stream_max_retries = Some(0);
+    })
+    .await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::BackgroundEvent(notice) = wait_for_event(
+        &codex,
+        |ev| matches!(ev, EventMsg::BackgroundEvent(ev) if ev.message.contains("falling back")),
+    )
+    .await
+    else {
+        unreachable!()
+    };
+    assert!(notice.message.contains("`primary-model`"));
+    assert!(notice.message.contains("`fallback-model`"));
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "again".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+}
@@ -0,0 +1,103 @@
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config::ConfigToml;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const CONFIG_TOML: &str = r#"
+model = "local-coder"
+
+[model_overrides.local-coder]
+context_window = 32768
+
+[model_overrides.local-reasoner]
+context_window = 8192
+supports_reasoning_summaries = true
+"#;
+
+/// `TaskStarted` reports the context window configured for the session's
+/// model and for a model switched to later.
+#[tokio::test]
+async fn task_started_reports_overridden_context_window() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().expect("tempdir");
+    let cfg: ConfigToml = toml::from_str(CONFIG_TOML).expect("valid config");
+    let mut config = Config::load_from_base_config_with_overrides(
+        cfg,
+        ConfigOverrides::default(),
+        home.path().to_path_buf(),
+    )
+    .expect("load config");
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation;
+
+    let mut context_windows = Vec::new();
+    for model in [None, Some("local-reasoner")] {
+        if let Some(model) = model {
+            codex
+                .submit(Op::OverrideTurnContext {
+                    cwd: None,
+                    approval_policy: None,
+                    sandbox_policy: None,
+                    model: Some(model.to_string()),
+                    effort: None,
+                    summary: None,
+                    danger_full_access_confirmed: false,
+                })
+                .await
+                .expect("override model");
+        }
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text {
+                    text: "hello".into(),
+                }],
+            })
+            .await
+            .expect("submit input");
+        let EventMsg::TaskStarted(started) =
+            wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskStarted(_))).await
+        else {
+            unreachable!("wait_for_event returned an unexpected event");
+        };
+        context_windows.push(started.model_context_window);
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+
+    assert_eq!(context_windows, vec![Some(32768), Some(8192)]);
+}
@@ -0,0 +1,104 @@
+use codex_apply_patch::FileProgress;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const PATCH: &str = "*** Begin Patch\n*** Add File: c.txt\n+c\n*** Add File: a.txt\n+a\n*** Add File: b.txt\n+b\n*** End Patch";
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn three_file_patch_reports_progress_per_file_before_end() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(vec![
+            json!({
+                "type": "response.output_item.done",
+                "item": {
+                    "type": "function_call",
+                    "name": "apply_patch",
+                    "arguments": json!({ "input": PATCH }).to_string(),
+                    "call_id": "call-1"
+                }
+            }),
+            completed("r1"),
+        ]),
+    )
+    .await;
+    mount_once(&server, sse(vec![completed("r2")])).await;
+
+    let workspace = TempDir::new().unwrap();
+    // The built-in applier re-executes the current binary, which is the test
+    // harness here, so stand in an external applier that reports each file
+    // the way `apply_patch_with_progress` does, pausing between them.
+    let script = ["c.txt", "a.txt", "b.txt"]
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let line = FileProgress {
+                path: name.into(),
+                index,
+                total: 3,
+            }
+            .report_line();
+            format!("echo '{line}' >&2; sleep 0.1")
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let test = start_mock_conversation(&server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        config.apply_patch_command = Some(vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!("cat > /dev/null; {script}"),
+        ]);
+    })
+    .await;
+    let codex = test.codex;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "add three files".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let mut seen = Vec::new();
+    loop {
+        let event = codex.next_event().await.unwrap();
+        match event.msg {
+            EventMsg::PatchApplyProgress(ev) => {
+                assert_eq!(ev.call_id, "call-1");
+                assert_eq!(ev.total, 3);
+                assert_eq!(ev.path.parent(), Some(workspace.path()));
+                let name = ev.path.file_name().unwrap().to_string_lossy().to_string();
+                seen.push(format!("{}:{name}", ev.index));
+            }
+            EventMsg::PatchApplyEnd(ev) => {
+                assert!(ev.success, "{ev:?}");
+                assert_eq!(ev.stderr, "");
+                seen.push("end".to_string());
+            }
+            EventMsg::TaskComplete(_) => break,
+            _ => {}
+        }
+    }
+
+    assert_eq!(seen, vec!["0:c.txt", "1:a.txt", "2:b.txt", "end"]);
+}
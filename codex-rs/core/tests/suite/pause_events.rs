@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses::completed;
+use core_test_support::responses::sse;
+use core_test_support::start_mock_conversation;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn assistant_message(text: &str) -> Value {
+    json!({
+        "type": "response.output_item.done",
+        "item": {
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "output_text", "text": text}]
+        }
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn events_produced_while_paused_are_delivered_in_order_after_resume() {
+    let server = MockServer::start().await;
+    let body = sse(vec![
+        assistant_message("first"),
+        assistant_message("second"),
+        completed("r1"),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(body, "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let test = start_mock_conversation(&server, |_| {}).await;
+    let codex = test.codex;
+
+    codex.pause_events();
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    // Nothing is delivered while paused, even though the turn runs.
+    let paused = tokio::time::timeout(Duration::from_millis(500), codex.next_event()).await;
+    assert!(paused.is_err(), "event delivered while paused: {paused:?}");
+
+    assert_eq!(codex.resume_events(), 0);
+    let mut msgs = Vec::new();
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), codex.next_event())
+            .await
+            .unwrap()
+            .unwrap();
+        match event.msg {
+            EventMsg::TaskStarted(_) => msgs.push("task_started".to_string()),
+            EventMsg::AgentMessage(ev) => msgs.push(ev.message),
+            EventMsg::TaskComplete(_) => {
+                msgs.push("task_complete".to_string());
+                break;
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(
+        msgs,
+        vec!["task_started", "first", "second", "task_complete"]
+    );
+}
@@ -0,0 +1,120 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::config_types::Environment;
+use codex_core::config_types::ProjectSummary;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Runs a single turn in `cwd` and returns the serialized
+/// `<environment_context>` message that was sent to the model.
+async fn environment_context_text(cwd: &TempDir, environment: Environment) -> String {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let codex_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&codex_home);
+    config.cwd = cwd.path().to_path_buf();
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.environment = environment;
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation")
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    let body = requests[0].body_json::<serde_json::Value>().unwrap();
+    body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .find(|text| text.starts_with("<environment_context>"))
+        .expect("environment context message")
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn environment_context_includes_project_summary() {
+    let cwd = TempDir::new().unwrap();
+    let text = environment_context_text(
+        &cwd,
+        Environment {
+            project_summary: Some(ProjectSummary::Text(
+                "Services live in services/; never edit vendor/.".to_string(),
+            )),
+            project_summary_max_chars: None,
+        },
+    )
+    .await;
+
+    assert!(
+        text.contains(
+            "<project_summary>Services live in services/; never edit vendor/.</project_summary>"
+        ),
+        "{text}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn project_summary_file_is_truncated_with_ellipsis() {
+    let cwd = TempDir::new().unwrap();
+    std::fs::write(cwd.path().join("ORIENTATION.md"), "0123456789abcdef\n").unwrap();
+
+    let text = environment_context_text(
+        &cwd,
+        Environment {
+            project_summary: Some(ProjectSummary::File {
+                path: "ORIENTATION.md".into(),
+            }),
+            project_summary_max_chars: Some(10),
+        },
+    )
+    .await;
+
+    assert!(
+        text.contains("<project_summary>0123456789…</project_summary>"),
+        "{text}"
+    );
+}
@@ -1,4 +1,4 @@
-#![allow(clippy::unwrap_used)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
 
 use codex_core::ConversationManager;
 use codex_core::ModelProviderInfo;
@@ -389,10 +389,13 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() {
                 network_access: true,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                tracked_files_only: false,
+                allow_git_writes: false,
             }),
             model: Some("o3".to_string()),
             effort: Some(ReasoningEffort::High),
             summary: Some(ReasoningSummary::Detailed),
+            danger_full_access_confirmed: false,
         })
         .await
         .unwrap();
@@ -517,10 +520,16 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
                 network_access: true,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                tracked_files_only: false,
+                allow_git_writes: false,
             },
             model: "o3".to_string(),
             effort: ReasoningEffort::High,
             summary: ReasoningSummary::Detailed,
+            include_raw_reasoning: false,
+            allowed_tools: None,
+            max_output_tokens: None,
+            danger_full_access_confirmed: false,
         })
         .await
         .unwrap();
@@ -556,3 +565,78 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
     );
     assert_eq!(body2["input"], expected_body2);
 }
+
+/// Runs two turns in a fresh conversation and returns the `prompt_cache_key`
+/// sent with each request.
+async fn prompt_cache_keys_for_two_turns(prompt_cache_key: Option<String>) -> Vec<String> {
+    let server = MockServer::start().await;
+
+    let sse = sse_completed("resp");
+    let template = ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_raw(sse, "text/event-stream");
+
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(template)
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+
+    let codex_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model_provider = model_provider;
+    config.prompt_cache_key = prompt_cache_key;
+
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation")
+        .conversation;
+
+    for text in ["hello 1", "hello 2"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text { text: text.into() }],
+            })
+            .await
+            .unwrap();
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            let body = request.body_json::<serde_json::Value>().unwrap();
+            body["prompt_cache_key"].as_str().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn prompt_cache_key_is_stable_per_conversation() {
+    let first = prompt_cache_keys_for_two_turns(None).await;
+    let second = prompt_cache_keys_for_two_turns(None).await;
+
+    assert_eq!(first.len(), 2);
+    assert_eq!(first[0], first[1], "same key across turns");
+    assert_eq!(second[0], second[1], "same key across turns");
+    assert_ne!(first[0], second[0], "different key across conversations");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn prompt_cache_key_uses_configured_override() {
+    let keys = prompt_cache_keys_for_two_turns(Some("team-shared-cache".to_string())).await;
+
+    assert_eq!(keys, vec!["team-shared-cache", "team-shared-cache"]);
+}
@@ -0,0 +1,76 @@
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::header;
+use wiremock::matchers::header_regex;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn provider_for(server: &MockServer) -> ModelProviderInfo {
+    ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    }
+}
+
+#[tokio::test]
+async fn health_check_reports_success_for_valid_credentials() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header("authorization", "Bearer good-key"))
+        .and(header("originator", "codex_cli_rs"))
+        .and(header_regex("user-agent", "^codex_cli_rs/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "object": "list",
+            "data": []
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let health = provider_for(&server)
+        .health_check(&Some(CodexAuth::from_api_key("good-key")))
+        .await
+        .unwrap();
+
+    assert_eq!(health.status, 200);
+    assert!(health.auth_ok);
+}
+
+#[tokio::test]
+async fn health_check_reports_auth_failure_on_401() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let health = provider_for(&server)
+        .health_check(&Some(CodexAuth::from_api_key("bad-key")))
+        .await
+        .unwrap();
+
+    assert_eq!(health.status, 401);
+    assert!(!health.auth_ok);
+}
@@ -0,0 +1,152 @@
+#![expect(clippy::unwrap_used, clippy::expect_used)]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use tokio::time::Duration;
+use tokio::time::timeout;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// A single turn that streams both summarized and raw reasoning before
+/// completing.
+fn reasoning_turn_sse() -> String {
+    sse(vec![
+        serde_json::json!({
+            "type": "response.reasoning_summary_text.delta",
+            "delta": "summary delta"
+        }),
+        serde_json::json!({
+            "type": "response.reasoning_text.delta",
+            "delta": "raw delta"
+        }),
+        serde_json::json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "reasoning",
+                "id": "rs1",
+                "summary": [{"type": "summary_text", "text": "summary text"}],
+                "content": [{"type": "reasoning_text", "text": "raw text"}],
+                "encrypted_content": null
+            }
+        }),
+        serde_json::json!({
+            "type": "response.completed",
+            "response": {
+                "id": "r1",
+                "usage": {"input_tokens":0,"input_tokens_details":null,"output_tokens":0,"output_tokens_details":null,"total_tokens":0}
+            }
+        }),
+    ])
+}
+
+/// Runs a single `UserTurn` against a mock server and returns every event
+/// emitted up to and including `TaskComplete`.
+async fn collect_turn_events(include_raw_reasoning: bool) -> Vec<EventMsg> {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(reasoning_turn_sse(), "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let test = start_mock_conversation(&server, |_| {}).await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "think out loud".into(),
+            }],
+            cwd: test.config.cwd.clone(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            model: test.config.model.clone(),
+            effort: ReasoningEffort::Medium,
+            summary: ReasoningSummary::Auto,
+            include_raw_reasoning,
+            allowed_tools: None,
+            max_output_tokens: None,
+            danger_full_access_confirmed: false,
+        })
+        .await
+        .unwrap();
+
+    let mut events = Vec::new();
+    loop {
+        let ev = timeout(Duration::from_secs(5), codex.next_event())
+            .await
+            .expect("timeout waiting for event")
+            .expect("stream ended unexpectedly");
+        let done = matches!(ev.msg, EventMsg::TaskComplete(_));
+        events.push(ev.msg);
+        if done {
+            return events;
+        }
+    }
+}
+
+fn is_raw_reasoning(ev: &EventMsg) -> bool {
+    matches!(
+        ev,
+        EventMsg::AgentReasoningRawContent(_) | EventMsg::AgentReasoningRawContentDelta(_)
+    )
+}
+
+fn is_summarized_reasoning(ev: &EventMsg) -> bool {
+    matches!(
+        ev,
+        EventMsg::AgentReasoning(_) | EventMsg::AgentReasoningDelta(_)
+    )
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn raw_reasoning_is_not_emitted_when_turn_does_not_opt_in() {
+    skip_if_no_network!();
+
+    let events = collect_turn_events(false).await;
+
+    assert!(
+        !events.iter().any(is_raw_reasoning),
+        "unexpected raw reasoning events: {events:?}"
+    );
+    assert!(
+        events.iter().any(is_summarized_reasoning),
+        "summarized reasoning should still be emitted: {events:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn raw_reasoning_is_emitted_when_turn_opts_in() {
+    skip_if_no_network!();
+
+    let events = collect_turn_events(true).await;
+
+    assert!(
+        events
+            .iter()
+            .any(|ev| matches!(ev, EventMsg::AgentReasoningRawContentDelta(_))),
+        "expected raw reasoning delta: {events:?}"
+    );
+    assert!(
+        events
+            .iter()
+            .any(|ev| matches!(ev, EventMsg::AgentReasoningRawContent(_))),
+        "expected raw reasoning content: {events:?}"
+    );
+}
@@ -0,0 +1,152 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::model_family::find_family_for_model;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn sse_completed(id: &str) -> String {
+    load_sse_fixture_with_id("tests/fixtures/completed_template.json", id)
+}
+
+fn find_rollout(dir: &Path) -> Option<PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()? {
+        let path = entry.ok()?.path();
+        if path.is_dir() {
+            if let Some(found) = find_rollout(&path) {
+                return Some(found);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+            return Some(path);
+        }
+    }
+    None
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn resumed_conversation_replays_rollout_history() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse_completed("resp"), "text/event-stream"),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+
+    let codex_home = TempDir::new().unwrap();
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model_provider = model_provider.clone();
+    config.model = "o3".to_string();
+    config.model_family = find_family_for_model("o3").unwrap();
+    let original = conversation_manager.new_conversation(config).await.unwrap();
+    original
+        .conversation
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "remember the marker".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&original.conversation, |ev| {
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    // The rollout writer runs on a background task; wait for it to flush.
+    let sessions_dir = codex_home.path().join("sessions");
+    let mut rollout = None;
+    for _ in 0..50 {
+        if let Some(path) = find_rollout(&sessions_dir)
+            && std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("remember the marker")
+        {
+            rollout = Some(path);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let rollout = rollout.expect("rollout with the user message");
+
+    // Simulate a session that ended while a tool call was still running.
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&rollout)
+        .unwrap();
+    writeln!(
+        file,
+        r#"{{"type":"function_call","name":"shell","arguments":"{{}}","call_id":"dangling-call"}}"#
+    )
+    .unwrap();
+    drop(file);
+
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model_provider = model_provider;
+    config.model = "gpt-5".to_string();
+    config.model_family = find_family_for_model("gpt-5").unwrap();
+    let resumed = conversation_manager
+        .resume_conversation_from_rollout(&rollout, config)
+        .await
+        .unwrap();
+    assert_ne!(resumed.conversation_id, original.conversation_id);
+    assert_eq!(resumed.session_configured.model, "o3");
+
+    resumed
+        .conversation
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "and now?".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&resumed.conversation, |ev| {
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let requests = server.received_requests().await.unwrap();
+    let body = requests[1].body_json::<serde_json::Value>().unwrap();
+    assert_eq!(body["model"], "o3");
+    let input = body["input"].to_string();
+    assert!(input.contains("remember the marker"), "{input}");
+    assert!(input.contains("and now?"), "{input}");
+    assert!(!input.contains("dangling-call"), "{input}");
+}
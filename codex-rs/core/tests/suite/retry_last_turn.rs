@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event_with_timeout;
+use serde_json::Value;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// How many user messages in the request body's `input` carry `text`.
+fn user_messages_with_text(body: &Value, text: &str) -> usize {
+    body["input"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| {
+            item["role"] == "user"
+                && item["content"]
+                    .as_array()
+                    .is_some_and(|content| content.iter().any(|c| c["text"] == text))
+        })
+        .count()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn retry_replaces_failed_turn_and_overrides_model_for_that_turn_only() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+
+    // The first turn fails however often it is retried.
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(500)
+                .insert_header("content-type", "application/json")
+                .set_body_string(
+                    serde_json::json!({
+                        "error": {"type": "bad_request", "message": "synthetic client error"}
+                    })
+                    .to_string(),
+                ),
+        )
+        .mount(&server)
+        .await;
+
+    let test = start_mock_conversation(&server, |config| {
+        let provider = &mut config.model_provider;
+        provider.request_max_retries = Some(1);
+        provider.stream_max_retries = Some(1);
+        provider.stream_idle_timeout_ms = Some(2_000);
+    })
+    .await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "first message".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TurnAborted(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+
+    server.reset().await;
+    mount_once(&server, sse(vec![completed("resp_retry")])).await;
+    mount_once(&server, sse(vec![completed("resp_follow_up")])).await;
+
+    codex
+        .submit(Op::RetryLastTurn {
+            model: Some("o3".to_string()),
+        })
+        .await
+        .unwrap();
+    let complete = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_) | EventMsg::Error(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+    assert!(
+        matches!(complete, EventMsg::TaskComplete(_)),
+        "{complete:?}"
+    );
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "follow up".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let retried = requests[0].body_json::<Value>().unwrap();
+    assert_eq!(retried["model"], "o3");
+    assert_eq!(user_messages_with_text(&retried, "first message"), 1);
+
+    let follow_up = requests[1].body_json::<Value>().unwrap();
+    assert_eq!(follow_up["model"], test.config.model.as_str());
+    assert_eq!(user_messages_with_text(&follow_up, "first message"), 1);
+    assert_eq!(user_messages_with_text(&follow_up, "follow up"), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn retry_without_previous_turn_reports_error() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let test = start_mock_conversation(&server, |_| {}).await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::RetryLastTurn { model: None })
+        .await
+        .unwrap();
+    let error = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::Error(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+    let EventMsg::Error(error) = error else {
+        unreachable!()
+    };
+    assert_eq!(error.message, "nothing to retry yet");
+}
@@ -80,6 +80,8 @@ async fn if_parent_of_repo_is_writable_then_dot_git_folder_is_writable() {
         network_access: false,
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
+        tracked_files_only: false,
+        allow_git_writes: false,
     };
 
     test_scenario
@@ -106,6 +108,8 @@ async fn if_git_repo_is_writable_root_then_dot_git_folder_is_read_only() {
         network_access: false,
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
+        tracked_files_only: false,
+        allow_git_writes: false,
     };
 
     test_scenario
@@ -199,3 +203,31 @@ async fn touch(path: &Path, policy: &SandboxPolicy) -> bool {
         .expect("should be able to wait for child process")
         .success()
 }
+
+#[tokio::test]
+#[expect(clippy::expect_used)]
+async fn self_test_reports_disallowed_write_as_blocked_under_read_only() {
+    if std::env::var(CODEX_SANDBOX_ENV_VAR) == Ok("seatbelt".to_string()) {
+        eprintln!("{CODEX_SANDBOX_ENV_VAR} is set to 'seatbelt', skipping test.");
+        return;
+    }
+
+    let cwd = TempDir::new().expect("should be able to create temp dir");
+    let probe_dir = TempDir::new().expect("should be able to create temp dir");
+    let report = codex_core::sandbox_self_test(
+        &SandboxPolicy::ReadOnly,
+        cwd.path(),
+        probe_dir.path(),
+        &None,
+    )
+    .await
+    .expect("self test should run");
+
+    let write = report
+        .probes
+        .iter()
+        .find(|result| result.probe == codex_core::SelfTestProbe::WriteOutsideWritableRoots)
+        .expect("write probe should be reported");
+    assert!(write.expected_blocked && write.blocked, "{report:?}");
+    assert!(report.all_passed(), "{report:?}");
+}
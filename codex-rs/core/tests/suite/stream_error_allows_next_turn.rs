@@ -6,6 +6,7 @@ use codex_core::WireApi;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::TurnAbortReason;
 use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
 use codex_login::CodexAuth;
 use core_test_support::load_default_config_for_test;
@@ -81,6 +82,12 @@ async fn continue_after_stream_error() {
         stream_max_retries: Some(1),
         stream_idle_timeout_ms: Some(2_000),
         requires_openai_auth: false,
+        instructions_role: None,
+        organization: None,
+        project: None,
+        circuit_breaker: None,
+        mock_script: None,
+        stream_usage: false,
     };
 
     let home = TempDir::new().unwrap();
@@ -105,7 +112,7 @@ async fn continue_after_stream_error() {
         .await
         .unwrap();
 
-    // Expect an Error followed by TaskComplete so the session is released.
+    // Expect an Error followed by TurnAborted so the session is released.
     wait_for_event_with_timeout(
         &codex,
         |ev| matches!(ev, EventMsg::Error(_)),
@@ -113,12 +120,19 @@ async fn continue_after_stream_error() {
     )
     .await;
 
-    wait_for_event_with_timeout(
+    let aborted = wait_for_event_with_timeout(
         &codex,
-        |ev| matches!(ev, EventMsg::TaskComplete(_)),
+        |ev| matches!(ev, EventMsg::TurnAborted(_)),
         Duration::from_secs(5),
     )
     .await;
+    let EventMsg::TurnAborted(aborted) = aborted else {
+        unreachable!()
+    };
+    assert!(matches!(
+        aborted.reason,
+        TurnAbortReason::StreamError { .. }
+    ));
 
     // 2) Second turn: now send another prompt that should succeed using the
     // mock server SSE stream. If the agent failed to clear the running task on
@@ -132,10 +146,13 @@ async fn continue_after_stream_error() {
         .await
         .unwrap();
 
-    wait_for_event_with_timeout(
+    // The aborted turn sends no TaskComplete, so the next one belongs to the
+    // follow-up turn.
+    let complete = wait_for_event_with_timeout(
         &codex,
-        |ev| matches!(ev, EventMsg::TaskComplete(_)),
+        |ev| matches!(ev, EventMsg::TaskComplete(_) | EventMsg::Error(_)),
         Duration::from_secs(5),
     )
     .await;
+    assert!(matches!(complete, EventMsg::TaskComplete(_)));
 }
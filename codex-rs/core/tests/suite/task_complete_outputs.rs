@@ -0,0 +1,139 @@
+#![cfg(unix)]
+#![expect(clippy::expect_used)]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TaskCompleteEvent;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use core_test_support::responses::mount_once;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const SSE_SHELL_CALL: &str = r#"[
+  {"type": "response.output_item.done", "item": {
+    "type": "function_call",
+    "name": "shell",
+    "arguments": "__ARGS__",
+    "call_id": "call-cat"
+  }},
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+const SSE_MESSAGE: &str = r#"[
+  {"type": "response.output_item.done", "item": {
+    "type": "message",
+    "role": "assistant",
+    "content": [{"type": "output_text", "text": "Done."}]
+  }},
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+const SSE_COMPLETED: &str = r#"[
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+/// Submit one user turn against `server` and return its `TaskComplete`.
+async fn run_turn(server: &MockServer, workspace: &TempDir) -> TaskCompleteEvent {
+    let test = start_mock_conversation(server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    })
+    .await;
+    let codex = test.codex;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "look at notes.txt".into(),
+            }],
+        })
+        .await
+        .expect("submit user input");
+
+    match wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await {
+        EventMsg::TaskComplete(event) => event,
+        _ => unreachable!(),
+    }
+}
+
+/// A turn that only reads a file and ends without a message is a no-op.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn read_only_turn_reports_no_changes_and_no_message() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let workspace = TempDir::new().expect("create workspace");
+    std::fs::write(workspace.path().join("notes.txt"), "hello\n").expect("write notes.txt");
+
+    let args = json!({ "command": ["cat", "notes.txt"] }).to_string();
+    let shell_call = SSE_SHELL_CALL.replace("__ARGS__", &args.replace('"', "\\\""));
+    mount_once(
+        &server,
+        load_sse_fixture_with_id_from_str(&shell_call, "r1"),
+    )
+    .await;
+    mount_once(
+        &server,
+        load_sse_fixture_with_id_from_str(SSE_COMPLETED, "r2"),
+    )
+    .await;
+
+    let complete = run_turn(&server, &workspace).await;
+    assert!(!complete.produced_changes, "{complete:?}");
+    assert!(!complete.produced_message, "{complete:?}");
+}
+
+/// A shell command that writes a file counts as a change even though it
+/// never shows up in the patch-based turn diff.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shell_write_sets_produced_changes() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let workspace = TempDir::new().expect("create workspace");
+
+    let args = json!({ "command": ["sh", "-c", "echo hello > notes.txt"] }).to_string();
+    let shell_call = SSE_SHELL_CALL.replace("__ARGS__", &args.replace('"', "\\\""));
+    mount_once(
+        &server,
+        load_sse_fixture_with_id_from_str(&shell_call, "r1"),
+    )
+    .await;
+    mount_once(
+        &server,
+        load_sse_fixture_with_id_from_str(SSE_COMPLETED, "r2"),
+    )
+    .await;
+
+    let complete = run_turn(&server, &workspace).await;
+    assert!(
+        workspace.path().join("notes.txt").exists(),
+        "command did not run"
+    );
+    assert!(complete.produced_changes, "{complete:?}");
+    assert!(!complete.produced_message, "{complete:?}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn assistant_message_sets_produced_message() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let workspace = TempDir::new().expect("create workspace");
+    mount_once(
+        &server,
+        load_sse_fixture_with_id_from_str(SSE_MESSAGE, "r1"),
+    )
+    .await;
+
+    let complete = run_turn(&server, &workspace).await;
+    assert!(!complete.produced_changes, "{complete:?}");
+    assert!(complete.produced_message, "{complete:?}");
+}
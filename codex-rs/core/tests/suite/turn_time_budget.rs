@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::TaskCompleteReason;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_stops_after_tool_call_when_time_budget_exceeded() {
+    skip_if_no_network!();
+
+    // The model asks for a tool call. Without a budget, Codex would send the
+    // tool output back for another sampling request.
+    let body = sse(vec![
+        serde_json::json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "function_call",
+                "name": "no_such_tool",
+                "arguments": "{}",
+                "call_id": "call1"
+            }
+        }),
+        serde_json::json!({
+            "type": "response.completed",
+            "response": {
+                "id": "r1",
+                "usage": {"input_tokens":0,"input_tokens_details":null,"output_tokens":0,"output_tokens_details":null,"total_tokens":0}
+            }
+        }),
+    ]);
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(body, "text/event-stream")
+                // Guarantee the tiny budget below is exhausted by the time
+                // the tool call has been handled.
+                .set_delay(Duration::from_millis(50)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let test = start_mock_conversation(&server, |config| {
+        config.max_turn_duration = Some(Duration::from_millis(1));
+    })
+    .await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "loop forever".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let ev = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = ev else {
+        unreachable!()
+    };
+    assert_eq!(complete.reason, TaskCompleteReason::TimeBudgetExceeded);
+
+    // Only the initial sampling request should have been made.
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const SSE_RESPONSE: &str = r#"[
+  {"type": "response.output_item.done", "item": {"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "hi"}]}},
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+const RESPONSE_DELAY: Duration = Duration::from_millis(300);
+/// Generous slack for slow CI machines.
+const TOLERANCE: Duration = Duration::from_secs(5);
+
+/// With `report_turn_timing`, each turn reports a time to first token that
+/// accounts for the delay before the server starts streaming.
+#[tokio::test]
+async fn turn_timing_reports_first_token_latency() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(SSE_RESPONSE, "resp"),
+                    "text/event-stream",
+                )
+                .set_delay(RESPONSE_DELAY),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().expect("tempdir");
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.report_turn_timing = true;
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .expect("submit input");
+
+    let EventMsg::TurnTiming(timing) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnTiming(_))).await
+    else {
+        unreachable!("wait_for_event returned an unexpected event");
+    };
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let first_token = timing
+        .time_to_first_token
+        .expect("the response contains an output item");
+    assert!(
+        first_token >= RESPONSE_DELAY && first_token < RESPONSE_DELAY + TOLERANCE,
+        "time to first token out of range: {first_token:?}"
+    );
+    assert!(timing.total_duration >= first_token, "{timing:?}");
+}
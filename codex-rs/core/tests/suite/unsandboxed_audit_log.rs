@@ -0,0 +1,88 @@
+#![cfg(unix)]
+
+use codex_core::audit_log::UnsandboxedExecRecord;
+use codex_core::audit_log::UnsandboxedReason;
+use codex_core::audit_log::unsandboxed_exec_log_path;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use core_test_support::responses::mount_once;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+const SSE_SHELL_CALL: &str = r#"[
+  {"type": "response.output_item.done", "item": {
+    "type": "function_call",
+    "name": "shell",
+    "arguments": "__ARGS__",
+    "call_id": "call-touch"
+  }},
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+const SSE_COMPLETED: &str = r#"[
+  {"type": "response.completed", "response": {"id": "__ID__"}}
+]"#;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn danger_full_access_run_writes_one_audit_record() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let workspace = TempDir::new().expect("create workspace");
+    let target = workspace.path().join("created.txt");
+    let command = vec!["touch".to_string(), target.display().to_string()];
+
+    let args = json!({ "command": command }).to_string();
+    let shell_call = SSE_SHELL_CALL.replace("__ARGS__", &args.replace('"', "\\\""));
+    mount_once(
+        &server,
+        load_sse_fixture_with_id_from_str(&shell_call, "r1"),
+    )
+    .await;
+    mount_once(
+        &server,
+        load_sse_fixture_with_id_from_str(SSE_COMPLETED, "r2"),
+    )
+    .await;
+
+    let test = start_mock_conversation(&server, |config| {
+        config.cwd = workspace.path().to_path_buf();
+        config.approval_policy = AskForApproval::Never;
+        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    })
+    .await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "create a file".into(),
+            }],
+        })
+        .await
+        .expect("submit user input");
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert!(target.exists(), "command did not run");
+
+    let log = std::fs::read_to_string(unsandboxed_exec_log_path(test.home.path()))
+        .expect("read audit log");
+    let records: Vec<UnsandboxedExecRecord> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("parse audit record"))
+        .collect();
+    assert_eq!(records.len(), 1, "{log}");
+    let record = &records[0];
+    assert_eq!(record.command, command);
+    assert_eq!(record.cwd, workspace.path());
+    assert_eq!(record.reason, UnsandboxedReason::DangerFullAccess);
+    assert_eq!(record.conversation_id, test.conversation_id.to_string());
+    assert!(!record.timestamp.is_empty());
+}
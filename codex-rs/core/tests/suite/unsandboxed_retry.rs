@@ -0,0 +1,152 @@
+#![cfg(target_os = "linux")]
+#![expect(clippy::unwrap_used)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::MockConversation;
+use core_test_support::responses::completed;
+use core_test_support::responses::mount_once;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::MockServer;
+
+fn shell_call(call_id: &str, command: &[&str]) -> Value {
+    json!({
+        "type": "response.output_item.done",
+        "item": {
+            "type": "function_call",
+            "name": "shell",
+            "arguments": json!({ "command": command }).to_string(),
+            "call_id": call_id
+        }
+    })
+}
+
+/// Stand-in for `codex-linux-sandbox` that rejects every command the way a
+/// sandbox write denial does.
+fn write_denying_sandbox(dir: &Path) -> PathBuf {
+    let exe = dir.join("fake-linux-sandbox");
+    std::fs::write(
+        &exe,
+        "#!/bin/sh\necho 'touch: cannot touch: Permission denied' >&2\nexit 1\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+    exe
+}
+
+/// Starts a turn whose only tool call writes outside the workspace under
+/// `OnFailure`, with sandbox failures forced via a denying sandbox binary.
+async fn start_write_denied_turn(
+    server: &MockServer,
+    allow_unsandboxed_retry: bool,
+) -> (MockConversation, String) {
+    mount_once(
+        server,
+        sse(vec![
+            shell_call("call-write", &["touch", "/outside-workspace/file"]),
+            completed("r1"),
+        ]),
+    )
+    .await;
+    mount_once(server, sse(vec![completed("r2")])).await;
+
+    let test = start_mock_conversation(server, |config| {
+        config.approval_policy = AskForApproval::OnFailure;
+        config.sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+        config.codex_linux_sandbox_exe = Some(write_denying_sandbox(&config.codex_home));
+        config.allow_unsandboxed_retry = allow_unsandboxed_retry;
+    })
+    .await;
+
+    let sub_id = test
+        .codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "write a file".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    (test, sub_id)
+}
+
+async fn function_call_output(server: &MockServer) -> String {
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let body = requests[1].body_json::<Value>().unwrap();
+    body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["type"] == "function_call_output")
+        .and_then(|item| item["output"].as_str())
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn on_failure_offers_unsandboxed_retry_by_default() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let (test, sub_id) = start_write_denied_turn(&server, true).await;
+    let codex = test.codex;
+
+    let EventMsg::ExecApprovalRequest(request) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecApprovalRequest(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        request.reason.as_deref(),
+        Some("command failed; retry without sandbox?")
+    );
+    codex
+        .submit(Op::ExecApproval {
+            id: sub_id,
+            decision: ReviewDecision::Denied,
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert_eq!(
+        function_call_output(&server).await,
+        "exec command rejected by user"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn on_failure_returns_sandbox_failure_when_retry_disallowed() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let (test, _sub_id) = start_write_denied_turn(&server, false).await;
+    let codex = test.codex;
+
+    let ev = wait_for_event(&codex, |ev| {
+        matches!(
+            ev,
+            EventMsg::ExecApprovalRequest(_) | EventMsg::TaskComplete(_)
+        )
+    })
+    .await;
+    assert!(matches!(ev, EventMsg::TaskComplete(_)), "{ev:?}");
+
+    let output = function_call_output(&server).await;
+    assert!(output.starts_with("failed in sandbox"), "{output}");
+    assert!(output.contains("Permission denied"), "{output}");
+}
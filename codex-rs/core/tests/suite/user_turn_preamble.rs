@@ -0,0 +1,83 @@
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const PREAMBLE: &str = "Compliance notice: do not share customer data.";
+
+/// The preamble is sent with every user turn, exactly once per request, and
+/// does not pile up in the history sent with later turns.
+#[tokio::test]
+async fn user_turn_preamble_is_sent_once_per_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().expect("tempdir");
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.user_turn_preamble = Some(PREAMBLE.to_string());
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation;
+
+    for text in ["first question", "second question"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text { text: text.into() }],
+            })
+            .await
+            .expect("submit input");
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+
+    let requests = server.received_requests().await.expect("recorded requests");
+    assert_eq!(requests.len(), 2);
+    for (request, text) in requests.iter().zip(["first question", "second question"]) {
+        let body: Value = request.body_json().expect("JSON body");
+        let input = body["input"].to_string();
+        assert_eq!(input.matches(PREAMBLE).count(), 1, "{input}");
+
+        // The preamble leads the content of the turn's own user message.
+        let last_user_content = body["input"]
+            .as_array()
+            .expect("input array")
+            .iter()
+            .rev()
+            .find(|item| item["role"] == "user")
+            .map(|item| item["content"].clone())
+            .expect("user message");
+        assert_eq!(last_user_content[0]["text"], PREAMBLE);
+        assert_eq!(last_user_content[1]["text"], text);
+    }
+}
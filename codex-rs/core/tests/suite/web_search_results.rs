@@ -0,0 +1,109 @@
+#![expect(clippy::unwrap_used)]
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::WebSearchEndEvent;
+use codex_protocol::models::WebSearchResult;
+use core_test_support::responses::completed;
+use core_test_support::responses::sse;
+use core_test_support::start_mock_conversation;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Runs one turn whose response contains a single `web_search_call` with the
+/// given `action` and returns the resulting `WebSearchEnd` event.
+async fn web_search_end_for_action(action: Value) -> WebSearchEndEvent {
+    let server = MockServer::start().await;
+    let body = sse(vec![
+        json!({
+            "type": "response.output_item.done",
+            "item": {
+                "type": "web_search_call",
+                "id": "ws_1",
+                "status": "completed",
+                "action": action,
+            }
+        }),
+        completed("r1"),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(body, "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let test = start_mock_conversation(&server, |_| {}).await;
+    let codex = test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "search the web".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::WebSearchEnd(end) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::WebSearchEnd(_))).await
+    else {
+        unreachable!()
+    };
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    end
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn web_search_end_carries_results_when_provider_exposes_them() {
+    let end = web_search_end_for_action(json!({
+        "type": "search",
+        "query": "codex cli",
+        "sources": [
+            {"type": "url", "url": "https://example.com/a"},
+            {"type": "url", "url": "https://example.com/b", "title": "B", "snippet": "About B"}
+        ]
+    }))
+    .await;
+
+    assert_eq!(end.call_id, "ws_1");
+    assert_eq!(end.query, "codex cli");
+    assert_eq!(
+        end.results,
+        Some(vec![
+            WebSearchResult {
+                title: None,
+                url: "https://example.com/a".to_string(),
+                snippet: None,
+            },
+            WebSearchResult {
+                title: Some("B".to_string()),
+                url: "https://example.com/b".to_string(),
+                snippet: Some("About B".to_string()),
+            },
+        ])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn web_search_end_without_results_leaves_them_unset() {
+    let end = web_search_end_for_action(json!({
+        "type": "search",
+        "query": "codex cli",
+    }))
+    .await;
+
+    assert_eq!(end.query, "codex cli");
+    assert_eq!(end.results, None);
+}
@@ -0,0 +1,258 @@
+//! Rules-based answers to approval requests for `codex exec --approval-rules`.
+//!
+//! The rules file is TOML. Rules are tried in order and the first match wins;
+//! requests no rule matches get `default` (`deny` unless set):
+//!
+//! ```toml
+//! default = "deny"
+//!
+//! [[rule]]
+//! program = "cargo"
+//! decision = "approve"
+//!
+//! [[rule]]
+//! path = "src/*"
+//! decision = "approve"
+//! ```
+//!
+//! `program` rules match commands by the name of the program they run;
+//! commands using shell syntax beyond a single simple command (pipes, `&&`,
+//! redirections, substitutions) never match a `program` rule. `path` rules
+//! match patches whose written files all match the pattern, relative to the
+//! working directory; files outside the working directory (including via
+//! `..`) never match. In `program` patterns, `*` matches any run of
+//! characters and `?` a single character. In `path` patterns they do not
+//! match `/`, and a `**` component matches any number of directories.
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use serde::Deserialize;
+use wildmatch::WildMatch;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RuleDecision {
+    Approve,
+    #[default]
+    Deny,
+}
+
+impl RuleDecision {
+    fn verb(self) -> &'static str {
+        match self {
+            RuleDecision::Approve => "approved",
+            RuleDecision::Deny => "denied",
+        }
+    }
+}
+
+impl From<RuleDecision> for ReviewDecision {
+    fn from(decision: RuleDecision) -> Self {
+        match decision {
+            RuleDecision::Approve => ReviewDecision::Approved,
+            // Let the agent carry on and try something else.
+            RuleDecision::Deny => ReviewDecision::Denied,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApprovalRule {
+    /// Pattern for the program a command runs.
+    program: Option<String>,
+    /// Pattern for every file a patch writes.
+    path: Option<String>,
+    decision: RuleDecision,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApprovalRulesFile {
+    #[serde(default)]
+    default: RuleDecision,
+    #[serde(default, rename = "rule")]
+    rules: Vec<ApprovalRule>,
+}
+
+/// Answers approval requests from a rules file in place of a user.
+#[derive(Debug)]
+pub(crate) struct ApprovalHandler {
+    rules: ApprovalRulesFile,
+    cwd: PathBuf,
+}
+
+impl ApprovalHandler {
+    pub(crate) fn load(path: &Path, cwd: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+        Self::from_toml(&contents, cwd)
+            .map_err(|e| anyhow::anyhow!("invalid approval rules in {}: {e}", path.display()))
+    }
+
+    fn from_toml(contents: &str, cwd: &Path) -> anyhow::Result<Self> {
+        let rules: ApprovalRulesFile = toml::from_str(contents)?;
+        for (i, rule) in rules.rules.iter().enumerate() {
+            if rule.program.is_some() == rule.path.is_some() {
+                anyhow::bail!("rule {} must set exactly one of `program` or `path`", i + 1);
+            }
+        }
+        Ok(Self {
+            rules,
+            cwd: normalize(cwd),
+        })
+    }
+
+    /// The reply to `msg` if it is an approval request, along with a
+    /// one-line summary of the decision for the user.
+    pub(crate) fn respond(&self, event_id: &str, msg: &EventMsg) -> Option<(Op, String)> {
+        match msg {
+            EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent { command, .. }) => {
+                let decision = self.decide_command(command);
+                let command = shlex::try_join(command.iter().map(String::as_str))
+                    .unwrap_or_else(|_| command.join(" "));
+                Some((
+                    Op::ExecApproval {
+                        id: event_id.to_string(),
+                        decision: decision.into(),
+                    },
+                    format!("{} command: {command}", decision.verb()),
+                ))
+            }
+            EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+                changes, ..
+            }) => {
+                let paths: Vec<&Path> = changes
+                    .iter()
+                    .flat_map(|(path, change)| {
+                        let dest = match change {
+                            FileChange::Update {
+                                move_path: Some(dest),
+                                ..
+                            } => Some(dest.as_path()),
+                            _ => None,
+                        };
+                        std::iter::once(path.as_path()).chain(dest)
+                    })
+                    .collect();
+                let decision = self.decide_patch(&paths);
+                let files = paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Some((
+                    Op::PatchApproval {
+                        id: event_id.to_string(),
+                        decision: decision.into(),
+                    },
+                    format!("{} patch to {files}", decision.verb()),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn decide_command(&self, command: &[String]) -> RuleDecision {
+        let program = simple_command_program(command);
+        self.rules
+            .rules
+            .iter()
+            .find(|rule| match (&rule.program, &program) {
+                (Some(pattern), Some(program)) => WildMatch::new(pattern).matches(program),
+                _ => false,
+            })
+            .map_or(self.rules.default, |rule| rule.decision)
+    }
+
+    fn decide_patch(&self, paths: &[&Path]) -> RuleDecision {
+        // A file outside the working directory can never match a `path`
+        // rule, so such patches always get the default.
+        let relative: Option<Vec<Vec<String>>> = paths
+            .iter()
+            .map(|path| {
+                let path = normalize(&self.cwd.join(path));
+                let relative = path.strip_prefix(&self.cwd).ok()?;
+                Some(
+                    relative
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                        .collect(),
+                )
+            })
+            .collect();
+        let Some(relative) = relative.filter(|paths| !paths.is_empty()) else {
+            return self.rules.default;
+        };
+        self.rules
+            .rules
+            .iter()
+            .find(|rule| {
+                rule.path.as_ref().is_some_and(|pattern| {
+                    let pattern: Vec<&str> = pattern.split('/').filter(|p| !p.is_empty()).collect();
+                    relative.iter().all(|path| path_matches(&pattern, path))
+                })
+            })
+            .map_or(self.rules.default, |rule| rule.decision)
+    }
+}
+
+/// Whether the components of a relative path match the `/`-separated
+/// `pattern`. Each component is matched on its own, so `*` cannot cross a
+/// `/`; a `**` component matches zero or more components.
+fn path_matches(pattern: &[&str], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| path_matches(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(component, path_rest)| {
+            WildMatch::new(first).matches(component) && path_matches(rest, path_rest)
+        }),
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Name of the program `command` runs, looking through a `bash -lc` style
+/// wrapper. `None` when the script is more than one simple command.
+fn simple_command_program(command: &[String]) -> Option<String> {
+    let words: Vec<String> = match command {
+        [shell, flag, script]
+            if matches!(
+                Path::new(shell).file_name().and_then(|name| name.to_str()),
+                Some("bash" | "sh" | "zsh")
+            ) && matches!(flag.as_str(), "-lc" | "-c") =>
+        {
+            if script.contains(|c| ";&|<>$`()\n".contains(c)) {
+                return None;
+            }
+            shlex::split(script)?
+        }
+        _ => command.to_vec(),
+    };
+    let program = words.first()?;
+    Some(Path::new(program).file_name().map_or_else(
+        || program.clone(),
+        |name| name.to_string_lossy().into_owned(),
+    ))
+}
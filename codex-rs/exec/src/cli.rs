@@ -22,6 +22,11 @@ pub struct Cli {
     #[arg(long = "sandbox", short = 's', value_enum)]
     pub sandbox_mode: Option<codex_common::SandboxModeCliArg>,
 
+    /// Confirm `--sandbox danger-full-access`, which is refused without this
+    /// flag unless `confirm_danger_full_access = false` is configured.
+    #[arg(long = "confirm-danger-full-access", default_value_t = false)]
+    pub confirm_danger_full_access: bool,
+
     /// Configuration profile from config.toml to specify default options.
     #[arg(long = "profile", short = 'p')]
     pub config_profile: Option<String>,
@@ -40,6 +45,16 @@ pub struct Cli {
     )]
     pub dangerously_bypass_approvals_and_sandbox: bool,
 
+    /// Answer approval requests from this TOML rules file instead of
+    /// rejecting them. Requests no rule matches are denied unless the file
+    /// sets `default = "approve"`.
+    #[arg(
+        long = "approval-rules",
+        value_name = "FILE",
+        conflicts_with = "dangerously_bypass_approvals_and_sandbox"
+    )]
+    pub approval_rules: Option<PathBuf>,
+
     /// Tell the agent to use the specified directory as its working root.
     #[clap(long = "cd", short = 'C', value_name = "DIR")]
     pub cwd: Option<PathBuf>,
@@ -59,6 +74,11 @@ pub struct Cli {
     #[arg(long = "json", default_value_t = false)]
     pub json: bool,
 
+    /// Print the model's reasoning summaries before its answers, even if
+    /// `hide_agent_reasoning` is set or reasoning summaries are turned off.
+    #[arg(long = "show-reasoning", default_value_t = false)]
+    pub show_reasoning: bool,
+
     /// Specifies file where the last message from the agent should be written.
     #[arg(long = "output-last-message")]
     pub last_message_file: Option<PathBuf>,
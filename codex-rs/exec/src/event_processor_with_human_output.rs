@@ -8,6 +8,7 @@ use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BackgroundEventLevel;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -17,6 +18,7 @@ use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::ModelPricing;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SessionConfiguredEvent;
@@ -24,6 +26,7 @@ use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnTimingEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use owo_colors::OwoColorize;
@@ -39,8 +42,8 @@ use crate::event_processor::EventProcessor;
 use crate::event_processor::handle_last_message;
 use codex_common::create_config_summary_entries;
 
-/// This should be configurable. When used in CI, users may not want to impose
-/// a limit so they can see the full transcript.
+/// Default limit when `exec_output_max_lines` is not configured. When used in
+/// CI, users may not want to impose a limit so they can see the full transcript.
 const MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL: usize = 20;
 pub(crate) struct EventProcessorWithHumanOutput {
     call_id_to_command: HashMap<String, ExecCommandBegin>,
@@ -61,9 +64,16 @@ pub(crate) struct EventProcessorWithHumanOutput {
     /// Whether to include `AgentReasoning` events in the output.
     show_agent_reasoning: bool,
     show_raw_agent_reasoning: bool,
+    /// Header printed above a complete `AgentReasoning` event: "thinking"
+    /// under `--show-reasoning`, "codex" otherwise.
+    reasoning_header: &'static str,
     answer_started: bool,
     reasoning_started: bool,
     raw_reasoning_started: bool,
+    /// Lines of command output printed per exec call.
+    exec_output_max_lines: usize,
+    /// Prices of the configured model, to estimate the cost of each response.
+    model_pricing: Option<ModelPricing>,
     last_message_path: Option<PathBuf>,
 }
 
@@ -71,10 +81,15 @@ impl EventProcessorWithHumanOutput {
     pub(crate) fn create_with_ansi(
         with_ansi: bool,
         config: &Config,
+        show_reasoning: bool,
         last_message_path: Option<PathBuf>,
     ) -> Self {
         let call_id_to_command = HashMap::new();
+        let reasoning_header = if show_reasoning { "thinking" } else { "codex" };
         let call_id_to_patch = HashMap::new();
+        let exec_output_max_lines = config
+            .exec_output_max_lines
+            .unwrap_or(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL);
 
         if with_ansi {
             Self {
@@ -89,9 +104,12 @@ impl EventProcessorWithHumanOutput {
                 cyan: Style::new().cyan(),
                 show_agent_reasoning: !config.hide_agent_reasoning,
                 show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+                reasoning_header,
                 answer_started: false,
                 reasoning_started: false,
                 raw_reasoning_started: false,
+                exec_output_max_lines,
+                model_pricing: config.model_pricing,
                 last_message_path,
             }
         } else {
@@ -107,9 +125,12 @@ impl EventProcessorWithHumanOutput {
                 cyan: Style::new(),
                 show_agent_reasoning: !config.hide_agent_reasoning,
                 show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+                reasoning_header,
                 answer_started: false,
                 reasoning_started: false,
                 raw_reasoning_started: false,
+                exec_output_max_lines,
+                model_pricing: config.model_pricing,
                 last_message_path,
             }
         }
@@ -174,24 +195,36 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 let prefix = "ERROR:".style(self.red);
                 ts_println!(self, "{prefix} {message}");
             }
-            EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
-                ts_println!(self, "{}", message.style(self.dimmed));
-            }
+            EventMsg::BackgroundEvent(BackgroundEventEvent { message, level }) => match level {
+                BackgroundEventLevel::Info => ts_println!(self, "{}", message.style(self.dimmed)),
+                BackgroundEventLevel::Warn => {
+                    let prefix = "WARNING:".style(self.red);
+                    ts_println!(self, "{prefix} {message}");
+                }
+            },
             EventMsg::StreamError(StreamErrorEvent { message }) => {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
             EventMsg::TaskStarted(_) => {
                 // Ignore.
             }
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message, ..
+            }) => {
                 if let Some(output_file) = self.last_message_path.as_deref() {
                     handle_last_message(last_agent_message.as_deref(), output_file);
                 }
                 return CodexStatus::InitiateShutdown;
             }
-            EventMsg::TokenCount(token_usage) => {
-                ts_println!(self, "tokens used: {}", token_usage.blended_total());
-            }
+            EventMsg::TokenCount(token_usage) => match &self.model_pricing {
+                Some(pricing) => ts_println!(
+                    self,
+                    "tokens used: {} (estimated cost ${:.4})",
+                    token_usage.blended_total(),
+                    token_usage.estimated_cost(pricing)
+                ),
+                None => ts_println!(self, "tokens used: {}", token_usage.blended_total()),
+            },
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
                 if !self.answer_started {
                     ts_println!(self, "{}\n", "codex".style(self.italic).style(self.magenta));
@@ -292,6 +325,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 aggregated_output,
                 duration,
                 exit_code,
+                timed_out,
                 ..
             }) => {
                 let exec_command = self.call_id_to_command.remove(&call_id);
@@ -307,7 +341,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
 
                 let truncated_output = aggregated_output
                     .lines()
-                    .take(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL)
+                    .take(self.exec_output_max_lines)
                     .collect::<Vec<_>>()
                     .join("\n");
                 match exit_code {
@@ -315,6 +349,10 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                         let title = format!("{call} succeeded{duration}:");
                         ts_println!(self, "{}", title.style(self.green));
                     }
+                    _ if timed_out => {
+                        let title = format!("{call} timed out{duration}:");
+                        ts_println!(self, "{}", title.style(self.red));
+                    }
                     _ => {
                         let title = format!("{call} exited {exit_code}{duration}:");
                         ts_println!(self, "{}", title.style(self.red));
@@ -340,9 +378,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     result,
                     invocation,
                     duration,
+                    attempts,
                 } = tool_call_end_event;
 
-                let duration = format!(" in {}", format_duration(duration));
+                let retries = match attempts.saturating_sub(1) {
+                    0 => String::new(),
+                    1 => " after 1 retry".to_string(),
+                    n => format!(" after {n} retries"),
+                };
+                let duration = format!(" in {}{retries}", format_duration(duration));
 
                 let status_str = if is_success { "success" } else { "failed" };
                 let title_style = if is_success { self.green } else { self.red };
@@ -364,13 +408,24 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 }
             }
             EventMsg::WebSearchBegin(WebSearchBeginEvent { call_id: _ }) => {}
-            EventMsg::WebSearchEnd(WebSearchEndEvent { call_id: _, query }) => {
+            EventMsg::WebSearchEnd(WebSearchEndEvent {
+                call_id: _,
+                query,
+                results,
+            }) => {
                 ts_println!(self, "🌐 Searched: {query}");
+                for result in results.unwrap_or_default() {
+                    match result.title {
+                        Some(title) => println!("  {title} {}", result.url.style(self.dimmed)),
+                        None => println!("  {}", result.url.style(self.dimmed)),
+                    }
+                }
             }
             EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                 call_id,
                 auto_approved,
                 changes,
+                dry_run,
             }) => {
                 // Store metadata so we can calculate duration later when we
                 // receive the corresponding PatchApplyEnd event.
@@ -382,12 +437,20 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     },
                 );
 
-                ts_println!(
-                    self,
-                    "{} auto_approved={}:",
-                    "apply_patch".style(self.magenta),
-                    auto_approved,
-                );
+                if dry_run {
+                    ts_println!(
+                        self,
+                        "{} (dry run, no files are changed):",
+                        "apply_patch".style(self.magenta),
+                    );
+                } else {
+                    ts_println!(
+                        self,
+                        "{} auto_approved={}:",
+                        "apply_patch".style(self.magenta),
+                        auto_approved,
+                    );
+                }
 
                 // Pretty-print the patch summary with colored diff markers so
                 // it's easy to scan in the terminal output.
@@ -451,6 +514,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 stdout,
                 stderr,
                 success,
+                dry_run,
                 ..
             }) => {
                 let patch_begin = self.call_id_to_patch.remove(&call_id);
@@ -461,10 +525,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     auto_approved,
                 }) = patch_begin
                 {
-                    (
-                        format!(" in {}", format_elapsed(start_time)),
-                        format!("apply_patch(auto_approved={auto_approved})"),
-                    )
+                    let label = if dry_run {
+                        "apply_patch(dry_run)".to_string()
+                    } else {
+                        format!("apply_patch(auto_approved={auto_approved})")
+                    };
+                    (format!(" in {}", format_elapsed(start_time)), label)
                 } else {
                     (String::new(), format!("apply_patch('{call_id}')"))
                 };
@@ -481,9 +547,41 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     println!("{}", line.style(self.dimmed));
                 }
             }
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+            EventMsg::TurnDiff(TurnDiffEvent {
+                unified_diff,
+                suppressed_files,
+            }) => {
                 ts_println!(self, "{}", "turn diff:".style(self.magenta));
                 println!("{unified_diff}");
+                if suppressed_files > 0 {
+                    println!(
+                        "{}",
+                        format!("({suppressed_files} ignored file(s) not shown)")
+                            .style(self.dimmed)
+                    );
+                }
+            }
+            EventMsg::CompactComplete(ev) => {
+                let message = match ev.tokens_saved() {
+                    Some(tokens_saved) => format!("context compacted, saved {tokens_saved} tokens"),
+                    None => "context compacted".to_string(),
+                };
+                ts_println!(self, "{}", message.style(self.dimmed));
+            }
+            EventMsg::TurnTiming(TurnTimingEvent {
+                time_to_first_token,
+                total_duration,
+            }) => {
+                let first_token = time_to_first_token
+                    .map(format_duration)
+                    .unwrap_or_else(|| "n/a".to_string());
+                let total = format_duration(total_duration);
+                ts_println!(
+                    self,
+                    "{}",
+                    format!("turn timing: first token {first_token}, total {total}")
+                        .style(self.dimmed)
+                );
             }
             EventMsg::ExecApprovalRequest(_) => {
                 // Should we exit?
@@ -497,7 +595,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                         ts_println!(
                             self,
                             "{}\n{}",
-                            "codex".style(self.italic).style(self.magenta),
+                            self.reasoning_header.style(self.italic).style(self.magenta),
                             agent_reasoning_event.text,
                         );
                     } else {
@@ -512,6 +610,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     model,
                     history_log_id: _,
                     history_entry_count: _,
+                    label,
+                    max_output_tokens: _,
                 } = session_configured_event;
 
                 ts_println!(
@@ -522,6 +622,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 );
 
                 ts_println!(self, "model: {}", model);
+                if let Some(label) = label {
+                    ts_println!(self, "label: {}", label);
+                }
                 println!();
             }
             EventMsg::PlanUpdate(plan_update_event) => {
@@ -535,17 +638,48 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::McpListToolsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::McpServerHealthResponse(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::ListCustomPromptsResponse(_) => {
                 // Currently ignored in exec output.
             }
-            EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
-                TurnAbortReason::Interrupted => {
-                    ts_println!(self, "task interrupted");
-                }
-                TurnAbortReason::Replaced => {
-                    ts_println!(self, "task aborted: replaced by a new task");
+            EventMsg::CommandEvaluation(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::GetDiffResponse(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::PatchApplyProgress(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::ResolvedConfig(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::PartialTokenCount(_) => {
+                // Only the final `TokenCount` of a response is printed.
+            }
+            EventMsg::TurnAborted(abort_reason) => {
+                match abort_reason.reason {
+                    TurnAbortReason::Interrupted => {
+                        ts_println!(self, "task interrupted");
+                    }
+                    TurnAbortReason::Replaced => {
+                        ts_println!(self, "task aborted: replaced by a new task");
+                    }
+                    TurnAbortReason::StreamError { message } => {
+                        ts_println!(self, "task aborted: {message}");
+                    }
+                    TurnAbortReason::Timeout => {
+                        ts_println!(self, "task aborted: model request timed out");
+                    }
+                    TurnAbortReason::ApprovalDenied => {
+                        ts_println!(self, "task aborted: approval denied");
+                    }
                 }
-            },
+                // An aborted task ends without a `TaskComplete`.
+                return CodexStatus::InitiateShutdown;
+            }
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationHistory(_) => {}
         }
@@ -45,12 +45,21 @@ impl EventProcessor for EventProcessorWithJsonOutput {
                 // Suppress streaming events in JSON mode.
                 CodexStatus::Running
             }
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message, ..
+            }) => {
                 if let Some(output_file) = self.last_message_path.as_deref() {
                     handle_last_message(last_agent_message.as_deref(), output_file);
                 }
                 CodexStatus::InitiateShutdown
             }
+            EventMsg::TurnAborted(_) => {
+                // An aborted task ends without a `TaskComplete`.
+                if let Ok(line) = serde_json::to_string(&event) {
+                    println!("{line}");
+                }
+                CodexStatus::InitiateShutdown
+            }
             EventMsg::ShutdownComplete => CodexStatus::Shutdown,
             _ => {
                 if let Ok(line) = serde_json::to_string(&event) {
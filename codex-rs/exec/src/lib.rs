@@ -1,3 +1,4 @@
+mod approval_rules;
 mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
@@ -7,21 +8,26 @@ use std::io::IsTerminal;
 use std::io::Read;
 use std::path::PathBuf;
 
+use approval_rules::ApprovalHandler;
 pub use cli::Cli;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::ConversationManager;
 use codex_core::NewConversation;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::config::find_codex_home;
+use codex_core::config::load_config_as_toml_with_cli_overrides;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::timestamps::TimestampTimer;
 use codex_core::util::is_inside_git_repo;
 use codex_login::AuthManager;
 use codex_ollama::DEFAULT_OSS_MODEL;
+use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
 use event_processor_with_json_output::EventProcessorWithJsonOutput;
@@ -41,48 +47,49 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         config_profile,
         full_auto,
         dangerously_bypass_approvals_and_sandbox,
+        approval_rules,
         cwd,
         skip_git_repo_check,
         color,
         last_message_file,
         json: json_mode,
+        show_reasoning,
         sandbox_mode: sandbox_mode_cli_arg,
+        confirm_danger_full_access,
         prompt,
         config_overrides,
     } = cli;
 
     // Determine the prompt based on CLI arg and/or stdin.
     let prompt = match prompt {
-        Some(p) if p != "-" => p,
+        Some(p) if p != "-" => Some(p),
         // Either `-` was passed or no positional arg.
         maybe_dash => {
-            // When no arg (None) **and** stdin is a TTY, bail out early – unless the
+            // When no arg (None) **and** stdin is a TTY, fall back to the
+            // project's initial prompt once the config is loaded – unless the
             // user explicitly forced reading via `-`.
             let force_stdin = matches!(maybe_dash.as_deref(), Some("-"));
 
             if std::io::stdin().is_terminal() && !force_stdin {
-                eprintln!(
-                    "No prompt provided. Either specify one as an argument or pipe the prompt into stdin."
-                );
-                std::process::exit(1);
-            }
-
-            // Ensure the user knows we are waiting on stdin, as they may
-            // have gotten into this state by mistake. If so, and they are not
-            // writing to stdin, Codex will hang indefinitely, so this should
-            // help them debug in that case.
-            if !force_stdin {
-                eprintln!("Reading prompt from stdin...");
-            }
-            let mut buffer = String::new();
-            if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
-                eprintln!("Failed to read prompt from stdin: {e}");
-                std::process::exit(1);
-            } else if buffer.trim().is_empty() {
-                eprintln!("No prompt provided via stdin.");
-                std::process::exit(1);
+                None
+            } else {
+                // Ensure the user knows we are waiting on stdin, as they may
+                // have gotten into this state by mistake. If so, and they are not
+                // writing to stdin, Codex will hang indefinitely, so this should
+                // help them debug in that case.
+                if !force_stdin {
+                    eprintln!("Reading prompt from stdin...");
+                }
+                let mut buffer = String::new();
+                if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
+                    eprintln!("Failed to read prompt from stdin: {e}");
+                    std::process::exit(1);
+                } else if buffer.trim().is_empty() {
+                    eprintln!("No prompt provided via stdin.");
+                    std::process::exit(1);
+                }
+                Some(buffer)
             }
-            buffer
         }
     };
 
@@ -95,20 +102,14 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         ),
     };
 
-    // TODO(mbolin): Take a more thoughtful approach to logging.
-    let default_level = "error";
-    let _ = tracing_subscriber::fmt()
-        // Fallback to the `default_level` log filter if the environment
-        // variable is not set _or_ contains an invalid value
-        .with_env_filter(
-            EnvFilter::try_from_default_env()
-                .or_else(|_| EnvFilter::try_new(default_level))
-                .unwrap_or_else(|_| EnvFilter::new(default_level)),
-        )
-        .with_ansi(stderr_with_ansi)
-        .with_writer(std::io::stderr)
-        .try_init();
-
+    // `--full-auto` and `--dangerously-bypass-approvals-and-sandbox` take
+    // precedence over `--sandbox`.
+    let danger_full_access_requested = !full_auto
+        && !dangerously_bypass_approvals_and_sandbox
+        && matches!(
+            sandbox_mode_cli_arg,
+            Some(codex_common::SandboxModeCliArg::DangerFullAccess)
+        );
     let sandbox_mode = if full_auto {
         Some(SandboxMode::WorkspaceWrite)
     } else if dangerously_bypass_approvals_and_sandbox {
@@ -134,13 +135,32 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         None // No specific model provider override.
     };
 
+    // Parse `-c` overrides.
+    let cli_kv_overrides = match config_overrides.parse_overrides() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error parsing -c overrides: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    // This CLI is intended to be headless and has no affordances for asking
+    // the user for approval. With a rules file, approval requests go to the
+    // rules instead: keep the user's configured policy, or ask for every
+    // untrusted command if they did not set one.
+    let approval_policy = match approval_rules {
+        None => Some(AskForApproval::Never),
+        Some(_) => match configured_approval_policy(&cli_kv_overrides, config_profile.as_deref()) {
+            Some(_) => None,
+            None => Some(AskForApproval::UnlessTrusted),
+        },
+    };
+
     // Load configuration and determine approval policy
     let overrides = ConfigOverrides {
         model,
         config_profile,
-        // This CLI is intended to be headless and has no affordances for asking
-        // the user for approval.
-        approval_policy: Some(AskForApproval::Never),
+        approval_policy,
         sandbox_mode,
         cwd: cwd.map(|p| p.canonicalize().unwrap_or(p)),
         model_provider,
@@ -153,22 +173,62 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         show_raw_agent_reasoning: oss.then_some(true),
         tools_web_search_request: None,
     };
-    // Parse `-c` overrides.
-    let cli_kv_overrides = match config_overrides.parse_overrides() {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("Error parsing -c overrides: {e}");
-            std::process::exit(1);
+    let mut config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+    if danger_full_access_requested
+        && config.confirm_danger_full_access
+        && !confirm_danger_full_access
+    {
+        eprintln!(
+            "Refusing to run with `--sandbox danger-full-access` without confirmation. \
+             Pass `--confirm-danger-full-access` or set `confirm_danger_full_access = false`."
+        );
+        std::process::exit(1);
+    }
+
+    // TODO(mbolin): Take a more thoughtful approach to logging.
+    let default_level = "error";
+    let _ = tracing_subscriber::fmt()
+        // Fallback to the `default_level` log filter if the environment
+        // variable is not set _or_ contains an invalid value
+        .with_env_filter(
+            EnvFilter::try_from_default_env()
+                .or_else(|_| EnvFilter::try_new(default_level))
+                .unwrap_or_else(|_| EnvFilter::new(default_level)),
+        )
+        .with_ansi(stderr_with_ansi)
+        .with_timer(TimestampTimer(config.timestamps))
+        .with_writer(std::io::stderr)
+        .try_init();
+
+    if show_reasoning {
+        config.hide_agent_reasoning = false;
+        if config.model_reasoning_summary == ReasoningSummary::None {
+            config.model_reasoning_summary = ReasoningSummary::Auto;
         }
+    }
+    let approval_handler = match approval_rules {
+        Some(path) => match ApprovalHandler::load(&path, &config.cwd) {
+            Ok(handler) => Some(handler),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let Some(prompt) = config.resolve_initial_prompt(prompt) else {
+        eprintln!(
+            "No prompt provided. Either specify one as an argument, pipe the prompt into stdin, or set `initial_prompt` for this project."
+        );
+        std::process::exit(1);
     };
-
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
     let mut event_processor: Box<dyn EventProcessor> = if json_mode {
         Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone()))
     } else {
         Box::new(EventProcessorWithHumanOutput::create_with_ansi(
             stdout_with_ansi,
             &config,
+            show_reasoning,
             last_message_file.clone(),
         ))
     };
@@ -252,9 +312,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             if event.id == initial_images_event_id
                 && matches!(
                     event.msg,
-                    EventMsg::TaskComplete(TaskCompleteEvent {
-                        last_agent_message: _,
-                    })
+                    EventMsg::TaskComplete(TaskCompleteEvent { .. }) | EventMsg::TurnAborted(_)
                 )
             {
                 break;
@@ -269,6 +327,13 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
 
     // Run the loop until the task is complete.
     while let Some(event) = rx.recv().await {
+        if let Some((op, summary)) = approval_handler
+            .as_ref()
+            .and_then(|handler| handler.respond(&event.id, &event.msg))
+        {
+            eprintln!("approval rules: {summary}");
+            conversation.submit(op).await?;
+        }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
@@ -283,3 +348,18 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
 
     Ok(())
 }
+
+/// The approval policy the user set in `config.toml`, the active profile or a
+/// `-c` override, if any.
+fn configured_approval_policy(
+    cli_kv_overrides: &[(String, toml::Value)],
+    config_profile: Option<&str>,
+) -> Option<AskForApproval> {
+    let codex_home = find_codex_home().ok()?;
+    let cfg =
+        load_config_as_toml_with_cli_overrides(&codex_home, cli_kv_overrides.to_vec()).ok()?;
+    let profile = config_profile.map(str::to_string).or(cfg.profile.clone());
+    profile
+        .and_then(|name| cfg.profiles.get(&name)?.approval_policy)
+        .or(cfg.approval_policy)
+}
@@ -3,6 +3,7 @@
 use anyhow::Context;
 use assert_cmd::prelude::*;
 use codex_core::CODEX_APPLY_PATCH_ARG1;
+use codex_core::CODEX_APPLY_PATCH_STDIN_ARG1;
 use std::fs;
 use std::process::Command;
 use tempfile::tempdir;
@@ -40,6 +41,34 @@ fn test_standalone_exec_cli_can_use_apply_patch() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Patches larger than the per-argument limit (128 KiB on Linux) cannot be
+/// passed via argv, so they must be accepted on stdin.
+#[test]
+fn test_standalone_exec_cli_can_apply_large_patch_from_stdin() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let lines: Vec<String> = (0..20_000).map(|i| format!("line number {i}")).collect();
+    let mut patch = String::from("*** Begin Patch\n*** Add File: large.txt\n");
+    for line in &lines {
+        patch.push_str(&format!("+{line}\n"));
+    }
+    patch.push_str("*** End Patch");
+    assert!(patch.len() > 128 * 1024);
+
+    assert_cmd::Command::cargo_bin("codex-exec")
+        .context("should find binary for codex-exec")?
+        .arg(CODEX_APPLY_PATCH_STDIN_ARG1)
+        .write_stdin(patch)
+        .current_dir(tmp.path())
+        .assert()
+        .code(0)
+        .stdout("Success. Updated the following files:\nA large.txt\n");
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("large.txt"))?,
+        format!("{}\n", lines.join("\n"))
+    );
+    Ok(())
+}
+
 #[cfg(not(target_os = "windows"))]
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn test_apply_patch_tool() -> anyhow::Result<()> {
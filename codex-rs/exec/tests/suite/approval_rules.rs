@@ -0,0 +1,165 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used)]
+
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use tempfile::tempdir;
+
+use crate::suite::common::run_e2e_exec_test_with_args;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn approval_rules_approve_matching_commands_and_deny_the_rest() -> anyhow::Result<()> {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return Ok(());
+    }
+
+    let tmp_cwd = tempdir().expect("failed to create temp dir");
+    let rules_path = tmp_cwd.path().join("approval-rules.toml");
+    std::fs::write(
+        &rules_path,
+        r#"
+[[rule]]
+program = "touch"
+decision = "approve"
+"#,
+    )?;
+
+    run_e2e_exec_test_with_args(
+        tmp_cwd.path(),
+        &[
+            "-s",
+            "read-only",
+            "--approval-rules",
+            rules_path.to_str().expect("utf-8 path"),
+        ],
+        vec![
+            include_str!("../fixtures/sse_shell_touch.json").to_string(),
+            include_str!("../fixtures/sse_apply_patch_add.json").to_string(),
+            include_str!("../fixtures/sse_response_completed.json").to_string(),
+        ],
+    )
+    .await;
+
+    assert!(tmp_cwd.path().join("approved.txt").exists());
+    assert!(!tmp_cwd.path().join("test.md").exists());
+    Ok(())
+}
+
+/// SSE stream in which the model adds `path` with `apply_patch`.
+fn apply_patch_add_sse(path: &str) -> String {
+    serde_json::json!([
+        {
+            "type": "response.output_item.done",
+            "item": {
+                "type": "custom_tool_call",
+                "name": "apply_patch",
+                "input": format!("*** Begin Patch\n*** Add File: {path}\n+Hello world\n*** End Patch"),
+                "call_id": "__ID__"
+            }
+        },
+        {
+            "type": "response.completed",
+            "response": {
+                "id": "__ID__",
+                "usage": {
+                    "input_tokens": 0,
+                    "input_tokens_details": null,
+                    "output_tokens": 0,
+                    "output_tokens_details": null,
+                    "total_tokens": 0
+                },
+                "output": []
+            }
+        }
+    ])
+    .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn path_rules_match_normalized_paths_one_directory_per_star() -> anyhow::Result<()> {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return Ok(());
+    }
+
+    let tmp = tempdir().expect("failed to create temp dir");
+    let cwd = tmp.path().join("workspace");
+    std::fs::create_dir(&cwd)?;
+    let rules_path = cwd.join("approval-rules.toml");
+    std::fs::write(
+        &rules_path,
+        r#"
+[[rule]]
+path = "src/*"
+decision = "approve"
+"#,
+    )?;
+
+    run_e2e_exec_test_with_args(
+        &cwd,
+        &[
+            "-s",
+            "read-only",
+            "--approval-rules",
+            rules_path.to_str().expect("utf-8 path"),
+        ],
+        vec![
+            apply_patch_add_sse("src/approved.txt"),
+            apply_patch_add_sse("src/../../escaped.txt"),
+            apply_patch_add_sse("src/../root.txt"),
+            apply_patch_add_sse("src/nested/deep.txt"),
+            include_str!("../fixtures/sse_response_completed.json").to_string(),
+        ],
+    )
+    .await;
+
+    assert!(cwd.join("src/approved.txt").exists());
+    assert!(!tmp.path().join("escaped.txt").exists());
+    assert!(!cwd.join("root.txt").exists());
+    assert!(!cwd.join("src/nested/deep.txt").exists());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn approval_rules_keep_the_configured_approval_policy() -> anyhow::Result<()> {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return Ok(());
+    }
+
+    // CODEX_HOME is the working directory in these tests.
+    let tmp_cwd = tempdir().expect("failed to create temp dir");
+    std::fs::write(
+        tmp_cwd.path().join("config.toml"),
+        "approval_policy = \"never\"\n",
+    )?;
+    let rules_path = tmp_cwd.path().join("approval-rules.toml");
+    std::fs::write(&rules_path, "default = \"deny\"\n")?;
+
+    run_e2e_exec_test_with_args(
+        tmp_cwd.path(),
+        &[
+            "-s",
+            "danger-full-access",
+            "--confirm-danger-full-access",
+            "--approval-rules",
+            rules_path.to_str().expect("utf-8 path"),
+        ],
+        vec![
+            include_str!("../fixtures/sse_shell_touch.json").to_string(),
+            include_str!("../fixtures/sse_response_completed.json").to_string(),
+        ],
+    )
+    .await;
+
+    // Under `never` nothing is sent for approval, so the deny-all rules
+    // never see the command.
+    assert!(tmp_cwd.path().join("approved.txt").exists());
+    Ok(())
+}
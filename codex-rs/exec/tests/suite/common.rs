@@ -40,6 +40,22 @@ impl Respond for SeqResponder {
 /// server, and returns the response_streams in order for each api call. Runs
 /// the codex-exec command with the wiremock server as the model server.
 pub(crate) async fn run_e2e_exec_test(cwd: &Path, response_streams: Vec<String>) {
+    run_e2e_exec_test_with_args(
+        cwd,
+        &["-s", "danger-full-access", "--confirm-danger-full-access"],
+        response_streams,
+    )
+    .await;
+}
+
+/// Like [`run_e2e_exec_test`], but passes `args` to codex-exec in place of
+/// the default `-s danger-full-access --confirm-danger-full-access`. Returns what codex-exec printed to
+/// stdout.
+pub(crate) async fn run_e2e_exec_test_with_args(
+    cwd: &Path,
+    args: &[&str],
+    response_streams: Vec<String>,
+) -> String {
     let server = MockServer::start().await;
 
     let num_calls = response_streams.len();
@@ -57,7 +73,7 @@ pub(crate) async fn run_e2e_exec_test(cwd: &Path, response_streams: Vec<String>)
 
     let cwd = cwd.to_path_buf();
     let uri = server.uri();
-    Command::cargo_bin("codex-exec")
+    let assert = Command::cargo_bin("codex-exec")
         .context("should find binary for codex-exec")
         .expect("should find binary for codex-exec")
         .current_dir(cwd.clone())
@@ -65,9 +81,9 @@ pub(crate) async fn run_e2e_exec_test(cwd: &Path, response_streams: Vec<String>)
         .env("OPENAI_API_KEY", "dummy")
         .env("OPENAI_BASE_URL", format!("{uri}/v1"))
         .arg("--skip-git-repo-check")
-        .arg("-s")
-        .arg("danger-full-access")
+        .args(args)
         .arg("foo")
         .assert()
         .success();
+    String::from_utf8_lossy(&assert.get_output().stdout).into_owned()
 }
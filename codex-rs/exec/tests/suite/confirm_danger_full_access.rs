@@ -0,0 +1,23 @@
+#![allow(clippy::expect_used)]
+
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::process::Command;
+use tempfile::tempdir;
+
+/// `--sandbox danger-full-access` is refused before anything runs unless it
+/// is confirmed.
+#[test]
+fn unconfirmed_danger_full_access_is_rejected() {
+    let home = tempdir().expect("tempdir");
+    Command::cargo_bin("codex-exec")
+        .expect("should find binary for codex-exec")
+        .current_dir(home.path())
+        .env("CODEX_HOME", home.path())
+        .env("OPENAI_API_KEY", "dummy")
+        .env("OPENAI_BASE_URL", "http://127.0.0.1:9/v1")
+        .args(["--skip-git-repo-check", "-s", "danger-full-access", "foo"])
+        .assert()
+        .failure()
+        .stderr(contains("--confirm-danger-full-access"));
+}
@@ -1,4 +1,8 @@
 // Aggregates all former standalone integration tests as modules.
 mod apply_patch;
+mod approval_rules;
 mod common;
+mod confirm_danger_full_access;
 mod sandbox;
+mod show_reasoning;
+mod token_cost;
@@ -0,0 +1,72 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used)]
+
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use tempfile::tempdir;
+
+use crate::suite::common::run_e2e_exec_test_with_args;
+
+/// `--show-reasoning` prints the reasoning summary in its own section ahead
+/// of the answer, even when the config hides reasoning.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn show_reasoning_prints_reasoning_before_the_answer() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let tmp_cwd = tempdir().expect("failed to create temp dir");
+    let stdout = run_e2e_exec_test_with_args(
+        tmp_cwd.path(),
+        &[
+            "--color",
+            "never",
+            "-c",
+            "hide_agent_reasoning=true",
+            "--show-reasoning",
+        ],
+        vec![include_str!("../fixtures/sse_reasoning_then_message.json").to_string()],
+    )
+    .await;
+
+    let thinking = stdout.find("thinking\n").expect("no reasoning section");
+    let reasoning = stdout
+        .find("Looking for the answer")
+        .expect("no reasoning summary");
+    let answer = stdout.find("The answer is 42.").expect("no answer");
+    assert!(
+        thinking < reasoning && reasoning < answer,
+        "unexpected output order:\n{stdout}"
+    );
+}
+
+/// Without `--show-reasoning`, a reasoning summary that arrives in one piece
+/// keeps the usual "codex" header.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn reasoning_summary_keeps_codex_header_without_show_reasoning() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let tmp_cwd = tempdir().expect("failed to create temp dir");
+    let stdout = run_e2e_exec_test_with_args(
+        tmp_cwd.path(),
+        &["--color", "never"],
+        vec![include_str!("../fixtures/sse_reasoning_summary_then_message.json").to_string()],
+    )
+    .await;
+
+    assert!(
+        stdout.contains("codex\nLooking for the answer"),
+        "expected a codex header above the reasoning:\n{stdout}"
+    );
+    assert!(
+        !stdout.contains("thinking"),
+        "unexpected relabel:\n{stdout}"
+    );
+}
@@ -0,0 +1,54 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used)]
+
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use tempfile::tempdir;
+
+use crate::suite::common::run_e2e_exec_test_with_args;
+
+/// The token count of each response comes with its estimated cost when the
+/// model has prices, and alone when it does not.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn token_count_includes_estimated_cost_when_model_is_priced() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let fixture = include_str!("../fixtures/sse_message_with_usage.json");
+
+    let tmp_cwd = tempdir().expect("failed to create temp dir");
+    let stdout = run_e2e_exec_test_with_args(
+        tmp_cwd.path(),
+        &[
+            "--color",
+            "never",
+            "-m",
+            "local",
+            "-c",
+            "model_overrides.local.input_price_per_mtok=1.25",
+            "-c",
+            "model_overrides.local.output_price_per_mtok=10.0",
+        ],
+        vec![fixture.to_string()],
+    )
+    .await;
+    assert!(
+        stdout.contains("tokens used: 12000 (estimated cost $0.0325)\n"),
+        "unexpected output:\n{stdout}"
+    );
+
+    let tmp_cwd = tempdir().expect("failed to create temp dir");
+    let stdout = run_e2e_exec_test_with_args(
+        tmp_cwd.path(),
+        &["--color", "never", "-m", "local"],
+        vec![fixture.to_string()],
+    )
+    .await;
+    assert!(
+        stdout.contains("tokens used: 12000\n"),
+        "unexpected output:\n{stdout}"
+    );
+}
@@ -42,6 +42,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        stdin: None,
     };
 
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
@@ -52,6 +53,8 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         // writing to in the sandbox.
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
+        tracked_files_only: false,
+        allow_git_writes: false,
     };
     let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
     let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
@@ -142,6 +145,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        stdin: None,
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -228,3 +232,27 @@ async fn sandbox_blocks_dev_tcp_redirection() {
     // all images ship bash, so we guard against 127 as well.
     assert_network_blocked(&["bash", "-c", "echo hi > /dev/tcp/127.0.0.1/80"]).await;
 }
+
+#[tokio::test]
+async fn self_test_reports_disallowed_write_as_blocked_under_read_only() {
+    let cwd = tempfile::tempdir().expect("tempdir");
+    let probe_dir = tempfile::tempdir().expect("tempdir");
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let report = codex_core::sandbox_self_test(
+        &SandboxPolicy::ReadOnly,
+        cwd.path(),
+        probe_dir.path(),
+        &Some(PathBuf::from(sandbox_program)),
+    )
+    .await
+    .expect("self test should run");
+
+    assert_eq!(report.sandbox_type, Some(SandboxType::LinuxSeccomp));
+    let write = report
+        .probes
+        .iter()
+        .find(|result| result.probe == codex_core::SelfTestProbe::WriteOutsideWritableRoots)
+        .expect("write probe");
+    assert!(write.expected_blocked && write.blocked, "{report:?}");
+    assert!(report.all_passed(), "{report:?}");
+}
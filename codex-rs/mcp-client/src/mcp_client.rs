@@ -59,6 +59,36 @@ const CHANNEL_CAPACITY: usize = 128;
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// A request that got no reply because of the transport, as opposed to an
+/// error returned by the server. Callers can find it with
+/// `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportError {
+    /// The writer task is gone, so the request was not sent.
+    WriterClosed,
+    /// The request was dropped before a reply arrived.
+    ReplyChannelClosed,
+    /// No reply arrived within the request timeout. The server may still
+    /// have acted on the request.
+    TimedOut,
+}
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransportError::WriterClosed => {
+                f.write_str("failed to send message to writer task - channel closed")
+            }
+            TransportError::ReplyChannelClosed => {
+                f.write_str("response channel closed before a reply was received")
+            }
+            TransportError::TimedOut => f.write_str("request timed out"),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
@@ -233,9 +263,7 @@ impl McpClient {
 
         // Send to writer task.
         if self.outgoing_tx.send(message).await.is_err() {
-            return Err(anyhow!(
-                "failed to send message to writer task - channel closed"
-            ));
+            return Err(TransportError::WriterClosed.into());
         }
 
         // Await the response, optionally bounded by a timeout.
@@ -247,21 +275,17 @@ impl McpClient {
                         // Channel closed without a reply – remove the pending entry.
                         let mut guard = self.pending.lock().await;
                         guard.remove(&id);
-                        return Err(anyhow!(
-                            "response channel closed before a reply was received"
-                        ));
+                        return Err(TransportError::ReplyChannelClosed.into());
                     }
                     Err(_) => {
                         // Timed out. Remove the pending entry so we don't leak.
                         let mut guard = self.pending.lock().await;
                         guard.remove(&id);
-                        return Err(anyhow!("request timed out"));
+                        return Err(TransportError::TimedOut.into());
                     }
                 }
             }
-            None => rx
-                .await
-                .map_err(|_| anyhow!("response channel closed before a reply was received"))?,
+            None => rx.await.map_err(|_| TransportError::ReplyChannelClosed)?,
         };
 
         match msg {
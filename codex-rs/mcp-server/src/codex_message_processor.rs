@@ -16,6 +16,7 @@ use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::TurnAbortReason;
 use codex_login::AuthManager;
 use codex_protocol::mcp_protocol::AuthMode;
 use codex_protocol::mcp_protocol::GitDiffToRemoteResponse;
@@ -505,6 +506,10 @@ impl CodexMessageProcessor {
             model,
             effort,
             summary,
+            include_raw_reasoning,
+            allowed_tools,
+            max_output_tokens,
+            danger_full_access_confirmed,
         } = params;
 
         let Ok(conversation) = self
@@ -539,6 +544,10 @@ impl CodexMessageProcessor {
                 model,
                 effort,
                 summary,
+                include_raw_reasoning,
+                allowed_tools,
+                max_output_tokens,
+                danger_full_access_confirmed,
             })
             .await;
 
@@ -712,6 +721,7 @@ async fn apply_bespoke_event_handling(
             changes,
             reason,
             grant_root,
+            grant_paths,
         }) => {
             let params = ApplyPatchApprovalParams {
                 conversation_id,
@@ -719,6 +729,7 @@ async fn apply_bespoke_event_handling(
                 file_changes: changes,
                 reason,
                 grant_root,
+                grant_paths,
             };
             let value = serde_json::to_value(&params).unwrap_or_default();
             let rx = outgoing
@@ -752,8 +763,11 @@ async fn apply_bespoke_event_handling(
                 on_exec_approval_response(event_id, rx, conversation).await;
             });
         }
-        // If this is a TurnAborted, reply to any pending interrupt requests.
-        EventMsg::TurnAborted(turn_aborted_event) => {
+        // If the turn was interrupted, reply to any pending interrupt requests.
+        // Other aborts (errors, timeouts) leave them waiting for the interrupt.
+        EventMsg::TurnAborted(turn_aborted_event)
+            if turn_aborted_event.reason == TurnAbortReason::Interrupted =>
+        {
             let pending = {
                 let mut map = pending_interrupts.lock().await;
                 map.remove(&conversation_id.0).unwrap_or_default()
@@ -205,12 +205,14 @@ async fn run_codex_tool_session_inner(
                         call_id,
                         reason,
                         grant_root,
+                        grant_paths,
                         changes,
                     }) => {
                         handle_patch_approval_request(
                             call_id,
                             reason,
                             grant_root,
+                            grant_paths,
                             changes,
                             outgoing.clone(),
                             codex.clone(),
@@ -221,7 +223,9 @@ async fn run_codex_tool_session_inner(
                         .await;
                         continue;
                     }
-                    EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                    EventMsg::TaskComplete(TaskCompleteEvent {
+                        last_agent_message, ..
+                    }) => {
                         let text = match last_agent_message {
                             Some(msg) => msg.clone(),
                             None => "".to_string(),
@@ -259,20 +263,28 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::AgentReasoningRawContentDelta(_)
                     | EventMsg::TaskStarted(_)
                     | EventMsg::TokenCount(_)
+                    | EventMsg::PartialTokenCount(_)
                     | EventMsg::AgentReasoning(_)
                     | EventMsg::AgentReasoningSectionBreak(_)
                     | EventMsg::McpToolCallBegin(_)
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::McpListToolsResponse(_)
+                    | EventMsg::McpServerHealthResponse(_)
                     | EventMsg::ListCustomPromptsResponse(_)
+                    | EventMsg::CommandEvaluation(_)
+                    | EventMsg::GetDiffResponse(_)
+                    | EventMsg::ResolvedConfig(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandOutputDelta(_)
                     | EventMsg::ExecCommandEnd(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::StreamError(_)
                     | EventMsg::PatchApplyBegin(_)
+                    | EventMsg::PatchApplyProgress(_)
                     | EventMsg::PatchApplyEnd(_)
                     | EventMsg::TurnDiff(_)
+                    | EventMsg::CompactComplete(_)
+                    | EventMsg::TurnTiming(_)
                     | EventMsg::WebSearchBegin(_)
                     | EventMsg::WebSearchEnd(_)
                     | EventMsg::GetHistoryEntryResponse(_)
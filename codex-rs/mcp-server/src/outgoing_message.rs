@@ -260,6 +260,8 @@ mod tests {
                 model: "gpt-4o".to_string(),
                 history_log_id: 1,
                 history_entry_count: 1000,
+                label: None,
+                max_output_tokens: None,
             }),
         };
 
@@ -289,6 +291,8 @@ mod tests {
             model: "gpt-4o".to_string(),
             history_log_id: 1,
             history_entry_count: 1000,
+            label: None,
+            max_output_tokens: None,
         };
         let event = Event {
             id: "1".to_string(),
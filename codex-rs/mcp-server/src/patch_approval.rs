@@ -32,6 +32,8 @@ pub struct PatchApprovalElicitRequestParams {
     pub codex_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codex_grant_root: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codex_grant_paths: Vec<PathBuf>,
     pub codex_changes: HashMap<PathBuf, FileChange>,
 }
 
@@ -45,6 +47,7 @@ pub(crate) async fn handle_patch_approval_request(
     call_id: String,
     reason: Option<String>,
     grant_root: Option<PathBuf>,
+    grant_paths: Vec<PathBuf>,
     changes: HashMap<PathBuf, FileChange>,
     outgoing: Arc<OutgoingMessageSender>,
     codex: Arc<CodexConversation>,
@@ -71,6 +74,7 @@ pub(crate) async fn handle_patch_approval_request(
         codex_call_id: call_id,
         codex_reason: reason,
         codex_grant_root: grant_root,
+        codex_grant_paths: grant_paths,
         codex_changes: changes,
     };
     let params_json = match serde_json::to_value(&params) {
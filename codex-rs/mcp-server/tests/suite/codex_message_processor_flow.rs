@@ -320,6 +320,10 @@ async fn test_send_user_turn_changes_approval_policy_behavior() {
             model: "mock-model".to_string(),
             effort: ReasoningEffort::Medium,
             summary: ReasoningSummary::Auto,
+            include_raw_reasoning: false,
+            allowed_tools: None,
+            max_output_tokens: None,
+            danger_full_access_confirmed: false,
         })
         .await
         .expect("send sendUserTurn");
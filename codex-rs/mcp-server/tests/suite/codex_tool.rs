@@ -260,7 +260,8 @@ async fn patch_approval_triggers_elicitation() -> anyhow::Result<()> {
     let expected_elicitation_request = create_expected_patch_approval_elicitation_request(
         elicitation_request_id.clone(),
         expected_changes,
-        None, // No grant_root expected
+        Some(cwd.path().to_path_buf()),
+        vec![test_file.clone()],
         None, // No reason expected
         codex_request_id.to_string(),
         "1".to_string(),
@@ -378,6 +379,7 @@ fn create_expected_patch_approval_elicitation_request(
     elicitation_request_id: RequestId,
     changes: HashMap<PathBuf, FileChange>,
     grant_root: Option<PathBuf>,
+    grant_paths: Vec<PathBuf>,
     reason: Option<String>,
     codex_mcp_tool_call_id: String,
     codex_event_id: String,
@@ -404,6 +406,7 @@ fn create_expected_patch_approval_elicitation_request(
             codex_event_id,
             codex_reason: reason,
             codex_grant_root: grant_root,
+            codex_grant_paths: grant_paths,
             codex_changes: changes,
             codex_call_id: "call1234".to_string(),
         })?),
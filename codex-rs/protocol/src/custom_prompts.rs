@@ -7,4 +7,8 @@ pub struct CustomPrompt {
     pub name: String,
     pub path: PathBuf,
     pub content: String,
+    /// Names of the `{{name}}` placeholders in `content`, in order of first
+    /// appearance. Each must be given a value when the prompt is run.
+    #[serde(default)]
+    pub arguments: Vec<String>,
 }
@@ -266,6 +266,18 @@ pub struct SendUserTurnParams {
     pub model: String,
     pub effort: ReasoningEffort,
     pub summary: ReasoningSummary,
+    #[serde(default)]
+    pub include_raw_reasoning: bool,
+    /// Restrict the tools advertised for this turn to these names.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+    /// Cap on output tokens per model request for this turn only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    /// Confirms running this turn with `danger-full-access` in a sandboxed
+    /// conversation.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub danger_full_access_confirmed: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -356,6 +368,10 @@ pub struct ApplyPatchApprovalParams {
     /// for the remainder of the session (unclear if this is honored today).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grant_root: Option<PathBuf>,
+    /// Files the user can allow writes to for the remainder of the session,
+    /// a narrower alternative to `grant_root`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grant_paths: Vec<PathBuf>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
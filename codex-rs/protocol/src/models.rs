@@ -183,11 +183,26 @@ pub struct LocalShellExecAction {
 pub enum WebSearchAction {
     Search {
         query: String,
+        /// Results retrieved by the search, when the provider exposes them
+        /// (e.g. `action.sources`). Never sent back to the provider.
+        #[serde(default, skip_serializing)]
+        sources: Option<Vec<WebSearchResult>>,
     },
     #[serde(other)]
     Other,
 }
 
+/// A single result retrieved by a web search. Providers may only report the
+/// URL, so `title` and `snippet` are optional.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebSearchResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ReasoningItemReasoningSummary {
@@ -346,6 +361,48 @@ mod tests {
         assert_eq!(v.get("output").unwrap().as_str().unwrap(), "bad");
     }
 
+    #[test]
+    fn deserialize_web_search_call_sources() {
+        let json = r#"{
+            "type": "web_search_call",
+            "id": "ws_1",
+            "status": "completed",
+            "action": {
+                "type": "search",
+                "query": "rust 2024 edition",
+                "sources": [
+                    {"type": "url", "url": "https://doc.rust-lang.org/edition-guide/"},
+                    {"url": "https://blog.rust-lang.org/", "title": "Rust Blog", "snippet": "News"}
+                ]
+            }
+        }"#;
+
+        let item: ResponseItem = serde_json::from_str(json).unwrap();
+        let ResponseItem::WebSearchCall {
+            action: WebSearchAction::Search { query, sources },
+            ..
+        } = item
+        else {
+            panic!("expected web search call");
+        };
+        assert_eq!(query, "rust 2024 edition");
+        assert_eq!(
+            sources,
+            Some(vec![
+                WebSearchResult {
+                    title: None,
+                    url: "https://doc.rust-lang.org/edition-guide/".to_string(),
+                    snippet: None,
+                },
+                WebSearchResult {
+                    title: Some("Rust Blog".to_string()),
+                    url: "https://blog.rust-lang.org/".to_string(),
+                    snippet: Some("News".to_string()),
+                },
+            ])
+        );
+    }
+
     #[test]
     fn deserialize_shell_tool_call_params() {
         let json = r#"{
@@ -1,39 +1,73 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+/// Every variant carries `elevated`, set when the command runs through
+/// `sudo`, `doas`, `su` or `pkexec`. `cmd` then includes that prefix while the
+/// variant still describes the command being elevated.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ParsedCommand {
     Read {
         cmd: String,
         name: String,
+        #[serde(default)]
+        elevated: bool,
     },
     ListFiles {
         cmd: String,
         path: Option<String>,
+        #[serde(default)]
+        elevated: bool,
     },
     Search {
         cmd: String,
         query: Option<String>,
         path: Option<String>,
+        #[serde(default)]
+        elevated: bool,
     },
     Format {
         cmd: String,
         tool: Option<String>,
         targets: Option<Vec<String>>,
+        #[serde(default)]
+        elevated: bool,
     },
     Test {
         cmd: String,
+        #[serde(default)]
+        elevated: bool,
     },
     Lint {
         cmd: String,
         tool: Option<String>,
         targets: Option<Vec<String>>,
+        #[serde(default)]
+        elevated: bool,
     },
     Noop {
         cmd: String,
+        #[serde(default)]
+        elevated: bool,
     },
     Unknown {
         cmd: String,
+        #[serde(default)]
+        elevated: bool,
     },
 }
+
+impl ParsedCommand {
+    pub fn is_elevated(&self) -> bool {
+        match self {
+            ParsedCommand::Read { elevated, .. }
+            | ParsedCommand::ListFiles { elevated, .. }
+            | ParsedCommand::Search { elevated, .. }
+            | ParsedCommand::Format { elevated, .. }
+            | ParsedCommand::Test { elevated, .. }
+            | ParsedCommand::Lint { elevated, .. }
+            | ParsedCommand::Noop { elevated, .. }
+            | ParsedCommand::Unknown { elevated, .. } => *elevated,
+        }
+    }
+}
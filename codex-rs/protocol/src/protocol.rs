@@ -8,10 +8,12 @@
 //! between user and agent.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::custom_prompts::CustomPrompt;
@@ -28,6 +30,7 @@ use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use crate::message_history::HistoryEntry;
 use crate::models::ResponseItem;
+use crate::models::WebSearchResult;
 use crate::parse_command::ParsedCommand;
 use crate::plan_tool::UpdatePlanArgs;
 
@@ -56,6 +59,13 @@ pub enum Op {
     /// This server sends [`EventMsg::TurnAborted`] in response.
     Interrupt,
 
+    /// 取消单个正在执行的工具调用，不中止当前轮次
+    /// Cancel a single in-flight shell or MCP tool call without aborting the
+    /// turn. The call's end event (`ExecCommandEnd`, `PatchApplyEnd` or
+    /// `McpToolCallEnd`) carries a cancelled result, and the turn continues.
+    /// An [`EventMsg::Error`] is sent if no such call is in flight.
+    CancelToolCall { call_id: String },
+
     /// 来自用户的输入
     /// Input from the user
     UserInput {
@@ -97,6 +107,36 @@ pub enum Op {
         /// 仅当模型配置为使用推理时才会生效
         /// Will only be honored if the model is configured to use reasoning.
         summary: ReasoningSummaryConfig,
+
+        /// 是否在本轮次中发送 `AgentReasoningRawContent*` 事件。默认关闭；
+        /// 无论是否开启，推理项都会照常记录到对话历史中
+        /// Whether `AgentReasoningRawContent*` events are emitted for this
+        /// turn. Off by default; reasoning items are recorded in the
+        /// conversation history either way.
+        #[serde(default)]
+        include_raw_reasoning: bool,
+
+        /// 仅对本轮次生效的可用工具名称列表（如 `local_shell`、`apply_patch`）；
+        /// 为 `None` 时提供会话的全部工具
+        /// Names of the tools (e.g. `local_shell`, `apply_patch`) the model
+        /// may use in this turn only. Other tools are not advertised, and a
+        /// call to one gets an error output instead of running. `None` allows
+        /// every tool the session has.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        allowed_tools: Option<Vec<String>>,
+
+        /// 仅对本轮次生效的最大输出令牌数；为 `None` 时使用会话默认值
+        /// Cap on output tokens per model request for this turn only. `None`
+        /// uses the session default (`max_output_tokens` in the config).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_output_tokens: Option<u64>,
+
+        /// 确认本轮次切换到 `DangerFullAccess`
+        /// Confirms running this turn with `DangerFullAccess` when the
+        /// session is sandboxed; see the field of the same name on
+        /// [`Op::OverrideTurnContext`].
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        danger_full_access_confirmed: bool,
     },
 
     /// 覆盖后续轮次的持久性轮次上下文的部分内容
@@ -139,8 +179,26 @@ pub enum Op {
         /// Updated reasoning summary preference (honored only for reasoning-capable models).
         #[serde(skip_serializing_if = "Option::is_none")]
         summary: Option<ReasoningSummaryConfig>,
+
+        /// 确认切换到 `DangerFullAccess`
+        /// Confirms a switch of `sandbox_policy` to `DangerFullAccess`. Unless
+        /// the session disables `confirm_danger_full_access`, such a switch
+        /// is rejected without it.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        danger_full_access_confirmed: bool,
     },
 
+    /// Set or clear headers sent with every subsequent model request, e.g. to
+    /// toggle experimental features behind a gateway. A `None` value removes
+    /// the header; headers not listed keep their current value.
+    SetExperimentHeaders {
+        headers: HashMap<String, Option<String>>,
+    },
+
+    /// Set or clear (`None`) the human-readable label of the conversation.
+    /// The label is recorded in the rollout so a resumed session keeps it.
+    SetConversationLabel { label: Option<String> },
+
     /// Approve a command execution
     ExecApproval {
         /// The id of the submission we are approving
@@ -177,12 +235,76 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::McpListToolsResponse`.
     ListMcpTools,
 
+    /// Check that every configured MCP server still answers requests.
+    /// Reply is delivered via `EventMsg::McpServerHealthResponse`.
+    PingMcpServers,
+
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Run the custom prompt `name` as user input, substituting each
+    /// `{{arg}}` placeholder with the matching entry of `arguments`.
+    /// Missing arguments are reported via `EventMsg::Error`.
+    RunCustomPrompt {
+        name: String,
+        arguments: HashMap<String, String>,
+    },
+
+    /// Request the effective configuration of the session, after profiles,
+    /// project settings and CLI overrides have been merged.
+    /// Reply is delivered via `EventMsg::ResolvedConfig`.
+    GetConfig,
+
+    /// Request the unified diff of the changes Codex has made so far.
+    /// Reply is delivered via `EventMsg::GetDiffResponse`.
+    GetDiff {
+        #[serde(default)]
+        scope: DiffScope,
+    },
+
+    /// Run the most recent user turn again. That turn's input and everything
+    /// recorded after it are removed from the history first, so the model
+    /// sees the prompt once. `model`, when set, applies to the retried turn
+    /// only. Fails with an `EventMsg::Error` while a task is running or when
+    /// there is no user turn to retry.
+    RetryLastTurn {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+    },
+
+    /// Evaluate how `command` would be handled under the current approval and
+    /// sandbox policies, without running it.
+    /// Reply is delivered via `EventMsg::CommandEvaluation`.
+    EvaluateCommand {
+        command: Vec<String>,
+        /// Working directory for the command. Relative paths are resolved
+        /// against the session's cwd.
+        cwd: PathBuf,
+    },
+
+    /// Stash the uncommitted changes in the session's cwd (untracked files
+    /// included) before a risky operation. Nothing is stashed on a clean
+    /// tree. The outcome is reported as a `BackgroundEvent`; stashes that are
+    /// still outstanding at shutdown are restored.
+    AutoStash {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+
+    /// Restore the most recent stash created with [`Op::AutoStash`].
+    /// The outcome is reported as a `BackgroundEvent`.
+    RestoreStash,
+
+    /// Move the session's rollout file to `path` and keep recording the
+    /// session there. Relative paths are resolved against the session's cwd.
+    /// Fails if `path` already exists or cannot be created. The outcome is
+    /// reported as a `BackgroundEvent`.
+    SaveSessionAs { path: PathBuf },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
-    /// to generate a summary which will be returned as an AgentMessage event.
+    /// to generate a summary which replaces the history. Completion is reported as a
+    /// `CompactComplete` event, followed by an AgentMessage event for older clients.
     Compact,
     /// Request to shut down codex instance.
     Shutdown,
@@ -264,6 +386,19 @@ pub enum SandboxPolicy {
         /// writable roots on UNIX. Defaults to `false`.
         #[serde(default)]
         exclude_slash_tmp: bool,
+
+        /// When set to `true`, writable roots inside a git repository only
+        /// allow writes to files git currently tracks, so the agent can edit
+        /// existing sources but not create new files. Roots outside a git
+        /// repository stay fully writable. Defaults to `false`.
+        #[serde(default)]
+        tracked_files_only: bool,
+
+        /// When set to `true`, the top-level `.git` directory of each writable
+        /// root is writable too instead of being kept read-only. Defaults to
+        /// `false`.
+        #[serde(default)]
+        allow_git_writes: bool,
     },
 }
 
@@ -278,6 +413,12 @@ pub struct WritableRoot {
 
     /// Also absolute paths, by construction.
     pub read_only_subpaths: Vec<PathBuf>,
+
+    /// When set, only these files (absolute paths) under `root` are
+    /// writable. Used for `tracked_files_only`; listing tracked files runs
+    /// git, so [`SandboxPolicy::get_writable_roots_with_cwd`] leaves this
+    /// unset and callers fill it in from a list they computed once.
+    pub tracked_files: Option<Arc<HashSet<PathBuf>>>,
 }
 
 impl WritableRoot {
@@ -294,7 +435,10 @@ impl WritableRoot {
             }
         }
 
-        true
+        match &self.tracked_files {
+            Some(tracked_files) => tracked_files.contains(path),
+            None => true,
+        }
     }
 }
 
@@ -323,6 +467,8 @@ impl SandboxPolicy {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            tracked_files_only: false,
+            allow_git_writes: false,
         }
     }
 
@@ -347,6 +493,18 @@ impl SandboxPolicy {
         }
     }
 
+    /// Whether writes are limited to files tracked by git. The tracked files
+    /// themselves are not listed here; see [`WritableRoot::tracked_files`].
+    pub fn tracked_files_only(&self) -> bool {
+        match self {
+            SandboxPolicy::DangerFullAccess => false,
+            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::WorkspaceWrite {
+                tracked_files_only, ..
+            } => *tracked_files_only,
+        }
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
@@ -358,6 +516,8 @@ impl SandboxPolicy {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
+                tracked_files_only: _,
+                allow_git_writes,
                 network_access: _,
             } => {
                 // Start from explicitly configured writable roots.
@@ -396,25 +556,47 @@ impl SandboxPolicy {
                     .map(|writable_root| {
                         let mut subpaths = Vec::new();
                         let top_level_git = writable_root.join(".git");
-                        if top_level_git.is_dir() {
+                        if !allow_git_writes && top_level_git.is_dir() {
                             subpaths.push(top_level_git);
                         }
+                        // Project-level Codex configuration must not be
+                        // rewritten by the agent without approval.
+                        let top_level_codex = writable_root.join(".codex");
+                        if top_level_codex.is_dir() {
+                            subpaths.push(top_level_codex);
+                        }
                         WritableRoot {
                             root: writable_root,
                             read_only_subpaths: subpaths,
+                            tracked_files: None,
                         }
                     })
                     .collect()
             }
         }
     }
+
+    /// Like [`Self::get_writable_roots_with_cwd`], but `codex_home` (Codex's
+    /// own configuration) also stays read-only under any root containing it.
+    pub fn get_writable_roots_with_cwd_and_codex_home(
+        &self,
+        cwd: &Path,
+        codex_home: &Path,
+    ) -> Vec<WritableRoot> {
+        let mut roots = self.get_writable_roots_with_cwd(cwd);
+        for root in &mut roots {
+            if codex_home.starts_with(&root.root) {
+                root.read_only_subpaths.push(codex_home.to_path_buf());
+            }
+        }
+        roots
+    }
 }
 
 /// User input
 #[non_exhaustive]
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
-
 // 将用户输入（可能包含一个文本和多个图片），这里将其拆解为多个 InputItem
 /// 用户输入项
 /// User input
@@ -472,6 +654,12 @@ pub enum EventMsg {
     /// used in the current session.
     TokenCount(TokenUsage),
 
+    /// 当前模型响应迄今为止的令牌用量（仅限流式报告用量的提供方）
+    /// Usage of the model response in progress so far, from providers that
+    /// stream it. Each value replaces the previous one and is not additive:
+    /// the response's final usage is reported once by `TokenCount`.
+    PartialTokenCount(TokenUsage),
+
     /// 代理文本输出消息
     /// Agent text output message
     AgentMessage(AgentMessageEvent),
@@ -527,20 +715,45 @@ pub enum EventMsg {
     /// `ExecCommandBegin` so front‑ends can show progress indicators.
     PatchApplyBegin(PatchApplyBeginEvent),
 
+    /// A file of an `apply_patch` call has been written. Sent once per file,
+    /// in path order, between `PatchApplyBegin` and `PatchApplyEnd`.
+    PatchApplyProgress(PatchApplyProgressEvent),
+
     /// Notification that a patch application has finished.
     PatchApplyEnd(PatchApplyEndEvent),
 
     TurnDiff(TurnDiffEvent),
 
+    /// The conversation history was replaced by a summary in response to
+    /// `Op::Compact`.
+    CompactComplete(CompactCompleteEvent),
+
+    /// Latency of the model request that just completed. Only emitted when
+    /// `report_turn_timing` is enabled.
+    TurnTiming(TurnTimingEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
+    /// Health of each configured MCP server, in response to
+    /// `Op::PingMcpServers` or from the periodic background check.
+    McpServerHealthResponse(McpServerHealthResponseEvent),
+
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// Response to EvaluateCommand.
+    CommandEvaluation(CommandEvaluationEvent),
+
+    /// Response to GetDiff.
+    GetDiffResponse(GetDiffResponseEvent),
+
+    /// Response to GetConfig.
+    ResolvedConfig(ResolvedConfigEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -561,6 +774,31 @@ pub struct ErrorEvent {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TaskCompleteEvent {
     pub last_agent_message: Option<String>,
+    /// Why the task stopped. Older producers omit this field, in which case
+    /// the task is assumed to have completed normally.
+    /// 任务结束的原因；旧版本不会发送该字段，此时视为正常完成。
+    #[serde(default)]
+    pub reason: TaskCompleteReason,
+    /// Whether the task applied a patch or ran a command that may have
+    /// written files, including files left out of the turn diff.
+    /// 任务是否应用了补丁或运行了可能写文件的命令（包括不计入差异的文件）。
+    #[serde(default)]
+    pub produced_changes: bool,
+    /// Whether the task emitted at least one `AgentMessage`.
+    /// 任务是否发出了至少一条 `AgentMessage`。
+    #[serde(default)]
+    pub produced_message: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskCompleteReason {
+    /// The model finished the task on its own.
+    #[default]
+    Completed,
+    /// The turn ran longer than `max_turn_duration`, so no further model
+    /// calls were made after the last tool call completed.
+    TimeBudgetExceeded,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -630,6 +868,34 @@ impl TokenUsage {
         let remaining = effective_window.saturating_sub(used);
         ((remaining as f32 / effective_window as f32) * 100.0).clamp(0.0, 100.0) as u8
     }
+
+    /// Estimated cost of this usage, in the currency of `pricing`. Cached
+    /// input tokens are billed at the cached rate, the rest of the input at
+    /// the regular input rate.
+    pub fn estimated_cost(&self, pricing: &ModelPricing) -> f64 {
+        let per_token = |price_per_mtok: f64| price_per_mtok / 1_000_000.0;
+        self.non_cached_input() as f64 * per_token(pricing.input_price_per_mtok)
+            + self.cached_input() as f64 * per_token(pricing.cached_input_price_per_mtok())
+            + self.output_tokens as f64 * per_token(pricing.output_price_per_mtok)
+    }
+}
+
+/// Price of a model per million tokens (USD for built-in models).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct ModelPricing {
+    pub input_price_per_mtok: f64,
+    pub output_price_per_mtok: f64,
+    /// Price for input tokens served from the prompt cache. When unset,
+    /// cached input is billed like regular input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input_price_per_mtok: Option<f64>,
+}
+
+impl ModelPricing {
+    pub fn cached_input_price_per_mtok(&self) -> f64 {
+        self.cached_input_price_per_mtok
+            .unwrap_or(self.input_price_per_mtok)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -723,6 +989,10 @@ pub struct McpToolCallEndEvent {
     pub duration: Duration,
     /// Result of the tool call. Note this could be an error.
     pub result: Result<CallToolResult, String>,
+    /// Requests sent to the server, including retries after transport
+    /// failures.
+    #[serde(default)]
+    pub attempts: u32,
 }
 
 impl McpToolCallEndEvent {
@@ -743,6 +1013,10 @@ pub struct WebSearchBeginEvent {
 pub struct WebSearchEndEvent {
     pub call_id: String,
     pub query: String,
+    /// Results retrieved by the search. `None` when the provider does not
+    /// expose them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<WebSearchResult>>,
 }
 
 /// Response payload for `Op::GetHistory` containing the current session's
@@ -753,6 +1027,10 @@ pub struct ConversationHistoryResponseEvent {
     pub entries: Vec<ResponseItem>,
 }
 
+/// Exec events are ordered per call: for a given `call_id`, the
+/// `ExecCommandBegin` precedes all of its `ExecCommandOutputDelta` events,
+/// which precede its `ExecCommandEnd`. Events of different calls may
+/// interleave, so clients must key their state on `call_id`.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ExecCommandBeginEvent {
     /// Identifier so this can be paired with the ExecCommandEnd event.
@@ -775,12 +1053,19 @@ pub struct ExecCommandEndEvent {
     /// Captured aggregated output
     #[serde(default)]
     pub aggregated_output: String,
-    /// The command's exit code.
+    /// The command's exit code. `124` when the command timed out.
     pub exit_code: i32,
     /// The duration of the command execution.
     pub duration: Duration,
+    /// Whether the command was killed because it exceeded its timeout.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
     /// Formatted output from the command, as seen by the model.
     pub formatted_output: String,
+    /// Peak resident set size of the command, in bytes, where the platform
+    /// can measure it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rss_bytes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -825,11 +1110,28 @@ pub struct ApplyPatchApprovalRequestEvent {
     /// When set, the agent is asking the user to allow writes under this root for the remainder of the session.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grant_root: Option<PathBuf>,
+    /// Files the user can allow writes to for the remainder of the session,
+    /// a narrower alternative to `grant_root`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grant_paths: Vec<PathBuf>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BackgroundEventEvent {
     pub message: String,
+    #[serde(default)]
+    pub level: BackgroundEventLevel,
+}
+
+/// Severity of a [`BackgroundEventEvent`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundEventLevel {
+    #[default]
+    Info,
+    /// Something the user should look at, e.g. a patch that touched a file
+    /// outside the sandbox's writable roots.
+    Warn,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -845,6 +1147,22 @@ pub struct PatchApplyBeginEvent {
     pub auto_approved: bool,
     /// The changes to be applied.
     pub changes: HashMap<PathBuf, FileChange>,
+    /// If true, the patch is only validated and previewed; no files are
+    /// written.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PatchApplyProgressEvent {
+    /// Identifier of the PatchApplyBegin this file belongs to.
+    pub call_id: String,
+    /// The file that was written (its source path for a move).
+    pub path: PathBuf,
+    /// Zero-based position of `path` among the patch's files.
+    pub index: usize,
+    /// Number of files changed by the patch.
+    pub total: usize,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -855,13 +1173,118 @@ pub struct PatchApplyEndEvent {
     pub stdout: String,
     /// Captured stderr (parser errors, IO failures, etc.).
     pub stderr: String,
-    /// Whether the patch was applied successfully.
+    /// Whether the patch was applied successfully. For a dry run, whether it
+    /// would apply cleanly.
     pub success: bool,
+    /// If true, no files were written and `stdout` holds the unified diff the
+    /// patch would produce.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Files the patch could not be applied to, parsed from `stderr`. Empty on
+    /// success or when the failure is not tied to a particular file.
+    #[serde(default)]
+    pub conflicts: Vec<PatchConflict>,
+}
+
+/// A file `apply_patch` failed on, and why.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PatchConflict {
+    pub path: PathBuf,
+    pub reason: PatchConflictReason,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchConflictReason {
+    /// The lines or context the hunk expects are not in the file, usually
+    /// because the patch was written against a stale copy.
+    ContextMismatch,
+    /// The file to update or delete does not exist.
+    MissingFile,
+    /// The file could not be read or written for lack of permissions.
+    PermissionDenied,
+    /// Any other I/O failure.
+    Other,
+}
+
+impl fmt::Display for PatchConflictReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PatchConflictReason::ContextMismatch => "context mismatch",
+            PatchConflictReason::MissingFile => "missing file",
+            PatchConflictReason::PermissionDenied => "permission denied",
+            PatchConflictReason::Other => "I/O error",
+        })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TurnDiffEvent {
     pub unified_diff: String,
+    /// Changed files left out of `unified_diff` because they match the
+    /// configured `diff_ignore_patterns`.
+    #[serde(default)]
+    pub suppressed_files: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CompactCompleteEvent {
+    /// Usage of the summarization request. Its input is the whole context
+    /// before compaction.
+    pub token_usage_before: TokenUsage,
+    /// Usage of the compacted context, which holds only the summary. The
+    /// compaction itself does not measure it, so this is `None` until a
+    /// request on the compacted context reports its usage.
+    #[serde(default)]
+    pub token_usage_after: Option<TokenUsage>,
+    /// The summary that replaced the conversation history.
+    pub summary: String,
+}
+
+impl CompactCompleteEvent {
+    /// Context window tokens freed by the compaction, once
+    /// `token_usage_after` is known.
+    pub fn tokens_saved(&self) -> Option<u64> {
+        let after = self.token_usage_after.as_ref()?;
+        Some(
+            self.token_usage_before
+                .tokens_in_context_window()
+                .saturating_sub(after.tokens_in_context_window()),
+        )
+    }
+}
+
+/// Timings of one model request, measured from just before it was sent.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TurnTimingEvent {
+    /// Time until the first model output (text, reasoning or an output item)
+    /// arrived. `None` if the response completed without any.
+    pub time_to_first_token: Option<Duration>,
+    /// Time until the response completed.
+    pub total_duration: Duration,
+}
+
+/// Which changes `Op::GetDiff` should cover.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffScope {
+    /// Changes made by the running task, or by the most recent one if idle.
+    #[default]
+    CurrentTurn,
+    /// All changes made since the session started.
+    Cumulative,
+}
+
+/// Response payload for `Op::GetDiff`. Shaped like [`TurnDiffEvent`]; the
+/// diff is empty when there are no changes in the requested scope.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetDiffResponseEvent {
+    pub scope: DiffScope,
+    pub unified_diff: String,
+    /// Changed files left out of `unified_diff` because they match the
+    /// configured `diff_ignore_patterns`.
+    #[serde(default)]
+    pub suppressed_files: usize,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -880,12 +1303,70 @@ pub struct McpListToolsResponseEvent {
     pub tools: std::collections::HashMap<String, McpTool>,
 }
 
+/// Response payload for `Op::PingMcpServers`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct McpServerHealthResponseEvent {
+    /// Server name -> health.
+    pub servers: std::collections::HashMap<String, McpServerHealth>,
+}
+
+/// Result of checking a single MCP server.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum McpServerHealth {
+    /// The server answered a `tools/list` request.
+    Healthy,
+    /// The server failed to start, or did not answer in time.
+    Unreachable { error: String },
+}
+
 /// Response payload for `Op::ListCustomPrompts`.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
 }
 
+/// Response payload for `Op::GetConfig`. Secrets such as API keys and
+/// provider headers are never included.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ResolvedConfigEvent {
+    pub model: String,
+    /// Key of the provider in the `model_providers` map.
+    pub model_provider_id: String,
+    pub cwd: PathBuf,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    pub reasoning_effort: ReasoningEffortConfig,
+    pub reasoning_summary: ReasoningSummaryConfig,
+    /// Names of the tools offered to the model, including MCP tools.
+    pub tools: Vec<String>,
+}
+
+/// Response payload for `Op::EvaluateCommand`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CommandEvaluationEvent {
+    pub command: Vec<String>,
+    /// The resolved working directory the command was evaluated for.
+    pub cwd: PathBuf,
+    pub assessment: SafetyAssessment,
+}
+
+/// What would happen if the agent tried to run a command.
+/// 代理尝试运行某条命令时会发生的情况。
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum SafetyAssessment {
+    /// The command would run without asking the user.
+    AutoApprove {
+        /// Whether it would run inside the platform sandbox.
+        sandboxed: bool,
+    },
+    /// The user would be asked to approve the command.
+    AskUser,
+    /// The command would be refused outright.
+    Reject { reason: String },
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct SessionConfiguredEvent {
     /// Unique id for this session.
@@ -899,6 +1380,14 @@ pub struct SessionConfiguredEvent {
 
     /// Current number of entries in the history log.
     pub history_entry_count: usize,
+
+    /// Human-readable label of the conversation, if one was set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// Session-wide cap on output tokens per model request, if configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
@@ -910,9 +1399,14 @@ pub enum ReviewDecision {
 
     /// User has approved this command and wants to automatically approve any
     /// future identical instances (`command` and `cwd` match exactly) for the
-    /// remainder of the session.
+    /// remainder of the session. For a patch, it instead allows writes under
+    /// the request's `grant_root` for the remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this patch and wants writes to exactly the request's
+    /// `grant_paths` allowed for the remainder of the session.
+    ApprovedPathsForSession,
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -952,14 +1446,95 @@ pub struct TurnAbortedEvent {
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum TurnAbortReason {
+    /// The user interrupted the turn.
     Interrupted,
+    /// A new task replaced the running one.
     Replaced,
+    /// The model request failed, after any retries. An `Error` event with
+    /// the same message precedes the abort.
+    StreamError { message: String },
+    /// The model request timed out.
+    Timeout,
+    /// The user declined an approval the turn needed.
+    ApprovalDenied,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn usage(input: u64, cached: Option<u64>, output: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            cached_input_tokens: cached,
+            output_tokens: output,
+            reasoning_output_tokens: None,
+            total_tokens: input + output,
+        }
+    }
+
+    #[test]
+    fn turn_abort_reasons_round_trip() {
+        let cases = [
+            (TurnAbortReason::Interrupted, r#"{"reason":"interrupted"}"#),
+            (TurnAbortReason::Replaced, r#"{"reason":"replaced"}"#),
+            (
+                TurnAbortReason::StreamError {
+                    message: "stream disconnected".to_string(),
+                },
+                r#"{"reason":{"stream_error":{"message":"stream disconnected"}}}"#,
+            ),
+            (TurnAbortReason::Timeout, r#"{"reason":"timeout"}"#),
+            (
+                TurnAbortReason::ApprovalDenied,
+                r#"{"reason":"approval_denied"}"#,
+            ),
+        ];
+        for (reason, expected) in cases {
+            let event = TurnAbortedEvent {
+                reason: reason.clone(),
+            };
+            let serialized = serde_json::to_string(&event).unwrap();
+            assert_eq!(serialized, expected);
+            let deserialized: TurnAbortedEvent = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(deserialized.reason, reason);
+        }
+    }
+
+    #[test]
+    fn estimated_cost_without_cached_input() {
+        let pricing = ModelPricing {
+            input_price_per_mtok: 2.0,
+            output_price_per_mtok: 8.0,
+            cached_input_price_per_mtok: Some(0.5),
+        };
+
+        // 1M input at $2 + 0.5M output at $8.
+        let cost = usage(1_000_000, None, 500_000).estimated_cost(&pricing);
+        assert!((cost - 6.0).abs() < 1e-9, "cost was {cost}");
+    }
+
+    #[test]
+    fn estimated_cost_bills_cached_input_at_cached_rate() {
+        let pricing = ModelPricing {
+            input_price_per_mtok: 2.0,
+            output_price_per_mtok: 8.0,
+            cached_input_price_per_mtok: Some(0.5),
+        };
+
+        // 200k non-cached input at $2 + 800k cached at $0.5 + 100k output at $8.
+        let cost = usage(1_000_000, Some(800_000), 100_000).estimated_cost(&pricing);
+        assert!((cost - 1.6).abs() < 1e-9, "cost was {cost}");
+
+        // Without a cached rate, cached input costs the same as regular input.
+        let no_discount = ModelPricing {
+            cached_input_price_per_mtok: None,
+            ..pricing
+        };
+        let cost = usage(1_000_000, Some(800_000), 100_000).estimated_cost(&no_discount);
+        assert!((cost - 2.8).abs() < 1e-9, "cost was {cost}");
+    }
+
     /// Serialize Event to verify that its JSON representation has the expected
     /// amount of nesting.
     #[test]
@@ -972,6 +1547,8 @@ mod tests {
                 model: "codex-mini-latest".to_string(),
                 history_log_id: 0,
                 history_entry_count: 0,
+                label: None,
+                max_output_tokens: None,
             }),
         };
         let serialized = serde_json::to_string(&event).unwrap();
@@ -980,4 +1557,189 @@ mod tests {
             r#"{"id":"1234","msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"codex-mini-latest","history_log_id":0,"history_entry_count":0}}"#
         );
     }
+
+    #[test]
+    fn serialize_web_search_end_results() {
+        let event = EventMsg::WebSearchEnd(WebSearchEndEvent {
+            call_id: "ws_1".to_string(),
+            query: "codex".to_string(),
+            results: Some(vec![WebSearchResult {
+                title: Some("Codex".to_string()),
+                url: "https://example.com/codex".to_string(),
+                snippet: None,
+            }]),
+        });
+        let serialized = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"web_search_end","call_id":"ws_1","query":"codex","results":[{"title":"Codex","url":"https://example.com/codex"}]}"#
+        );
+
+        // Providers without results omit the field entirely.
+        let without = EventMsg::WebSearchEnd(WebSearchEndEvent {
+            call_id: "ws_2".to_string(),
+            query: "codex".to_string(),
+            results: None,
+        });
+        let serialized = serde_json::to_string(&without).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"web_search_end","call_id":"ws_2","query":"codex"}"#
+        );
+        let EventMsg::WebSearchEnd(round_trip) = serde_json::from_str(&serialized).unwrap() else {
+            panic!("expected WebSearchEnd");
+        };
+        assert_eq!(round_trip.results, None);
+    }
+
+    #[test]
+    fn serialize_resolved_config() {
+        let event = EventMsg::ResolvedConfig(ResolvedConfigEvent {
+            model: "o3".to_string(),
+            model_provider_id: "openai".to_string(),
+            cwd: PathBuf::from("/repo"),
+            approval_policy: AskForApproval::OnRequest,
+            sandbox_policy: SandboxPolicy::ReadOnly,
+            reasoning_effort: ReasoningEffortConfig::Medium,
+            reasoning_summary: ReasoningSummaryConfig::Auto,
+            tools: vec!["shell".to_string(), "update_plan".to_string()],
+        });
+        let serialized = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"resolved_config","model":"o3","model_provider_id":"openai","cwd":"/repo","approval_policy":"on-request","sandbox_policy":{"mode":"read-only"},"reasoning_effort":"medium","reasoning_summary":"auto","tools":["shell","update_plan"]}"#
+        );
+        let EventMsg::ResolvedConfig(round_trip) = serde_json::from_str(&serialized).unwrap()
+        else {
+            panic!("expected ResolvedConfig");
+        };
+        assert_eq!(round_trip.tools, vec!["shell", "update_plan"]);
+
+        let op = serde_json::to_string(&Op::GetConfig).unwrap();
+        assert_eq!(op, r#"{"type":"get_config"}"#);
+    }
+
+    #[test]
+    fn serialize_evaluate_command_round_trip() {
+        let op = Op::EvaluateCommand {
+            command: vec!["ls".to_string(), "-l".to_string()],
+            cwd: PathBuf::from("/repo"),
+        };
+        let serialized = serde_json::to_string(&op).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"evaluate_command","command":["ls","-l"],"cwd":"/repo"}"#
+        );
+
+        let event = EventMsg::CommandEvaluation(CommandEvaluationEvent {
+            command: vec!["rm".to_string(), "-rf".to_string(), "build".to_string()],
+            cwd: PathBuf::from("/repo"),
+            assessment: SafetyAssessment::Reject {
+                reason: "blocked".to_string(),
+            },
+        });
+        let serialized = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"command_evaluation","command":["rm","-rf","build"],"cwd":"/repo","assessment":{"decision":"reject","reason":"blocked"}}"#
+        );
+        let EventMsg::CommandEvaluation(round_trip) = serde_json::from_str(&serialized).unwrap()
+        else {
+            panic!("expected CommandEvaluation");
+        };
+        assert_eq!(
+            round_trip.assessment,
+            SafetyAssessment::Reject {
+                reason: "blocked".to_string()
+            }
+        );
+    }
+
+    fn workspace_write(tracked_files_only: bool) -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only,
+            allow_git_writes: false,
+        }
+    }
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .arg("-C")
+            .arg(repo)
+            .args(args)
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn tracked_files_limit_writes_to_listed_files() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let repo = tmp.path();
+
+        let mut roots = workspace_write(true).get_writable_roots_with_cwd(repo);
+        assert_eq!(roots.len(), 1);
+        // Listing tracked files is left to the caller.
+        assert_eq!(roots[0].tracked_files, None);
+        assert!(roots[0].is_path_writable(&repo.join("untracked.txt")));
+
+        let root = &mut roots[0];
+        root.tracked_files = Some(Arc::new(HashSet::from([
+            repo.join("tracked.txt"),
+            repo.join("src/lib.rs"),
+        ])));
+        assert!(root.is_path_writable(&repo.join("tracked.txt")));
+        assert!(root.is_path_writable(&repo.join("src/lib.rs")));
+        assert!(!root.is_path_writable(&repo.join("untracked.txt")));
+        assert!(!root.is_path_writable(&repo.join("src/new.rs")));
+    }
+
+    #[test]
+    fn top_level_git_is_read_only_unless_git_writes_allowed() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let repo = tmp.path();
+        git(repo, &["init", "-q"]);
+        let git_config = repo.join(".git/config");
+
+        let roots = workspace_write(false).get_writable_roots_with_cwd(repo);
+        assert_eq!(roots[0].read_only_subpaths, vec![repo.join(".git")]);
+        assert!(!roots[0].is_path_writable(&git_config));
+        assert!(roots[0].is_path_writable(&repo.join("README.md")));
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            tracked_files_only: false,
+            allow_git_writes: true,
+        };
+        let roots = policy.get_writable_roots_with_cwd(repo);
+        assert_eq!(roots[0].read_only_subpaths, Vec::<PathBuf>::new());
+        assert!(roots[0].is_path_writable(&git_config));
+    }
+
+    #[test]
+    fn codex_home_under_a_root_is_read_only() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let home = tmp.path();
+        let codex_home = home.join(".codex");
+
+        let roots =
+            workspace_write(false).get_writable_roots_with_cwd_and_codex_home(home, &codex_home);
+        assert_eq!(roots[0].read_only_subpaths, vec![codex_home.clone()]);
+        assert!(!roots[0].is_path_writable(&codex_home.join("config.toml")));
+        assert!(roots[0].is_path_writable(&home.join("notes.txt")));
+
+        let elsewhere = tmp.path().join("project");
+        let roots = workspace_write(false)
+            .get_writable_roots_with_cwd_and_codex_home(&elsewhere, &codex_home);
+        assert_eq!(roots[0].read_only_subpaths, Vec::<PathBuf>::new());
+    }
 }
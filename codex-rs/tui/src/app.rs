@@ -6,34 +6,34 @@
 //! - 处理用户输入、AI 响应、文件操作等核心业务流程
 //! - 实现会话回退 (backtrack) 功能和覆盖层 (overlay) 管理
 
-use crate::app_backtrack::BacktrackState;          // 回退状态管理
-use crate::app_event::AppEvent;                    // 应用级事件定义
-use crate::app_event_sender::AppEventSender;      // 事件发送器封装
-use crate::chatwidget::ChatWidget;                 // 主聊天界面组件
-use crate::file_search::FileSearchManager;        // 文件搜索管理器
-use crate::pager_overlay::Overlay;                 // 覆盖层组件 (如会话记录查看器)
-use crate::tui;                                    // TUI 基础设施
-use crate::tui::TuiEvent;                          // 终端UI事件
-use codex_ansi_escape::ansi_escape_line;          // ANSI 转义序列处理
-use codex_core::ConversationManager;              // 会话管理器
-use codex_core::config::Config;                   // 配置管理
-use codex_core::protocol::TokenUsage;             // Token 使用统计
-use codex_login::AuthManager;                     // 认证管理器
-use color_eyre::eyre::Result;                     // 错误处理
-use crossterm::event::KeyCode;                    // 按键码定义
-use crossterm::event::KeyEvent;                   // 按键事件
-use crossterm::event::KeyEventKind;               // 按键事件类型
+use crate::app_backtrack::BacktrackState; // 回退状态管理
+use crate::app_event::AppEvent; // 应用级事件定义
+use crate::app_event_sender::AppEventSender; // 事件发送器封装
+use crate::chatwidget::ChatWidget; // 主聊天界面组件
+use crate::file_search::FileSearchManager; // 文件搜索管理器
+use crate::pager_overlay::Overlay; // 覆盖层组件 (如会话记录查看器)
+use crate::tui; // TUI 基础设施
+use crate::tui::TuiEvent; // 终端UI事件
+use codex_ansi_escape::ansi_escape_line; // ANSI 转义序列处理
+use codex_core::ConversationManager; // 会话管理器
+use codex_core::config::Config; // 配置管理
+use codex_core::protocol::TokenUsage; // Token 使用统计
+use codex_login::AuthManager; // 认证管理器
+use color_eyre::eyre::Result; // 错误处理
+use crossterm::event::KeyCode; // 按键码定义
+use crossterm::event::KeyEvent; // 按键事件
+use crossterm::event::KeyEventKind; // 按键事件类型
 use crossterm::terminal::supports_keyboard_enhancement; // 键盘增强功能检测
-use ratatui::style::Stylize;                      // 样式化工具
-use ratatui::text::Line;                          // 文本行
-use std::path::PathBuf;                           // 路径处理
-use std::sync::Arc;                               // 原子引用计数
-use std::sync::atomic::AtomicBool;                // 原子布尔值
-use std::sync::atomic::Ordering;                  // 内存排序
-use std::thread;                                  // 线程支持
-use std::time::Duration;                          // 时间间隔
-use tokio::select;                                // 异步选择宏
-use tokio::sync::mpsc::unbounded_channel;         // 无界消息通道
+use ratatui::style::Stylize; // 样式化工具
+use ratatui::text::Line; // 文本行
+use std::path::PathBuf; // 路径处理
+use std::sync::Arc; // 原子引用计数
+use std::sync::atomic::AtomicBool; // 原子布尔值
+use std::sync::atomic::Ordering; // 内存排序
+use std::thread; // 线程支持
+use std::time::Duration; // 时间间隔
+use tokio::select; // 异步选择宏
+use tokio::sync::mpsc::unbounded_channel; // 无界消息通道
 // use uuid::Uuid;
 
 /// App 结构体 - Codex CLI 应用的主控制器
@@ -241,8 +241,8 @@ impl App {
                     self.server.clone(),
                     tui.frame_requester(),
                     self.app_event_tx.clone(),
-                    None,           // 没有初始提示词
-                    Vec::new(),     // 没有初始图片
+                    None,       // 没有初始提示词
+                    Vec::new(), // 没有初始图片
                     self.enhanced_keys_supported,
                 );
                 tui.frame_requester().schedule_frame();
@@ -365,6 +365,10 @@ impl App {
             AppEvent::UpdateSandboxPolicy(policy) => {
                 self.chat_widget.set_sandbox_policy(policy);
             }
+            AppEvent::ConfirmDangerFullAccess(approval) => {
+                self.chat_widget
+                    .open_danger_full_access_confirmation(approval);
+            }
         }
         Ok(true)
     }
@@ -33,7 +33,15 @@ impl App {
         tui: &mut tui::Tui,
         event: TuiEvent,
     ) -> Result<bool> {
-        if self.backtrack.overlay_preview_active {
+        if self
+            .overlay
+            .as_ref()
+            .is_some_and(Overlay::is_search_input_active)
+        {
+            // Esc/Enter edit the search query instead of driving backtrack.
+            self.overlay_forward_event(tui, event)?;
+            Ok(true)
+        } else if self.backtrack.overlay_preview_active {
             match event {
                 TuiEvent::Key(KeyEvent {
                     code: KeyCode::Esc,
@@ -286,7 +294,12 @@ impl App {
         let cfg = self.chat_widget.config_ref().clone();
         // Perform the fork via a thin wrapper for clarity/testability.
         let result = self
-            .perform_fork(ev.entries.clone(), drop_count, cfg.clone())
+            .perform_fork(
+                ev.conversation_id,
+                ev.entries.clone(),
+                drop_count,
+                cfg.clone(),
+            )
             .await;
         match result {
             Ok(new_conv) => {
@@ -296,15 +309,24 @@ impl App {
         }
     }
 
-    /// Thin wrapper around ConversationManager::fork_conversation.
+    /// Thin wrapper around ConversationManager::fork_conversation_with_label.
+    /// The fork keeps the source conversation's label while the source is
+    /// still registered.
     async fn perform_fork(
         &self,
+        conversation_id: uuid::Uuid,
         entries: Vec<codex_protocol::models::ResponseItem>,
         drop_count: usize,
         cfg: codex_core::config::Config,
     ) -> codex_core::error::Result<codex_core::NewConversation> {
+        let label = self
+            .server
+            .get_conversation(conversation_id)
+            .await
+            .ok()
+            .and_then(|source| source.label());
         self.server
-            .fork_conversation(entries, drop_count, cfg)
+            .fork_conversation_with_label(entries, drop_count, cfg, label)
             .await
     }
 
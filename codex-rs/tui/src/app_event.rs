@@ -74,6 +74,9 @@ pub(crate) enum AppEvent {
     /// 更新沙箱策略（SandboxPolicy），影响执行/限制相关的行为。
     UpdateSandboxPolicy(SandboxPolicy),
 
+    /// 请求用户确认切换到完全访问（`DangerFullAccess`），确认后使用给定的审批策略。
+    ConfirmDangerFullAccess(AskForApproval),
+
     /// 来自后端会话的会话历史快照事件，包含会话历史的响应数据，
     /// UI 可用它来重播或渲染完整会话历史。
     ConversationHistory(ConversationHistoryResponseEvent),
@@ -57,6 +57,10 @@ impl BottomPaneView for ApprovalModalView {
         self.current.is_complete() && self.queue.is_empty()
     }
 
+    fn is_awaiting_approval(&self) -> bool {
+        !self.is_complete()
+    }
+
     fn desired_height(&self, width: u16) -> u16 {
         self.current.desired_height(width)
     }
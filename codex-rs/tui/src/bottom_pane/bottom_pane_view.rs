@@ -17,6 +17,12 @@ pub(crate) trait BottomPaneView {
         false
     }
 
+    /// Return `true` while the view holds an approval request the agent is
+    /// blocked on.
+    fn is_awaiting_approval(&self) -> bool {
+        false
+    }
+
     /// Handle Ctrl-C while this view is active.
     fn on_ctrl_c(&mut self, _pane: &mut BottomPane) -> CancellationEvent {
         CancellationEvent::Ignored
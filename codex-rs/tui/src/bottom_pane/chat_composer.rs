@@ -36,6 +36,7 @@ use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
+use crate::exec_command::relativize_to_home;
 use codex_file_search::FileMatch;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -53,6 +54,16 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 pub enum InputResult {
     Submitted(String),
     Command(SlashCommand),
+    /// A command that accepts arguments, together with the (trimmed) text
+    /// that followed it on the first line.
+    CommandWithArgs(SlashCommand, String),
+    /// A custom prompt to run with `Op::RunCustomPrompt`, together with the
+    /// values given for its arguments and the command line to show for it.
+    RunPrompt {
+        name: String,
+        arguments: HashMap<String, String>,
+        command_line: String,
+    },
     None,
 }
 
@@ -90,6 +101,8 @@ pub(crate) struct ChatComposer {
     current_file_query: Option<String>,
     pending_pastes: Vec<(String, String)>,
     token_usage_info: Option<TokenUsageInfo>,
+    /// Working directory shown in the footer, if known.
+    cwd: Option<PathBuf>,
     has_focus: bool,
     attached_images: Vec<AttachedImage>,
     placeholder_text: String,
@@ -130,6 +143,7 @@ impl ChatComposer {
             current_file_query: None,
             pending_pastes: Vec::new(),
             token_usage_info: None,
+            cwd: None,
             has_focus: has_input_focus,
             attached_images: Vec::new(),
             placeholder_text,
@@ -194,6 +208,19 @@ impl ChatComposer {
         });
     }
 
+    /// Percentage of the model's context window still available, when the
+    /// context window size is known.
+    pub(crate) fn context_percent_remaining(&self) -> Option<u8> {
+        let info = self.token_usage_info.as_ref()?;
+        let context_window = info.model_context_window?;
+        Some(if context_window > 0 {
+            info.last_token_usage
+                .percent_of_context_window_remaining(context_window, info.initial_prompt_tokens)
+        } else {
+            100
+        })
+    }
+
     /// Record the history metadata advertised by `SessionConfiguredEvent` so
     /// that the composer can navigate cross-session history.
     pub(crate) fn set_history_metadata(&mut self, log_id: u64, entry_count: usize) {
@@ -331,6 +358,16 @@ impl ChatComposer {
         }
     }
 
+    /// Update the working directory shown in the footer.
+    pub(crate) fn set_cwd(&mut self, cwd: PathBuf) {
+        self.cwd = Some(cwd);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
     pub fn set_ctrl_c_quit_hint(&mut self, show: bool, has_focus: bool) {
         self.ctrl_c_quit_hint = show;
         self.set_has_focus(has_focus);
@@ -432,25 +469,55 @@ impl ChatComposer {
                 ..
             } => {
                 if let Some(sel) = popup.selected_item() {
+                    // Capture anything typed after the command name before
+                    // clearing the textarea, e.g. the path in `/cd src`.
+                    let args = self
+                        .textarea
+                        .text()
+                        .lines()
+                        .next()
+                        .unwrap_or("")
+                        .trim_start()
+                        .split_once(char::is_whitespace)
+                        .map(|(_, rest)| rest.trim().to_string())
+                        .unwrap_or_default();
                     // Clear textarea so no residual text remains.
                     self.textarea.set_text("");
                     // Capture any needed data from popup before clearing it.
-                    let prompt_content = match sel {
-                        CommandItem::UserPrompt(idx) => {
-                            popup.prompt_content(idx).map(|s| s.to_string())
-                        }
+                    let prompt_run = match sel {
+                        CommandItem::UserPrompt(idx) => popup.prompt(idx).map(|prompt| {
+                            (
+                                prompt.name.clone(),
+                                prompt_argument_values(&prompt.arguments, &args),
+                            )
+                        }),
                         _ => None,
                     };
                     // Hide popup since an action has been dispatched.
                     self.active_popup = ActivePopup::None;
 
                     match sel {
+                        CommandItem::Builtin(cmd) if cmd.takes_arguments() => {
+                            return (InputResult::CommandWithArgs(cmd, args), true);
+                        }
                         CommandItem::Builtin(cmd) => {
                             return (InputResult::Command(cmd), true);
                         }
                         CommandItem::UserPrompt(_) => {
-                            if let Some(contents) = prompt_content {
-                                return (InputResult::Submitted(contents), true);
+                            if let Some((name, arguments)) = prompt_run {
+                                let command_line = if args.is_empty() {
+                                    format!("/{name}")
+                                } else {
+                                    format!("/{name} {args}")
+                                };
+                                return (
+                                    InputResult::RunPrompt {
+                                        name,
+                                        arguments,
+                                        command_line,
+                                    },
+                                    true,
+                                );
                             }
                             return (InputResult::None, true);
                         }
@@ -1275,16 +1342,7 @@ impl WidgetRef for ChatComposer {
                         Span::from(format!("{} tokens used", token_usage.blended_total()))
                             .style(Style::default().add_modifier(Modifier::DIM)),
                     );
-                    let last_token_usage = &token_usage_info.last_token_usage;
-                    if let Some(context_window) = token_usage_info.model_context_window {
-                        let percent_remaining: u8 = if context_window > 0 {
-                            last_token_usage.percent_of_context_window_remaining(
-                                context_window,
-                                token_usage_info.initial_prompt_tokens,
-                            )
-                        } else {
-                            100
-                        };
+                    if let Some(percent_remaining) = self.context_percent_remaining() {
                         hint.push(Span::from("   "));
                         hint.push(
                             Span::from(format!("{percent_remaining}% context left"))
@@ -1293,6 +1351,19 @@ impl WidgetRef for ChatComposer {
                     }
                 }
 
+                if let Some(cwd) = &self.cwd {
+                    let cwd_str = match relativize_to_home(cwd) {
+                        Some(rel) if !rel.as_os_str().is_empty() => {
+                            let sep = std::path::MAIN_SEPARATOR;
+                            format!("~{sep}{}", rel.display())
+                        }
+                        Some(_) => "~".to_string(),
+                        None => cwd.display().to_string(),
+                    };
+                    hint.push(Span::from("   "));
+                    hint.push(Span::from(cwd_str).dim());
+                }
+
                 Line::from(hint)
                     .style(Style::default().dim())
                     .render_ref(bottom_line_rect, buf);
@@ -1325,6 +1396,32 @@ impl WidgetRef for ChatComposer {
     }
 }
 
+/// Values for a prompt's `declared` arguments from the text typed after its
+/// name. `name=value` sets an argument by name; other words fill the
+/// remaining arguments in declaration order. Quotes group words as in a
+/// shell.
+fn prompt_argument_values(declared: &[String], args: &str) -> HashMap<String, String> {
+    let words =
+        shlex::split(args).unwrap_or_else(|| args.split_whitespace().map(str::to_string).collect());
+    let mut values = HashMap::new();
+    let mut positional = Vec::new();
+    for word in words {
+        match word.split_once('=') {
+            Some((name, value)) if declared.iter().any(|d| d == name) => {
+                values.insert(name.to_string(), value.to_string());
+            }
+            _ => positional.push(word),
+        }
+    }
+    let unset = declared
+        .iter()
+        .filter(|name| !values.contains_key(*name))
+        .cloned()
+        .collect::<Vec<_>>();
+    values.extend(unset.into_iter().zip(positional));
+    values
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1684,6 +1781,9 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgs(cmd, args) => {
+                panic!("unexpected command with args: {cmd:?} {args:?}")
+            }
             InputResult::None => panic!("expected Command result for '/init'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
@@ -1705,7 +1805,7 @@ mod tests {
             false,
         );
 
-        type_chars_humanlike(&mut composer, &['/', 'c']);
+        type_chars_humanlike(&mut composer, &['/', 'c', 'o']);
 
         let (_result, _needs_redraw) =
             composer.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
@@ -1714,6 +1814,37 @@ mod tests {
         assert_eq!(composer.textarea.cursor(), composer.textarea.text().len());
     }
 
+    #[test]
+    fn slash_cd_dispatches_command_with_path_argument() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        type_chars_humanlike(&mut composer, &['/', 'c', 'd', ' ', 's', 'r', 'c']);
+
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        match result {
+            InputResult::CommandWithArgs(cmd, args) => {
+                assert_eq!(cmd, SlashCommand::Cd);
+                assert_eq!(args, "src");
+            }
+            other => panic!("expected CommandWithArgs for '/cd src', got {other:?}"),
+        }
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn slash_mention_dispatches_command_and_inserts_at() {
         use crossterm::event::KeyCode;
@@ -1742,6 +1873,9 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgs(cmd, args) => {
+                panic!("unexpected command with args: {cmd:?} {args:?}")
+            }
             InputResult::None => panic!("expected Command result for '/mention'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
@@ -2136,7 +2270,7 @@ mod tests {
     }
 
     #[test]
-    fn selecting_custom_prompt_submits_file_contents() {
+    fn selecting_custom_prompt_runs_it() {
         let prompt_text = "Hello from saved prompt";
 
         let (tx, _rx) = unbounded_channel::<AppEvent>();
@@ -2154,6 +2288,7 @@ mod tests {
             name: "my-prompt".to_string(),
             path: "/tmp/my-prompt.md".to_string().into(),
             content: prompt_text.to_string(),
+            arguments: Vec::new(),
         }]);
 
         type_chars_humanlike(
@@ -2164,7 +2299,60 @@ mod tests {
         let (result, _needs_redraw) =
             composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
 
-        assert_eq!(InputResult::Submitted(prompt_text.to_string()), result);
+        assert_eq!(
+            InputResult::RunPrompt {
+                name: "my-prompt".to_string(),
+                arguments: HashMap::new(),
+                command_line: "/my-prompt".to_string(),
+            },
+            result
+        );
+    }
+
+    #[test]
+    fn custom_prompt_arguments_are_taken_from_the_command_line() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        composer.set_custom_prompts(vec![CustomPrompt {
+            name: "review".to_string(),
+            path: "/tmp/review.md".to_string().into(),
+            content: "Review {{file}} for {{focus}}".to_string(),
+            arguments: vec!["file".to_string(), "focus".to_string()],
+        }]);
+
+        composer.handle_paste("/review focus=\"error handling\" src/lib.rs".to_string());
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        let arguments = HashMap::from([
+            ("file".to_string(), "src/lib.rs".to_string()),
+            ("focus".to_string(), "error handling".to_string()),
+        ]);
+        assert_eq!(
+            InputResult::RunPrompt {
+                name: "review".to_string(),
+                arguments,
+                command_line: "/review focus=\"error handling\" src/lib.rs".to_string(),
+            },
+            result
+        );
+    }
+
+    #[test]
+    fn missing_prompt_arguments_are_left_unset() {
+        let declared = vec!["file".to_string(), "focus".to_string()];
+        let values = prompt_argument_values(&declared, "src/lib.rs");
+        assert_eq!(
+            values,
+            HashMap::from([("file".to_string(), "src/lib.rs".to_string())])
+        );
     }
 
     #[test]
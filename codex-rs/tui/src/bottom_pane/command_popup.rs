@@ -57,8 +57,8 @@ impl CommandPopup {
         self.prompts.get(idx).map(|p| p.name.as_str())
     }
 
-    pub(crate) fn prompt_content(&self, idx: usize) -> Option<&str> {
-        self.prompts.get(idx).map(|p| p.content.as_str())
+    pub(crate) fn prompt(&self, idx: usize) -> Option<&CustomPrompt> {
+        self.prompts.get(idx)
     }
 
     /// Update the filter string based on the current composer text. The text
@@ -171,6 +171,15 @@ impl CommandPopup {
     }
 }
 
+/// Popup description of a custom prompt, naming the arguments it asks for.
+fn prompt_description(prompt: &CustomPrompt) -> String {
+    if prompt.arguments.is_empty() {
+        "send saved prompt".to_string()
+    } else {
+        format!("send saved prompt · args: {}", prompt.arguments.join(" "))
+    }
+}
+
 impl WidgetRef for CommandPopup {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let matches = self.filtered();
@@ -190,7 +199,7 @@ impl WidgetRef for CommandPopup {
                         name: format!("/{}", self.prompts[i].name),
                         match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
                         is_current: false,
-                        description: Some("send saved prompt".to_string()),
+                        description: Some(prompt_description(&self.prompts[i])),
                     },
                 })
                 .collect()
@@ -245,11 +254,13 @@ mod tests {
                 name: "foo".to_string(),
                 path: "/tmp/foo.md".to_string().into(),
                 content: "hello from foo".to_string(),
+                arguments: Vec::new(),
             },
             CustomPrompt {
                 name: "bar".to_string(),
                 path: "/tmp/bar.md".to_string().into(),
                 content: "hello from bar".to_string(),
+                arguments: Vec::new(),
             },
         ];
         let popup = CommandPopup::new(prompts);
@@ -272,6 +283,7 @@ mod tests {
             name: "init".to_string(),
             path: "/tmp/init.md".to_string().into(),
             content: "should be ignored".to_string(),
+            arguments: Vec::new(),
         }]);
         let items = popup.filtered_items();
         let has_collision_prompt = items.into_iter().any(|it| match it {
@@ -108,9 +108,10 @@ impl BottomPane {
         } else {
             self.composer.desired_height(width)
         };
-        // If a status indicator is active and no modal is covering the composer,
-        // include its height above the composer.
-        if self.active_view.is_none()
+        // If a status indicator is active and no modal is covering the composer
+        // (or the modal is an approval the agent is blocked on), include its
+        // height above the composer.
+        if self.status_visible()
             && let Some(status) = self.status.as_ref()
         {
             base = base.saturating_add(status.desired_height(width));
@@ -128,14 +129,9 @@ impl BottomPane {
             top_margin = 0;
         }
 
-        let status_height = if self.active_view.is_none() {
-            if let Some(status) = self.status.as_ref() {
-                status.desired_height(area.width)
-            } else {
-                0
-            }
-        } else {
-            0
+        let status_height = match self.status.as_ref() {
+            Some(status) if self.status_visible() => status.desired_height(area.width),
+            _ => 0,
         };
 
         let [_, status, content, _] = Layout::vertical([
@@ -162,6 +158,28 @@ impl BottomPane {
         }
     }
 
+    /// The status indicator is hidden behind modal views, except approval
+    /// requests: those keep it visible so the blocked state is obvious.
+    fn status_visible(&self) -> bool {
+        self.active_view.is_none() || self.is_awaiting_approval()
+    }
+
+    /// Whether the agent is blocked waiting for the user to resolve an
+    /// approval request.
+    pub(crate) fn is_awaiting_approval(&self) -> bool {
+        self.active_view
+            .as_ref()
+            .is_some_and(|view| view.is_awaiting_approval())
+    }
+
+    /// Mirror the approval-blocked state into the status indicator.
+    fn sync_awaiting_approval(&mut self) {
+        let awaiting = self.is_awaiting_approval();
+        if let Some(status) = self.status.as_mut() {
+            status.set_awaiting_approval(awaiting);
+        }
+    }
+
     /// Forward a key event to the active view or the composer.
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> InputResult {
         if let Some(mut view) = self.active_view.take() {
@@ -169,6 +187,7 @@ impl BottomPane {
             if !view.is_complete() {
                 self.active_view = Some(view);
             }
+            self.sync_awaiting_approval();
             self.request_redraw();
             InputResult::None
         } else {
@@ -214,6 +233,7 @@ impl BottomPane {
                 self.active_view = Some(view);
             }
         }
+        self.sync_awaiting_approval();
         event
     }
 
@@ -302,6 +322,7 @@ impl BottomPane {
             if let Some(status) = self.status.as_mut() {
                 status.set_queued_messages(self.queued_user_messages.clone());
             }
+            self.sync_awaiting_approval();
             self.request_redraw();
         } else {
             // Hide the status indicator when a task completes, but keep other modal views.
@@ -371,12 +392,28 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    pub(crate) fn context_percent_remaining(&self) -> Option<u8> {
+        self.composer.context_percent_remaining()
+    }
+
+    /// Update the working directory shown in the composer footer.
+    pub(crate) fn set_cwd(&mut self, cwd: PathBuf) {
+        self.composer.set_cwd(cwd);
+        self.request_redraw();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn cwd(&self) -> Option<&std::path::Path> {
+        self.composer.cwd()
+    }
+
     /// Called when the agent requests user approval.
     pub fn push_approval_request(&mut self, request: ApprovalRequest) {
         let request = if let Some(view) = self.active_view.as_mut() {
             match view.try_consume_approval_request(request) {
                 Some(request) => request,
                 None => {
+                    self.sync_awaiting_approval();
                     self.request_redraw();
                     return;
                 }
@@ -388,6 +425,7 @@ impl BottomPane {
         // Otherwise create a new approval modal overlay.
         let modal = ApprovalModalView::new(request, self.app_event_tx.clone());
         self.active_view = Some(Box::new(modal));
+        self.sync_awaiting_approval();
         self.request_redraw()
     }
 
@@ -457,8 +495,14 @@ impl WidgetRef for &BottomPane {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let [status_area, content] = self.layout(area);
 
-        // When a modal view is active, it owns the whole content area.
+        // When a modal view is active, it owns the whole content area. An
+        // approval modal keeps the status line above it.
         if let Some(view) = &self.active_view {
+            if self.status_visible()
+                && let Some(status) = &self.status
+            {
+                status.render_ref(status_area, buf);
+            }
             view.render(content, buf);
         } else {
             // No active modal:
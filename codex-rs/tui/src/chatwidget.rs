@@ -12,12 +12,15 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BackgroundEventLevel;
+use codex_core::protocol::CompactCompleteEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::FileChange;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
@@ -25,11 +28,13 @@ use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::PatchApplyProgressEvent;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnTimingEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::parse_command::ParsedCommand;
@@ -98,6 +103,9 @@ pub(crate) struct ChatWidget {
     initial_user_message: Option<UserMessage>,
     total_token_usage: TokenUsage,
     last_token_usage: TokenUsage,
+    // Whether the low-context warning was shown since the context left last
+    // dropped below the threshold
+    context_warning_shown: bool,
     // Stream lifecycle controller
     stream: StreamController,
     running_commands: HashMap<String, RunningCommand>,
@@ -116,8 +124,11 @@ pub(crate) struct ChatWidget {
     last_history_was_exec: bool,
     // User messages queued while a turn is in progress
     queued_user_messages: VecDeque<UserMessage>,
+    // Most recently submitted user message, shown again by `/retry`
+    last_user_message: Option<UserMessage>,
 }
 
+#[derive(Clone)]
 struct UserMessage {
     text: String,
     image_paths: Vec<PathBuf>,
@@ -245,6 +256,37 @@ impl ChatWidget {
             self.last_token_usage.clone(),
             self.config.model_context_window,
         );
+        self.maybe_warn_low_context();
+    }
+
+    /// Shows the usage of the response in progress on top of the session
+    /// total without accumulating it; the final `TokenCount` replaces it.
+    fn on_partial_token_count(&mut self, token_usage: TokenUsage) {
+        self.bottom_pane.set_token_usage(
+            add_token_usage(&self.total_token_usage, &token_usage),
+            token_usage,
+            self.config.model_context_window,
+        );
+    }
+
+    /// Adds a warning to the history the first time the context left drops
+    /// below the configured threshold. It fires again only after the context
+    /// has recovered above the threshold (e.g. after `/compact`).
+    fn maybe_warn_low_context(&mut self) {
+        let threshold = self
+            .config
+            .tui
+            .context_warning_percent
+            .unwrap_or(DEFAULT_CONTEXT_WARNING_PERCENT);
+        let Some(percent_remaining) = self.bottom_pane.context_percent_remaining() else {
+            return;
+        };
+        if percent_remaining >= threshold {
+            self.context_warning_shown = false;
+        } else if !self.context_warning_shown {
+            self.context_warning_shown = true;
+            self.add_to_history(history_cell::new_context_warning_event(percent_remaining));
+        }
     }
 
     /// Finalize any active exec as failed, push an error message into history,
@@ -328,12 +370,23 @@ impl ChatWidget {
     }
 
     fn on_patch_apply_begin(&mut self, event: PatchApplyBeginEvent) {
-        self.add_to_history(history_cell::new_patch_event(
+        let event_type = if event.dry_run {
+            PatchEventType::DryRun
+        } else {
             PatchEventType::ApplyBegin {
                 auto_approved: event.auto_approved,
-            },
-            event.changes,
+            }
+        };
+        self.add_to_history(history_cell::new_patch_event(event_type, event.changes));
+    }
+
+    fn on_patch_apply_progress(&mut self, event: PatchApplyProgressEvent) {
+        self.bottom_pane.update_status_header(format!(
+            "Applying patch ({}/{})",
+            event.index + 1,
+            event.total
         ));
+        self.request_redraw();
     }
 
     fn on_patch_apply_end(&mut self, event: codex_core::protocol::PatchApplyEndEvent) {
@@ -365,10 +418,10 @@ impl ChatWidget {
 
     fn on_web_search_end(&mut self, ev: WebSearchEndEvent) {
         self.flush_answer_stream_with_separator();
-        self.add_to_history(history_cell::new_web_search_call(format!(
-            "Searched: {}",
-            ev.query
-        )));
+        self.add_to_history(history_cell::new_web_search_call(
+            format!("Searched: {}", ev.query),
+            ev.results.as_deref().unwrap_or_default(),
+        ));
     }
 
     fn on_get_history_entry_response(
@@ -392,8 +445,22 @@ impl ChatWidget {
         debug!("TurnDiffEvent: {unified_diff}");
     }
 
-    fn on_background_event(&mut self, message: String) {
+    fn on_background_event(&mut self, message: String, level: BackgroundEventLevel) {
         debug!("BackgroundEvent: {message}");
+        if level == BackgroundEventLevel::Warn {
+            self.add_to_history(history_cell::new_warning_event(message));
+            self.request_redraw();
+        }
+    }
+
+    fn on_compact_complete(&mut self, ev: CompactCompleteEvent) {
+        self.add_to_history(history_cell::new_compact_complete_event(ev.tokens_saved()));
+        self.request_redraw();
+    }
+
+    fn on_turn_timing(&mut self, ev: TurnTimingEvent) {
+        self.add_to_history(history_cell::new_turn_timing_event(ev));
+        self.request_redraw();
     }
 
     fn on_stream_error(&mut self, message: String) {
@@ -470,6 +537,7 @@ impl ChatWidget {
                 stdout: ev.stdout.clone(),
                 stderr: ev.stderr.clone(),
                 formatted_output: ev.formatted_output.clone(),
+                timed_out: ev.timed_out,
             },
         ));
 
@@ -484,6 +552,7 @@ impl ChatWidget {
                     output,
                     include_header,
                     ev.duration,
+                    self.config.exec_output_max_lines,
                 );
                 self.add_to_history(cell);
                 self.last_history_was_exec = true;
@@ -495,10 +564,16 @@ impl ChatWidget {
         &mut self,
         event: codex_core::protocol::PatchApplyEndEvent,
     ) {
-        if event.success {
+        if event.success && event.dry_run {
+            self.add_to_history(history_cell::new_patch_dry_run_success());
+        } else if event.success {
             self.add_to_history(history_cell::new_patch_apply_success(event.stdout));
         } else {
-            self.add_to_history(history_cell::new_patch_apply_failure(event.stderr));
+            self.add_to_history(history_cell::new_patch_apply_failure(
+                event.stderr,
+                &event.conflicts,
+                &self.config.cwd,
+            ));
         }
     }
 
@@ -525,10 +600,29 @@ impl ChatWidget {
             ev.changes.clone(),
         ));
 
+        let threshold = self
+            .config
+            .tui
+            .patch_delete_confirmation_threshold
+            .unwrap_or(DEFAULT_PATCH_DELETE_CONFIRMATION_THRESHOLD);
+        let mut deleted_files: Vec<PathBuf> = ev
+            .changes
+            .iter()
+            .filter(|(_, change)| matches!(change, FileChange::Delete))
+            .map(|(path, _)| path.clone())
+            .collect();
+        if deleted_files.len() > threshold {
+            deleted_files.sort();
+        } else {
+            deleted_files.clear();
+        }
+
         let request = ApprovalRequest::ApplyPatch {
             id,
             reason: ev.reason,
             grant_root: ev.grant_root,
+            grant_paths: ev.grant_paths,
+            deleted_files,
         };
         self.bottom_pane.push_approval_request(request);
         self.request_redraw();
@@ -554,6 +648,7 @@ impl ChatWidget {
                     ev.command,
                     ev.parsed_cmd,
                     include_header,
+                    self.config.exec_output_max_lines,
                 ));
             }
         }
@@ -572,6 +667,7 @@ impl ChatWidget {
             80,
             ev.invocation,
             ev.duration,
+            ev.attempts,
             ev.result
                 .as_ref()
                 .map(|r| !r.is_error.unwrap_or(false))
@@ -605,7 +701,7 @@ impl ChatWidget {
         let placeholder = EXAMPLE_PROMPTS[rng.random_range(0..EXAMPLE_PROMPTS.len())].to_string();
         let codex_op_tx = spawn_agent(config.clone(), app_event_tx.clone(), conversation_manager);
 
-        Self {
+        let mut widget = Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
             codex_op_tx,
@@ -625,6 +721,7 @@ impl ChatWidget {
             ),
             total_token_usage: TokenUsage::default(),
             last_token_usage: TokenUsage::default(),
+            context_warning_shown: false,
             stream: StreamController::new(config),
             running_commands: HashMap::new(),
             pending_exec_completions: Vec::new(),
@@ -635,8 +732,11 @@ impl ChatWidget {
             session_id: None,
             last_history_was_exec: false,
             queued_user_messages: VecDeque::new(),
+            last_user_message: None,
             show_welcome_banner: true,
-        }
+        };
+        widget.bottom_pane.set_cwd(widget.config.cwd.clone());
+        widget
     }
 
     /// Create a ChatWidget attached to an existing conversation (e.g., a fork).
@@ -654,7 +754,7 @@ impl ChatWidget {
         let codex_op_tx =
             spawn_agent_from_existing(conversation, session_configured, app_event_tx.clone());
 
-        Self {
+        let mut widget = Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
             codex_op_tx,
@@ -671,6 +771,7 @@ impl ChatWidget {
             initial_user_message: None,
             total_token_usage: TokenUsage::default(),
             last_token_usage: TokenUsage::default(),
+            context_warning_shown: false,
             stream: StreamController::new(config),
             running_commands: HashMap::new(),
             pending_exec_completions: Vec::new(),
@@ -681,8 +782,11 @@ impl ChatWidget {
             session_id: None,
             last_history_was_exec: false,
             queued_user_messages: VecDeque::new(),
+            last_user_message: None,
             show_welcome_banner: false,
-        }
+        };
+        widget.bottom_pane.set_cwd(widget.config.cwd.clone());
+        widget
     }
 
     pub fn desired_height(&self, width: u16) -> u16 {
@@ -742,6 +846,16 @@ impl ChatWidget {
                     InputResult::Command(cmd) => {
                         self.dispatch_command(cmd);
                     }
+                    InputResult::CommandWithArgs(cmd, args) => {
+                        self.dispatch_command_with_args(cmd, args);
+                    }
+                    InputResult::RunPrompt {
+                        name,
+                        arguments,
+                        command_line,
+                    } => {
+                        self.run_custom_prompt(name, arguments, command_line);
+                    }
                     InputResult::None => {}
                 }
             }
@@ -764,6 +878,10 @@ impl ChatWidget {
     }
 
     fn dispatch_command(&mut self, cmd: SlashCommand) {
+        self.dispatch_command_with_args(cmd, String::new());
+    }
+
+    fn dispatch_command_with_args(&mut self, cmd: SlashCommand, args: String) {
         if !cmd.available_during_task() && self.bottom_pane.is_task_running() {
             let message = format!(
                 "'/'{}' is disabled while a task is in progress.",
@@ -817,6 +935,12 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::Retry => {
+                self.retry_last_turn(&args);
+            }
+            SlashCommand::Cd => {
+                self.change_directory(&args);
+            }
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
@@ -861,12 +985,120 @@ impl ChatWidget {
                         ]),
                         reason: None,
                         grant_root: Some(PathBuf::from("/tmp")),
+                        grant_paths: Vec::new(),
                     }),
                 }));
             }
         }
     }
 
+    /// Handle `/retry [--model <name>]`: have core run the last user turn
+    /// again, optionally on another model for that turn only.
+    fn retry_last_turn(&mut self, args: &str) {
+        let model = match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [] => None,
+            ["--model", model] => Some(model.to_string()),
+            _ => {
+                self.add_to_history(history_cell::new_error_event(
+                    "Usage: /retry [--model <name>]".to_string(),
+                ));
+                self.request_redraw();
+                return;
+            }
+        };
+        let Some(message) = self.last_user_message.clone() else {
+            self.add_to_history(history_cell::new_error_event(
+                "Nothing to retry yet.".to_string(),
+            ));
+            self.request_redraw();
+            return;
+        };
+
+        // Core drops the failed turn from its history before running it
+        // again, so the prompt is not resent as new input.
+        self.submit_op(Op::RetryLastTurn { model });
+        if !message.text.is_empty() {
+            let preamble = self
+                .config
+                .user_turn_preamble
+                .as_deref()
+                .filter(|_| self.config.tui.show_user_turn_preamble);
+            self.add_to_history(history_cell::new_user_prompt(message.text, preamble));
+        }
+        self.request_redraw();
+    }
+
+    /// Handle `/cd <path>`: resolve `path` against the current cwd, make sure
+    /// it is a directory the sandbox allows writing to, then switch the
+    /// session over to it.
+    fn change_directory(&mut self, arg: &str) {
+        if arg.is_empty() {
+            self.add_to_history(history_cell::new_error_event(
+                "Usage: /cd <path>".to_string(),
+            ));
+            self.request_redraw();
+            return;
+        }
+
+        match self.resolve_cd_target(arg) {
+            Ok(target) => {
+                self.submit_op(Op::OverrideTurnContext {
+                    cwd: Some(target.clone()),
+                    approval_policy: None,
+                    sandbox_policy: None,
+                    model: None,
+                    effort: None,
+                    summary: None,
+                    danger_full_access_confirmed: false,
+                });
+                self.config.cwd = target.clone();
+                self.bottom_pane.set_cwd(target);
+            }
+            Err(message) => {
+                self.add_to_history(history_cell::new_error_event(message));
+            }
+        }
+        self.request_redraw();
+    }
+
+    fn resolve_cd_target(&self, arg: &str) -> Result<PathBuf, String> {
+        let expanded = match arg.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with(std::path::MAIN_SEPARATOR) => {
+                match std::env::var_os("HOME") {
+                    Some(home) => {
+                        PathBuf::from(home).join(rest.trim_start_matches(std::path::MAIN_SEPARATOR))
+                    }
+                    None => PathBuf::from(arg),
+                }
+            }
+            _ => PathBuf::from(arg),
+        };
+        let candidate = self.config.cwd.join(expanded);
+        let target = match candidate.canonicalize() {
+            Ok(path) if path.is_dir() => path,
+            Ok(path) => return Err(format!("/cd: not a directory: {}", path.display())),
+            Err(e) => return Err(format!("/cd: {}: {e}", candidate.display())),
+        };
+
+        if let SandboxPolicy::WorkspaceWrite { .. } = self.config.sandbox_policy {
+            let allowed = self
+                .config
+                .sandbox_policy
+                .get_writable_roots_with_cwd(&self.config.cwd)
+                .iter()
+                .filter_map(|root| root.root.canonicalize().ok())
+                .any(|root| target.starts_with(root));
+            if !allowed {
+                return Err(format!(
+                    "/cd: {} is outside the sandbox's writable roots",
+                    target.display()
+                ));
+            }
+        }
+
+        Ok(target)
+    }
+
     pub(crate) fn handle_paste(&mut self, text: String) {
         self.bottom_pane.handle_paste(text);
     }
@@ -914,6 +1146,9 @@ impl ChatWidget {
     }
 
     fn submit_user_message(&mut self, user_message: UserMessage) {
+        if !user_message.text.is_empty() || !user_message.image_paths.is_empty() {
+            self.last_user_message = Some(user_message.clone());
+        }
         // 用户输入可能包含多个图片
         let UserMessage { text, image_paths } = user_message;
         let mut items: Vec<InputItem> = Vec::new();
@@ -947,10 +1182,38 @@ impl ChatWidget {
 
         // Only show the text portion in conversation history.
         if !text.is_empty() {
-            self.add_to_history(history_cell::new_user_prompt(text.clone()));
+            let preamble = self
+                .config
+                .user_turn_preamble
+                .as_deref()
+                .filter(|_| self.config.tui.show_user_turn_preamble);
+            self.add_to_history(history_cell::new_user_prompt(text.clone(), preamble));
         }
     }
 
+    /// Have core render custom prompt `name` with `arguments` and send it as
+    /// user input; missing arguments come back as an error event.
+    fn run_custom_prompt(
+        &mut self,
+        name: String,
+        arguments: HashMap<String, String>,
+        command_line: String,
+    ) {
+        self.codex_op_tx
+            .send(Op::RunCustomPrompt { name, arguments })
+            .unwrap_or_else(|e| {
+                tracing::error!("failed to send RunCustomPrompt op: {e}");
+            });
+        self.codex_op_tx
+            .send(Op::AddToHistory {
+                text: command_line.clone(),
+            })
+            .unwrap_or_else(|e| {
+                tracing::error!("failed to send AddHistory op: {e}");
+            });
+        self.add_to_history(history_cell::new_user_prompt(command_line, None));
+    }
+
     pub(crate) fn handle_codex_event(&mut self, event: Event) {
         let Event { id, msg } = event;
 
@@ -981,14 +1244,17 @@ impl ChatWidget {
             EventMsg::TaskStarted(_) => self.on_task_started(),
             EventMsg::TaskComplete(TaskCompleteEvent { .. }) => self.on_task_complete(),
             EventMsg::TokenCount(token_usage) => self.on_token_count(token_usage),
+            EventMsg::PartialTokenCount(token_usage) => self.on_partial_token_count(token_usage),
             EventMsg::Error(ErrorEvent { message }) => self.on_error(message),
             EventMsg::TurnAborted(ev) => match ev.reason {
-                TurnAbortReason::Interrupted => {
+                TurnAbortReason::Interrupted | TurnAbortReason::ApprovalDenied => {
                     self.on_interrupted_turn();
                 }
                 TurnAbortReason::Replaced => {
                     self.on_error("Turn aborted: replaced by a new task".to_owned())
                 }
+                // The preceding `Error` event already reported the failure.
+                TurnAbortReason::StreamError { .. } | TurnAbortReason::Timeout => {}
             },
             EventMsg::PlanUpdate(update) => self.on_plan_update(update),
             EventMsg::ExecApprovalRequest(ev) => self.on_exec_approval_request(id, ev),
@@ -996,6 +1262,7 @@ impl ChatWidget {
             EventMsg::ExecCommandBegin(ev) => self.on_exec_command_begin(ev),
             EventMsg::ExecCommandOutputDelta(delta) => self.on_exec_command_output_delta(delta),
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
+            EventMsg::PatchApplyProgress(ev) => self.on_patch_apply_progress(ev),
             EventMsg::PatchApplyEnd(ev) => self.on_patch_apply_end(ev),
             EventMsg::ExecCommandEnd(ev) => self.on_exec_command_end(ev),
             EventMsg::McpToolCallBegin(ev) => self.on_mcp_tool_call_begin(ev),
@@ -1005,10 +1272,18 @@ impl ChatWidget {
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
+            EventMsg::CommandEvaluation(_)
+            | EventMsg::McpServerHealthResponse(_)
+            | EventMsg::GetDiffResponse(_)
+            | EventMsg::ResolvedConfig(_) => {}
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
-            EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
-                self.on_background_event(message)
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
+                self.on_turn_diff(unified_diff)
+            }
+            EventMsg::TurnTiming(ev) => self.on_turn_timing(ev),
+            EventMsg::CompactComplete(ev) => self.on_compact_complete(ev),
+            EventMsg::BackgroundEvent(BackgroundEventEvent { message, level }) => {
+                self.on_background_event(message, level)
             }
             EventMsg::StreamError(StreamErrorEvent { message }) => self.on_stream_error(message),
             EventMsg::ConversationHistory(ev) => {
@@ -1092,6 +1367,7 @@ impl ChatWidget {
                     model: Some(model_slug.clone()),
                     effort: Some(effort),
                     summary: None,
+                    danger_full_access_confirmed: false,
                 }));
                 tx.send(AppEvent::UpdateModel(model_slug.clone()));
                 tx.send(AppEvent::UpdateReasoningEffort(effort));
@@ -1125,18 +1401,17 @@ impl ChatWidget {
             let sandbox = preset.sandbox.clone();
             let name = preset.label.to_string();
             let description = Some(preset.description.to_string());
-            let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
-                tx.send(AppEvent::CodexOp(Op::OverrideTurnContext {
-                    cwd: None,
-                    approval_policy: Some(approval),
-                    sandbox_policy: Some(sandbox.clone()),
-                    model: None,
-                    effort: None,
-                    summary: None,
-                }));
-                tx.send(AppEvent::UpdateAskForApprovalPolicy(approval));
-                tx.send(AppEvent::UpdateSandboxPolicy(sandbox.clone()));
-            })];
+            // Leaving the sandbox for full access is confirmed in a second popup.
+            let needs_confirmation = self.config.confirm_danger_full_access
+                && sandbox == SandboxPolicy::DangerFullAccess
+                && current_sandbox != SandboxPolicy::DangerFullAccess;
+            let actions: Vec<SelectionAction> = if needs_confirmation {
+                vec![Box::new(move |tx| {
+                    tx.send(AppEvent::ConfirmDangerFullAccess(approval));
+                })]
+            } else {
+                approval_preset_actions(approval, sandbox, false)
+            };
             items.push(SelectionItem {
                 name,
                 description,
@@ -1153,6 +1428,33 @@ impl ChatWidget {
         );
     }
 
+    /// Ask before switching to `DangerFullAccess` with `approval`.
+    pub(crate) fn open_danger_full_access_confirmation(&mut self, approval: AskForApproval) {
+        let items = vec![
+            SelectionItem {
+                name: "No, keep the current mode".to_string(),
+                description: None,
+                is_current: false,
+                actions: Vec::new(),
+            },
+            SelectionItem {
+                name: "Yes, turn off the sandbox".to_string(),
+                description: Some(
+                    "Commands can change any file and use the network without approval".to_string(),
+                ),
+                is_current: false,
+                actions: approval_preset_actions(approval, SandboxPolicy::DangerFullAccess, true),
+            },
+        ];
+
+        self.bottom_pane.show_selection_view(
+            "Switch to Full Access?".to_string(),
+            Some("Codex will run every command without a sandbox".to_string()),
+            Some("Press Enter to confirm or Esc to go back".to_string()),
+            items,
+        );
+    }
+
     /// Set the approval policy in the widget's config copy.
     pub(crate) fn set_approval_policy(&mut self, policy: AskForApproval) {
         self.config.approval_policy = policy;
@@ -1290,6 +1592,14 @@ impl WidgetRef for &ChatWidget {
     }
 }
 
+/// Percentage of context left below which the user is warned, unless
+/// overridden by `tui.context_warning_percent`.
+const DEFAULT_CONTEXT_WARNING_PERCENT: u8 = 15;
+
+/// Number of deleted files above which approving a patch needs a second
+/// confirmation, unless overridden by `tui.patch_delete_confirmation_threshold`.
+const DEFAULT_PATCH_DELETE_CONFIRMATION_THRESHOLD: usize = 5;
+
 const EXAMPLE_PROMPTS: [&str; 6] = [
     "Explain this codebase",
     "Summarize recent commits",
@@ -1357,5 +1667,26 @@ fn extract_first_bold(s: &str) -> Option<String> {
     None
 }
 
+/// Actions applying an approvals preset to the session and the widget.
+fn approval_preset_actions(
+    approval: AskForApproval,
+    sandbox: SandboxPolicy,
+    danger_full_access_confirmed: bool,
+) -> Vec<SelectionAction> {
+    vec![Box::new(move |tx| {
+        tx.send(AppEvent::CodexOp(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: Some(approval),
+            sandbox_policy: Some(sandbox.clone()),
+            model: None,
+            effort: None,
+            summary: None,
+            danger_full_access_confirmed,
+        }));
+        tx.send(AppEvent::UpdateAskForApprovalPolicy(approval));
+        tx.send(AppEvent::UpdateSandboxPolicy(sandbox.clone()));
+    })]
+}
+
 #[cfg(test)]
 mod tests;
@@ -12,6 +12,7 @@ use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
@@ -22,6 +23,7 @@ use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TaskCompleteReason;
 use codex_core::protocol::TaskStartedEvent;
 use codex_login::CodexAuth;
 use crossterm::event::KeyCode;
@@ -175,6 +177,7 @@ fn make_chatwidget_manual() -> (
         initial_user_message: None,
         total_token_usage: TokenUsage::default(),
         last_token_usage: TokenUsage::default(),
+        context_warning_shown: false,
         stream: StreamController::new(cfg),
         running_commands: HashMap::new(),
         pending_exec_completions: Vec::new(),
@@ -187,6 +190,7 @@ fn make_chatwidget_manual() -> (
         show_welcome_banner: true,
         last_history_was_exec: false,
         queued_user_messages: std::collections::VecDeque::new(),
+        last_user_message: None,
     };
     (widget, rx, op_rx)
 }
@@ -284,6 +288,7 @@ fn exec_history_cell_shows_working_then_completed() {
             parsed_cmd: vec![
                 codex_core::parse_command::ParsedCommand::Unknown {
                     cmd: "echo done".into(),
+                    elevated: false,
                 }
                 .into(),
             ],
@@ -300,7 +305,9 @@ fn exec_history_cell_shows_working_then_completed() {
             aggregated_output: "done".into(),
             exit_code: 0,
             duration: std::time::Duration::from_millis(5),
+            timed_out: false,
             formatted_output: "done".into(),
+            peak_rss_bytes: None,
         }),
     });
 
@@ -335,6 +342,7 @@ fn exec_history_cell_shows_working_then_failed() {
             parsed_cmd: vec![
                 codex_core::parse_command::ParsedCommand::Unknown {
                     cmd: "false".into(),
+                    elevated: false,
                 }
                 .into(),
             ],
@@ -351,7 +359,9 @@ fn exec_history_cell_shows_working_then_failed() {
             aggregated_output: "error".into(),
             exit_code: 2,
             duration: std::time::Duration::from_millis(7),
+            timed_out: false,
             formatted_output: "".into(),
+            peak_rss_bytes: None,
         }),
     });
 
@@ -388,6 +398,7 @@ fn interrupt_exec_marks_failed_snapshot() {
             parsed_cmd: vec![
                 codex_core::parse_command::ParsedCommand::Unknown {
                     cmd: "sleep 1".into(),
+                    elevated: false,
                 }
                 .into(),
             ],
@@ -428,6 +439,7 @@ fn exec_history_extends_previous_when_consecutive() {
             parsed_cmd: vec![
                 codex_core::parse_command::ParsedCommand::Unknown {
                     cmd: "echo one".into(),
+                    elevated: false,
                 }
                 .into(),
             ],
@@ -442,7 +454,9 @@ fn exec_history_extends_previous_when_consecutive() {
             aggregated_output: "one".into(),
             exit_code: 0,
             duration: std::time::Duration::from_millis(5),
+            timed_out: false,
             formatted_output: "one".into(),
+            peak_rss_bytes: None,
         }),
     });
     let first_cells = drain_insert_history(&mut rx);
@@ -458,6 +472,7 @@ fn exec_history_extends_previous_when_consecutive() {
             parsed_cmd: vec![
                 codex_core::parse_command::ParsedCommand::Unknown {
                     cmd: "echo two".into(),
+                    elevated: false,
                 }
                 .into(),
             ],
@@ -472,7 +487,9 @@ fn exec_history_extends_previous_when_consecutive() {
             aggregated_output: "two".into(),
             exit_code: 0,
             duration: std::time::Duration::from_millis(5),
+            timed_out: false,
             formatted_output: "two".into(),
+            peak_rss_bytes: None,
         }),
     });
     let second_cells = drain_insert_history(&mut rx);
@@ -749,6 +766,7 @@ fn approval_modal_patch_snapshot() {
         changes,
         reason: Some("The model wants to apply changes".into()),
         grant_root: Some(PathBuf::from("/tmp")),
+        grant_paths: vec![PathBuf::from("/tmp/README.md")],
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-patch".into(),
@@ -852,8 +870,8 @@ fn ui_snapshots_small_heights_task_running() {
 }
 
 // Snapshot test: status widget + approval modal active together
-// The modal takes precedence visually; this captures the layout with a running
-// task (status indicator active) while an approval request is shown.
+// The modal replaces the composer while the status line above it switches to
+// the "waiting for approval" state for a running task.
 #[test]
 fn status_widget_and_approval_modal_snapshot() {
     use codex_core::protocol::ExecApprovalRequestEvent;
@@ -896,6 +914,43 @@ fn status_widget_and_approval_modal_snapshot() {
     assert_snapshot!("status_widget_and_approval_modal", terminal.backend());
 }
 
+#[test]
+fn exec_approval_request_blocks_until_resolved() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+    chat.handle_codex_event(Event {
+        id: "task-1".into(),
+        msg: EventMsg::TaskStarted(TaskStartedEvent {
+            model_context_window: None,
+        }),
+    });
+    assert!(!chat.bottom_pane.is_awaiting_approval());
+
+    chat.handle_codex_event(Event {
+        id: "sub-blocked".into(),
+        msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call-blocked".into(),
+            command: vec!["echo".into(), "hi".into()],
+            cwd: std::path::PathBuf::from("/tmp"),
+            reason: None,
+        }),
+    });
+    assert!(chat.bottom_pane.is_awaiting_approval());
+
+    // Quick-deny from the status line hint resolves the request.
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    assert!(!chat.bottom_pane.is_awaiting_approval());
+
+    let mut found = false;
+    while let Ok(app_ev) = rx.try_recv() {
+        if let AppEvent::CodexOp(Op::ExecApproval { id, decision }) = app_ev {
+            assert_eq!(id, "sub-blocked");
+            assert_eq!(decision, codex_core::protocol::ReviewDecision::Denied);
+            found = true;
+        }
+    }
+    assert!(found, "expected ExecApproval op to be sent");
+}
+
 // Snapshot test: status widget active (StatusIndicatorView)
 // Ensures the VT100 rendering of the status indicator is stable when active.
 #[test]
@@ -942,6 +997,7 @@ fn apply_patch_events_emit_history_cells() {
         changes,
         reason: None,
         grant_root: None,
+        grant_paths: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -967,6 +1023,7 @@ fn apply_patch_events_emit_history_cells() {
         call_id: "c1".into(),
         auto_approved: true,
         changes: changes2,
+        dry_run: false,
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -986,6 +1043,8 @@ fn apply_patch_events_emit_history_cells() {
         stdout: "ok\n".into(),
         stderr: String::new(),
         success: true,
+        dry_run: false,
+        conflicts: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -1016,6 +1075,7 @@ fn apply_patch_approval_sends_op_with_submission_id() {
         changes,
         reason: None,
         grant_root: None,
+        grant_paths: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-123".into(),
@@ -1041,6 +1101,71 @@ fn apply_patch_approval_sends_op_with_submission_id() {
     assert!(found, "expected PatchApproval op to be sent");
 }
 
+fn deleting_patch_approval(chat: &mut ChatWidget, deleted: usize) {
+    let changes = (0..deleted)
+        .map(|i| (PathBuf::from(format!("old_{i}.rs")), FileChange::Delete))
+        .collect();
+    chat.handle_codex_event(Event {
+        id: "sub-delete".into(),
+        msg: EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+            call_id: "call-delete".into(),
+            changes,
+            reason: None,
+            grant_root: None,
+            grant_paths: Vec::new(),
+        }),
+    });
+}
+
+fn patch_approval_sent(rx: &mut tokio::sync::mpsc::UnboundedReceiver<AppEvent>) -> bool {
+    let mut found = false;
+    while let Ok(app_ev) = rx.try_recv() {
+        found |= matches!(app_ev, AppEvent::CodexOp(Op::PatchApproval { .. }));
+    }
+    found
+}
+
+#[test]
+fn patch_deleting_many_files_needs_second_confirmation() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+    chat.config.tui.patch_delete_confirmation_threshold = Some(3);
+    deleting_patch_approval(&mut chat, 5);
+
+    // The first approval only switches to the deletion confirmation.
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    assert!(!patch_approval_sent(&mut rx));
+
+    let height = chat.desired_height(80);
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, height))
+        .expect("create terminal");
+    terminal
+        .draw(|f| f.render_widget_ref(&chat, f.area()))
+        .expect("draw deletion confirmation");
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(screen.contains("Delete 5 files?"), "screen: {screen}");
+    assert!(screen.contains("This patch deletes 5 files:"));
+    assert!(screen.contains("old_0.rs") && screen.contains("old_4.rs"));
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    assert!(patch_approval_sent(&mut rx));
+}
+
+#[test]
+fn patch_deleting_few_files_is_approved_directly() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+    chat.config.tui.patch_delete_confirmation_threshold = Some(3);
+    deleting_patch_approval(&mut chat, 1);
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    assert!(patch_approval_sent(&mut rx));
+}
+
 #[test]
 fn apply_patch_full_flow_integration_like() {
     let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
@@ -1058,6 +1183,7 @@ fn apply_patch_full_flow_integration_like() {
             changes,
             reason: None,
             grant_root: None,
+            grant_paths: Vec::new(),
         }),
     });
 
@@ -1100,6 +1226,7 @@ fn apply_patch_full_flow_integration_like() {
             call_id: "call-1".into(),
             auto_approved: false,
             changes: changes2,
+            dry_run: false,
         }),
     });
     chat.handle_codex_event(Event {
@@ -1109,6 +1236,8 @@ fn apply_patch_full_flow_integration_like() {
             stdout: String::from("ok"),
             stderr: String::new(),
             success: true,
+            dry_run: false,
+            conflicts: Vec::new(),
         }),
     });
 }
@@ -1132,6 +1261,7 @@ fn apply_patch_untrusted_shows_approval_modal() {
             changes,
             reason: None,
             grant_root: None,
+            grant_paths: Vec::new(),
         }),
     });
 
@@ -1180,6 +1310,7 @@ fn apply_patch_request_shows_diff_summary() {
             changes,
             reason: None,
             grant_root: None,
+            grant_paths: Vec::new(),
         }),
     });
 
@@ -1380,6 +1511,9 @@ fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            reason: TaskCompleteReason::Completed,
+            produced_changes: false,
+            produced_message: false,
         }),
     });
 
@@ -1504,3 +1638,246 @@ fn deltas_then_same_final_message_are_rendered_snapshot() {
         .collect::<String>();
     assert_snapshot!(combined);
 }
+
+#[test]
+fn slash_cd_into_valid_dir_updates_indicator_and_turn_context() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
+    let root = tempfile::tempdir().expect("tempdir");
+    let root_path = root.path().canonicalize().expect("canonicalize root");
+    std::fs::create_dir(root_path.join("sub")).expect("create sub");
+    chat.config.cwd = root_path.clone();
+    chat.config.sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+
+    chat.dispatch_command_with_args(SlashCommand::Cd, "sub".to_string());
+
+    let expected = root_path.join("sub");
+    assert_eq!(chat.bottom_pane.cwd(), Some(expected.as_path()));
+    assert_eq!(chat.config.cwd, expected);
+    match op_rx.try_recv() {
+        Ok(Op::OverrideTurnContext { cwd, .. }) => assert_eq!(cwd, Some(expected)),
+        other => panic!("expected OverrideTurnContext, got {other:?}"),
+    }
+}
+
+#[test]
+fn slash_cd_into_invalid_dir_shows_error() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+    let root = tempfile::tempdir().expect("tempdir");
+    let root_path = root.path().canonicalize().expect("canonicalize root");
+    chat.config.cwd = root_path.clone();
+    chat.bottom_pane.set_cwd(root_path.clone());
+
+    chat.dispatch_command_with_args(SlashCommand::Cd, "missing".to_string());
+
+    assert_eq!(chat.bottom_pane.cwd(), Some(root_path.as_path()));
+    assert_eq!(chat.config.cwd, root_path);
+    assert!(op_rx.try_recv().is_err(), "no op should be submitted");
+
+    let cells = drain_insert_history(&mut rx);
+    let rendered = cells
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect::<String>();
+    assert!(
+        rendered.contains("missing"),
+        "expected error mentioning the path, got: {rendered}"
+    );
+}
+
+fn fail_turn_after_submitting(chat: &mut ChatWidget, message: UserMessage) {
+    chat.submit_user_message(message);
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::TaskStarted(TaskStartedEvent {
+            model_context_window: None,
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::Error(ErrorEvent {
+            message: "stream disconnected".to_string(),
+        }),
+    });
+}
+
+fn next_user_input(op_rx: &mut tokio::sync::mpsc::UnboundedReceiver<Op>) -> Vec<InputItem> {
+    while let Ok(op) = op_rx.try_recv() {
+        if let Op::UserInput { items } = op {
+            return items;
+        }
+    }
+    panic!("expected a UserInput op");
+}
+
+#[test]
+fn slash_retry_asks_core_to_retry_last_turn_after_failed_turn() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
+    fail_turn_after_submitting(
+        &mut chat,
+        UserMessage {
+            text: "fix the failing test".to_string(),
+            image_paths: vec![PathBuf::from("/tmp/screenshot.png")],
+        },
+    );
+    next_user_input(&mut op_rx);
+    while op_rx.try_recv().is_ok() {}
+
+    chat.dispatch_command_with_args(SlashCommand::Retry, String::new());
+
+    match op_rx.try_recv() {
+        Ok(Op::RetryLastTurn { model }) => assert_eq!(model, None),
+        other => panic!("expected RetryLastTurn, got {other:?}"),
+    }
+    assert!(op_rx.try_recv().is_err(), "the input must not be resent");
+}
+
+#[test]
+fn slash_retry_with_model_overrides_model_for_the_retried_turn_only() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
+    let session_model = chat.config.model.clone();
+    fail_turn_after_submitting(&mut chat, "fix the failing test".to_string().into());
+    next_user_input(&mut op_rx);
+    while op_rx.try_recv().is_ok() {}
+
+    chat.dispatch_command_with_args(SlashCommand::Retry, "--model o3".to_string());
+
+    match op_rx.try_recv() {
+        Ok(Op::RetryLastTurn { model }) => assert_eq!(model.as_deref(), Some("o3")),
+        other => panic!("expected RetryLastTurn, got {other:?}"),
+    }
+    assert!(
+        op_rx.try_recv().is_err(),
+        "the session model must not change"
+    );
+    assert_eq!(chat.config.model, session_model);
+}
+
+#[test]
+fn slash_retry_without_previous_input_shows_error() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+
+    chat.dispatch_command_with_args(SlashCommand::Retry, String::new());
+
+    assert!(op_rx.try_recv().is_err(), "no op should be submitted");
+    let rendered = drain_insert_history(&mut rx)
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect::<String>();
+    assert!(
+        rendered.contains("Nothing to retry"),
+        "expected retry error, got: {rendered}"
+    );
+}
+
+#[test]
+fn low_context_warning_fires_once_per_crossing() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+    chat.config.model_context_window = Some(1_000);
+    chat.config.tui.context_warning_percent = Some(15);
+
+    let mut send_usage = |chat: &mut ChatWidget, total_tokens: u64| {
+        chat.handle_codex_event(Event {
+            id: "sub-1".into(),
+            msg: EventMsg::TokenCount(TokenUsage {
+                total_tokens,
+                ..TokenUsage::default()
+            }),
+        });
+        drain_insert_history(&mut rx)
+            .iter()
+            .map(|lines| lines_to_single_string(lines))
+            .filter(|text| text.contains("/compact"))
+            .count()
+    };
+
+    // 50% left: no warning.
+    assert_eq!(send_usage(&mut chat, 500), 0);
+    // Crossing below 15% warns once...
+    assert_eq!(send_usage(&mut chat, 900), 1);
+    // ...and staying below does not warn again.
+    assert_eq!(send_usage(&mut chat, 950), 0);
+    // Recovering (e.g. after /compact) re-arms the warning.
+    assert_eq!(send_usage(&mut chat, 200), 0);
+    assert_eq!(send_usage(&mut chat, 900), 1);
+}
+
+#[test]
+fn partial_token_counts_are_not_accumulated() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    let usage = |output_tokens: u64| TokenUsage {
+        output_tokens,
+        total_tokens: output_tokens,
+        ..TokenUsage::default()
+    };
+
+    for output_tokens in [1, 2] {
+        chat.handle_codex_event(Event {
+            id: "sub-1".into(),
+            msg: EventMsg::PartialTokenCount(usage(output_tokens)),
+        });
+    }
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::TokenCount(usage(3)),
+    });
+
+    assert_eq!(chat.token_usage().output_tokens, 3);
+    assert_eq!(chat.token_usage().total_tokens, 3);
+}
+
+#[test]
+fn full_access_preset_asks_for_confirmation() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+    chat.config.approval_policy = AskForApproval::OnRequest;
+    chat.config.sandbox_policy = SandboxPolicy::ReadOnly;
+    chat.config.confirm_danger_full_access = true;
+
+    // Read Only is selected; Full Access is two rows down.
+    chat.open_approvals_popup();
+    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+    let mut events = Vec::new();
+    while let Ok(ev) = rx.try_recv() {
+        events.push(ev);
+    }
+    assert!(
+        events
+            .iter()
+            .any(|ev| matches!(ev, AppEvent::ConfirmDangerFullAccess(AskForApproval::Never))),
+        "expected a confirmation request, got {events:?}"
+    );
+    assert!(
+        !events.iter().any(|ev| matches!(ev, AppEvent::CodexOp(_))),
+        "the switch must wait for confirmation, got {events:?}"
+    );
+}
+
+#[test]
+fn danger_full_access_confirmation_defaults_to_no() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    // Enter on the default row declines.
+    chat.open_danger_full_access_confirmation(AskForApproval::Never);
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    while let Ok(ev) = rx.try_recv() {
+        assert!(!matches!(ev, AppEvent::CodexOp(_)), "unexpected {ev:?}");
+    }
+
+    chat.open_danger_full_access_confirmation(AskForApproval::Never);
+    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let mut confirmed = false;
+    while let Ok(ev) = rx.try_recv() {
+        if let AppEvent::CodexOp(Op::OverrideTurnContext {
+            sandbox_policy: Some(SandboxPolicy::DangerFullAccess),
+            danger_full_access_confirmed: true,
+            ..
+        }) = ev
+        {
+            confirmed = true;
+        }
+    }
+    assert!(confirmed, "expected a confirmed switch to full access");
+}
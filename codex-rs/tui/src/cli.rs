@@ -32,6 +32,11 @@ pub struct Cli {
     #[arg(long = "sandbox", short = 's')]
     pub sandbox_mode: Option<codex_common::SandboxModeCliArg>,
 
+    /// Confirm `--sandbox danger-full-access`, which is refused without this
+    /// flag unless `confirm_danger_full_access = false` is configured.
+    #[arg(long = "confirm-danger-full-access", default_value_t = false)]
+    pub confirm_danger_full_access: bool,
+
     /// Configure when the model requires human approval before executing a command.
     #[arg(long = "ask-for-approval", short = 'a')]
     pub approval_policy: Option<ApprovalModeCliArg>,
@@ -11,6 +11,7 @@ use crate::common::DEFAULT_WRAP_COLS;
 use codex_core::protocol::FileChange;
 
 use crate::history_cell::PatchEventType;
+use crate::theme;
 
 const SPACES_AFTER_LINE_NUMBER: usize = 6;
 
@@ -116,12 +117,12 @@ pub(crate) fn create_diff_summary(
     header_spans.push(RtSpan::raw("("));
     header_spans.push(RtSpan::styled(
         format!("+{total_added}"),
-        Style::default().fg(Color::Green),
+        Style::default().fg(theme::diff_add()),
     ));
     header_spans.push(RtSpan::raw(" "));
     header_spans.push(RtSpan::styled(
         format!("-{total_removed}"),
-        Style::default().fg(Color::Red),
+        Style::default().fg(theme::diff_remove()),
     ));
     header_spans.push(RtSpan::raw(")"));
     out.push(RtLine::from(header_spans));
@@ -135,12 +136,12 @@ pub(crate) fn create_diff_summary(
             spans.push(RtSpan::raw(" ("));
             spans.push(RtSpan::styled(
                 format!("+{}", f.added),
-                Style::default().fg(Color::Green),
+                Style::default().fg(theme::diff_add()),
             ));
             spans.push(RtSpan::raw(" "));
             spans.push(RtSpan::styled(
                 format!("-{}", f.removed),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme::diff_remove()),
             ));
             spans.push(RtSpan::raw(")"));
         }
@@ -159,6 +160,7 @@ pub(crate) fn create_diff_summary(
         PatchEventType::ApplyBegin {
             auto_approved: true
         } | PatchEventType::ApprovalRequest
+            | PatchEventType::DryRun
     );
 
     if show_details {
@@ -361,11 +363,11 @@ fn style_dim() -> Style {
 }
 
 fn style_add() -> Style {
-    Style::default().fg(Color::Green)
+    Style::default().fg(theme::diff_add())
 }
 
 fn style_del() -> Style {
-    Style::default().fg(Color::Red)
+    Style::default().fg(theme::diff_remove())
 }
 
 #[cfg(test)]
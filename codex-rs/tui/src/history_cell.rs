@@ -4,6 +4,7 @@ use crate::exec_command::strip_bash_lc_and_escape;
 use crate::markdown::append_markdown;
 use crate::slash_command::SlashCommand;
 use crate::text_formatting::format_and_truncate_tool_result;
+use crate::theme;
 use base64::Engine;
 use codex_ansi_escape::ansi_escape_line;
 use codex_common::create_config_summary_entries;
@@ -15,11 +16,14 @@ use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::project_doc::discover_project_doc_paths;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::PatchConflict;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TurnTimingEvent;
 use codex_login::get_auth_file;
 use codex_login::try_read_auth_json;
+use codex_protocol::models::WebSearchResult;
 use codex_protocol::parse_command::ParsedCommand;
 use image::DynamicImage;
 use image::ImageReader;
@@ -35,6 +39,7 @@ use ratatui::widgets::WidgetRef;
 use ratatui::widgets::Wrap;
 use std::collections::HashMap;
 use std::io::Cursor;
+use std::path::Path;
 use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
@@ -47,11 +52,17 @@ pub(crate) struct CommandOutput {
     pub(crate) stdout: String,
     pub(crate) stderr: String,
     pub(crate) formatted_output: String,
+    /// The command was killed because it exceeded its timeout.
+    pub(crate) timed_out: bool,
 }
 
 pub(crate) enum PatchEventType {
     ApprovalRequest,
-    ApplyBegin { auto_approved: bool },
+    ApplyBegin {
+        auto_approved: bool,
+    },
+    /// The patch is only validated and previewed; nothing is written.
+    DryRun,
 }
 
 /// Represents an event to display in the conversation history. Returns its
@@ -107,6 +118,8 @@ pub(crate) struct ExecCell {
     start_time: Option<Instant>,
     duration: Option<Duration>,
     include_header: bool,
+    /// Head and tail lines of output shown around the ellipsis.
+    output_max_lines: usize,
 }
 impl HistoryCell for ExecCell {
     fn display_lines(&self) -> Vec<Line<'static>> {
@@ -116,6 +129,7 @@ impl HistoryCell for ExecCell {
             self.output.as_ref(),
             self.start_time,
             self.include_header,
+            self.output_max_lines,
         )
     }
 
@@ -143,6 +157,8 @@ impl HistoryCell for ExecCell {
                 .unwrap_or_else(|| "unknown".to_string());
             let mut result = if output.exit_code == 0 {
                 Line::from("✓".green().bold())
+            } else if output.timed_out {
+                Line::from(vec!["✗".red().bold(), " timed out".into()])
             } else {
                 Line::from(vec![
                     "✗".red().bold(),
@@ -190,6 +206,7 @@ impl ExecCell {
             stdout: String::new(),
             stderr: String::new(),
             formatted_output: String::new(),
+            timed_out: false,
         });
         self
     }
@@ -252,6 +269,8 @@ pub(crate) fn new_session_info(
         session_id: _,
         history_log_id: _,
         history_entry_count: _,
+        label: _,
+        max_output_tokens: _,
     } = event;
     if is_first_event {
         let cwd_str = match relativize_to_home(&config.cwd) {
@@ -339,10 +358,19 @@ pub(crate) fn new_session_info(
     }
 }
 
-pub(crate) fn new_user_prompt(message: String) -> PlainHistoryCell {
+/// `preamble` is the configured `user_turn_preamble`, shown dimmed above the
+/// message when the transcript should include it.
+pub(crate) fn new_user_prompt(message: String, preamble: Option<&str>) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
     lines.push(Line::from(""));
-    lines.push(Line::from("user".cyan().bold()));
+    lines.push(Line::from("user".fg(theme::user_message()).bold()));
+    if let Some(preamble) = preamble {
+        lines.extend(
+            preamble
+                .lines()
+                .map(|l| Line::from(l.to_string().dim().italic())),
+        );
+    }
     lines.extend(message.lines().map(|l| Line::from(l.to_string())));
 
     PlainHistoryCell { lines }
@@ -352,6 +380,7 @@ pub(crate) fn new_active_exec_command(
     command: Vec<String>,
     parsed: Vec<ParsedCommand>,
     include_header: bool,
+    output_max_lines: Option<usize>,
 ) -> ExecCell {
     ExecCell {
         command,
@@ -360,6 +389,7 @@ pub(crate) fn new_active_exec_command(
         start_time: Some(Instant::now()),
         duration: None,
         include_header,
+        output_max_lines: output_max_lines.unwrap_or(TOOL_CALL_MAX_LINES),
     }
 }
 
@@ -369,6 +399,7 @@ pub(crate) fn new_completed_exec_command(
     output: CommandOutput,
     include_header: bool,
     duration: Duration,
+    output_max_lines: Option<usize>,
 ) -> ExecCell {
     ExecCell {
         command,
@@ -377,6 +408,7 @@ pub(crate) fn new_completed_exec_command(
         start_time: None,
         duration: Some(duration),
         include_header,
+        output_max_lines: output_max_lines.unwrap_or(TOOL_CALL_MAX_LINES),
     }
 }
 
@@ -386,10 +418,24 @@ fn exec_command_lines(
     output: Option<&CommandOutput>,
     start_time: Option<Instant>,
     include_header: bool,
+    output_max_lines: usize,
 ) -> Vec<Line<'static>> {
     match parsed.is_empty() {
-        true => new_exec_command_generic(command, output, start_time, include_header),
-        false => new_parsed_command(command, parsed, output, start_time, include_header),
+        true => new_exec_command_generic(
+            command,
+            output,
+            start_time,
+            include_header,
+            output_max_lines,
+        ),
+        false => new_parsed_command(
+            command,
+            parsed,
+            output,
+            start_time,
+            include_header,
+            output_max_lines,
+        ),
     }
 }
 fn new_parsed_command(
@@ -398,6 +444,7 @@ fn new_parsed_command(
     output: Option<&CommandOutput>,
     start_time: Option<Instant>,
     include_header: bool,
+    output_max_lines: usize,
 ) -> Vec<Line<'static>> {
     let mut lines: Vec<Line> = Vec::new();
     // Leading spacer and header line above command list
@@ -424,21 +471,29 @@ fn new_parsed_command(
     for parsed in parsed_commands.iter() {
         let text = match parsed {
             ParsedCommand::Read { name, .. } => padded_emoji_with("📖", name),
-            ParsedCommand::ListFiles { cmd, path } => match path {
+            ParsedCommand::ListFiles { cmd, path, .. } => match path {
                 Some(p) => padded_emoji_with("📂", p),
                 None => padded_emoji_with("📂", cmd),
             },
-            ParsedCommand::Search { query, path, cmd } => match (query, path) {
+            ParsedCommand::Search {
+                query, path, cmd, ..
+            } => match (query, path) {
                 (Some(q), Some(p)) => padded_emoji_with("🔎", format!("{q} in {p}")),
                 (Some(q), None) => padded_emoji_with("🔎", q),
                 (None, Some(p)) => padded_emoji_with("🔎", p),
                 (None, None) => padded_emoji_with("🔎", cmd),
             },
             ParsedCommand::Format { .. } => padded_emoji_with("✨", "Formatting"),
-            ParsedCommand::Test { cmd } => padded_emoji_with("🧪", cmd),
+            ParsedCommand::Test { cmd, .. } => padded_emoji_with("🧪", cmd),
             ParsedCommand::Lint { cmd, .. } => padded_emoji_with("🧹", cmd),
-            ParsedCommand::Unknown { cmd } => padded_emoji_with("⌨️", cmd),
-            ParsedCommand::Noop { cmd } => padded_emoji_with("🔄", cmd),
+            ParsedCommand::Unknown { cmd, .. } => padded_emoji_with("⌨️", cmd),
+            ParsedCommand::Noop { cmd, .. } => padded_emoji_with("🔄", cmd),
+        };
+        // Elevated commands keep their classification but are always marked.
+        let text = if parsed.is_elevated() {
+            padded_emoji_with("🔐", text)
+        } else {
+            text
         };
         // Prefix: two spaces, marker, space. Continuations align under the text block.
         for (j, line_text) in text.lines().enumerate() {
@@ -458,7 +513,7 @@ fn new_parsed_command(
         }
     }
 
-    lines.extend(output_lines(output, true, false));
+    lines.extend(output_lines(output, true, false, output_max_lines));
 
     lines
 }
@@ -468,6 +523,7 @@ fn new_exec_command_generic(
     output: Option<&CommandOutput>,
     start_time: Option<Instant>,
     include_header: bool,
+    output_max_lines: usize,
 ) -> Vec<Line<'static>> {
     let mut lines: Vec<Line<'static>> = Vec::new();
     // Leading spacer and header line above command list
@@ -507,7 +563,7 @@ fn new_exec_command_generic(
         }
     }
 
-    lines.extend(output_lines(output, false, true));
+    lines.extend(output_lines(output, false, true, output_max_lines));
 
     lines
 }
@@ -523,11 +579,20 @@ pub(crate) fn new_active_mcp_tool_call(invocation: McpInvocation) -> PlainHistor
     PlainHistoryCell { lines }
 }
 
-pub(crate) fn new_web_search_call(query: String) -> PlainHistoryCell {
-    let lines: Vec<Line<'static>> = vec![
+pub(crate) fn new_web_search_call(query: String, results: &[WebSearchResult]) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
         Line::from(""),
         Line::from(vec![padded_emoji("🌐").into(), query.into()]),
     ];
+    for result in results {
+        let mut spans: Vec<Span<'static>> = vec!["  └ ".dim()];
+        if let Some(title) = &result.title {
+            spans.push(title.clone().into());
+            spans.push(" ".into());
+        }
+        spans.push(result.url.clone().dim().underlined());
+        lines.push(Line::from(spans));
+    }
     PlainHistoryCell { lines }
 }
 
@@ -575,6 +640,7 @@ pub(crate) fn new_completed_mcp_tool_call(
     num_cols: usize,
     invocation: McpInvocation,
     duration: Duration,
+    attempts: u32,
     success: bool,
     result: Result<mcp_types::CallToolResult, String>,
 ) -> Box<dyn HistoryCell> {
@@ -593,6 +659,11 @@ pub(crate) fn new_completed_mcp_tool_call(
             status_str.red()
         },
         format!(", duration: {duration}").dim(),
+        match attempts.saturating_sub(1) {
+            0 => "".into(),
+            1 => ", after 1 retry".dim(),
+            n => format!(", after {n} retries").dim(),
+        },
     ]);
 
     let mut lines: Vec<Line<'static>> = Vec::new();
@@ -941,11 +1012,57 @@ pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
     // in terminals like Ghostty.
     let lines: Vec<Line<'static>> = vec![
         "".into(),
-        vec![padded_emoji("🖐").red().bold(), " ".into(), message.into()].into(),
+        vec![
+            padded_emoji("🖐").fg(theme::error()).bold(),
+            " ".into(),
+            message.into(),
+        ]
+        .into(),
     ];
     PlainHistoryCell { lines }
 }
 
+pub(crate) fn new_context_warning_event(percent_remaining: u8) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            padded_emoji("⚠").cyan().bold(),
+            " ".into(),
+            format!("Only {percent_remaining}% of the context window is left. ").into(),
+            "Run /compact to summarize the conversation and free up space.".dim(),
+        ]
+        .into(),
+        "".into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_turn_timing_event(ev: TurnTimingEvent) -> PlainHistoryCell {
+    let TurnTimingEvent {
+        time_to_first_token,
+        total_duration,
+    } = ev;
+    let first_token = time_to_first_token
+        .map(format_duration)
+        .unwrap_or_else(|| "n/a".to_string());
+    let total = format_duration(total_duration);
+    let lines: Vec<Line<'static>> = vec![
+        format!("⏱ first token {first_token} · total {total}")
+            .dim()
+            .into(),
+        "".into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_compact_complete_event(tokens_saved: Option<u64>) -> PlainHistoryCell {
+    let message = match tokens_saved {
+        Some(tokens_saved) => format!("context compacted, saved {tokens_saved} tokens"),
+        None => "context compacted".to_string(),
+    };
+    let lines: Vec<Line<'static>> = vec![message.dim().into(), "".into()];
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_stream_error_event(message: String) -> PlainHistoryCell {
     let lines: Vec<Line<'static>> = vec![
         vec![
@@ -959,6 +1076,14 @@ pub(crate) fn new_stream_error_event(message: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+pub(crate) fn new_warning_event(message: String) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        vec![padded_emoji("⚠").cyan().bold(), " ".into(), message.into()].into(),
+        "".into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
 /// Render a user‑friendly plan update styled like a checkbox todo list.
 pub(crate) fn new_plan_update(update: UpdatePlanArgs) -> PlainHistoryCell {
     let UpdatePlanArgs { explanation, plan } = update;
@@ -1068,6 +1193,7 @@ pub(crate) fn new_patch_event(
 ) -> PlainHistoryCell {
     let title = match &event_type {
         PatchEventType::ApprovalRequest => "proposed patch",
+        PatchEventType::DryRun => "🔍 Previewing patch (dry run)",
         PatchEventType::ApplyBegin {
             auto_approved: true,
         } => "✏️ Applying patch",
@@ -1089,12 +1215,27 @@ pub(crate) fn new_patch_event(
     PlainHistoryCell { lines }
 }
 
-pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
+pub(crate) fn new_patch_apply_failure(
+    stderr: String,
+    conflicts: &[PatchConflict],
+    cwd: &Path,
+) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
 
     // Failure title
     lines.push(Line::from("✘ Failed to apply patch".magenta().bold()));
 
+    // One line per file that failed, so it is clear what to retry.
+    for (i, conflict) in conflicts.iter().enumerate() {
+        let prefix = if i == 0 { "  └ " } else { "    " };
+        let path = conflict.path.strip_prefix(cwd).unwrap_or(&conflict.path);
+        lines.push(Line::from(vec![
+            prefix.dim(),
+            path.display().to_string().into(),
+            format!(" ({})", conflict.reason).red(),
+        ]));
+    }
+
     if !stderr.trim().is_empty() {
         lines.extend(output_lines(
             Some(&CommandOutput {
@@ -1102,9 +1243,11 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
                 stdout: String::new(),
                 stderr,
                 formatted_output: String::new(),
+                timed_out: false,
             }),
             true,
             true,
+            TOOL_CALL_MAX_LINES,
         ));
     }
 
@@ -1113,6 +1256,17 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+/// A dry run found that the patch applies cleanly. Its diff was already shown
+/// with the preview, so only the outcome is reported.
+pub(crate) fn new_patch_dry_run_success() -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from("✓ Patch applies cleanly".magenta().bold()),
+        Line::from("  └ dry run, no files were changed").dim(),
+    ];
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_patch_apply_success(stdout: String) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
 
@@ -1167,7 +1321,7 @@ pub(crate) fn new_reasoning_block(
 ) -> TranscriptOnlyHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
     lines.push(Line::from(""));
-    lines.push(Line::from("thinking".magenta().italic()));
+    lines.push(Line::from("thinking".fg(theme::reasoning()).italic()));
     append_markdown(&full_reasoning_buffer, &mut lines, config);
     TranscriptOnlyHistoryCell { lines }
 }
@@ -1176,6 +1330,7 @@ fn output_lines(
     output: Option<&CommandOutput>,
     only_err: bool,
     include_angle_pipe: bool,
+    limit: usize,
 ) -> Vec<Line<'static>> {
     let CommandOutput {
         exit_code,
@@ -1191,7 +1346,6 @@ fn output_lines(
     let src = if *exit_code == 0 { stdout } else { stderr };
     let lines: Vec<&str> = src.lines().collect();
     let total = lines.len();
-    let limit = TOOL_CALL_MAX_LINES;
 
     let mut out = Vec::new();
 
@@ -1263,8 +1417,9 @@ mod tests {
     fn parsed_command_with_newlines_starts_each_line_at_origin() {
         let parsed = vec![ParsedCommand::Unknown {
             cmd: "printf 'foo\nbar'".to_string(),
+            elevated: false,
         }];
-        let lines = exec_command_lines(&[], &parsed, None, None, true);
+        let lines = exec_command_lines(&[], &parsed, None, None, true, TOOL_CALL_MAX_LINES);
         assert!(lines.len() >= 4);
         // Leading spacer then header line
         assert!(lines[0].spans.is_empty() || lines[0].spans[0].content.is_empty());
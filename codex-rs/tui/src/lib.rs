@@ -12,6 +12,7 @@ use codex_core::config::find_codex_home;
 use codex_core::config::load_config_as_toml_with_cli_overrides;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::timestamps::TimestampTimer;
 use codex_login::AuthManager;
 use codex_login::AuthMode;
 use codex_login::CodexAuth;
@@ -53,7 +54,9 @@ mod shimmer;
 mod slash_command;
 mod status_indicator_widget;
 mod streaming;
+mod terminal;
 mod text_formatting;
+mod theme;
 mod tui;
 mod user_approval_widget;
 
@@ -167,6 +170,28 @@ pub async fn run_main(
         }
     };
 
+    // `--full-auto` and `--dangerously-bypass-approvals-and-sandbox` take
+    // precedence over `--sandbox`.
+    let danger_full_access_requested = !cli.full_auto
+        && !cli.dangerously_bypass_approvals_and_sandbox
+        && matches!(
+            cli.sandbox_mode,
+            Some(codex_common::SandboxModeCliArg::DangerFullAccess)
+        );
+    if danger_full_access_requested
+        && config.confirm_danger_full_access
+        && !cli.confirm_danger_full_access
+    {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!(
+                "Refusing to run with `--sandbox danger-full-access` without confirmation. \
+                 Pass `--confirm-danger-full-access` or set `confirm_danger_full_access = false`."
+            );
+            std::process::exit(1);
+        }
+    }
+
     // we load config.toml here to determine project state.
     #[allow(clippy::print_stderr)]
     let config_toml = {
@@ -226,6 +251,7 @@ pub async fn run_main(
     let file_layer = tracing_subscriber::fmt::layer()
         .with_writer(non_blocking)
         .with_target(false)
+        .with_timer(TimestampTimer(config.timestamps))
         .with_filter(env_filter());
 
     if cli.oss {
@@ -312,8 +338,10 @@ async fn run_ratatui_app(
 
     // Initialize high-fidelity session event logging if enabled.
     session_log::maybe_init(&config);
+    theme::init(config.theme);
 
     let Cli { prompt, images, .. } = cli;
+    let prompt = config.resolve_initial_prompt(prompt);
 
     let auth_manager = AuthManager::shared(config.codex_home.clone(), config.preferred_auth_method);
     let login_status = get_login_status(&config);
@@ -2,11 +2,13 @@ use std::io::Result;
 use std::time::Duration;
 
 use crate::insert_history;
+use crate::terminal;
 use crate::tui;
 use crate::tui::TuiEvent;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
@@ -45,6 +47,15 @@ impl Overlay {
             Overlay::Static(o) => o.is_done(),
         }
     }
+
+    /// True while a search query is being typed, in which case every key
+    /// (including Esc and Enter) belongs to the overlay.
+    pub(crate) fn is_search_input_active(&self) -> bool {
+        match self {
+            Overlay::Transcript(o) => o.search.editing,
+            Overlay::Static(_) => false,
+        }
+    }
 }
 
 // Common pager navigation hints rendered on the first line
@@ -71,12 +82,86 @@ fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&str, &str)]) {
     Paragraph::new(vec![Line::from(spans).dim()]).render_ref(area, buf);
 }
 
+/// Byte ranges of the ASCII case-insensitive occurrences of `query` in `text`.
+fn match_ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let needle = query.to_ascii_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    text.to_ascii_lowercase()
+        .match_indices(&needle)
+        .map(|(start, m)| (start, start + m.len()))
+        .collect()
+}
+
+fn line_text(line: &Line<'_>) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+/// Returns `line` with every occurrence of `query` styled as a search match.
+/// Occurrences split across a wrap boundary are not highlighted.
+fn highlight_query(line: Line<'static>, query: &str, current: bool) -> Line<'static> {
+    let ranges = match_ranges(&line_text(&line), query);
+    if ranges.is_empty() {
+        return line;
+    }
+    let mut spans: Vec<Span<'static>> = Vec::with_capacity(line.spans.len() + ranges.len() * 2);
+    let mut offset = 0;
+    for span in line.spans {
+        let content = span.content.as_ref();
+        let span_end = offset + content.len();
+        let mut pos = offset;
+        for &(start, end) in &ranges {
+            let (start, end) = (start.max(pos), end.min(span_end));
+            if start >= end {
+                continue;
+            }
+            if start > pos {
+                spans.push(Span::styled(
+                    content[pos - offset..start - offset].to_string(),
+                    span.style,
+                ));
+            }
+            let matched = Span::styled(
+                content[start - offset..end - offset].to_string(),
+                span.style,
+            );
+            spans.push(if current {
+                matched.reversed()
+            } else {
+                matched.cyan().underlined()
+            });
+            pos = end;
+        }
+        if pos < span_end {
+            spans.push(Span::styled(
+                content[pos - offset..].to_string(),
+                span.style,
+            ));
+        }
+        offset = span_end;
+    }
+    Line { spans, ..line }
+}
+
+/// Search matches to highlight on a rendered page.
+struct SearchHighlight<'a> {
+    query: &'a str,
+    /// Source line holding the selected match.
+    current_line: Option<usize>,
+}
+
 /// Generic widget for rendering a pager view.
 struct PagerView {
     lines: Vec<Line<'static>>,
     scroll_offset: usize,
     title: String,
     wrap_cache: Option<WrapCache>,
+    /// Source line to bring into view on the next render, once the wrapped
+    /// row it starts on is known.
+    pending_scroll_to_line: Option<usize>,
+    /// Outcome of the last copy, shown until the next key press.
+    status: Option<String>,
 }
 
 impl PagerView {
@@ -86,6 +171,8 @@ impl PagerView {
             scroll_offset,
             title,
             wrap_cache: None,
+            pending_scroll_to_line: None,
+            status: None,
         }
     }
 
@@ -93,6 +180,7 @@ impl PagerView {
         self.render_header(area, buf);
         let content_area = self.scroll_area(area);
         self.ensure_wrapped(content_area.width);
+        self.apply_pending_scroll();
         // Compute page bounds without holding an immutable borrow on cache while mutating self
         let wrapped_len = self
             .wrap_cache
@@ -116,10 +204,12 @@ impl PagerView {
         area: Rect,
         buf: &mut Buffer,
         highlight: Option<(usize, usize)>,
+        search: Option<&SearchHighlight>,
     ) {
         self.render_header(area, buf);
         let content_area = self.scroll_area(area);
         self.ensure_wrapped(content_area.width);
+        self.apply_pending_scroll();
         // Compute page bounds first to avoid borrow conflicts
         let wrapped_len = self
             .wrap_cache
@@ -133,7 +223,8 @@ impl PagerView {
         let end = (start + content_area.height as usize).min(wrapped_len);
 
         let (wrapped, src_idx) = self.cached();
-        let page = self.page_with_optional_highlight(wrapped, src_idx, start, end, highlight);
+        let page =
+            self.page_with_optional_highlight(wrapped, src_idx, start, end, highlight, search);
         self.render_content_page_prepared(content_area, buf, &page);
         self.render_bottom_bar(area, content_area, buf, wrapped);
     }
@@ -196,7 +287,17 @@ impl PagerView {
     }
 
     fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> Result<()> {
+        if key_event.kind == KeyEventKind::Press {
+            self.status = None;
+        }
         match key_event {
+            KeyEvent {
+                code: KeyCode::Char('y'),
+                kind: KeyEventKind::Press,
+                ..
+            } => {
+                self.copy_to_clipboard();
+            }
             KeyEvent {
                 code: KeyCode::Up,
                 kind: KeyEventKind::Press | KeyEventKind::Repeat,
@@ -250,6 +351,26 @@ impl PagerView {
         Ok(())
     }
 
+    /// Copy the full pager content to the system clipboard as plain text.
+    fn copy_to_clipboard(&mut self) {
+        let text = self
+            .lines
+            .iter()
+            .map(line_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.status = Some(match terminal::copy_to_clipboard(&text) {
+            Ok(()) => format!("copied {} lines to clipboard", self.lines.len()),
+            Err(err) => format!("copy failed: {err}"),
+        });
+    }
+
+    fn render_status(&self, area: Rect, buf: &mut Buffer) {
+        if let Some(status) = &self.status {
+            Line::from(vec![" ".into(), Span::from(status.clone()).dim()]).render_ref(area, buf);
+        }
+    }
+
     fn scroll_area(&self, area: Rect) -> Rect {
         let mut area = area;
         area.y = area.y.saturating_add(1);
@@ -291,6 +412,19 @@ impl PagerView {
         });
     }
 
+    fn apply_pending_scroll(&mut self) {
+        let Some(line) = self.pending_scroll_to_line.take() else {
+            return;
+        };
+        if let Some(row) = self
+            .wrap_cache
+            .as_ref()
+            .and_then(|c| c.src_idx.iter().position(|&src| src == line))
+        {
+            self.scroll_offset = row;
+        }
+    }
+
     fn cached(&self) -> (&[Line<'static>], &[usize]) {
         if let Some(cache) = self.wrap_cache.as_ref() {
             (&cache.wrapped, &cache.src_idx)
@@ -306,12 +440,13 @@ impl PagerView {
         start: usize,
         end: usize,
         highlight: Option<(usize, usize)>,
+        search: Option<&SearchHighlight>,
     ) -> std::borrow::Cow<'a, [Line<'static>]> {
         use ratatui::style::Modifier;
-        let (hi_start, hi_end) = match highlight {
-            Some(r) => r,
-            None => return std::borrow::Cow::Borrowed(&wrapped[start..end]),
-        };
+        if highlight.is_none() && search.is_none() {
+            return std::borrow::Cow::Borrowed(&wrapped[start..end]);
+        }
+        let (hi_start, hi_end) = highlight.unwrap_or((0, 0));
         let mut out: Vec<Line<'static>> = Vec::with_capacity(end - start);
         let mut bold_done = false;
         for (row, src_line) in wrapped
@@ -321,7 +456,12 @@ impl PagerView {
             .take(end.saturating_sub(start))
         {
             let mut line = src_line.clone();
-            if let Some(src) = src_idx.get(row).copied()
+            let src = src_idx.get(row).copied();
+            if let Some(search) = search {
+                let current = src.is_some() && src == search.current_line;
+                line = highlight_query(line, search.query, current);
+            }
+            if let Some(src) = src
                 && src >= hi_start
                 && src < hi_end
             {
@@ -339,9 +479,29 @@ impl PagerView {
     }
 }
 
+/// State of the `/` search in the transcript overlay. Matching is done on
+/// the stored source lines, so results do not depend on the terminal width.
+#[derive(Debug, Default)]
+struct TranscriptSearch {
+    query: String,
+    /// True while the query is being typed.
+    editing: bool,
+    /// Indices of the transcript lines containing `query`.
+    matches: Vec<usize>,
+    /// Position of the selected match in `matches`.
+    current: usize,
+}
+
+impl TranscriptSearch {
+    fn current_line(&self) -> Option<usize> {
+        self.matches.get(self.current).copied()
+    }
+}
+
 pub(crate) struct TranscriptOverlay {
     view: PagerView,
     highlight_range: Option<(usize, usize)>,
+    search: TranscriptSearch,
     is_done: bool,
 }
 
@@ -354,13 +514,22 @@ impl TranscriptOverlay {
                 usize::MAX,
             ),
             highlight_range: None,
+            search: TranscriptSearch::default(),
             is_done: false,
         }
     }
 
     pub(crate) fn insert_lines(&mut self, lines: Vec<Line<'static>>) {
+        let first_new = self.view.lines.len();
         self.view.lines.extend(lines);
         self.view.wrap_cache = None;
+        if !self.search.editing && !self.search.query.is_empty() {
+            let query = &self.search.query;
+            self.search.matches.extend(
+                (first_new..self.view.lines.len())
+                    .filter(|&i| !match_ranges(&line_text(&self.view.lines[i]), query).is_empty()),
+            );
+        }
     }
 
     pub(crate) fn set_highlight_range(&mut self, range: Option<(usize, usize)>) {
@@ -377,15 +546,118 @@ impl TranscriptOverlay {
         {
             pairs.push(("⏎", "edit message"));
         }
+        pairs.push(("/", "search"));
+        if !self.search.matches.is_empty() {
+            pairs.push(("n/N", "next/prev"));
+        }
+        pairs.push(("y", "copy"));
         render_key_hints(line2, buf, &pairs);
+
+        let line3 = Rect::new(area.x, area.y.saturating_add(2), area.width, 1);
+        if self.search.editing {
+            Line::from(vec![" /".into(), self.search.query.clone().into()]).render_ref(line3, buf);
+        } else if !self.search.query.is_empty() {
+            let status = if self.search.matches.is_empty() {
+                "no matches".to_string()
+            } else {
+                format!(
+                    "match {}/{}",
+                    self.search.current + 1,
+                    self.search.matches.len()
+                )
+            };
+            Line::from(vec![
+                " /".into(),
+                self.search.query.clone().into(),
+                "  ".into(),
+                Span::from(status).dim(),
+            ])
+            .render_ref(line3, buf);
+        } else {
+            self.view.render_status(line3, buf);
+        }
+    }
+
+    /// Handle a key aimed at the search prompt or match navigation. Returns
+    /// `false` when the key is not search related.
+    fn handle_search_key(&mut self, key_event: KeyEvent) -> bool {
+        if key_event.kind == KeyEventKind::Release {
+            return false;
+        }
+        if self.search.editing {
+            match key_event.code {
+                KeyCode::Enter => {
+                    self.search.editing = false;
+                    self.run_search();
+                }
+                KeyCode::Esc => self.search = TranscriptSearch::default(),
+                KeyCode::Backspace => {
+                    self.search.query.pop();
+                }
+                KeyCode::Char(c)
+                    if !key_event
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    self.search.query.push(c);
+                }
+                _ => return false,
+            }
+            return true;
+        }
+        match key_event.code {
+            KeyCode::Char('/') => {
+                self.search = TranscriptSearch {
+                    editing: true,
+                    ..Default::default()
+                };
+            }
+            KeyCode::Char('n') if !self.search.matches.is_empty() => self.step_match(true),
+            KeyCode::Char('N') if !self.search.matches.is_empty() => self.step_match(false),
+            _ => return false,
+        }
+        true
+    }
+
+    fn run_search(&mut self) {
+        let query = &self.search.query;
+        self.search.matches = self
+            .view
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !match_ranges(&line_text(line), query).is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        self.search.current = 0;
+        self.view.pending_scroll_to_line = self.search.current_line();
+    }
+
+    /// Select the next (or previous) match, wrapping around at either end.
+    fn step_match(&mut self, forward: bool) {
+        let len = self.search.matches.len();
+        if len == 0 {
+            return;
+        }
+        self.search.current = if forward {
+            (self.search.current + 1) % len
+        } else {
+            (self.search.current + len - 1) % len
+        };
+        self.view.pending_scroll_to_line = self.search.current_line();
     }
 
     pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
         let top_h = area.height.saturating_sub(3);
         let top = Rect::new(area.x, area.y, area.width, top_h);
         let bottom = Rect::new(area.x, area.y + top_h, area.width, 3);
+        let search =
+            (!self.search.editing && !self.search.query.is_empty()).then(|| SearchHighlight {
+                query: &self.search.query,
+                current_line: self.search.current_line(),
+            });
         self.view
-            .render_with_highlight(top, buf, self.highlight_range);
+            .render_with_highlight(top, buf, self.highlight_range, search.as_ref());
         self.render_hints(bottom, buf);
     }
 }
@@ -393,6 +665,11 @@ impl TranscriptOverlay {
 impl TranscriptOverlay {
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match event {
+            TuiEvent::Key(key_event) if self.handle_search_key(key_event) => {
+                tui.frame_requester()
+                    .schedule_frame_in(Duration::from_millis(16));
+                Ok(())
+            }
             TuiEvent::Key(key_event) => match key_event {
                 KeyEvent {
                     code: KeyCode::Char('q'),
@@ -450,8 +727,10 @@ impl StaticOverlay {
         let line1 = Rect::new(area.x, area.y, area.width, 1);
         let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
         render_key_hints(line1, buf, PAGER_KEY_HINTS);
-        let pairs = [("q", "quit")];
+        let pairs = [("q", "quit"), ("y", "copy")];
         render_key_hints(line2, buf, &pairs);
+        let line3 = Rect::new(area.x, area.y.saturating_add(2), area.width, 1);
+        self.view.render_status(line3, buf);
     }
 
     pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
@@ -503,6 +782,7 @@ mod tests {
     use insta::assert_snapshot;
     use ratatui::Terminal;
     use ratatui::backend::TestBackend;
+    use ratatui::style::Modifier;
 
     #[test]
     fn edit_prev_hint_is_visible() {
@@ -541,6 +821,81 @@ mod tests {
         assert_snapshot!(term.backend());
     }
 
+    fn type_keys(overlay: &mut TranscriptOverlay, keys: &[KeyCode]) {
+        for code in keys {
+            assert!(overlay.handle_search_key(KeyEvent::new(*code, KeyModifiers::NONE)));
+        }
+    }
+
+    fn search(overlay: &mut TranscriptOverlay, query: &str) {
+        type_keys(overlay, &[KeyCode::Char('/')]);
+        let chars: Vec<KeyCode> = query.chars().map(KeyCode::Char).collect();
+        type_keys(overlay, &chars);
+        type_keys(overlay, &[KeyCode::Enter]);
+    }
+
+    #[test]
+    fn transcript_search_finds_and_highlights_matches() {
+        let mut overlay = TranscriptOverlay::new(vec![
+            Line::from("alpha"),
+            Line::from("beta"),
+            Line::from("Alphabet"),
+        ]);
+        search(&mut overlay, "alph");
+        assert_eq!(overlay.search.matches, vec![0, 2]);
+        assert_eq!(overlay.search.current_line(), Some(0));
+
+        let area = Rect::new(0, 0, 40, 10);
+        let mut buf = Buffer::empty(area);
+        overlay.render(area, &mut buf);
+
+        // The selected match is reversed, other matches are underlined.
+        assert!(buf[(0, 1)].modifier.contains(Modifier::REVERSED));
+        assert!(!buf[(4, 1)].modifier.contains(Modifier::REVERSED));
+        assert_eq!(buf[(0, 3)].fg, Color::Cyan);
+        assert!(buf[(0, 3)].modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(buf[(0, 2)].fg, Color::Reset);
+
+        let footer: String = (0..area.width)
+            .map(|x| buf[(x, 9)].symbol().to_string())
+            .collect();
+        assert!(footer.contains("/alph  match 1/2"), "footer: {footer:?}");
+    }
+
+    #[test]
+    fn transcript_search_n_and_shift_n_cycle_matches() {
+        let mut overlay = TranscriptOverlay::new(vec![
+            Line::from("one match"),
+            Line::from("nothing here"),
+            Line::from("two match"),
+            Line::from("three match"),
+        ]);
+        search(&mut overlay, "MATCH");
+        assert_eq!(overlay.search.matches, vec![0, 2, 3]);
+
+        type_keys(&mut overlay, &[KeyCode::Char('n')]);
+        assert_eq!(overlay.search.current_line(), Some(2));
+        type_keys(&mut overlay, &[KeyCode::Char('n'), KeyCode::Char('n')]);
+        assert_eq!(overlay.search.current_line(), Some(0));
+        type_keys(&mut overlay, &[KeyCode::Char('N')]);
+        assert_eq!(overlay.search.current_line(), Some(3));
+        assert_eq!(overlay.view.pending_scroll_to_line, Some(3));
+    }
+
+    #[test]
+    fn transcript_search_esc_cancels_query() {
+        let mut overlay = TranscriptOverlay::new(vec![Line::from("alpha")]);
+        type_keys(&mut overlay, &[KeyCode::Char('/'), KeyCode::Char('q')]);
+        assert_eq!(overlay.search.query, "q");
+        assert!(!overlay.is_done);
+
+        type_keys(&mut overlay, &[KeyCode::Esc]);
+        assert!(!overlay.search.editing);
+        assert!(overlay.search.query.is_empty());
+        // Without an active search, `n` is left to the pager.
+        assert!(!overlay.handle_search_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)));
+    }
+
     #[test]
     fn static_overlay_snapshot_basic() {
         // Prepare a static overlay with a few lines and a title
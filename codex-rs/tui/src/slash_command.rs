@@ -17,7 +17,9 @@ pub enum SlashCommand {
     New,
     Init,
     Compact,
+    Retry,
     Diff,
+    Cd,
     Mention,
     Status,
     Mcp,
@@ -34,8 +36,12 @@ impl SlashCommand {
             SlashCommand::New => "start a new chat during a conversation",
             SlashCommand::Init => "create an AGENTS.md file with instructions for Codex",
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
+            SlashCommand::Retry => {
+                "retry your last message (`--model <name>` to use another model)"
+            }
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
+            SlashCommand::Cd => "change the working directory",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Model => "choose what model and reasoning effort to use",
@@ -61,6 +67,8 @@ impl SlashCommand {
             | SlashCommand::Compact
             | SlashCommand::Model
             | SlashCommand::Approvals
+            | SlashCommand::Cd
+            | SlashCommand::Retry
             | SlashCommand::Logout => false,
             SlashCommand::Diff
             | SlashCommand::Mention
@@ -72,6 +80,12 @@ impl SlashCommand {
             SlashCommand::TestApproval => true,
         }
     }
+
+    /// Whether the text following the command (e.g. the path in `/cd src`)
+    /// should be passed along when the command is dispatched.
+    pub fn takes_arguments(self) -> bool {
+        matches!(self, SlashCommand::Cd | SlashCommand::Retry)
+    }
 }
 
 /// Return all built-in commands in a Vec paired with their command string.
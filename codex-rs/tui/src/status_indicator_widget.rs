@@ -24,6 +24,8 @@ pub(crate) struct StatusIndicatorWidget {
     header: String,
     /// Queued user messages to display under the status line.
     queued_messages: Vec<String>,
+    /// The agent is blocked until the user resolves an approval request.
+    awaiting_approval: bool,
 
     start_time: Instant,
     app_event_tx: AppEventSender,
@@ -35,6 +37,7 @@ impl StatusIndicatorWidget {
         Self {
             header: String::from("Working"),
             queued_messages: Vec::new(),
+            awaiting_approval: false,
             start_time: Instant::now(),
 
             app_event_tx,
@@ -81,6 +84,15 @@ impl StatusIndicatorWidget {
         }
     }
 
+    /// Switch between the animated header and the "waiting for approval"
+    /// state, which also advertises the quick approve/deny keys.
+    pub(crate) fn set_awaiting_approval(&mut self, awaiting: bool) {
+        if self.awaiting_approval != awaiting {
+            self.awaiting_approval = awaiting;
+            self.frame_requester.schedule_frame();
+        }
+    }
+
     /// Replace the queued messages displayed beneath the header.
     pub(crate) fn set_queued_messages(&mut self, queued: Vec<String>) {
         self.queued_messages = queued;
@@ -102,13 +114,25 @@ impl WidgetRef for StatusIndicatorWidget {
 
         // Plain rendering: no borders or padding so the live cell is visually indistinguishable from terminal scrollback.
         let mut spans = vec![" ".into()];
-        spans.extend(shimmer_spans(&self.header));
-        spans.extend(vec![
-            " ".into(),
-            format!("({elapsed}s • ").dim(),
-            "Esc".dim().bold(),
-            " to interrupt)".dim(),
-        ]);
+        if self.awaiting_approval {
+            spans.extend(vec![
+                "Waiting for approval".bold(),
+                " ".into(),
+                format!("({elapsed}s • ").dim(),
+                "y".dim().bold(),
+                " approve • ".dim(),
+                "d".dim().bold(),
+                " deny)".dim(),
+            ]);
+        } else {
+            spans.extend(shimmer_spans(&self.header));
+            spans.extend(vec![
+                " ".into(),
+                format!("({elapsed}s • ").dim(),
+                "Esc".dim().bold(),
+                " to interrupt)".dim(),
+            ]);
+        }
 
         // Build lines: status, then queued messages, then spacer.
         let mut lines: Vec<Line<'static>> = Vec::new();
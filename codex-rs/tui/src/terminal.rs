@@ -0,0 +1,158 @@
+//! Terminal capabilities that are not covered by crossterm.
+//!
+//! [`copy_to_clipboard`] uses the OSC 52 escape sequence, which asks the
+//! terminal emulator itself to set the system clipboard. Because the sequence
+//! travels over the pty, it also works when Codex runs on a remote host over
+//! SSH.
+
+use std::io::IsTerminal;
+use std::io::Result;
+use std::io::Write;
+use std::io::stdout;
+
+use base64::Engine;
+
+/// Largest base64 payload we send. Many terminals drop OSC 52 requests
+/// above roughly 100 kB, so larger content is rejected up front instead of
+/// silently not reaching the clipboard.
+const MAX_OSC52_PAYLOAD_BYTES: usize = 100_000;
+
+/// Size of each write to the terminal, so a large sequence does not rely on
+/// the pty accepting it in one go.
+const WRITE_CHUNK_BYTES: usize = 4096;
+
+/// GNU screen truncates DCS strings longer than 768 bytes, so the payload is
+/// split into pieces well below that, each in its own passthrough.
+const SCREEN_CHUNK_BYTES: usize = 76;
+
+/// Terminal multiplexer the sequence has to be tunnelled through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Multiplexer {
+    None,
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    fn detect() -> Self {
+        if std::env::var_os("TMUX").is_some() {
+            Multiplexer::Tmux
+        } else if std::env::var("TERM").is_ok_and(|term| term.starts_with("screen")) {
+            Multiplexer::Screen
+        } else {
+            Multiplexer::None
+        }
+    }
+}
+
+/// Whether stdout is a terminal that is expected to understand OSC 52.
+pub(crate) fn supports_osc52() -> bool {
+    if !stdout().is_terminal() {
+        return false;
+    }
+    // The Linux console and dumb terminals print the payload instead.
+    !matches!(std::env::var("TERM").as_deref(), Ok("dumb" | "linux"))
+}
+
+/// Copy `text` to the system clipboard using OSC 52.
+pub(crate) fn copy_to_clipboard(text: &str) -> Result<()> {
+    if !supports_osc52() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "terminal does not support OSC 52 clipboard access",
+        ));
+    }
+    let chunks = osc52_chunks(text, Multiplexer::detect())?;
+    let mut out = stdout().lock();
+    for chunk in chunks {
+        out.write_all(chunk.as_bytes())?;
+    }
+    out.flush()
+}
+
+/// The OSC 52 sequence that sets the clipboard to `text`, split into the
+/// pieces to write to the terminal in order.
+fn osc52_chunks(text: &str, mux: Multiplexer) -> Result<Vec<String>> {
+    let payload = base64::engine::general_purpose::STANDARD.encode(text);
+    if payload.len() > MAX_OSC52_PAYLOAD_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "content is too large to copy ({} bytes encoded, limit {MAX_OSC52_PAYLOAD_BYTES})",
+                payload.len()
+            ),
+        ));
+    }
+    let chunks = match mux {
+        Multiplexer::None => split_ascii(&format!("\x1b]52;c;{payload}\x07"), WRITE_CHUNK_BYTES),
+        // tmux forwards a DCS passthrough with every ESC doubled.
+        Multiplexer::Tmux => split_ascii(
+            &format!("\x1bPtmux;\x1b\x1b]52;c;{payload}\x07\x1b\\"),
+            WRITE_CHUNK_BYTES,
+        ),
+        Multiplexer::Screen => {
+            let mut chunks = vec!["\x1bP\x1b]52;c;\x1b\\".to_string()];
+            chunks.extend(
+                split_ascii(&payload, SCREEN_CHUNK_BYTES)
+                    .into_iter()
+                    .map(|piece| format!("\x1bP{piece}\x1b\\")),
+            );
+            chunks.push("\x1bP\x07\x1b\\".to_string());
+            chunks
+        }
+    };
+    Ok(chunks)
+}
+
+/// Split an ASCII string into pieces of at most `size` bytes.
+fn split_ascii(s: &str, size: usize) -> Vec<String> {
+    s.as_bytes()
+        .chunks(size)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn osc52_sequence_encodes_text_as_base64() {
+        let chunks = osc52_chunks("hello", Multiplexer::None).expect("sequence");
+        assert_eq!(chunks, vec!["\x1b]52;c;aGVsbG8=\x07".to_string()]);
+
+        let chunks = osc52_chunks("hello", Multiplexer::Tmux).expect("sequence");
+        assert_eq!(
+            chunks,
+            vec!["\x1bPtmux;\x1b\x1b]52;c;aGVsbG8=\x07\x1b\\".to_string()]
+        );
+    }
+
+    #[test]
+    fn large_content_is_chunked_and_oversized_content_rejected() {
+        let text = "x".repeat(10_000);
+        let payload = base64::engine::general_purpose::STANDARD.encode(&text);
+
+        let chunks = osc52_chunks(&text, Multiplexer::None).expect("sequence");
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= WRITE_CHUNK_BYTES));
+        assert_eq!(chunks.concat(), format!("\x1b]52;c;{payload}\x07"));
+
+        let chunks = osc52_chunks(&text, Multiplexer::Screen).expect("sequence");
+        let inner: Vec<&str> = chunks[1..chunks.len() - 1]
+            .iter()
+            .map(|c| {
+                c.strip_prefix("\x1bP")
+                    .and_then(|c| c.strip_suffix("\x1b\\"))
+                    .expect("DCS passthrough")
+            })
+            .collect();
+        assert!(inner.iter().all(|piece| piece.len() <= SCREEN_CHUNK_BYTES));
+        assert_eq!(inner.concat(), payload);
+
+        let oversized = "x".repeat(MAX_OSC52_PAYLOAD_BYTES);
+        let err = osc52_chunks(&oversized, Multiplexer::None).expect_err("too large");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,72 @@
+//! Process-wide color theme for the TUI, loaded from the `[theme]` config
+//! table. Styling code asks for a semantic role (e.g. [`error`]) rather than
+//! hard-coding a color.
+
+use std::sync::OnceLock;
+
+use codex_core::config_types::Theme;
+use codex_core::config_types::ThemeColor;
+use ratatui::style::Color;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Install the theme from config. Only the first call has an effect; until
+/// then (e.g. in tests) the default theme is used.
+pub(crate) fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+fn current() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+pub(crate) fn user_message() -> Color {
+    to_color(current().user_message)
+}
+
+pub(crate) fn agent_message() -> Color {
+    to_color(current().agent_message)
+}
+
+pub(crate) fn reasoning() -> Color {
+    to_color(current().reasoning)
+}
+
+pub(crate) fn error() -> Color {
+    to_color(current().error)
+}
+
+pub(crate) fn diff_add() -> Color {
+    to_color(current().diff_add)
+}
+
+pub(crate) fn diff_remove() -> Color {
+    to_color(current().diff_remove)
+}
+
+fn to_color(color: ThemeColor) -> Color {
+    match color {
+        ThemeColor::Black => Color::Black,
+        ThemeColor::Red => Color::Red,
+        ThemeColor::Green => Color::Green,
+        ThemeColor::Yellow => Color::Yellow,
+        ThemeColor::Blue => Color::Blue,
+        ThemeColor::Magenta => Color::Magenta,
+        ThemeColor::Cyan => Color::Cyan,
+        ThemeColor::Gray => Color::Gray,
+        ThemeColor::DarkGray => Color::DarkGray,
+        ThemeColor::LightRed => Color::LightRed,
+        ThemeColor::LightGreen => Color::LightGreen,
+        ThemeColor::LightYellow => Color::LightYellow,
+        ThemeColor::LightBlue => Color::LightBlue,
+        ThemeColor::LightMagenta => Color::LightMagenta,
+        ThemeColor::LightCyan => Color::LightCyan,
+        ThemeColor::White => Color::White,
+        // Only reachable when the user configured an RGB color themselves.
+        #[expect(
+            clippy::disallowed_methods,
+            reason = "RGB theme colors are an explicit user choice"
+        )]
+        ThemeColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
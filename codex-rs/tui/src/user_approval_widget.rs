@@ -41,12 +41,17 @@ pub(crate) enum ApprovalRequest {
         id: String,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        grant_paths: Vec<PathBuf>,
+        /// Files the patch deletes, when there are enough of them that
+        /// approving requires a second confirmation. Empty otherwise.
+        deleted_files: Vec<PathBuf>,
     },
 }
 
 /// Options displayed in the *select* mode.
 ///
 /// The `key` is matched case-insensitively.
+#[derive(Clone)]
 struct SelectOption {
     label: Line<'static>,
     description: &'static str,
@@ -77,13 +82,52 @@ static COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
     ]
 });
 
-static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+/// Patch options, offering session-wide write access to the patched files
+/// and/or their directory when the request includes them.
+fn patch_select_options(grant_root: bool, grant_paths: bool) -> Vec<SelectOption> {
+    let mut options = vec![SelectOption {
+        label: Line::from(vec!["Y".underlined(), "es".into()]),
+        description: "Approve and apply the changes",
+        key: KeyCode::Char('y'),
+        decision: ReviewDecision::Approved,
+    }];
+    if grant_paths {
+        options.push(SelectOption {
+            label: Line::from(vec!["F".underlined(), "iles for session".into()]),
+            description: "Approve and allow writes to these files for the remainder of this session",
+            key: KeyCode::Char('f'),
+            decision: ReviewDecision::ApprovedPathsForSession,
+        });
+    }
+    if grant_root {
+        options.push(SelectOption {
+            label: Line::from(vec!["A".underlined(), "lways".into()]),
+            description: "Approve and allow writes under the directory for the remainder of this session",
+            key: KeyCode::Char('a'),
+            decision: ReviewDecision::ApprovedForSession,
+        });
+    }
+    options.push(SelectOption {
+        label: Line::from(vec!["N".underlined(), "o, provide feedback".into()]),
+        description: "Do not apply the changes; provide feedback",
+        key: KeyCode::Char('n'),
+        decision: ReviewDecision::Abort,
+    });
+    options
+}
+
+/// Options for the second confirmation of a patch that deletes many files.
+/// `approval` is the decision picked in the first step.
+fn delete_confirmation_options(approval: ReviewDecision, count: usize) -> Vec<SelectOption> {
     vec![
         SelectOption {
-            label: Line::from(vec!["Y".underlined(), "es".into()]),
-            description: "Approve and apply the changes",
+            label: Line::from(vec![
+                "Y".underlined(),
+                format!("es, delete {count} files").into(),
+            ]),
+            description: "Apply the changes, including the deletions",
             key: KeyCode::Char('y'),
-            decision: ReviewDecision::Approved,
+            decision: approval,
         },
         SelectOption {
             label: Line::from(vec!["N".underlined(), "o, provide feedback".into()]),
@@ -92,18 +136,45 @@ static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
             decision: ReviewDecision::Abort,
         },
     ]
-});
+}
+
+/// Maximum number of deleted files listed in the deletion confirmation.
+const MAX_LISTED_DELETIONS: usize = 10;
+
+fn delete_confirmation_prompt(deleted_files: &[PathBuf]) -> Paragraph<'static> {
+    let mut contents: Vec<Line> = vec![Line::from(vec![
+        "! ".red(),
+        format!("This patch deletes {} files:", deleted_files.len()).bold(),
+    ])];
+    for path in deleted_files.iter().take(MAX_LISTED_DELETIONS) {
+        contents.push(Line::from(format!("    {}", path.display()).dim()));
+    }
+    if deleted_files.len() > MAX_LISTED_DELETIONS {
+        let more = deleted_files.len() - MAX_LISTED_DELETIONS;
+        contents.push(Line::from(format!("    … and {more} more").dim()));
+    }
+    contents.push(Line::from(""));
+    Paragraph::new(contents).wrap(Wrap { trim: false })
+}
+
+/// Shortcut that denies the pending request without interrupting the task.
+/// Advertised in the status line while an approval is pending.
+const QUICK_DENY_KEY: KeyCode = KeyCode::Char('d');
 
 /// A modal prompting the user to approve or deny the pending request.
 pub(crate) struct UserApprovalWidget {
     approval_request: ApprovalRequest,
     app_event_tx: AppEventSender,
     confirmation_prompt: Paragraph<'static>,
-    select_options: &'static Vec<SelectOption>,
+    select_options: Vec<SelectOption>,
 
     /// Currently selected index in *select* mode.
     selected_option: usize,
 
+    /// Whether the widget shows the second confirmation for a patch that
+    /// deletes many files.
+    confirming_deletions: bool,
+
     /// Set to `true` once a decision has been sent – the parent view can then
     /// remove this widget from its queue.
     done: bool,
@@ -169,7 +240,7 @@ impl UserApprovalWidget {
 
                 if let Some(root) = grant_root {
                     contents.push(Line::from(format!(
-                        "This will grant write access to {} for the remainder of this session.",
+                        "Choose Always to allow writes under {} for the remainder of this session.",
                         root.display()
                     )));
                     contents.push(Line::from(""));
@@ -181,13 +252,18 @@ impl UserApprovalWidget {
 
         Self {
             select_options: match &approval_request {
-                ApprovalRequest::Exec { .. } => &COMMAND_SELECT_OPTIONS,
-                ApprovalRequest::ApplyPatch { .. } => &PATCH_SELECT_OPTIONS,
+                ApprovalRequest::Exec { .. } => COMMAND_SELECT_OPTIONS.clone(),
+                ApprovalRequest::ApplyPatch {
+                    grant_root,
+                    grant_paths,
+                    ..
+                } => patch_select_options(grant_root.is_some(), !grant_paths.is_empty()),
             },
             approval_request,
             app_event_tx,
             confirmation_prompt,
             selected_option: 0,
+            confirming_deletions: false,
             done: false,
         }
     }
@@ -235,11 +311,16 @@ impl UserApprovalWidget {
             }
             KeyCode::Enter => {
                 let opt = &self.select_options[self.selected_option];
-                self.send_decision(opt.decision);
+                self.choose(opt.decision);
             }
             KeyCode::Esc => {
                 self.send_decision(ReviewDecision::Abort);
             }
+            // Quick deny: reject the request but let the agent carry on,
+            // unlike "No, provide feedback" which interrupts the task.
+            other if Self::normalize_keycode(other) == QUICK_DENY_KEY => {
+                self.send_decision(ReviewDecision::Denied);
+            }
             other => {
                 let normalized = Self::normalize_keycode(other);
                 if let Some(opt) = self
@@ -247,12 +328,34 @@ impl UserApprovalWidget {
                     .iter()
                     .find(|opt| Self::normalize_keycode(opt.key) == normalized)
                 {
-                    self.send_decision(opt.decision);
+                    self.choose(opt.decision);
                 }
             }
         }
     }
 
+    /// Send `decision`, unless it approves a patch with many deletions that
+    /// has not been confirmed yet: then switch to the deletion confirmation.
+    fn choose(&mut self, decision: ReviewDecision) {
+        let approves = matches!(
+            decision,
+            ReviewDecision::Approved
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedPathsForSession
+        );
+        match &self.approval_request {
+            ApprovalRequest::ApplyPatch { deleted_files, .. }
+                if approves && !deleted_files.is_empty() && !self.confirming_deletions =>
+            {
+                self.select_options = delete_confirmation_options(decision, deleted_files.len());
+                self.confirmation_prompt = delete_confirmation_prompt(deleted_files);
+                self.selected_option = 0;
+                self.confirming_deletions = true;
+            }
+            _ => self.send_decision(decision),
+        }
+    }
+
     fn send_decision(&mut self, decision: ReviewDecision) {
         self.send_decision_with_feedback(decision, String::new())
     }
@@ -267,7 +370,7 @@ impl UserApprovalWidget {
 
                 // Result line based on decision.
                 match decision {
-                    ReviewDecision::Approved => {
+                    ReviewDecision::Approved | ReviewDecision::ApprovedPathsForSession => {
                         lines.extend(to_command_display(
                             vec![
                                 "✔ ".fg(Color::Green),
@@ -388,8 +491,11 @@ impl WidgetRef for &UserApprovalWidget {
         ])
         .areas(response_chunk.inner(Margin::new(1, 0)));
         let title = match &self.approval_request {
-            ApprovalRequest::Exec { .. } => "Allow command?",
-            ApprovalRequest::ApplyPatch { .. } => "Apply changes?",
+            ApprovalRequest::Exec { .. } => "Allow command?".to_string(),
+            ApprovalRequest::ApplyPatch { deleted_files, .. } if self.confirming_deletions => {
+                format!("Delete {} files?", deleted_files.len())
+            }
+            ApprovalRequest::ApplyPatch { .. } => "Apply changes?".to_string(),
         };
         Line::from(title).render(title_area, buf);
 
@@ -454,6 +560,31 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn quick_deny_key_denies_without_abort() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let req = ApprovalRequest::Exec {
+            id: "3".to_string(),
+            command: vec!["echo".to_string()],
+            reason: None,
+        };
+        let mut widget = UserApprovalWidget::new(req, tx);
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+        assert!(widget.is_complete());
+        let mut events: Vec<AppEvent> = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            events.push(ev);
+        }
+        assert!(events.iter().any(|e| matches!(
+            e,
+            AppEvent::CodexOp(Op::ExecApproval {
+                decision: ReviewDecision::Denied,
+                ..
+            })
+        )));
+    }
+
     #[test]
     fn uppercase_shortcut_is_accepted() {
         let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();